@@ -227,6 +227,29 @@ impl JsonSchemaBuilder {
                     obj.insert("maxItems".to_owned(), JsonValue::Number(d.into()));
                 }
             }
+            schema::BasicValueType::SparseVector => {
+                let obj = schema.ensure_object();
+                obj.insert("type".to_owned(), JsonValue::String("object".to_owned()));
+                obj.insert(
+                    "properties".to_owned(),
+                    serde_json::json!({
+                        "indices": {"type": "array", "items": {"type": "integer"}},
+                        "values": {"type": "array", "items": {"type": "number"}},
+                    }),
+                );
+                obj.insert(
+                    "required".to_owned(),
+                    serde_json::json!(["indices", "values"]),
+                );
+                if self.options.supports_additional_properties {
+                    obj.insert("additionalProperties".to_owned(), JsonValue::Bool(false));
+                }
+                self.add_description(
+                    &mut schema,
+                    "A sparse vector, with strictly increasing indices and their corresponding values.",
+                    field_path,
+                );
+            }
             schema::BasicValueType::Union(s) => {
                 let one_of: Vec<JsonValue> = s
                     .types
@@ -775,6 +798,44 @@ mod tests {
         .assert_eq(&serde_json::to_string_pretty(&json_schema).unwrap());
     }
 
+    #[test]
+    fn test_basic_types_sparse_vector() {
+        let value_type = EnrichedValueType {
+            typ: ValueType::Basic(BasicValueType::SparseVector),
+            nullable: false,
+            attrs: Arc::new(BTreeMap::new()),
+        };
+        let options = create_test_options();
+        let result = build_json_schema(value_type, options).unwrap();
+        let json_schema = schema_to_json(&result.schema);
+
+        expect![[r#"
+            {
+              "additionalProperties": false,
+              "description": "A sparse vector, with strictly increasing indices and their corresponding values.",
+              "properties": {
+                "indices": {
+                  "items": {
+                    "type": "integer"
+                  },
+                  "type": "array"
+                },
+                "values": {
+                  "items": {
+                    "type": "number"
+                  },
+                  "type": "array"
+                }
+              },
+              "required": [
+                "indices",
+                "values"
+              ],
+              "type": "object"
+            }"#]]
+        .assert_eq(&serde_json::to_string_pretty(&json_schema).unwrap());
+    }
+
     #[test]
     fn test_basic_types_union() {
         let value_type = EnrichedValueType {
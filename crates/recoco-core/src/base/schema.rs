@@ -74,6 +74,9 @@ pub enum BasicValueType {
     /// A vector of values (usually numbers, for embeddings).
     Vector(VectorTypeSchema),
 
+    /// A sparse vector of 32-bit floats, as pairs of non-negative indices and values.
+    SparseVector,
+
     /// A union
     Union(UnionTypeSchema),
 }
@@ -102,6 +105,7 @@ impl std::fmt::Display for BasicValueType {
                 }
                 write!(f, "]")
             }
+            BasicValueType::SparseVector => write!(f, "SparseVector"),
             BasicValueType::Union(s) => {
                 write!(f, "Union[")?;
                 for (i, typ) in s.types.iter().enumerate() {
@@ -98,6 +98,53 @@ impl<'de> Deserialize<'de> for RangeValue {
     }
 }
 
+/// A sparse vector, with indices in strictly increasing order.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct SparseVectorValue {
+    pub indices: Vec<u32>,
+    pub values: Vec<f32>,
+}
+
+impl SparseVectorValue {
+    /// Builds a sparse vector from (index, value) pairs in any order. Values of duplicated indices are summed.
+    pub fn from_pairs(pairs: impl IntoIterator<Item = (u32, f32)>) -> Self {
+        let mut pairs: Vec<_> = pairs.into_iter().collect();
+        pairs.sort_by_key(|(index, _)| *index);
+        let mut result = Self::default();
+        for (index, value) in pairs {
+            if result.indices.last() == Some(&index) {
+                *result.values.last_mut().unwrap() += value;
+            } else {
+                result.indices.push(index);
+                result.values.push(value);
+            }
+        }
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.indices.len() != self.values.len() {
+            client_bail!(
+                "sparse vector has {} indices but {} values",
+                self.indices.len(),
+                self.values.len()
+            );
+        }
+        if !self.indices.windows(2).all(|w| w[0] < w[1]) {
+            client_bail!("sparse vector indices must be strictly increasing");
+        }
+        Ok(())
+    }
+}
+
 /// Value of key.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
 pub enum KeyPart {
@@ -524,6 +571,7 @@ pub enum BasicValue {
     TimeDelta(chrono::Duration),
     Json(Arc<serde_json::Value>),
     Vector(Arc<[BasicValue]>),
+    SparseVector(Arc<SparseVectorValue>),
     UnionVariant {
         tag_id: usize,
         value: Box<BasicValue>,
@@ -614,6 +662,12 @@ impl From<chrono::DateTime<chrono::FixedOffset>> for BasicValue {
     }
 }
 
+impl From<SparseVectorValue> for BasicValue {
+    fn from(value: SparseVectorValue) -> Self {
+        BasicValue::SparseVector(Arc::new(value))
+    }
+}
+
 impl From<chrono::Duration> for BasicValue {
     fn from(value: chrono::Duration) -> Self {
         BasicValue::TimeDelta(value)
@@ -652,6 +706,7 @@ impl BasicValue {
             | BasicValue::TimeDelta(_)
             | BasicValue::Json(_)
             | BasicValue::Vector(_)
+            | BasicValue::SparseVector(_)
             | BasicValue::UnionVariant { .. } => api_bail!("invalid key value type"),
         };
         Ok(result)
@@ -674,6 +729,7 @@ impl BasicValue {
             | BasicValue::TimeDelta(_)
             | BasicValue::Json(_)
             | BasicValue::Vector(_)
+            | BasicValue::SparseVector(_)
             | BasicValue::UnionVariant { .. } => api_bail!("invalid key value type"),
         };
         Ok(result)
@@ -696,6 +752,7 @@ impl BasicValue {
             BasicValue::TimeDelta(_) => "timedelta",
             BasicValue::Json(_) => "json",
             BasicValue::Vector(_) => "vector",
+            BasicValue::SparseVector(_) => "sparse_vector",
             BasicValue::UnionVariant { .. } => "union",
         }
     }
@@ -734,6 +791,11 @@ impl BasicValue {
                     .sum::<usize>()
                     + v.len() * std::mem::size_of::<BasicValue>()
             }
+            BasicValue::SparseVector(v) => {
+                std::mem::size_of::<SparseVectorValue>()
+                    + v.indices.len() * std::mem::size_of::<u32>()
+                    + v.values.len() * std::mem::size_of::<f32>()
+            }
             BasicValue::UnionVariant { value, .. } => {
                 value.estimated_detached_byte_size() + std::mem::size_of::<BasicValue>()
             }
@@ -1151,6 +1213,7 @@ impl serde::Serialize for BasicValue {
             BasicValue::TimeDelta(v) => serializer.serialize_str(&v.to_string()),
             BasicValue::Json(v) => v.serialize(serializer),
             BasicValue::Vector(v) => v.serialize(serializer),
+            BasicValue::SparseVector(v) => v.serialize(serializer),
             BasicValue::UnionVariant { tag_id, value } => {
                 let mut s = serializer.serialize_tuple(2)?;
                 s.serialize_element(tag_id)?;
@@ -1222,6 +1285,11 @@ impl BasicValue {
                     .collect::<Result<Vec<_>>>()?;
                 BasicValue::Vector(Arc::from(vec))
             }
+            (v, BasicValueType::SparseVector) => {
+                let sparse_vector: SparseVectorValue = utils::deser::from_json_value(v)?;
+                sparse_vector.validate()?;
+                BasicValue::SparseVector(Arc::new(sparse_vector))
+            }
             (v, BasicValueType::Union(typ)) => {
                 let arr = match v {
                     serde_json::Value::Array(arr) => arr,
@@ -1553,6 +1621,29 @@ mod tests {
         assert_eq!(range.extract_str(utf8_text), "héllo wörld");
     }
 
+    #[test]
+    fn test_sparse_vector_value() {
+        let v = SparseVectorValue::from_pairs([(7, 0.5), (2, 1.0), (7, 0.25)]);
+        assert_eq!(v.indices, vec![2, 7]);
+        assert_eq!(v.values, vec![1.0, 0.75]);
+        v.validate().unwrap();
+
+        let json = serde_json::to_value(BasicValue::from(v.clone())).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"indices": [2, 7], "values": [1.0, 0.75]})
+        );
+        assert_eq!(
+            BasicValue::from_json(json, &BasicValueType::SparseVector).unwrap(),
+            BasicValue::SparseVector(Arc::new(v))
+        );
+
+        let unordered = serde_json::json!({"indices": [7, 2], "values": [1.0, 0.75]});
+        assert!(BasicValue::from_json(unordered, &BasicValueType::SparseVector).is_err());
+        let mismatched = serde_json::json!({"indices": [2], "values": [1.0, 0.75]});
+        assert!(BasicValue::from_json(mismatched, &BasicValueType::SparseVector).is_err());
+    }
+
     #[test]
     fn test_estimated_byte_size_null() {
         let value = Value::<ScopeValue>::Null;
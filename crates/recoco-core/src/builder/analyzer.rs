@@ -1302,9 +1302,28 @@ pub fn build_flow_instance_context(flow_inst_name: &str) -> Arc<FlowInstanceCont
     Arc::new(FlowInstanceContext {
         flow_instance_name: flow_inst_name.to_string(),
         auth_registry: get_auth_registry().clone(),
+        function_state_store: Default::default(),
     })
 }
 
+/// Whether any function in the reactive ops (including nested scopes) persists its own state.
+fn functions_use_state_store(reactive_ops: &[NamedSpec<ReactiveOpSpec>]) -> Result<bool> {
+    for reactive_op in reactive_ops.iter() {
+        let uses_state_store = match &reactive_op.spec {
+            ReactiveOpSpec::Transform(op) => get_function_factory(&op.op.kind)?
+                .uses_state_store(&serde_json::Value::Object(op.op.spec.clone())),
+            ReactiveOpSpec::ForEach(foreach_op) => {
+                functions_use_state_store(&foreach_op.op_scope.ops)?
+            }
+            ReactiveOpSpec::Collect(_) => false,
+        };
+        if uses_state_store {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 fn build_flow_schema(root_op_scope: &OpScope) -> Result<FlowSchema> {
     let schema = (&root_op_scope.data.lock().unwrap().data).try_into()?;
     let root_op_scope_schema = root_op_scope.states.lock().unwrap().build_op_scope_schema();
@@ -1334,7 +1353,20 @@ pub async fn analyze_flow(
         FieldDefFingerprint::default(),
     );
     let mut import_ops_futs = Vec::with_capacity(flow_inst.import_ops.len());
+    let mut uses_source_state = false;
+    let mut source_resources = HashMap::new();
     for import_op in flow_inst.import_ops.iter() {
+        let source_factory = get_source_factory(&import_op.spec.source.kind)?;
+        let source_spec = serde_json::Value::Object(import_op.spec.source.spec.clone());
+        uses_source_state |= source_factory.uses_state_store(&source_spec);
+        source_resources.insert(
+            import_op.name.clone(),
+            source_factory.setup_resources(
+                &import_op.name,
+                &source_spec,
+                &analyzer_ctx.flow_ctx,
+            )?,
+        );
         import_ops_futs.push(
             analyzer_ctx
                 .analyze_import_op(&root_op_scope, import_op.clone())
@@ -1342,6 +1374,7 @@ pub async fn analyze_flow(
                 .with_context(|| format!("Preparing for import op: {}", import_op.name))?,
         );
     }
+    uses_source_state |= functions_use_state_store(&flow_inst.reactive_ops)?;
     let op_scope_fut = analyzer_ctx
         .analyze_op_scope(&root_op_scope, &flow_inst.reactive_ops)
         .await?;
@@ -1407,6 +1440,8 @@ pub async fn analyze_flow(
             .map(|(idx, v)| v.ok_or_else(|| internal_error!("target op `{}` not found", idx)))
             .collect::<Result<Vec<_>>>()?,
         declarations: declarations_analyzed_ss,
+        uses_source_state,
+        source_resources,
     };
 
     let legacy_fingerprint_v1 = Fingerprinter::default()
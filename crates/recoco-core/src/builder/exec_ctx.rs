@@ -50,6 +50,10 @@ pub struct AnalyzedTargetSetupState {
 pub struct AnalyzedSetupState {
     pub targets: Vec<AnalyzedTargetSetupState>,
     pub declarations: Vec<AnalyzedTargetSetupState>,
+    /// Whether any source persists its own state, which needs a source state table.
+    pub uses_source_state: bool,
+    /// Resources created by each source outside the flow's own tables, by source name.
+    pub source_resources: HashMap<String, Vec<serde_json::Value>>,
 }
 
 #[cfg(feature = "persistence")]
@@ -57,6 +61,7 @@ fn build_import_op_exec_ctx(
     import_op: &spec::NamedSpec<spec::ImportOpSpec>,
     import_op_output_type: &schema::EnrichedValueType,
     existing_source_states: Option<&Vec<&setup::SourceSetupState>>,
+    resources: Vec<serde_json::Value>,
     metadata: &mut setup::FlowSetupMetadata,
 ) -> Result<ImportOpExecutionContext> {
     let keys_schema_no_attrs = import_op_output_type
@@ -129,6 +134,7 @@ fn build_import_op_exec_ctx(
             }),
             keys_schema: Some(keys_schema_no_attrs),
             source_kind: import_op.spec.source.kind.clone(),
+            resources,
         },
     );
     Ok(ImportOpExecutionContext { source_id })
@@ -286,6 +292,11 @@ pub fn build_flow_setup_execution_context(
             })
             .unwrap_or_else(setup::flow_features::default_features),
     };
+    if analyzed_ss.uses_source_state {
+        metadata
+            .features
+            .insert(setup::flow_features::SOURCE_STATE_TABLE.to_string());
+    }
     let mut target_states = IndexMap::new();
 
     let import_op_exec_ctx = flow_inst
@@ -301,6 +312,11 @@ pub fn build_flow_setup_execution_context(
                 import_op,
                 output_type,
                 source_states_by_name.get(&import_op.name.as_str()),
+                analyzed_ss
+                    .source_resources
+                    .get(&import_op.name)
+                    .cloned()
+                    .unwrap_or_default(),
                 &mut metadata,
             )
         })
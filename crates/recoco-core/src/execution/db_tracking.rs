@@ -451,7 +451,27 @@ pub async fn read_source_state(
     Ok(state)
 }
 
-#[allow(dead_code)]
+pub async fn list_source_states(
+    source_id: i32,
+    db_setup: &TrackingTableSetupState,
+    db_executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Vec<(serde_json::Value, serde_json::Value)>> {
+    let Some(raw_table_name) = db_setup.source_state_table_name.as_ref() else {
+        client_bail!("Source state table not enabled for this flow");
+    };
+
+    let qualified_table_name = qualify_table_name_with_schema(raw_table_name);
+    let query_str = format!(
+        "SELECT key, value FROM {} WHERE source_id = $1",
+        qualified_table_name
+    );
+    let states = sqlx::query_as(&query_str)
+        .bind(source_id)
+        .fetch_all(db_executor)
+        .await?;
+    Ok(states)
+}
+
 pub async fn upsert_source_state(
     source_id: i32,
     source_key_json: &serde_json::Value,
@@ -477,3 +497,26 @@ pub async fn upsert_source_state(
         .await?;
     Ok(())
 }
+
+pub async fn delete_source_state(
+    source_id: i32,
+    source_key_json: &serde_json::Value,
+    db_setup: &TrackingTableSetupState,
+    db_executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<()> {
+    let Some(raw_table_name) = db_setup.source_state_table_name.as_ref() else {
+        client_bail!("Source state table not enabled for this flow");
+    };
+
+    let qualified_table_name = qualify_table_name_with_schema(raw_table_name);
+    let query_str = format!(
+        "DELETE FROM {} WHERE source_id = $1 AND key = $2",
+        qualified_table_name
+    );
+    sqlx::query(&query_str)
+        .bind(source_id)
+        .bind(source_key_json)
+        .execute(db_executor)
+        .await?;
+    Ok(())
+}
@@ -53,7 +53,7 @@ pub fn default_source_state_table_name(flow_name: &str) -> String {
 
 pub const CURRENT_TRACKING_TABLE_VERSION: i32 = 1;
 
-async fn ensure_internal_schema_exists(pool: &PgPool) -> Result<()> {
+pub(crate) async fn ensure_internal_schema_exists(pool: &PgPool) -> Result<()> {
     if let Some(schema) = get_internal_db_schema() {
         let query = format!(
             "CREATE SCHEMA IF NOT EXISTS {}",
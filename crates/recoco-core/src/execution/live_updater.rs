@@ -155,7 +155,39 @@ impl SourceUpdateTask {
         let task = &self;
 
         // Deal with change streams.
-        if let Some(change_stream) = import_op.executor.change_stream().await? {
+        let change_stream = match source_indexing_context.state_store() {
+            Some(state_store) => {
+                import_op
+                    .executor
+                    .change_stream_with_state(state_store)
+                    .await?
+            }
+            None => import_op.executor.change_stream().await?.map(|stream| {
+                interface::SourceChangeStream {
+                    stream,
+                    catches_up: false,
+                    initial_update_ack_fn: None,
+                }
+            }),
+        };
+        // The change stream itself catches up with changes since the last run, so the initial
+        // full pass isn't needed as long as everything was processed with the current logic.
+        let skip_initial_update = change_stream
+            .as_ref()
+            .is_some_and(|change_stream| change_stream.catches_up)
+            && matches!(
+                initial_update_options.mode,
+                super::source_indexer::UpdateMode::Normal
+            )
+            && source_indexing_context.is_up_to_date();
+        let mut initial_update_ack_fn = None;
+        if let Some(interface::SourceChangeStream {
+            stream: change_stream,
+            initial_update_ack_fn: ack_fn,
+            ..
+        }) = change_stream
+        {
+            initial_update_ack_fn = ack_fn;
             let stats = Arc::new(stats::UpdateStats::default());
             let stats_to_report = stats.clone();
 
@@ -266,16 +298,25 @@ impl SourceUpdateTask {
             async move {
                 let refresh_interval = import_op.refresh_options.refresh_interval;
 
-                task.update_one_pass_with_error_logging(
-                    source_indexing_context,
-                    if refresh_interval.is_some() {
-                        "initial interval update"
-                    } else {
-                        "batch update"
-                    },
-                    initial_update_options,
-                )
-                .await;
+                if !skip_initial_update {
+                    let succeeded = task
+                        .update_one_pass_with_error_logging(
+                            source_indexing_context,
+                            if refresh_interval.is_some() {
+                                "initial interval update"
+                            } else {
+                                "batch update"
+                            },
+                            initial_update_options,
+                        )
+                        .await;
+                    if succeeded
+                        && let Some(ack_fn) = initial_update_ack_fn
+                        && let Err(err) = ack_fn().await
+                    {
+                        error!("{:?}", err);
+                    }
+                }
 
                 let Some(refresh_interval) = refresh_interval else {
                     return Ok(());
@@ -443,19 +484,21 @@ impl SourceUpdateTask {
         Ok(())
     }
 
+    /// Returns whether the update succeeded.
     async fn update_one_pass_with_error_logging(
         &self,
         source_indexing_context: &Arc<SourceIndexingContext>,
         update_title: &str,
         update_options: super::source_indexer::UpdateOptions,
-    ) {
+    ) -> bool {
         let result = self
             .update_one_pass(source_indexing_context, update_title, update_options)
             .await;
 
-        if let Err(err) = result {
+        if let Err(err) = &result {
             error!("{:?}", err);
         }
+        result.is_ok()
     }
 
     fn import_op(&self) -> &plan::AnalyzedImportOp {
@@ -86,6 +86,8 @@ pub struct SourceIndexingContext {
     state: Mutex<SourceIndexingState>,
     setup_execution_ctx: Arc<exec_ctx::FlowSetupExecutionContext>,
     needs_to_track_rows_to_retry: bool,
+    // Whether any row had its processing started but not finished when loaded.
+    has_unfinished_rows: bool,
     #[cfg(any(
         feature = "function-embed",
         feature = "source-azure",
@@ -100,6 +102,66 @@ pub struct SourceIndexingContext {
 
 pub const NO_ACK: Option<fn() -> Ready<Result<()>>> = None;
 
+/// Source id under which functions of a flow keep their states in the source state table.
+/// Source ids of import ops start from 1, so it never collides with them.
+const FUNCTION_STATE_SOURCE_ID: i32 = 0;
+
+/// Store for the states of the flow's functions. `None` if the flow has no source state table.
+pub fn function_state_store(
+    setup_execution_ctx: Arc<exec_ctx::FlowSetupExecutionContext>,
+    pool: PgPool,
+) -> Option<Arc<dyn interface::SourceStateStore>> {
+    setup_execution_ctx
+        .setup_state
+        .tracking_table
+        .source_state_table_name
+        .as_ref()?;
+    Some(Arc::new(DbSourceStateStore {
+        source_id: FUNCTION_STATE_SOURCE_ID,
+        setup_execution_ctx,
+        pool,
+    }))
+}
+
+struct DbSourceStateStore {
+    source_id: i32,
+    setup_execution_ctx: Arc<exec_ctx::FlowSetupExecutionContext>,
+    pool: PgPool,
+}
+
+#[async_trait]
+impl interface::SourceStateStore for DbSourceStateStore {
+    async fn list(&self) -> Result<Vec<(serde_json::Value, serde_json::Value)>> {
+        db_tracking::list_source_states(
+            self.source_id,
+            &self.setup_execution_ctx.setup_state.tracking_table,
+            &self.pool,
+        )
+        .await
+    }
+
+    async fn upsert(&self, key: &serde_json::Value, state: serde_json::Value) -> Result<()> {
+        db_tracking::upsert_source_state(
+            self.source_id,
+            key,
+            state,
+            &self.setup_execution_ctx.setup_state.tracking_table,
+            &self.pool,
+        )
+        .await
+    }
+
+    async fn delete(&self, key: &serde_json::Value) -> Result<()> {
+        db_tracking::delete_source_state(
+            self.source_id,
+            key,
+            &self.setup_execution_ctx.setup_state.tracking_table,
+            &self.pool,
+        )
+        .await
+    }
+}
+
 struct LocalSourceRowStateOperator<'a> {
     key: &'a value::KeyValue,
     indexing_state: &'a Mutex<SourceIndexingState>,
@@ -305,6 +367,7 @@ impl SourceIndexingContext {
         let mut list_state = db_tracking::ListTrackedSourceKeyMetadataState::new();
         let mut rows = HashMap::new();
         let mut rows_to_retry: Option<HashSet<value::KeyValue>> = None;
+        let mut has_unfinished_rows = false;
         let scan_generation = 0;
         let source_logic_fp = SourceLogicFingerprint::new(
             &plan,
@@ -324,10 +387,11 @@ impl SourceIndexingContext {
                     key_metadata.source_key,
                     &import_op.primary_key_schema,
                 )?;
-                if let Some(rows_to_retry) = &mut rows_to_retry
-                    && key_metadata.max_process_ordinal > key_metadata.process_ordinal
-                {
-                    rows_to_retry.insert(source_pk.clone());
+                if key_metadata.max_process_ordinal > key_metadata.process_ordinal {
+                    has_unfinished_rows = true;
+                    if let Some(rows_to_retry) = &mut rows_to_retry {
+                        rows_to_retry.insert(source_pk.clone());
+                    }
                 }
                 rows.insert(
                     source_pk,
@@ -351,6 +415,7 @@ impl SourceIndexingContext {
             flow,
             source_idx,
             needs_to_track_rows_to_retry: rows_to_retry.is_some(),
+            has_unfinished_rows,
             state: Mutex::new(SourceIndexingState {
                 rows,
                 scan_generation,
@@ -585,6 +650,35 @@ impl SourceIndexingContext {
         }
     }
 
+    /// Store for the source's own state. `None` if the flow has no source state table.
+    pub fn state_store(&self) -> Option<Arc<dyn interface::SourceStateStore>> {
+        self.setup_execution_ctx
+            .setup_state
+            .tracking_table
+            .source_state_table_name
+            .as_ref()?;
+        Some(Arc::new(DbSourceStateStore {
+            source_id: self.setup_execution_ctx.import_ops[self.source_idx].source_id,
+            setup_execution_ctx: self.setup_execution_ctx.clone(),
+            pool: self.pool.clone(),
+        }))
+    }
+
+    /// Whether all known rows were processed with the current logic, with no failure to retry.
+    /// If so, only changes of the source need to be processed.
+    pub fn is_up_to_date(&self) -> bool {
+        if self.has_unfinished_rows {
+            return false;
+        }
+        let state = self.state.lock().unwrap();
+        state.rows.values().all(|row| {
+            row.version_state.source_version.kind == row_indexer::SourceVersionKind::CurrentLogic
+        }) && state
+            .rows_to_retry
+            .as_ref()
+            .is_none_or(|rows_to_retry| rows_to_retry.is_empty())
+    }
+
     #[instrument(name = "source_indexing.update", skip_all, fields(flow_name = %self.flow.flow_instance.name, source_idx = %self.source_idx))]
     pub async fn update(
         self: &Arc<Self>,
@@ -17,7 +17,7 @@ use crate::prelude::*;
 
 use crate::builder::AnalyzedFlow;
 #[cfg(feature = "persistence")]
-use crate::execution::source_indexer::SourceIndexingContext;
+use crate::execution::source_indexer::{self, SourceIndexingContext};
 #[cfg(feature = "persistence")]
 use crate::service::query_handler::{QueryHandler, QueryHandlerSpec};
 use crate::settings;
@@ -56,6 +56,19 @@ async fn build_setup_context(
         existing_flow_ss,
     )?);
 
+    let function_state_store = get_lib_context()
+        .await?
+        .require_builtin_db_pool()
+        .ok()
+        .and_then(|pool| {
+            source_indexer::function_state_store(setup_execution_context.clone(), pool.clone())
+        });
+    *analyzed_flow
+        .flow_instance_ctx
+        .function_state_store
+        .write()
+        .unwrap() = function_state_store;
+
     let setup_change = setup::diff_flow_setup_states(
         Some(&setup_execution_context.setup_state),
         existing_flow_ss,
@@ -193,6 +206,31 @@ impl FlowContext {
     pub fn get_execution_ctx_for_setup(&self) -> &tokio::sync::RwLock<FlowExecutionContext> {
         &self.execution_ctx
     }
+
+    /// Register a query handler, served under `/flows/{flow}/queryHandlers/{name}`.
+    #[cfg(feature = "persistence")]
+    pub fn add_query_handler(
+        &self,
+        name: String,
+        spec: QueryHandlerSpec,
+        handler: Arc<dyn QueryHandler>,
+    ) -> Result<()> {
+        let mut query_handlers = self.query_handlers.write().unwrap();
+        if query_handlers.contains_key(&name) {
+            client_bail!(
+                "Query handler `{name}` already exists for flow `{}`",
+                self.flow_name()
+            );
+        }
+        query_handlers.insert(
+            name,
+            QueryHandlerContext {
+                info: Arc::new(spec),
+                handler,
+            },
+        );
+        Ok(())
+    }
 }
 
 static TOKIO_RUNTIME: LazyLock<Runtime> = LazyLock::new(|| Runtime::new().unwrap());
@@ -14,8 +14,8 @@ use crate::prelude::*;
 use base64::prelude::*;
 
 use crate::llm::{
-    GeneratedOutput, LlmGenerateRequest, LlmGenerateResponse, LlmGenerationClient, OutputFormat,
-    ToJsonSchemaOptions, detect_image_mime_type,
+    GeneratedOutput, LlmAttachmentKind, LlmGenerateRequest, LlmGenerateResponse,
+    LlmGenerationClient, OutputFormat, ToJsonSchemaOptions,
 };
 use urlencoding::encode;
 
@@ -44,60 +44,144 @@ impl Client {
     }
 }
 
-#[async_trait]
-impl LlmGenerationClient for Client {
-    async fn generate<'req>(
-        &self,
-        request: LlmGenerateRequest<'req>,
-    ) -> Result<LlmGenerateResponse> {
-        let mut user_content_parts: Vec<serde_json::Value> = Vec::new();
+/// Builds the Messages API payload for a generation request.
+///
+/// Shared by the synchronous client and the batch API client.
+pub(super) fn build_message_payload(request: &LlmGenerateRequest<'_>) -> Result<serde_json::Value> {
+    let mut user_content_parts: Vec<serde_json::Value> = Vec::new();
 
-        // Add image part if present
-        if let Some(image_bytes) = &request.image {
-            let base64_image = BASE64_STANDARD.encode(image_bytes.as_ref());
-            let mime_type = detect_image_mime_type(image_bytes.as_ref())?;
-            user_content_parts.push(serde_json::json!({
+    // Add attachment parts before the prompt, as recommended by Anthropic
+    for attachment in request.attachments.iter() {
+        let mut part = match attachment.kind {
+            LlmAttachmentKind::Image => serde_json::json!({
                 "type": "image",
                 "source": {
                     "type": "base64",
-                    "media_type": mime_type,
-                    "data": base64_image,
+                    "media_type": attachment.mime_type,
+                    "data": BASE64_STANDARD.encode(attachment.data.as_ref()),
                 }
-            }));
+            }),
+            LlmAttachmentKind::Pdf => serde_json::json!({
+                "type": "document",
+                "source": {
+                    "type": "base64",
+                    "media_type": "application/pdf",
+                    "data": BASE64_STANDARD.encode(attachment.data.as_ref()),
+                }
+            }),
+            LlmAttachmentKind::Text => serde_json::json!({
+                "type": "document",
+                "source": {
+                    "type": "text",
+                    "media_type": "text/plain",
+                    "data": attachment.as_text()?,
+                }
+            }),
+        };
+        if let (Some(name), LlmAttachmentKind::Pdf | LlmAttachmentKind::Text) =
+            (&attachment.name, attachment.kind)
+        {
+            part["title"] = serde_json::json!(name);
         }
+        user_content_parts.push(part);
+    }
 
-        // Add text part
-        user_content_parts.push(serde_json::json!({
-            "type": "text",
-            "text": request.user_prompt
-        }));
-
-        let messages = vec![serde_json::json!({
-            "role": "user",
-            "content": user_content_parts
-        })];
-
-        let mut payload = serde_json::json!({
-            "model": request.model,
-            "messages": messages,
-            "max_tokens": 4096
-        });
-
-        // Add system prompt as top-level field if present (required)
-        if let Some(system) = request.system_prompt {
-            payload["system"] = serde_json::json!(system);
-        }
+    // Add text part
+    user_content_parts.push(serde_json::json!({
+        "type": "text",
+        "text": request.user_prompt
+    }));
+
+    let messages = vec![serde_json::json!({
+        "role": "user",
+        "content": user_content_parts
+    })];
+
+    let mut payload = serde_json::json!({
+        "model": request.model,
+        "messages": messages,
+        "max_tokens": 4096
+    });
+
+    // Add system prompt as top-level field if present (required)
+    if let Some(system) = &request.system_prompt {
+        payload["system"] = serde_json::json!(system);
+    }
 
-        // Extract schema from output_format, error if not JsonSchema
-        let schema = match request.output_format.as_ref() {
-            Some(OutputFormat::JsonSchema { schema, .. }) => schema,
-            _ => api_bail!("Anthropic client expects OutputFormat::JsonSchema for all requests"),
-        };
+    // Extract schema from output_format, error if not JsonSchema
+    let schema = match request.output_format.as_ref() {
+        Some(OutputFormat::JsonSchema { schema, .. }) => schema,
+        _ => api_bail!("Anthropic client expects OutputFormat::JsonSchema for all requests"),
+    };
+
+    let schema_json = serde_json::to_value(schema)?;
+    payload["tools"] = serde_json::json!([
+        { "type": "custom", "name": "report_result", "input_schema": schema_json }
+    ]);
+    Ok(payload)
+}
+
+/// Extracts the structured output from a Messages API response.
+///
+/// Shared by the synchronous client and the batch API client.
+pub(super) fn parse_message_response(mut resp_json: serde_json::Value) -> Result<GeneratedOutput> {
+    let resp_content = &resp_json["content"];
+    let tool_name = "report_result";
+    let mut extracted_json: Option<serde_json::Value> = None;
+    if let Some(array) = resp_content.as_array() {
+        for item in array {
+            if item.get("type") == Some(&serde_json::Value::String("tool_use".to_string()))
+                && item.get("name") == Some(&serde_json::Value::String(tool_name.to_string()))
+            {
+                if let Some(input) = item.get("input") {
+                    extracted_json = Some(input.clone());
+                    break;
+                }
+            }
+        }
+    }
+    let json_value = if let Some(json) = extracted_json {
+        json
+    } else {
+        // Fallback: try text if no tool output found
+        match &mut resp_json["content"][0]["text"] {
+            serde_json::Value::String(s) => {
+                // Try strict JSON parsing first
+                match utils::deser::from_json_str::<serde_json::Value>(s) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        // Try permissive json5 parsing as fallback
+                        match json5::from_str::<serde_json::Value>(s) {
+                            Ok(value) => {
+                                println!("[Anthropic] Used permissive JSON5 parser for output");
+                                value
+                            }
+                            Err(e2) => {
+                                return Err(client_error!(
+                                    "No structured tool output or text found in response, and permissive JSON5 parsing also failed: {e}; {e2}"
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {
+                return Err(client_error!(
+                    "No structured tool output or text found in response"
+                ));
+            }
+        }
+    };
+    Ok(GeneratedOutput::Json(json_value))
+}
 
-        let schema_json = serde_json::to_value(schema)?;
-        payload["tools"] = serde_json::json!([
-            { "type": "custom", "name": "report_result", "input_schema": schema_json }
-        ]);
+#[async_trait]
+impl LlmGenerationClient for Client {
+    async fn generate<'req>(
+        &self,
+        request: LlmGenerateRequest<'req>,
+    ) -> Result<LlmGenerateResponse> {
+        let payload = build_message_payload(&request)?;
 
         let url = "https://api.anthropic.com/v1/messages";
 
@@ -113,7 +197,7 @@ impl LlmGenerationClient for Client {
         .await
         .with_context(|| "Anthropic API error")?;
 
-        let mut resp_json: serde_json::Value = resp
+        let resp_json: serde_json::Value = resp
             .json()
             .await
             .map_err(Error::internal)
@@ -125,56 +209,8 @@ impl LlmGenerationClient for Client {
         // Debug print full response
         // println!("Anthropic API full response: {resp_json:?}");
 
-        let resp_content = &resp_json["content"];
-        let tool_name = "report_result";
-        let mut extracted_json: Option<serde_json::Value> = None;
-        if let Some(array) = resp_content.as_array() {
-            for item in array {
-                if item.get("type") == Some(&serde_json::Value::String("tool_use".to_string()))
-                    && item.get("name") == Some(&serde_json::Value::String(tool_name.to_string()))
-                {
-                    if let Some(input) = item.get("input") {
-                        extracted_json = Some(input.clone());
-                        break;
-                    }
-                }
-            }
-        }
-        let json_value = if let Some(json) = extracted_json {
-            json
-        } else {
-            // Fallback: try text if no tool output found
-            match &mut resp_json["content"][0]["text"] {
-                serde_json::Value::String(s) => {
-                    // Try strict JSON parsing first
-                    match utils::deser::from_json_str::<serde_json::Value>(s) {
-                        Ok(value) => value,
-                        Err(e) => {
-                            // Try permissive json5 parsing as fallback
-                            match json5::from_str::<serde_json::Value>(s) {
-                                Ok(value) => {
-                                    println!("[Anthropic] Used permissive JSON5 parser for output");
-                                    value
-                                }
-                                Err(e2) => {
-                                    return Err(client_error!(
-                                        "No structured tool output or text found in response, and permissive JSON5 parsing also failed: {e}; {e2}"
-                                    ));
-                                }
-                            }
-                        }
-                    }
-                }
-                _ => {
-                    return Err(client_error!(
-                        "No structured tool output or text found in response"
-                    ));
-                }
-            }
-        };
-
         Ok(LlmGenerateResponse {
-            output: GeneratedOutput::Json(json_value),
+            output: parse_message_response(resp_json)?,
         })
     }
 
@@ -0,0 +1,1047 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for running LLM requests through the providers' asynchronous batch APIs.
+//!
+//! Requests are collected into a batch job, submitted, polled until the job ends, and the
+//! results are mapped back by a `custom_id` derived from the request body. Submitted jobs are
+//! recorded in the flow's source state table (see [`FlowInstanceContext::function_state_store`]),
+//! so a restarted process picks up the pending jobs instead of submitting the same requests again,
+//! and the records are dropped together with the flow.
+
+use crate::prelude::*;
+
+use super::{BatchApiSpec, GeneratedOutput, LlmEmbeddingRequest, LlmGenerateRequest};
+use crate::ops::interface::{FlowInstanceContext, InMemoryStateStore, SourceStateStore};
+use base64::prelude::*;
+use std::time::Duration;
+use utils::fingerprint::Fingerprinter;
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+// Providers finish batch jobs within a 24-hour window. Leave headroom for submission and
+// downloading the results.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(26 * 60 * 60);
+const DEFAULT_MAX_BATCH_SIZE: usize = 10_000;
+
+/// The kind of requests carried by a batch job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmBatchEndpoint {
+    Embeddings,
+    Generation,
+}
+
+pub struct LlmBatchRequest {
+    pub custom_id: String,
+    pub body: serde_json::Value,
+}
+
+#[derive(Debug, Clone)]
+pub enum LlmBatchResult {
+    Succeeded(serde_json::Value),
+    Failed(String),
+}
+
+#[derive(Debug)]
+pub enum LlmBatchJobStatus {
+    InProgress,
+    Completed,
+    Failed(String),
+}
+
+#[async_trait]
+pub trait LlmBatchClient: Send + Sync {
+    /// Identifies the provider, address and endpoint. Job records are scoped by it.
+    fn scope(&self) -> &str;
+
+    fn embedding_request_body(
+        &self,
+        _request: &LlmEmbeddingRequest<'_>,
+    ) -> Result<serde_json::Value> {
+        api_bail!(
+            "Embedding is not supported by the batch API of {}",
+            self.scope()
+        )
+    }
+
+    fn parse_embedding_response(&self, _body: serde_json::Value) -> Result<Vec<Vec<f32>>> {
+        api_bail!(
+            "Embedding is not supported by the batch API of {}",
+            self.scope()
+        )
+    }
+
+    fn generation_request_body(
+        &self,
+        _request: &LlmGenerateRequest<'_>,
+    ) -> Result<serde_json::Value> {
+        api_bail!(
+            "Generation is not supported by the batch API of {}",
+            self.scope()
+        )
+    }
+
+    fn parse_generation_response(&self, _body: serde_json::Value) -> Result<GeneratedOutput> {
+        api_bail!(
+            "Generation is not supported by the batch API of {}",
+            self.scope()
+        )
+    }
+
+    /// Submits a new job and returns its ID.
+    async fn submit(&self, requests: Vec<LlmBatchRequest>) -> Result<String>;
+
+    async fn poll(&self, job_id: &str) -> Result<LlmBatchJobStatus>;
+
+    /// Fetches the results of a completed job, keyed by `custom_id`.
+    async fn fetch_results(&self, job_id: &str) -> Result<HashMap<String, LlmBatchResult>>;
+}
+
+fn request_custom_id(scope: &str, body: &serde_json::Value) -> Result<String> {
+    let fingerprint = Fingerprinter::default()
+        .with(scope)?
+        .with(body)?
+        .into_fingerprint();
+    // Providers restrict custom IDs to `[a-zA-Z0-9_-]`.
+    Ok(BASE64_URL_SAFE_NO_PAD.encode(fingerprint.as_slice()))
+}
+
+const BATCH_JOB_STATE_KIND: &str = "LlmBatchJob";
+
+/// Key of a pending job in the state store. The value is the list of its requests' custom IDs.
+#[derive(Serialize, Deserialize)]
+struct BatchJobStateKey {
+    kind: String,
+    scope: String,
+    job_id: String,
+}
+
+impl BatchJobStateKey {
+    fn new(scope: &str, job_id: &str) -> Self {
+        Self {
+            kind: BATCH_JOB_STATE_KIND.to_string(),
+            scope: scope.to_string(),
+            job_id: job_id.to_string(),
+        }
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+/// Records of pending jobs.
+struct BatchJobStore {
+    /// The flow whose function state store keeps the records, once its setup is resolved.
+    flow_ctx: Option<Arc<FlowInstanceContext>>,
+    /// Used when the flow has no state store, e.g. without a database.
+    fallback: Arc<dyn SourceStateStore>,
+    warn_fallback: std::sync::Once,
+}
+
+impl BatchJobStore {
+    fn for_flow(flow_ctx: Arc<FlowInstanceContext>) -> Self {
+        Self {
+            flow_ctx: Some(flow_ctx),
+            fallback: Arc::new(InMemoryStateStore::default()),
+            warn_fallback: std::sync::Once::new(),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_store(store: Arc<dyn SourceStateStore>) -> Self {
+        Self {
+            flow_ctx: None,
+            fallback: store,
+            warn_fallback: std::sync::Once::new(),
+        }
+    }
+
+    fn store(&self) -> Arc<dyn SourceStateStore> {
+        let Some(flow_ctx) = &self.flow_ctx else {
+            return self.fallback.clone();
+        };
+        if let Some(store) = flow_ctx.function_state_store.read().unwrap().clone() {
+            return store;
+        }
+        self.warn_fallback.call_once(|| {
+            warn!(
+                "Flow `{}` has no state store. Submitted batch jobs won't be resumed after a restart.",
+                flow_ctx.flow_instance_name
+            )
+        });
+        self.fallback.clone()
+    }
+
+    /// Returns the IDs of previously submitted jobs for the given custom IDs.
+    async fn lookup(&self, scope: &str, custom_ids: &[String]) -> Result<HashMap<String, String>> {
+        let custom_ids: HashSet<&str> = custom_ids.iter().map(String::as_str).collect();
+        let mut job_ids = HashMap::new();
+        for (key, value) in self.store().list().await? {
+            // Skip states of other functions.
+            let Ok(key) = serde_json::from_value::<BatchJobStateKey>(key) else {
+                continue;
+            };
+            if key.kind != BATCH_JOB_STATE_KIND || key.scope != scope {
+                continue;
+            }
+            let job_custom_ids: Vec<String> = utils::deser::from_json_value(value)?;
+            for custom_id in job_custom_ids {
+                if custom_ids.contains(custom_id.as_str()) {
+                    job_ids.insert(custom_id, key.job_id.clone());
+                }
+            }
+        }
+        Ok(job_ids)
+    }
+
+    async fn record(&self, scope: &str, job_id: &str, custom_ids: &[String]) -> Result<()> {
+        self.store()
+            .upsert(
+                &BatchJobStateKey::new(scope, job_id).to_json()?,
+                serde_json::to_value(custom_ids)?,
+            )
+            .await
+    }
+
+    async fn remove_job(&self, scope: &str, job_id: &str) -> Result<()> {
+        self.store()
+            .delete(&BatchJobStateKey::new(scope, job_id).to_json()?)
+            .await
+    }
+}
+
+/// Submits request bodies as batch jobs and waits for their results.
+pub struct LlmBatchRunner {
+    client: Box<dyn LlmBatchClient>,
+    job_store: BatchJobStore,
+    poll_interval: Duration,
+    timeout: Duration,
+    max_batch_size: usize,
+}
+
+impl LlmBatchRunner {
+    /// Pending jobs are recorded in the function state store of the flow.
+    pub fn new(
+        client: Box<dyn LlmBatchClient>,
+        spec: &BatchApiSpec,
+        flow_ctx: Arc<FlowInstanceContext>,
+    ) -> Self {
+        Self::with_job_store(client, spec, BatchJobStore::for_flow(flow_ctx))
+    }
+
+    fn with_job_store(
+        client: Box<dyn LlmBatchClient>,
+        spec: &BatchApiSpec,
+        job_store: BatchJobStore,
+    ) -> Self {
+        Self {
+            client,
+            job_store,
+            poll_interval: spec.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL),
+            timeout: spec.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            max_batch_size: spec.max_batch_size.unwrap_or(DEFAULT_MAX_BATCH_SIZE),
+        }
+    }
+
+    pub fn client(&self) -> &dyn LlmBatchClient {
+        self.client.as_ref()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    /// Runs the request bodies through the batch API and returns the response bodies in the same order.
+    ///
+    /// Requests already covered by a pending job (e.g. submitted before a restart) are not
+    /// submitted again; the runner waits for the existing job instead.
+    pub async fn run(&self, bodies: Vec<serde_json::Value>) -> Result<Vec<serde_json::Value>> {
+        let scope = self.client.scope();
+        let custom_ids = bodies
+            .iter()
+            .map(|body| request_custom_id(scope, body))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut job_ids = self.job_store.lookup(scope, &custom_ids).await?;
+        let mut new_requests = IndexMap::new();
+        for (custom_id, body) in custom_ids.iter().zip(bodies) {
+            if !job_ids.contains_key(custom_id) {
+                new_requests.entry(custom_id.clone()).or_insert(body);
+            }
+        }
+        if !job_ids.is_empty() {
+            info!(
+                "Resuming {} request(s) from previously submitted batch job(s) of {scope}",
+                job_ids.len()
+            );
+        }
+        if !new_requests.is_empty() {
+            let new_custom_ids: Vec<String> = new_requests.keys().cloned().collect();
+            let requests = new_requests
+                .into_iter()
+                .map(|(custom_id, body)| LlmBatchRequest { custom_id, body })
+                .collect();
+            let job_id = self.client.submit(requests).await?;
+            info!(
+                "Submitted batch job {job_id} with {} request(s) to {scope}",
+                new_custom_ids.len()
+            );
+            self.job_store
+                .record(scope, &job_id, &new_custom_ids)
+                .await?;
+            job_ids.extend(new_custom_ids.into_iter().map(|id| (id, job_id.clone())));
+        }
+
+        let pending_jobs: IndexSet<&str> = custom_ids
+            .iter()
+            .filter_map(|custom_id| job_ids.get(custom_id).map(String::as_str))
+            .collect();
+        let mut results: HashMap<String, LlmBatchResult> = HashMap::new();
+        for job_results in futures::future::try_join_all(
+            pending_jobs
+                .into_iter()
+                .map(|job_id| self.wait_for_results(job_id)),
+        )
+        .await?
+        {
+            results.extend(job_results);
+        }
+
+        let mut num_failures = 0;
+        let mut first_error = None;
+        let mut outputs = Vec::with_capacity(custom_ids.len());
+        for custom_id in custom_ids.iter() {
+            match results.get(custom_id) {
+                Some(LlmBatchResult::Succeeded(body)) => outputs.push(body.clone()),
+                Some(LlmBatchResult::Failed(error)) => {
+                    num_failures += 1;
+                    first_error.get_or_insert_with(|| error.clone());
+                }
+                None => {
+                    num_failures += 1;
+                    first_error.get_or_insert_with(|| {
+                        format!("No result returned for request {custom_id}")
+                    });
+                }
+            }
+        }
+        if let Some(error) = first_error {
+            client_bail!(
+                "{num_failures} of {} request(s) failed in the batch job(s) of {scope}. First error: {error}",
+                custom_ids.len()
+            );
+        }
+        Ok(outputs)
+    }
+
+    async fn wait_for_results(&self, job_id: &str) -> Result<HashMap<String, LlmBatchResult>> {
+        let scope = self.client.scope();
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        loop {
+            match self.client.poll(job_id).await? {
+                LlmBatchJobStatus::InProgress => {
+                    if tokio::time::Instant::now() >= deadline {
+                        // Keep the job records, so the job is picked up again on the next attempt.
+                        client_bail!(
+                            "Batch job {job_id} of {scope} didn't end within {:?}",
+                            self.timeout
+                        );
+                    }
+                    tokio::time::sleep(self.poll_interval).await;
+                }
+                LlmBatchJobStatus::Completed => break,
+                LlmBatchJobStatus::Failed(message) => {
+                    self.job_store.remove_job(scope, job_id).await?;
+                    client_bail!("Batch job {job_id} of {scope} failed: {message}");
+                }
+            }
+        }
+        let results = self.client.fetch_results(job_id).await?;
+        self.job_store.remove_job(scope, job_id).await?;
+        Ok(results)
+    }
+}
+
+async fn get_json<T: DeserializeOwned>(
+    req_builder: impl Fn() -> reqwest::RequestBuilder,
+    context: &str,
+) -> Result<T> {
+    let resp = http::request(req_builder)
+        .await
+        .with_context(|| context.to_string())?;
+    resp.json()
+        .await
+        .map_err(Error::internal)
+        .with_context(|| format!("{context}: invalid JSON response"))
+}
+
+async fn get_text(
+    req_builder: impl Fn() -> reqwest::RequestBuilder,
+    context: &str,
+) -> Result<String> {
+    let resp = http::request(req_builder)
+        .await
+        .with_context(|| context.to_string())?;
+    resp.text()
+        .await
+        .map_err(Error::internal)
+        .with_context(|| format!("{context}: invalid response body"))
+}
+
+////////////////////////////////////////////////////////////
+// OpenAI
+////////////////////////////////////////////////////////////
+
+#[cfg(feature = "provider-openai")]
+pub use openai_batch::OpenAiBatchClient;
+
+#[cfg(feature = "provider-openai")]
+mod openai_batch {
+    use super::*;
+
+    const DEFAULT_ADDRESS: &str = "https://api.openai.com/v1";
+
+    #[derive(Deserialize)]
+    struct ObjectId {
+        id: String,
+    }
+
+    #[derive(Deserialize)]
+    struct BatchErrorData {
+        message: String,
+    }
+
+    #[derive(Deserialize)]
+    struct BatchErrors {
+        #[serde(default)]
+        data: Vec<BatchErrorData>,
+    }
+
+    #[derive(Deserialize)]
+    struct BatchObject {
+        status: String,
+        output_file_id: Option<String>,
+        error_file_id: Option<String>,
+        errors: Option<BatchErrors>,
+    }
+
+    #[derive(Deserialize)]
+    struct OutputResponse {
+        status_code: u16,
+        body: serde_json::Value,
+    }
+
+    #[derive(Deserialize)]
+    struct OutputLine {
+        custom_id: String,
+        response: Option<OutputResponse>,
+        error: Option<serde_json::Value>,
+    }
+
+    /// Client for the OpenAI batch API, or any server implementing the same endpoints.
+    pub struct OpenAiBatchClient {
+        client: reqwest::Client,
+        address: String,
+        api_key: String,
+        endpoint: &'static str,
+        scope: String,
+    }
+
+    impl OpenAiBatchClient {
+        pub fn new(
+            address: Option<String>,
+            api_key: Option<String>,
+            endpoint: LlmBatchEndpoint,
+        ) -> Result<Self> {
+            let address = address
+                .unwrap_or_else(|| DEFAULT_ADDRESS.to_string())
+                .trim_end_matches('/')
+                .to_string();
+            let api_key = match api_key {
+                Some(key) => key,
+                None => std::env::var("OPENAI_API_KEY").map_err(|_| {
+                    client_error!("OPENAI_API_KEY environment variable must be set")
+                })?,
+            };
+            let endpoint = match endpoint {
+                LlmBatchEndpoint::Embeddings => "/v1/embeddings",
+                LlmBatchEndpoint::Generation => "/v1/chat/completions",
+            };
+            Ok(Self {
+                client: reqwest::Client::new(),
+                scope: format!("openai:{address}{endpoint}"),
+                address,
+                api_key,
+                endpoint,
+            })
+        }
+
+        async fn get_batch(&self, job_id: &str) -> Result<BatchObject> {
+            get_json(
+                || {
+                    self.client
+                        .get(format!("{}/batches/{job_id}", self.address))
+                        .bearer_auth(&self.api_key)
+                },
+                "Failed to get OpenAI batch job",
+            )
+            .await
+        }
+
+        async fn read_output_file(
+            &self,
+            file_id: &str,
+            results: &mut HashMap<String, LlmBatchResult>,
+        ) -> Result<()> {
+            let content = get_text(
+                || {
+                    self.client
+                        .get(format!("{}/files/{file_id}/content", self.address))
+                        .bearer_auth(&self.api_key)
+                },
+                "Failed to download OpenAI batch output",
+            )
+            .await?;
+            for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                let line: OutputLine = utils::deser::from_json_str(line)?;
+                let result = match (line.response, line.error) {
+                    (Some(response), _) if (200..300).contains(&response.status_code) => {
+                        LlmBatchResult::Succeeded(response.body)
+                    }
+                    (Some(response), _) => LlmBatchResult::Failed(format!(
+                        "HTTP status {}: {}",
+                        response.status_code, response.body
+                    )),
+                    (None, Some(error)) => LlmBatchResult::Failed(error.to_string()),
+                    (None, None) => LlmBatchResult::Failed("Empty result".to_string()),
+                };
+                results.insert(line.custom_id, result);
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl LlmBatchClient for OpenAiBatchClient {
+        fn scope(&self) -> &str {
+            &self.scope
+        }
+
+        fn embedding_request_body(
+            &self,
+            request: &LlmEmbeddingRequest<'_>,
+        ) -> Result<serde_json::Value> {
+            let mut body = serde_json::json!({
+                "model": request.model,
+                "input": request.texts,
+            });
+            if let Some(dimensions) = request.output_dimension {
+                body["dimensions"] = dimensions.into();
+            }
+            Ok(body)
+        }
+
+        fn parse_embedding_response(&self, body: serde_json::Value) -> Result<Vec<Vec<f32>>> {
+            #[derive(Deserialize)]
+            struct EmbeddingData {
+                embedding: Vec<f32>,
+            }
+            #[derive(Deserialize)]
+            struct EmbeddingResponse {
+                data: Vec<EmbeddingData>,
+            }
+            let resp: EmbeddingResponse = utils::deser::from_json_value(body)?;
+            Ok(resp.data.into_iter().map(|d| d.embedding).collect())
+        }
+
+        fn generation_request_body(
+            &self,
+            request: &LlmGenerateRequest<'_>,
+        ) -> Result<serde_json::Value> {
+            super::super::openai::create_llm_generation_request(request)
+        }
+
+        fn parse_generation_response(&self, body: serde_json::Value) -> Result<GeneratedOutput> {
+            let text = body["choices"][0]["message"]["content"]
+                .as_str()
+                .ok_or_else(|| client_error!("No response from OpenAI"))?;
+            Ok(match utils::deser::from_json_str(text) {
+                Ok(json) => GeneratedOutput::Json(json),
+                Err(_) => GeneratedOutput::Text(text.to_string()),
+            })
+        }
+
+        async fn submit(&self, requests: Vec<LlmBatchRequest>) -> Result<String> {
+            let mut input = Vec::new();
+            for request in requests {
+                serde_json::to_writer(
+                    &mut input,
+                    &serde_json::json!({
+                        "custom_id": request.custom_id,
+                        "method": "POST",
+                        "url": self.endpoint,
+                        "body": request.body,
+                    }),
+                )?;
+                input.push(b'\n');
+            }
+
+            let input_file: ObjectId = get_json(
+                || {
+                    let form = reqwest::multipart::Form::new()
+                        .text("purpose", "batch")
+                        .part(
+                            "file",
+                            reqwest::multipart::Part::bytes(input.clone())
+                                .file_name("batch_input.jsonl"),
+                        );
+                    self.client
+                        .post(format!("{}/files", self.address))
+                        .bearer_auth(&self.api_key)
+                        .multipart(form)
+                },
+                "Failed to upload OpenAI batch input file",
+            )
+            .await?;
+
+            let payload = serde_json::json!({
+                "input_file_id": input_file.id,
+                "endpoint": self.endpoint,
+                "completion_window": "24h",
+            });
+            let batch: ObjectId = get_json(
+                || {
+                    self.client
+                        .post(format!("{}/batches", self.address))
+                        .bearer_auth(&self.api_key)
+                        .json(&payload)
+                },
+                "Failed to create OpenAI batch job",
+            )
+            .await?;
+            Ok(batch.id)
+        }
+
+        async fn poll(&self, job_id: &str) -> Result<LlmBatchJobStatus> {
+            let batch = self.get_batch(job_id).await?;
+            let status = match batch.status.as_str() {
+                "validating" | "in_progress" | "finalizing" => LlmBatchJobStatus::InProgress,
+                "completed" => LlmBatchJobStatus::Completed,
+                status => {
+                    let errors = batch
+                        .errors
+                        .map(|errors| {
+                            errors
+                                .data
+                                .into_iter()
+                                .map(|e| e.message)
+                                .collect::<Vec<_>>()
+                                .join("; ")
+                        })
+                        .unwrap_or_default();
+                    LlmBatchJobStatus::Failed(format!("status `{status}`. {errors}"))
+                }
+            };
+            Ok(status)
+        }
+
+        async fn fetch_results(&self, job_id: &str) -> Result<HashMap<String, LlmBatchResult>> {
+            let batch = self.get_batch(job_id).await?;
+            let mut results = HashMap::new();
+            for file_id in [batch.output_file_id, batch.error_file_id]
+                .into_iter()
+                .flatten()
+            {
+                self.read_output_file(&file_id, &mut results).await?;
+            }
+            Ok(results)
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Anthropic
+////////////////////////////////////////////////////////////
+
+#[cfg(feature = "provider-anthropic")]
+pub use anthropic_batch::AnthropicBatchClient;
+
+#[cfg(feature = "provider-anthropic")]
+mod anthropic_batch {
+    use super::*;
+
+    const DEFAULT_ADDRESS: &str = "https://api.anthropic.com";
+    const API_VERSION: &str = "2023-06-01";
+
+    #[derive(Deserialize)]
+    struct MessageBatch {
+        id: String,
+        processing_status: String,
+        results_url: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum ResultEntry {
+        Succeeded { message: serde_json::Value },
+        Errored { error: serde_json::Value },
+        Canceled,
+        Expired,
+    }
+
+    #[derive(Deserialize)]
+    struct ResultLine {
+        custom_id: String,
+        result: ResultEntry,
+    }
+
+    /// Client for the Anthropic Message Batches API.
+    pub struct AnthropicBatchClient {
+        client: reqwest::Client,
+        address: String,
+        api_key: String,
+        scope: String,
+    }
+
+    impl AnthropicBatchClient {
+        pub fn new(address: Option<String>, api_key: Option<String>) -> Result<Self> {
+            let address = address
+                .unwrap_or_else(|| DEFAULT_ADDRESS.to_string())
+                .trim_end_matches('/')
+                .to_string();
+            let api_key = match api_key {
+                Some(key) => key,
+                None => std::env::var("ANTHROPIC_API_KEY").map_err(|_| {
+                    client_error!("ANTHROPIC_API_KEY environment variable must be set")
+                })?,
+            };
+            Ok(Self {
+                client: reqwest::Client::new(),
+                scope: format!("anthropic:{address}/v1/messages"),
+                address,
+                api_key,
+            })
+        }
+
+        fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+            self.client
+                .request(method, url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", API_VERSION)
+        }
+
+        async fn get_batch(&self, job_id: &str) -> Result<MessageBatch> {
+            let url = format!("{}/v1/messages/batches/{job_id}", self.address);
+            get_json(
+                || self.request(reqwest::Method::GET, &url),
+                "Failed to get Anthropic message batch",
+            )
+            .await
+        }
+    }
+
+    #[async_trait]
+    impl LlmBatchClient for AnthropicBatchClient {
+        fn scope(&self) -> &str {
+            &self.scope
+        }
+
+        fn generation_request_body(
+            &self,
+            request: &LlmGenerateRequest<'_>,
+        ) -> Result<serde_json::Value> {
+            super::super::anthropic::build_message_payload(request)
+        }
+
+        fn parse_generation_response(&self, body: serde_json::Value) -> Result<GeneratedOutput> {
+            super::super::anthropic::parse_message_response(body)
+        }
+
+        async fn submit(&self, requests: Vec<LlmBatchRequest>) -> Result<String> {
+            let payload = serde_json::json!({
+                "requests": requests
+                    .into_iter()
+                    .map(|request| serde_json::json!({
+                        "custom_id": request.custom_id,
+                        "params": request.body,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            let url = format!("{}/v1/messages/batches", self.address);
+            let batch: MessageBatch = get_json(
+                || self.request(reqwest::Method::POST, &url).json(&payload),
+                "Failed to create Anthropic message batch",
+            )
+            .await?;
+            Ok(batch.id)
+        }
+
+        async fn poll(&self, job_id: &str) -> Result<LlmBatchJobStatus> {
+            let batch = self.get_batch(job_id).await?;
+            Ok(match batch.processing_status.as_str() {
+                "ended" => LlmBatchJobStatus::Completed,
+                _ => LlmBatchJobStatus::InProgress,
+            })
+        }
+
+        async fn fetch_results(&self, job_id: &str) -> Result<HashMap<String, LlmBatchResult>> {
+            let batch = self.get_batch(job_id).await?;
+            let Some(results_url) = batch.results_url else {
+                client_bail!("Anthropic message batch {job_id} has no results");
+            };
+            let content = get_text(
+                || self.request(reqwest::Method::GET, &results_url),
+                "Failed to download Anthropic message batch results",
+            )
+            .await?;
+            let mut results = HashMap::new();
+            for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                let line: ResultLine = utils::deser::from_json_str(line)?;
+                let result = match line.result {
+                    ResultEntry::Succeeded { message } => LlmBatchResult::Succeeded(message),
+                    ResultEntry::Errored { error } => LlmBatchResult::Failed(error.to_string()),
+                    ResultEntry::Canceled => LlmBatchResult::Failed("Canceled".to_string()),
+                    ResultEntry::Expired => LlmBatchResult::Failed("Expired".to_string()),
+                };
+                results.insert(line.custom_id, result);
+            }
+            Ok(results)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "provider-openai", feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::ops::sdk::AuthRegistry;
+    use axum::{
+        Json, Router,
+        extract::{Path, State},
+        routing::{get, post},
+    };
+
+    /// A minimal server implementing the OpenAI batch endpoints. Each embedding is `[len(text)]`.
+    #[derive(Default)]
+    struct StubState {
+        input_lines: Vec<String>,
+        num_submissions: usize,
+        num_polls: usize,
+    }
+
+    type SharedState = Arc<Mutex<StubState>>;
+
+    async fn upload_file(
+        State(state): State<SharedState>,
+        body: String,
+    ) -> Json<serde_json::Value> {
+        let mut state = state.lock().unwrap();
+        state.input_lines.extend(
+            body.lines()
+                .filter(|line| line.starts_with('{'))
+                .map(str::to_string),
+        );
+        Json(serde_json::json!({ "id": "file-input" }))
+    }
+
+    async fn create_batch(State(state): State<SharedState>) -> Json<serde_json::Value> {
+        let mut state = state.lock().unwrap();
+        state.num_submissions += 1;
+        state.num_polls = 0;
+        Json(serde_json::json!({ "id": format!("batch-{}", state.num_submissions) }))
+    }
+
+    async fn get_batch(
+        State(state): State<SharedState>,
+        Path(_id): Path<String>,
+    ) -> Json<serde_json::Value> {
+        let mut state = state.lock().unwrap();
+        state.num_polls += 1;
+        // Report the job as in progress on the first poll of each job.
+        if state.num_polls == 1 {
+            Json(serde_json::json!({ "status": "in_progress" }))
+        } else {
+            Json(serde_json::json!({ "status": "completed", "output_file_id": "file-output" }))
+        }
+    }
+
+    async fn get_file_content(State(state): State<SharedState>, Path(_id): Path<String>) -> String {
+        let state = state.lock().unwrap();
+        state
+            .input_lines
+            .iter()
+            .map(|line| {
+                let request: serde_json::Value = serde_json::from_str(line).unwrap();
+                let embeddings = request["body"]["input"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|text| {
+                        serde_json::json!({ "embedding": [text.as_str().unwrap().len() as f32] })
+                    })
+                    .collect::<Vec<_>>();
+                serde_json::json!({
+                    "custom_id": request["custom_id"],
+                    "response": { "status_code": 200, "body": { "data": embeddings } },
+                })
+                .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    async fn start_stub_server() -> (String, SharedState) {
+        let state = SharedState::default();
+        let app = Router::new()
+            .route("/files", post(upload_file))
+            .route("/files/{id}/content", get(get_file_content))
+            .route("/batches", post(create_batch))
+            .route("/batches/{id}", get(get_batch))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (address, state)
+    }
+
+    fn new_client(address: &str) -> Box<dyn LlmBatchClient> {
+        Box::new(
+            OpenAiBatchClient::new(
+                Some(address.to_string()),
+                Some("test-key".to_string()),
+                LlmBatchEndpoint::Embeddings,
+            )
+            .unwrap(),
+        )
+    }
+
+    fn test_spec() -> BatchApiSpec {
+        BatchApiSpec {
+            poll_interval: Some(Duration::from_millis(10)),
+            ..Default::default()
+        }
+    }
+
+    fn new_runner(address: &str) -> LlmBatchRunner {
+        LlmBatchRunner::with_job_store(
+            new_client(address),
+            &test_spec(),
+            BatchJobStore::with_store(Arc::new(InMemoryStateStore::default())),
+        )
+    }
+
+    /// A runner of a flow whose function state store is `store`.
+    fn new_flow_runner(address: &str, store: Arc<dyn SourceStateStore>) -> LlmBatchRunner {
+        let flow_ctx = Arc::new(FlowInstanceContext {
+            flow_instance_name: "test_flow".to_string(),
+            auth_registry: Arc::new(AuthRegistry::default()),
+            function_state_store: RwLock::new(Some(store)),
+        });
+        LlmBatchRunner::new(new_client(address), &test_spec(), flow_ctx)
+    }
+
+    fn embedding_bodies(runner: &LlmBatchRunner, texts: &[&str]) -> Vec<serde_json::Value> {
+        texts
+            .iter()
+            .map(|text| {
+                runner
+                    .client()
+                    .embedding_request_body(&LlmEmbeddingRequest {
+                        model: "text-embedding-3-small",
+                        texts: vec![Cow::Borrowed(*text)],
+                        output_dimension: None,
+                        task_type: None,
+                    })
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_run_maps_results_back_in_order() {
+        let (address, state) = start_stub_server().await;
+        let runner = new_runner(&address);
+        let bodies = embedding_bodies(&runner, &["a", "abc", "a", "ab"]);
+
+        let outputs = runner.run(bodies).await.unwrap();
+        let embeddings = outputs
+            .into_iter()
+            .map(|body| runner.client().parse_embedding_response(body).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            embeddings,
+            vec![
+                vec![vec![1.0]],
+                vec![vec![3.0]],
+                vec![vec![1.0]],
+                vec![vec![2.0]]
+            ]
+        );
+
+        let state = state.lock().unwrap();
+        assert_eq!(state.num_submissions, 1);
+        // Duplicated requests are only submitted once.
+        assert_eq!(state.input_lines.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_restarted_runner_resumes_submitted_job() {
+        let (address, state) = start_stub_server().await;
+        let store: Arc<dyn SourceStateStore> = Arc::new(InMemoryStateStore::default());
+
+        // A runner submits a job, and the process stops before the job ends.
+        let custom_ids = {
+            let runner = new_flow_runner(&address, store.clone());
+            let bodies = embedding_bodies(&runner, &["hello", "world!"]);
+            let scope = runner.client().scope().to_string();
+            let custom_ids = bodies
+                .iter()
+                .map(|body| request_custom_id(&scope, body).unwrap())
+                .collect::<Vec<_>>();
+            let requests = custom_ids
+                .iter()
+                .zip(bodies)
+                .map(|(custom_id, body)| LlmBatchRequest {
+                    custom_id: custom_id.clone(),
+                    body,
+                })
+                .collect();
+            let job_id = runner.client().submit(requests).await.unwrap();
+            runner
+                .job_store
+                .record(&scope, &job_id, &custom_ids)
+                .await
+                .unwrap();
+            custom_ids
+        };
+        assert_eq!(store.list().await.unwrap().len(), 1);
+
+        // The runner of the restarted process picks up the job from the flow's state store.
+        let runner = new_flow_runner(&address, store.clone());
+        let bodies = embedding_bodies(&runner, &["hello", "world!"]);
+        let outputs = runner.run(bodies).await.unwrap();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(state.lock().unwrap().num_submissions, 1);
+        assert!(
+            runner
+                .job_store
+                .lookup(runner.client().scope(), &custom_ids)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(store.list().await.unwrap().is_empty());
+    }
+}
@@ -14,8 +14,8 @@ use crate::prelude::*;
 use base64::prelude::*;
 
 use crate::llm::{
-    GeneratedOutput, LlmGenerateRequest, LlmGenerateResponse, LlmGenerationClient, OutputFormat,
-    ToJsonSchemaOptions, detect_image_mime_type,
+    GeneratedOutput, LlmAttachmentKind, LlmGenerateRequest, LlmGenerateResponse,
+    LlmGenerationClient, OutputFormat, ToJsonSchemaOptions,
 };
 use urlencoding::encode;
 
@@ -55,18 +55,47 @@ impl LlmGenerationClient for Client {
     ) -> Result<LlmGenerateResponse> {
         let mut user_content_parts: Vec<serde_json::Value> = Vec::new();
 
-        // Add image part if present
-        if let Some(image_bytes) = &request.image {
-            let base64_image = BASE64_STANDARD.encode(image_bytes.as_ref());
-            let mime_type = detect_image_mime_type(image_bytes.as_ref())?;
-            user_content_parts.push(serde_json::json!({
-                "image": {
-                    "format": mime_type.split('/').nth(1).unwrap_or("png"),
-                    "source": {
-                        "bytes": base64_image,
+        // Add attachment parts
+        for (index, attachment) in request.attachments.iter().enumerate() {
+            let base64_data = BASE64_STANDARD.encode(attachment.data.as_ref());
+            let part = match attachment.kind {
+                LlmAttachmentKind::Image => serde_json::json!({
+                    "image": {
+                        "format": attachment.mime_type.split('/').nth(1).unwrap_or("png"),
+                        "source": {
+                            "bytes": base64_data,
+                        }
                     }
+                }),
+                LlmAttachmentKind::Pdf | LlmAttachmentKind::Text => {
+                    // Bedrock requires a name for each document, restricted to a few characters.
+                    let name = attachment
+                        .name
+                        .as_deref()
+                        .map(|name| {
+                            name.chars()
+                                .map(|c| {
+                                    if c.is_ascii_alphanumeric() || c == '-' || c == ' ' {
+                                        c
+                                    } else {
+                                        '-'
+                                    }
+                                })
+                                .collect::<String>()
+                        })
+                        .unwrap_or_else(|| format!("attachment-{index}"));
+                    serde_json::json!({
+                        "document": {
+                            "format": if attachment.kind == LlmAttachmentKind::Pdf { "pdf" } else { "txt" },
+                            "name": name,
+                            "source": {
+                                "bytes": base64_data,
+                            }
+                        }
+                    })
                 }
-            }));
+            };
+            user_content_parts.push(part);
         }
 
         // Add text part
@@ -13,8 +13,9 @@
 use crate::prelude::*;
 
 use crate::llm::{
-    GeneratedOutput, LlmEmbeddingClient, LlmGenerateRequest, LlmGenerateResponse,
-    LlmGenerationClient, OutputFormat, ToJsonSchemaOptions, detect_image_mime_type,
+    GeneratedOutput, LlmEmbeddingClient, LlmEmbeddingContent, LlmGenerateRequest,
+    LlmGenerateResponse, LlmGenerationClient, LlmMultimodalEmbeddingRequest, OutputFormat,
+    ToJsonSchemaOptions,
 };
 use base64::prelude::*;
 use google_cloud_aiplatform_v1 as vertexai;
@@ -34,6 +35,8 @@ fn get_embedding_dimension(model: &str) -> Option<u32> {
         || model.starts_with("text-multilingual-embedding-")
     {
         Some(768)
+    } else if model.starts_with("multimodalembedding") {
+        Some(1408)
     } else {
         None
     }
@@ -118,14 +121,12 @@ impl LlmGenerationClient for AiStudioClient {
         // Add text part first
         user_parts.push(serde_json::json!({ "text": request.user_prompt }));
 
-        // Add image part if present
-        if let Some(image_bytes) = &request.image {
-            let base64_image = BASE64_STANDARD.encode(image_bytes.as_ref());
-            let mime_type = detect_image_mime_type(image_bytes.as_ref())?;
+        // Add attachment parts; Gemini accepts images, PDFs and text as inline data
+        for attachment in request.attachments.iter() {
             user_parts.push(serde_json::json!({
                 "inlineData": {
-                    "mimeType": mime_type,
-                    "data": base64_image
+                    "mimeType": attachment.mime_type,
+                    "data": BASE64_STANDARD.encode(attachment.data.as_ref())
                 }
             }));
         }
@@ -336,14 +337,13 @@ impl LlmGenerationClient for VertexAiClient {
         let mut parts = Vec::new();
         // Add text part
         parts.push(Part::new().set_text(request.user_prompt.to_string()));
-        // Add image part if present
-        if let Some(image_bytes) = request.image {
-            let mime_type = detect_image_mime_type(image_bytes.as_ref())?;
+        // Add attachment parts
+        for attachment in request.attachments {
             parts.push(
                 Part::new().set_inline_data(
                     Blob::new()
-                        .set_data(image_bytes.into_owned())
-                        .set_mime_type(mime_type.to_string()),
+                        .set_data(attachment.data.into_owned())
+                        .set_mime_type(attachment.mime_type.into_owned()),
                 ),
             );
         }
@@ -416,6 +416,68 @@ impl LlmGenerationClient for VertexAiClient {
     }
 }
 
+/// Instance of a Vertex AI multimodal embedding request for an input.
+///
+/// Vertex AI takes at most one text and one image per instance, embedded into the same space.
+/// Text parts of an input are joined by line breaks.
+fn multimodal_instance(parts: &[LlmEmbeddingContent<'_>]) -> Result<Value> {
+    let mut texts = Vec::new();
+    let mut image = None;
+    for part in parts {
+        match part {
+            LlmEmbeddingContent::Text(text) => texts.push(text.as_ref()),
+            LlmEmbeddingContent::Image(bytes) => {
+                if image.is_some() {
+                    client_bail!(
+                        "Vertex AI multimodal embedding takes at most one image per input"
+                    );
+                }
+                image = Some(BASE64_STANDARD.encode(bytes.as_ref()));
+            }
+        }
+    }
+    let mut instance = serde_json::json!({});
+    if !texts.is_empty() {
+        instance["text"] = Value::String(texts.join("\n"));
+    }
+    if let Some(image) = image {
+        instance["image"] = serde_json::json!({ "bytesBase64Encoded": image });
+    }
+    if instance
+        .as_object()
+        .is_some_and(|instance| instance.is_empty())
+    {
+        client_bail!("Vertex AI multimodal embedding input is empty");
+    }
+    Ok(instance)
+}
+
+/// Embedding of an input from a Vertex AI multimodal embedding prediction.
+/// For an input with both text and image, it's the mean of the text and image embeddings.
+fn multimodal_prediction_embedding(prediction: Value) -> Result<Vec<f32>> {
+    let mut prediction: serde_json::Map<String, Value> = utils::deser::from_json_value(prediction)?;
+    let mut embedding = |field: &str| -> Result<Option<Vec<f32>>> {
+        Ok(prediction
+            .remove(field)
+            .map(utils::deser::from_json_value)
+            .transpose()?)
+    };
+    match (embedding("textEmbedding")?, embedding("imageEmbedding")?) {
+        (None, None) => client_bail!("No embeddings in prediction"),
+        (Some(embedding), None) | (None, Some(embedding)) => Ok(embedding),
+        (Some(text_embedding), Some(image_embedding)) => {
+            if text_embedding.len() != image_embedding.len() {
+                client_bail!("Text and image embeddings in prediction have different dimensions");
+            }
+            Ok(text_embedding
+                .iter()
+                .zip(image_embedding.iter())
+                .map(|(text, image)| (text + image) / 2.0)
+                .collect())
+        }
+    }
+}
+
 #[async_trait]
 impl LlmEmbeddingClient for VertexAiClient {
     async fn embed_text<'req>(
@@ -472,7 +534,101 @@ impl LlmEmbeddingClient for VertexAiClient {
         Ok(super::LlmEmbeddingResponse { embeddings })
     }
 
+    async fn embed_multimodal<'req>(
+        &self,
+        request: LlmMultimodalEmbeddingRequest<'req>,
+    ) -> Result<super::LlmEmbeddingResponse> {
+        let instances: Vec<_> = request
+            .inputs
+            .iter()
+            .map(|parts| multimodal_instance(parts))
+            .collect::<Result<_>>()?;
+
+        let mut parameters = serde_json::json!({});
+        if let Some(output_dimension) = request.output_dimension {
+            parameters["dimension"] = serde_json::Value::Number(output_dimension.into());
+        }
+
+        let response = self
+            .client
+            .predict()
+            .set_endpoint(self.get_model_path(request.model))
+            .set_instances(instances)
+            .set_parameters(parameters)
+            .with_idempotency(true)
+            .send()
+            .await
+            .map_err(Error::internal)?;
+
+        let embeddings: Vec<Vec<f32>> = response
+            .predictions
+            .into_iter()
+            .map(multimodal_prediction_embedding)
+            .collect::<Result<_>>()?;
+        Ok(super::LlmEmbeddingResponse { embeddings })
+    }
+
     fn get_default_embedding_dimension(&self, model: &str) -> Option<u32> {
         get_embedding_dimension(model)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multimodal_instance() {
+        let image = b"\x89PNG\r\n\x1a\n".as_slice();
+        let instance = multimodal_instance(&[
+            LlmEmbeddingContent::Text("a diagram".into()),
+            LlmEmbeddingContent::Image(image.into()),
+            LlmEmbeddingContent::Text("of the pipeline".into()),
+        ])
+        .unwrap();
+        assert_eq!(
+            instance,
+            serde_json::json!({
+                "text": "a diagram\nof the pipeline",
+                "image": { "bytesBase64Encoded": BASE64_STANDARD.encode(image) },
+            })
+        );
+        assert_eq!(
+            multimodal_instance(&[LlmEmbeddingContent::Text("text".into())]).unwrap(),
+            serde_json::json!({ "text": "text" })
+        );
+        assert!(
+            multimodal_instance(&[
+                LlmEmbeddingContent::Image(image.into()),
+                LlmEmbeddingContent::Image(image.into()),
+            ])
+            .is_err()
+        );
+        assert!(multimodal_instance(&[]).is_err());
+    }
+
+    #[test]
+    fn test_multimodal_prediction_embedding() {
+        let embedding = multimodal_prediction_embedding(serde_json::json!({
+            "imageEmbedding": [0.5, 1.0],
+        }))
+        .unwrap();
+        assert_eq!(embedding, vec![0.5, 1.0]);
+
+        let embedding = multimodal_prediction_embedding(serde_json::json!({
+            "textEmbedding": [0.25, 0.0],
+            "imageEmbedding": [0.75, 1.0],
+        }))
+        .unwrap();
+        assert_eq!(embedding, vec![0.5, 0.5]);
+
+        assert!(multimodal_prediction_embedding(serde_json::json!({ "other": [] })).is_err());
+        assert!(
+            multimodal_prediction_embedding(serde_json::json!({
+                "textEmbedding": [0.25],
+                "imageEmbedding": [0.75, 1.0],
+            }))
+            .is_err()
+        );
+    }
+}
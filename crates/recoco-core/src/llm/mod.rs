@@ -20,6 +20,9 @@ use infer::Infer;
 use schemars::Schema;
 use std::borrow::Cow;
 
+#[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+pub use batch::{LlmBatchClient, LlmBatchEndpoint, LlmBatchRunner};
+
 static INFER: LazyLock<Infer> = LazyLock::new(Infer::new);
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -48,6 +51,18 @@ pub enum LlmApiType {
     AzureOpenAi,
 }
 
+/// Options for running requests through the provider's batch API instead of the synchronous API.
+/// Only supported with `provider-openai` or `provider-anthropic`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchApiSpec {
+    /// Interval between two polls of a submitted job. Defaults to 60 seconds.
+    pub poll_interval: Option<std::time::Duration>,
+    /// Maximum time to wait for a submitted job to end. Defaults to 26 hours.
+    pub timeout: Option<std::time::Duration>,
+    /// Maximum number of requests submitted in a single job. Defaults to 10,000.
+    pub max_batch_size: Option<usize>,
+}
+
 #[cfg(feature = "provider-gemini")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VertexAiConfig {
@@ -98,12 +113,63 @@ pub enum OutputFormat<'a> {
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmAttachmentKind {
+    Image,
+    Pdf,
+    Text,
+}
+
+/// A file passed to the model alongside the user prompt.
+#[derive(Debug)]
+pub struct LlmAttachment<'a> {
+    pub kind: LlmAttachmentKind,
+    pub mime_type: Cow<'a, str>,
+    pub data: Cow<'a, [u8]>,
+    pub name: Option<Cow<'a, str>>,
+}
+
+impl<'a> LlmAttachment<'a> {
+    /// Creates an attachment, detecting the MIME type from the content if it's not provided.
+    pub fn new(
+        data: Cow<'a, [u8]>,
+        mime_type: Option<Cow<'a, str>>,
+        name: Option<Cow<'a, str>>,
+    ) -> Result<Self> {
+        let mime_type = match mime_type {
+            Some(mime_type) => mime_type,
+            None => Cow::Borrowed(detect_attachment_mime_type(data.as_ref())?),
+        };
+        let kind = attachment_kind(&mime_type)?;
+        Ok(Self {
+            kind,
+            mime_type,
+            data,
+            name,
+        })
+    }
+
+    pub fn as_text(&self) -> Result<&str> {
+        std::str::from_utf8(self.data.as_ref())
+            .map_err(|_| client_error!("Text attachment is not valid UTF-8"))
+    }
+
+    /// Renders a text attachment as a prompt section, prefixed by its name if any.
+    pub fn to_prompt_text(&self) -> Result<String> {
+        let text = self.as_text()?;
+        Ok(match &self.name {
+            Some(name) => format!("<attachment name=\"{name}\">\n{text}\n</attachment>"),
+            None => format!("<attachment>\n{text}\n</attachment>"),
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct LlmGenerateRequest<'a> {
     pub model: &'a str,
     pub system_prompt: Option<Cow<'a, str>>,
     pub user_prompt: Cow<'a, str>,
-    pub image: Option<Cow<'a, [u8]>>,
+    pub attachments: Vec<LlmAttachment<'a>>,
     pub output_format: Option<OutputFormat<'a>>,
 }
 
@@ -137,6 +203,22 @@ pub struct LlmEmbeddingRequest<'a> {
     pub task_type: Option<Cow<'a, str>>,
 }
 
+/// A part of a multimodal embedding input.
+#[derive(Debug)]
+pub enum LlmEmbeddingContent<'a> {
+    Text(Cow<'a, str>),
+    Image(Cow<'a, [u8]>),
+}
+
+#[derive(Debug)]
+pub struct LlmMultimodalEmbeddingRequest<'a> {
+    pub model: &'a str,
+    /// Each input is a sequence of interleaved text and image parts, embedded into one vector.
+    pub inputs: Vec<Vec<LlmEmbeddingContent<'a>>>,
+    pub output_dimension: Option<u32>,
+    pub task_type: Option<Cow<'a, str>>,
+}
+
 pub struct LlmEmbeddingResponse {
     pub embeddings: Vec<Vec<f32>>,
 }
@@ -148,6 +230,17 @@ pub trait LlmEmbeddingClient: Send + Sync {
         request: LlmEmbeddingRequest<'req>,
     ) -> Result<LlmEmbeddingResponse>;
 
+    /// Embeds inputs mixing text and images. Only supported by multimodal embedding APIs.
+    async fn embed_multimodal<'req>(
+        &self,
+        request: LlmMultimodalEmbeddingRequest<'req>,
+    ) -> Result<LlmEmbeddingResponse> {
+        api_bail!(
+            "Multimodal embedding is not supported by this API (model: {})",
+            request.model
+        )
+    }
+
     fn get_default_embedding_dimension(&self, model: &str) -> Option<u32>;
 
     fn behavior_version(&self) -> Option<u32> {
@@ -157,6 +250,8 @@ pub trait LlmEmbeddingClient: Send + Sync {
 
 #[cfg(feature = "provider-anthropic")]
 mod anthropic;
+#[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+mod batch;
 #[cfg(feature = "provider-bedrock")]
 mod bedrock;
 #[cfg(feature = "provider-gemini")]
@@ -265,18 +360,83 @@ pub async fn new_llm_embedding_client(
             Box::new(openai::Client::new_azure(address, api_key, api_config).await?)
                 as Box<dyn LlmEmbeddingClient>
         }
-        #[cfg(any(
-            feature = "provider-openai",
-            feature = "provider-anthropic",
-            feature = "provider-bedrock"
-        ))]
-        LlmApiType::LiteLlm | LlmApiType::Vllm | LlmApiType::Anthropic | LlmApiType::Bedrock => {
+        #[cfg(feature = "provider-openai")]
+        LlmApiType::LiteLlm | LlmApiType::Vllm => {
+            api_bail!("Embedding is not supported for API type {:?}", api_type)
+        }
+        #[cfg(feature = "provider-anthropic")]
+        LlmApiType::Anthropic => {
+            api_bail!("Embedding is not supported for API type {:?}", api_type)
+        }
+        #[cfg(feature = "provider-bedrock")]
+        LlmApiType::Bedrock => {
             api_bail!("Embedding is not supported for API type {:?}", api_type)
         }
     };
     Ok(client)
 }
 
+#[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+pub fn new_llm_batch_client(
+    api_type: LlmApiType,
+    address: Option<String>,
+    api_key: Option<String>,
+    endpoint: LlmBatchEndpoint,
+) -> Result<Box<dyn LlmBatchClient>> {
+    match (api_type, endpoint) {
+        #[cfg(feature = "provider-openai")]
+        (LlmApiType::OpenAi, _) => Ok(Box::new(batch::OpenAiBatchClient::new(
+            address, api_key, endpoint,
+        )?)),
+        #[cfg(feature = "provider-anthropic")]
+        (LlmApiType::Anthropic, LlmBatchEndpoint::Generation) => Ok(Box::new(
+            batch::AnthropicBatchClient::new(address, api_key)?,
+        )),
+        #[allow(unreachable_patterns)]
+        _ => api_bail!(
+            "Batch API is not supported for API type {:?} with {:?} requests",
+            api_type,
+            endpoint
+        ),
+    }
+}
+
+fn attachment_kind(mime_type: &str) -> Result<LlmAttachmentKind> {
+    let essence = mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let kind = if essence.starts_with("image/") {
+        LlmAttachmentKind::Image
+    } else if essence == "application/pdf" {
+        LlmAttachmentKind::Pdf
+    } else if essence.starts_with("text/")
+        || matches!(
+            essence.as_str(),
+            "application/json" | "application/xml" | "application/yaml" | "application/x-yaml"
+        )
+    {
+        LlmAttachmentKind::Text
+    } else {
+        client_bail!("Unsupported attachment MIME type: {mime_type}");
+    };
+    Ok(kind)
+}
+
+/// Detects the MIME type of an attachment: images and PDFs by their magic bytes, UTF-8 content as plain text.
+pub fn detect_attachment_mime_type(bytes: &[u8]) -> Result<&'static str> {
+    let infer = &*INFER;
+    match infer.get(bytes) {
+        Some(info) if info.mime_type().starts_with("image/") => Ok(info.mime_type()),
+        Some(info) if info.mime_type() == "application/pdf" => Ok(info.mime_type()),
+        Some(info) => client_bail!("Unsupported attachment format: {}", info.mime_type()),
+        None if std::str::from_utf8(bytes).is_ok() => Ok("text/plain"),
+        None => client_bail!("Unknown attachment format"),
+    }
+}
+
 pub fn detect_image_mime_type(bytes: &[u8]) -> Result<&'static str> {
     let infer = &*INFER;
     match infer.get(bytes) {
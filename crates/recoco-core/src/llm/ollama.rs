@@ -12,7 +12,7 @@
 
 use crate::prelude::*;
 
-use super::{LlmEmbeddingClient, LlmGenerationClient};
+use super::{LlmAttachmentKind, LlmEmbeddingClient, LlmGenerationClient};
 use schemars::Schema;
 use serde_with::{base64::Base64, serde_as};
 
@@ -102,10 +102,28 @@ impl LlmGenerationClient for Client {
         request: super::LlmGenerateRequest<'req>,
     ) -> Result<super::LlmGenerateResponse> {
         let has_json_schema = request.output_format.is_some();
+        // Ollama only takes images natively; text attachments are inlined before the prompt.
+        let mut images = Vec::new();
+        let mut prompt_sections = Vec::new();
+        for attachment in request.attachments.iter() {
+            match attachment.kind {
+                LlmAttachmentKind::Image => images.push(attachment.data.as_ref()),
+                LlmAttachmentKind::Text => prompt_sections.push(attachment.to_prompt_text()?),
+                LlmAttachmentKind::Pdf => {
+                    client_bail!("Ollama doesn't support PDF attachments")
+                }
+            }
+        }
+        let prompt = if prompt_sections.is_empty() {
+            Cow::Borrowed(request.user_prompt.as_ref())
+        } else {
+            prompt_sections.push(request.user_prompt.to_string());
+            Cow::Owned(prompt_sections.join("\n\n"))
+        };
         let req = OllamaRequest {
             model: request.model,
-            prompt: request.user_prompt.as_ref(),
-            images: request.image.as_deref().map(|img| vec![img]),
+            prompt: prompt.as_ref(),
+            images: (!images.is_empty()).then_some(images),
             format: request.output_format.as_ref().map(
                 |super::OutputFormat::JsonSchema { schema, .. }| {
                     OllamaFormat::JsonSchema(schema.as_ref())
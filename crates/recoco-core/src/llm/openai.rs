@@ -15,7 +15,7 @@
 use crate::prelude::*;
 use base64::prelude::*;
 
-use super::{LlmEmbeddingClient, LlmGenerationClient, detect_image_mime_type};
+use super::{LlmAttachmentKind, LlmEmbeddingClient, LlmGenerationClient};
 use async_openai::{
     Client as OpenAIClient,
     config::{AzureConfig, OpenAIConfig},
@@ -24,8 +24,8 @@ use async_openai::{
         ChatCompletionRequestMessageContentPartText, ChatCompletionRequestSystemMessage,
         ChatCompletionRequestSystemMessageContent, ChatCompletionRequestUserMessage,
         ChatCompletionRequestUserMessageContent, ChatCompletionRequestUserMessageContentPart,
-        CreateChatCompletionRequest, CreateEmbeddingRequest, EmbeddingInput, ImageDetail,
-        ResponseFormat, ResponseFormatJsonSchema,
+        CreateChatCompletionRequest, CreateChatCompletionResponse, CreateEmbeddingRequest,
+        EmbeddingInput, ImageDetail, ResponseFormat, ResponseFormatJsonSchema,
     },
 };
 use phf::phf_map;
@@ -129,9 +129,31 @@ impl Client<AzureConfig> {
     }
 }
 
+/// A part of the user message content. `async_openai` doesn't model `file` parts, which carry PDFs.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum UserMessageContentPart {
+    Part(ChatCompletionRequestUserMessageContentPart),
+    File(FileContentPart),
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "file")]
+struct FileContentPart {
+    file: FileContent,
+}
+
+#[derive(Serialize)]
+struct FileContent {
+    filename: String,
+    /// A `data:` URL with the base64 encoded content.
+    file_data: String,
+}
+
+/// Builds the chat completions request body.
 pub(super) fn create_llm_generation_request(
     request: &super::LlmGenerateRequest,
-) -> Result<CreateChatCompletionRequest> {
+) -> Result<serde_json::Value> {
     let mut messages = Vec::new();
 
     // Add system prompt if provided
@@ -144,36 +166,62 @@ pub(super) fn create_llm_generation_request(
         ));
     }
 
-    // Add user message
-    let user_message_content = match &request.image {
-        Some(img_bytes) => {
-            let base64_image = BASE64_STANDARD.encode(img_bytes.as_ref());
-            let mime_type = detect_image_mime_type(img_bytes.as_ref())?;
-            let image_url = format!("data:{mime_type};base64,{base64_image}");
-            ChatCompletionRequestUserMessageContent::Array(vec![
-                ChatCompletionRequestUserMessageContentPart::Text(
-                    ChatCompletionRequestMessageContentPartText {
-                        text: request.user_prompt.to_string(),
-                    },
-                ),
-                ChatCompletionRequestUserMessageContentPart::ImageUrl(
-                    ChatCompletionRequestMessageContentPartImage {
-                        image_url: async_openai::types::ImageUrl {
-                            url: image_url,
-                            detail: Some(ImageDetail::Auto),
-                        },
-                    },
-                ),
-            ])
-        }
-        None => ChatCompletionRequestUserMessageContent::Text(request.user_prompt.to_string()),
-    };
+    // Add user message. With attachments, its content is replaced by the parts below once serialized.
     messages.push(ChatCompletionRequestMessage::User(
         ChatCompletionRequestUserMessage {
-            content: user_message_content,
+            content: ChatCompletionRequestUserMessageContent::Text(request.user_prompt.to_string()),
             ..Default::default()
         },
     ));
+    let mut user_message_parts = Vec::new();
+    if !request.attachments.is_empty() {
+        user_message_parts.push(UserMessageContentPart::Part(
+            ChatCompletionRequestUserMessageContentPart::Text(
+                ChatCompletionRequestMessageContentPartText {
+                    text: request.user_prompt.to_string(),
+                },
+            ),
+        ));
+    }
+    for attachment in request.attachments.iter() {
+        let data_url = || {
+            format!(
+                "data:{};base64,{}",
+                attachment.mime_type,
+                BASE64_STANDARD.encode(attachment.data.as_ref())
+            )
+        };
+        let part = match attachment.kind {
+            LlmAttachmentKind::Image => {
+                UserMessageContentPart::Part(ChatCompletionRequestUserMessageContentPart::ImageUrl(
+                    ChatCompletionRequestMessageContentPartImage {
+                        image_url: async_openai::types::ImageUrl {
+                            url: data_url(),
+                            detail: Some(ImageDetail::Auto),
+                        },
+                    },
+                ))
+            }
+            LlmAttachmentKind::Text => {
+                UserMessageContentPart::Part(ChatCompletionRequestUserMessageContentPart::Text(
+                    ChatCompletionRequestMessageContentPartText {
+                        text: attachment.to_prompt_text()?,
+                    },
+                ))
+            }
+            LlmAttachmentKind::Pdf => UserMessageContentPart::File(FileContentPart {
+                file: FileContent {
+                    filename: attachment
+                        .name
+                        .as_deref()
+                        .unwrap_or("attachment.pdf")
+                        .to_string(),
+                    file_data: data_url(),
+                },
+            }),
+        };
+        user_message_parts.push(part);
+    }
     // Create the chat completion request
     let request = CreateChatCompletionRequest {
         model: request.model.to_string(),
@@ -194,7 +242,15 @@ pub(super) fn create_llm_generation_request(
         ..Default::default()
     };
 
-    Ok(request)
+    let mut body = serde_json::to_value(request)?;
+    if !user_message_parts.is_empty()
+        && let Some(user_message) = body["messages"]
+            .as_array_mut()
+            .and_then(|messages| messages.last_mut())
+    {
+        user_message["content"] = serde_json::to_value(user_message_parts)?;
+    }
+    Ok(body)
 }
 
 #[async_trait]
@@ -211,7 +267,8 @@ where
         let response = retryable::run(
             || async {
                 let req = create_llm_generation_request(request)?;
-                let response = self.client.chat().create(req).await?;
+                let response: CreateChatCompletionResponse =
+                    self.client.chat().create_byot(req).await?;
                 retryable::Ok(response)
             },
             &retryable::RetryOptions::default(),
@@ -283,3 +340,62 @@ where
         DEFAULT_EMBEDDING_DIMENSIONS.get(model).copied()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::LlmAttachment;
+
+    #[test]
+    fn test_generation_request_attachments() {
+        let pdf = b"%PDF-1.4\n".as_slice();
+        let image = b"\x89PNG\r\n\x1a\n".as_slice();
+        let request = super::super::LlmGenerateRequest {
+            model: "gpt-4o",
+            system_prompt: Some("Be brief.".into()),
+            user_prompt: "Summarize.".into(),
+            attachments: vec![
+                LlmAttachment::new(pdf.into(), None, Some("report.pdf".into())).unwrap(),
+                LlmAttachment::new(image.into(), Some("image/png".into()), None).unwrap(),
+                LlmAttachment::new(b"notes".as_slice().into(), Some("text/plain".into()), None)
+                    .unwrap(),
+            ],
+            output_format: None,
+        };
+        let body = create_llm_generation_request(&request).unwrap();
+        assert_eq!(body["model"], "gpt-4o");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["role"], "user");
+        assert_eq!(
+            body["messages"][1]["content"],
+            serde_json::json!([
+                { "type": "text", "text": "Summarize." },
+                {
+                    "type": "file",
+                    "file": {
+                        "filename": "report.pdf",
+                        "file_data": format!(
+                            "data:application/pdf;base64,{}",
+                            BASE64_STANDARD.encode(pdf)
+                        ),
+                    },
+                },
+                {
+                    "type": "image_url",
+                    "image_url": {
+                        "url": format!("data:image/png;base64,{}", BASE64_STANDARD.encode(image)),
+                        "detail": "auto",
+                    },
+                },
+                { "type": "text", "text": request.attachments[2].to_prompt_text().unwrap() },
+            ])
+        );
+
+        let request = super::super::LlmGenerateRequest {
+            attachments: vec![],
+            ..request
+        };
+        let body = create_llm_generation_request(&request).unwrap();
+        assert_eq!(body["messages"][1]["content"], "Summarize.");
+    }
+}
@@ -12,7 +12,11 @@
 
 use crate::prelude::*;
 
-use crate::llm::{LlmEmbeddingClient, LlmEmbeddingRequest, LlmEmbeddingResponse};
+use crate::llm::{
+    LlmEmbeddingClient, LlmEmbeddingContent, LlmEmbeddingRequest, LlmEmbeddingResponse,
+    LlmMultimodalEmbeddingRequest, detect_image_mime_type,
+};
+use base64::prelude::*;
 use phf::phf_map;
 
 static DEFAULT_EMBEDDING_DIMENSIONS: phf::Map<&str, u32> = phf_map! {
@@ -24,6 +28,7 @@ static DEFAULT_EMBEDDING_DIMENSIONS: phf::Map<&str, u32> = phf_map! {
     "voyage-finance-2" => 1024,
     "voyage-law-2" => 1024,
     "voyage-code-2" => 1536,
+    "voyage-multimodal-3" => 1024,
 
     // Legacy models
     "voyage-3" => 1024,
@@ -40,15 +45,14 @@ static DEFAULT_EMBEDDING_DIMENSIONS: phf::Map<&str, u32> = phf_map! {
 };
 
 pub struct Client {
+    address: String,
     api_key: String,
     client: reqwest::Client,
 }
 
 impl Client {
     pub fn new(address: Option<String>, api_key: Option<String>) -> Result<Self> {
-        if address.is_some() {
-            api_bail!("Voyage AI doesn't support custom API address");
-        }
+        let address = address.unwrap_or_else(|| "https://api.voyageai.com/v1".to_string());
 
         let api_key = if let Some(key) = api_key {
             key
@@ -58,6 +62,7 @@ impl Client {
         };
 
         Ok(Self {
+            address,
             api_key,
             client: reqwest::Client::new(),
         })
@@ -67,6 +72,8 @@ impl Client {
 #[derive(Deserialize)]
 struct EmbeddingData {
     embedding: Vec<f32>,
+    #[serde(default)]
+    index: usize,
 }
 
 #[derive(Deserialize)]
@@ -74,13 +81,57 @@ struct EmbedResponse {
     data: Vec<EmbeddingData>,
 }
 
+impl EmbedResponse {
+    /// Embeddings in the order of inputs.
+    fn into_embeddings(mut self) -> Vec<Vec<f32>> {
+        self.data.sort_by_key(|d| d.index);
+        self.data.into_iter().map(|d| d.embedding).collect()
+    }
+}
+
+/// Payload of a request to the multimodal embeddings endpoint.
+fn multimodal_payload(request: &LlmMultimodalEmbeddingRequest<'_>) -> Result<serde_json::Value> {
+    let inputs = request
+        .inputs
+        .iter()
+        .map(|parts| {
+            let content = parts
+                .iter()
+                .map(|part| match part {
+                    LlmEmbeddingContent::Text(text) => Ok(serde_json::json!({
+                        "type": "text",
+                        "text": text,
+                    })),
+                    LlmEmbeddingContent::Image(bytes) => {
+                        let mime_type = detect_image_mime_type(bytes.as_ref())?;
+                        let base64_image = BASE64_STANDARD.encode(bytes.as_ref());
+                        Ok(serde_json::json!({
+                            "type": "image_base64",
+                            "image_base64": format!("data:{mime_type};base64,{base64_image}"),
+                        }))
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(serde_json::json!({ "content": content }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut payload = serde_json::json!({
+        "inputs": inputs,
+        "model": request.model,
+    });
+    if let Some(task_type) = &request.task_type {
+        payload["input_type"] = serde_json::Value::String(task_type.to_string());
+    }
+    Ok(payload)
+}
+
 #[async_trait]
 impl LlmEmbeddingClient for Client {
     async fn embed_text<'req>(
         &self,
         request: LlmEmbeddingRequest<'req>,
     ) -> Result<LlmEmbeddingResponse> {
-        let url = "https://api.voyageai.com/v1/embeddings";
+        let url = format!("{}/embeddings", self.address);
 
         let texts: Vec<String> = request.texts.iter().map(|t| t.to_string()).collect();
         let mut payload = serde_json::json!({
@@ -94,7 +145,34 @@ impl LlmEmbeddingClient for Client {
 
         let resp = http::request(|| {
             self.client
-                .post(url)
+                .post(&url)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&payload)
+        })
+        .await
+        .with_context(|| "Voyage AI API error")?;
+
+        let embedding_resp: EmbedResponse = resp
+            .json()
+            .await
+            .map_err(Error::internal)
+            .context("Invalid JSON")?;
+
+        Ok(LlmEmbeddingResponse {
+            embeddings: embedding_resp.into_embeddings(),
+        })
+    }
+
+    async fn embed_multimodal<'req>(
+        &self,
+        request: LlmMultimodalEmbeddingRequest<'req>,
+    ) -> Result<LlmEmbeddingResponse> {
+        let url = format!("{}/multimodalembeddings", self.address);
+        let payload = multimodal_payload(&request)?;
+
+        let resp = http::request(|| {
+            self.client
+                .post(&url)
                 .header("Authorization", format!("Bearer {}", self.api_key))
                 .json(&payload)
         })
@@ -108,11 +186,7 @@ impl LlmEmbeddingClient for Client {
             .context("Invalid JSON")?;
 
         Ok(LlmEmbeddingResponse {
-            embeddings: embedding_resp
-                .data
-                .into_iter()
-                .map(|d| d.embedding)
-                .collect(),
+            embeddings: embedding_resp.into_embeddings(),
         })
     }
 
@@ -120,3 +194,70 @@ impl LlmEmbeddingClient for Client {
         DEFAULT_EMBEDDING_DIMENSIONS.get(model).copied()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn test_multimodal_payload() {
+        let request = LlmMultimodalEmbeddingRequest {
+            model: "voyage-multimodal-3",
+            inputs: vec![
+                vec![
+                    LlmEmbeddingContent::Text("a diagram".into()),
+                    LlmEmbeddingContent::Image(PNG_HEADER.into()),
+                ],
+                vec![LlmEmbeddingContent::Text("plain".into())],
+            ],
+            output_dimension: None,
+            task_type: Some("document".into()),
+        };
+        let image = format!(
+            "data:image/png;base64,{}",
+            BASE64_STANDARD.encode(PNG_HEADER)
+        );
+        assert_eq!(
+            multimodal_payload(&request).unwrap(),
+            serde_json::json!({
+                "inputs": [
+                    { "content": [
+                        { "type": "text", "text": "a diagram" },
+                        { "type": "image_base64", "image_base64": image },
+                    ] },
+                    { "content": [{ "type": "text", "text": "plain" }] },
+                ],
+                "model": "voyage-multimodal-3",
+                "input_type": "document",
+            })
+        );
+
+        let request = LlmMultimodalEmbeddingRequest {
+            model: "voyage-multimodal-3",
+            inputs: vec![vec![LlmEmbeddingContent::Image(b"not an image"[..].into())]],
+            output_dimension: None,
+            task_type: None,
+        };
+        assert!(multimodal_payload(&request).is_err());
+    }
+
+    #[test]
+    fn test_embed_response() {
+        let response: EmbedResponse = serde_json::from_value(serde_json::json!({
+            "object": "list",
+            "data": [
+                { "object": "embedding", "embedding": [0.5, 0.25], "index": 1 },
+                { "object": "embedding", "embedding": [1.0, 0.0], "index": 0 },
+            ],
+            "model": "voyage-multimodal-3",
+            "usage": { "text_tokens": 5, "image_pixels": 2000000, "total_tokens": 3576 },
+        }))
+        .unwrap();
+        assert_eq!(
+            response.into_embeddings(),
+            vec![vec![1.0, 0.0], vec![0.5, 0.25]]
+        );
+    }
+}
@@ -35,21 +35,35 @@ pub struct OpArgResolver<'arg> {
 
 impl<'arg> OpArgResolver<'arg> {
     pub fn expect_nullable_type(self, expected_type: &ValueType) -> Result<Self> {
+        self.expect_nullable_type_in(std::slice::from_ref(expected_type))
+    }
+    pub fn expect_type(self, expected_type: &ValueType) -> Result<Self> {
+        self.expect_type_in(std::slice::from_ref(expected_type))
+    }
+
+    /// Like [`Self::expect_nullable_type`], but accepts any of `expected_types`.
+    pub fn expect_nullable_type_in(self, expected_types: &[ValueType]) -> Result<Self> {
         let Some((_, typ)) = &self.resolved_op_arg else {
             return Ok(self);
         };
-        if &typ.typ != expected_type {
+        if !expected_types.contains(&typ.typ) {
             api_bail!(
-                "Expected argument `{}` to be of type `{}`, got `{}`",
+                "Expected argument `{}` to be of type {}, got `{}`",
                 self.name,
-                expected_type,
+                expected_types
+                    .iter()
+                    .map(|t| format!("`{t}`"))
+                    .collect::<Vec<_>>()
+                    .join(" or "),
                 typ.typ
             );
         }
         Ok(self)
     }
-    pub fn expect_type(self, expected_type: &ValueType) -> Result<Self> {
-        let resolver = self.expect_nullable_type(expected_type)?;
+
+    /// Like [`Self::expect_type`], but accepts any of `expected_types`.
+    pub fn expect_type_in(self, expected_types: &[ValueType]) -> Result<Self> {
+        let resolver = self.expect_nullable_type_in(expected_types)?;
         if let Some((idx, typ)) = resolver.resolved_op_arg.as_ref() {
             resolver.nonnull_args_idx.push(*idx);
             if typ.nullable {
@@ -243,6 +257,29 @@ pub trait SourceFactoryBase: SourceFactory + Send + Sync + 'static {
         context: Arc<FlowInstanceContext>,
     ) -> Result<Box<dyn SourceExecutor>>;
 
+    /// Whether the source with the spec persists its own state through a [`SourceStateStore`].
+    fn uses_state_store(&self, _spec: &Self::Spec) -> bool {
+        false
+    }
+
+    /// See [`SourceFactory::setup_resources`].
+    fn setup_resources(
+        &self,
+        _source_name: &str,
+        _spec: &Self::Spec,
+        _context: &FlowInstanceContext,
+    ) -> Result<Vec<serde_json::Value>> {
+        Ok(vec![])
+    }
+
+    async fn drop_resource(
+        &self,
+        _resource: serde_json::Value,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     fn register(self, registry: &mut ExecutorFactoryRegistry) -> Result<()>
     where
         Self: Sized,
@@ -273,6 +310,32 @@ impl<T: SourceFactoryBase> SourceFactory for T {
         let executor = async move { self.build_executor(&source_name, spec, context).await };
         Ok((output_schema, Box::pin(executor)))
     }
+
+    fn uses_state_store(&self, spec: &serde_json::Value) -> bool {
+        // Invalid specs are reported when the source is built.
+        utils::deser::from_json_value::<T::Spec>(spec.clone())
+            .is_ok_and(|spec| SourceFactoryBase::uses_state_store(self, &spec))
+    }
+
+    fn setup_resources(
+        &self,
+        source_name: &str,
+        spec: &serde_json::Value,
+        context: &FlowInstanceContext,
+    ) -> Result<Vec<serde_json::Value>> {
+        let spec: T::Spec = utils::deser::from_json_value(spec.clone())
+            .map_err(Error::from)
+            .with_context(|| format!("Failed in parsing spec for source `{source_name}`"))?;
+        SourceFactoryBase::setup_resources(self, source_name, &spec, context)
+    }
+
+    async fn drop_resource(
+        &self,
+        resource: serde_json::Value,
+        context: Arc<FlowInstanceContext>,
+    ) -> Result<()> {
+        SourceFactoryBase::drop_resource(self, resource, context).await
+    }
 }
 
 ////////////////////////////////////////////////////////
@@ -306,6 +369,12 @@ pub trait SimpleFunctionFactoryBase: SimpleFunctionFactory + Send + Sync + 'stat
         context: Arc<FlowInstanceContext>,
     ) -> Result<impl SimpleFunctionExecutor>;
 
+    /// Whether the function with the spec persists its own state through
+    /// [`FlowInstanceContext::function_state_store`].
+    fn uses_state_store(&self, _spec: &Self::Spec) -> bool {
+        false
+    }
+
     fn register(self, registry: &mut ExecutorFactoryRegistry) -> Result<()>
     where
         Self: Sized,
@@ -380,6 +449,11 @@ impl<T: SimpleFunctionFactoryBase> SimpleFunctionFactory for T {
             executor: Box::pin(executor),
         })
     }
+    fn uses_state_store(&self, spec: &serde_json::Value) -> bool {
+        // Invalid specs are reported when the function is built.
+        utils::deser::from_json_value::<T::Spec>(spec.clone())
+            .is_ok_and(|spec| SimpleFunctionFactoryBase::uses_state_store(self, &spec))
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,162 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+use crate::prelude::*;
+
+use crate::ops::registry::ExecutorFactoryRegistry;
+use utils::similarity::{DEFAULT_NUM_PERMUTATIONS, MinHasher};
+const DEFAULT_SHINGLE_SIZE: usize = 3;
+
+/// Computes a MinHash signature of the text's word shingles, for estimating Jaccard similarity
+/// between texts. The signature is encoded as 4 little-endian bytes per permutation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spec {
+    /// Number of hash permutations, i.e. the signature length. Defaults to 128.
+    num_permutations: Option<usize>,
+    /// Number of words in each shingle. Defaults to 3.
+    shingle_size: Option<usize>,
+    /// Seed of the hash permutations. Only signatures with the same seed are comparable.
+    seed: Option<u64>,
+}
+
+struct Args {
+    text: ResolvedOpArg,
+}
+
+struct Executor {
+    args: Args,
+    hasher: MinHasher,
+    shingle_size: usize,
+}
+
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let text = self.args.text.value(&input)?.as_str()?;
+        let signature = self.hasher.text_signature(text, self.shingle_size);
+        Ok(BasicValue::from(signature.to_bytes()).into())
+    }
+
+    fn enable_cache(&self) -> bool {
+        true
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = Spec;
+    type ResolvedArgs = Args;
+
+    fn name(&self) -> &str {
+        "ComputeMinHash"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        spec: &'a Spec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        _context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
+        if spec.num_permutations == Some(0) {
+            api_bail!("`num_permutations` must be positive");
+        }
+        if spec.shingle_size == Some(0) {
+            api_bail!("`shingle_size` must be positive");
+        }
+        let args = Args {
+            text: args_resolver
+                .next_arg("text")?
+                .expect_type(&ValueType::Basic(BasicValueType::Str))?
+                .required()?,
+        };
+        Ok(SimpleFunctionAnalysisOutput {
+            resolved_args: args,
+            output_schema: make_output_type(BasicValueType::Bytes),
+            behavior_version: None,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        spec: Spec,
+        args: Args,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        Ok(Executor {
+            args,
+            hasher: MinHasher::new(
+                spec.num_permutations.unwrap_or(DEFAULT_NUM_PERMUTATIONS),
+                spec.seed.unwrap_or_default(),
+            ),
+            shingle_size: spec.shingle_size.unwrap_or(DEFAULT_SHINGLE_SIZE),
+        })
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::functions::test_utils::test_flow_function;
+    use utils::similarity::MinHashSignature;
+
+    async fn compute(spec: Spec, text: &str) -> MinHashSignature {
+        let input_arg_schemas = &[(Some("text"), make_output_type(BasicValueType::Str))];
+        let result = test_flow_function(
+            &Arc::new(Factory),
+            &spec,
+            input_arg_schemas,
+            vec![text.to_string().into()],
+        )
+        .await
+        .unwrap();
+        match result {
+            Value::Basic(BasicValue::Bytes(bytes)) => MinHashSignature::from_bytes(&bytes).unwrap(),
+            other => panic!("Expected bytes, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compute_minhash() {
+        let spec = Spec {
+            num_permutations: Some(64),
+            shingle_size: Some(2),
+            seed: None,
+        };
+        let text = "Licensed under the Apache License, Version 2.0. You may not use this file \
+                    except in compliance with the License.";
+        let a = compute(spec.clone(), text).await;
+        assert_eq!(a.0.len(), 64);
+
+        let b = compute(spec.clone(), &text.replace("2.0", "2.1")).await;
+        assert!(a.jaccard(&b) > 0.6, "{}", a.jaccard(&b));
+
+        let c = compute(spec.clone(), "Chunking splits documents into pieces.").await;
+        assert!(a.jaccard(&c) < 0.2, "{}", a.jaccard(&c));
+
+        let reseeded = compute(
+            Spec {
+                seed: Some(7),
+                ..spec
+            },
+            text,
+        )
+        .await;
+        assert_ne!(a, reseeded);
+    }
+}
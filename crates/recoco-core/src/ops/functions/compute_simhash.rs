@@ -0,0 +1,132 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+use crate::prelude::*;
+
+use crate::ops::registry::ExecutorFactoryRegistry;
+use utils::similarity::SimHash;
+
+const DEFAULT_SHINGLE_SIZE: usize = 2;
+
+/// Computes a 64-bit SimHash of the text's word shingles. Near-duplicate texts differ in few
+/// bits. The hash is returned as an `Int64` with the same bits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spec {
+    /// Number of words in each shingle. Defaults to 2.
+    shingle_size: Option<usize>,
+}
+
+struct Args {
+    text: ResolvedOpArg,
+}
+
+struct Executor {
+    args: Args,
+    shingle_size: usize,
+}
+
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let text = self.args.text.value(&input)?.as_str()?;
+        let hash = SimHash::from_text(text, self.shingle_size);
+        Ok(BasicValue::Int64(hash.0 as i64).into())
+    }
+
+    fn enable_cache(&self) -> bool {
+        true
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = Spec;
+    type ResolvedArgs = Args;
+
+    fn name(&self) -> &str {
+        "ComputeSimHash"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        spec: &'a Spec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        _context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
+        if spec.shingle_size == Some(0) {
+            api_bail!("`shingle_size` must be positive");
+        }
+        let args = Args {
+            text: args_resolver
+                .next_arg("text")?
+                .expect_type(&ValueType::Basic(BasicValueType::Str))?
+                .required()?,
+        };
+        Ok(SimpleFunctionAnalysisOutput {
+            resolved_args: args,
+            output_schema: make_output_type(BasicValueType::Int64),
+            behavior_version: None,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        spec: Spec,
+        args: Args,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        Ok(Executor {
+            args,
+            shingle_size: spec.shingle_size.unwrap_or(DEFAULT_SHINGLE_SIZE),
+        })
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::functions::test_utils::test_flow_function;
+
+    async fn compute(text: &str) -> SimHash {
+        let input_arg_schemas = &[(Some("text"), make_output_type(BasicValueType::Str))];
+        let result = test_flow_function(
+            &Arc::new(Factory),
+            &Spec { shingle_size: None },
+            input_arg_schemas,
+            vec![text.to_string().into()],
+        )
+        .await
+        .unwrap();
+        match result {
+            Value::Basic(BasicValue::Int64(v)) => SimHash(v as u64),
+            other => panic!("Expected int64, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compute_simhash() {
+        let text = "Recoco keeps derived data in sync with its sources, reprocessing only the \
+                    rows whose inputs changed since the previous run.";
+        let a = compute(text).await;
+        assert_eq!(a, compute(text).await);
+        let near = compute(&text.replace("previous", "last")).await;
+        let far = compute("An unrelated sentence about cooking pasta at home.").await;
+        assert!(a.hamming_distance(&near) < a.hamming_distance(&far));
+    }
+}
@@ -0,0 +1,1531 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use quick_xml::events::{BytesStart, Event};
+use scraper::{ElementRef, Html, Node};
+
+use crate::{fields_value, ops::sdk::*};
+
+/// Upper bound on the decompressed size of a single DOCX, PPTX or EPUB archive member.
+const MAX_ARCHIVE_MEMBER_SIZE: u64 = 256 * 1024 * 1024;
+
+/// HTML elements whose content is never converted.
+const HTML_SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "iframe", "noscript", "object", "script", "style", "svg", "template",
+];
+
+/// HTML elements that start a new block. Everything else is converted as inline text.
+const HTML_BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "center",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DocumentFormat {
+    Pdf,
+    Docx,
+    Pptx,
+    Html,
+    Epub,
+    Markdown,
+    Text,
+}
+
+impl DocumentFormat {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DocumentFormat::Pdf => "pdf",
+            DocumentFormat::Docx => "docx",
+            DocumentFormat::Pptx => "pptx",
+            DocumentFormat::Html => "html",
+            DocumentFormat::Epub => "epub",
+            DocumentFormat::Markdown => "markdown",
+            DocumentFormat::Text => "text",
+        }
+    }
+
+    fn from_mime_type(mime_type: &str) -> Option<Self> {
+        let essence = mime_type.split(';').next()?.trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "application/pdf" => Some(DocumentFormat::Pdf),
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+                Some(DocumentFormat::Docx)
+            }
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation" => {
+                Some(DocumentFormat::Pptx)
+            }
+            "text/html" | "application/xhtml+xml" => Some(DocumentFormat::Html),
+            "application/epub+zip" => Some(DocumentFormat::Epub),
+            "text/markdown" | "text/x-markdown" => Some(DocumentFormat::Markdown),
+            "text/plain" => Some(DocumentFormat::Text),
+            _ => None,
+        }
+    }
+
+    fn from_filename(filename: &str) -> Option<Self> {
+        let extension = Path::new(filename).extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "pdf" => Some(DocumentFormat::Pdf),
+            "docx" => Some(DocumentFormat::Docx),
+            "pptx" => Some(DocumentFormat::Pptx),
+            "htm" | "html" | "xhtml" => Some(DocumentFormat::Html),
+            "epub" => Some(DocumentFormat::Epub),
+            "markdown" | "md" => Some(DocumentFormat::Markdown),
+            "txt" => Some(DocumentFormat::Text),
+            _ => None,
+        }
+    }
+
+    /// Detects the format from the content's magic bytes, and for ZIP-based formats,
+    /// from the archive's members.
+    fn sniff(content: &[u8]) -> Option<Self> {
+        if content.starts_with(b"%PDF-") {
+            return Some(DocumentFormat::Pdf);
+        }
+        if content.starts_with(b"PK\x03\x04") {
+            let archive = zip::ZipArchive::new(Cursor::new(content)).ok()?;
+            return if archive.index_for_name("word/document.xml").is_some() {
+                Some(DocumentFormat::Docx)
+            } else if archive.index_for_name("ppt/presentation.xml").is_some() {
+                Some(DocumentFormat::Pptx)
+            } else if archive.index_for_name("META-INF/container.xml").is_some() {
+                Some(DocumentFormat::Epub)
+            } else {
+                None
+            };
+        }
+        let head = String::from_utf8_lossy(&content[..content.len().min(1024)]);
+        let head = head.trim_start().to_ascii_lowercase();
+        if head.starts_with("<!doctype html") || head.starts_with("<html") {
+            return Some(DocumentFormat::Html);
+        }
+        None
+    }
+
+    /// Declared metadata wins over sniffing. Undetected UTF-8 content is treated as plain text.
+    fn detect(content: &[u8], mime_type: Option<&str>, filename: Option<&str>) -> Result<Self> {
+        mime_type
+            .and_then(Self::from_mime_type)
+            .or_else(|| filename.and_then(Self::from_filename))
+            .or_else(|| Self::sniff(content))
+            .or_else(|| {
+                std::str::from_utf8(content)
+                    .is_ok()
+                    .then_some(DocumentFormat::Text)
+            })
+            .ok_or_else(|| client_error!("unable to detect the document format"))
+    }
+}
+
+/// A part of the converted document: a page, slide, chapter, or the span under a heading.
+struct Section {
+    kind: &'static str,
+    title: Option<String>,
+    /// Byte range in the Markdown output.
+    start: usize,
+    end: usize,
+    has_content: bool,
+}
+
+/// Accumulates Markdown blocks and the sections they belong to.
+struct MarkdownWriter {
+    out: String,
+    sections: Vec<Section>,
+    /// Whether each heading starts a new section, for formats without pages or chapters.
+    sections_by_heading: bool,
+    last_block_is_list_item: bool,
+}
+
+impl MarkdownWriter {
+    fn new(sections_by_heading: bool) -> Self {
+        Self {
+            out: String::new(),
+            sections: Vec::new(),
+            sections_by_heading,
+            last_block_is_list_item: false,
+        }
+    }
+
+    fn begin_section(&mut self, kind: &'static str, title: Option<String>) {
+        self.sections.push(Section {
+            kind,
+            title,
+            start: self.out.len(),
+            end: self.out.len(),
+            has_content: false,
+        });
+    }
+
+    fn write_block(&mut self, block: &str, is_list_item: bool) {
+        if !self.out.is_empty() {
+            self.out
+                .push_str(if is_list_item && self.last_block_is_list_item {
+                    "\n"
+                } else {
+                    "\n\n"
+                });
+        }
+        let start = self.out.len();
+        self.out.push_str(block);
+        self.last_block_is_list_item = is_list_item;
+        if let Some(section) = self.sections.last_mut() {
+            if !section.has_content {
+                section.start = start;
+                section.has_content = true;
+            }
+            section.end = self.out.len();
+        }
+    }
+
+    fn heading(&mut self, level: usize, text: &str) {
+        let text = collapse_whitespace(text);
+        if text.is_empty() {
+            return;
+        }
+        if self.sections_by_heading {
+            self.begin_section("section", Some(text.clone()));
+        } else if let Some(section) = self.sections.last_mut()
+            && section.title.is_none()
+        {
+            section.title = Some(text.clone());
+        }
+        self.write_block(
+            &format!("{} {}", "#".repeat(level.clamp(1, 6)), text),
+            false,
+        );
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        let text = text.trim();
+        if !text.is_empty() {
+            self.write_block(text, false);
+        }
+    }
+
+    /// Writes a list item, numbered if `number` is set.
+    fn list_item(&mut self, depth: usize, number: Option<usize>, text: &str) {
+        let text = collapse_whitespace(text);
+        if text.is_empty() {
+            return;
+        }
+        let marker = number.map_or_else(|| "-".to_string(), |n| format!("{n}."));
+        self.write_block(&format!("{}{} {}", "  ".repeat(depth), marker, text), true);
+    }
+
+    /// Writes a table whose first row is the header.
+    fn table(&mut self, rows: &[Vec<String>]) {
+        let num_columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if num_columns == 0 {
+            return;
+        }
+        let format_row = |row: &[String]| {
+            let cells = (0..num_columns).map(|i| {
+                row.get(i)
+                    .map(|cell| collapse_whitespace(cell).replace('|', "\\|"))
+                    .unwrap_or_default()
+            });
+            format!("| {} |", cells.collect::<Vec<_>>().join(" | "))
+        };
+        let mut lines = vec![
+            format_row(&rows[0]),
+            format!("|{}", " --- |".repeat(num_columns)),
+        ];
+        lines.extend(rows[1..].iter().map(|row| format_row(row)));
+        self.write_block(&lines.join("\n"), false);
+    }
+
+    fn code_block(&mut self, code: &str) {
+        let code = code.trim_matches('\n');
+        if !code.trim().is_empty() {
+            self.write_block(&format!("```\n{code}\n```"), false);
+        }
+    }
+
+    /// Writes plain text, such as extracted from a PDF page. Lines are joined into
+    /// paragraphs at blank lines, and lines starting with a bullet become list items.
+    fn plain_text(&mut self, text: &str) {
+        let mut paragraph = String::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() {
+                self.paragraph(&std::mem::take(&mut paragraph));
+            } else if let Some(item) = ["•", "◦", "▪", "- ", "* "]
+                .iter()
+                .find_map(|bullet| line.strip_prefix(bullet))
+            {
+                self.paragraph(&std::mem::take(&mut paragraph));
+                self.list_item(0, None, item);
+            } else {
+                if !paragraph.is_empty() {
+                    paragraph.push(' ');
+                }
+                paragraph.push_str(line);
+            }
+        }
+        self.paragraph(&paragraph);
+    }
+
+    /// Returns the Markdown and the sections, with byte offsets converted to char offsets.
+    fn finish(self) -> (String, Vec<(Section, RangeValue)>) {
+        let mut char_offset = 0;
+        let mut byte_offset = 0;
+        let mut to_char_offset = |offset: usize| {
+            char_offset += self.out[byte_offset..offset].chars().count();
+            byte_offset = offset;
+            char_offset
+        };
+        let sections = self
+            .sections
+            .into_iter()
+            .map(|section| {
+                let range =
+                    RangeValue::new(to_char_offset(section.start), to_char_offset(section.end));
+                (section, range)
+            })
+            .collect();
+        (self.out, sections)
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn convert(content: &[u8], format: DocumentFormat) -> Result<MarkdownWriter> {
+    let mut writer = MarkdownWriter::new(matches!(
+        format,
+        DocumentFormat::Docx | DocumentFormat::Html
+    ));
+    match format {
+        DocumentFormat::Pdf => convert_pdf(content, &mut writer)?,
+        DocumentFormat::Docx => convert_docx(&mut open_archive(content)?, &mut writer)?,
+        DocumentFormat::Pptx => convert_pptx(&mut open_archive(content)?, &mut writer)?,
+        DocumentFormat::Epub => convert_epub(&mut open_archive(content)?, &mut writer)?,
+        DocumentFormat::Html => convert_html(&String::from_utf8_lossy(content), &mut writer),
+        DocumentFormat::Markdown | DocumentFormat::Text => {
+            writer.out = String::from_utf8_lossy(content).into_owned();
+        }
+    }
+    Ok(writer)
+}
+
+////////////////////////////////////////////////////////
+// PDF
+////////////////////////////////////////////////////////
+
+fn convert_pdf(content: &[u8], writer: &mut MarkdownWriter) -> Result<()> {
+    // The PDF parser panics on some malformed documents.
+    let pages = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(content))
+        .map_err(|_| client_error!("failed to parse PDF: malformed document"))?
+        .map_err(|e| client_error!("failed to parse PDF: {e}"))?;
+    for page in pages {
+        writer.begin_section("page", None);
+        writer.plain_text(&page);
+    }
+    Ok(())
+}
+
+////////////////////////////////////////////////////////
+// ZIP-based formats (DOCX, PPTX, EPUB)
+////////////////////////////////////////////////////////
+
+type Archive<'a> = zip::ZipArchive<Cursor<&'a [u8]>>;
+
+fn open_archive(content: &[u8]) -> Result<Archive<'_>> {
+    zip::ZipArchive::new(Cursor::new(content))
+        .map_err(|e| client_error!("failed to open document archive: {e}"))
+}
+
+fn read_member(archive: &mut Archive<'_>, name: &str) -> Result<String> {
+    let file = archive
+        .by_name(name)
+        .map_err(|e| client_error!("failed to read `{name}` from document: {e}"))?;
+    let mut content = String::new();
+    file.take(MAX_ARCHIVE_MEMBER_SIZE + 1)
+        .read_to_string(&mut content)
+        .map_err(|e| client_error!("failed to read `{name}` from document: {e}"))?;
+    if content.len() as u64 > MAX_ARCHIVE_MEMBER_SIZE {
+        client_bail!("`{name}` in document exceeds {MAX_ARCHIVE_MEMBER_SIZE} bytes");
+    }
+    Ok(content)
+}
+
+/// Resolves `href` relative to the archive directory `base_dir`.
+fn resolve_member_path(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let joined = match href.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None if base_dir.is_empty() => href.to_string(),
+        None => format!("{base_dir}/{href}"),
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in joined.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// Value of the attribute with the given local name, ignoring namespace prefixes.
+fn xml_attr(element: &BytesStart<'_>, local_name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == local_name)
+        .and_then(|attr| attr.unescape_value().ok().map(|v| v.into_owned()))
+}
+
+fn xml_reader(xml: &str) -> quick_xml::Reader<&[u8]> {
+    quick_xml::Reader::from_str(xml)
+}
+
+fn xml_error(e: quick_xml::Error) -> Error {
+    client_error!("malformed document XML: {e}")
+}
+
+/// Tables being built, innermost last. Nested tables are flattened into their cell.
+#[derive(Default)]
+struct TableStack {
+    tables: Vec<Vec<Vec<String>>>,
+    cell: Vec<Option<String>>,
+}
+
+impl TableStack {
+    fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    fn start_table(&mut self) {
+        self.tables.push(Vec::new());
+        self.cell.push(None);
+    }
+
+    fn start_row(&mut self) {
+        if let Some(table) = self.tables.last_mut() {
+            table.push(Vec::new());
+        }
+    }
+
+    fn start_cell(&mut self) {
+        if let Some(cell) = self.cell.last_mut() {
+            *cell = Some(String::new());
+        }
+    }
+
+    /// Appends a paragraph to the open cell.
+    fn push_text(&mut self, text: &str) {
+        if let Some(Some(cell)) = self.cell.last_mut() {
+            if !cell.is_empty() {
+                cell.push(' ');
+            }
+            cell.push_str(text);
+        }
+    }
+
+    fn end_cell(&mut self) {
+        if let (Some(table), Some(cell)) = (self.tables.last_mut(), self.cell.last_mut())
+            && let Some(text) = cell.take()
+        {
+            if table.is_empty() {
+                table.push(Vec::new());
+            }
+            table.last_mut().unwrap().push(text);
+        }
+    }
+
+    /// Closes the innermost table, writing it out unless it's nested.
+    fn end_table(&mut self, writer: &mut MarkdownWriter) {
+        let Some(rows) = self.tables.pop() else {
+            return;
+        };
+        self.cell.pop();
+        if self.tables.is_empty() {
+            writer.table(&rows);
+        } else {
+            let text = rows
+                .iter()
+                .map(|row| row.join(" "))
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.push_text(&text);
+        }
+    }
+}
+
+////////////////////////////////////////////////////////
+// DOCX
+////////////////////////////////////////////////////////
+
+/// List formats from `word/numbering.xml`: whether each list level is numbered.
+#[derive(Default)]
+struct DocxNumbering {
+    abstract_num_ids: HashMap<String, String>,
+    numbered_levels: HashMap<(String, usize), bool>,
+}
+
+impl DocxNumbering {
+    fn parse(xml: &str) -> Result<Self> {
+        let mut numbering = Self::default();
+        let mut reader = xml_reader(xml);
+        let mut abstract_num_id = None;
+        let mut level = 0;
+        let mut num_id = None;
+        loop {
+            match reader.read_event().map_err(xml_error)? {
+                Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                    b"abstractNum" => abstract_num_id = xml_attr(&e, b"abstractNumId"),
+                    b"lvl" => {
+                        level = xml_attr(&e, b"ilvl")
+                            .and_then(|v| v.parse().ok())
+                            .unwrap_or(0)
+                    }
+                    b"numFmt" => {
+                        if let (Some(id), Some(format)) = (&abstract_num_id, xml_attr(&e, b"val")) {
+                            numbering.numbered_levels.insert(
+                                (id.clone(), level),
+                                !matches!(format.as_str(), "bullet" | "none"),
+                            );
+                        }
+                    }
+                    b"num" => num_id = xml_attr(&e, b"numId"),
+                    b"abstractNumId" => {
+                        if let (Some(num_id), Some(id)) = (&num_id, xml_attr(&e, b"val")) {
+                            numbering.abstract_num_ids.insert(num_id.clone(), id);
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(numbering)
+    }
+
+    fn is_numbered(&self, num_id: &str, level: usize) -> bool {
+        self.abstract_num_ids
+            .get(num_id)
+            .and_then(|id| self.numbered_levels.get(&(id.clone(), level)))
+            .copied()
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Default)]
+struct DocxParagraph {
+    text: String,
+    heading_level: Option<usize>,
+    num_id: Option<String>,
+    list_level: usize,
+    list_style: bool,
+}
+
+fn docx_heading_level(style: &str) -> Option<usize> {
+    match style {
+        "Title" => Some(1),
+        "Subtitle" => Some(2),
+        _ => style
+            .strip_prefix("Heading")
+            .and_then(|level| level.parse().ok()),
+    }
+}
+
+fn convert_docx(archive: &mut Archive<'_>, writer: &mut MarkdownWriter) -> Result<()> {
+    let numbering = if archive.index_for_name("word/numbering.xml").is_some() {
+        DocxNumbering::parse(&read_member(archive, "word/numbering.xml")?)?
+    } else {
+        DocxNumbering::default()
+    };
+    let document = read_member(archive, "word/document.xml")?;
+    let mut reader = xml_reader(&document);
+    let mut paragraph: Option<DocxParagraph> = None;
+    let mut in_run = false;
+    let mut in_text = false;
+    let mut tables = TableStack::default();
+    let mut list_counters: HashMap<(String, usize), usize> = HashMap::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"p" => paragraph = Some(DocxParagraph::default()),
+                b"r" => in_run = true,
+                b"t" => in_text = true,
+                b"tbl" => tables.start_table(),
+                b"tr" => tables.start_row(),
+                b"tc" => tables.start_cell(),
+                _ => {}
+            },
+            Event::Empty(e) => match (e.local_name().as_ref(), paragraph.as_mut()) {
+                (b"pStyle", Some(p)) => {
+                    if let Some(style) = xml_attr(&e, b"val") {
+                        p.heading_level = docx_heading_level(&style);
+                        p.list_style = style.starts_with("List");
+                    }
+                }
+                (b"numId", Some(p)) => p.num_id = xml_attr(&e, b"val").filter(|id| id != "0"),
+                (b"ilvl", Some(p)) => {
+                    p.list_level = xml_attr(&e, b"val")
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(0)
+                }
+                (b"tab" | b"br" | b"cr", Some(p)) if in_run => p.text.push(' '),
+                _ => {}
+            },
+            Event::Text(text) if in_text => {
+                if let Some(p) = paragraph.as_mut() {
+                    p.text.push_str(&text.unescape().map_err(xml_error)?);
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"r" => in_run = false,
+                b"p" => {
+                    let Some(p) = paragraph.take() else {
+                        continue;
+                    };
+                    let text = collapse_whitespace(&p.text);
+                    if text.is_empty() {
+                        continue;
+                    }
+                    if !tables.is_empty() {
+                        tables.push_text(&text);
+                    } else if let Some(level) = p.heading_level {
+                        writer.heading(level, &text);
+                    } else if let Some(num_id) = p.num_id {
+                        let level = p.list_level;
+                        list_counters.retain(|(id, l), _| id != &num_id || *l <= level);
+                        let counter = list_counters.entry((num_id.clone(), level)).or_default();
+                        *counter += 1;
+                        let number = numbering.is_numbered(&num_id, level).then_some(*counter);
+                        writer.list_item(level, number, &text);
+                    } else if p.list_style {
+                        writer.list_item(0, None, &text);
+                    } else {
+                        writer.paragraph(&text);
+                    }
+                }
+                b"tc" => tables.end_cell(),
+                b"tbl" => tables.end_table(writer),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+////////////////////////////////////////////////////////
+// PPTX
+////////////////////////////////////////////////////////
+
+/// Maps relationship IDs to their targets in a `.rels` part.
+fn parse_relationships(xml: &str) -> Result<HashMap<String, String>> {
+    let mut reader = xml_reader(xml);
+    let mut relationships = HashMap::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                if let (Some(id), Some(target)) = (xml_attr(&e, b"Id"), xml_attr(&e, b"Target")) {
+                    relationships.insert(id, target);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(relationships)
+}
+
+fn convert_pptx(archive: &mut Archive<'_>, writer: &mut MarkdownWriter) -> Result<()> {
+    let relationships =
+        parse_relationships(&read_member(archive, "ppt/_rels/presentation.xml.rels")?)?;
+    let presentation = read_member(archive, "ppt/presentation.xml")?;
+    let mut reader = xml_reader(&presentation);
+    let mut slide_paths = Vec::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sldId" => {
+                // The relationship ID is the namespaced `r:id`, unlike the plain numeric `id`.
+                let rel_id = e
+                    .attributes()
+                    .flatten()
+                    .find(|attr| {
+                        attr.key.prefix().is_some() && attr.key.local_name().as_ref() == b"id"
+                    })
+                    .and_then(|attr| attr.unescape_value().ok().map(|v| v.into_owned()));
+                if let Some(target) = rel_id.and_then(|id| relationships.get(&id)) {
+                    slide_paths.push(resolve_member_path("ppt", target));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    for path in slide_paths {
+        writer.begin_section("slide", None);
+        convert_slide(&read_member(archive, &path)?, writer)?;
+    }
+    Ok(())
+}
+
+/// The placeholder role of the shape being converted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ShapeRole {
+    Text,
+    Title,
+    Body,
+    /// Dates, footers and slide numbers, which are left out.
+    Skipped,
+}
+
+fn convert_slide(xml: &str, writer: &mut MarkdownWriter) -> Result<()> {
+    let mut reader = xml_reader(xml);
+    let mut role = ShapeRole::Text;
+    let mut paragraph: Option<(String, usize)> = None;
+    let mut in_text = false;
+    let mut tables = TableStack::default();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"sp" => role = ShapeRole::Text,
+                b"p" => paragraph = Some((String::new(), 0)),
+                b"pPr" => set_pptx_level(&e, &mut paragraph),
+                b"t" => in_text = true,
+                b"tbl" => tables.start_table(),
+                b"tr" => tables.start_row(),
+                b"tc" => tables.start_cell(),
+                _ => {}
+            },
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"ph" => {
+                    role = match xml_attr(&e, b"type").as_deref() {
+                        Some("title" | "ctrTitle") => ShapeRole::Title,
+                        Some("dt" | "ftr" | "sldNum") => ShapeRole::Skipped,
+                        Some("body") | None => ShapeRole::Body,
+                        Some(_) => ShapeRole::Text,
+                    }
+                }
+                b"pPr" => set_pptx_level(&e, &mut paragraph),
+                b"br" => {
+                    if let Some((text, _)) = paragraph.as_mut() {
+                        text.push(' ');
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(text) if in_text => {
+                if let Some((paragraph_text, _)) = paragraph.as_mut() {
+                    paragraph_text.push_str(&text.unescape().map_err(xml_error)?);
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => {
+                    let Some((text, level)) = paragraph.take() else {
+                        continue;
+                    };
+                    let text = collapse_whitespace(&text);
+                    if text.is_empty() {
+                        continue;
+                    }
+                    if !tables.is_empty() {
+                        tables.push_text(&text);
+                        continue;
+                    }
+                    match role {
+                        ShapeRole::Title => writer.heading(1, &text),
+                        ShapeRole::Body => writer.list_item(level, None, &text),
+                        ShapeRole::Text => writer.paragraph(&text),
+                        ShapeRole::Skipped => {}
+                    }
+                }
+                b"tc" => tables.end_cell(),
+                b"tbl" => tables.end_table(writer),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn set_pptx_level(element: &BytesStart<'_>, paragraph: &mut Option<(String, usize)>) {
+    if let Some((_, level)) = paragraph.as_mut() {
+        *level = xml_attr(element, b"lvl")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+    }
+}
+
+////////////////////////////////////////////////////////
+// EPUB
+////////////////////////////////////////////////////////
+
+fn convert_epub(archive: &mut Archive<'_>, writer: &mut MarkdownWriter) -> Result<()> {
+    let container = read_member(archive, "META-INF/container.xml")?;
+    let mut reader = xml_reader(&container);
+    let mut package_path = None;
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"rootfile" => {
+                package_path = xml_attr(&e, b"full-path");
+                break;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let Some(package_path) = package_path else {
+        client_bail!("invalid EPUB: no package document in META-INF/container.xml");
+    };
+
+    let package = read_member(archive, &package_path)?;
+    let mut reader = xml_reader(&package);
+    let mut manifest: HashMap<String, (String, String)> = HashMap::new();
+    let mut spine = Vec::new();
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"item" => {
+                    if let (Some(id), Some(href)) = (xml_attr(&e, b"id"), xml_attr(&e, b"href")) {
+                        let media_type = xml_attr(&e, b"media-type").unwrap_or_default();
+                        manifest.insert(id, (href, media_type));
+                    }
+                }
+                b"itemref" => spine.extend(xml_attr(&e, b"idref")),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let base_dir = parent_dir(&package_path);
+    for id in spine {
+        let Some((href, media_type)) = manifest.get(&id) else {
+            continue;
+        };
+        if !media_type.contains("html") {
+            continue;
+        }
+        let chapter = read_member(archive, &resolve_member_path(base_dir, href))?;
+        writer.begin_section("chapter", None);
+        convert_html(&chapter, writer);
+    }
+    Ok(())
+}
+
+////////////////////////////////////////////////////////
+// HTML
+////////////////////////////////////////////////////////
+
+fn convert_html(html: &str, writer: &mut MarkdownWriter) {
+    let document = Html::parse_document(html);
+    html_blocks(document.root_element(), writer, 0);
+}
+
+/// Converts the children of `parent`, gathering consecutive inline content into paragraphs.
+fn html_blocks(parent: ElementRef<'_>, writer: &mut MarkdownWriter, list_depth: usize) {
+    let mut inline = String::new();
+    for child in parent.children() {
+        match child.value() {
+            Node::Text(text) => inline.push_str(text),
+            Node::Element(element) => {
+                let name = element.name();
+                if HTML_SKIPPED_ELEMENTS.contains(&name) {
+                    continue;
+                }
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                if HTML_BLOCK_ELEMENTS.contains(&name) {
+                    writer.paragraph(&collapse_whitespace(&std::mem::take(&mut inline)));
+                    html_block(child, writer, list_depth);
+                } else {
+                    html_inline(child, &mut inline);
+                }
+            }
+            _ => {}
+        }
+    }
+    writer.paragraph(&collapse_whitespace(&inline));
+}
+
+fn html_block(element: ElementRef<'_>, writer: &mut MarkdownWriter, list_depth: usize) {
+    let name = element.value().name();
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            writer.heading(name[1..].parse().unwrap_or(1), &html_inline_text(element));
+        }
+        "ul" | "ol" => {
+            let start = element
+                .value()
+                .attr("start")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1);
+            for (i, item) in element
+                .child_elements()
+                .filter(|e| e.value().name() == "li")
+                .enumerate()
+            {
+                let number = (name == "ol").then_some(start + i);
+                html_list_item(item, writer, list_depth, number);
+            }
+        }
+        "table" => {
+            let rows: Vec<Vec<String>> = element
+                .descendent_elements()
+                .filter(|e| e.value().name() == "tr")
+                .map(|row| {
+                    row.child_elements()
+                        .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                        .map(html_inline_text)
+                        .collect()
+                })
+                .collect();
+            writer.table(&rows);
+        }
+        "pre" => writer.code_block(&element.text().collect::<String>()),
+        "blockquote" => {
+            let text = collapse_whitespace(&element.text().collect::<String>());
+            if !text.is_empty() {
+                writer.paragraph(&format!("> {text}"));
+            }
+        }
+        "hr" => {}
+        "p" | "dt" | "dd" | "summary" | "figcaption" => {
+            writer.paragraph(&html_inline_text(element));
+        }
+        "li" => html_list_item(element, writer, list_depth, None),
+        _ => html_blocks(element, writer, list_depth),
+    }
+}
+
+/// Writes a list item from its inline content, followed by its nested lists.
+fn html_list_item(
+    item: ElementRef<'_>,
+    writer: &mut MarkdownWriter,
+    depth: usize,
+    number: Option<usize>,
+) {
+    let mut inline = String::new();
+    let mut nested_lists = Vec::new();
+    for child in item.children() {
+        match child.value() {
+            Node::Text(text) => inline.push_str(text),
+            Node::Element(element) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                match element.name() {
+                    "ul" | "ol" => nested_lists.push(child),
+                    name if HTML_SKIPPED_ELEMENTS.contains(&name) => {}
+                    _ => {
+                        html_inline(child, &mut inline);
+                        inline.push(' ');
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    writer.list_item(depth, number, &inline);
+    for list in nested_lists {
+        html_block(list, writer, depth + 1);
+    }
+}
+
+fn html_inline_text(element: ElementRef<'_>) -> String {
+    let mut text = String::new();
+    for child in element.children() {
+        match child.value() {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    html_inline(child, &mut text);
+                }
+            }
+            _ => {}
+        }
+    }
+    collapse_whitespace(&text)
+}
+
+/// Appends the inline Markdown for `element`, keeping emphasis, inline code and links.
+fn html_inline(element: ElementRef<'_>, out: &mut String) {
+    let name = element.value().name();
+    if HTML_SKIPPED_ELEMENTS.contains(&name) {
+        return;
+    }
+    if name == "br" {
+        out.push(' ');
+        return;
+    }
+    let inner = html_inline_text(element);
+    if inner.is_empty() {
+        return;
+    }
+    // Keep the whitespace separating the element from its neighbors.
+    let raw: String = element.text().collect();
+    if raw.starts_with(char::is_whitespace) {
+        out.push(' ');
+    }
+    match name {
+        "strong" | "b" => out.push_str(&format!("**{inner}**")),
+        "em" | "i" => out.push_str(&format!("*{inner}*")),
+        "code" => out.push_str(&format!("`{inner}`")),
+        "a" => match element.value().attr("href") {
+            Some(href) if !href.starts_with('#') && !href.starts_with("javascript:") => {
+                out.push_str(&format!("[{inner}]({href})"))
+            }
+            _ => out.push_str(&inner),
+        },
+        _ => out.push_str(&inner),
+    }
+    if raw.ends_with(char::is_whitespace) {
+        out.push(' ');
+    }
+}
+
+////////////////////////////////////////////////////////
+// Op
+////////////////////////////////////////////////////////
+
+pub struct Args {
+    content: ResolvedOpArg,
+    mime_type: Option<ResolvedOpArg>,
+    filename: Option<ResolvedOpArg>,
+}
+
+struct Executor {
+    args: Args,
+}
+
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let content: &[u8] = match self.args.content.value(&input)? {
+            Value::Basic(BasicValue::Bytes(bytes)) => bytes,
+            Value::Basic(BasicValue::Str(text)) => text.as_bytes(),
+            value => client_bail!("expected bytes or str content, got {}", value.kind()),
+        };
+        let mime_type = (self.args.mime_type.value(&input)?)
+            .optional()
+            .map(|v| v.as_str())
+            .transpose()?;
+        let filename = (self.args.filename.value(&input)?)
+            .optional()
+            .map(|v| v.as_str())
+            .transpose()?;
+
+        let format = DocumentFormat::detect(
+            content,
+            mime_type.map(|v| v.as_ref()),
+            filename.map(|v| v.as_ref()),
+        )?;
+        let (markdown, sections) = convert(content, format)?.finish();
+        let sections = sections
+            .into_iter()
+            .enumerate()
+            .map(|(i, (section, location))| {
+                (
+                    KeyValue::from_single_part(i as i64 + 1),
+                    fields_value!(
+                        section.kind.to_string(),
+                        section.title.map(Value::from).unwrap_or(Value::Null),
+                        Value::Basic(BasicValue::Range(location))
+                    )
+                    .into(),
+                )
+            })
+            .collect();
+        Ok(Value::Struct(fields_value!(
+            format.as_str().to_string(),
+            markdown,
+            Value::KTable(sections)
+        )))
+    }
+
+    fn enable_cache(&self) -> bool {
+        true
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = EmptySpec;
+    type ResolvedArgs = Args;
+
+    fn name(&self) -> &str {
+        "ConvertToMarkdown"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        _spec: &'a EmptySpec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        _context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
+        let args = Args {
+            content: args_resolver
+                .next_arg("content")?
+                .expect_type_in(&[
+                    ValueType::Basic(BasicValueType::Bytes),
+                    ValueType::Basic(BasicValueType::Str),
+                ])?
+                .required()?,
+            mime_type: args_resolver
+                .next_arg("mime_type")?
+                .expect_nullable_type(&ValueType::Basic(BasicValueType::Str))?
+                .optional(),
+            filename: args_resolver
+                .next_arg("filename")?
+                .expect_nullable_type(&ValueType::Basic(BasicValueType::Str))?
+                .optional(),
+        };
+
+        let mut sections_schema = StructSchema::default();
+        let mut sb = StructSchemaBuilder::new(&mut sections_schema);
+        sb.add_field(FieldSchema::new(
+            "number",
+            make_output_type(BasicValueType::Int64),
+        ));
+        sb.add_field(FieldSchema::new(
+            "kind",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "title",
+            make_output_type(BasicValueType::Str).with_nullable(true),
+        ));
+        sb.add_field(FieldSchema::new(
+            "location",
+            make_output_type(BasicValueType::Range),
+        ));
+
+        let mut output_schema = StructSchema::default();
+        let mut sb = StructSchemaBuilder::new(&mut output_schema);
+        sb.add_field(FieldSchema::new(
+            "format",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "markdown",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "sections",
+            make_output_type(TableSchema::new(
+                TableKind::KTable(KTableInfo { num_key_parts: 1 }),
+                sections_schema,
+            )),
+        ));
+        Ok(SimpleFunctionAnalysisOutput {
+            resolved_args: args,
+            output_schema: make_output_type(output_schema),
+            behavior_version: None,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        _spec: EmptySpec,
+        args: Args,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        Ok(Executor { args })
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::functions::test_utils::{build_arg_schema, test_flow_function};
+    use std::io::Write;
+
+    fn build_zip(members: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (name, content) in members {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    /// Builds a single-page PDF showing each line of text, with a valid cross-reference table.
+    fn build_pdf(lines: &[&str]) -> Vec<u8> {
+        let stream = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| format!("BT /F1 12 Tf 72 {} Td ({line}) Tj ET", 720 - 40 * i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>".to_string(),
+            format!("<< /Length {} >>\nstream\n{stream}\nendstream", stream.len()),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ];
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n{object}\nendobj\n", i + 1).as_bytes());
+        }
+        let xref_offset = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref_offset}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .as_bytes(),
+        );
+        pdf
+    }
+
+    /// Kind, title and text of a section.
+    type SectionSummary = (&'static str, Option<String>, String);
+
+    fn section_summary(writer: MarkdownWriter) -> (String, Vec<SectionSummary>) {
+        let (markdown, sections) = writer.finish();
+        let sections = sections
+            .into_iter()
+            .map(|(section, range)| {
+                let text = markdown
+                    .chars()
+                    .skip(range.start)
+                    .take(range.end - range.start)
+                    .collect();
+                (section.kind, section.title, text)
+            })
+            .collect();
+        (markdown, sections)
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let docx = build_zip(&[("word/document.xml", "<w:document/>")]);
+        let detect = |content: &[u8], mime_type, filename| {
+            DocumentFormat::detect(content, mime_type, filename).unwrap()
+        };
+        assert_eq!(detect(b"%PDF-1.7", None, None), DocumentFormat::Pdf);
+        assert_eq!(detect(&docx, None, None), DocumentFormat::Docx);
+        assert_eq!(
+            detect(b"<!DOCTYPE html><p>x</p>", None, None),
+            DocumentFormat::Html
+        );
+        assert_eq!(
+            detect(b"# Title", None, Some("README.md")),
+            DocumentFormat::Markdown
+        );
+        assert_eq!(detect(b"plain", None, None), DocumentFormat::Text);
+        assert_eq!(
+            detect(
+                b"<p>x</p>",
+                Some("text/html; charset=utf-8"),
+                Some("page.txt")
+            ),
+            DocumentFormat::Html
+        );
+        assert!(DocumentFormat::detect(&[0xff, 0xfe, 0x00], None, None).is_err());
+    }
+
+    #[test]
+    fn test_convert_html() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Ignored</title><style>p { color: red; }</style></head>
+<body>
+  <h1>Guide</h1>
+  <p>Read the <a href="https://example.com">docs</a> with <strong>care</strong>.</p>
+  <ul>
+    <li>First
+      <ol><li>Nested one</li><li>Nested two</li></ol>
+    </li>
+    <li>Second</li>
+  </ul>
+  <h2>Data</h2>
+  <table>
+    <tr><th>Name</th><th>Value</th></tr>
+    <tr><td>a|b</td><td>1</td></tr>
+  </table>
+  <script>alert("ignored")</script>
+</body></html>"#;
+        let (markdown, sections) =
+            section_summary(convert(html.as_bytes(), DocumentFormat::Html).unwrap());
+        assert_eq!(
+            markdown,
+            "# Guide\n\n\
+             Read the [docs](https://example.com) with **care**.\n\n\
+             - First\n  1. Nested one\n  2. Nested two\n- Second\n\n\
+             ## Data\n\n\
+             | Name | Value |\n| --- | --- |\n| a\\|b | 1 |"
+        );
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].0, "section");
+        assert_eq!(sections[0].1.as_deref(), Some("Guide"));
+        assert!(sections[0].2.starts_with("# Guide"));
+        assert!(sections[0].2.ends_with("- Second"));
+        assert_eq!(sections[1].1.as_deref(), Some("Data"));
+        assert!(sections[1].2.ends_with("| a\\|b | 1 |"));
+    }
+
+    #[test]
+    fn test_convert_docx() {
+        let document = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+  <w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr><w:r><w:t>Report</w:t></w:r></w:p>
+  <w:p><w:r><w:t xml:space="preserve">Hello </w:t></w:r><w:r><w:t>world</w:t></w:r></w:p>
+  <w:p><w:pPr><w:pStyle w:val="Heading2"/></w:pPr><w:r><w:t>Steps</w:t></w:r></w:p>
+  <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Prepare</w:t></w:r></w:p>
+  <w:p><w:pPr><w:numPr><w:ilvl w:val="1"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Detail</w:t></w:r></w:p>
+  <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Run</w:t></w:r></w:p>
+  <w:tbl>
+    <w:tr><w:tc><w:p><w:r><w:t>Key</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Value</w:t></w:r></w:p></w:tc></w:tr>
+    <w:tr><w:tc><w:p><w:r><w:t>x</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>1</w:t></w:r></w:p></w:tc></w:tr>
+  </w:tbl>
+</w:body></w:document>"#;
+        let numbering = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:abstractNum w:abstractNumId="7">
+    <w:lvl w:ilvl="0"><w:numFmt w:val="decimal"/></w:lvl>
+    <w:lvl w:ilvl="1"><w:numFmt w:val="bullet"/></w:lvl>
+  </w:abstractNum>
+  <w:num w:numId="1"><w:abstractNumId w:val="7"/></w:num>
+</w:numbering>"#;
+        let docx = build_zip(&[
+            ("word/document.xml", document),
+            ("word/numbering.xml", numbering),
+        ]);
+        let (markdown, sections) = section_summary(convert(&docx, DocumentFormat::Docx).unwrap());
+        assert_eq!(
+            markdown,
+            "# Report\n\nHello world\n\n## Steps\n\n\
+             1. Prepare\n  - Detail\n2. Run\n\n\
+             | Key | Value |\n| --- | --- |\n| x | 1 |"
+        );
+        let titles: Vec<_> = sections.iter().map(|s| s.1.as_deref()).collect();
+        assert_eq!(titles, vec![Some("Report"), Some("Steps")]);
+    }
+
+    #[test]
+    fn test_convert_pptx() {
+        let presentation = r#"<?xml version="1.0" encoding="UTF-8"?>
+<p:presentation xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <p:sldIdLst><p:sldId id="257" r:id="rId3"/><p:sldId id="256" r:id="rId2"/></p:sldIdLst>
+</p:presentation>"#;
+        let rels = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId2" Type="slide" Target="slides/slide2.xml"/>
+  <Relationship Id="rId3" Type="slide" Target="slides/slide1.xml"/>
+</Relationships>"#;
+        let slide = |title: &str, body: &str| {
+            format!(
+                r#"<p:sld xmlns:p="p" xmlns:a="a"><p:cSld><p:spTree>
+<p:sp><p:nvSpPr><p:nvPr><p:ph type="title"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>{title}</a:t></a:r></a:p></p:txBody></p:sp>
+<p:sp><p:nvSpPr><p:nvPr><p:ph idx="1"/></p:nvPr></p:nvSpPr><p:txBody>{body}</p:txBody></p:sp>
+<p:sp><p:nvSpPr><p:nvPr><p:ph type="sldNum"/></p:nvPr></p:nvSpPr><p:txBody><a:p><a:r><a:t>7</a:t></a:r></a:p></p:txBody></p:sp>
+</p:spTree></p:cSld></p:sld>"#
+            )
+        };
+        let slide1 = slide(
+            "Intro",
+            r#"<a:p><a:r><a:t>Point</a:t></a:r></a:p><a:p><a:pPr lvl="1"/><a:r><a:t>Sub point</a:t></a:r></a:p>"#,
+        );
+        let slide2 = slide("Outro", r#"<a:p><a:r><a:t>Thanks</a:t></a:r></a:p>"#);
+        let pptx = build_zip(&[
+            ("ppt/presentation.xml", presentation),
+            ("ppt/_rels/presentation.xml.rels", rels),
+            ("ppt/slides/slide1.xml", &slide1),
+            ("ppt/slides/slide2.xml", &slide2),
+        ]);
+        let (markdown, sections) = section_summary(convert(&pptx, DocumentFormat::Pptx).unwrap());
+        assert_eq!(
+            markdown,
+            "# Intro\n\n- Point\n  - Sub point\n\n# Outro\n\n- Thanks"
+        );
+        assert_eq!(
+            sections,
+            vec![
+                (
+                    "slide",
+                    Some("Intro".to_string()),
+                    "# Intro\n\n- Point\n  - Sub point".to_string()
+                ),
+                (
+                    "slide",
+                    Some("Outro".to_string()),
+                    "# Outro\n\n- Thanks".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_epub() {
+        let container = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#;
+        let package = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <manifest>
+    <item id="c1" href="text/one.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="text/two.xhtml" media-type="application/xhtml+xml"/>
+    <item id="css" href="style.css" media-type="text/css"/>
+  </manifest>
+  <spine><itemref idref="c1"/><itemref idref="c2"/></spine>
+</package>"#;
+        let epub = build_zip(&[
+            ("mimetype", "application/epub+zip"),
+            ("META-INF/container.xml", container),
+            ("OEBPS/content.opf", package),
+            (
+                "OEBPS/text/one.xhtml",
+                "<html><body><h1>Chapter 1</h1><p>It begins.</p></body></html>",
+            ),
+            (
+                "OEBPS/text/two.xhtml",
+                "<html><body><h1>Chapter 2</h1><p>It ends.</p></body></html>",
+            ),
+        ]);
+        assert_eq!(
+            DocumentFormat::detect(&epub, None, None).unwrap(),
+            DocumentFormat::Epub
+        );
+        let (markdown, sections) = section_summary(convert(&epub, DocumentFormat::Epub).unwrap());
+        assert_eq!(
+            markdown,
+            "# Chapter 1\n\nIt begins.\n\n# Chapter 2\n\nIt ends."
+        );
+        let titles: Vec<_> = sections.iter().map(|s| (s.0, s.1.as_deref())).collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("chapter", Some("Chapter 1")),
+                ("chapter", Some("Chapter 2"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_pdf() {
+        let pdf = build_pdf(&["Quarterly results", "- Revenue grew"]);
+        let (markdown, sections) = section_summary(convert(&pdf, DocumentFormat::Pdf).unwrap());
+        assert!(markdown.contains("Quarterly results"), "{markdown}");
+        assert!(markdown.contains("- Revenue grew"), "{markdown}");
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].0, "page");
+
+        assert!(convert(b"%PDF-1.4 truncated", DocumentFormat::Pdf).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_convert_to_markdown() {
+        let factory = Arc::new(Factory);
+        let html = "<html><body><h1>Título</h1><p>Olá mundo</p></body></html>";
+        let input_arg_schemas = &[
+            build_arg_schema("content", BasicValueType::Bytes),
+            build_arg_schema("filename", BasicValueType::Str),
+        ];
+        let result = test_flow_function(
+            &factory,
+            &EmptySpec {},
+            input_arg_schemas,
+            vec![
+                BasicValue::from(html.as_bytes().to_vec()).into(),
+                "page.html".to_string().into(),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let Value::Struct(fields) = result else {
+            panic!("Expected Value::Struct, got {result:?}");
+        };
+        assert_eq!(fields.fields[0], Value::from("html".to_string()));
+        assert_eq!(
+            fields.fields[1],
+            Value::from("# Título\n\nOlá mundo".to_string())
+        );
+        let Value::KTable(sections) = &fields.fields[2] else {
+            panic!("Expected Value::KTable, got {:?}", fields.fields[2]);
+        };
+        let section = sections.get(&KeyValue::from_single_part(1i64)).unwrap();
+        assert_eq!(section.0.fields[0], Value::from("section".to_string()));
+        assert_eq!(section.0.fields[1], Value::from("Título".to_string()));
+        assert_eq!(
+            section.0.fields[2],
+            Value::Basic(BasicValue::Range(RangeValue::new(0, 19)))
+        );
+    }
+}
@@ -0,0 +1,286 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+use crate::prelude::*;
+
+use crate::ops::registry::ExecutorFactoryRegistry;
+use crate::ops::targets::near_duplicate_index::{IndexStore, index_file_path, key_to_row_id};
+use std::path::PathBuf;
+use utils::similarity::MinHashSignature;
+
+/// Flags rows whose MinHash signature (from `ComputeMinHash`) is within a Jaccard threshold
+/// of another row in a `NearDuplicateIndex` target.
+///
+/// Export the key and signature of each row to the `NearDuplicateIndex` target with the same
+/// `path` and `index_name`. It keeps the band buckets of each row on disk, replaces them when the
+/// row changes and removes them when the row is deleted. Of near-duplicates, the one with the
+/// smallest key is kept, whatever the processing order. A near-duplicate reports the key of the
+/// row it duplicates, and its optional `value` argument comes out as null, so downstream
+/// functions taking it (e.g. `EmbedText`) skip the row.
+///
+/// The index is read when a row is evaluated, and rows aren't re-evaluated when other rows
+/// change. E.g. a row stays flagged after the row it duplicates is deleted, until its own
+/// input changes or the flow is fully reprocessed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spec {
+    /// Directory of the `NearDuplicateIndex` target.
+    path: String,
+    /// Name of the index in the `NearDuplicateIndex` target.
+    index_name: String,
+    /// Minimum estimated Jaccard similarity of a near-duplicate. Defaults to the threshold of
+    /// the index.
+    threshold: Option<f64>,
+}
+
+struct Args {
+    key: ResolvedOpArg,
+    signature: ResolvedOpArg,
+    value: Option<ResolvedOpArg>,
+}
+
+struct Executor {
+    args: Args,
+    file_path: PathBuf,
+    /// Schema to decode keys of the index with.
+    key_schema: [FieldSchema; 1],
+    threshold: Option<f64>,
+}
+
+impl Executor {
+    /// The most similar row with a smaller key than `key`, and its similarity.
+    async fn find_duplicate(
+        &self,
+        key: &KeyPart,
+        signature: &MinHashSignature,
+    ) -> Result<Option<(KeyPart, f64)>> {
+        let store = IndexStore::open(&self.file_path).await?;
+        let id = key_to_row_id(&KeyValue::from_single_part(key.clone()))?;
+        let threshold = self.threshold.unwrap_or_else(|| store.threshold());
+        let mut duplicate: Option<(KeyPart, f64)> = None;
+        for index_match in store.query(signature, threshold).await? {
+            if index_match.id == id {
+                continue;
+            }
+            let other_key = KeyValue::from_json(index_match.key, &self.key_schema)?
+                .single_part()?
+                .clone();
+            if other_key >= *key {
+                continue;
+            }
+            // Matches come most similar first. Ties go to the smallest key.
+            match &duplicate {
+                Some((dup_key, similarity))
+                    if *similarity > index_match.similarity || *dup_key < other_key => {}
+                _ => duplicate = Some((other_key, index_match.similarity)),
+            }
+        }
+        Ok(duplicate)
+    }
+}
+
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let key = self.args.key.value(&input)?.as_key()?;
+        let signature =
+            MinHashSignature::from_bytes(self.args.signature.value(&input)?.as_bytes()?)?;
+        let duplicate = self.find_duplicate(&key, &signature).await?;
+
+        let mut fields = match duplicate {
+            Some((key, similarity)) => vec![
+                Value::from(key),
+                Value::Basic(BasicValue::Float64(similarity)),
+            ],
+            None => vec![Value::Null, Value::Null],
+        };
+        if let Some(value) = &self.args.value {
+            fields.push(if fields[0].is_null() {
+                value.value(&input)?.clone()
+            } else {
+                Value::Null
+            });
+        }
+        Ok(Value::Struct(FieldValues { fields }))
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = Spec;
+    type ResolvedArgs = Args;
+
+    fn name(&self) -> &str {
+        "DetectNearDuplicate"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        spec: &'a Spec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        _context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
+        if spec.index_name.is_empty() {
+            api_bail!("`index_name` must not be empty");
+        }
+        if spec.threshold.is_some_and(|t| !(t > 0.0 && t <= 1.0)) {
+            api_bail!("`threshold` must be in (0, 1]");
+        }
+        let args = Args {
+            key: args_resolver.next_arg("key")?.required()?,
+            signature: args_resolver
+                .next_arg("signature")?
+                .expect_type(&ValueType::Basic(BasicValueType::Bytes))?
+                .required()?,
+            value: args_resolver.next_arg("value")?.optional(),
+        };
+        let mut output_schema = StructSchema::default();
+        let mut sb = StructSchemaBuilder::new(&mut output_schema);
+        sb.add_field(FieldSchema::new(
+            "duplicate_of",
+            args.key.typ.without_attrs().with_nullable(true),
+        ));
+        sb.add_field(FieldSchema::new(
+            "similarity",
+            make_output_type(BasicValueType::Float64).with_nullable(true),
+        ));
+        if let Some(value) = &args.value {
+            sb.add_field(FieldSchema::new(
+                "unique_value",
+                value.typ.clone().with_nullable(true),
+            ));
+        }
+        Ok(SimpleFunctionAnalysisOutput {
+            resolved_args: args,
+            output_schema: make_output_type(output_schema),
+            behavior_version: None,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        spec: Spec,
+        args: Args,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        let key_schema = [FieldSchema::new("key", args.key.typ.without_attrs())];
+        Ok(Executor {
+            args,
+            file_path: index_file_path(&spec.path, &spec.index_name),
+            key_schema,
+            threshold: spec.threshold,
+        })
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::functions::test_utils::{build_arg_schema, test_flow_function};
+    use crate::ops::targets::near_duplicate_index::test_utils::build_index;
+    use utils::similarity::{DEFAULT_NUM_PERMUTATIONS, MinHasher};
+
+    const TEXT: &str = "Recoco keeps derived data in sync with its sources, reprocessing only \
+                        the rows whose inputs changed since the previous run of the flow.";
+    const OTHER_TEXT: &str = "A completely different document about cooking.";
+
+    fn signature_bytes(text: &str) -> Vec<u8> {
+        MinHasher::new(DEFAULT_NUM_PERMUTATIONS, 0)
+            .text_signature(text, 3)
+            .to_bytes()
+    }
+
+    #[tokio::test]
+    async fn test_detect_near_duplicate() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!(
+            "recoco-detect-near-duplicate-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let copy_text = format!("{TEXT} Thanks!");
+        build_index(
+            &dir,
+            "docs",
+            0.7,
+            vec![
+                ("a.md", signature_bytes(TEXT)),
+                ("b.md", signature_bytes(&copy_text)),
+                ("c.md", signature_bytes(OTHER_TEXT)),
+            ],
+        )
+        .await?;
+
+        let factory = Arc::new(Factory);
+        let spec = Spec {
+            path: dir.to_string_lossy().to_string(),
+            index_name: "docs".to_string(),
+            threshold: None,
+        };
+        let input_arg_schemas = &[
+            build_arg_schema("key", BasicValueType::Str),
+            build_arg_schema("signature", BasicValueType::Bytes),
+            build_arg_schema("value", BasicValueType::Str),
+        ];
+        let evaluate = async |spec: &Spec, key: &str, text: &str| -> Result<Vec<Value>> {
+            let input = vec![
+                key.to_string().into(),
+                BasicValue::from(signature_bytes(text)).into(),
+                text.to_string().into(),
+            ];
+            match test_flow_function(&factory, spec, input_arg_schemas, input).await? {
+                Value::Struct(fields) => Ok(fields.fields),
+                other => panic!("Expected struct, got {other:?}"),
+            }
+        };
+
+        // The indexed row with the smallest key is kept, and isn't a duplicate of itself.
+        let original = evaluate(&spec, "a.md", TEXT).await?;
+        assert_eq!(
+            original,
+            vec![Value::Null, Value::Null, TEXT.to_string().into()]
+        );
+
+        let copy = evaluate(&spec, "b.md", &copy_text).await?;
+        assert_eq!(copy[0], Value::from("a.md".to_string()));
+        assert!(matches!(copy[1], Value::Basic(BasicValue::Float64(s)) if s >= 0.7));
+        assert_eq!(copy[2], Value::Null);
+
+        // A new row with a smaller key is kept, though others were indexed before it.
+        let earlier = evaluate(&spec, "0.md", TEXT).await?;
+        assert_eq!(earlier[0], Value::Null);
+
+        let other = evaluate(&spec, "d.md", OTHER_TEXT).await?;
+        assert_eq!(other[0], Value::from("c.md".to_string()));
+
+        // The threshold of the index can be raised per function.
+        let strict = Spec {
+            threshold: Some(1.0),
+            ..spec.clone()
+        };
+        assert_eq!(evaluate(&strict, "b.md", &copy_text).await?[0], Value::Null);
+
+        let missing = Spec {
+            index_name: "missing".to_string(),
+            ..spec.clone()
+        };
+        assert!(evaluate(&missing, "a.md", TEXT).await.is_err());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -10,14 +10,23 @@
 // Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::ops::sdk::*;
+use crate::{fields_value, ops::sdk::*};
 use recoco_splitters::prog_langs;
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Spec {
+    /// Return a `{language, confidence}` struct instead of just the language name.
+    #[serde(default)]
+    include_confidence: bool,
+}
+
 pub struct Args {
     filename: ResolvedOpArg,
+    content: Option<ResolvedOpArg>,
 }
 
 struct Executor {
+    spec: Spec,
     args: Args,
 }
 
@@ -25,9 +34,27 @@ struct Executor {
 impl SimpleFunctionExecutor for Executor {
     async fn evaluate(&self, input: Vec<value::Value>) -> Result<value::Value> {
         let filename = self.args.filename.value(&input)?.as_str()?;
-        let lang_name = prog_langs::detect_language(filename)
-            .map(|name| value::Value::Basic(value::BasicValue::Str(name.into())));
-        Ok(lang_name.unwrap_or(value::Value::Null))
+        let content = self
+            .args
+            .content
+            .value(&input)?
+            .optional()
+            .map(|v| v.as_str())
+            .transpose()?;
+        let Some(detection) =
+            prog_langs::detect_language_with_content(filename, content.map(|c| c.as_ref()))
+        else {
+            return Ok(value::Value::Null);
+        };
+        let language = value::Value::Basic(value::BasicValue::Str(detection.language));
+        if self.spec.include_confidence {
+            Ok(value::Value::Struct(fields_value!(
+                language,
+                detection.confidence
+            )))
+        } else {
+            Ok(language)
+        }
     }
 }
 
@@ -35,7 +62,7 @@ struct Factory;
 
 #[async_trait]
 impl SimpleFunctionFactoryBase for Factory {
-    type Spec = EmptySpec;
+    type Spec = Spec;
     type ResolvedArgs = Args;
 
     fn name(&self) -> &str {
@@ -44,7 +71,7 @@ impl SimpleFunctionFactoryBase for Factory {
 
     async fn analyze<'a>(
         &'a self,
-        _spec: &'a EmptySpec,
+        spec: &'a Spec,
         args_resolver: &mut OpArgsResolver<'a>,
         _context: &FlowInstanceContext,
     ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
@@ -53,9 +80,23 @@ impl SimpleFunctionFactoryBase for Factory {
                 .next_arg("filename")?
                 .expect_type(&ValueType::Basic(BasicValueType::Str))?
                 .required()?,
+            content: args_resolver
+                .next_arg("content")?
+                .expect_nullable_type(&ValueType::Basic(BasicValueType::Str))?
+                .optional(),
         };
 
-        let output_schema = make_output_type(BasicValueType::Str);
+        let output_schema = if spec.include_confidence {
+            make_output_type(StructSchema {
+                fields: Arc::new(vec![
+                    FieldSchema::new("language", make_output_type(BasicValueType::Str)),
+                    FieldSchema::new("confidence", make_output_type(BasicValueType::Float64)),
+                ]),
+                description: None,
+            })
+        } else {
+            make_output_type(BasicValueType::Str)
+        };
         Ok(SimpleFunctionAnalysisOutput {
             resolved_args: args,
             output_schema,
@@ -65,11 +106,11 @@ impl SimpleFunctionFactoryBase for Factory {
 
     async fn build_executor(
         self: Arc<Self>,
-        _spec: EmptySpec,
+        spec: Spec,
         args: Args,
         _context: Arc<FlowInstanceContext>,
     ) -> Result<impl SimpleFunctionExecutor> {
-        Ok(Executor { args })
+        Ok(Executor { spec, args })
     }
 }
 
@@ -84,7 +125,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_detect_programming_language() {
-        let spec = EmptySpec {};
+        let spec = Spec::default();
         let factory = Arc::new(Factory);
 
         let input_args_values = vec!["test.rs".to_string().into()];
@@ -110,7 +151,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_detect_programming_language_unknown() {
-        let spec = EmptySpec {};
+        let spec = Spec::default();
         let factory = Arc::new(Factory);
 
         let input_args_values = vec!["test.unknown".to_string().into()];
@@ -133,4 +174,42 @@ mod tests {
             _ => panic!("Expected Value::Null, got {value:?}"),
         }
     }
+
+    #[tokio::test]
+    async fn test_detect_programming_language_from_content() {
+        let spec = Spec {
+            include_confidence: true,
+        };
+        let factory = Arc::new(Factory);
+        let input_arg_schemas = &[
+            build_arg_schema("filename", BasicValueType::Str),
+            build_arg_schema("content", BasicValueType::Str),
+        ];
+
+        let value = test_flow_function(
+            &factory,
+            &spec,
+            input_arg_schemas,
+            vec![
+                "bin/deploy".to_string().into(),
+                "#!/usr/bin/env python3\nprint('hi')\n".to_string().into(),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            value,
+            Value::Struct(fields_value!("python".to_string(), 0.9f64))
+        );
+
+        let value = test_flow_function(
+            &factory,
+            &spec,
+            input_arg_schemas,
+            vec!["notes".to_string().into(), "hello".to_string().into()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(value, Value::Null);
+    }
 }
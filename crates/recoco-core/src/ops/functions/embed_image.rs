@@ -0,0 +1,358 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::embed_text::{embedding_to_value, resolve_expected_output_dimension};
+use crate::{
+    llm::{
+        LlmApiConfig, LlmApiType, LlmEmbeddingClient, LlmEmbeddingContent,
+        LlmMultimodalEmbeddingRequest, new_llm_embedding_client,
+    },
+    ops::sdk::*,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct Spec {
+    api_type: LlmApiType,
+    model: String,
+    address: Option<String>,
+    api_config: Option<LlmApiConfig>,
+    output_dimension: Option<u32>,
+    expected_output_dimension: Option<u32>,
+    task_type: Option<String>,
+    api_key: Option<AuthEntryReference<String>>,
+}
+
+struct Args {
+    client: Box<dyn LlmEmbeddingClient>,
+    image: ResolvedOpArg,
+    text: Option<ResolvedOpArg>,
+    expected_output_dimension: usize,
+}
+
+struct Executor {
+    spec: Spec,
+    args: Args,
+}
+
+#[async_trait]
+impl BatchedFunctionExecutor for Executor {
+    fn enable_cache(&self) -> bool {
+        true
+    }
+
+    #[cfg(feature = "batching")]
+    fn batching_options(&self) -> batching::BatchingOptions {
+        // Images make requests much larger than texts, so keep batches small.
+        batching::BatchingOptions {
+            max_batch_size: Some(16),
+        }
+    }
+
+    async fn evaluate_batch(&self, args: Vec<Vec<Value>>) -> Result<Vec<Value>> {
+        let inputs = args
+            .iter()
+            .map(|arg| {
+                let mut parts = Vec::with_capacity(2);
+                if let Some(text) = self.args.text.as_ref()
+                    && let Some(value) = text.value(arg)?.optional()
+                {
+                    parts.push(LlmEmbeddingContent::Text(Cow::Borrowed(
+                        value.as_str()?.as_ref(),
+                    )));
+                }
+                parts.push(LlmEmbeddingContent::Image(Cow::Borrowed(
+                    self.args.image.value(arg)?.as_bytes()?.as_ref(),
+                )));
+                Ok(parts)
+            })
+            .collect::<Result<_>>()?;
+        let req = LlmMultimodalEmbeddingRequest {
+            model: &self.spec.model,
+            inputs,
+            output_dimension: self.spec.output_dimension,
+            task_type: self
+                .spec
+                .task_type
+                .as_ref()
+                .map(|s| Cow::Borrowed(s.as_str())),
+        };
+        let resp = self.args.client.embed_multimodal(req).await?;
+        if resp.embeddings.len() != args.len() {
+            api_bail!(
+                "Expected {expected} embeddings but got {actual} from the embedding API.",
+                expected = args.len(),
+                actual = resp.embeddings.len()
+            );
+        }
+        resp.embeddings
+            .into_iter()
+            .map(|embedding| {
+                embedding_to_value(
+                    embedding,
+                    self.args.expected_output_dimension,
+                    self.spec.output_dimension,
+                )
+            })
+            .collect()
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = Spec;
+    type ResolvedArgs = Args;
+
+    fn name(&self) -> &str {
+        "EmbedImage"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        spec: &'a Spec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Self::ResolvedArgs>> {
+        let image = args_resolver
+            .next_arg("image")?
+            .expect_type(&ValueType::Basic(BasicValueType::Bytes))?
+            .required()?;
+        let text = args_resolver
+            .next_arg("text")?
+            .expect_nullable_type(&ValueType::Basic(BasicValueType::Str))?
+            .optional();
+
+        let api_key = spec
+            .api_key
+            .as_ref()
+            .map(|key_ref| context.auth_registry.get(key_ref))
+            .transpose()?;
+
+        let client = new_llm_embedding_client(
+            spec.api_type,
+            spec.address.clone(),
+            api_key,
+            spec.api_config.clone(),
+        )
+        .await?;
+
+        let expected_output_dimension = resolve_expected_output_dimension(
+            spec.api_type,
+            &spec.model,
+            spec.expected_output_dimension,
+            spec.output_dimension,
+            client.as_ref(),
+        )?;
+        let output_schema = make_output_type(BasicValueType::Vector(VectorTypeSchema {
+            dimension: Some(expected_output_dimension),
+            element_type: Box::new(BasicValueType::Float32),
+        }));
+        Ok(SimpleFunctionAnalysisOutput {
+            behavior_version: client.behavior_version(),
+            resolved_args: Args {
+                client,
+                image,
+                text,
+                expected_output_dimension,
+            },
+            output_schema,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        spec: Spec,
+        args: Args,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        Ok(Executor { spec, args }.into_fn_executor())
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(all(test, feature = "provider-voyage"))]
+mod tests {
+    use super::*;
+    use crate::ops::functions::test_utils::{
+        build_arg_schema, test_flow_function, test_flow_function_with_context,
+    };
+
+    /// A 1x1 transparent PNG.
+    const PNG: &[u8] = &[
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F,
+        0x15, 0xC4, 0x89, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x00,
+        0x01, 0x00, 0x00, 0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00, 0x00, 0x00, 0x00, 0x49,
+        0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
+    ];
+
+    #[tokio::test]
+    #[ignore = "This test requires a Voyage AI API key and makes network calls."]
+    async fn test_embed_image() {
+        let spec = Spec {
+            api_type: LlmApiType::Voyage,
+            model: "voyage-multimodal-3".to_string(),
+            address: None,
+            api_config: None,
+            output_dimension: None,
+            expected_output_dimension: None,
+            task_type: None,
+            api_key: None,
+        };
+
+        let factory = Arc::new(Factory);
+        let input_args_values = vec![
+            Value::Basic(BasicValue::Bytes(bytes::Bytes::from_static(PNG))),
+            "A blank diagram".to_string().into(),
+        ];
+        let input_arg_schemas = &[
+            build_arg_schema("image", BasicValueType::Bytes),
+            build_arg_schema("text", BasicValueType::Str),
+        ];
+
+        let result = test_flow_function(&factory, &spec, input_arg_schemas, input_args_values)
+            .await
+            .unwrap();
+        match result {
+            Value::Basic(BasicValue::Vector(arc_vec)) => {
+                assert_eq!(arc_vec.len(), 1024, "Embedding vector dimension mismatch");
+            }
+            _ => panic!("Expected Value::Basic(BasicValue::Vector), got {result:?}"),
+        }
+    }
+
+    /// A minimal server implementing the Voyage AI multimodal embeddings endpoint. The embedding
+    /// of each input is `[number of text parts, number of image parts]`. Request bodies are
+    /// recorded.
+    #[cfg(feature = "server")]
+    async fn start_stub_server() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        use axum::{Json, Router, extract::State, http::HeaderMap, routing::post};
+
+        async fn embed(
+            State(requests): State<Arc<Mutex<Vec<serde_json::Value>>>>,
+            headers: HeaderMap,
+            Json(body): Json<serde_json::Value>,
+        ) -> Json<serde_json::Value> {
+            assert_eq!(headers["authorization"], "Bearer test-key");
+            let data = body["inputs"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(index, input)| {
+                    let content = input["content"].as_array().unwrap();
+                    let count = |part_type: &str| {
+                        content
+                            .iter()
+                            .filter(|part| part["type"] == part_type)
+                            .count() as f32
+                    };
+                    serde_json::json!({
+                        "embedding": [count("text"), count("image_base64")],
+                        "index": index,
+                    })
+                })
+                .collect::<Vec<_>>();
+            requests.lock().unwrap().push(body);
+            Json(serde_json::json!({ "data": data }))
+        }
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route("/multimodalembeddings", post(embed))
+            .with_state(requests.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (address, requests)
+    }
+
+    #[cfg(feature = "server")]
+    #[tokio::test]
+    async fn test_embed_image_with_stub_server() -> Result<()> {
+        use base64::prelude::*;
+
+        let (address, requests) = start_stub_server().await;
+        let auth_registry = Arc::new(AuthRegistry::default());
+        auth_registry.add("voyage_key".to_string(), serde_json::json!("test-key"))?;
+        let context = Arc::new(FlowInstanceContext {
+            flow_instance_name: "test_embed_image".to_string(),
+            auth_registry,
+            function_state_store: Default::default(),
+        });
+        let spec = Spec {
+            api_type: LlmApiType::Voyage,
+            model: "voyage-multimodal-3".to_string(),
+            address: Some(address),
+            api_config: None,
+            output_dimension: None,
+            expected_output_dimension: Some(2),
+            task_type: Some("document".to_string()),
+            api_key: Some(AuthEntryReference::new("voyage_key".to_string())),
+        };
+        let factory = Arc::new(Factory);
+        let image = Value::Basic(BasicValue::Bytes(bytes::Bytes::from_static(PNG)));
+
+        // Image with text.
+        let result = test_flow_function_with_context(
+            &factory,
+            &spec,
+            &[
+                build_arg_schema("image", BasicValueType::Bytes),
+                build_arg_schema("text", BasicValueType::Str),
+            ],
+            vec![image.clone(), "A blank diagram".to_string().into()],
+            context.clone(),
+        )
+        .await?;
+        assert_eq!(result, Value::from(vec![1.0f32, 1.0]));
+
+        // Image only.
+        let result = test_flow_function_with_context(
+            &factory,
+            &spec,
+            &[build_arg_schema("image", BasicValueType::Bytes)],
+            vec![image],
+            context,
+        )
+        .await?;
+        assert_eq!(result, Value::from(vec![0.0f32, 1.0]));
+
+        let image_part = serde_json::json!({
+            "type": "image_base64",
+            "image_base64": format!("data:image/png;base64,{}", BASE64_STANDARD.encode(PNG)),
+        });
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                serde_json::json!({
+                    "inputs": [{ "content": [
+                        { "type": "text", "text": "A blank diagram" },
+                        image_part,
+                    ] }],
+                    "model": "voyage-multimodal-3",
+                    "input_type": "document",
+                }),
+                serde_json::json!({
+                    "inputs": [{ "content": [image_part] }],
+                    "model": "voyage-multimodal-3",
+                    "input_type": "document",
+                }),
+            ]
+        );
+        Ok(())
+    }
+}
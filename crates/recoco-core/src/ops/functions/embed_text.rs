@@ -10,9 +10,12 @@
 // Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+use crate::llm::{LlmBatchClient, LlmBatchEndpoint, LlmBatchRunner, new_llm_batch_client};
 use crate::{
     llm::{
-        LlmApiConfig, LlmApiType, LlmEmbeddingClient, LlmEmbeddingRequest, new_llm_embedding_client,
+        BatchApiSpec, LlmApiConfig, LlmApiType, LlmEmbeddingClient, LlmEmbeddingRequest,
+        new_llm_embedding_client,
     },
     ops::sdk::*,
 };
@@ -27,10 +30,65 @@ pub struct Spec {
     expected_output_dimension: Option<u32>,
     task_type: Option<String>,
     api_key: Option<AuthEntryReference<String>>,
+    /// When set, embeddings are computed through the provider's asynchronous batch API.
+    batch_api: Option<BatchApiSpec>,
+}
+
+/// Checks the embedding against the dimension in the output schema and converts it to a value.
+pub(super) fn embedding_to_value(
+    embedding: Vec<f32>,
+    expected_output_dimension: usize,
+    output_dimension: Option<u32>,
+) -> Result<value::Value> {
+    if embedding.len() != expected_output_dimension {
+        if output_dimension.is_some() {
+            api_bail!(
+                "Expected output dimension {expected} but got {actual} from the embedding API. \
+                 Consider setting `output_dimension` to {actual} or leave it unset to use the default.",
+                expected = expected_output_dimension,
+                actual = embedding.len(),
+            );
+        } else {
+            client_bail!(
+                "Expected output dimension {expected} but got {actual} from the embedding API. \
+                 Consider setting `output_dimension` to {actual} as a workaround.",
+                expected = expected_output_dimension,
+                actual = embedding.len(),
+            );
+        }
+    };
+    Ok(embedding.into())
+}
+
+/// Resolves the dimension of the output vectors from the spec, falling back to the model's default.
+pub(super) fn resolve_expected_output_dimension(
+    api_type: LlmApiType,
+    model: &str,
+    expected_output_dimension: Option<u32>,
+    output_dimension: Option<u32>,
+    client: &dyn LlmEmbeddingClient,
+) -> Result<usize> {
+    // Warn if both parameters are specified but have different values
+    if let (Some(expected), Some(output)) = (expected_output_dimension, output_dimension) {
+        if expected != output {
+            warn!(
+                "Both `expected_output_dimension` ({expected}) and `output_dimension` ({output}) are specified but have different values. \
+                 `expected_output_dimension` will be used for output schema and validation, while `output_dimension` will be sent to the embedding API."
+            );
+        }
+    }
+
+    let dimension = expected_output_dimension
+        .or(output_dimension)
+        .or_else(|| client.get_default_embedding_dimension(model))
+        .ok_or_else(|| api_error!("model \"{}\" is unknown for {:?}, needs to specify `expected_output_dimension` (or `output_dimension`) explicitly", model, api_type))?;
+    Ok(dimension as usize)
 }
 
 struct Args {
     client: Box<dyn LlmEmbeddingClient>,
+    #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+    batch_client: Option<Box<dyn LlmBatchClient>>,
     text: ResolvedOpArg,
     expected_output_dimension: usize,
 }
@@ -38,6 +96,58 @@ struct Args {
 struct Executor {
     spec: Spec,
     args: Args,
+    #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+    batch_runner: Option<LlmBatchRunner>,
+}
+
+impl Executor {
+    fn task_type(&self) -> Option<Cow<'_, str>> {
+        self.spec
+            .task_type
+            .as_ref()
+            .map(|s| Cow::Borrowed(s.as_str()))
+    }
+
+    fn validate_embedding(&self, embedding: Vec<f32>) -> Result<value::Value> {
+        embedding_to_value(
+            embedding,
+            self.args.expected_output_dimension,
+            self.spec.output_dimension,
+        )
+    }
+
+    #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+    async fn evaluate_batch_api(
+        &self,
+        batch_runner: &LlmBatchRunner,
+        args: Vec<Vec<Value>>,
+    ) -> Result<Vec<Value>> {
+        let client = batch_runner.client();
+        // One request per row, so each row is matched to its own result.
+        let bodies = args
+            .iter()
+            .map(|arg| {
+                client.embedding_request_body(&LlmEmbeddingRequest {
+                    model: &self.spec.model,
+                    texts: vec![Cow::Borrowed(self.args.text.value(arg)?.as_str()?.as_ref())],
+                    output_dimension: self.spec.output_dimension,
+                    task_type: self.task_type(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        batch_runner
+            .run(bodies)
+            .await?
+            .into_iter()
+            .map(|body| {
+                let mut embeddings = client.parse_embedding_response(body)?;
+                match (embeddings.pop(), embeddings.is_empty()) {
+                    (Some(embedding), true) => self.validate_embedding(embedding),
+                    _ => client_bail!("Expected exactly one embedding per batch API response"),
+                }
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -46,8 +156,19 @@ impl BatchedFunctionExecutor for Executor {
         true
     }
 
+    #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+    fn timeout(&self) -> Option<std::time::Duration> {
+        self.batch_runner.as_ref().map(|runner| runner.timeout())
+    }
+
     #[cfg(feature = "batching")]
     fn batching_options(&self) -> batching::BatchingOptions {
+        #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+        if let Some(batch_runner) = &self.batch_runner {
+            return batching::BatchingOptions {
+                max_batch_size: Some(batch_runner.max_batch_size()),
+            };
+        }
         // A safe default for most embeddings providers.
         // May tune it for specific providers later.
         batching::BatchingOptions {
@@ -56,6 +177,10 @@ impl BatchedFunctionExecutor for Executor {
     }
 
     async fn evaluate_batch(&self, args: Vec<Vec<Value>>) -> Result<Vec<Value>> {
+        #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+        if let Some(batch_runner) = &self.batch_runner {
+            return self.evaluate_batch_api(batch_runner, args).await;
+        }
         let texts = args
             .iter()
             .map(|arg| Ok(Cow::Borrowed(self.args.text.value(arg)?.as_str()?.as_ref())))
@@ -64,11 +189,7 @@ impl BatchedFunctionExecutor for Executor {
             model: &self.spec.model,
             texts,
             output_dimension: self.spec.output_dimension,
-            task_type: self
-                .spec
-                .task_type
-                .as_ref()
-                .map(|s| Cow::Borrowed(s.as_str())),
+            task_type: self.task_type(),
         };
         let resp = self.args.client.embed_text(req).await?;
         if resp.embeddings.len() != args.len() {
@@ -80,26 +201,7 @@ impl BatchedFunctionExecutor for Executor {
         }
         resp.embeddings
             .into_iter()
-            .map(|embedding| {
-                if embedding.len() != self.args.expected_output_dimension {
-                    if self.spec.output_dimension.is_some() {
-                        api_bail!(
-                            "Expected output dimension {expected} but got {actual} from the embedding API. \
-                             Consider setting `output_dimension` to {actual} or leave it unset to use the default.",
-                            expected = self.args.expected_output_dimension,
-                            actual = embedding.len(),
-                        );
-                    } else {
-                        client_bail!(
-                            "Expected output dimension {expected} but got {actual} from the embedding API. \
-                             Consider setting `output_dimension` to {actual} as a workaround.",
-                            expected = self.args.expected_output_dimension,
-                            actual = embedding.len(),
-                        );
-                    }
-                };
-                Ok(embedding.into())
-            })
+            .map(|embedding| self.validate_embedding(embedding))
             .collect::<Result<Vec<value::Value>>>()
     }
 }
@@ -135,27 +237,34 @@ impl SimpleFunctionFactoryBase for Factory {
         let client = new_llm_embedding_client(
             spec.api_type,
             spec.address.clone(),
-            api_key,
+            api_key.clone(),
             spec.api_config.clone(),
         )
         .await?;
 
-        // Warn if both parameters are specified but have different values
-        if let (Some(expected), Some(output)) =
-            (spec.expected_output_dimension, spec.output_dimension)
-        {
-            if expected != output {
-                warn!(
-                    "Both `expected_output_dimension` ({expected}) and `output_dimension` ({output}) are specified but have different values. \
-                     `expected_output_dimension` will be used for output schema and validation, while `output_dimension` will be sent to the embedding API."
-                );
-            }
+        #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+        let batch_client = if spec.batch_api.is_some() {
+            Some(new_llm_batch_client(
+                spec.api_type,
+                spec.address.clone(),
+                api_key.clone(),
+                LlmBatchEndpoint::Embeddings,
+            )?)
+        } else {
+            None
+        };
+        #[cfg(not(any(feature = "provider-openai", feature = "provider-anthropic")))]
+        if spec.batch_api.is_some() {
+            api_bail!("`batch_api` requires the `provider-openai` or `provider-anthropic` feature");
         }
 
-        let expected_output_dimension = spec.expected_output_dimension
-            .or(spec.output_dimension)
-            .or_else(|| client.get_default_embedding_dimension(spec.model.as_str()))
-            .ok_or_else(|| api_error!("model \"{}\" is unknown for {:?}, needs to specify `expected_output_dimension` (or `output_dimension`) explicitly", spec.model, spec.api_type))? as usize;
+        let expected_output_dimension = resolve_expected_output_dimension(
+            spec.api_type,
+            &spec.model,
+            spec.expected_output_dimension,
+            spec.output_dimension,
+            client.as_ref(),
+        )?;
         let output_schema = make_output_type(BasicValueType::Vector(VectorTypeSchema {
             dimension: Some(expected_output_dimension),
             element_type: Box::new(BasicValueType::Float32),
@@ -164,6 +273,8 @@ impl SimpleFunctionFactoryBase for Factory {
             behavior_version: client.behavior_version(),
             resolved_args: Args {
                 client,
+                #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+                batch_client,
                 text,
                 expected_output_dimension,
             },
@@ -175,9 +286,34 @@ impl SimpleFunctionFactoryBase for Factory {
         self: Arc<Self>,
         spec: Spec,
         args: Args,
-        _context: Arc<FlowInstanceContext>,
+        context: Arc<FlowInstanceContext>,
     ) -> Result<impl SimpleFunctionExecutor> {
-        Ok(Executor { spec, args }.into_fn_executor())
+        // Only the batch API needs the flow context.
+        #[cfg(not(any(feature = "provider-openai", feature = "provider-anthropic")))]
+        let _ = context;
+        #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+        let (args, batch_runner) = {
+            let mut args = args;
+            let batch_runner = match (args.batch_client.take(), &spec.batch_api) {
+                (Some(batch_client), Some(batch_api)) => {
+                    Some(LlmBatchRunner::new(batch_client, batch_api, context))
+                }
+                _ => None,
+            };
+            (args, batch_runner)
+        };
+        Ok(Executor {
+            spec,
+            args,
+            #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+            batch_runner,
+        }
+        .into_fn_executor())
+    }
+
+    fn uses_state_store(&self, spec: &Spec) -> bool {
+        // Pending batch jobs are recorded in the state store.
+        spec.batch_api.is_some()
     }
 }
 
@@ -203,6 +339,7 @@ mod tests {
             expected_output_dimension: None,
             task_type: None,
             api_key: None,
+            batch_api: None,
         };
 
         let factory = Arc::new(Factory);
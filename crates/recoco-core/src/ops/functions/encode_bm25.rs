@@ -0,0 +1,209 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+use crate::prelude::*;
+
+use crate::ops::registry::ExecutorFactoryRegistry;
+use utils::fingerprint::Fingerprinter;
+
+const DEFAULT_K1: f32 = 1.2;
+const DEFAULT_B: f32 = 0.75;
+const DEFAULT_AVG_DOC_LENGTH: f32 = 256.0;
+
+/// Encodes text into a BM25-style sparse vector, with hashed terms as indices.
+///
+/// Values only carry the term frequency component of BM25. The IDF component depends on the
+/// whole corpus, so it's expected to be applied by the vector store (e.g. Qdrant's IDF modifier).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spec {
+    /// Term frequency saturation. Defaults to 1.2.
+    k1: Option<f32>,
+    /// Document length normalization. Defaults to 0.75.
+    b: Option<f32>,
+    /// Expected average document length in tokens. Defaults to 256.
+    avg_doc_length: Option<f32>,
+    /// Encode the text as a query: each distinct term gets a weight of 1.
+    #[serde(default)]
+    query: bool,
+}
+
+struct Args {
+    text: ResolvedOpArg,
+}
+
+struct Executor {
+    args: Args,
+    k1: f32,
+    b: f32,
+    avg_doc_length: f32,
+    query: bool,
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+}
+
+fn term_index(term: &str) -> u32 {
+    let mut fingerprinter = Fingerprinter::default();
+    fingerprinter.write_raw_bytes(term.as_bytes());
+    let fingerprint = fingerprinter.into_fingerprint();
+    let bytes = fingerprint.as_slice();
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+impl Executor {
+    fn encode(&self, text: &str) -> SparseVectorValue {
+        let mut term_freqs = HashMap::<String, u32>::new();
+        let mut doc_length = 0usize;
+        for token in tokenize(text) {
+            *term_freqs.entry(token).or_default() += 1;
+            doc_length += 1;
+        }
+        let length_norm = 1.0 - self.b + self.b * doc_length as f32 / self.avg_doc_length;
+        SparseVectorValue::from_pairs(term_freqs.into_iter().map(|(term, freq)| {
+            let weight = if self.query {
+                1.0
+            } else {
+                let freq = freq as f32;
+                freq * (self.k1 + 1.0) / (freq + self.k1 * length_norm)
+            };
+            (term_index(&term), weight)
+        }))
+    }
+}
+
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let text = self.args.text.value(&input)?.as_str()?;
+        Ok(BasicValue::from(self.encode(text)).into())
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = Spec;
+    type ResolvedArgs = Args;
+
+    fn name(&self) -> &str {
+        "EncodeBm25"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        spec: &'a Spec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        _context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
+        if spec.k1.is_some_and(|k1| k1 < 0.0) {
+            api_bail!("`k1` must be non-negative");
+        }
+        if spec.b.is_some_and(|b| !(0.0..=1.0).contains(&b)) {
+            api_bail!("`b` must be between 0 and 1");
+        }
+        if spec.avg_doc_length.is_some_and(|l| l <= 0.0) {
+            api_bail!("`avg_doc_length` must be positive");
+        }
+        let args = Args {
+            text: args_resolver
+                .next_arg("text")?
+                .expect_type(&ValueType::Basic(BasicValueType::Str))?
+                .required()?,
+        };
+        Ok(SimpleFunctionAnalysisOutput {
+            resolved_args: args,
+            output_schema: make_output_type(BasicValueType::SparseVector),
+            behavior_version: None,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        spec: Spec,
+        args: Args,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        Ok(Executor {
+            args,
+            k1: spec.k1.unwrap_or(DEFAULT_K1),
+            b: spec.b.unwrap_or(DEFAULT_B),
+            avg_doc_length: spec.avg_doc_length.unwrap_or(DEFAULT_AVG_DOC_LENGTH),
+            query: spec.query,
+        })
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::functions::test_utils::test_flow_function;
+
+    async fn encode(spec: Spec, text: &str) -> SparseVectorValue {
+        let input_arg_schemas = &[(Some("text"), make_output_type(BasicValueType::Str))];
+        let result = test_flow_function(
+            &Arc::new(Factory),
+            &spec,
+            input_arg_schemas,
+            vec![text.to_string().into()],
+        )
+        .await
+        .unwrap();
+        match result {
+            Value::Basic(BasicValue::SparseVector(v)) => (*v).clone(),
+            other => panic!("Expected sparse vector, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encode_bm25() {
+        let spec = Spec {
+            k1: None,
+            b: Some(0.0),
+            avg_doc_length: None,
+            query: false,
+        };
+        let doc = encode(spec.clone(), "The cat sat. the CAT ran!").await;
+        doc.validate().unwrap();
+        assert_eq!(doc.len(), 4);
+
+        let weight_of = |v: &SparseVectorValue, term: &str| {
+            let pos = v.indices.iter().position(|i| *i == term_index(term));
+            pos.map(|pos| v.values[pos])
+        };
+        // With b = 0, the weight only depends on the term frequency.
+        let once = 2.2 / (1.0 + 1.2);
+        let twice = 2.0 * 2.2 / (2.0 + 1.2);
+        assert!((weight_of(&doc, "sat").unwrap() - once).abs() < 1e-6);
+        assert!((weight_of(&doc, "cat").unwrap() - twice).abs() < 1e-6);
+
+        let query = encode(
+            Spec {
+                query: true,
+                ..spec
+            },
+            "cat cat dog",
+        )
+        .await;
+        assert_eq!(query.len(), 2);
+        assert!(query.values.iter().all(|v| *v == 1.0));
+        assert_eq!(weight_of(&query, "cat"), Some(1.0));
+    }
+}
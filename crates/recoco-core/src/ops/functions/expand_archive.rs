@@ -0,0 +1,257 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Expands archive content, e.g. from object storage sources, into a table of its members.
+
+use std::io::Cursor;
+
+use crate::ops::registry::ExecutorFactoryRegistry;
+use crate::ops::shared::archive::{self, ArchiveFormat, ArchiveLimits};
+use crate::{fields_value, ops::sdk::*};
+use recoco_splitters::pattern_matcher::PatternMatcher;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Spec {
+    /// If true, members are output as bytes, otherwise as text. Defaults to false.
+    #[serde(default)]
+    binary: bool,
+    /// Patterns matched against paths of members within the archive.
+    included_patterns: Option<Vec<String>>,
+    excluded_patterns: Option<Vec<String>>,
+    /// Members larger than this (uncompressed) are skipped.
+    max_member_size: Option<i64>,
+    /// Maximum total uncompressed size of members read from the archive. Defaults to 1 GiB.
+    max_total_size: Option<i64>,
+    /// Maximum number of members read from the archive. Defaults to 100,000.
+    max_members: Option<i64>,
+}
+
+pub struct Args {
+    content: ResolvedOpArg,
+    /// Name or path of the archive, whose extension determines the format.
+    filename: ResolvedOpArg,
+}
+
+struct Executor {
+    args: Args,
+    binary: bool,
+    pattern_matcher: PatternMatcher,
+    limits: ArchiveLimits,
+}
+
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let content = self.args.content.value(&input)?.as_bytes()?;
+        let filename = self.args.filename.value(&input)?.as_str()?;
+        if ArchiveFormat::from_path(filename).is_none() {
+            client_bail!("`{filename}` is not a zip, tar or gzip archive");
+        }
+        let members = archive::read_members(
+            filename,
+            Cursor::new(content.as_ref()),
+            &self.pattern_matcher,
+            &self.limits,
+            true,
+        )?;
+        let table = members
+            .into_iter()
+            .filter_map(|member| {
+                let content = member.content?;
+                let value = if self.binary {
+                    fields_value!(content)
+                } else {
+                    let (s, _) = utils::bytes_decode::bytes_to_string(&content);
+                    fields_value!(s)
+                };
+                Some((KeyValue::from_single_part(member.path), value.into()))
+            })
+            .collect();
+        Ok(Value::KTable(table))
+    }
+
+    fn enable_cache(&self) -> bool {
+        true
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = Spec;
+    type ResolvedArgs = Args;
+
+    fn name(&self) -> &str {
+        "ExpandArchive"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        spec: &'a Spec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        _context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
+        let args = Args {
+            content: args_resolver
+                .next_arg("content")?
+                .expect_type(&ValueType::Basic(BasicValueType::Bytes))?
+                .required()?,
+            filename: args_resolver
+                .next_arg("filename")?
+                .expect_type(&ValueType::Basic(BasicValueType::Str))?
+                .required()?,
+        };
+
+        let mut member_schema = StructSchema::default();
+        let mut sb = StructSchemaBuilder::new(&mut member_schema);
+        sb.add_field(FieldSchema::new(
+            "path",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "content",
+            make_output_type(if spec.binary {
+                BasicValueType::Bytes
+            } else {
+                BasicValueType::Str
+            }),
+        ));
+        Ok(SimpleFunctionAnalysisOutput {
+            resolved_args: args,
+            output_schema: make_output_type(TableSchema::new(
+                TableKind::KTable(KTableInfo { num_key_parts: 1 }),
+                member_schema,
+            )),
+            behavior_version: None,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        spec: Spec,
+        args: Args,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        let max_total_size = spec
+            .max_total_size
+            .unwrap_or(archive::DEFAULT_MAX_TOTAL_SIZE)
+            .max(0) as u64;
+        Ok(Executor {
+            args,
+            binary: spec.binary,
+            pattern_matcher: PatternMatcher::new(spec.included_patterns, spec.excluded_patterns)?,
+            limits: ArchiveLimits {
+                max_member_size: spec
+                    .max_member_size
+                    .map_or(max_total_size, |size| size.max(0) as u64),
+                max_total_size,
+                max_members: spec
+                    .max_members
+                    .unwrap_or(archive::DEFAULT_MAX_MEMBERS)
+                    .max(0) as usize,
+            },
+        })
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::functions::test_utils::test_flow_function;
+    use std::io::Write;
+
+    fn zip_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, content) in files {
+            writer
+                .start_file(*path, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn spec(binary: bool) -> Spec {
+        Spec {
+            binary,
+            included_patterns: None,
+            excluded_patterns: Some(vec!["**/*.bin".to_string()]),
+            max_member_size: None,
+            max_total_size: None,
+            max_members: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expand_archive() -> Result<()> {
+        let factory = Arc::new(Factory);
+        let input_arg_schemas = &[
+            (Some("content"), make_output_type(BasicValueType::Bytes)),
+            (Some("filename"), make_output_type(BasicValueType::Str)),
+        ];
+        let data = zip_archive(&[("docs/a.md", b"# A"), ("b.txt", b"b"), ("c.bin", b"\x00")]);
+        let input = || -> Vec<Value> {
+            vec![
+                Value::Basic(BasicValue::Bytes(data.clone().into())),
+                "s3/bucket/docs.zip".to_string().into(),
+            ]
+        };
+
+        let result = test_flow_function(&factory, &spec(false), input_arg_schemas, input()).await?;
+        let Value::KTable(table) = result else {
+            panic!("Expected KTable, got {result:?}");
+        };
+        let members = table
+            .iter()
+            .map(|(key, row)| {
+                (
+                    key.single_part().unwrap().str_value().unwrap().to_string(),
+                    row.0.fields[0].as_str().unwrap().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            members,
+            [
+                ("b.txt".to_string(), "b".to_string()),
+                ("docs/a.md".to_string(), "# A".to_string()),
+            ]
+        );
+
+        let result = test_flow_function(&factory, &spec(true), input_arg_schemas, input()).await?;
+        let Value::KTable(table) = result else {
+            panic!("Expected KTable, got {result:?}");
+        };
+        let row = table
+            .get(&KeyValue::from_single_part("b.txt".to_string()))
+            .unwrap();
+        assert_eq!(row.0.fields[0].as_bytes()?.as_ref(), b"b");
+
+        let not_archive = test_flow_function(
+            &factory,
+            &spec(false),
+            input_arg_schemas,
+            vec![
+                Value::Basic(BasicValue::Bytes(data.into())),
+                "docs.md".to_string().into(),
+            ],
+        )
+        .await;
+        assert!(not_archive.is_err());
+        Ok(())
+    }
+}
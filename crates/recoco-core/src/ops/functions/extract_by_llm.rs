@@ -11,9 +11,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::llm::{
-    GeneratedOutput, LlmGenerateRequest, LlmGenerationClient, LlmSpec, OutputFormat,
-    new_llm_generation_client,
+    BatchApiSpec, GeneratedOutput, LlmAttachment, LlmGenerateRequest, LlmGenerationClient, LlmSpec,
+    OutputFormat, detect_image_mime_type, new_llm_generation_client,
 };
+#[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+use crate::llm::{LlmBatchEndpoint, LlmBatchRunner, new_llm_batch_client};
 use crate::ops::sdk::*;
 use crate::prelude::*;
 use base::json_schema::build_json_schema;
@@ -25,15 +27,184 @@ pub struct Spec {
     llm_spec: LlmSpec,
     output_type: EnrichedValueType,
     instruction: Option<String>,
+    /// A Jinja template for the user prompt, e.g. `"Title: {{ title }}\n\n{{ body }}"`.
+    ///
+    /// Each placeholder binds to the op arg with the same name, which can be of any type.
+    /// `text` refers to the `text` arg. Being part of the spec, the template is covered by the
+    /// cache fingerprint, so editing it invalidates cached results.
+    prompt_template: Option<String>,
+    /// When set, extraction runs through the provider's asynchronous batch API.
+    batch_api: Option<BatchApiSpec>,
 }
 
 pub struct Args {
     text: Option<ResolvedOpArg>,
     image: Option<ResolvedOpArg>,
+    attachments: Option<AttachmentsArg>,
+    template_args: Vec<ResolvedOpArg>,
+}
+
+/// A table argument whose rows are attachments: a `content` bytes field, with optional
+/// `mime_type` and `filename` string fields. MIME types are detected from content if absent.
+struct AttachmentsArg {
+    arg: ResolvedOpArg,
+    num_key_parts: usize,
+    content_idx: usize,
+    mime_type_idx: Option<usize>,
+    filename_idx: Option<usize>,
+}
+
+impl AttachmentsArg {
+    fn new(arg: ResolvedOpArg) -> Result<Self> {
+        let ValueType::Table(table_schema) = &arg.typ.typ else {
+            api_bail!(
+                "Expected argument `{}` to be a table, got `{}`",
+                arg.name,
+                arg.typ.typ
+            );
+        };
+        let field_idx = |name: &str, expected_type: BasicValueType| -> Result<Option<usize>> {
+            let Some((idx, field)) = table_schema
+                .row
+                .fields
+                .iter()
+                .enumerate()
+                .find(|(_, f)| f.name == name)
+            else {
+                return Ok(None);
+            };
+            let expected_type = ValueType::Basic(expected_type);
+            if field.value_type.typ != expected_type {
+                api_bail!(
+                    "Expected field `{name}` of `{}` to be of type `{expected_type}`, got `{}`",
+                    arg.name,
+                    field.value_type.typ
+                );
+            }
+            Ok(Some(idx))
+        };
+        let content_idx = field_idx("content", BasicValueType::Bytes)?
+            .ok_or_else(|| api_error!("Rows of `{}` must have a `content` field", arg.name))?;
+        let mime_type_idx = field_idx("mime_type", BasicValueType::Str)?;
+        let filename_idx = field_idx("filename", BasicValueType::Str)?;
+        let num_key_parts = match &table_schema.kind {
+            TableKind::KTable(info) => info.num_key_parts,
+            TableKind::UTable | TableKind::LTable => 0,
+        };
+        Ok(Self {
+            arg,
+            num_key_parts,
+            content_idx,
+            mime_type_idx,
+            filename_idx,
+        })
+    }
+
+    fn collect<'a>(
+        &self,
+        input: &'a [Value],
+        attachments: &mut Vec<LlmAttachment<'a>>,
+    ) -> Result<()> {
+        let rows: Vec<(Option<&'a KeyValue>, &'a ScopeValue)> = match self.arg.value(input)? {
+            Value::Null => return Ok(()),
+            Value::UTable(rows) | Value::LTable(rows) => {
+                rows.iter().map(|row| (None, row)).collect()
+            }
+            Value::KTable(rows) => rows.iter().map(|(k, row)| (Some(k), row)).collect(),
+            v => api_bail!("Expected a table for `{}`, got {}", self.arg.name, v.kind()),
+        };
+        for (key, row) in rows {
+            let field = |idx: usize| -> Result<Option<&'a [u8]>> {
+                let bytes: &'a [u8] = if idx < self.num_key_parts {
+                    let key_part = key
+                        .and_then(|k| k.0.get(idx))
+                        .ok_or_else(|| internal_error!("Missing key part {idx}"))?;
+                    match key_part {
+                        KeyPart::Bytes(v) => v.as_ref(),
+                        KeyPart::Str(v) => v.as_bytes(),
+                        _ => internal_bail!("Unexpected key part type for attachment field"),
+                    }
+                } else {
+                    match &row.0.fields[idx - self.num_key_parts] {
+                        Value::Null => return Ok(None),
+                        Value::Basic(BasicValue::Bytes(v)) => v.as_ref(),
+                        Value::Basic(BasicValue::Str(v)) => v.as_bytes(),
+                        v => internal_bail!("Unexpected attachment field value: {}", v.kind()),
+                    }
+                };
+                Ok(Some(bytes))
+            };
+            let str_field = |idx: Option<usize>| -> Result<Option<Cow<'a, str>>> {
+                let Some(bytes) = idx.map(field).transpose()?.flatten() else {
+                    return Ok(None);
+                };
+                Ok(Some(Cow::Borrowed(
+                    std::str::from_utf8(bytes).map_err(Error::internal)?,
+                )))
+            };
+            let Some(content) = field(self.content_idx)? else {
+                continue;
+            };
+            let name = str_field(self.filename_idx)?;
+            let attachment =
+                LlmAttachment::new(Cow::Borrowed(content), str_field(self.mime_type_idx)?, name)
+                    .with_context(|| format!("Attachment in `{}`", self.arg.name))?;
+            attachments.push(attachment);
+        }
+        Ok(())
+    }
+}
+
+const PROMPT_TEMPLATE_NAME: &str = "prompt";
+
+struct PromptTemplate {
+    env: minijinja::Environment<'static>,
+}
+
+impl PromptTemplate {
+    fn new(template: String) -> Result<Self> {
+        let mut env = minijinja::Environment::new();
+        // Render missing values as empty strings instead of `none`.
+        env.set_formatter(|out, state, value| {
+            minijinja::escape_formatter(
+                out,
+                state,
+                if value.is_none() {
+                    &minijinja::Value::UNDEFINED
+                } else {
+                    value
+                },
+            )
+        });
+        env.add_template_owned(PROMPT_TEMPLATE_NAME, template)
+            .map_err(|e| api_error!("Invalid prompt template: {e}"))?;
+        Ok(Self { env })
+    }
+
+    /// Names of the variables the template reads from its context, in a deterministic order.
+    fn variables(&self) -> Result<Vec<String>> {
+        let template = self.template()?;
+        let mut variables: Vec<String> = template.undeclared_variables(false).into_iter().collect();
+        variables.sort();
+        Ok(variables)
+    }
+
+    fn template(&self) -> Result<minijinja::Template<'_, '_>> {
+        self.env
+            .get_template(PROMPT_TEMPLATE_NAME)
+            .map_err(|e| internal_error!("Prompt template is missing: {e}"))
+    }
+
+    fn render(&self, context: BTreeMap<&str, minijinja::Value>) -> Result<String> {
+        self.template()?
+            .render(context)
+            .map_err(|e| client_error!("Failed to render prompt template: {e}"))
+    }
 }
 
 struct Executor {
     args: Args,
+    prompt_template: Option<PromptTemplate>,
     client: Box<dyn LlmGenerationClient>,
     model: String,
     output_json_schema: Schema,
@@ -82,6 +253,7 @@ impl Executor {
         let schema_output = build_json_schema(spec.output_type, client.json_schema_options())?;
         Ok(Self {
             args,
+            prompt_template: spec.prompt_template.map(PromptTemplate::new).transpose()?,
             client,
             model: spec.llm_spec.model,
             output_json_schema: schema_output.schema,
@@ -91,46 +263,67 @@ impl Executor {
     }
 }
 
-#[async_trait]
-impl SimpleFunctionExecutor for Executor {
-    fn enable_cache(&self) -> bool {
-        true
-    }
-
-    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
-        let image_bytes: Option<Cow<'_, [u8]>> = if let Some(arg) = self.args.image.as_ref()
-            && let Some(value) = arg.value(&input)?.optional()
+impl Executor {
+    /// Returns `None` if there's no input to extract from.
+    fn build_request<'a>(&'a self, input: &'a [Value]) -> Result<Option<LlmGenerateRequest<'a>>> {
+        let mut attachments = Vec::new();
+        if let Some(arg) = self.args.image.as_ref()
+            && let Some(value) = arg.value(input)?.optional()
         {
-            Some(Cow::Borrowed(value.as_bytes()?))
-        } else {
-            None
-        };
+            let image_bytes = value.as_bytes()?;
+            let mime_type = detect_image_mime_type(image_bytes)?;
+            attachments.push(LlmAttachment::new(
+                Cow::Borrowed(image_bytes.as_ref()),
+                Some(Cow::Borrowed(mime_type)),
+                None,
+            )?);
+        }
+        if let Some(arg) = self.args.attachments.as_ref() {
+            arg.collect(input, &mut attachments)?;
+        }
 
         let text = if let Some(arg) = self.args.text.as_ref()
-            && let Some(value) = arg.value(&input)?.optional()
+            && let Some(value) = arg.value(input)?.optional()
         {
             Some(value.as_str()?)
         } else {
             None
         };
 
-        if text.is_none() && image_bytes.is_none() {
-            return Ok(Value::Null);
-        }
+        let user_prompt = if let Some(prompt_template) = &self.prompt_template {
+            let mut context = BTreeMap::new();
+            if let Some(text) = text {
+                context.insert("text", minijinja::Value::from(text.as_ref()));
+            }
+            for arg in self.args.template_args.iter() {
+                let value = value::TypedValue {
+                    t: &arg.typ.typ,
+                    v: arg.value(input)?,
+                };
+                context.insert(arg.name.as_str(), minijinja::Value::from_serialize(value));
+            }
+            Cow::Owned(prompt_template.render(context)?)
+        } else {
+            if text.is_none() && attachments.is_empty() {
+                return Ok(None);
+            }
+            Cow::Borrowed(text.map_or("", |v| v.as_ref()))
+        };
 
-        let user_prompt = text.map_or("", |v| v);
-        let req = LlmGenerateRequest {
+        Ok(Some(LlmGenerateRequest {
             model: &self.model,
             system_prompt: Some(Cow::Borrowed(&self.system_prompt)),
-            user_prompt: Cow::Borrowed(user_prompt),
-            image: image_bytes,
+            user_prompt,
+            attachments,
             output_format: Some(OutputFormat::JsonSchema {
                 name: Cow::Borrowed("ExtractedData"),
                 schema: Cow::Borrowed(&self.output_json_schema),
             }),
-        };
-        let res = self.client.generate(req).await?;
-        let json_value = match res.output {
+        }))
+    }
+
+    fn extract_value(&self, output: GeneratedOutput) -> Result<Value> {
+        let json_value = match output {
             GeneratedOutput::Json(json) => json,
             GeneratedOutput::Text(text) => {
                 internal_bail!("Expected JSON response but got text: {}", text)
@@ -141,6 +334,79 @@ impl SimpleFunctionExecutor for Executor {
     }
 }
 
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    fn enable_cache(&self) -> bool {
+        true
+    }
+
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let Some(req) = self.build_request(&input)? else {
+            return Ok(Value::Null);
+        };
+        let res = self.client.generate(req).await?;
+        self.extract_value(res.output)
+    }
+}
+
+/// Collects rows into jobs for the provider's batch API.
+#[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+struct BatchApiExecutor {
+    executor: Executor,
+    batch_runner: LlmBatchRunner,
+}
+
+#[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+#[async_trait]
+impl BatchedFunctionExecutor for BatchApiExecutor {
+    fn enable_cache(&self) -> bool {
+        true
+    }
+
+    fn timeout(&self) -> Option<std::time::Duration> {
+        Some(self.batch_runner.timeout())
+    }
+
+    #[cfg(feature = "batching")]
+    fn batching_options(&self) -> batching::BatchingOptions {
+        batching::BatchingOptions {
+            max_batch_size: Some(self.batch_runner.max_batch_size()),
+        }
+    }
+
+    async fn evaluate_batch(&self, args: Vec<Vec<Value>>) -> Result<Vec<Value>> {
+        let client = self.batch_runner.client();
+        let bodies = args
+            .iter()
+            .map(|input| {
+                self.executor
+                    .build_request(input)?
+                    .map(|req| client.generation_request_body(&req))
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let has_input = bodies.iter().map(Option::is_some).collect::<Vec<_>>();
+        let mut responses = self
+            .batch_runner
+            .run(bodies.into_iter().flatten().collect())
+            .await?
+            .into_iter();
+        has_input
+            .into_iter()
+            .map(|has_input| {
+                if !has_input {
+                    return Ok(Value::Null);
+                }
+                let body = responses
+                    .next()
+                    .ok_or_else(|| internal_error!("Missing response from the batch API"))?;
+                self.executor
+                    .extract_value(client.parse_generation_response(body)?)
+            })
+            .collect()
+    }
+}
+
 pub struct Factory;
 
 #[async_trait]
@@ -158,7 +424,7 @@ impl SimpleFunctionFactoryBase for Factory {
         args_resolver: &mut OpArgsResolver<'a>,
         _context: &FlowInstanceContext,
     ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
-        let args = Args {
+        let mut args = Args {
             text: args_resolver
                 .next_arg("text")?
                 .expect_nullable_type(&ValueType::Basic(BasicValueType::Str))?
@@ -167,15 +433,43 @@ impl SimpleFunctionFactoryBase for Factory {
                 .next_arg("image")?
                 .expect_nullable_type(&ValueType::Basic(BasicValueType::Bytes))?
                 .optional(),
+            attachments: args_resolver
+                .next_arg("attachments")?
+                .optional()
+                .map(AttachmentsArg::new)
+                .transpose()?,
+            template_args: vec![],
         };
 
-        if args.text.is_none() && args.image.is_none() {
-            api_bail!("At least one of 'text' or 'image' must be provided");
+        if let Some(prompt_template) = &spec.prompt_template {
+            for name in PromptTemplate::new(prompt_template.clone())?.variables()? {
+                match name.as_str() {
+                    "text" => {
+                        if args.text.is_none() {
+                            api_bail!("Prompt template refers to `text`, but it's not provided");
+                        }
+                    }
+                    "image" | "attachments" => {
+                        api_bail!(
+                            "`{name}` is sent as attachments and can't be used in the prompt template"
+                        )
+                    }
+                    _ => args.template_args.push(
+                        args_resolver.next_arg(&name)?.required().with_context(|| {
+                            format!("Argument for placeholder `{name}` in the prompt template")
+                        })?,
+                    ),
+                }
+            }
+        } else if args.text.is_none() && args.image.is_none() && args.attachments.is_none() {
+            api_bail!("At least one of 'text', 'image' or 'attachments' must be provided");
         }
 
         let mut output_type = spec.output_type.clone();
-        if args.text.as_ref().is_none_or(|arg| arg.typ.nullable)
+        if spec.prompt_template.is_none()
+            && args.text.as_ref().is_none_or(|arg| arg.typ.nullable)
             && args.image.as_ref().is_none_or(|arg| arg.typ.nullable)
+            && args.attachments.is_none()
         {
             output_type.nullable = true;
         }
@@ -192,15 +486,186 @@ impl SimpleFunctionFactoryBase for Factory {
         resolved_input_schema: Args,
         context: Arc<FlowInstanceContext>,
     ) -> Result<impl SimpleFunctionExecutor> {
-        Executor::new(spec, resolved_input_schema, &context.auth_registry).await
+        #[cfg(not(any(feature = "provider-openai", feature = "provider-anthropic")))]
+        if spec.batch_api.is_some() {
+            api_bail!("`batch_api` requires the `provider-openai` or `provider-anthropic` feature");
+        }
+        #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+        let batch_runner = if let Some(batch_api) = &spec.batch_api {
+            let api_key = spec
+                .llm_spec
+                .api_key
+                .as_ref()
+                .map(|key_ref| context.auth_registry.get(key_ref))
+                .transpose()?;
+            let batch_client = new_llm_batch_client(
+                spec.llm_spec.api_type,
+                spec.llm_spec.address.clone(),
+                api_key,
+                LlmBatchEndpoint::Generation,
+            )?;
+            Some(LlmBatchRunner::new(
+                batch_client,
+                batch_api,
+                context.clone(),
+            ))
+        } else {
+            None
+        };
+        let executor = Executor::new(spec, resolved_input_schema, &context.auth_registry).await?;
+        #[cfg(any(feature = "provider-openai", feature = "provider-anthropic"))]
+        let executor: Box<dyn SimpleFunctionExecutor> = match batch_runner {
+            Some(batch_runner) => Box::new(
+                BatchApiExecutor {
+                    executor,
+                    batch_runner,
+                }
+                .into_fn_executor(),
+            ),
+            None => Box::new(executor),
+        };
+        Ok(executor)
+    }
+
+    fn uses_state_store(&self, spec: &Spec) -> bool {
+        // Pending batch jobs are recorded in the state store.
+        spec.batch_api.is_some()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llm::LlmAttachmentKind;
     use crate::ops::functions::test_utils::{build_arg_schema, test_flow_function};
 
+    #[test]
+    fn test_prompt_template_renders_structured_values() {
+        let template = PromptTemplate::new(
+            "{{ title }} ({{ meta.lang }}){% for s in sections %}\n- {{ s.name }}{% endfor %}{{ missing_summary }}"
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            template.variables().unwrap(),
+            vec!["meta", "missing_summary", "sections", "title"]
+        );
+
+        let name_field = || FieldSchema::new("name", make_output_type(BasicValueType::Str));
+        let meta_type = ValueType::Struct(StructSchema {
+            fields: Arc::new(vec![FieldSchema::new(
+                "lang",
+                make_output_type(BasicValueType::Str),
+            )]),
+            description: None,
+        });
+        let meta_value = Value::Struct(FieldValues {
+            fields: vec![Value::from("en".to_string())],
+        });
+        let sections_type = ValueType::Table(TableSchema {
+            kind: TableKind::LTable,
+            row: StructSchema {
+                fields: Arc::new(vec![name_field()]),
+                description: None,
+            },
+        });
+        let sections_value = Value::LTable(vec![
+            ScopeValue(FieldValues {
+                fields: vec![Value::from("Intro".to_string())],
+            }),
+            ScopeValue(FieldValues {
+                fields: vec![Value::from("Usage".to_string())],
+            }),
+        ]);
+
+        let context = BTreeMap::from([
+            ("title", minijinja::Value::from("Guide")),
+            (
+                "meta",
+                minijinja::Value::from_serialize(value::TypedValue {
+                    t: &meta_type,
+                    v: &meta_value,
+                }),
+            ),
+            (
+                "sections",
+                minijinja::Value::from_serialize(value::TypedValue {
+                    t: &sections_type,
+                    v: &sections_value,
+                }),
+            ),
+            ("missing_summary", minijinja::Value::from(())),
+        ]);
+        assert_eq!(
+            template.render(context).unwrap(),
+            "Guide (en)\n- Intro\n- Usage"
+        );
+    }
+
+    #[test]
+    fn test_attachments_arg_collects_rows() {
+        let row_schema = StructSchema {
+            fields: Arc::new(vec![
+                FieldSchema::new("filename", make_output_type(BasicValueType::Str)),
+                FieldSchema::new("content", make_output_type(BasicValueType::Bytes)),
+                FieldSchema::new(
+                    "mime_type",
+                    make_output_type(BasicValueType::Str).with_nullable(true),
+                ),
+            ]),
+            description: None,
+        };
+        let arg = AttachmentsArg::new(ResolvedOpArg {
+            name: "attachments".to_string(),
+            typ: make_output_type(TableSchema::new(
+                TableKind::KTable(KTableInfo { num_key_parts: 1 }),
+                row_schema,
+            )),
+            idx: 0,
+        })
+        .unwrap();
+
+        let row = |filename: &str, content: &'static [u8], mime_type: Option<&str>| {
+            (
+                KeyValue::from(vec![KeyPart::Str(Arc::from(filename))]),
+                ScopeValue(FieldValues {
+                    fields: vec![
+                        Value::Basic(BasicValue::Bytes(bytes::Bytes::from_static(content))),
+                        mime_type.map_or(Value::Null, |m| Value::from(m.to_string())),
+                    ],
+                }),
+            )
+        };
+        let input = vec![Value::KTable(BTreeMap::from([
+            row("a.pdf", b"%PDF-1.7\n", None),
+            row("data.json", b"{\"a\": 1}", Some("application/json")),
+            row("notes.txt", b"hello", None),
+        ]))];
+
+        let mut attachments = Vec::new();
+        arg.collect(&input, &mut attachments).unwrap();
+        let summary: Vec<_> = attachments
+            .iter()
+            .map(|a| (a.kind, a.mime_type.as_ref(), a.name.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (LlmAttachmentKind::Pdf, "application/pdf", Some("a.pdf")),
+                (
+                    LlmAttachmentKind::Text,
+                    "application/json",
+                    Some("data.json")
+                ),
+                (LlmAttachmentKind::Text, "text/plain", Some("notes.txt")),
+            ]
+        );
+        assert_eq!(
+            attachments[2].to_prompt_text().unwrap(),
+            "<attachment name=\"notes.txt\">\nhello\n</attachment>"
+        );
+    }
+
     #[cfg(feature = "provider-openai")]
     #[tokio::test]
     #[ignore = "This test requires an OpenAI API key or a configured local LLM and may make network calls."]
@@ -236,6 +701,8 @@ mod tests {
             },
             output_type: output_type_spec,
             instruction: Some("Extract the name and value from the text. The name is a string, the value is an integer.".to_string()),
+            prompt_template: None,
+            batch_api: None,
         };
 
         let factory = Arc::new(Factory);
@@ -307,6 +774,8 @@ mod tests {
             },
             output_type: make_output_type(BasicValueType::Str),
             instruction: None,
+            prompt_template: None,
+            batch_api: None,
         };
         let input_arg_schemas = &[
             (
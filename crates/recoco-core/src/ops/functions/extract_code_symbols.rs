@@ -0,0 +1,228 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use crate::base::field_attrs;
+use crate::ops::shared::split::{output_position_schema, output_position_to_value};
+use crate::{fields_value, ops::sdk::*};
+use recoco_splitters::symbols;
+
+/// Separator used to join the enclosing symbol names into the `parent` field.
+const PARENT_PATH_SEPARATOR: &str = ".";
+
+pub struct Args {
+    text: ResolvedOpArg,
+    language: ResolvedOpArg,
+}
+
+struct Executor {
+    args: Args,
+}
+
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let text = self.args.text.value(&input)?.as_str()?;
+        let language = self.args.language.value(&input)?.as_str()?;
+
+        // Languages without a grammar or symbol query yield an empty table.
+        let symbols = symbols::extract_symbols(text, language).unwrap_or_default();
+        let table = symbols
+            .into_iter()
+            .map(|symbol| {
+                let parent = if symbol.parent_path.is_empty() {
+                    Value::Null
+                } else {
+                    symbol.parent_path.join(PARENT_PATH_SEPARATOR).into()
+                };
+                (
+                    KeyValue::from_single_part(RangeValue::new(
+                        symbol.start.char_offset,
+                        symbol.end.char_offset,
+                    )),
+                    fields_value!(
+                        symbol.name,
+                        symbol.kind.as_str().to_string(),
+                        parent,
+                        symbol.signature,
+                        symbol.doc.map(Value::from).unwrap_or(Value::Null),
+                        output_position_to_value(symbol.start),
+                        output_position_to_value(symbol.end)
+                    )
+                    .into(),
+                )
+            })
+            .collect();
+        Ok(Value::KTable(table))
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = EmptySpec;
+    type ResolvedArgs = Args;
+
+    fn name(&self) -> &str {
+        "ExtractCodeSymbols"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        _spec: &'a EmptySpec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        _context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
+        let args = Args {
+            text: args_resolver
+                .next_arg("text")?
+                .expect_type(&ValueType::Basic(BasicValueType::Str))?
+                .required()?,
+            language: args_resolver
+                .next_arg("language")?
+                .expect_type(&ValueType::Basic(BasicValueType::Str))?
+                .required()?,
+        };
+
+        let mut struct_schema = StructSchema::default();
+        let mut sb = StructSchemaBuilder::new(&mut struct_schema);
+        sb.add_field(FieldSchema::new(
+            "location",
+            make_output_type(BasicValueType::Range),
+        ));
+        sb.add_field(FieldSchema::new(
+            "name",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "kind",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "parent",
+            make_output_type(BasicValueType::Str).with_nullable(true),
+        ));
+        sb.add_field(FieldSchema::new(
+            "signature",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "doc",
+            make_output_type(BasicValueType::Str).with_nullable(true),
+        ));
+        sb.add_field(FieldSchema::new(
+            "start",
+            make_output_type(output_position_schema()),
+        ));
+        sb.add_field(FieldSchema::new(
+            "end",
+            make_output_type(output_position_schema()),
+        ));
+        let output_schema = make_output_type(TableSchema::new(
+            TableKind::KTable(KTableInfo { num_key_parts: 1 }),
+            struct_schema,
+        ))
+        .with_attr(
+            field_attrs::CHUNK_BASE_TEXT,
+            serde_json::to_value(args_resolver.get_analyze_value(&args.text))?,
+        );
+        Ok(SimpleFunctionAnalysisOutput {
+            resolved_args: args,
+            output_schema,
+            behavior_version: None,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        _spec: EmptySpec,
+        args: Args,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        Ok(Executor { args })
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::functions::test_utils::{build_arg_schema, test_flow_function};
+
+    #[tokio::test]
+    async fn test_extract_code_symbols_unsupported_language() {
+        let factory = Arc::new(Factory);
+        let input_arg_schemas = &[
+            build_arg_schema("text", BasicValueType::Str),
+            build_arg_schema("language", BasicValueType::Str),
+        ];
+
+        let result = test_flow_function(
+            &factory,
+            &EmptySpec {},
+            input_arg_schemas,
+            vec![
+                "plain text".to_string().into(),
+                "unknown".to_string().into(),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, Value::KTable(Default::default()));
+    }
+
+    #[cfg(feature = "splitter-language-rust")]
+    #[tokio::test]
+    async fn test_extract_code_symbols() {
+        let factory = Arc::new(Factory);
+        let input_arg_schemas = &[
+            build_arg_schema("text", BasicValueType::Str),
+            build_arg_schema("language", BasicValueType::Str),
+        ];
+        let text = "/// A point.\nstruct Point;\n\nimpl Point {\n    fn norm(&self) -> f64 {\n        0.0\n    }\n}\n";
+
+        let result = test_flow_function(
+            &factory,
+            &EmptySpec {},
+            input_arg_schemas,
+            vec![text.to_string().into(), "rust".to_string().into()],
+        )
+        .await
+        .unwrap();
+        let Value::KTable(table) = result else {
+            panic!("Expected Value::KTable, got {result:?}");
+        };
+        assert_eq!(table.len(), 2);
+
+        let point = table
+            .get(&KeyValue::from_single_part(RangeValue::new(13, 26)))
+            .unwrap();
+        assert_eq!(point.0.fields[0], "Point".to_string().into());
+        assert_eq!(point.0.fields[1], "struct".to_string().into());
+        assert_eq!(point.0.fields[2], Value::Null);
+        assert_eq!(point.0.fields[4], "A point.".to_string().into());
+
+        let norm = table
+            .get(&KeyValue::from_single_part(RangeValue::new(45, 86)))
+            .unwrap();
+        assert_eq!(norm.0.fields[0], "norm".to_string().into());
+        assert_eq!(norm.0.fields[1], "method".to_string().into());
+        assert_eq!(norm.0.fields[2], "Point".to_string().into());
+        assert_eq!(norm.0.fields[3], "fn norm(&self) -> f64".to_string().into());
+        assert_eq!(norm.0.fields[4], Value::Null);
+    }
+}
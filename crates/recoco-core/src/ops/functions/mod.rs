@@ -10,18 +10,41 @@
 // Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "function-near-dedup")]
+pub mod compute_minhash;
+#[cfg(feature = "function-near-dedup")]
+pub mod compute_simhash;
+#[cfg(feature = "function-convert-markdown")]
+pub mod convert_to_markdown;
+#[cfg(all(
+    feature = "function-near-dedup",
+    feature = "target-near-duplicate-index"
+))]
+pub mod detect_near_duplicate;
 #[cfg(feature = "function-detect-lang")]
 pub mod detect_program_lang;
 #[cfg(feature = "function-embed")]
+pub mod embed_image;
+#[cfg(feature = "function-embed")]
 pub mod embed_text;
+#[cfg(feature = "function-bm25")]
+pub mod encode_bm25;
+#[cfg(feature = "function-archive")]
+pub mod expand_archive;
 #[cfg(feature = "function-extract-llm")]
 pub mod extract_by_llm;
+#[cfg(feature = "function-extract-symbols")]
+pub mod extract_code_symbols;
 #[cfg(feature = "function-json")]
 pub mod parse_json;
+#[cfg(feature = "function-redact-pii")]
+pub mod redact_pii;
 #[cfg(feature = "function-split")]
 pub mod split_by_separators;
 #[cfg(feature = "function-split")]
 pub mod split_recursively;
+#[cfg(feature = "function-split-semantic")]
+pub mod split_semantically;
 
 #[cfg(test)]
 mod test_utils;
@@ -0,0 +1,356 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::ops::shared::split::{output_position_schema, output_position_to_value};
+use crate::{fields_value, ops::sdk::*};
+use recoco_splitters::pii::{CustomPiiPattern, PiiDetector, PiiDetectorConfig, PiiKind};
+use utils::fingerprint::Fingerprinter;
+
+const DEFAULT_MASK_CHAR: char = '*';
+/// Number of hex digits of the salted hash kept in a `Hash` replacement.
+const HASH_HEX_DIGITS: usize = 16;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum PiiKindSpec {
+    Email,
+    Phone,
+    Iban,
+    CreditCard,
+    SecretKey,
+    IpAddress,
+}
+
+impl From<PiiKindSpec> for PiiKind {
+    fn from(value: PiiKindSpec) -> Self {
+        match value {
+            PiiKindSpec::Email => PiiKind::Email,
+            PiiKindSpec::Phone => PiiKind::Phone,
+            PiiKindSpec::Iban => PiiKind::Iban,
+            PiiKindSpec::CreditCard => PiiKind::CreditCard,
+            PiiKindSpec::SecretKey => PiiKind::SecretKey,
+            PiiKindSpec::IpAddress => PiiKind::IpAddress,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CustomPatternSpec {
+    /// Kind reported for matches, and used in placeholders.
+    name: String,
+    regex: String,
+}
+
+/// How detected spans are replaced in the output text.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind")]
+pub enum Replacement {
+    /// Replaces each character with `mask_char` (`*` by default), except the last `keep_last`.
+    Mask {
+        #[serde(default)]
+        mask_char: Option<char>,
+        #[serde(default)]
+        keep_last: usize,
+    },
+    /// Replaces with the kind and a salted hash of the value, e.g. `<EMAIL:3f9a...>`. Equal
+    /// values get equal replacements, so they can still be matched up.
+    Hash { salt: String },
+    /// Replaces with the kind, e.g. `<EMAIL>`.
+    Placeholder,
+}
+
+impl Replacement {
+    fn replace(&self, kind: &PiiKind, value: &str) -> String {
+        match self {
+            Replacement::Mask {
+                mask_char,
+                keep_last,
+            } => {
+                let num_chars = value.chars().count();
+                let num_masked = num_chars.saturating_sub(*keep_last);
+                let mask_char = mask_char.unwrap_or(DEFAULT_MASK_CHAR);
+                std::iter::repeat_n(mask_char, num_masked)
+                    .chain(value.chars().skip(num_masked))
+                    .collect()
+            }
+            Replacement::Hash { salt } => {
+                let mut fingerprinter = Fingerprinter::default();
+                fingerprinter.write_raw_bytes(salt.as_bytes());
+                fingerprinter.write_raw_bytes(b"\0");
+                fingerprinter.write_raw_bytes(value.as_bytes());
+                let hash = fingerprinter.into_fingerprint().to_string();
+                format!("<{}:{}>", kind.name(), &hash[1..=HASH_HEX_DIGITS])
+            }
+            Replacement::Placeholder => format!("<{}>", kind.name()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Spec {
+    /// Built-in detectors to run. Defaults to all of them.
+    #[serde(default)]
+    detectors: Option<Vec<PiiKindSpec>>,
+    #[serde(default)]
+    custom_patterns: Vec<CustomPatternSpec>,
+    /// Defaults to `Placeholder`.
+    #[serde(default)]
+    replacement: Option<Replacement>,
+}
+
+pub struct Args {
+    text: ResolvedOpArg,
+}
+
+struct Executor {
+    args: Args,
+    detector: PiiDetector,
+    replacement: Replacement,
+}
+
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let text = self.args.text.value(&input)?.as_str()?;
+        let matches = self.detector.detect(text);
+
+        let mut redacted = String::with_capacity(text.len());
+        let mut last_end = 0;
+        let mut detections = BTreeMap::new();
+        for m in matches {
+            let replacement = self
+                .replacement
+                .replace(&m.kind, &text[m.range.start..m.range.end]);
+            redacted.push_str(&text[last_end..m.range.start]);
+            redacted.push_str(&replacement);
+            last_end = m.range.end;
+            detections.insert(
+                KeyValue::from_single_part(RangeValue::new(m.start.char_offset, m.end.char_offset)),
+                fields_value!(
+                    m.kind.name().to_string(),
+                    replacement,
+                    output_position_to_value(m.start),
+                    output_position_to_value(m.end)
+                )
+                .into(),
+            );
+        }
+        redacted.push_str(&text[last_end..]);
+
+        Ok(Value::Struct(fields_value!(
+            Arc::<str>::from(redacted),
+            Value::KTable(detections)
+        )))
+    }
+
+    fn enable_cache(&self) -> bool {
+        true
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = Spec;
+    type ResolvedArgs = Args;
+
+    fn name(&self) -> &str {
+        "RedactPii"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        spec: &'a Spec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        _context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
+        if let Some(Replacement::Hash { salt }) = &spec.replacement
+            && salt.is_empty()
+        {
+            api_bail!("`salt` must not be empty");
+        }
+        let args = Args {
+            text: args_resolver
+                .next_arg("text")?
+                .expect_type(&ValueType::Basic(BasicValueType::Str))?
+                .required()?,
+        };
+
+        let mut detections_schema = StructSchema::default();
+        let mut sb = StructSchemaBuilder::new(&mut detections_schema);
+        sb.add_field(FieldSchema::new(
+            "location",
+            make_output_type(BasicValueType::Range),
+        ));
+        sb.add_field(FieldSchema::new(
+            "kind",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "replacement",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "start",
+            make_output_type(output_position_schema()),
+        ));
+        sb.add_field(FieldSchema::new(
+            "end",
+            make_output_type(output_position_schema()),
+        ));
+
+        let mut output_schema = StructSchema::default();
+        let mut sb = StructSchemaBuilder::new(&mut output_schema);
+        sb.add_field(FieldSchema::new(
+            "text",
+            make_output_type(BasicValueType::Str),
+        ));
+        sb.add_field(FieldSchema::new(
+            "detections",
+            make_output_type(TableSchema::new(
+                TableKind::KTable(KTableInfo { num_key_parts: 1 }),
+                detections_schema,
+            )),
+        ));
+        Ok(SimpleFunctionAnalysisOutput {
+            resolved_args: args,
+            output_schema: make_output_type(output_schema),
+            behavior_version: None,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        spec: Spec,
+        args: Args,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        let config = PiiDetectorConfig {
+            kinds: match spec.detectors {
+                Some(detectors) => detectors.into_iter().map(PiiKind::from).collect(),
+                None => PiiKind::BUILT_IN.to_vec(),
+            },
+            custom_patterns: spec
+                .custom_patterns
+                .into_iter()
+                .map(|pattern| CustomPiiPattern {
+                    name: pattern.name,
+                    regex: pattern.regex,
+                })
+                .collect(),
+        };
+        let detector =
+            PiiDetector::new(config).map_err(|e| api_error!("Invalid custom PII pattern: {e}"))?;
+        Ok(Executor {
+            args,
+            detector,
+            replacement: spec.replacement.unwrap_or(Replacement::Placeholder),
+        })
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::functions::test_utils::{build_arg_schema, test_flow_function};
+
+    const TEXT: &str = "Mail jane@example.com\nCard 4111 1111 1111 1111, ticket T-98765.";
+
+    async fn redact(spec: Spec, text: &str) -> (String, BTreeMap<KeyValue, ScopeValue>) {
+        let result = test_flow_function(
+            &Arc::new(Factory),
+            &spec,
+            &[build_arg_schema("text", BasicValueType::Str)],
+            vec![text.to_string().into()],
+        )
+        .await
+        .unwrap();
+        match result {
+            Value::Struct(mut fields) => match (fields.fields.remove(0), fields.fields.remove(0)) {
+                (Value::Basic(BasicValue::Str(text)), Value::KTable(detections)) => {
+                    (text.to_string(), detections)
+                }
+                other => panic!("Unexpected output fields: {other:?}"),
+            },
+            other => panic!("Expected struct, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redact_pii_placeholder() {
+        let spec = Spec {
+            detectors: None,
+            custom_patterns: vec![CustomPatternSpec {
+                name: "TICKET".to_string(),
+                regex: r"\bT-\d{5}\b".to_string(),
+            }],
+            replacement: None,
+        };
+        let (text, detections) = redact(spec, TEXT).await;
+        assert_eq!(text, "Mail <EMAIL>\nCard <CREDIT_CARD>, ticket <TICKET>.");
+
+        assert_eq!(detections.len(), 3);
+        let card = detections
+            .get(&KeyValue::from_single_part(RangeValue::new(27, 46)))
+            .unwrap();
+        assert_eq!(card.0.fields[0], Value::from("CREDIT_CARD".to_string()));
+        assert_eq!(card.0.fields[1], Value::from("<CREDIT_CARD>".to_string()));
+        assert_eq!(
+            card.0.fields[2],
+            output_position_to_value(recoco_splitters::split::OutputPosition {
+                char_offset: 27,
+                line: 2,
+                column: 6,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_redact_pii_mask_and_hash() {
+        let mask = Spec {
+            detectors: Some(vec![PiiKindSpec::CreditCard]),
+            custom_patterns: vec![],
+            replacement: Some(Replacement::Mask {
+                mask_char: Some('#'),
+                keep_last: 4,
+            }),
+        };
+        let (text, detections) = redact(mask, TEXT).await;
+        assert_eq!(
+            text,
+            "Mail jane@example.com\nCard ###############1111, ticket T-98765."
+        );
+        assert_eq!(detections.len(), 1);
+
+        let hash = |salt: &str| Spec {
+            detectors: Some(vec![PiiKindSpec::Email]),
+            custom_patterns: vec![],
+            replacement: Some(Replacement::Hash {
+                salt: salt.to_string(),
+            }),
+        };
+        let (text, _) = redact(hash("pepper"), "a@b.io and a@b.io").await;
+        let tokens: Vec<&str> = text.split(" and ").collect();
+        assert!(tokens[0].starts_with("<EMAIL:") && tokens[0].len() == 8 + HASH_HEX_DIGITS);
+        assert_eq!(tokens[0], tokens[1]);
+        let (resalted, _) = redact(hash("salt"), "a@b.io").await;
+        assert_ne!(resalted, tokens[0]);
+    }
+}
@@ -125,7 +125,7 @@ impl SimpleFunctionFactoryBase for Factory {
                 .required()?,
         };
 
-        let output_schema = make_common_chunk_schema(args_resolver, &args.text)?;
+        let output_schema = make_common_chunk_schema(args_resolver, &args.text, false)?;
         Ok(SimpleFunctionAnalysisOutput {
             resolved_args: args,
             output_schema,
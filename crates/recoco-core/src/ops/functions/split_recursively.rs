@@ -13,23 +13,42 @@
 use std::sync::Arc;
 
 use crate::ops::shared::split::{
-    CustomLanguageConfig, RecursiveChunkConfig, RecursiveChunker, RecursiveSplitConfig,
-    make_common_chunk_schema, output_position_to_value,
+    CustomGrammarConfig, CustomLanguageConfig, RecursiveChunkConfig, RecursiveChunker,
+    RecursiveSplitConfig, chunk_context_to_value, make_common_chunk_schema,
+    output_position_to_value,
 };
 use crate::{fields_value, ops::sdk::*};
 
+/// A tree-sitter grammar loaded at runtime: a shared library, or a WebAssembly module
+/// if `path` ends with `.wasm`.
+#[derive(Serialize, Deserialize)]
+pub struct GrammarSpec {
+    path: String,
+    #[serde(default)]
+    grammar_name: Option<String>,
+    #[serde(default)]
+    terminal_node_kinds: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct CustomLanguageSpec {
     language_name: String,
     #[serde(default)]
     aliases: Vec<String>,
+    #[serde(default)]
     separators_regex: Vec<String>,
+    #[serde(default)]
+    grammar: Option<GrammarSpec>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Spec {
     #[serde(default)]
     custom_languages: Vec<CustomLanguageSpec>,
+    /// Adds a `context` field with the scopes enclosing each chunk: named syntax nodes
+    /// (module, class, function, ...) for code, the heading hierarchy for Markdown.
+    #[serde(default)]
+    include_context: bool,
 }
 
 pub struct Args {
@@ -43,6 +62,7 @@ pub struct Args {
 struct Executor {
     args: Args,
     chunker: RecursiveChunker,
+    include_context: bool,
 }
 
 impl Executor {
@@ -55,11 +75,20 @@ impl Executor {
                     language_name: lang.language_name,
                     aliases: lang.aliases,
                     separators_regex: lang.separators_regex,
+                    grammar: lang.grammar.map(|grammar| CustomGrammarConfig {
+                        path: grammar.path.into(),
+                        grammar_name: grammar.grammar_name,
+                        terminal_node_kinds: grammar.terminal_node_kinds,
+                    }),
                 })
                 .collect(),
         };
         let chunker = RecursiveChunker::new(config).map_err(|e| api_error!("{}", e))?;
-        Ok(Self { args, chunker })
+        Ok(Self {
+            args,
+            chunker,
+            include_context: spec.include_context,
+        })
     }
 }
 
@@ -89,6 +118,7 @@ impl SimpleFunctionExecutor for Executor {
             min_chunk_size,
             chunk_overlap,
             language,
+            include_context: self.include_context,
         };
 
         let chunks = self.chunker.split(full_text, config);
@@ -97,17 +127,20 @@ impl SimpleFunctionExecutor for Executor {
             .into_iter()
             .map(|chunk| {
                 let chunk_text = &full_text[chunk.range.start..chunk.range.end];
+                let mut fields = fields_value!(
+                    Arc::<str>::from(chunk_text),
+                    output_position_to_value(chunk.start),
+                    output_position_to_value(chunk.end)
+                );
+                if self.include_context {
+                    fields.fields.push(chunk_context_to_value(chunk.context));
+                }
                 (
                     KeyValue::from_single_part(RangeValue::new(
                         chunk.start.char_offset,
                         chunk.end.char_offset,
                     )),
-                    fields_value!(
-                        Arc::<str>::from(chunk_text),
-                        output_position_to_value(chunk.start),
-                        output_position_to_value(chunk.end)
-                    )
-                    .into(),
+                    fields.into(),
                 )
             })
             .collect();
@@ -129,7 +162,7 @@ impl SimpleFunctionFactoryBase for Factory {
 
     async fn analyze<'a>(
         &'a self,
-        _spec: &'a Spec,
+        spec: &'a Spec,
         args_resolver: &mut OpArgsResolver<'a>,
         _context: &FlowInstanceContext,
     ) -> Result<SimpleFunctionAnalysisOutput<Args>> {
@@ -156,7 +189,8 @@ impl SimpleFunctionFactoryBase for Factory {
                 .optional(),
         };
 
-        let output_schema = make_common_chunk_schema(args_resolver, &args.text)?;
+        let output_schema =
+            make_common_chunk_schema(args_resolver, &args.text, spec.include_context)?;
         Ok(SimpleFunctionAnalysisOutput {
             resolved_args: args,
             output_schema,
@@ -212,6 +246,7 @@ mod tests {
     async fn test_split_recursively() {
         let spec = Spec {
             custom_languages: vec![],
+            include_context: false,
         };
         let factory = Arc::new(Factory);
         let text_content = "Linea 1.\nLinea 2.\n\nLinea 3.";
@@ -306,6 +341,7 @@ mod tests {
     async fn test_basic_split_no_overlap() {
         let spec = Spec {
             custom_languages: vec![],
+            include_context: false,
         };
         let factory = Arc::new(Factory);
         let text = "Linea 1.\nLinea 2.\n\nLinea 3.";
@@ -389,6 +425,7 @@ mod tests {
     async fn test_basic_split_with_overlap() {
         let spec = Spec {
             custom_languages: vec![],
+            include_context: false,
         };
         let factory = Arc::new(Factory);
         let text = "This is a test text that is a bit longer to see how the overlap works.";
@@ -437,6 +474,7 @@ mod tests {
     async fn test_split_trims_whitespace() {
         let spec = Spec {
             custom_languages: vec![],
+            include_context: false,
         };
         let factory = Arc::new(Factory);
         let text = "  \n First chunk  \n\n  Second chunk with spaces at the end    \n";
@@ -490,4 +528,48 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_split_with_context() {
+        let spec = Spec {
+            custom_languages: vec![],
+            include_context: true,
+        };
+        let factory = Arc::new(Factory);
+        let text = "# Guide\n\n## Install\n\nRun the installer.\n";
+        let input_arg_schemas = &build_split_recursively_arg_schemas();
+
+        let value = test_flow_function(
+            &factory,
+            &spec,
+            input_arg_schemas,
+            vec![
+                text.to_string().into(),
+                (24i64).into(),
+                (5i64).into(),
+                (0i64).into(),
+                "markdown".to_string().into(),
+            ],
+        )
+        .await
+        .unwrap();
+        let Value::KTable(table) = value else {
+            panic!("Expected Value::KTable, got {value:?}");
+        };
+        let (_, row) = table
+            .iter()
+            .find(|(_, row)| {
+                row.0.fields[0]
+                    .as_str()
+                    .is_ok_and(|t| t.contains("installer"))
+            })
+            .unwrap();
+        assert_eq!(
+            row.0.fields[3],
+            Value::Basic(BasicValue::Vector(Arc::from(vec![
+                BasicValue::Str("Guide".into()),
+                BasicValue::Str("Install".into()),
+            ])))
+        );
+    }
 }
@@ -0,0 +1,310 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use crate::llm::{
+    LlmApiConfig, LlmApiType, LlmEmbeddingClient, LlmEmbeddingRequest, new_llm_embedding_client,
+};
+use crate::ops::shared::split::{
+    Chunk, SemanticChunkConfig, SemanticSplitConfig, SemanticSplitter, make_common_chunk_schema,
+    output_position_to_value,
+};
+use crate::{fields_value, ops::sdk::*};
+
+/// Maximum number of sentence windows sent in one embedding request.
+const EMBEDDING_BATCH_SIZE: usize = 64;
+
+fn default_buffer_size() -> usize {
+    1
+}
+
+fn default_breakpoint_percentile() -> f64 {
+    5.0
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Spec {
+    api_type: LlmApiType,
+    model: String,
+    address: Option<String>,
+    api_config: Option<LlmApiConfig>,
+    output_dimension: Option<u32>,
+    task_type: Option<String>,
+    api_key: Option<AuthEntryReference<String>>,
+    /// Regexes for sentence boundaries. Defaults to sentence-ending punctuation and blank lines.
+    #[serde(default)]
+    sentence_separators_regex: Vec<String>,
+    /// Number of neighboring sentences on each side embedded along with each sentence.
+    #[serde(default = "default_buffer_size")]
+    buffer_size: usize,
+    /// Chunks break where adjacent similarity is below this percentile (0-100).
+    #[serde(default = "default_breakpoint_percentile")]
+    breakpoint_percentile: f64,
+}
+
+pub struct Args {
+    text: ResolvedOpArg,
+    max_chunk_size: ResolvedOpArg,
+    min_chunk_size: Option<ResolvedOpArg>,
+}
+
+/// Embeds sentence windows and groups sentences into chunks.
+struct SemanticChunker {
+    client: Box<dyn LlmEmbeddingClient>,
+    model: String,
+    output_dimension: Option<u32>,
+    task_type: Option<String>,
+    splitter: SemanticSplitter,
+}
+
+impl SemanticChunker {
+    async fn split(&self, text: &str, config: SemanticChunkConfig) -> Result<Vec<Chunk>> {
+        let split = self.splitter.prepare(text, config);
+        let mut embeddings = Vec::new();
+        for windows in split.windows().chunks(EMBEDDING_BATCH_SIZE) {
+            let resp = self
+                .client
+                .embed_text(LlmEmbeddingRequest {
+                    model: &self.model,
+                    texts: windows.iter().map(|w| Cow::Borrowed(*w)).collect(),
+                    output_dimension: self.output_dimension,
+                    task_type: self.task_type.as_deref().map(Cow::Borrowed),
+                })
+                .await?;
+            if resp.embeddings.len() != windows.len() {
+                api_bail!(
+                    "Expected {expected} embeddings but got {actual} from the embedding API.",
+                    expected = windows.len(),
+                    actual = resp.embeddings.len()
+                );
+            }
+            embeddings.extend(resp.embeddings);
+        }
+        split
+            .into_chunks(&embeddings)
+            .map_err(|e| internal_error!("{}", e))
+    }
+}
+
+struct Executor {
+    args: Args,
+    chunker: SemanticChunker,
+}
+
+#[async_trait]
+impl SimpleFunctionExecutor for Executor {
+    async fn evaluate(&self, input: Vec<Value>) -> Result<Value> {
+        let full_text = self.args.text.value(&input)?.as_str()?;
+        let max_chunk_size = self.args.max_chunk_size.value(&input)?.as_int64()?;
+        if max_chunk_size <= 0 {
+            client_bail!("max_chunk_size must be positive, got {max_chunk_size}");
+        }
+        let min_chunk_size = (self.args.min_chunk_size.value(&input)?)
+            .optional()
+            .map(|v| v.as_int64())
+            .transpose()?
+            .map_or(0, |v| v.max(0) as usize);
+
+        let chunks = self
+            .chunker
+            .split(
+                full_text,
+                SemanticChunkConfig {
+                    min_chunk_size,
+                    max_chunk_size: max_chunk_size as usize,
+                },
+            )
+            .await?;
+
+        let table = chunks
+            .into_iter()
+            .map(|chunk| {
+                let chunk_text = &full_text[chunk.range.start..chunk.range.end];
+                (
+                    KeyValue::from_single_part(RangeValue::new(
+                        chunk.start.char_offset,
+                        chunk.end.char_offset,
+                    )),
+                    fields_value!(
+                        Arc::<str>::from(chunk_text),
+                        output_position_to_value(chunk.start),
+                        output_position_to_value(chunk.end)
+                    )
+                    .into(),
+                )
+            })
+            .collect();
+
+        Ok(Value::KTable(table))
+    }
+
+    fn enable_cache(&self) -> bool {
+        true
+    }
+}
+
+struct Factory;
+
+#[async_trait]
+impl SimpleFunctionFactoryBase for Factory {
+    type Spec = Spec;
+    type ResolvedArgs = (Args, Box<dyn LlmEmbeddingClient>);
+
+    fn name(&self) -> &str {
+        "SplitSemantically"
+    }
+
+    async fn analyze<'a>(
+        &'a self,
+        spec: &'a Spec,
+        args_resolver: &mut OpArgsResolver<'a>,
+        context: &FlowInstanceContext,
+    ) -> Result<SimpleFunctionAnalysisOutput<Self::ResolvedArgs>> {
+        let args = Args {
+            text: args_resolver
+                .next_arg("text")?
+                .expect_type(&ValueType::Basic(BasicValueType::Str))?
+                .required()?,
+            max_chunk_size: args_resolver
+                .next_arg("max_chunk_size")?
+                .expect_type(&ValueType::Basic(BasicValueType::Int64))?
+                .required()?,
+            min_chunk_size: args_resolver
+                .next_arg("min_chunk_size")?
+                .expect_nullable_type(&ValueType::Basic(BasicValueType::Int64))?
+                .optional(),
+        };
+
+        let api_key = spec
+            .api_key
+            .as_ref()
+            .map(|key_ref| context.auth_registry.get(key_ref))
+            .transpose()?;
+        let client = new_llm_embedding_client(
+            spec.api_type,
+            spec.address.clone(),
+            api_key,
+            spec.api_config.clone(),
+        )
+        .await?;
+
+        let output_schema = make_common_chunk_schema(args_resolver, &args.text, false)?;
+        Ok(SimpleFunctionAnalysisOutput {
+            behavior_version: client.behavior_version(),
+            resolved_args: (args, client),
+            output_schema,
+        })
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        spec: Spec,
+        (args, client): Self::ResolvedArgs,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<impl SimpleFunctionExecutor> {
+        let splitter = SemanticSplitter::new(SemanticSplitConfig {
+            sentence_separators_regex: spec.sentence_separators_regex,
+            buffer_size: spec.buffer_size,
+            breakpoint_percentile: spec.breakpoint_percentile,
+        })
+        .map_err(|e| api_error!("{}", e))?;
+        Ok(Executor {
+            args,
+            chunker: SemanticChunker {
+                client,
+                model: spec.model,
+                output_dimension: spec.output_dimension,
+                task_type: spec.task_type,
+                splitter,
+            },
+        })
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::LlmEmbeddingResponse;
+
+    /// Embeds each text by how often it mentions each topic.
+    struct TopicEmbeddingClient;
+
+    #[async_trait]
+    impl LlmEmbeddingClient for TopicEmbeddingClient {
+        async fn embed_text<'req>(
+            &self,
+            request: LlmEmbeddingRequest<'req>,
+        ) -> Result<LlmEmbeddingResponse> {
+            let embeddings = request
+                .texts
+                .iter()
+                .map(|text| {
+                    ["tea", "train"]
+                        .iter()
+                        .map(|topic| text.matches(topic).count() as f32)
+                        .collect()
+                })
+                .collect();
+            Ok(LlmEmbeddingResponse { embeddings })
+        }
+
+        fn get_default_embedding_dimension(&self, _model: &str) -> Option<u32> {
+            Some(2)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_split_semantically() {
+        let chunker = SemanticChunker {
+            client: Box::new(TopicEmbeddingClient),
+            model: "topics".to_string(),
+            output_dimension: None,
+            task_type: None,
+            splitter: SemanticSplitter::new(SemanticSplitConfig {
+                sentence_separators_regex: vec![],
+                buffer_size: 0,
+                breakpoint_percentile: 50.0,
+            })
+            .unwrap(),
+        };
+        let text = "Green tea is mild.\nBlack tea is strong.\n\nThe train left early. \
+                    The train was full.";
+        let chunks = chunker
+            .split(
+                text,
+                SemanticChunkConfig {
+                    min_chunk_size: 0,
+                    max_chunk_size: 1000,
+                },
+            )
+            .await
+            .unwrap();
+        let texts: Vec<_> = chunks
+            .iter()
+            .map(|c| &text[c.range.start..c.range.end])
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Green tea is mild.\nBlack tea is strong.",
+                "The train left early. The train was full."
+            ]
+        );
+        assert_eq!(chunks[1].start.line, 4);
+        assert_eq!(chunks[1].start.column, 1);
+    }
+}
@@ -36,6 +36,24 @@ pub async fn test_flow_function(
     spec: &impl Serialize,
     input_arg_schemas: &[(Option<&str>, EnrichedValueType)],
     input_arg_values: Vec<Value>,
+) -> Result<Value> {
+    let context = Arc::new(FlowInstanceContext {
+        flow_instance_name: "test_flow_function".to_string(),
+        auth_registry: Arc::new(AuthRegistry::default()),
+        function_state_store: Default::default(),
+    });
+    test_flow_function_with_context(factory, spec, input_arg_schemas, input_arg_values, context)
+        .await
+}
+
+// Same as `test_flow_function`, within the given flow context (e.g. with auth entries).
+#[cfg(feature = "persistence")]
+pub async fn test_flow_function_with_context(
+    factory: &Arc<impl SimpleFunctionFactory>,
+    spec: &impl Serialize,
+    input_arg_schemas: &[(Option<&str>, EnrichedValueType)],
+    input_arg_values: Vec<Value>,
+    context: Arc<FlowInstanceContext>,
 ) -> Result<Value> {
     // 1. Construct OpArgSchema
     let op_arg_schemas: Vec<OpArgSchema> = input_arg_schemas
@@ -56,10 +74,6 @@ pub async fn test_flow_function(
         .collect();
 
     // 2. Build Executor
-    let context = Arc::new(FlowInstanceContext {
-        flow_instance_name: "test_flow_function".to_string(),
-        auth_registry: Arc::new(AuthRegistry::default()),
-    });
     let build_output = factory
         .clone()
         .build(serde_json::to_value(spec)?, op_arg_schemas, context)
@@ -22,6 +22,9 @@ use serde::Serialize;
 pub struct FlowInstanceContext {
     pub flow_instance_name: String,
     pub auth_registry: Arc<AuthRegistry>,
+    /// Store for functions of the flow to persist their own state across runs. Set once the
+    /// flow's setup is resolved, if the flow has a source state table.
+    pub function_state_store: RwLock<Option<Arc<dyn SourceStateStore>>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -145,6 +148,59 @@ pub struct SourceExecutorReadOptions {
     pub include_value: bool,
 }
 
+/// Store for sources and functions to persist their own state across runs, in the flow's source
+/// state table. Keys are scoped to the source, or shared by all functions of the flow.
+#[async_trait]
+pub trait SourceStateStore: Send + Sync {
+    async fn list(&self) -> Result<Vec<(serde_json::Value, serde_json::Value)>>;
+
+    async fn upsert(&self, key: &serde_json::Value, state: serde_json::Value) -> Result<()>;
+
+    async fn delete(&self, key: &serde_json::Value) -> Result<()>;
+}
+
+/// A [`SourceStateStore`] keeping states in memory only, e.g. when no database is configured.
+#[derive(Default)]
+pub struct InMemoryStateStore {
+    /// States by JSON of their keys.
+    states: Mutex<BTreeMap<String, serde_json::Value>>,
+}
+
+#[async_trait]
+impl SourceStateStore for InMemoryStateStore {
+    async fn list(&self) -> Result<Vec<(serde_json::Value, serde_json::Value)>> {
+        self.states
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| Ok((serde_json::from_str(key)?, value.clone())))
+            .collect()
+    }
+
+    async fn upsert(&self, key: &serde_json::Value, state: serde_json::Value) -> Result<()> {
+        self.states.lock().unwrap().insert(key.to_string(), state);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &serde_json::Value) -> Result<()> {
+        self.states.lock().unwrap().remove(&key.to_string());
+        Ok(())
+    }
+}
+
+pub struct SourceChangeStream<'a> {
+    pub stream: BoxStream<'a, Result<SourceChangeMessage>>,
+
+    /// Whether the stream starts with the changes made while the flow wasn't running.
+    /// If so, the initial listing of the source can be skipped in live mode.
+    pub catches_up: bool,
+
+    /// Called once the initial listing of the source is fully processed, e.g. to record the
+    /// state the stream catches up from on the next run.
+    pub initial_update_ack_fn:
+        Option<Box<dyn FnOnce() -> BoxFuture<'static, Result<()>> + Send + Sync>>,
+}
+
 #[async_trait]
 pub trait SourceExecutor: Send + Sync {
     /// Get the list of keys for the source.
@@ -167,6 +223,21 @@ pub trait SourceExecutor: Send + Sync {
         Ok(None)
     }
 
+    /// Used instead of `change_stream()` when the flow has a source state table.
+    async fn change_stream_with_state(
+        &self,
+        _state_store: Arc<dyn SourceStateStore>,
+    ) -> Result<Option<SourceChangeStream<'async_trait>>> {
+        Ok(self
+            .change_stream()
+            .await?
+            .map(|stream| SourceChangeStream {
+                stream,
+                catches_up: false,
+                initial_update_ack_fn: None,
+            }))
+    }
+
     fn provides_ordinal(&self) -> bool;
 }
 
@@ -181,6 +252,32 @@ pub trait SourceFactory {
         EnrichedValueType,
         BoxFuture<'static, Result<Box<dyn SourceExecutor>>>,
     )>;
+
+    /// Whether the source with the spec persists its own state through a [`SourceStateStore`].
+    /// If any source of a flow does, the flow gets a source state table.
+    fn uses_state_store(&self, _spec: &serde_json::Value) -> bool {
+        false
+    }
+
+    /// Resources the source with the spec creates outside the flow's own tables, e.g. a
+    /// replication slot. They're recorded in the flow's setup state, and each one is passed to
+    /// [`Self::drop_resource`] once no source of the flow lists it any more.
+    fn setup_resources(
+        &self,
+        _source_name: &str,
+        _spec: &serde_json::Value,
+        _context: &FlowInstanceContext,
+    ) -> Result<Vec<serde_json::Value>> {
+        Ok(vec![])
+    }
+
+    async fn drop_resource(
+        &self,
+        _resource: serde_json::Value,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -198,6 +295,21 @@ pub trait SimpleFunctionExecutor: Send + Sync {
     }
 }
 
+#[async_trait]
+impl SimpleFunctionExecutor for Box<dyn SimpleFunctionExecutor> {
+    async fn evaluate(&self, args: Vec<Value>) -> Result<Value> {
+        self.as_ref().evaluate(args).await
+    }
+
+    fn enable_cache(&self) -> bool {
+        self.as_ref().enable_cache()
+    }
+
+    fn timeout(&self) -> Option<std::time::Duration> {
+        self.as_ref().timeout()
+    }
+}
+
 pub struct SimpleFunctionBuildOutput {
     pub output_type: EnrichedValueType,
 
@@ -216,6 +328,13 @@ pub trait SimpleFunctionFactory {
         input_schema: Vec<OpArgSchema>,
         context: Arc<FlowInstanceContext>,
     ) -> Result<SimpleFunctionBuildOutput>;
+
+    /// Whether the function with the spec persists its own state through
+    /// [`FlowInstanceContext::function_state_store`].
+    /// If any function of a flow does, the flow gets a source state table.
+    fn uses_state_store(&self, _spec: &serde_json::Value) -> bool {
+        false
+    }
 }
 
 #[derive(Debug)]
@@ -11,27 +11,42 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #[cfg(any(
+    feature = "function-archive",
+    feature = "function-bm25",
+    feature = "function-convert-markdown",
     feature = "function-detect-lang",
     feature = "function-embed",
     feature = "function-extract-llm",
+    feature = "function-extract-symbols",
     feature = "function-json",
+    feature = "function-near-dedup",
+    feature = "function-redact-pii",
     feature = "function-split"
 ))]
 use super::functions;
 #[cfg(any(
     feature = "source-azure",
     feature = "source-gdrive",
+    feature = "source-git",
     feature = "source-local-file",
+    feature = "source-mysql",
     feature = "source-postgres",
-    feature = "source-s3"
+    feature = "source-s3",
+    feature = "source-sqlite",
+    feature = "source-web-crawl"
 ))]
 use super::sources;
 #[cfg(any(
+    feature = "target-elasticsearch",
+    feature = "target-http-webhook",
     feature = "target-kuzu",
     feature = "target-ladybug",
+    feature = "target-local-vector-store",
+    feature = "target-near-duplicate-index",
     feature = "target-neo4j",
     feature = "target-postgres",
-    feature = "target-qdrant"
+    feature = "target-qdrant",
+    feature = "target-redis"
 ))]
 use super::targets;
 #[allow(unused_imports)]
@@ -43,7 +58,12 @@ use std::sync::{LazyLock, RwLock};
 fn register_executor_factories(
     #[allow(unused_variables)] registry: &mut ExecutorFactoryRegistry,
 ) -> Result<()> {
-    #[cfg(any(feature = "target-kuzu", feature = "target-ladybug"))]
+    #[cfg(any(
+        feature = "target-elasticsearch",
+        feature = "target-http-webhook",
+        feature = "target-kuzu",
+        feature = "target-ladybug"
+    ))]
     let reqwest_client = reqwest::Client::new();
 
     #[cfg(feature = "source-local-file")]
@@ -56,24 +76,65 @@ fn register_executor_factories(
     sources::azure_blob::Factory.register(registry)?;
     #[cfg(feature = "source-postgres")]
     sources::postgres::Factory.register(registry)?;
-
+    #[cfg(feature = "source-mysql")]
+    sources::mysql::Factory.register(registry)?;
+    #[cfg(feature = "source-sqlite")]
+    sources::sqlite::Factory.register(registry)?;
+    #[cfg(feature = "source-git")]
+    sources::git_repo::Factory.register(registry)?;
+    #[cfg(feature = "source-web-crawl")]
+    sources::web_crawl::Factory.register(registry)?;
+
+    #[cfg(feature = "function-archive")]
+    functions::expand_archive::register(registry)?;
+    #[cfg(feature = "function-bm25")]
+    functions::encode_bm25::register(registry)?;
+    #[cfg(feature = "function-convert-markdown")]
+    functions::convert_to_markdown::register(registry)?;
     #[cfg(feature = "function-detect-lang")]
     functions::detect_program_lang::register(registry)?;
     #[cfg(feature = "function-embed")]
+    functions::embed_image::register(registry)?;
+    #[cfg(feature = "function-embed")]
     functions::embed_text::register(registry)?;
     #[cfg(feature = "function-extract-llm")]
     functions::extract_by_llm::Factory.register(registry)?;
+    #[cfg(feature = "function-extract-symbols")]
+    functions::extract_code_symbols::register(registry)?;
     #[cfg(feature = "function-json")]
     functions::parse_json::Factory.register(registry)?;
+    #[cfg(feature = "function-near-dedup")]
+    functions::compute_minhash::register(registry)?;
+    #[cfg(feature = "function-near-dedup")]
+    functions::compute_simhash::register(registry)?;
+    #[cfg(all(
+        feature = "function-near-dedup",
+        feature = "target-near-duplicate-index"
+    ))]
+    functions::detect_near_duplicate::register(registry)?;
+    #[cfg(feature = "function-redact-pii")]
+    functions::redact_pii::register(registry)?;
     #[cfg(feature = "function-split")]
     functions::split_by_separators::register(registry)?;
     #[cfg(feature = "function-split")]
     functions::split_recursively::register(registry)?;
+    #[cfg(feature = "function-split-semantic")]
+    functions::split_semantically::register(registry)?;
 
     #[cfg(feature = "target-postgres")]
     targets::postgres::register(registry)?;
     #[cfg(feature = "target-qdrant")]
     targets::qdrant::register(registry)?;
+    #[cfg(feature = "target-redis")]
+    targets::redis::register(registry)?;
+    #[cfg(feature = "target-local-vector-store")]
+    targets::local_vector_store::register(registry)?;
+    #[cfg(feature = "target-near-duplicate-index")]
+    targets::near_duplicate_index::register(registry)?;
+    #[cfg(feature = "target-elasticsearch")]
+    targets::elasticsearch::register(registry, reqwest_client.clone())?;
+    #[cfg(feature = "target-http-webhook")]
+    targets::http_webhook::register(registry, reqwest_client.clone())?;
     #[cfg(all(feature = "target-kuzu", feature = "target-ladybug"))]
     targets::kuzu::register(registry, reqwest_client.clone())?;
     #[cfg(all(feature = "target-kuzu", not(feature = "target-ladybug")))]
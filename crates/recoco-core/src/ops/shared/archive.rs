@@ -0,0 +1,512 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reading members of zip, tar and gzip archives, for sources exposing them as rows and the
+//! `ExpandArchive` function.
+
+use flate2::read::GzDecoder;
+use std::io::{Read, Seek};
+
+use crate::ops::sdk::*;
+use recoco_splitters::pattern_matcher::PatternMatcher;
+use recoco_utils::fingerprint::Fingerprinter;
+
+/// Separates the archive path and the member path in keys of archive members. File paths in keys
+/// have it escaped (see [`file_key`]), so the first one in a key is always the separator.
+pub const MEMBER_KEY_SEPARATOR: char = '!';
+/// Default maximum total uncompressed size of members read from an archive.
+pub const DEFAULT_MAX_TOTAL_SIZE: i64 = 1 << 30;
+/// Default maximum number of members read from an archive.
+pub const DEFAULT_MAX_MEMBERS: i64 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    /// A single gzip-compressed file.
+    Gz,
+}
+
+impl ArchiveFormat {
+    pub fn from_path(path: &str) -> Option<Self> {
+        let path = path.to_ascii_lowercase();
+        if path.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if path.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if path.ends_with(".gz") {
+            Some(Self::Gz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Guards against archives expanding to much more than their own size.
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveLimits {
+    /// Members larger than this (uncompressed) are skipped.
+    pub max_member_size: u64,
+    /// Members are read until their total uncompressed size exceeds this.
+    pub max_total_size: u64,
+    /// Members are read until their number exceeds this.
+    pub max_members: usize,
+}
+
+#[derive(Debug)]
+pub struct ArchiveMember {
+    pub path: String,
+    /// CRC-32 for zip members, which is available without decompressing. A fingerprint of the
+    /// content for other formats.
+    pub checksum: Vec<u8>,
+    pub content: Option<Vec<u8>>,
+}
+
+/// Key of a file, for sources keying both files and archive members. `%` and `!` in the path are
+/// percent-encoded, so it never collides with keys of archive members.
+pub fn file_key(path: &str) -> String {
+    path.replace('%', "%25")
+        .replace(MEMBER_KEY_SEPARATOR, "%21")
+}
+
+pub fn member_key(archive_path: &str, member_path: &str) -> String {
+    format!(
+        "{}{MEMBER_KEY_SEPARATOR}{member_path}",
+        file_key(archive_path)
+    )
+}
+
+/// Decode a file path encoded by [`file_key`]. Returns `None` if it's not a valid encoding.
+fn decode_file_key(key: &str) -> Option<String> {
+    let mut path = String::with_capacity(key.len());
+    let mut parts = key.split('%');
+    path.push_str(parts.next()?);
+    for part in parts {
+        if let Some(rest) = part.strip_prefix("25") {
+            path.push('%');
+            path.push_str(rest);
+        } else if let Some(rest) = part.strip_prefix("21") {
+            path.push(MEMBER_KEY_SEPARATOR);
+            path.push_str(rest);
+        } else {
+            return None;
+        }
+    }
+    Some(path)
+}
+
+/// Parse a key built by [`file_key`] or [`member_key`] into the file path, and the member path
+/// for members of archives. Returns `None` for keys not built by them.
+pub fn parse_key(key: &str) -> Option<(String, Option<&str>)> {
+    match key.split_once(MEMBER_KEY_SEPARATOR) {
+        Some((archive_key, member_path)) => {
+            let archive_path = decode_file_key(archive_key)?;
+            if member_path.is_empty() || ArchiveFormat::from_path(&archive_path).is_none() {
+                return None;
+            }
+            Some((archive_path, Some(member_path)))
+        }
+        None => Some((decode_file_key(key)?, None)),
+    }
+}
+
+/// Normalize a member path to `/`-separated relative components.
+/// Returns `None` for paths escaping the archive root.
+fn normalize_member_path(path: &str) -> Option<String> {
+    let mut components = Vec::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => return None,
+            component => components.push(component),
+        }
+    }
+    (!components.is_empty()).then(|| components.join("/"))
+}
+
+fn read_limited(reader: impl Read, limit: u64) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    reader.take(limit + 1).read_to_end(&mut content)?;
+    if content.len() as u64 > limit {
+        client_bail!("Archive member is larger than its declared size");
+    }
+    Ok(content)
+}
+
+fn fingerprint(content: &[u8]) -> Vec<u8> {
+    let mut fp = Fingerprinter::default();
+    fp.write_raw_bytes(content);
+    fp.into_fingerprint().as_slice().to_vec()
+}
+
+/// Tracks the limits while visiting members of an archive.
+struct LimitsTracker<'a> {
+    archive_path: &'a str,
+    limits: &'a ArchiveLimits,
+    num_members: usize,
+    total_size: u64,
+}
+
+impl LimitsTracker<'_> {
+    /// Account for a member. Returns false if the archive's limits are exceeded.
+    fn add(&mut self, size: u64) -> bool {
+        self.num_members += 1;
+        self.total_size = self.total_size.saturating_add(size);
+        if self.num_members > self.limits.max_members {
+            warn!(
+                "Archive {} has more than {} members, skipped remaining ones",
+                self.archive_path, self.limits.max_members
+            );
+            return false;
+        }
+        if self.total_size > self.limits.max_total_size {
+            warn!(
+                "Archive {} expands to more than {} bytes, skipped remaining members",
+                self.archive_path, self.limits.max_total_size
+            );
+            return false;
+        }
+        true
+    }
+}
+
+/// Visit file members of an archive accepted by `filter`, until `visit` returns false.
+fn visit_members(
+    archive_path: &str,
+    reader: impl Read + Seek,
+    limits: &ArchiveLimits,
+    include_content: bool,
+    filter: impl Fn(&str) -> bool,
+    mut visit: impl FnMut(ArchiveMember) -> bool,
+) -> Result<()> {
+    let Some(format) = ArchiveFormat::from_path(archive_path) else {
+        internal_bail!("Not an archive: {archive_path}");
+    };
+    let mut tracker = LimitsTracker {
+        archive_path,
+        limits,
+        num_members: 0,
+        total_size: 0,
+    };
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(reader).map_err(Error::internal)?;
+            for index in 0..archive.len() {
+                let (path, size, crc32) = {
+                    let file = archive.by_index_raw(index).map_err(Error::internal)?;
+                    if !file.is_file() {
+                        continue;
+                    }
+                    (
+                        normalize_member_path(file.name()),
+                        file.size(),
+                        file.crc32(),
+                    )
+                };
+                if !tracker.add(size) {
+                    break;
+                }
+                let Some(path) = path.filter(|path| filter(path)) else {
+                    continue;
+                };
+                if size > limits.max_member_size {
+                    continue;
+                }
+                let content = if include_content {
+                    let file = archive.by_index(index).map_err(Error::internal)?;
+                    Some(read_limited(file, size)?)
+                } else {
+                    None
+                };
+                let member = ArchiveMember {
+                    path,
+                    checksum: crc32.to_be_bytes().to_vec(),
+                    content,
+                };
+                if !visit(member) {
+                    break;
+                }
+            }
+        }
+        ArchiveFormat::Tar | ArchiveFormat::TarGz => {
+            let reader: Box<dyn Read> = if format == ArchiveFormat::TarGz {
+                Box::new(GzDecoder::new(reader))
+            } else {
+                Box::new(reader)
+            };
+            let mut archive = tar::Archive::new(reader);
+            for entry in archive.entries()? {
+                let entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let size = entry.size();
+                if !tracker.add(size) {
+                    break;
+                }
+                let path = entry
+                    .path()?
+                    .to_str()
+                    .and_then(normalize_member_path)
+                    .filter(|path| filter(path));
+                let Some(path) = path else {
+                    continue;
+                };
+                if size > limits.max_member_size {
+                    continue;
+                }
+                let content = read_limited(entry, size)?;
+                let member = ArchiveMember {
+                    path,
+                    checksum: fingerprint(&content),
+                    content: include_content.then_some(content),
+                };
+                if !visit(member) {
+                    break;
+                }
+            }
+        }
+        ArchiveFormat::Gz => {
+            let file_name = archive_path.rsplit(['/', '\\']).next().unwrap_or_default();
+            let path = &file_name[..file_name.len() - ".gz".len()];
+            if path.is_empty() || !filter(path) {
+                return Ok(());
+            }
+            // The uncompressed size isn't known upfront, so both limits apply while reading.
+            let limit = limits.max_member_size.min(limits.max_total_size);
+            let mut content = Vec::new();
+            GzDecoder::new(reader)
+                .take(limit + 1)
+                .read_to_end(&mut content)?;
+            if content.len() as u64 > limit {
+                warn!("{archive_path} expands to more than {limit} bytes, skipped");
+                return Ok(());
+            }
+            visit(ArchiveMember {
+                path: path.to_string(),
+                checksum: fingerprint(&content),
+                content: include_content.then_some(content),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Read members of an archive accepted by `pattern_matcher`.
+pub fn read_members(
+    archive_path: &str,
+    reader: impl Read + Seek,
+    pattern_matcher: &PatternMatcher,
+    limits: &ArchiveLimits,
+    include_content: bool,
+) -> Result<Vec<ArchiveMember>> {
+    let mut members = Vec::new();
+    visit_members(
+        archive_path,
+        reader,
+        limits,
+        include_content,
+        |path| pattern_matcher.is_file_included(path),
+        |member| {
+            members.push(member);
+            true
+        },
+    )?;
+    Ok(members)
+}
+
+/// Read a single member of an archive. Returns `None` if it doesn't exist or exceeds the limits.
+pub fn read_member(
+    archive_path: &str,
+    reader: impl Read + Seek,
+    member_path: &str,
+    limits: &ArchiveLimits,
+    include_content: bool,
+) -> Result<Option<ArchiveMember>> {
+    let mut result = None;
+    visit_members(
+        archive_path,
+        reader,
+        limits,
+        include_content,
+        |path| path == member_path,
+        |member| {
+            result = Some(member);
+            false
+        },
+    )?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    const LIMITS: ArchiveLimits = ArchiveLimits {
+        max_member_size: 1024,
+        max_total_size: 4096,
+        max_members: 10,
+    };
+
+    fn zip_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        for (path, content) in files {
+            writer.start_file(*path, options).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn tar_gz_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn member_paths(members: &[ArchiveMember]) -> Vec<&str> {
+        members.iter().map(|m| m.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_member_keys() {
+        assert_eq!(
+            member_key("data/a.zip", "docs/b.md"),
+            "data/a.zip!docs/b.md"
+        );
+        assert_eq!(
+            member_key("data/a!b%21.tar.gz", "docs/b!.md"),
+            "data/a%21b%2521.tar.gz!docs/b!.md"
+        );
+        assert_eq!(
+            parse_key("data/a%21b%2521.tar.gz!docs/b!.md"),
+            Some(("data/a!b%21.tar.gz".to_string(), Some("docs/b!.md")))
+        );
+        assert_eq!(parse_key("data/a.zip!"), None);
+        assert_eq!(parse_key("data/a.md!b.md"), None);
+        assert_eq!(parse_key("data/a%2"), None);
+
+        // A file whose path looks like an archive member has a different key than the member.
+        assert_eq!(file_key("data/a.zip!b.md"), "data/a.zip%21b.md");
+        assert_ne!(
+            file_key("data/a.zip!b.md"),
+            member_key("data/a.zip", "b.md")
+        );
+        assert_eq!(
+            parse_key(&file_key("data/a.zip!b.md")),
+            Some(("data/a.zip!b.md".to_string(), None))
+        );
+        assert_eq!(
+            parse_key(&file_key("data/50%.md")),
+            Some(("data/50%.md".to_string(), None))
+        );
+        assert_eq!(
+            normalize_member_path("./docs//a.md"),
+            Some("docs/a.md".to_string())
+        );
+        assert_eq!(normalize_member_path("../etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_read_members() -> Result<()> {
+        let files: &[(&str, &[u8])] = &[
+            ("docs/a.md", b"a"),
+            ("docs/b.txt", b"b"),
+            ("big.md", &[b'x'; 2048]),
+        ];
+        let pattern_matcher = PatternMatcher::new(None, Some(vec!["**/*.txt".to_string()]))?;
+        for (archive_path, data) in [
+            ("a.zip", zip_archive(files)),
+            ("a.tar.gz", tar_gz_archive(files)),
+        ] {
+            let members = read_members(
+                archive_path,
+                Cursor::new(&data),
+                &pattern_matcher,
+                &LIMITS,
+                true,
+            )?;
+            // The excluded member and the one over the size limit are skipped.
+            assert_eq!(member_paths(&members), ["docs/a.md"], "{archive_path}");
+            assert_eq!(members[0].content.as_deref(), Some(&b"a"[..]));
+
+            let listed = read_members(
+                archive_path,
+                Cursor::new(&data),
+                &pattern_matcher,
+                &LIMITS,
+                false,
+            )?;
+            assert!(listed[0].content.is_none());
+            assert_eq!(listed[0].checksum, members[0].checksum);
+
+            let member = read_member(
+                archive_path,
+                Cursor::new(&data),
+                "docs/b.txt",
+                &LIMITS,
+                true,
+            )?;
+            assert_eq!(member.and_then(|m| m.content).as_deref(), Some(&b"b"[..]));
+            assert!(
+                read_member(archive_path, Cursor::new(&data), "big.md", &LIMITS, true)?.is_none()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_limits() -> Result<()> {
+        let content = [b'x'; 1000];
+        let files = (0..8)
+            .map(|i| (format!("{i}.txt"), &content[..]))
+            .collect::<Vec<_>>();
+        let files = files
+            .iter()
+            .map(|(path, content)| (path.as_str(), *content))
+            .collect::<Vec<_>>();
+        let pattern_matcher = PatternMatcher::new(None, None)?;
+        let members = read_members(
+            "a.zip",
+            Cursor::new(zip_archive(&files)),
+            &pattern_matcher,
+            &LIMITS,
+            false,
+        )?;
+        // Reading stops once the total size exceeds 4096 bytes.
+        assert_eq!(members.len(), 4);
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&[0; 8192])?;
+        let members = read_members(
+            "data/zeros.bin.gz",
+            Cursor::new(gz.finish()?),
+            &pattern_matcher,
+            &LIMITS,
+            false,
+        )?;
+        assert!(members.is_empty());
+        Ok(())
+    }
+}
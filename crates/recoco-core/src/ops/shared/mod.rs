@@ -10,7 +10,21 @@
 // Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(any(feature = "function-archive", feature = "source-local-file"))]
+pub mod archive;
 #[cfg(any(feature = "source-postgres", feature = "target-postgres"))]
 pub mod postgres;
-#[cfg(feature = "function-split")]
+#[cfg(any(
+    feature = "function-extract-symbols",
+    feature = "function-redact-pii",
+    feature = "function-split"
+))]
 pub mod split;
+#[cfg(any(
+    feature = "source-mysql",
+    feature = "source-postgres",
+    feature = "source-sqlite"
+))]
+pub mod sql_table;
+#[cfg(feature = "target-local-vector-store")]
+pub mod vector_index;
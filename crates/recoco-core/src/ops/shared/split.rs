@@ -12,21 +12,31 @@
 
 //! Split utilities - re-exports and schema helpers.
 
+#[cfg(feature = "function-split")]
 use crate::{
     base::field_attrs,
-    fields_value,
-    ops::sdk::value,
     ops::sdk::{
-        BasicValueType, EnrichedValueType, FieldSchema, KTableInfo, OpArgsResolver, StructSchema,
-        StructSchemaBuilder, TableKind, TableSchema, make_output_type, schema,
+        EnrichedValueType, FieldSchema, KTableInfo, OpArgsResolver, StructSchema,
+        StructSchemaBuilder, TableKind, TableSchema, VectorTypeSchema,
     },
     prelude::*,
 };
+use crate::{
+    fields_value,
+    ops::sdk::value,
+    ops::sdk::{BasicValueType, make_output_type, schema},
+};
 
 // Re-export core types from splitters
+pub use recoco_splitters::split::OutputPosition;
+#[cfg(feature = "function-split-semantic")]
 pub use recoco_splitters::split::{
-    CustomLanguageConfig, KeepSeparator, OutputPosition, RecursiveChunkConfig, RecursiveChunker,
-    RecursiveSplitConfig, SeparatorSplitConfig, SeparatorSplitter,
+    Chunk, SemanticChunkConfig, SemanticSplitConfig, SemanticSplitter,
+};
+#[cfg(feature = "function-split")]
+pub use recoco_splitters::split::{
+    CustomGrammarConfig, CustomLanguageConfig, KeepSeparator, RecursiveChunkConfig,
+    RecursiveChunker, RecursiveSplitConfig, SeparatorSplitConfig, SeparatorSplitter,
 };
 
 /// Convert an OutputPosition to cocoindex Value format.
@@ -38,20 +48,39 @@ pub fn output_position_to_value(pos: OutputPosition) -> value::Value {
     ))
 }
 
-/// Build the common chunk output schema used by splitters.
-/// Fields: `location: Range`, `text: Str`, `start: {offset,line,column}`, `end: {offset,line,column}`.
-pub fn make_common_chunk_schema<'a>(
-    args_resolver: &OpArgsResolver<'a>,
-    text_arg: &crate::ops::sdk::ResolvedOpArg,
-) -> Result<EnrichedValueType> {
-    let pos_struct = schema::ValueType::Struct(schema::StructSchema {
+/// Schema of a value produced by [`output_position_to_value`]: `{offset, line, column}`.
+pub fn output_position_schema() -> schema::StructSchema {
+    schema::StructSchema {
         fields: std::sync::Arc::new(vec![
             schema::FieldSchema::new("offset", make_output_type(BasicValueType::Int64)),
             schema::FieldSchema::new("line", make_output_type(BasicValueType::Int64)),
             schema::FieldSchema::new("column", make_output_type(BasicValueType::Int64)),
         ]),
         description: None,
-    });
+    }
+}
+
+/// Convert a chunk's enclosing scopes to cocoindex Value format.
+#[cfg(feature = "function-split")]
+pub fn chunk_context_to_value(context: Vec<String>) -> value::Value {
+    value::Value::Basic(value::BasicValue::Vector(
+        context
+            .into_iter()
+            .map(|name| value::BasicValue::Str(name.into()))
+            .collect(),
+    ))
+}
+
+/// Build the common chunk output schema used by splitters.
+/// Fields: `location: Range`, `text: Str`, `start: {offset,line,column}`, `end: {offset,line,column}`,
+/// plus `context: Vector[Str]` when `include_context` is set.
+#[cfg(feature = "function-split")]
+pub fn make_common_chunk_schema<'a>(
+    args_resolver: &OpArgsResolver<'a>,
+    text_arg: &crate::ops::sdk::ResolvedOpArg,
+    include_context: bool,
+) -> Result<EnrichedValueType> {
+    let pos_struct = schema::ValueType::Struct(output_position_schema());
 
     let mut struct_schema = StructSchema::default();
     let mut sb = StructSchemaBuilder::new(&mut struct_schema);
@@ -79,6 +108,15 @@ pub fn make_common_chunk_schema<'a>(
             attrs: Default::default(),
         },
     ));
+    if include_context {
+        sb.add_field(FieldSchema::new(
+            "context",
+            make_output_type(BasicValueType::Vector(VectorTypeSchema {
+                element_type: Box::new(BasicValueType::Str),
+                dimension: None,
+            })),
+        ));
+    }
     let output_schema = make_output_type(TableSchema::new(
         TableKind::KTable(KTableInfo { num_key_parts: 1 }),
         struct_schema,
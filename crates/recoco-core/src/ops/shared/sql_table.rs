@@ -0,0 +1,431 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Core shared by the sources reading rows from a SQL table.
+
+use crate::ops::sdk::*;
+
+use sqlx::Row;
+
+pub type SqlValueDecoder<R> = fn(&R, usize) -> Result<Value>;
+pub type SqlOrdinalDecoder<R> = fn(&R, usize) -> Result<Ordinal>;
+
+pub struct SqlFieldSchemaInfo<R> {
+    pub schema: FieldSchema,
+    pub decoder: SqlValueDecoder<R>,
+}
+
+impl<R> Clone for SqlFieldSchemaInfo<R> {
+    fn clone(&self) -> Self {
+        Self {
+            schema: self.schema.clone(),
+            decoder: self.decoder,
+        }
+    }
+}
+
+/// A column of the table, as described by the database catalog.
+pub struct SqlColumnInfo<R> {
+    pub name: String,
+    pub value_type: BasicValueType,
+    pub decoder: SqlValueDecoder<R>,
+    pub is_nullable: bool,
+    pub is_primary_key: bool,
+}
+
+/// An ordinal computed by a SQL expression instead of read from a column.
+pub struct ComputedOrdinal<R> {
+    pub expr: String,
+    pub decoder: SqlOrdinalDecoder<R>,
+}
+
+pub struct SqlTableSchema<R> {
+    pub primary_key_columns: Vec<SqlFieldSchemaInfo<R>>,
+    pub value_columns: Vec<SqlFieldSchemaInfo<R>>,
+    pub ordinal_field_idx: Option<usize>,
+    pub ordinal_field_schema: Option<SqlFieldSchemaInfo<R>>,
+    pub computed_ordinal: Option<ComputedOrdinal<R>>,
+}
+
+impl<R: Row> SqlTableSchema<R> {
+    pub fn new(
+        table_name: &str,
+        columns: impl IntoIterator<Item = SqlColumnInfo<R>>,
+        included_columns: &Option<Vec<String>>,
+        ordinal_column: &Option<String>,
+    ) -> Result<Self> {
+        let mut primary_key_columns: Vec<SqlFieldSchemaInfo<R>> = Vec::new();
+        let mut value_columns: Vec<SqlFieldSchemaInfo<R>> = Vec::new();
+        let mut ordinal_field_schema: Option<SqlFieldSchemaInfo<R>> = None;
+
+        for column in columns {
+            let info = SqlFieldSchemaInfo {
+                schema: FieldSchema::new(
+                    &column.name,
+                    make_output_type(column.value_type).with_nullable(column.is_nullable),
+                ),
+                decoder: column.decoder,
+            };
+
+            if let Some(ord_col) = ordinal_column
+                && &column.name == ord_col
+            {
+                ordinal_field_schema = Some(info.clone());
+                if column.is_primary_key {
+                    api_bail!(
+                        "`ordinal_column` cannot be a primary key column. It must be one of the value columns."
+                    );
+                }
+            }
+
+            if column.is_primary_key {
+                primary_key_columns.push(info);
+            } else if included_columns
+                .as_ref()
+                .is_none_or(|cols| cols.contains(&column.name))
+            {
+                value_columns.push(info);
+            }
+        }
+
+        if primary_key_columns.is_empty() {
+            if value_columns.is_empty() {
+                api_bail!("Table `{table_name}` not found");
+            }
+            api_bail!("Table `{table_name}` has no primary key defined");
+        }
+
+        // If ordinal column specified, validate and compute its index within value columns if present
+        let ordinal_field_idx = match ordinal_column {
+            Some(ord) => {
+                let schema = ordinal_field_schema.as_ref().ok_or_else(|| {
+                    client_error!("`ordinal_column` `{}` not found in table", ord)
+                })?;
+                if !is_supported_ordinal_type(&schema.schema.value_type.typ) {
+                    api_bail!(
+                        "Unsupported `ordinal_column` type for `{}`. Supported types: Int64, LocalDateTime, OffsetDateTime",
+                        schema.schema.name
+                    );
+                }
+                value_columns.iter().position(|c| c.schema.name == *ord)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            primary_key_columns,
+            value_columns,
+            ordinal_field_idx,
+            ordinal_field_schema,
+            computed_ordinal: None,
+        })
+    }
+
+    pub fn output_type(&self) -> EnrichedValueType {
+        make_output_type(TableSchema::new(
+            TableKind::KTable(KTableInfo {
+                num_key_parts: self.primary_key_columns.len(),
+            }),
+            StructSchema {
+                fields: Arc::new(
+                    (self.primary_key_columns.iter())
+                        .chain(self.value_columns.iter())
+                        .map(|col| {
+                            FieldSchema::new(&col.schema.name, col.schema.value_type.clone())
+                        })
+                        .collect(),
+                ),
+                description: None,
+            },
+        ))
+    }
+
+    pub fn provides_ordinal(&self) -> bool {
+        self.ordinal_field_schema.is_some() || self.computed_ordinal.is_some()
+    }
+
+    /// Append value and ordinal columns to the provided columns vector.
+    /// Returns the optional index of the ordinal column in the final selection.
+    fn build_selected_columns(
+        &self,
+        columns: &mut Vec<String>,
+        options: &SourceExecutorReadOptions,
+        quote_identifier: fn(&str) -> String,
+    ) -> Option<usize> {
+        let base_len = columns.len();
+        if options.include_value {
+            columns.extend(
+                self.value_columns
+                    .iter()
+                    .map(|col| quote_identifier(&col.schema.name)),
+            );
+        }
+
+        if options.include_ordinal {
+            if let Some(ord_schema) = &self.ordinal_field_schema {
+                if options.include_value {
+                    if let Some(val_idx) = self.ordinal_field_idx {
+                        return Some(base_len + val_idx);
+                    }
+                }
+                columns.push(quote_identifier(&ord_schema.schema.name));
+                return Some(columns.len() - 1);
+            }
+            if let Some(computed_ordinal) = &self.computed_ordinal {
+                columns.push(computed_ordinal.expr.clone());
+                return Some(columns.len() - 1);
+            }
+        }
+
+        None
+    }
+
+    fn decode_key(&self, row: &R) -> Result<KeyValue> {
+        let parts = self
+            .primary_key_columns
+            .iter()
+            .enumerate()
+            .map(|(i, info)| (info.decoder)(row, i)?.into_key())
+            .collect::<Result<Box<[KeyPart]>>>()?;
+        Ok(KeyValue(parts))
+    }
+
+    /// Decode all value columns from a row, starting at the given index offset.
+    fn decode_row_data(
+        &self,
+        row: &R,
+        options: &SourceExecutorReadOptions,
+        ordinal_col_index: Option<usize>,
+        value_start_idx: usize,
+    ) -> Result<PartialSourceRowData> {
+        let value = if options.include_value {
+            let mut fields = Vec::with_capacity(self.value_columns.len());
+            for (i, info) in self.value_columns.iter().enumerate() {
+                let value = (info.decoder)(row, value_start_idx + i)?;
+                fields.push(value);
+            }
+            Some(SourceValue::Existence(FieldValues { fields }))
+        } else {
+            None
+        };
+
+        let ordinal = if options.include_ordinal {
+            match (
+                ordinal_col_index,
+                &self.ordinal_field_schema,
+                &self.computed_ordinal,
+            ) {
+                (Some(idx), Some(ord_schema), _) => {
+                    let val = (ord_schema.decoder)(row, idx)?;
+                    Some(value_to_ordinal(&val))
+                }
+                (Some(idx), None, Some(computed_ordinal)) => {
+                    Some((computed_ordinal.decoder)(row, idx)?)
+                }
+                _ => Some(Ordinal::unavailable()),
+            }
+        } else {
+            None
+        };
+
+        Ok(PartialSourceRowData {
+            value,
+            ordinal,
+            content_version_fp: None,
+        })
+    }
+}
+
+/// Database-specific operations needed to read rows from a SQL table.
+#[async_trait]
+pub trait SqlTableDriver: Send + Sync + 'static {
+    type Row: Row;
+
+    fn quote_identifier(name: &str) -> String;
+
+    fn fetch_rows(&self, query: String) -> BoxStream<'_, Result<Self::Row>>;
+
+    /// Run `query` extended by conditions matching `key_columns` to `key` and the `filter`.
+    /// `query` ends with `WHERE `.
+    async fn fetch_row_by_key(
+        &self,
+        query: String,
+        key_columns: &[String],
+        key: &KeyValue,
+        filter: Option<&str>,
+    ) -> Result<Option<Self::Row>>;
+}
+
+pub struct SqlTableReader<D: SqlTableDriver> {
+    pub driver: D,
+    pub table_name: String,
+    pub table_schema: SqlTableSchema<D::Row>,
+    pub filter: Option<String>,
+}
+
+impl<D: SqlTableDriver> SqlTableReader<D> {
+    pub async fn list<'a>(
+        &'a self,
+        options: &'a SourceExecutorReadOptions,
+    ) -> Result<BoxStream<'a, Result<Vec<PartialSourceRow>>>> {
+        // Build selection including PKs (for keys), and optionally values and ordinal
+        let pk_columns: Vec<String> = self
+            .table_schema
+            .primary_key_columns
+            .iter()
+            .map(|col| D::quote_identifier(&col.schema.name))
+            .collect();
+        let pk_count = pk_columns.len();
+        let mut select_parts = pk_columns;
+        let ordinal_col_index = self.table_schema.build_selected_columns(
+            &mut select_parts,
+            options,
+            D::quote_identifier,
+        );
+
+        let mut query = format!(
+            "SELECT {} FROM {}",
+            select_parts.join(", "),
+            D::quote_identifier(&self.table_name)
+        );
+
+        // Add WHERE filter if specified
+        if let Some(where_clause) = &self.filter {
+            query.push_str(" WHERE ");
+            query.push_str(where_clause);
+        }
+
+        let stream = try_stream! {
+            let mut rows = self.driver.fetch_rows(query);
+            while let Some(row) = rows.try_next().await? {
+                // Decode key from PKs (selected first)
+                let key = self.table_schema.decode_key(&row)?;
+
+                // Decode value and ordinal
+                let data = self.table_schema.decode_row_data(&row, options, ordinal_col_index, pk_count)?;
+
+                yield vec![PartialSourceRow {
+                    key,
+                    key_aux_info: serde_json::Value::Null,
+                    data,
+                }];
+            }
+        };
+        Ok(stream.boxed())
+    }
+
+    pub async fn get_value(
+        &self,
+        key: &KeyValue,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<PartialSourceRowData> {
+        let mut selected_columns: Vec<String> = Vec::new();
+        let ordinal_col_index = self.table_schema.build_selected_columns(
+            &mut selected_columns,
+            options,
+            D::quote_identifier,
+        );
+        let query = format!(
+            "SELECT {} FROM {} WHERE ",
+            if selected_columns.is_empty() {
+                "1".to_string()
+            } else {
+                selected_columns.join(", ")
+            },
+            D::quote_identifier(&self.table_name)
+        );
+
+        if key.len() != self.table_schema.primary_key_columns.len() {
+            internal_bail!(
+                "Composite key has {} values but table has {} primary key columns",
+                key.len(),
+                self.table_schema.primary_key_columns.len()
+            );
+        }
+        let key_columns: Vec<String> = self
+            .table_schema
+            .primary_key_columns
+            .iter()
+            .map(|col| D::quote_identifier(&col.schema.name))
+            .collect();
+
+        let row_opt = self
+            .driver
+            .fetch_row_by_key(query, &key_columns, key, self.filter.as_deref())
+            .await?;
+        let data = match &row_opt {
+            Some(row) => self
+                .table_schema
+                .decode_row_data(row, options, ordinal_col_index, 0)?,
+            None => PartialSourceRowData {
+                value: Some(SourceValue::NonExistence),
+                ordinal: Some(Ordinal::unavailable()),
+                content_version_fp: None,
+            },
+        };
+
+        Ok(data)
+    }
+}
+
+/// Append conditions matching `key_columns` to `key` and the optional `filter` to a query.
+pub fn push_key_conditions<'arg, DB: sqlx::Database>(
+    builder: &mut sqlx::QueryBuilder<'arg, DB>,
+    key_columns: &[String],
+    key: &'arg KeyValue,
+    filter: Option<&str>,
+    bind_key_field: fn(&mut sqlx::QueryBuilder<'arg, DB>, &'arg KeyPart) -> Result<()>,
+) -> Result<()> {
+    for (i, (column, key_value)) in key_columns.iter().zip(key.iter()).enumerate() {
+        if i > 0 {
+            builder.push(" AND ");
+        }
+        builder.push(column);
+        builder.push(" = ");
+        bind_key_field(builder, key_value)?;
+    }
+
+    // Add WHERE filter if specified
+    if let Some(where_clause) = filter {
+        builder.push(" AND (");
+        builder.push(where_clause);
+        builder.push(")");
+    }
+    Ok(())
+}
+
+/// Whether values of the type can be converted into an `Ordinal`. Supported types:
+/// - Int64: interpreted directly as microseconds
+/// - LocalDateTime: converted to UTC micros
+/// - OffsetDateTime: micros since epoch
+pub fn is_supported_ordinal_type(t: &ValueType) -> bool {
+    matches!(
+        t,
+        ValueType::Basic(BasicValueType::Int64)
+            | ValueType::Basic(BasicValueType::LocalDateTime)
+            | ValueType::Basic(BasicValueType::OffsetDateTime)
+    )
+}
+
+/// Convert a `Value` into an `Ordinal` if supported, otherwise returns unavailable.
+pub fn value_to_ordinal(value: &Value) -> Ordinal {
+    match value {
+        Value::Null => Ordinal::unavailable(),
+        Value::Basic(basic) => match basic {
+            BasicValue::Int64(v) => Ordinal(Some(*v)),
+            BasicValue::LocalDateTime(dt) => Ordinal(Some(dt.and_utc().timestamp_micros())),
+            BasicValue::OffsetDateTime(dt) => Ordinal(Some(dt.timestamp_micros())),
+            _ => Ordinal::unavailable(),
+        },
+        _ => Ordinal::unavailable(),
+    }
+}
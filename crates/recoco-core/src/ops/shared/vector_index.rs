@@ -0,0 +1,585 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! In-memory vector indexes for nearest neighbor search: exact (flat), HNSW and IVFFlat.
+
+use crate::base::spec::{VectorIndexMethod, VectorSimilarityMetric};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+const DEFAULT_HNSW_M: usize = 16;
+const DEFAULT_HNSW_EF_CONSTRUCTION: usize = 64;
+const HNSW_EF_SEARCH: usize = 64;
+const DEFAULT_IVF_LISTS: usize = 100;
+const IVF_KMEANS_ITERATIONS: usize = 10;
+
+/// Distance between two vectors under the metric. Smaller is closer.
+pub fn distance(metric: VectorSimilarityMetric, a: &[f32], b: &[f32]) -> f32 {
+    match metric {
+        VectorSimilarityMetric::CosineSimilarity => {
+            let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+            for (x, y) in a.iter().zip(b.iter()) {
+                dot += x * y;
+                norm_a += x * x;
+                norm_b += y * y;
+            }
+            if norm_a == 0.0 || norm_b == 0.0 {
+                1.0
+            } else {
+                1.0 - dot / (norm_a.sqrt() * norm_b.sqrt())
+            }
+        }
+        VectorSimilarityMetric::L2Distance => a
+            .iter()
+            .zip(b.iter())
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f32>()
+            .sqrt(),
+        VectorSimilarityMetric::InnerProduct => {
+            -a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f32>()
+        }
+    }
+}
+
+/// Score reported for a distance, in the metric's own terms: the similarity for cosine and
+/// inner product, the distance for L2.
+pub fn score(metric: VectorSimilarityMetric, distance: f32) -> f32 {
+    match metric {
+        VectorSimilarityMetric::CosineSimilarity => 1.0 - distance,
+        VectorSimilarityMetric::L2Distance => distance,
+        VectorSimilarityMetric::InnerProduct => -distance,
+    }
+}
+
+/// A candidate during search, ordered by distance.
+#[derive(Clone, Copy, PartialEq)]
+struct Candidate {
+    distance: f32,
+    node: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+struct HnswNode {
+    id: Arc<str>,
+    vector: Vec<f32>,
+    /// Neighbors on each layer the node is on, from layer 0.
+    neighbors: Vec<Vec<usize>>,
+    deleted: bool,
+}
+
+/// Hierarchical Navigable Small World graph. Removed vectors stay in the graph to keep it
+/// connected, and the graph is rebuilt once they're the majority.
+struct Hnsw {
+    metric: VectorSimilarityMetric,
+    m: usize,
+    ef_construction: usize,
+    level_mult: f64,
+    nodes: Vec<HnswNode>,
+    node_by_id: HashMap<Arc<str>, usize>,
+    entry_point: Option<usize>,
+}
+
+impl Hnsw {
+    fn new(metric: VectorSimilarityMetric, m: usize, ef_construction: usize) -> Self {
+        let m = m.max(2);
+        Self {
+            metric,
+            m,
+            ef_construction: ef_construction.max(m),
+            level_mult: 1.0 / (m as f64).ln(),
+            nodes: Vec::new(),
+            node_by_id: HashMap::new(),
+            entry_point: None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.node_by_id.len()
+    }
+
+    /// Level of a new node. Derived from the ID, so the graph is deterministic.
+    fn random_level(&self, id: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        let uniform = ((hasher.finish() >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        (-uniform.ln() * self.level_mult).floor() as usize
+    }
+
+    fn max_neighbors(&self, layer: usize) -> usize {
+        if layer == 0 { self.m * 2 } else { self.m }
+    }
+
+    fn distance_to(&self, query: &[f32], node: usize) -> f32 {
+        distance(self.metric, query, &self.nodes[node].vector)
+    }
+
+    /// Closest nodes to the query on the layer, sorted by distance.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry_points: &[usize],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Candidate> {
+        let mut visited = HashSet::new();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+        for &node in entry_points {
+            if visited.insert(node) {
+                let candidate = Candidate {
+                    distance: self.distance_to(query, node),
+                    node,
+                };
+                candidates.push(Reverse(candidate));
+                results.push(candidate);
+            }
+        }
+        while let Some(Reverse(current)) = candidates.pop() {
+            if results.len() >= ef
+                && results
+                    .peek()
+                    .is_some_and(|furthest: &Candidate| current.distance > furthest.distance)
+            {
+                break;
+            }
+            let Some(neighbors) = self.nodes[current.node].neighbors.get(layer) else {
+                continue;
+            };
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let candidate = Candidate {
+                    distance: self.distance_to(query, neighbor),
+                    node: neighbor,
+                };
+                if results.len() < ef
+                    || results
+                        .peek()
+                        .is_some_and(|furthest| candidate.distance < furthest.distance)
+                {
+                    candidates.push(Reverse(candidate));
+                    results.push(candidate);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+
+    /// Entry point on the layer, descending greedily from the top.
+    fn descend(&self, query: &[f32], to_layer: usize) -> Option<usize> {
+        let mut entry_point = self.entry_point?;
+        let top_layer = self.nodes[entry_point].neighbors.len() - 1;
+        for layer in (to_layer + 1..=top_layer).rev() {
+            entry_point = self.search_layer(query, &[entry_point], 1, layer)[0].node;
+        }
+        Some(entry_point)
+    }
+
+    fn insert(&mut self, id: Arc<str>, vector: Vec<f32>) {
+        self.remove(&id);
+        let level = self.random_level(&id);
+        let node = self.nodes.len();
+        self.nodes.push(HnswNode {
+            id: id.clone(),
+            vector,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.node_by_id.insert(id, node);
+        let Some(entry_point) = self.entry_point else {
+            self.entry_point = Some(node);
+            return;
+        };
+        let top_layer = self.nodes[entry_point].neighbors.len() - 1;
+        let query = self.nodes[node].vector.clone();
+        let mut entry_points = vec![self.descend(&query, level).unwrap_or(entry_point)];
+        for layer in (0..=level.min(top_layer)).rev() {
+            let candidates = self.search_layer(&query, &entry_points, self.ef_construction, layer);
+            let max_neighbors = self.max_neighbors(layer);
+            let neighbors = candidates
+                .iter()
+                .take(max_neighbors)
+                .map(|c| c.node)
+                .collect::<Vec<_>>();
+            for &neighbor in neighbors.iter() {
+                self.nodes[neighbor].neighbors[layer].push(node);
+                if self.nodes[neighbor].neighbors[layer].len() > max_neighbors {
+                    self.prune(neighbor, layer, max_neighbors);
+                }
+            }
+            self.nodes[node].neighbors[layer] = neighbors;
+            entry_points = candidates.iter().map(|c| c.node).collect();
+        }
+        if level > top_layer {
+            self.entry_point = Some(node);
+        }
+    }
+
+    /// Keep the closest neighbors of the node on the layer.
+    fn prune(&mut self, node: usize, layer: usize, max_neighbors: usize) {
+        let mut neighbors = self.nodes[node].neighbors[layer]
+            .iter()
+            .map(|&neighbor| Candidate {
+                distance: distance(
+                    self.metric,
+                    &self.nodes[node].vector,
+                    &self.nodes[neighbor].vector,
+                ),
+                node: neighbor,
+            })
+            .collect::<Vec<_>>();
+        neighbors.sort();
+        neighbors.truncate(max_neighbors);
+        self.nodes[node].neighbors[layer] = neighbors.into_iter().map(|c| c.node).collect();
+    }
+
+    fn remove(&mut self, id: &str) {
+        let Some(node) = self.node_by_id.remove(id) else {
+            return;
+        };
+        self.nodes[node].deleted = true;
+        if self.node_by_id.len() * 2 < self.nodes.len() {
+            self.rebuild();
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let nodes = std::mem::take(&mut self.nodes);
+        self.node_by_id.clear();
+        self.entry_point = None;
+        for node in nodes.into_iter().filter(|node| !node.deleted) {
+            self.insert(node.id, node.vector);
+        }
+    }
+
+    fn search(&self, query: &[f32], k: usize) -> Vec<(Arc<str>, f32)> {
+        let Some(entry_point) = self.descend(query, 0) else {
+            return vec![];
+        };
+        // Removed nodes are skipped in results, so search wider to make up for them.
+        let num_deleted = self.nodes.len() - self.len();
+        let ef = HNSW_EF_SEARCH.max(k) + num_deleted.min(k);
+        self.search_layer(query, &[entry_point], ef, 0)
+            .into_iter()
+            .filter(|c| !self.nodes[c.node].deleted)
+            .take(k)
+            .map(|c| (self.nodes[c.node].id.clone(), c.distance))
+            .collect()
+    }
+}
+
+/// Inverted file index with exact distances within lists. Lists are trained by k-means once
+/// there're enough vectors, and retrained whenever the number of vectors doubles.
+struct IvfFlat {
+    metric: VectorSimilarityMetric,
+    lists: usize,
+    vectors: HashMap<Arc<str>, (Vec<f32>, usize)>,
+    centroids: Vec<Vec<f32>>,
+    members: Vec<HashSet<Arc<str>>>,
+    trained_size: usize,
+}
+
+impl IvfFlat {
+    fn new(metric: VectorSimilarityMetric, lists: usize) -> Self {
+        Self {
+            metric,
+            lists: lists.max(1),
+            vectors: HashMap::new(),
+            centroids: Vec::new(),
+            members: Vec::new(),
+            trained_size: 0,
+        }
+    }
+
+    fn nearest_centroids(&self, vector: &[f32], n: usize) -> Vec<usize> {
+        let mut centroids = self
+            .centroids
+            .iter()
+            .enumerate()
+            .map(|(i, centroid)| Candidate {
+                distance: distance(self.metric, vector, centroid),
+                node: i,
+            })
+            .collect::<Vec<_>>();
+        centroids.sort();
+        centroids.into_iter().take(n).map(|c| c.node).collect()
+    }
+
+    fn insert(&mut self, id: Arc<str>, vector: Vec<f32>) {
+        self.remove(&id);
+        let list = if self.centroids.is_empty() {
+            0
+        } else {
+            let list = self.nearest_centroids(&vector, 1)[0];
+            self.members[list].insert(id.clone());
+            list
+        };
+        self.vectors.insert(id, (vector, list));
+        if self.vectors.len() >= self.lists && self.vectors.len() >= self.trained_size * 2 {
+            self.train();
+        }
+    }
+
+    fn remove(&mut self, id: &str) {
+        if let Some((_, list)) = self.vectors.remove(id)
+            && let Some(members) = self.members.get_mut(list)
+        {
+            members.remove(id);
+        }
+    }
+
+    fn train(&mut self) {
+        let mut ids = self.vectors.keys().cloned().collect::<Vec<_>>();
+        ids.sort();
+        let step = ids.len() / self.lists;
+        let mut centroids = (0..self.lists)
+            .map(|i| self.vectors[&ids[i * step]].0.clone())
+            .collect::<Vec<_>>();
+        let dimension = centroids[0].len();
+        for _ in 0..IVF_KMEANS_ITERATIONS {
+            self.centroids = centroids;
+            let mut sums = vec![vec![0.0f32; dimension]; self.lists];
+            let mut counts = vec![0usize; self.lists];
+            for (vector, _) in self.vectors.values() {
+                let list = self.nearest_centroids(vector, 1)[0];
+                for (sum, x) in sums[list].iter_mut().zip(vector.iter()) {
+                    *sum += x;
+                }
+                counts[list] += 1;
+            }
+            centroids = sums
+                .into_iter()
+                .zip(counts)
+                .zip(self.centroids.iter())
+                .map(|((sum, count), prev)| {
+                    if count == 0 {
+                        prev.clone()
+                    } else {
+                        sum.into_iter().map(|x| x / count as f32).collect()
+                    }
+                })
+                .collect();
+        }
+        self.centroids = centroids;
+        self.members = vec![HashSet::new(); self.lists];
+        let assignments = self
+            .vectors
+            .iter()
+            .map(|(id, (vector, _))| (id.clone(), self.nearest_centroids(vector, 1)[0]))
+            .collect::<Vec<_>>();
+        for (id, list) in assignments {
+            self.members[list].insert(id.clone());
+            if let Some(entry) = self.vectors.get_mut(&id) {
+                entry.1 = list;
+            }
+        }
+        self.trained_size = self.vectors.len();
+    }
+
+    fn search(&self, query: &[f32], k: usize) -> Vec<(Arc<str>, f32)> {
+        let candidates: Box<dyn Iterator<Item = &Arc<str>>> = if self.centroids.is_empty() {
+            Box::new(self.vectors.keys())
+        } else {
+            let probes = (self.lists as f64).sqrt().ceil() as usize;
+            Box::new(
+                self.nearest_centroids(query, probes)
+                    .into_iter()
+                    .flat_map(|list| self.members[list].iter()),
+            )
+        };
+        top_k(
+            candidates.map(|id| (id, distance(self.metric, query, &self.vectors[id].0))),
+            k,
+        )
+    }
+}
+
+fn top_k<'a>(
+    candidates: impl Iterator<Item = (&'a Arc<str>, f32)>,
+    k: usize,
+) -> Vec<(Arc<str>, f32)> {
+    let mut results = candidates.collect::<Vec<_>>();
+    results.sort_by(|a, b| a.1.total_cmp(&b.1));
+    results
+        .into_iter()
+        .take(k)
+        .map(|(id, distance)| (id.clone(), distance))
+        .collect()
+}
+
+enum IndexKind {
+    Flat(HashMap<Arc<str>, Vec<f32>>),
+    Hnsw(Hnsw),
+    IvfFlat(IvfFlat),
+}
+
+/// Index of vectors by ID, for nearest neighbor search under a metric.
+pub struct VectorIndex {
+    metric: VectorSimilarityMetric,
+    kind: IndexKind,
+}
+
+impl VectorIndex {
+    /// Without a method, search is exact.
+    pub fn new(metric: VectorSimilarityMetric, method: Option<&VectorIndexMethod>) -> Self {
+        let kind = match method {
+            None => IndexKind::Flat(HashMap::new()),
+            Some(VectorIndexMethod::Hnsw { m, ef_construction }) => IndexKind::Hnsw(Hnsw::new(
+                metric,
+                m.map_or(DEFAULT_HNSW_M, |m| m as usize),
+                ef_construction.map_or(DEFAULT_HNSW_EF_CONSTRUCTION, |ef| ef as usize),
+            )),
+            Some(VectorIndexMethod::IvfFlat { lists }) => IndexKind::IvfFlat(IvfFlat::new(
+                metric,
+                lists.map_or(DEFAULT_IVF_LISTS, |lists| lists as usize),
+            )),
+        };
+        Self { metric, kind }
+    }
+
+    pub fn metric(&self) -> VectorSimilarityMetric {
+        self.metric
+    }
+
+    /// Insert the vector, replacing any existing one with the same ID.
+    pub fn upsert(&mut self, id: Arc<str>, vector: Vec<f32>) {
+        match &mut self.kind {
+            IndexKind::Flat(vectors) => {
+                vectors.insert(id, vector);
+            }
+            IndexKind::Hnsw(hnsw) => hnsw.insert(id, vector),
+            IndexKind::IvfFlat(ivf) => ivf.insert(id, vector),
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        match &mut self.kind {
+            IndexKind::Flat(vectors) => {
+                vectors.remove(id);
+            }
+            IndexKind::Hnsw(hnsw) => hnsw.remove(id),
+            IndexKind::IvfFlat(ivf) => ivf.remove(id),
+        }
+    }
+
+    /// IDs of the (approximately) nearest `k` vectors with their distances, closest first.
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(Arc<str>, f32)> {
+        match &self.kind {
+            IndexKind::Flat(vectors) => top_k(
+                vectors
+                    .iter()
+                    .map(|(id, vector)| (id, distance(self.metric, query, vector))),
+                k,
+            ),
+            IndexKind::Hnsw(hnsw) => hnsw.search(query, k),
+            IndexKind::IvfFlat(ivf) => ivf.search(query, k),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points on a 2D grid, so nearest neighbors are easy to tell.
+    fn grid_index(method: Option<&VectorIndexMethod>) -> VectorIndex {
+        let mut index = VectorIndex::new(VectorSimilarityMetric::L2Distance, method);
+        for x in 0..20 {
+            for y in 0..20 {
+                index.upsert(format!("{x},{y}").into(), vec![x as f32, y as f32]);
+            }
+        }
+        index
+    }
+
+    fn ids(results: &[(Arc<str>, f32)]) -> Vec<&str> {
+        results.iter().map(|(id, _)| id.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_search() {
+        for method in [
+            None,
+            Some(VectorIndexMethod::Hnsw {
+                m: Some(8),
+                ef_construction: None,
+            }),
+            Some(VectorIndexMethod::IvfFlat { lists: Some(16) }),
+        ] {
+            let mut index = grid_index(method.as_ref());
+            let results = index.search(&[3.1, 4.2], 1);
+            assert_eq!(ids(&results), vec!["3,4"], "{method:?}");
+            assert!((results[0].1 - (0.01f32 + 0.04).sqrt()).abs() < 1e-5);
+
+            // Removed vectors are no longer found, and replaced ones are found at new places.
+            index.remove("3,4");
+            index.upsert("5,5".into(), vec![3.0, 4.0]);
+            assert_eq!(
+                ids(&index.search(&[3.1, 4.2], 1)),
+                vec!["5,5"],
+                "{method:?}"
+            );
+            assert_eq!(index.search(&[5.0, 5.0], 1).len(), 1);
+
+            // Removing most vectors keeps the rest searchable.
+            for x in 0..20 {
+                for y in 0..20 {
+                    if x >= 2 || y >= 2 {
+                        index.remove(&format!("{x},{y}"));
+                    }
+                }
+            }
+            let results = index.search(&[0.0, 0.0], 10);
+            let mut results = ids(&results);
+            results.sort();
+            assert_eq!(results, vec!["0,0", "0,1", "1,0", "1,1"], "{method:?}");
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut index = VectorIndex::new(VectorSimilarityMetric::CosineSimilarity, None);
+        index.upsert("x".into(), vec![10.0, 0.0]);
+        index.upsert("y".into(), vec![0.0, 1.0]);
+        let results = index.search(&[1.0, 0.1], 2);
+        assert_eq!(ids(&results), vec!["x", "y"]);
+        assert!(score(index.metric(), results[0].1) > 0.99);
+
+        let mut index = VectorIndex::new(VectorSimilarityMetric::InnerProduct, None);
+        index.upsert("x".into(), vec![10.0, 0.0]);
+        index.upsert("y".into(), vec![0.0, 1.0]);
+        let results = index.search(&[0.1, 1.0], 2);
+        assert_eq!(ids(&results), vec!["y", "x"]);
+        assert_eq!(score(index.metric(), results[1].1), 1.0);
+    }
+}
@@ -0,0 +1,855 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use git2::{ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::base::field_attrs;
+use crate::{fields_value, ops::sdk::*};
+use recoco_splitters::pattern_matcher::PatternMatcher;
+
+const DEFAULT_REV: &str = "HEAD";
+const DEFAULT_MAX_HISTORY_DEPTH: usize = 1000;
+const SYMLINK_FILE_MODE: i32 = 0o120000;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Spec {
+    /// Path to a local repository, either a working tree or a bare repository.
+    path: String,
+    /// Branch, tag or commit to read. Defaults to `HEAD`.
+    rev: Option<String>,
+    binary: bool,
+    included_patterns: Option<Vec<String>>,
+    excluded_patterns: Option<Vec<String>>,
+    max_file_size: Option<i64>,
+    /// If set, checks at this interval whether `rev` moved to another commit, and captures the
+    /// files changed between the two commits.
+    poll_interval: Option<std::time::Duration>,
+    /// Maximum number of commits walked back from `rev` to find the last commit modifying each
+    /// file, when there's no previous snapshot to stop at (e.g. on start, or after `rev` is
+    /// rebased). Defaults to 1000. Files not modified within them are attributed to the commit
+    /// `rev` resolved to when they're processed, and only reprocessed when their content changes.
+    max_history_depth: Option<usize>,
+}
+
+#[derive(Debug)]
+struct CommitInfo {
+    id: Arc<str>,
+    author: Arc<str>,
+    time: chrono::DateTime<chrono::FixedOffset>,
+}
+
+impl CommitInfo {
+    fn new(commit: &git2::Commit<'_>) -> Result<Self> {
+        let author = commit.author();
+        let author = match (author.name(), author.email()) {
+            (Some(name), Some(email)) if !email.is_empty() => format!("{name} <{email}>"),
+            (Some(name), _) => name.to_string(),
+            (None, _) => String::new(),
+        };
+        let time = commit.time();
+        let offset = chrono::FixedOffset::east_opt(time.offset_minutes() * 60)
+            .ok_or_else(|| client_error!("Invalid time offset in commit {}", commit.id()))?;
+        let time = chrono::DateTime::from_timestamp(time.seconds(), 0)
+            .ok_or_else(|| client_error!("Invalid time in commit {}", commit.id()))?
+            .with_timezone(&offset);
+        Ok(Self {
+            id: commit.id().to_string().into(),
+            author: author.into(),
+            time,
+        })
+    }
+}
+
+/// Files in the tree of a commit, with the last commit modifying each of them.
+struct Snapshot {
+    commit_id: Oid,
+    tree_id: Oid,
+    commit: Arc<CommitInfo>,
+    /// Files not modified within the walked history are missing.
+    last_commits: HashMap<String, Arc<CommitInfo>>,
+}
+
+struct Executor {
+    repo: Arc<Mutex<Repository>>,
+    rev: String,
+    binary: bool,
+    pattern_matcher: Arc<PatternMatcher>,
+    max_file_size: Option<i64>,
+    poll_interval: Option<std::time::Duration>,
+    max_history_depth: usize,
+    snapshot: Mutex<Option<Arc<Snapshot>>>,
+}
+
+/// A file in the tree, as a path relative to the repository root and the id of its blob.
+type TreeFile = (String, Oid);
+
+fn list_tree_files(
+    repo: &Repository,
+    tree_id: Oid,
+    pattern_matcher: &PatternMatcher,
+) -> Result<Vec<TreeFile>> {
+    let tree = repo.find_tree(tree_id)?;
+    let mut files = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        let Some(name) = entry.name() else {
+            warn!("Skipped ill-formed file path in {dir}");
+            return TreeWalkResult::Skip;
+        };
+        let path = format!("{dir}{name}");
+        match entry.kind() {
+            Some(ObjectType::Tree) => {
+                if pattern_matcher.is_excluded(&path) {
+                    TreeWalkResult::Skip
+                } else {
+                    TreeWalkResult::Ok
+                }
+            }
+            Some(ObjectType::Blob) => {
+                if entry.filemode() != SYMLINK_FILE_MODE && pattern_matcher.is_file_included(&path)
+                {
+                    files.push((path, entry.id()));
+                }
+                TreeWalkResult::Ok
+            }
+            // Submodules
+            _ => TreeWalkResult::Skip,
+        }
+    })?;
+    Ok(files)
+}
+
+/// Build the snapshot of a commit. The last commit modifying each file is searched along the
+/// first-parent history only, so changes merged from other branches are attributed to the merge
+/// commit. The walk stops at the previous snapshot if it's a first-parent ancestor (files not
+/// changed since then keep their previous last commit), or after `max_history_depth` commits.
+fn build_snapshot(
+    repo: &Repository,
+    commit_id: Oid,
+    pattern_matcher: &PatternMatcher,
+    prev: Option<&Snapshot>,
+    max_history_depth: usize,
+) -> Result<Snapshot> {
+    let head = repo.find_commit(commit_id)?;
+    let tree_id = head.tree_id();
+    let mut pending: HashSet<String> = list_tree_files(repo, tree_id, pattern_matcher)?
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    let mut last_commits = HashMap::with_capacity(pending.len());
+
+    let mut current = head.clone();
+    let mut depth = 0;
+    while !pending.is_empty() {
+        if let Some(prev) = prev
+            && current.id() == prev.commit_id
+        {
+            for path in pending.drain() {
+                if let Some(info) = prev.last_commits.get(&path) {
+                    last_commits.insert(path, info.clone());
+                }
+            }
+            break;
+        }
+        if depth == max_history_depth {
+            break;
+        }
+        depth += 1;
+        let parent = current.parents().next();
+        let parent_tree = parent.as_ref().map(|p| p.tree()).transpose()?;
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&current.tree()?), None)?;
+        let mut info: Option<Arc<CommitInfo>> = None;
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().and_then(|p| p.to_str()) else {
+                continue;
+            };
+            if pending.remove(path) {
+                let info = match &info {
+                    Some(info) => info.clone(),
+                    None => info.insert(Arc::new(CommitInfo::new(&current)?)).clone(),
+                };
+                last_commits.insert(path.to_string(), info);
+            }
+        }
+        match parent {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+
+    Ok(Snapshot {
+        commit_id,
+        tree_id,
+        commit: Arc::new(CommitInfo::new(&head)?),
+        last_commits,
+    })
+}
+
+impl Executor {
+    fn resolve_commit(repo: &Repository, rev: &str) -> Result<Oid> {
+        let commit = repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| client_error!("Failed to resolve `{rev}` to a commit: {e}"))?;
+        Ok(commit.id())
+    }
+
+    /// Resolve `rev` again and update the snapshot if it moved.
+    /// Returns the previous snapshot, if any, along with the current one.
+    async fn refresh_snapshot(&self) -> Result<(Option<Arc<Snapshot>>, Arc<Snapshot>)> {
+        let prev = self.snapshot.lock().unwrap().clone();
+        let repo = self.repo.clone();
+        let rev = self.rev.clone();
+        let pattern_matcher = self.pattern_matcher.clone();
+        let max_history_depth = self.max_history_depth;
+        let prev_for_build = prev.clone();
+        let snapshot = tokio::task::spawn_blocking(move || -> Result<Arc<Snapshot>> {
+            let repo = repo.lock().unwrap();
+            let commit_id = Self::resolve_commit(&repo, &rev)?;
+            match prev_for_build {
+                Some(prev) if prev.commit_id == commit_id => Ok(prev),
+                prev => Ok(Arc::new(build_snapshot(
+                    &repo,
+                    commit_id,
+                    &pattern_matcher,
+                    prev.as_deref(),
+                    max_history_depth,
+                )?)),
+            }
+        })
+        .await??;
+        *self.snapshot.lock().unwrap() = Some(snapshot.clone());
+        Ok((prev, snapshot))
+    }
+
+    async fn current_snapshot(&self) -> Result<Arc<Snapshot>> {
+        let snapshot = self.snapshot.lock().unwrap().clone();
+        match snapshot {
+            Some(snapshot) => Ok(snapshot),
+            None => Ok(self.refresh_snapshot().await?.1),
+        }
+    }
+
+    fn last_commit_of<'a>(snapshot: &'a Snapshot, path: &str) -> &'a Arc<CommitInfo> {
+        snapshot.last_commits.get(path).unwrap_or(&snapshot.commit)
+    }
+
+    /// Commit times aren't monotonic (clock skew, rebases, `rev` moving backwards), so no
+    /// ordinal is provided. Versions are told apart by the blob and the commit it's attributed
+    /// to, which covers all fields of the row. Files not modified within the walked history are
+    /// told apart by the blob only, so they aren't reprocessed each time `rev` moves.
+    fn content_version_fp(oid: Oid, snapshot: &Snapshot, path: &str) -> Vec<u8> {
+        let mut fp = oid.as_bytes().to_vec();
+        if let Some(last_commit) = snapshot.last_commits.get(path) {
+            fp.extend_from_slice(last_commit.id.as_bytes());
+        }
+        fp
+    }
+}
+
+#[async_trait]
+impl SourceExecutor for Executor {
+    async fn list(
+        &self,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<BoxStream<'async_trait, Result<Vec<PartialSourceRow>>>> {
+        let (_, snapshot) = self.refresh_snapshot().await?;
+        let repo = self.repo.clone();
+        let pattern_matcher = self.pattern_matcher.clone();
+        let max_file_size = self.max_file_size;
+        let tree_id = snapshot.tree_id;
+        let files = tokio::task::spawn_blocking(move || -> Result<Vec<TreeFile>> {
+            let repo = repo.lock().unwrap();
+            let mut files = list_tree_files(&repo, tree_id, &pattern_matcher)?;
+            if let Some(max_size) = max_file_size {
+                let odb = repo.odb()?;
+                files.retain(|(_, oid)| {
+                    odb.read_header(*oid)
+                        .is_ok_and(|(size, _)| size as u64 <= max_size as u64)
+                });
+            }
+            Ok(files)
+        })
+        .await??;
+
+        let include_ordinal = options.include_ordinal;
+        let include_content_version_fp = options.include_content_version_fp;
+        let rows = files.into_iter().map(move |(path, oid)| {
+            let content_version_fp =
+                include_content_version_fp.then(|| Self::content_version_fp(oid, &snapshot, &path));
+            Ok(vec![PartialSourceRow {
+                key: KeyValue::from_single_part(path),
+                key_aux_info: serde_json::Value::Null,
+                data: PartialSourceRowData {
+                    ordinal: include_ordinal.then(Ordinal::unavailable),
+                    content_version_fp,
+                    value: None,
+                },
+            }])
+        });
+        Ok(futures::stream::iter(rows).boxed())
+    }
+
+    async fn get_value(
+        &self,
+        key: &KeyValue,
+        _key_aux_info: &serde_json::Value,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<PartialSourceRowData> {
+        let path = key.single_part()?.str_value()?.to_string();
+        if !self.pattern_matcher.is_file_included(&path) {
+            return Ok(PartialSourceRowData {
+                value: Some(SourceValue::NonExistence),
+                ordinal: Some(Ordinal::unavailable()),
+                content_version_fp: None,
+            });
+        }
+
+        let snapshot = self.current_snapshot().await?;
+        let repo = self.repo.clone();
+        let tree_id = snapshot.tree_id;
+        let max_file_size = self.max_file_size;
+        let include_value = options.include_value;
+        let lookup_path = path.clone();
+        let blob =
+            tokio::task::spawn_blocking(move || -> Result<Option<(Oid, Option<Vec<u8>>)>> {
+                let repo = repo.lock().unwrap();
+                let tree = repo.find_tree(tree_id)?;
+                let entry = match tree.get_path(Path::new(&lookup_path)) {
+                    Ok(entry) => entry,
+                    Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+                    Err(e) => return Err(e.into()),
+                };
+                if entry.kind() != Some(ObjectType::Blob) || entry.filemode() == SYMLINK_FILE_MODE {
+                    return Ok(None);
+                }
+                let oid = entry.id();
+                if let Some(max_size) = max_file_size {
+                    let (size, _) = repo.odb()?.read_header(oid)?;
+                    if size as u64 > max_size as u64 {
+                        return Ok(None);
+                    }
+                }
+                let content = if include_value {
+                    Some(repo.find_blob(oid)?.content().to_vec())
+                } else {
+                    None
+                };
+                Ok(Some((oid, content)))
+            })
+            .await??;
+
+        let Some((oid, content)) = blob else {
+            return Ok(PartialSourceRowData {
+                value: Some(SourceValue::NonExistence),
+                ordinal: Some(Ordinal::unavailable()),
+                content_version_fp: None,
+            });
+        };
+        let last_commit = Self::last_commit_of(&snapshot, &path);
+        let value = content.map(|content| {
+            let content: Value = if self.binary {
+                content.into()
+            } else {
+                let (s, _) = utils::bytes_decode::bytes_to_string(&content);
+                s.into()
+            };
+            SourceValue::Existence(fields_value!(
+                content,
+                last_commit.id.clone(),
+                last_commit.author.clone(),
+                last_commit.time
+            ))
+        });
+        Ok(PartialSourceRowData {
+            value,
+            ordinal: options.include_ordinal.then(Ordinal::unavailable),
+            content_version_fp: options
+                .include_content_version_fp
+                .then(|| Self::content_version_fp(oid, &snapshot, &path)),
+        })
+    }
+
+    fn provides_ordinal(&self) -> bool {
+        false
+    }
+
+    async fn change_stream(
+        &self,
+    ) -> Result<Option<BoxStream<'async_trait, Result<SourceChangeMessage>>>> {
+        let Some(poll_interval) = self.poll_interval else {
+            return Ok(None);
+        };
+        let stream = async_stream::stream! {
+            let mut interval = tokio::time::interval(poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let changes = self.poll_changes().await;
+                match changes {
+                    Ok(changes) if changes.is_empty() => {}
+                    Ok(changes) => yield Ok(SourceChangeMessage { changes, ack_fn: None }),
+                    Err(e) => yield Err(e),
+                }
+            }
+        };
+        Ok(Some(stream.boxed()))
+    }
+}
+
+impl Executor {
+    /// Capture the files changed since the last snapshot, from the diff between the two trees.
+    async fn poll_changes(&self) -> Result<Vec<SourceChange>> {
+        let (Some(prev), snapshot) = self.refresh_snapshot().await? else {
+            return Ok(Vec::new());
+        };
+        if prev.commit_id == snapshot.commit_id {
+            return Ok(Vec::new());
+        }
+        let repo = self.repo.clone();
+        let (old_tree_id, new_tree_id) = (prev.tree_id, snapshot.tree_id);
+        let paths = tokio::task::spawn_blocking(move || -> Result<Vec<(String, bool)>> {
+            let repo = repo.lock().unwrap();
+            let old_tree = repo.find_tree(old_tree_id)?;
+            let new_tree = repo.find_tree(new_tree_id)?;
+            let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+            let mut paths = Vec::new();
+            for delta in diff.deltas() {
+                let deleted = delta.status() == git2::Delta::Deleted;
+                let file = if deleted {
+                    delta.old_file()
+                } else {
+                    delta.new_file()
+                };
+                if let Some(path) = file.path().and_then(|p| p.to_str()) {
+                    paths.push((path.to_string(), deleted));
+                }
+            }
+            Ok(paths)
+        })
+        .await??;
+
+        let changes = paths
+            .into_iter()
+            .filter(|(path, _)| self.pattern_matcher.is_file_included(path))
+            .map(|(path, deleted)| SourceChange {
+                data: if deleted {
+                    PartialSourceRowData {
+                        value: Some(SourceValue::NonExistence),
+                        ordinal: Some(Ordinal::unavailable()),
+                        content_version_fp: None,
+                    }
+                } else {
+                    PartialSourceRowData {
+                        value: None,
+                        ordinal: Some(Ordinal::unavailable()),
+                        content_version_fp: None,
+                    }
+                },
+                key: KeyValue::from_single_part(path),
+                key_aux_info: serde_json::Value::Null,
+            })
+            .collect();
+        Ok(changes)
+    }
+}
+
+pub struct Factory;
+
+#[async_trait]
+impl SourceFactoryBase for Factory {
+    type Spec = Spec;
+
+    fn name(&self) -> &str {
+        "GitRepo"
+    }
+
+    async fn get_output_schema(
+        &self,
+        spec: &Spec,
+        _context: &FlowInstanceContext,
+    ) -> Result<EnrichedValueType> {
+        let mut struct_schema = StructSchema::default();
+        let mut schema_builder = StructSchemaBuilder::new(&mut struct_schema);
+        let filename_field = schema_builder.add_field(FieldSchema::new(
+            "filename",
+            make_output_type(BasicValueType::Str),
+        ));
+        schema_builder.add_field(FieldSchema::new(
+            "content",
+            make_output_type(if spec.binary {
+                BasicValueType::Bytes
+            } else {
+                BasicValueType::Str
+            })
+            .with_attr(
+                field_attrs::CONTENT_FILENAME,
+                serde_json::to_value(filename_field.to_field_ref())?,
+            ),
+        ));
+        schema_builder.add_field(FieldSchema::new(
+            "last_commit",
+            make_output_type(BasicValueType::Str),
+        ));
+        schema_builder.add_field(FieldSchema::new(
+            "last_commit_author",
+            make_output_type(BasicValueType::Str),
+        ));
+        schema_builder.add_field(FieldSchema::new(
+            "last_commit_time",
+            make_output_type(BasicValueType::OffsetDateTime),
+        ));
+
+        Ok(make_output_type(TableSchema::new(
+            TableKind::KTable(KTableInfo { num_key_parts: 1 }),
+            struct_schema,
+        )))
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        _source_name: &str,
+        spec: Spec,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<Box<dyn SourceExecutor>> {
+        let path = spec.path;
+        let repo = tokio::task::spawn_blocking(move || {
+            Repository::open(&path)
+                .map_err(|e| client_error!("Failed to open git repository at `{path}`: {e}"))
+        })
+        .await??;
+
+        Ok(Box::new(Executor {
+            repo: Arc::new(Mutex::new(repo)),
+            rev: spec.rev.unwrap_or_else(|| DEFAULT_REV.to_string()),
+            binary: spec.binary,
+            pattern_matcher: Arc::new(PatternMatcher::new(
+                spec.included_patterns,
+                spec.excluded_patterns,
+            )?),
+            max_file_size: spec.max_file_size,
+            poll_interval: spec.poll_interval,
+            max_history_depth: spec.max_history_depth.unwrap_or(DEFAULT_MAX_HISTORY_DEPTH),
+            snapshot: Mutex::new(None),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestRepo {
+        dir: std::path::PathBuf,
+        repo: Repository,
+    }
+
+    impl TestRepo {
+        fn new(name: &str) -> Result<Self> {
+            let dir = std::env::temp_dir().join(format!(
+                "recoco-git-repo-{name}-{}-{}",
+                std::process::id(),
+                chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+            ));
+            let repo = Repository::init(&dir)?;
+            Ok(Self { dir, repo })
+        }
+
+        /// Write (or delete, for `None`) files and commit them at the given time.
+        fn commit(&self, files: &[(&str, Option<&str>)], time: i64) -> Result<Oid> {
+            let mut index = self.repo.index()?;
+            for (path, content) in files {
+                let full_path = self.dir.join(path);
+                match content {
+                    Some(content) => {
+                        std::fs::create_dir_all(full_path.parent().unwrap())?;
+                        std::fs::write(&full_path, content)?;
+                        index.add_path(Path::new(path))?;
+                    }
+                    None => {
+                        std::fs::remove_file(&full_path)?;
+                        index.remove_path(Path::new(path))?;
+                    }
+                }
+            }
+            index.write()?;
+            let tree = self.repo.find_tree(index.write_tree()?)?;
+            let signature =
+                git2::Signature::new("Tester", "tester@example.com", &git2::Time::new(time, 0))?;
+            let parent = match self.repo.head() {
+                Ok(head) => Some(head.peel_to_commit()?),
+                Err(_) => None,
+            };
+            let parents = parent.iter().collect::<Vec<_>>();
+            Ok(self.repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "test commit",
+                &tree,
+                &parents,
+            )?)
+        }
+
+        /// Move `HEAD` (and the working tree) to the commit, e.g. to rewrite the history after it.
+        fn reset(&self, commit: Oid) -> Result<()> {
+            let commit = self.repo.find_object(commit, Some(ObjectType::Commit))?;
+            self.repo.reset(&commit, git2::ResetType::Hard, None)?;
+            Ok(())
+        }
+
+        fn executor(&self, excluded_patterns: Option<Vec<String>>) -> Result<Executor> {
+            Ok(Executor {
+                repo: Arc::new(Mutex::new(Repository::open(&self.dir)?)),
+                rev: DEFAULT_REV.to_string(),
+                binary: false,
+                pattern_matcher: Arc::new(PatternMatcher::new(None, excluded_patterns)?),
+                max_file_size: None,
+                poll_interval: None,
+                max_history_depth: DEFAULT_MAX_HISTORY_DEPTH,
+                snapshot: Mutex::new(None),
+            })
+        }
+    }
+
+    impl Drop for TestRepo {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn key_str(key: &KeyValue) -> String {
+        key.single_part().unwrap().str_value().unwrap().to_string()
+    }
+
+    /// The expected version fingerprint of a file with the content, last modified in the commit.
+    fn version_fp(content: &str, commit: Oid) -> Vec<u8> {
+        let mut fp = blob_version_fp(content);
+        fp.extend_from_slice(commit.to_string().as_bytes());
+        fp
+    }
+
+    /// The expected version fingerprint of a file not modified within the walked history.
+    fn blob_version_fp(content: &str) -> Vec<u8> {
+        let oid = Oid::hash_object(ObjectType::Blob, content.as_bytes()).unwrap();
+        oid.as_bytes().to_vec()
+    }
+
+    async fn list_versions(executor: &Executor) -> Result<Vec<(String, Vec<u8>)>> {
+        let options = SourceExecutorReadOptions {
+            include_ordinal: true,
+            include_content_version_fp: true,
+            ..Default::default()
+        };
+        let mut rows = executor
+            .list(&options)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .flatten()
+            .map(|row| {
+                assert_eq!(row.data.ordinal, Some(Ordinal::unavailable()));
+                (key_str(&row.key), row.data.content_version_fp.unwrap())
+            })
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(rows)
+    }
+
+    #[tokio::test]
+    async fn test_list_with_last_commits() -> Result<()> {
+        let test_repo = TestRepo::new("list")?;
+        let first = test_repo.commit(&[("a.txt", Some("a")), ("src/b.rs", Some("b"))], 1000)?;
+        let second = test_repo.commit(&[("src/b.rs", Some("b2")), ("c.md", Some("c"))], 2000)?;
+        test_repo.commit(&[("target/out.txt", Some("out"))], 3000)?;
+
+        let executor = test_repo.executor(Some(vec!["target".to_string()]))?;
+        assert!(!executor.provides_ordinal());
+        assert_eq!(
+            list_versions(&executor).await?,
+            [
+                ("a.txt".to_string(), version_fp("a", first)),
+                ("c.md".to_string(), version_fp("c", second)),
+                ("src/b.rs".to_string(), version_fp("b2", second)),
+            ]
+        );
+
+        let options = SourceExecutorReadOptions {
+            include_ordinal: true,
+            include_content_version_fp: true,
+            include_value: true,
+        };
+        let data = executor
+            .get_value(
+                &KeyValue::from_single_part("src/b.rs".to_string()),
+                &serde_json::Value::Null,
+                &options,
+            )
+            .await?;
+        assert_eq!(data.ordinal, Some(Ordinal::unavailable()));
+        assert_eq!(data.content_version_fp, Some(version_fp("b2", second)));
+        let Some(SourceValue::Existence(values)) = data.value else {
+            panic!("Expected an existing value");
+        };
+        assert_eq!(
+            values.fields,
+            [
+                Value::from("b2".to_string()),
+                Value::from(second.to_string()),
+                Value::from("Tester <tester@example.com>".to_string()),
+                Value::from(
+                    chrono::DateTime::from_timestamp(2000, 0)
+                        .unwrap()
+                        .fixed_offset()
+                ),
+            ]
+        );
+
+        let data = executor
+            .get_value(
+                &KeyValue::from_single_part("missing.txt".to_string()),
+                &serde_json::Value::Null,
+                &options,
+            )
+            .await?;
+        assert!(matches!(data.value, Some(SourceValue::NonExistence)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_poll_changes() -> Result<()> {
+        let test_repo = TestRepo::new("poll")?;
+        test_repo.commit(&[("a.txt", Some("a")), ("b.txt", Some("b"))], 1000)?;
+        let executor = test_repo.executor(None)?;
+        list_versions(&executor).await?;
+        assert!(executor.poll_changes().await?.is_empty());
+
+        let second = test_repo.commit(&[("a.txt", Some("a2"))], 2000)?;
+        let third = test_repo.commit(&[("b.txt", None), ("d.txt", Some("d"))], 3000)?;
+        let mut changes = executor
+            .poll_changes()
+            .await?
+            .into_iter()
+            .map(|change| {
+                let deleted = matches!(change.data.value, Some(SourceValue::NonExistence));
+                assert_eq!(change.data.ordinal, Some(Ordinal::unavailable()));
+                (key_str(&change.key), deleted)
+            })
+            .collect::<Vec<_>>();
+        changes.sort();
+        assert_eq!(
+            changes,
+            [
+                ("a.txt".to_string(), false),
+                ("b.txt".to_string(), true),
+                ("d.txt".to_string(), false),
+            ]
+        );
+        // Attribution of unchanged files is carried over from the previous snapshot.
+        assert_eq!(
+            list_versions(&executor).await?,
+            [
+                ("a.txt".to_string(), version_fp("a2", second)),
+                ("d.txt".to_string(), version_fp("d", third)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rebased_history() -> Result<()> {
+        let test_repo = TestRepo::new("rebase")?;
+        let first = test_repo.commit(&[("a.txt", Some("a")), ("b.txt", Some("b"))], 1000)?;
+        let second = test_repo.commit(&[("b.txt", Some("b2"))], 2000)?;
+        let executor = test_repo.executor(None)?;
+        assert_eq!(
+            list_versions(&executor).await?,
+            [
+                ("a.txt".to_string(), version_fp("a", first)),
+                ("b.txt".to_string(), version_fp("b2", second)),
+            ]
+        );
+
+        // Rewrite the history after the first commit. The previous snapshot isn't an ancestor
+        // anymore, so last commits are searched in the new history.
+        test_repo.reset(first)?;
+        let rewritten = test_repo.commit(&[("b.txt", Some("b2")), ("c.txt", Some("c"))], 3000)?;
+        let mut changes = executor
+            .poll_changes()
+            .await?
+            .into_iter()
+            .map(|change| key_str(&change.key))
+            .collect::<Vec<_>>();
+        changes.sort();
+        // The content of `b.txt` is the same in both trees.
+        assert_eq!(changes, ["c.txt".to_string()]);
+        assert_eq!(
+            list_versions(&executor).await?,
+            [
+                ("a.txt".to_string(), version_fp("a", first)),
+                ("b.txt".to_string(), version_fp("b2", rewritten)),
+                ("c.txt".to_string(), version_fp("c", rewritten)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_history_depth() -> Result<()> {
+        let test_repo = TestRepo::new("depth")?;
+        test_repo.commit(&[("a.txt", Some("a")), ("b.txt", Some("b"))], 1000)?;
+        let second = test_repo.commit(&[("b.txt", Some("b2"))], 2000)?;
+        let executor = Executor {
+            max_history_depth: 1,
+            ..test_repo.executor(None)?
+        };
+        // `a.txt` isn't modified within the walked history.
+        assert_eq!(
+            list_versions(&executor).await?,
+            [
+                ("a.txt".to_string(), blob_version_fp("a")),
+                ("b.txt".to_string(), version_fp("b2", second)),
+            ]
+        );
+
+        // Its version doesn't change when `rev` moves, so it isn't reprocessed.
+        let third = test_repo.commit(&[("c.txt", Some("c"))], 3000)?;
+        assert_eq!(
+            list_versions(&executor).await?,
+            [
+                ("a.txt".to_string(), blob_version_fp("a")),
+                ("b.txt".to_string(), version_fp("b2", second)),
+                ("c.txt".to_string(), version_fp("c", third)),
+            ]
+        );
+        let data = executor
+            .get_value(
+                &KeyValue::from_single_part("a.txt".to_string()),
+                &serde_json::Value::Null,
+                &SourceExecutorReadOptions {
+                    include_value: true,
+                    include_content_version_fp: true,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        assert_eq!(data.content_version_fp, Some(blob_version_fp("a")));
+        let Some(SourceValue::Existence(values)) = data.value else {
+            panic!("Expected an existing value");
+        };
+        assert_eq!(values.fields[1], Value::from(third.to_string()));
+        Ok(())
+    }
+}
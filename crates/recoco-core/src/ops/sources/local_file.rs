@@ -12,15 +12,23 @@
 
 use async_stream::try_stream;
 use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs::Metadata;
 use std::path::Path;
+use std::time::Duration;
 use std::{path::PathBuf, sync::Arc};
 use tracing::warn;
 
 use crate::base::field_attrs;
+use crate::ops::shared::archive::{self, ArchiveFormat, ArchiveLimits};
 use crate::{fields_value, ops::sdk::*};
+use recoco_splitters::ignore_files::IgnoreFiles;
 use recoco_splitters::pattern_matcher::PatternMatcher;
 
+const DEFAULT_WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+/// Maximum number of files in a change message when catching up with the snapshot.
+const CATCH_UP_BATCH_SIZE: usize = 1000;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Spec {
     path: String,
@@ -29,6 +37,35 @@ pub struct Spec {
     excluded_patterns: Option<Vec<String>>,
     max_file_size: Option<i64>,
     watch_changes: Option<bool>,
+    /// If true, files ignored by `.gitignore` and `.ignore` files, `.git/info/exclude` and git's
+    /// global excludes file are skipped, as well as `.git` directories. Defaults to false.
+    respect_ignore_files: Option<bool>,
+    /// Window to coalesce bursts of change events on the same path when watching changes.
+    /// Defaults to 200ms.
+    watch_debounce: Option<Duration>,
+    /// If set, zip, tar and gzip archives are expanded into one row per member, keyed by
+    /// `archive_path!member_path`, instead of one row for the archive. `%` and `!` in file paths
+    /// of keys are then percent-encoded (e.g. `a!b.txt` is keyed by `a%21b.txt`), so keys of
+    /// files and members never collide. For other sources, the `ExpandArchive` function expands
+    /// archive content instead.
+    archives: Option<ArchivesSpec>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivesSpec {
+    /// Patterns matched against paths of members within archives.
+    included_patterns: Option<Vec<String>>,
+    excluded_patterns: Option<Vec<String>>,
+    /// Maximum total uncompressed size of members read from an archive. Defaults to 1 GiB.
+    max_total_size: Option<i64>,
+    /// Maximum number of members read from an archive. Defaults to 100,000.
+    max_members: Option<i64>,
+}
+
+#[derive(Clone)]
+struct ArchiveExpansion {
+    pattern_matcher: Arc<PatternMatcher>,
+    limits: ArchiveLimits,
 }
 
 struct Executor {
@@ -38,6 +75,8 @@ struct Executor {
     pattern_matcher: PatternMatcher,
     max_file_size: Option<i64>,
     watch_changes: bool,
+    watch_debounce: Duration,
+    archives: Option<ArchiveExpansion>,
 }
 
 async fn ensure_metadata<'a>(
@@ -69,12 +108,762 @@ async fn try_ensure_metadata<'a>(
     metadata.as_ref()
 }
 
+fn non_existence() -> PartialSourceRowData {
+    PartialSourceRowData {
+        value: Some(SourceValue::NonExistence),
+        ordinal: Some(Ordinal::unavailable()),
+        content_version_fp: None,
+    }
+}
+
+/// Key of the row for a file. When expanding archives, the path is encoded so that it never
+/// collides with keys of archive members.
+fn file_key(path: &str, archives: Option<&ArchiveExpansion>) -> KeyValue {
+    KeyValue::from_single_part(if archives.is_some() {
+        archive::file_key(path)
+    } else {
+        path.to_string()
+    })
+}
+
+/// List members of an archive, without their content.
+async fn list_archive_members(
+    path: PathBuf,
+    archive_path: String,
+    archives: ArchiveExpansion,
+) -> Result<Vec<archive::ArchiveMember>> {
+    tokio::task::spawn_blocking(move || {
+        let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+        archive::read_members(
+            &archive_path,
+            reader,
+            &archives.pattern_matcher,
+            &archives.limits,
+            false,
+        )
+    })
+    .await?
+}
+
+/// Size, modification time (in microseconds) and inode of a file.
+/// The file is considered changed when any of them differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp(u64, i64, u64);
+
+impl FileStamp {
+    fn new(metadata: &Metadata) -> Self {
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|mtime| mtime.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_micros() as i64);
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(metadata);
+        #[cfg(not(unix))]
+        let inode = 0;
+        Self(metadata.len(), mtime, inode)
+    }
+}
+
+/// Split a relative path into its parent directory and file name.
+fn split_parent(path: &str) -> (&str, &str) {
+    path.rsplit_once(std::path::MAIN_SEPARATOR)
+        .unwrap_or(("", path))
+}
+
+fn join_parent(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{dir}{}{name}", std::path::MAIN_SEPARATOR)
+    }
+}
+
+/// Stamps of included files at `sub_path` (relative to the root), which is either a file or a
+/// directory walked recursively. An empty `sub_path` is the root.
+fn scan_files(
+    root_path: &Path,
+    pattern_matcher: &PatternMatcher,
+    sub_path: &str,
+) -> BTreeMap<String, FileStamp> {
+    let mut files = BTreeMap::new();
+    let mut dirs = Vec::new();
+    if sub_path.is_empty() {
+        dirs.push(String::new());
+    } else {
+        // Follow symlinks.
+        match std::fs::metadata(root_path.join(sub_path)) {
+            Ok(metadata) if metadata.is_dir() => {
+                if !pattern_matcher.is_excluded(sub_path) {
+                    dirs.push(sub_path.to_string());
+                }
+            }
+            Ok(metadata) => {
+                if pattern_matcher.is_file_included(sub_path) {
+                    files.insert(sub_path.to_string(), FileStamp::new(&metadata));
+                }
+            }
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Failed to get metadata for {sub_path}: {e}");
+                }
+            }
+        }
+    }
+    while let Some(dir) = dirs.pop() {
+        let entries = match std::fs::read_dir(root_path.join(&dir)) {
+            Ok(entries) => entries,
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Failed to read directory {dir}: {e}");
+                }
+                continue;
+            }
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    warn!("Failed to read directory entry in {dir}: {e}");
+                    continue;
+                }
+            };
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                warn!("Skipped ill-formed file path: {}", entry.path().display());
+                continue;
+            };
+            let relative_path = join_parent(&dir, &name);
+            // Follow symlinks.
+            let metadata = match std::fs::metadata(entry.path()) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    warn!("Failed to get metadata for {relative_path}: {e}");
+                    continue;
+                }
+            };
+            if metadata.is_dir() {
+                if !pattern_matcher.is_excluded(&relative_path) {
+                    dirs.push(relative_path);
+                }
+            } else if pattern_matcher.is_file_included(&relative_path) {
+                files.insert(relative_path, FileStamp::new(&metadata));
+            }
+        }
+    }
+    files
+}
+
+/// Files added, changed or removed from `old` to `new`, with their new stamps.
+fn diff_files<'a>(
+    old: impl IntoIterator<Item = (&'a String, &'a FileStamp)>,
+    new: &BTreeMap<String, FileStamp>,
+) -> Vec<(String, Option<FileStamp>)> {
+    let old: HashMap<&String, &FileStamp> = old.into_iter().collect();
+    let mut changes = new
+        .iter()
+        .filter(|(path, stamp)| old.get(path) != Some(stamp))
+        .map(|(path, stamp)| (path.clone(), Some(*stamp)))
+        .collect::<Vec<_>>();
+    changes.extend(
+        old.keys()
+            .filter(|path| !new.contains_key(**path))
+            .map(|path| ((*path).clone(), None)),
+    );
+    changes
+}
+
+/// Key of the entry marking the snapshot as complete, i.e. covering all files processed by a
+/// full listing. Other keys are directories.
+const SNAPSHOT_COMPLETE_KEY: serde_json::Value = serde_json::Value::Null;
+
+/// Snapshot of files under the root, persisted in the source state table with one entry per
+/// directory. It lets changes made while the flow wasn't running be caught up on startup
+/// without a full listing of the source.
+struct DirSnapshot {
+    state_store: Arc<dyn SourceStateStore>,
+    /// Stamps of processed files, by directory and file name.
+    committed: tokio::sync::Mutex<HashMap<String, BTreeMap<String, FileStamp>>>,
+}
+
+impl DirSnapshot {
+    /// Load the snapshot from the store. Also returns the stamps by relative path, or `None`
+    /// if the snapshot isn't complete.
+    async fn load(
+        state_store: Arc<dyn SourceStateStore>,
+    ) -> Result<(Self, Option<BTreeMap<String, FileStamp>>)> {
+        let mut committed = HashMap::new();
+        let mut files = BTreeMap::new();
+        let mut complete = false;
+        for (key, value) in state_store.list().await? {
+            if key == SNAPSHOT_COMPLETE_KEY {
+                complete = true;
+                continue;
+            }
+            let serde_json::Value::String(dir) = key else {
+                internal_bail!("Unexpected key in LocalFile source state: {key}");
+            };
+            let entries: BTreeMap<String, FileStamp> = utils::deser::from_json_value(value)?;
+            for (name, stamp) in entries.iter() {
+                files.insert(join_parent(&dir, name), *stamp);
+            }
+            committed.insert(dir, entries);
+        }
+        if !complete {
+            // Stamps are recorded again by the baseline.
+            committed.clear();
+        }
+        Ok((
+            Self {
+                state_store,
+                committed: tokio::sync::Mutex::new(committed),
+            },
+            complete.then_some(files),
+        ))
+    }
+
+    /// Record stamps of files covered by a full listing, and mark the snapshot as complete.
+    /// Files with stamps recorded since the snapshot was loaded are left as is.
+    async fn commit_baseline(&self, files: BTreeMap<String, FileStamp>) -> Result<()> {
+        let committed_paths = {
+            let committed = self.committed.lock().await;
+            committed
+                .iter()
+                .flat_map(|(dir, entries)| entries.keys().map(|name| join_parent(dir, name)))
+                .collect::<HashSet<_>>()
+        };
+        let baseline = files
+            .into_iter()
+            .filter(|(path, _)| !committed_paths.contains(path))
+            .map(|(path, stamp)| (path, Some(stamp)))
+            .collect::<Vec<_>>();
+        self.commit(&baseline).await?;
+        self.state_store
+            .upsert(&SNAPSHOT_COMPLETE_KEY, serde_json::Value::Bool(true))
+            .await
+    }
+
+    /// Record stamps of processed files. `None` is for removed files.
+    async fn commit(&self, changes: &[(String, Option<FileStamp>)]) -> Result<()> {
+        let mut committed = self.committed.lock().await;
+        let mut dirs = BTreeSet::new();
+        for (path, stamp) in changes {
+            let (dir, name) = split_parent(path);
+            let entries = committed.entry(dir.to_string()).or_default();
+            match stamp {
+                Some(stamp) => {
+                    entries.insert(name.to_string(), *stamp);
+                }
+                None => {
+                    entries.remove(name);
+                }
+            }
+            dirs.insert(dir);
+        }
+        for dir in dirs {
+            let key = serde_json::Value::String(dir.to_string());
+            match committed.get(dir).filter(|entries| !entries.is_empty()) {
+                Some(entries) => {
+                    self.state_store
+                        .upsert(&key, serde_json::to_value(entries)?)
+                        .await?
+                }
+                None => {
+                    committed.remove(dir);
+                    self.state_store.delete(&key).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// State of a change stream, shared across the events it handles.
+struct WatchState {
+    root_path: PathBuf,
+    pattern_matcher: PatternMatcher,
+    archives: Option<ArchiveExpansion>,
+    /// `None` if the source has no state store. Files in changed directories are then only
+    /// picked up by the next refresh.
+    snapshot: Option<Arc<DirSnapshot>>,
+    /// Latest known stamps of files, by relative path.
+    files: BTreeMap<String, FileStamp>,
+    /// Latest known member paths of archives, by relative path of the archive. Members removed
+    /// from an archive, or deleted with it, are deleted.
+    archive_members: HashMap<String, BTreeSet<String>>,
+}
+
+impl WatchState {
+    /// Refresh stamps of files at the path (relative to the root) and return the changed ones.
+    async fn refresh(&mut self, mut relative_path: String) -> Vec<(String, Option<FileStamp>)> {
+        if let Some(ignore_files) = self.pattern_matcher.ignore_files()
+            && IgnoreFiles::is_ignore_file(&relative_path)
+        {
+            let dir = split_parent(&relative_path).0.to_string();
+            ignore_files.invalidate(&dir);
+            // Files under the directory may be ignored or un-ignored now, so it's rescanned.
+            // Without a snapshot, they're only picked up by the next refresh.
+            if self.snapshot.is_some() {
+                relative_path = dir;
+            }
+        }
+        if self.snapshot.is_none() {
+            let is_dir = match std::fs::metadata(self.root_path.join(&relative_path)) {
+                Ok(metadata) => metadata.is_dir(),
+                Err(err) => {
+                    // If the file no longer exists, this may be a deletion event; do not skip it.
+                    if err.kind() != std::io::ErrorKind::NotFound {
+                        warn!("Failed to read metadata for path {relative_path}: {err}");
+                    }
+                    false
+                }
+            };
+            if is_dir || !self.pattern_matcher.is_file_included(&relative_path) {
+                return vec![];
+            }
+            return vec![(relative_path, None)];
+        }
+
+        let root_path = self.root_path.clone();
+        let pattern_matcher = self.pattern_matcher.clone();
+        let sub_path = relative_path.clone();
+        let scanned = match tokio::task::spawn_blocking(move || {
+            scan_files(&root_path, &pattern_matcher, &sub_path)
+        })
+        .await
+        {
+            Ok(scanned) => scanned,
+            Err(e) => {
+                warn!("Failed to scan {relative_path}: {e}");
+                return vec![];
+            }
+        };
+        let dir_prefix = if relative_path.is_empty() {
+            String::new()
+        } else {
+            format!("{relative_path}{}", std::path::MAIN_SEPARATOR)
+        };
+        let old = self.files.get_key_value(&relative_path).into_iter().chain(
+            self.files
+                .range(dir_prefix.clone()..)
+                .take_while(|(path, _)| path.starts_with(&dir_prefix)),
+        );
+        let changes = diff_files(old, &scanned);
+        for (path, stamp) in changes.iter() {
+            match stamp {
+                Some(stamp) => {
+                    self.files.insert(path.clone(), *stamp);
+                }
+                None => {
+                    self.files.remove(path);
+                }
+            }
+        }
+        changes
+    }
+
+    /// Record members of the archives among the paths.
+    async fn load_archive_members(&mut self, paths: impl IntoIterator<Item = String>) {
+        let Some(archives) = &self.archives else {
+            return;
+        };
+        for path in paths {
+            if ArchiveFormat::from_path(&path).is_none() {
+                continue;
+            }
+            let full_path = self.root_path.join(&path);
+            match list_archive_members(full_path.clone(), path.clone(), archives.clone()).await {
+                Ok(members) => {
+                    self.archive_members
+                        .insert(path, members.into_iter().map(|m| m.path).collect());
+                }
+                Err(e) => warn!("Failed to read archive {}: {e:?}", full_path.display()),
+            }
+        }
+    }
+
+    /// Build the message for changed files. Returns `None` if there's no row to update.
+    async fn change_message(
+        &mut self,
+        changes: Vec<(String, Option<FileStamp>)>,
+    ) -> Result<Option<SourceChangeMessage>> {
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        let mut source_changes = Vec::new();
+        for (path, stamp) in changes.iter() {
+            let Some(archives) = self
+                .archives
+                .as_ref()
+                .filter(|_| ArchiveFormat::from_path(path).is_some())
+            else {
+                source_changes.push(SourceChange {
+                    key: file_key(path, self.archives.as_ref()),
+                    key_aux_info: serde_json::Value::Null,
+                    data: PartialSourceRowData {
+                        ordinal: None,
+                        content_version_fp: None,
+                        value: None,
+                    },
+                });
+                continue;
+            };
+            // For a changed archive, all its current members are refreshed, as well as known
+            // members removed from it, or deleted with it.
+            let full_path = self.root_path.join(path);
+            let exists = stamp.is_some()
+                || (self.snapshot.is_none()
+                    && tokio::fs::try_exists(&full_path).await.unwrap_or(true));
+            let members: BTreeSet<String> = if exists {
+                match list_archive_members(full_path.clone(), path.clone(), archives.clone()).await
+                {
+                    Ok(members) => members.into_iter().map(|m| m.path).collect(),
+                    Err(e) => {
+                        warn!("Failed to read archive {}: {e:?}", full_path.display());
+                        continue;
+                    }
+                }
+            } else {
+                BTreeSet::new()
+            };
+            let known_members = self.archive_members.remove(path).unwrap_or_default();
+            source_changes.extend(members.union(&known_members).map(|member| SourceChange {
+                key: KeyValue::from_single_part(archive::member_key(path, member)),
+                key_aux_info: serde_json::Value::Null,
+                data: PartialSourceRowData {
+                    ordinal: None,
+                    content_version_fp: None,
+                    value: None,
+                },
+            }));
+            if !members.is_empty() {
+                self.archive_members.insert(path.clone(), members);
+            }
+        }
+        let Some(snapshot) = &self.snapshot else {
+            return Ok((!source_changes.is_empty()).then_some(SourceChangeMessage {
+                changes: source_changes,
+                ack_fn: None,
+            }));
+        };
+        if source_changes.is_empty() {
+            snapshot.commit(&changes).await?;
+            return Ok(None);
+        }
+        let snapshot = snapshot.clone();
+        Ok(Some(SourceChangeMessage {
+            changes: source_changes,
+            ack_fn: Some(Box::new(move || {
+                async move { snapshot.commit(&changes).await }.boxed()
+            })),
+        }))
+    }
+}
+
+impl Executor {
+    fn archive_expansion(&self, path: &str) -> Option<&ArchiveExpansion> {
+        self.archives
+            .as_ref()
+            .filter(|_| ArchiveFormat::from_path(path).is_some())
+    }
+
+    fn content_fields(&self, content: Vec<u8>) -> FieldValues {
+        if self.binary {
+            fields_value!(content)
+        } else {
+            let (s, _) = utils::bytes_decode::bytes_to_string(&content);
+            fields_value!(s)
+        }
+    }
+
+    /// Resolve a path relative to the root. Returns `None` if no included file can be reached.
+    async fn resolve_path(&self, path: &str) -> Result<Option<PathBuf>> {
+        let path_obj = Path::new(path);
+
+        // Prevent path traversal vulnerabilities by verifying the path
+        // doesn't contain parent directory or absolute components.
+        if path_obj.components().any(|c| {
+            matches!(
+                c,
+                std::path::Component::ParentDir
+                    | std::path::Component::RootDir
+                    | std::path::Component::Prefix(_)
+            )
+        }) || !self.pattern_matcher.is_file_included(path)
+        {
+            return Ok(None);
+        }
+
+        let path = self.root_path.join(path);
+
+        // Mitigate symlink-based path traversal by canonicalizing and checking boundaries
+        if let Some(root_canon) = &self.canonical_root_path {
+            let path_canon = match tokio::fs::canonicalize(&path).await {
+                Ok(c) => c,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    // Target file doesn't exist.
+                    return Ok(None);
+                }
+                Err(e) => Err(e)?,
+            };
+
+            if !path_canon.starts_with(root_canon) {
+                // Symlink points outside the allowed root directory.
+                return Ok(None);
+            }
+        } else {
+            // Root doesn't exist (failed to canonicalize during setup), so the file cannot exist.
+            return Ok(None);
+        }
+        Ok(Some(path))
+    }
+
+    async fn get_archive_member_value(
+        &self,
+        archives: &ArchiveExpansion,
+        archive_path: &str,
+        member_path: &str,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<PartialSourceRowData> {
+        if !archives.pattern_matcher.is_file_included(member_path) {
+            return Ok(non_existence());
+        }
+        let Some(path) = self.resolve_path(archive_path).await? else {
+            return Ok(non_existence());
+        };
+        let ordinal = if options.include_ordinal {
+            let mut metadata: Option<Metadata> = None;
+            let metadata = ensure_metadata(&path, &mut metadata).await?;
+            Some(metadata.modified()?.try_into()?)
+        } else {
+            None
+        };
+        let archive_path = archive_path.to_string();
+        let member_path = member_path.to_string();
+        let limits = archives.limits;
+        let include_value = options.include_value;
+        let member = tokio::task::spawn_blocking(move || {
+            let file = match std::fs::File::open(path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => Err(e)?,
+            };
+            archive::read_member(
+                &archive_path,
+                std::io::BufReader::new(file),
+                &member_path,
+                &limits,
+                include_value,
+            )
+        })
+        .await??;
+        let Some(member) = member else {
+            return Ok(non_existence());
+        };
+        Ok(PartialSourceRowData {
+            value: member
+                .content
+                .map(|content| SourceValue::Existence(self.content_fields(content))),
+            ordinal,
+            content_version_fp: Some(member.checksum),
+        })
+    }
+
+    async fn watch_changes_stream(
+        &self,
+        state_store: Option<Arc<dyn SourceStateStore>>,
+    ) -> Result<Option<SourceChangeStream<'static>>> {
+        if !self.watch_changes {
+            return Ok(None);
+        }
+
+        use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+        use tokio::sync::mpsc;
+
+        let root_path = self.root_path.clone();
+        let root_component_size = root_path.components().count();
+
+        let (tx, mut rx) = mpsc::channel::<PathBuf>(100);
+
+        let mut watcher = RecommendedWatcher::new(
+            move |res: notify::Result<notify::Event>| match res {
+                Ok(event) => {
+                    for path in event.paths {
+                        if let Err(err) = tx.try_send(path) {
+                            use tokio::sync::mpsc::error::TrySendError;
+                            match err {
+                                TrySendError::Full(_) => {
+                                    warn!(
+                                        "File watcher channel is full; dropping file change event"
+                                    );
+                                }
+                                TrySendError::Closed(_) => {
+                                    warn!(
+                                        "File watcher channel is closed; dropping file change event"
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("File watcher error: {}", e);
+                }
+            },
+            Config::default(),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to create file watcher: {}", e))?;
+
+        // Start watching before the snapshot is diffed, so no change falls in between.
+        watcher
+            .watch(&root_path, RecursiveMode::Recursive)
+            .map_err(|e| anyhow::anyhow!("Failed to watch path: {}", e))?;
+
+        let mut state = WatchState {
+            root_path: root_path.clone(),
+            pattern_matcher: self.pattern_matcher.clone(),
+            archives: self.archives.clone(),
+            snapshot: None,
+            files: BTreeMap::new(),
+            archive_members: HashMap::new(),
+        };
+        let mut initial_changes = Vec::new();
+        let mut catches_up = false;
+        let mut initial_update_ack_fn = None;
+        if let Some(state_store) = state_store {
+            let (snapshot, stored_files) = DirSnapshot::load(state_store).await?;
+            if let Some(ignore_files) = self.pattern_matcher.ignore_files() {
+                ignore_files.reload();
+            }
+            let scan_root_path = root_path.clone();
+            let pattern_matcher = self.pattern_matcher.clone();
+            let files = tokio::task::spawn_blocking(move || {
+                scan_files(&scan_root_path, &pattern_matcher, "")
+            })
+            .await?;
+            let snapshot = Arc::new(snapshot);
+            match stored_files {
+                Some(stored_files) => {
+                    initial_changes = diff_files(&stored_files, &files);
+                    // Members removed from archives while not running are only deleted by a full
+                    // listing.
+                    catches_up = !initial_changes
+                        .iter()
+                        .any(|(path, _)| self.archive_expansion(path).is_some());
+                }
+                None => {
+                    // No complete snapshot yet. Existing files are covered by the initial
+                    // listing, and recorded once it's processed.
+                    let snapshot = snapshot.clone();
+                    let baseline = files.clone();
+                    initial_update_ack_fn = Some(Box::new(move || {
+                        async move { snapshot.commit_baseline(baseline).await }.boxed()
+                    })
+                        as Box<dyn FnOnce() -> BoxFuture<'static, Result<()>> + Send + Sync>);
+                }
+            }
+            state.snapshot = Some(snapshot);
+            state.files = files;
+        }
+        if self.archives.is_some() {
+            // Members are known from here on, so the ones later removed can be deleted.
+            let archive_paths = if state.snapshot.is_some() {
+                state.files.keys().cloned().collect::<Vec<_>>()
+            } else {
+                let scan_root_path = root_path.clone();
+                let pattern_matcher = self.pattern_matcher.clone();
+                tokio::task::spawn_blocking(move || {
+                    scan_files(&scan_root_path, &pattern_matcher, "")
+                })
+                .await?
+                .into_keys()
+                .collect()
+            };
+            state.load_archive_members(archive_paths).await;
+        }
+        let debounce = self.watch_debounce;
+
+        let stream = async_stream::stream! {
+            // Keep the watcher alive for the duration of the stream
+            let _watcher = watcher;
+
+            for changes in initial_changes.chunks(CATCH_UP_BATCH_SIZE) {
+                match state.change_message(changes.to_vec()).await {
+                    Ok(Some(change_msg)) => yield Ok(change_msg),
+                    Ok(None) => {}
+                    Err(e) => yield Err(e),
+                }
+            }
+
+            // Bursts of events on the same path are coalesced, until it's quiet for `debounce`.
+            let mut pending = HashMap::<PathBuf, tokio::time::Instant>::new();
+            loop {
+                let flush_at = pending.values().min().copied();
+                tokio::select! {
+                    path = rx.recv() => {
+                        let Some(path) = path else {
+                            break;
+                        };
+                        pending.insert(path, tokio::time::Instant::now() + debounce);
+                        continue;
+                    }
+                    _ = async {
+                        match flush_at {
+                            Some(flush_at) => tokio::time::sleep_until(flush_at).await,
+                            None => std::future::pending().await,
+                        }
+                    } => {}
+                }
+
+                let now = tokio::time::Instant::now();
+                let due_paths = pending
+                    .iter()
+                    .filter(|(_, deadline)| **deadline <= now)
+                    .map(|(path, _)| path.clone())
+                    .collect::<Vec<_>>();
+                let mut changes = Vec::new();
+                for path in due_paths {
+                    pending.remove(&path);
+
+                    let mut path_components = path.components();
+                    for _ in 0..root_component_size {
+                        path_components.next();
+                    }
+                    let Some(relative_path) = path_components.as_path().to_str() else {
+                        continue;
+                    };
+
+                    // Skip events that correspond to the root directory itself or yield no relative path.
+                    if relative_path.is_empty() {
+                        continue;
+                    }
+                    changes.extend(state.refresh(relative_path.to_string()).await);
+                }
+                match state.change_message(changes).await {
+                    Ok(Some(change_msg)) => yield Ok(change_msg),
+                    Ok(None) => {}
+                    Err(e) => yield Err(e),
+                }
+            }
+        };
+
+        Ok(Some(SourceChangeStream {
+            stream: stream.boxed(),
+            catches_up,
+            initial_update_ack_fn,
+        }))
+    }
+}
+
 #[async_trait]
 impl SourceExecutor for Executor {
     async fn list(
         &self,
         options: &SourceExecutorReadOptions,
     ) -> Result<BoxStream<'async_trait, Result<Vec<PartialSourceRow>>>> {
+        // Ignore files may have changed since the last listing.
+        if let Some(ignore_files) = self.pattern_matcher.ignore_files() {
+            ignore_files.reload();
+        }
         let root_component_size = self.root_path.components().count();
         let mut dirs = Vec::new();
         dirs.push(Cow::Borrowed(&self.root_path));
@@ -119,8 +908,10 @@ impl SourceExecutor for Executor {
                             new_dirs.push(Cow::Owned(path));
                         }
                     } else if self.pattern_matcher.is_file_included(relative_path) {
-                        // Check file size limit
-                        if let Some(max_size) = self.max_file_size
+                        let archives = self.archive_expansion(relative_path);
+                        // Check file size limit. For archives, it applies to members instead.
+                        if archives.is_none()
+                            && let Some(max_size) = self.max_file_size
                             && try_ensure_metadata(&path, &mut metadata)
                                 .await
                                 .is_none_or(|m| m.len() > max_size as u64)
@@ -150,8 +941,44 @@ impl SourceExecutor for Executor {
                         } else {
                             None
                         };
+                        if let Some(archives) = archives {
+                            let members = match list_archive_members(
+                                path.clone(),
+                                relative_path.to_string(),
+                                archives.clone(),
+                            )
+                            .await
+                            {
+                                Ok(members) => members,
+                                Err(e) => {
+                                    warn!("Failed to read archive {}: {e:?}", path.display());
+                                    continue;
+                                }
+                            };
+                            let rows = members
+                                .into_iter()
+                                .map(|member| PartialSourceRow {
+                                    key: KeyValue::from_single_part(archive::member_key(
+                                        relative_path,
+                                        &member.path,
+                                    )),
+                                    key_aux_info: serde_json::Value::Null,
+                                    data: PartialSourceRowData {
+                                        ordinal,
+                                        content_version_fp: options
+                                            .include_content_version_fp
+                                            .then_some(member.checksum),
+                                        value: None,
+                                    },
+                                })
+                                .collect::<Vec<_>>();
+                            if !rows.is_empty() {
+                                yield rows;
+                            }
+                            continue;
+                        }
                         yield vec![PartialSourceRow {
-                            key: KeyValue::from_single_part(relative_path.to_string()),
+                            key: file_key(relative_path, self.archives.as_ref()),
                             key_aux_info: serde_json::Value::Null,
                             data: PartialSourceRowData {
                                 ordinal,
@@ -173,60 +1000,27 @@ impl SourceExecutor for Executor {
         _key_aux_info: &serde_json::Value,
         options: &SourceExecutorReadOptions,
     ) -> Result<PartialSourceRowData> {
-        let path = key.single_part()?.str_value()?.as_ref();
-        let path_obj = Path::new(path);
-
-        // Prevent path traversal vulnerabilities by verifying the path
-        // doesn't contain parent directory or absolute components.
-        if path_obj.components().any(|c| {
-            matches!(
-                c,
-                std::path::Component::ParentDir
-                    | std::path::Component::RootDir
-                    | std::path::Component::Prefix(_)
-            )
-        }) || !self.pattern_matcher.is_file_included(path)
-        {
-            return Ok(PartialSourceRowData {
-                value: Some(SourceValue::NonExistence),
-                ordinal: Some(Ordinal::unavailable()),
-                content_version_fp: None,
-            });
-        }
-
-        let path = self.root_path.join(path);
-
-        // Mitigate symlink-based path traversal by canonicalizing and checking boundaries
-        if let Some(root_canon) = &self.canonical_root_path {
-            let path_canon = match tokio::fs::canonicalize(&path).await {
-                Ok(c) => c,
-                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-                    // Target file doesn't exist.
-                    return Ok(PartialSourceRowData {
-                        value: Some(SourceValue::NonExistence),
-                        ordinal: Some(Ordinal::unavailable()),
-                        content_version_fp: None,
-                    });
-                }
-                Err(e) => Err(e)?,
+        let key = key.single_part()?.str_value()?.as_ref();
+        let path = if let Some(archives) = &self.archives {
+            let Some((path, member_path)) = archive::parse_key(key) else {
+                return Ok(non_existence());
             };
-
-            if !path_canon.starts_with(root_canon) {
-                // Symlink points outside the allowed root directory.
-                return Ok(PartialSourceRowData {
-                    value: Some(SourceValue::NonExistence),
-                    ordinal: Some(Ordinal::unavailable()),
-                    content_version_fp: None,
-                });
+            if let Some(member_path) = member_path {
+                return self
+                    .get_archive_member_value(archives, &path, member_path, options)
+                    .await;
+            }
+            // Archives themselves are expanded into their members.
+            if ArchiveFormat::from_path(&path).is_some() {
+                return Ok(non_existence());
             }
+            Cow::Owned(path)
         } else {
-            // Root doesn't exist (failed to canonicalize during setup), so the file cannot exist.
-            return Ok(PartialSourceRowData {
-                value: Some(SourceValue::NonExistence),
-                ordinal: Some(Ordinal::unavailable()),
-                content_version_fp: None,
-            });
-        }
+            Cow::Borrowed(key)
+        };
+        let Some(path) = self.resolve_path(&path).await? else {
+            return Ok(non_existence());
+        };
 
         let mut metadata: Option<Metadata> = None;
         // Check file size limit
@@ -234,11 +1028,7 @@ impl SourceExecutor for Executor {
             && let Ok(metadata) = ensure_metadata(&path, &mut metadata).await
             && metadata.len() > max_size as u64
         {
-            return Ok(PartialSourceRowData {
-                value: Some(SourceValue::NonExistence),
-                ordinal: Some(Ordinal::unavailable()),
-                content_version_fp: None,
-            });
+            return Ok(non_existence());
         }
         let ordinal = if options.include_ordinal {
             let metadata = ensure_metadata(&path, &mut metadata).await?;
@@ -248,15 +1038,7 @@ impl SourceExecutor for Executor {
         };
         let value = if options.include_value {
             match std::fs::read(path) {
-                Ok(content) => {
-                    let content = if self.binary {
-                        fields_value!(content)
-                    } else {
-                        let (s, _) = utils::bytes_decode::bytes_to_string(&content);
-                        fields_value!(s)
-                    };
-                    Some(SourceValue::Existence(content))
-                }
+                Ok(content) => Some(SourceValue::Existence(self.content_fields(content))),
                 Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                     Some(SourceValue::NonExistence)
                 }
@@ -279,105 +1061,17 @@ impl SourceExecutor for Executor {
     async fn change_stream(
         &self,
     ) -> Result<Option<BoxStream<'async_trait, Result<SourceChangeMessage>>>> {
-        if !self.watch_changes {
-            return Ok(None);
-        }
-
-        use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
-        use tokio::sync::mpsc;
-
-        let root_path = self.root_path.clone();
-        let root_component_size = root_path.components().count();
-        let pattern_matcher = self.pattern_matcher.clone();
-
-        let (tx, mut rx) = mpsc::channel::<PathBuf>(100);
-
-        let mut watcher = RecommendedWatcher::new(
-            move |res: notify::Result<notify::Event>| match res {
-                Ok(event) => {
-                    for path in event.paths {
-                        if let Err(err) = tx.try_send(path) {
-                            use tokio::sync::mpsc::error::TrySendError;
-                            match err {
-                                TrySendError::Full(_) => {
-                                    warn!(
-                                        "File watcher channel is full; dropping file change event"
-                                    );
-                                }
-                                TrySendError::Closed(_) => {
-                                    warn!(
-                                        "File watcher channel is closed; dropping file change event"
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    warn!("File watcher error: {}", e);
-                }
-            },
-            Config::default(),
-        )
-        .map_err(|e| anyhow::anyhow!("Failed to create file watcher: {}", e))?;
-
-        watcher
-            .watch(&root_path, RecursiveMode::Recursive)
-            .map_err(|e| anyhow::anyhow!("Failed to watch path: {}", e))?;
-
-        let stream = async_stream::stream! {
-            // Keep the watcher alive for the duration of the stream
-            let _watcher = watcher;
-
-            while let Some(path) = rx.recv().await {
-                // Skip directory paths - notify can emit events for directories,
-                // and reading a directory as a file would produce an EISDIR error.
-                let is_dir = match std::fs::metadata(&path) {
-                    Ok(metadata) => metadata.is_dir(),
-                    Err(err) => {
-                        // If the file no longer exists, this may be a deletion event; do not skip it.
-                        if err.kind() != std::io::ErrorKind::NotFound {
-                            warn!("Failed to read metadata for path {:?}: {}", path, err);
-                        }
-                        false
-                    }
-                };
-                if is_dir {
-                    continue;
-                }
-
-                let mut path_components = path.components();
-                for _ in 0..root_component_size {
-                    path_components.next();
-                }
-                let Some(relative_path) = path_components.as_path().to_str() else {
-                    continue;
-                };
-
-                // Skip events that correspond to the root directory itself or yield no relative path.
-                if relative_path.is_empty() {
-                    continue;
-                }
-
-                // Filter through pattern matcher
-                if pattern_matcher.is_file_included(relative_path) {
-                    yield Ok(SourceChangeMessage {
-                        changes: vec![SourceChange {
-                            key: KeyValue::from_single_part(relative_path.to_string()),
-                            key_aux_info: serde_json::Value::Null,
-                            data: PartialSourceRowData {
-                                ordinal: None,
-                                content_version_fp: None,
-                                value: None,
-                            },
-                        }],
-                        ack_fn: None,
-                    });
-                }
-            }
-        };
+        Ok(self
+            .watch_changes_stream(None)
+            .await?
+            .map(|change_stream| change_stream.stream))
+    }
 
-        Ok(Some(stream.boxed()))
+    async fn change_stream_with_state(
+        &self,
+        state_store: Arc<dyn SourceStateStore>,
+    ) -> Result<Option<SourceChangeStream<'async_trait>>> {
+        self.watch_changes_stream(Some(state_store)).await
     }
 }
 
@@ -391,6 +1085,10 @@ impl SourceFactoryBase for Factory {
         "LocalFile"
     }
 
+    fn uses_state_store(&self, spec: &Spec) -> bool {
+        spec.watch_changes.unwrap_or(false)
+    }
+
     async fn get_output_schema(
         &self,
         spec: &Spec,
@@ -429,14 +1127,380 @@ impl SourceFactoryBase for Factory {
     ) -> Result<Box<dyn SourceExecutor>> {
         let root_path = PathBuf::from(spec.path);
         let canonical_root_path = tokio::fs::canonicalize(&root_path).await.ok();
+        let archives = spec
+            .archives
+            .map(|archives| -> Result<_> {
+                let max_total_size = archives
+                    .max_total_size
+                    .unwrap_or(archive::DEFAULT_MAX_TOTAL_SIZE)
+                    .max(0) as u64;
+                Ok(ArchiveExpansion {
+                    pattern_matcher: Arc::new(PatternMatcher::new(
+                        archives.included_patterns,
+                        archives.excluded_patterns,
+                    )?),
+                    limits: ArchiveLimits {
+                        max_member_size: spec
+                            .max_file_size
+                            .map_or(max_total_size, |size| size.max(0) as u64),
+                        max_total_size,
+                        max_members: archives
+                            .max_members
+                            .unwrap_or(archive::DEFAULT_MAX_MEMBERS)
+                            .max(0) as usize,
+                    },
+                })
+            })
+            .transpose()?;
+        let mut pattern_matcher =
+            PatternMatcher::new(spec.included_patterns, spec.excluded_patterns)?;
+        if spec.respect_ignore_files.unwrap_or(false) {
+            pattern_matcher = pattern_matcher.with_ignore_files(root_path.to_path_buf());
+        }
 
         Ok(Box::new(Executor {
             root_path,
             canonical_root_path,
             binary: spec.binary,
-            pattern_matcher: PatternMatcher::new(spec.included_patterns, spec.excluded_patterns)?,
+            pattern_matcher,
             max_file_size: spec.max_file_size,
             watch_changes: spec.watch_changes.unwrap_or(false),
+            watch_debounce: spec.watch_debounce.unwrap_or(DEFAULT_WATCH_DEBOUNCE),
+            archives,
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temporary directory, removed when dropped (also when the test fails).
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Result<Self> {
+            static NEXT_ID: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+            let id = NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let path = std::env::temp_dir().join(format!(
+                "recoco-local-file-{name}-{}-{id}",
+                std::process::id()
+            ));
+            // Left over by an earlier process with the same ID.
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path)?;
+            Ok(Self(path))
+        }
+    }
+
+    impl std::ops::Deref for TestDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn test_executor(root_path: &Path) -> Result<Executor> {
+        Ok(Executor {
+            root_path: root_path.to_path_buf(),
+            canonical_root_path: std::fs::canonicalize(root_path).ok(),
+            binary: false,
+            pattern_matcher: PatternMatcher::new(None, Some(vec!["**/*.tmp".to_string()]))?,
+            max_file_size: None,
+            watch_changes: true,
+            watch_debounce: Duration::from_millis(100),
+            archives: None,
+        })
+    }
+
+    async fn next_change(
+        change_stream: &mut SourceChangeStream<'_>,
+    ) -> Result<(BTreeSet<String>, SourceChangeMessage)> {
+        let change_msg = tokio::time::timeout(Duration::from_secs(10), change_stream.stream.next())
+            .await
+            .map_err(Error::internal)?
+            .unwrap()?;
+        let keys = change_msg
+            .changes
+            .iter()
+            .map(|change| {
+                Ok(change
+                    .key
+                    .single_part()?
+                    .str_value()?
+                    .as_ref()
+                    .replace(std::path::MAIN_SEPARATOR, "/"))
+            })
+            .collect::<Result<_>>()?;
+        Ok((keys, change_msg))
+    }
+
+    #[tokio::test]
+    async fn test_change_stream_with_snapshot() -> Result<()> {
+        let root_path = TestDir::new("snapshot")?;
+        std::fs::create_dir_all(root_path.join("dir"))?;
+        std::fs::write(root_path.join("a.txt"), "a")?;
+        std::fs::write(root_path.join("b.txt"), "b")?;
+        std::fs::write(root_path.join("dir/c.txt"), "c")?;
+        let executor = test_executor(&root_path)?;
+        let state_store = Arc::new(InMemoryStateStore::default());
+
+        // The first run leaves existing files to the initial listing. The snapshot is only
+        // recorded once it's processed, so an interrupted run doesn't catch up on the next one.
+        for _ in 0..2 {
+            let change_stream = executor
+                .change_stream_with_state(state_store.clone())
+                .await?
+                .unwrap();
+            assert!(!change_stream.catches_up);
+            assert!(state_store.list().await?.is_empty());
+            assert!(change_stream.initial_update_ack_fn.is_some());
+        }
+        let change_stream = executor
+            .change_stream_with_state(state_store.clone())
+            .await?
+            .unwrap();
+        (change_stream.initial_update_ack_fn.unwrap())().await?;
+        // Two directories, and the marker of the complete snapshot.
+        assert_eq!(state_store.list().await?.len(), 3);
+
+        // Changes while not running are caught up on the next run.
+        std::fs::write(root_path.join("a.txt"), "a updated")?;
+        std::fs::remove_file(root_path.join("dir/c.txt"))?;
+        std::fs::write(root_path.join("dir/d.txt"), "d")?;
+        std::fs::write(root_path.join("dir/e.tmp"), "e")?;
+        let mut change_stream = executor
+            .change_stream_with_state(state_store.clone())
+            .await?
+            .unwrap();
+        assert!(change_stream.catches_up);
+        assert!(change_stream.initial_update_ack_fn.is_none());
+        let (keys, change_msg) = next_change(&mut change_stream).await?;
+        assert_eq!(
+            keys,
+            BTreeSet::from(["a.txt", "dir/c.txt", "dir/d.txt"].map(str::to_string))
+        );
+        (change_msg.ack_fn.unwrap())().await?;
+        let stored_files = DirSnapshot::load(state_store.clone()).await?.1.unwrap();
+        assert_eq!(
+            stored_files
+                .keys()
+                .map(|path| path.replace(std::path::MAIN_SEPARATOR, "/"))
+                .collect::<Vec<_>>(),
+            vec!["a.txt", "b.txt", "dir/d.txt"]
+        );
+
+        // Bursts of writes to the same file are coalesced.
+        for i in 0..5 {
+            std::fs::write(root_path.join("b.txt"), "b".repeat(i + 2))?;
+        }
+        let (keys, _) = next_change(&mut change_stream).await?;
+        assert_eq!(keys, BTreeSet::from(["b.txt".to_string()]));
+        Ok(())
+    }
+
+    async fn list_keys(executor: &Executor) -> Result<BTreeSet<String>> {
+        let options = SourceExecutorReadOptions {
+            include_ordinal: false,
+            include_content_version_fp: false,
+            include_value: false,
+        };
+        executor
+            .list(&options)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .flatten()
+            .map(|row| {
+                Ok(row
+                    .key
+                    .single_part()?
+                    .str_value()?
+                    .as_ref()
+                    .replace(std::path::MAIN_SEPARATOR, "/"))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_list_respects_ignore_files() -> Result<()> {
+        let root_path = TestDir::new("ignore")?;
+        std::fs::create_dir_all(root_path.join("build"))?;
+        std::fs::create_dir_all(root_path.join("logs"))?;
+        std::fs::write(
+            root_path.join(".gitignore"),
+            "*.log
+build/
+",
+        )?;
+        std::fs::write(root_path.join("a.txt"), "a")?;
+        std::fs::write(root_path.join("build/b.txt"), "b")?;
+        std::fs::write(
+            root_path.join("logs/.gitignore"),
+            "!keep.log
+",
+        )?;
+        std::fs::write(root_path.join("logs/keep.log"), "keep")?;
+        std::fs::write(root_path.join("logs/drop.log"), "drop")?;
+        let executor = Executor {
+            pattern_matcher: PatternMatcher::new(None, None)?
+                .with_ignore_files(root_path.to_path_buf()),
+            ..test_executor(&root_path)?
+        };
+
+        assert_eq!(
+            list_keys(&executor).await?,
+            BTreeSet::from(
+                [".gitignore", "a.txt", "logs/.gitignore", "logs/keep.log"].map(str::to_string)
+            )
+        );
+        let options = SourceExecutorReadOptions {
+            include_ordinal: false,
+            include_content_version_fp: false,
+            include_value: true,
+        };
+        let key = KeyValue::from_single_part("build/b.txt".to_string());
+        let data = executor
+            .get_value(&key, &serde_json::Value::Null, &options)
+            .await?;
+        assert!(matches!(data.value, Some(SourceValue::NonExistence)));
+
+        // Changed ignore files are picked up by the next listing.
+        std::fs::write(
+            root_path.join(".gitignore"),
+            "*.log
+",
+        )?;
+        assert_eq!(
+            list_keys(&executor).await?,
+            BTreeSet::from(
+                [
+                    ".gitignore",
+                    "a.txt",
+                    "build/b.txt",
+                    "logs/.gitignore",
+                    "logs/keep.log"
+                ]
+                .map(str::to_string)
+            )
+        );
+
+        Ok(())
+    }
+
+    fn zip_archive(files: &[(&str, &[u8])]) -> Result<Vec<u8>> {
+        use std::io::Write;
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (path, content) in files {
+            writer
+                .start_file(*path, zip::write::SimpleFileOptions::default())
+                .map_err(Error::internal)?;
+            writer.write_all(content)?;
+        }
+        Ok(writer.finish().map_err(Error::internal)?.into_inner())
+    }
+
+    #[tokio::test]
+    async fn test_archive_members() -> Result<()> {
+        let root_path = TestDir::new("archive")?;
+        std::fs::write(root_path.join("plain.txt"), "plain")?;
+        // A file whose path looks like the key of an archive member.
+        std::fs::write(root_path.join("docs.zip!a.md"), "not a member")?;
+        std::fs::write(
+            root_path.join("docs.zip"),
+            zip_archive(&[("a.md", b"a"), ("sub/b.md", b"b"), ("c.bin", b"c")])?,
+        )?;
+        let executor = Executor {
+            archives: Some(ArchiveExpansion {
+                pattern_matcher: Arc::new(PatternMatcher::new(
+                    None,
+                    Some(vec!["**/*.bin".to_string()]),
+                )?),
+                limits: ArchiveLimits {
+                    max_member_size: 1024,
+                    max_total_size: 4096,
+                    max_members: 10,
+                },
+            }),
+            ..test_executor(&root_path)?
+        };
+
+        assert_eq!(
+            list_keys(&executor).await?,
+            BTreeSet::from(
+                [
+                    "docs.zip!a.md",
+                    "docs.zip!sub/b.md",
+                    "docs.zip%21a.md",
+                    "plain.txt"
+                ]
+                .map(str::to_string)
+            )
+        );
+        let options = SourceExecutorReadOptions {
+            include_ordinal: false,
+            include_content_version_fp: true,
+            include_value: true,
+        };
+        let get_value = |key: &str| {
+            let key = KeyValue::from_single_part(key.to_string());
+            let executor = &executor;
+            let options = &options;
+            async move {
+                executor
+                    .get_value(&key, &serde_json::Value::Null, options)
+                    .await
+            }
+        };
+        let data = get_value("docs.zip!sub/b.md").await?;
+        let Some(SourceValue::Existence(fields)) = data.value else {
+            panic!("Expected an existing member, got {:?}", data.value);
+        };
+        assert_eq!(fields.fields[0].as_str()?.as_ref(), "b");
+        assert!(data.content_version_fp.is_some());
+        for (key, content) in [("docs.zip!a.md", "a"), ("docs.zip%21a.md", "not a member")] {
+            let data = get_value(key).await?;
+            let Some(SourceValue::Existence(fields)) = data.value else {
+                panic!("Expected an existing row for {key}, got {:?}", data.value);
+            };
+            assert_eq!(fields.fields[0].as_str()?.as_ref(), content);
+        }
+        for key in ["docs.zip!c.bin", "docs.zip!missing.md", "missing.zip!a.md"] {
+            let data = get_value(key).await?;
+            assert!(
+                matches!(data.value, Some(SourceValue::NonExistence)),
+                "{key}"
+            );
+        }
+
+        // Members removed from a watched archive, or deleted with it, are deleted.
+        let state_store = Arc::new(InMemoryStateStore::default());
+        let mut change_stream = executor
+            .change_stream_with_state(state_store)
+            .await?
+            .unwrap();
+        (change_stream.initial_update_ack_fn.take().unwrap())().await?;
+        std::fs::write(
+            root_path.join("docs.zip"),
+            zip_archive(&[("a.md", b"a updated")])?,
+        )?;
+        let (keys, change_msg) = next_change(&mut change_stream).await?;
+        assert_eq!(
+            keys,
+            BTreeSet::from(["docs.zip!a.md", "docs.zip!sub/b.md"].map(str::to_string))
+        );
+        (change_msg.ack_fn.unwrap())().await?;
+        std::fs::remove_file(root_path.join("docs.zip"))?;
+        let (keys, _) = next_change(&mut change_stream).await?;
+        assert_eq!(keys, BTreeSet::from(["docs.zip!a.md".to_string()]));
+        Ok(())
+    }
+}
@@ -14,9 +14,17 @@
 pub mod amazon_s3;
 #[cfg(feature = "source-azure")]
 pub mod azure_blob;
+#[cfg(feature = "source-git")]
+pub mod git_repo;
 #[cfg(feature = "source-gdrive")]
 pub mod google_drive;
 #[cfg(feature = "source-local-file")]
 pub mod local_file;
+#[cfg(feature = "source-mysql")]
+pub mod mysql;
 #[cfg(feature = "source-postgres")]
 pub mod postgres;
+#[cfg(feature = "source-sqlite")]
+pub mod sqlite;
+#[cfg(feature = "source-web-crawl")]
+pub mod web_crawl;
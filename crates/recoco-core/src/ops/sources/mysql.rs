@@ -0,0 +1,356 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+
+use crate::ops::shared::sql_table::{
+    SqlColumnInfo, SqlTableDriver, SqlTableReader, SqlTableSchema, SqlValueDecoder,
+    push_key_conditions,
+};
+use crate::settings::DatabaseConnectionSpec;
+use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions, MySqlRow};
+use sqlx::{MySql, MySqlPool, Row};
+
+type MySqlValueDecoder = SqlValueDecoder<MySqlRow>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Spec {
+    /// Table name to read from (required)
+    table_name: String,
+    /// Database connection specification (required), with a `mysql://` URL
+    database: spec::AuthEntryReference<DatabaseConnectionSpec>,
+    /// Optional: columns to include (if None, includes all columns)
+    included_columns: Option<Vec<String>>,
+    /// Optional: ordinal column for tracking changes
+    ordinal_column: Option<String>,
+    /// Optional: WHERE clause filter for rows (arbitrary SQL boolean expression)
+    filter: Option<String>,
+}
+
+struct MySqlTableDriver {
+    db_pool: MySqlPool,
+}
+
+fn bind_key_field<'arg>(
+    builder: &mut sqlx::QueryBuilder<'arg, MySql>,
+    key_value: &'arg KeyPart,
+) -> Result<()> {
+    match key_value {
+        KeyPart::Bytes(v) => {
+            builder.push_bind(&**v);
+        }
+        KeyPart::Str(v) => {
+            builder.push_bind(&**v);
+        }
+        KeyPart::Bool(v) => {
+            builder.push_bind(v);
+        }
+        KeyPart::Int64(v) => {
+            builder.push_bind(v);
+        }
+        KeyPart::Date(v) => {
+            builder.push_bind(v);
+        }
+        KeyPart::Uuid(_) | KeyPart::Range(_) | KeyPart::Struct(_) => {
+            internal_bail!("Unsupported key type for MySQL: {key_value}");
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl SqlTableDriver for MySqlTableDriver {
+    type Row = MySqlRow;
+
+    fn quote_identifier(name: &str) -> String {
+        format!("`{name}`")
+    }
+
+    fn fetch_rows(&self, query: String) -> BoxStream<'_, Result<MySqlRow>> {
+        try_stream! {
+            let mut rows = sqlx::query(&query).fetch(&self.db_pool);
+            while let Some(row) = rows.try_next().await? {
+                yield row;
+            }
+        }
+        .boxed()
+    }
+
+    async fn fetch_row_by_key(
+        &self,
+        query: String,
+        key_columns: &[String],
+        key: &KeyValue,
+        filter: Option<&str>,
+    ) -> Result<Option<MySqlRow>> {
+        let mut qb = sqlx::QueryBuilder::new(query);
+        push_key_conditions(&mut qb, key_columns, key, filter, bind_key_field)?;
+        Ok(qb.build().fetch_optional(&self.db_pool).await?)
+    }
+}
+
+struct MySqlSourceExecutor {
+    reader: SqlTableReader<MySqlTableDriver>,
+}
+
+#[async_trait]
+impl SourceExecutor for MySqlSourceExecutor {
+    async fn list(
+        &self,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<BoxStream<'async_trait, Result<Vec<PartialSourceRow>>>> {
+        self.reader.list(options).await
+    }
+
+    async fn get_value(
+        &self,
+        key: &KeyValue,
+        _key_aux_info: &serde_json::Value,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<PartialSourceRowData> {
+        self.reader.get_value(key, options).await
+    }
+
+    fn provides_ordinal(&self) -> bool {
+        self.reader.table_schema.provides_ordinal()
+    }
+}
+
+/// Map MySQL data types to CocoIndex BasicValueType and a decoder function.
+/// `data_type` is the bare type name, e.g. `int`, and `column_type` the full one, e.g. `int(10) unsigned`.
+fn map_mysql_type_to_cocoindex_and_decoder(
+    data_type: &str,
+    column_type: &str,
+) -> Option<(BasicValueType, MySqlValueDecoder)> {
+    let unsigned = column_type.contains("unsigned");
+    let result = match data_type {
+        // MySQL has no boolean type: `BOOLEAN` is an alias of `TINYINT(1)`
+        "tinyint" if column_type.starts_with("tinyint(1)") => (
+            BasicValueType::Bool,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<bool>, _>(idx)?))) as MySqlValueDecoder,
+        ),
+        "bigint" if unsigned => (
+            BasicValueType::Int64,
+            (|row, idx| {
+                let opt_v = row.try_get::<Option<u64>, _>(idx)?;
+                let opt_v = opt_v
+                    .map(|v| {
+                        i64::try_from(v)
+                            .map_err(|_| client_error!("Unsigned integer value out of range: {v}"))
+                    })
+                    .transpose()?;
+                Ok(Value::from(opt_v))
+            }) as MySqlValueDecoder,
+        ),
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" if unsigned => (
+            BasicValueType::Int64,
+            (|row, idx| {
+                let opt_v = row.try_get::<Option<u64>, _>(idx)?;
+                Ok(Value::from(opt_v.map(|v| v as i64)))
+            }) as MySqlValueDecoder,
+        ),
+        // Signed integers of all widths decode into i64
+        "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" => (
+            BasicValueType::Int64,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<i64>, _>(idx)?))) as MySqlValueDecoder,
+        ),
+        "float" => (
+            BasicValueType::Float32,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<f32>, _>(idx)?))) as MySqlValueDecoder,
+        ),
+        "double" | "real" => (
+            BasicValueType::Float64,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<f64>, _>(idx)?))) as MySqlValueDecoder,
+        ),
+        "char" | "varchar" | "tinytext" | "text" | "mediumtext" | "longtext" | "enum" | "set" => (
+            BasicValueType::Str,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<String>, _>(idx)?)))
+                as MySqlValueDecoder,
+        ),
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => (
+            BasicValueType::Bytes,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<Vec<u8>>, _>(idx)?)))
+                as MySqlValueDecoder,
+        ),
+        "date" => (
+            BasicValueType::Date,
+            (|row, idx| {
+                Ok(Value::from(
+                    row.try_get::<Option<chrono::NaiveDate>, _>(idx)?,
+                ))
+            }) as MySqlValueDecoder,
+        ),
+        "time" => (
+            BasicValueType::Time,
+            (|row, idx| {
+                Ok(Value::from(
+                    row.try_get::<Option<chrono::NaiveTime>, _>(idx)?,
+                ))
+            }) as MySqlValueDecoder,
+        ),
+        "datetime" => (
+            BasicValueType::LocalDateTime,
+            (|row, idx| {
+                Ok(Value::from(
+                    row.try_get::<Option<chrono::NaiveDateTime>, _>(idx)?,
+                ))
+            }) as MySqlValueDecoder,
+        ),
+        // `TIMESTAMP` values are stored in UTC, and sessions are set to UTC by the driver
+        "timestamp" => (
+            BasicValueType::OffsetDateTime,
+            (|row, idx| {
+                let opt_dt = row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>(idx)?;
+                Ok(Value::from(opt_dt.map(|dt| dt.fixed_offset())))
+            }) as MySqlValueDecoder,
+        ),
+        "json" => (
+            BasicValueType::Json,
+            (|row, idx| {
+                Ok(Value::from(
+                    row.try_get::<Option<serde_json::Value>, _>(idx)?,
+                ))
+            }) as MySqlValueDecoder,
+        ),
+        // Skip others
+        t => {
+            warn!("Skipping unsupported MySQL type: {t}");
+            return None;
+        }
+    };
+    Some(result)
+}
+
+async fn get_db_pool(
+    db_ref: &spec::AuthEntryReference<DatabaseConnectionSpec>,
+    auth_registry: &AuthRegistry,
+) -> Result<MySqlPool> {
+    let conn_spec = auth_registry.get(db_ref)?;
+    let mut options: MySqlConnectOptions = conn_spec.url.parse()?;
+    if let Some(user) = &conn_spec.user {
+        options = options.username(user);
+    }
+    if let Some(password) = &conn_spec.password {
+        options = options.password(password);
+    }
+    let pool = MySqlPoolOptions::new()
+        .max_connections(conn_spec.max_connections)
+        .min_connections(conn_spec.min_connections)
+        .connect_with(options)
+        .await
+        .map_err(Error::from)
+        .with_context(|| format!("Failed to connect to database {}", conn_spec.url))?;
+    Ok(pool)
+}
+
+/// Fetch table schema information from MySQL
+async fn fetch_table_schema(
+    pool: &MySqlPool,
+    table_name: &str,
+    included_columns: &Option<Vec<String>>,
+    ordinal_column: &Option<String>,
+) -> Result<SqlTableSchema<MySqlRow>> {
+    // Catalog columns may be reported as binary strings, so cast them all to text
+    let query = r#"
+        SELECT
+            CAST(COLUMN_NAME AS CHAR) AS column_name,
+            CAST(DATA_TYPE AS CHAR) AS data_type,
+            CAST(COLUMN_TYPE AS CHAR) AS column_type,
+            CAST(IS_NULLABLE AS CHAR) AS is_nullable,
+            CAST(COLUMN_KEY AS CHAR) AS column_key
+        FROM
+            information_schema.COLUMNS
+        WHERE
+            TABLE_SCHEMA = DATABASE()
+            AND TABLE_NAME = ?
+        ORDER BY ORDINAL_POSITION
+    "#;
+
+    let rows = sqlx::query(query).bind(table_name).fetch_all(pool).await?;
+
+    let mut columns = Vec::with_capacity(rows.len());
+    for row in rows {
+        let col_name: String = row.try_get("column_name")?;
+        let data_type: String = row.try_get("data_type")?;
+        let column_type: String = row.try_get("column_type")?;
+        let is_nullable: bool = row.try_get::<String, _>("is_nullable")? == "YES";
+        let is_primary_key: bool = row.try_get::<String, _>("column_key")? == "PRI";
+
+        let Some((value_type, decoder)) = map_mysql_type_to_cocoindex_and_decoder(
+            &data_type.to_lowercase(),
+            &column_type.to_lowercase(),
+        ) else {
+            continue;
+        };
+        columns.push(SqlColumnInfo {
+            name: col_name,
+            value_type,
+            decoder,
+            is_nullable,
+            is_primary_key,
+        });
+    }
+
+    SqlTableSchema::new(table_name, columns, included_columns, ordinal_column)
+}
+
+pub struct Factory;
+
+#[async_trait]
+impl SourceFactoryBase for Factory {
+    type Spec = Spec;
+
+    fn name(&self) -> &str {
+        "MySql"
+    }
+
+    async fn get_output_schema(
+        &self,
+        spec: &Spec,
+        context: &FlowInstanceContext,
+    ) -> Result<EnrichedValueType> {
+        let db_pool = get_db_pool(&spec.database, &context.auth_registry).await?;
+        let table_schema = fetch_table_schema(
+            &db_pool,
+            &spec.table_name,
+            &spec.included_columns,
+            &spec.ordinal_column,
+        )
+        .await?;
+        Ok(table_schema.output_type())
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        _source_name: &str,
+        spec: Spec,
+        context: Arc<FlowInstanceContext>,
+    ) -> Result<Box<dyn SourceExecutor>> {
+        let db_pool = get_db_pool(&spec.database, &context.auth_registry).await?;
+        let table_schema = fetch_table_schema(
+            &db_pool,
+            &spec.table_name,
+            &spec.included_columns,
+            &spec.ordinal_column,
+        )
+        .await?;
+
+        Ok(Box::new(MySqlSourceExecutor {
+            reader: SqlTableReader {
+                driver: MySqlTableDriver { db_pool },
+                table_name: spec.table_name,
+                table_schema,
+                filter: spec.filter,
+            },
+        }))
+    }
+}
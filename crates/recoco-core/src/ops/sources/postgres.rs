@@ -10,35 +10,78 @@
 // Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
 // SPDX-License-Identifier: Apache-2.0
 
+mod replication;
+
 use crate::ops::sdk::*;
 
+use self::replication::{
+    ColumnValue, ColumnValues, DecodedMessage, Lsn, LsnAckTracker, PgOutputDecoder,
+};
 use crate::ops::shared::postgres::{bind_key_field, get_db_pool};
+use crate::ops::shared::sql_table::{
+    ComputedOrdinal, SqlColumnInfo, SqlTableDriver, SqlTableReader, SqlTableSchema,
+    SqlValueDecoder, push_key_conditions, value_to_ordinal,
+};
 use crate::settings::DatabaseConnectionSpec;
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 use indoc::formatdoc;
 use sqlx::postgres::types::PgInterval;
-use sqlx::postgres::{PgListener, PgNotification};
+use sqlx::postgres::{PgListener, PgNotification, PgRow};
 use sqlx::{PgPool, Row};
-use std::fmt::Write;
 
-type PgValueDecoder = fn(&sqlx::postgres::PgRow, usize) -> Result<Value>;
+type PgValueDecoder = SqlValueDecoder<PgRow>;
 
 const LISTENER_HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(45);
-#[derive(Clone)]
-struct FieldSchemaInfo {
-    schema: FieldSchema,
-    decoder: PgValueDecoder,
-}
+const DEFAULT_REPLICATION_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+/// Maximum number of changes decoded in a single poll. Decoding always stops at a transaction
+/// boundary, so a poll may return more.
+const REPLICATION_POLL_MAX_CHANGES: i32 = 10_000;
+const MAX_SLOT_NAME_LEN: usize = 63;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationSpec {
     channel_name: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplicationPlugin {
+    #[default]
+    #[serde(rename = "pgoutput")]
+    PgOutput,
+    #[serde(rename = "wal2json")]
+    Wal2Json,
+}
+
+impl ReplicationPlugin {
+    fn name(&self) -> &'static str {
+        match self {
+            ReplicationPlugin::PgOutput => "pgoutput",
+            ReplicationPlugin::Wal2Json => "wal2json",
+        }
+    }
+}
+
+/// Change capture through a logical replication slot. Requires `wal_level = logical`.
+///
+/// The slot and publication are recorded in the flow's setup state, and dropped once the source is
+/// removed or the flow is dropped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationSpec {
+    /// Name of the replication slot, created if missing. Defaults to one derived from the flow and source names.
+    slot_name: Option<String>,
+    /// Output plugin used to decode the WAL. Defaults to `pgoutput`.
+    #[serde(default)]
+    plugin: ReplicationPlugin,
+    /// Publication used by `pgoutput`, created for the table if missing. Defaults to `{slot_name}_pub`.
+    publication_name: Option<String>,
+    /// Interval between two polls of the slot. Defaults to 1 second.
+    poll_interval: Option<std::time::Duration>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Spec {
-    /// Table name to read from (required)
+    /// Table name to read from (required), unqualified and looked up through the `search_path`
     table_name: String,
     /// Database connection specification (optional)
     database: Option<spec::AuthEntryReference<DatabaseConnectionSpec>>,
@@ -48,103 +91,106 @@ pub struct Spec {
     ordinal_column: Option<String>,
     /// Optional: notification for change capture
     notification: Option<NotificationSpec>,
+    /// Optional: logical replication for change capture, exclusive with `notification`
+    replication: Option<ReplicationSpec>,
     /// Optional: WHERE clause filter for rows (arbitrary SQL boolean expression)
     filter: Option<String>,
 }
 
-#[derive(Clone)]
-struct PostgresTableSchema {
-    primary_key_columns: Vec<FieldSchemaInfo>,
-    value_columns: Vec<FieldSchemaInfo>,
-    ordinal_field_idx: Option<usize>,
-    ordinal_field_schema: Option<FieldSchemaInfo>,
-}
-
 struct NotificationContext {
     channel_name: String,
     function_name: String,
     trigger_name: String,
 }
 
-struct PostgresSourceExecutor {
+struct ReplicationContext {
+    /// Schema of the table, resolved once through the `search_path`.
+    namespace: String,
+    slot_name: String,
+    plugin: ReplicationPlugin,
+    publication_name: String,
+    poll_interval: std::time::Duration,
+    /// Ordinal of listed rows. It doesn't advance with the WAL, so refreshes don't supersede
+    /// changes captured by the slot, which carry their commit LSN.
+    listing_ordinal: Ordinal,
+    /// Also serializes reads and advances of the slot, which Postgres rejects when concurrent.
+    ack_tracker: Arc<tokio::sync::Mutex<LsnAckTracker>>,
+}
+
+struct PostgresTableDriver {
     db_pool: PgPool,
-    table_name: String,
-    table_schema: PostgresTableSchema,
-    notification_ctx: Option<NotificationContext>,
-    filter: Option<String>,
 }
 
-impl PostgresSourceExecutor {
-    /// Append value and ordinal columns to the provided columns vector.
-    /// Returns the optional index of the ordinal column in the final selection.
-    fn build_selected_columns(
-        &self,
-        columns: &mut Vec<String>,
-        options: &SourceExecutorReadOptions,
-    ) -> Option<usize> {
-        let base_len = columns.len();
-        if options.include_value {
-            columns.extend(
-                self.table_schema
-                    .value_columns
-                    .iter()
-                    .map(|col| format!("\"{}\"", col.schema.name)),
-            );
-        }
+#[async_trait]
+impl SqlTableDriver for PostgresTableDriver {
+    type Row = PgRow;
 
-        if options.include_ordinal {
-            if let Some(ord_schema) = &self.table_schema.ordinal_field_schema {
-                if options.include_value {
-                    if let Some(val_idx) = self.table_schema.ordinal_field_idx {
-                        return Some(base_len + val_idx);
-                    }
-                }
-                columns.push(format!("\"{}\"", ord_schema.schema.name));
-                return Some(columns.len() - 1);
+    fn quote_identifier(name: &str) -> String {
+        format!("\"{name}\"")
+    }
+
+    fn fetch_rows(&self, query: String) -> BoxStream<'_, Result<PgRow>> {
+        try_stream! {
+            let mut rows = sqlx::query(&query).fetch(&self.db_pool);
+            while let Some(row) = rows.try_next().await? {
+                yield row;
             }
         }
-
-        None
+        .boxed()
     }
 
-    /// Decode all value columns from a row, starting at the given index offset.
-    fn decode_row_data(
+    async fn fetch_row_by_key(
         &self,
-        row: &sqlx::postgres::PgRow,
-        options: &SourceExecutorReadOptions,
-        ordinal_col_index: Option<usize>,
-        value_start_idx: usize,
-    ) -> Result<PartialSourceRowData> {
-        let value = if options.include_value {
-            let mut fields = Vec::with_capacity(self.table_schema.value_columns.len());
-            for (i, info) in self.table_schema.value_columns.iter().enumerate() {
-                let value = (info.decoder)(row, value_start_idx + i)?;
-                fields.push(value);
-            }
-            Some(SourceValue::Existence(FieldValues { fields }))
-        } else {
-            None
-        };
+        query: String,
+        key_columns: &[String],
+        key: &KeyValue,
+        filter: Option<&str>,
+    ) -> Result<Option<PgRow>> {
+        let mut qb = sqlx::QueryBuilder::new(query);
+        push_key_conditions(&mut qb, key_columns, key, filter, bind_key_field)?;
+        Ok(qb.build().fetch_optional(&self.db_pool).await?)
+    }
+}
 
-        let ordinal = if options.include_ordinal {
-            if let (Some(idx), Some(ord_schema)) = (
-                ordinal_col_index,
-                self.table_schema.ordinal_field_schema.as_ref(),
-            ) {
-                let val = (ord_schema.decoder)(row, idx)?;
-                Some(value_to_ordinal(&val))
-            } else {
-                Some(Ordinal::unavailable())
-            }
-        } else {
-            None
-        };
+struct PostgresSourceExecutor {
+    reader: SqlTableReader<PostgresTableDriver>,
+    db_pool: PgPool,
+    notification_ctx: Option<NotificationContext>,
+    replication_ctx: Option<ReplicationContext>,
+}
 
-        Ok(PartialSourceRowData {
-            value,
-            ordinal,
-            content_version_fp: None,
-        })
+impl PostgresSourceExecutor {
+    fn new(
+        db_pool: PgPool,
+        table_name: String,
+        mut table_schema: SqlTableSchema<PgRow>,
+        filter: Option<String>,
+        notification_ctx: Option<NotificationContext>,
+        replication_ctx: Option<ReplicationContext>,
+    ) -> Self {
+        if replication_ctx.is_some() {
+            // Values fetched for a captured change are at least as recent as its commit LSN.
+            table_schema.computed_ordinal = Some(ComputedOrdinal {
+                expr: "pg_current_wal_lsn()::text".to_string(),
+                decoder: |row, idx| {
+                    let lsn = row.try_get::<String, _>(idx)?.parse::<Lsn>()?;
+                    Ok(Ordinal(Some(lsn.to_ordinal()?)))
+                },
+            });
+        }
+        Self {
+            reader: SqlTableReader {
+                driver: PostgresTableDriver {
+                    db_pool: db_pool.clone(),
+                },
+                table_name,
+                table_schema,
+                filter,
+            },
+            db_pool,
+            notification_ctx,
+            replication_ctx,
+        }
     }
 }
 
@@ -308,13 +354,32 @@ fn map_postgres_type_to_cocoindex_and_decoder(
     Some(result)
 }
 
+/// Resolve the schema of the table, looked up through the `search_path` like other statements.
+async fn resolve_table_namespace(pool: &PgPool, table_name: &str) -> Result<String> {
+    if table_name.contains('.') {
+        client_bail!(
+            "`table_name` must not be schema-qualified, the table is looked up through the `search_path`: {table_name}"
+        );
+    }
+    let (namespace,): (String,) = sqlx::query_as(
+        "SELECT n.nspname::text FROM pg_class c JOIN pg_namespace n ON c.relnamespace = n.oid WHERE c.oid = $1::text::regclass",
+    )
+    .bind(PostgresTableDriver::quote_identifier(table_name))
+    .fetch_one(pool)
+    .await
+    .map_err(Error::from)
+    .with_context(|| format!("Failed to resolve table `{table_name}`"))?;
+    Ok(namespace)
+}
+
 /// Fetch table schema information from PostgreSQL
 async fn fetch_table_schema(
     pool: &PgPool,
+    namespace: &str,
     table_name: &str,
     included_columns: &Option<Vec<String>>,
     ordinal_column: &Option<String>,
-) -> Result<PostgresTableSchema> {
+) -> Result<SqlTableSchema<PgRow>> {
     // Query to get column information including primary key status
     let query = r#"
         SELECT
@@ -337,125 +402,42 @@ async fn fetch_table_schema(
                     AND tc.table_schema = kcu.table_schema
             WHERE
                 tc.constraint_type = 'PRIMARY KEY'
-                AND tc.table_name = $1
+                AND tc.table_schema = $1
+                AND tc.table_name = $2
         ) pk ON c.column_name = pk.column_name
         WHERE
-            c.table_name = $1
+            c.table_schema = $1
+            AND c.table_name = $2
         ORDER BY c.ordinal_position
     "#;
 
-    let rows = sqlx::query(query).bind(table_name).fetch_all(pool).await?;
-
-    let mut primary_key_columns: Vec<FieldSchemaInfo> = Vec::new();
-    let mut value_columns: Vec<FieldSchemaInfo> = Vec::new();
-    let mut ordinal_field_schema: Option<FieldSchemaInfo> = None;
+    let rows = sqlx::query(query)
+        .bind(namespace)
+        .bind(table_name)
+        .fetch_all(pool)
+        .await?;
 
+    let mut columns = Vec::with_capacity(rows.len());
     for row in rows {
         let col_name: String = row.try_get::<String, _>("column_name")?;
         let pg_type_str: String = row.try_get::<String, _>("data_type")?;
         let is_nullable: bool = row.try_get::<String, _>("is_nullable")? == "YES";
         let is_primary_key: bool = row.try_get::<bool, _>("is_primary_key")?;
 
-        let Some((basic_type, decoder)) = map_postgres_type_to_cocoindex_and_decoder(&pg_type_str)
+        let Some((value_type, decoder)) = map_postgres_type_to_cocoindex_and_decoder(&pg_type_str)
         else {
             continue;
         };
-        let field_schema = FieldSchema::new(
-            &col_name,
-            make_output_type(basic_type).with_nullable(is_nullable),
-        );
-
-        let info = FieldSchemaInfo {
-            schema: field_schema.clone(),
+        columns.push(SqlColumnInfo {
+            name: col_name,
+            value_type,
             decoder,
-        };
-
-        if let Some(ord_col) = ordinal_column
-            && &col_name == ord_col
-        {
-            ordinal_field_schema = Some(info.clone());
-            if is_primary_key {
-                api_bail!(
-                    "`ordinal_column` cannot be a primary key column. It must be one of the value columns."
-                );
-            }
-        }
-
-        if is_primary_key {
-            primary_key_columns.push(info);
-        } else if included_columns
-            .as_ref()
-            .is_none_or(|cols| cols.contains(&col_name))
-        {
-            value_columns.push(info.clone());
-        }
-    }
-
-    if primary_key_columns.is_empty() {
-        if value_columns.is_empty() {
-            api_bail!("Table `{table_name}` not found");
-        }
-        api_bail!("Table `{table_name}` has no primary key defined");
+            is_nullable,
+            is_primary_key,
+        });
     }
 
-    // If ordinal column specified, validate and compute its index within value columns if present
-    let ordinal_field_idx = match ordinal_column {
-        Some(ord) => {
-            let schema = ordinal_field_schema
-                .as_ref()
-                .ok_or_else(|| client_error!("`ordinal_column` `{}` not found in table", ord))?;
-            if !is_supported_ordinal_type(&schema.schema.value_type.typ) {
-                api_bail!(
-                    "Unsupported `ordinal_column` type for `{}`. Supported types: Int64, LocalDateTime, OffsetDateTime",
-                    schema.schema.name
-                );
-            }
-            value_columns.iter().position(|c| c.schema.name == *ord)
-        }
-        None => None,
-    };
-
-    Ok(PostgresTableSchema {
-        primary_key_columns,
-        value_columns,
-        ordinal_field_idx,
-        ordinal_field_schema,
-    })
-}
-
-// Per-column decoders are attached to schema; no generic converter needed anymore
-
-/// Convert a CocoIndex `Value` into an `Ordinal` if supported.
-/// Supported inputs:
-/// - Basic(Int64): interpreted directly as microseconds
-/// - Basic(LocalDateTime): converted to UTC micros
-/// - Basic(OffsetDateTime): micros since epoch
-///
-///   Otherwise returns unavailable.
-fn is_supported_ordinal_type(t: &ValueType) -> bool {
-    matches!(
-        t,
-        ValueType::Basic(BasicValueType::Int64)
-            | ValueType::Basic(BasicValueType::LocalDateTime)
-            | ValueType::Basic(BasicValueType::OffsetDateTime)
-    )
-}
-
-fn value_to_ordinal(value: &Value) -> Ordinal {
-    match value {
-        Value::Null => Ordinal::unavailable(),
-        Value::Basic(basic) => match basic {
-            crate::base::value::BasicValue::Int64(v) => Ordinal(Some(*v)),
-            crate::base::value::BasicValue::LocalDateTime(dt) => {
-                Ordinal(Some(dt.and_utc().timestamp_micros()))
-            }
-            crate::base::value::BasicValue::OffsetDateTime(dt) => {
-                Ordinal(Some(dt.timestamp_micros()))
-            }
-            _ => Ordinal::unavailable(),
-        },
-        _ => Ordinal::unavailable(),
-    }
+    SqlTableSchema::new(table_name, columns, included_columns, ordinal_column)
 }
 
 #[async_trait]
@@ -464,50 +446,22 @@ impl SourceExecutor for PostgresSourceExecutor {
         &self,
         options: &SourceExecutorReadOptions,
     ) -> Result<BoxStream<'async_trait, Result<Vec<PartialSourceRow>>>> {
-        // Build selection including PKs (for keys), and optionally values and ordinal
-        let pk_columns: Vec<String> = self
-            .table_schema
-            .primary_key_columns
-            .iter()
-            .map(|col| format!("\"{}\"", col.schema.name))
-            .collect();
-        let pk_count = pk_columns.len();
-        let mut select_parts = pk_columns;
-        let ordinal_col_index = self.build_selected_columns(&mut select_parts, options);
-
-        let mut query = format!(
-            "SELECT {} FROM \"{}\"",
-            select_parts.join(", "),
-            self.table_name
-        );
-
-        // Add WHERE filter if specified
-        if let Some(where_clause) = &self.filter {
-            write!(&mut query, " WHERE {}", where_clause)?;
-        }
-
-        let stream = try_stream! {
-            let mut rows = sqlx::query(&query).fetch(&self.db_pool);
-            while let Some(row) = rows.try_next().await? {
-                // Decode key from PKs (selected first)
-                let parts = self.table_schema.primary_key_columns
-                    .iter()
-                    .enumerate()
-                    .map(|(i, info)| (info.decoder)(&row, i)?.into_key())
-                    .collect::<Result<Box<[KeyPart]>>>()?;
-                let key = KeyValue(parts);
-
-                // Decode value and ordinal
-                let data = self.decode_row_data(&row, options, ordinal_col_index, pk_count)?;
-
-                yield vec![PartialSourceRow {
-                    key,
-                    key_aux_info: serde_json::Value::Null,
-                    data,
-                }];
-            }
+        let rows = self.reader.list(options).await?;
+        let Some(replication_ctx) = &self.replication_ctx else {
+            return Ok(rows);
         };
-        Ok(stream.boxed())
+        if !options.include_ordinal {
+            return Ok(rows);
+        }
+        let ordinal = replication_ctx.listing_ordinal;
+        Ok(rows
+            .map_ok(move |mut rows| {
+                for row in rows.iter_mut() {
+                    row.data.ordinal = Some(ordinal);
+                }
+                rows
+            })
+            .boxed())
     }
 
     async fn get_value(
@@ -516,66 +470,15 @@ impl SourceExecutor for PostgresSourceExecutor {
         _key_aux_info: &serde_json::Value,
         options: &SourceExecutorReadOptions,
     ) -> Result<PartialSourceRowData> {
-        let mut qb = sqlx::QueryBuilder::new("SELECT ");
-        let mut selected_columns: Vec<String> = Vec::new();
-        let ordinal_col_index = self.build_selected_columns(&mut selected_columns, options);
-
-        if selected_columns.is_empty() {
-            qb.push("1");
-        } else {
-            qb.push(selected_columns.join(", "));
-        }
-        qb.push(" FROM \"");
-        qb.push(&self.table_name);
-        qb.push("\" WHERE ");
-
-        if key.len() != self.table_schema.primary_key_columns.len() {
-            internal_bail!(
-                "Composite key has {} values but table has {} primary key columns",
-                key.len(),
-                self.table_schema.primary_key_columns.len()
-            );
-        }
-
-        for (i, (pk_col, key_value)) in self
-            .table_schema
-            .primary_key_columns
-            .iter()
-            .zip(key.iter())
-            .enumerate()
-        {
-            if i > 0 {
-                qb.push(" AND ");
-            }
-            qb.push("\"");
-            qb.push(pk_col.schema.name.as_str());
-            qb.push("\" = ");
-            bind_key_field(&mut qb, key_value)?;
-        }
-
-        // Add WHERE filter if specified
-        if let Some(where_clause) = &self.filter {
-            qb.push(" AND (");
-            qb.push(where_clause);
-            qb.push(")");
-        }
-
-        let row_opt = qb.build().fetch_optional(&self.db_pool).await?;
-        let data = match &row_opt {
-            Some(row) => self.decode_row_data(row, options, ordinal_col_index, 0)?,
-            None => PartialSourceRowData {
-                value: Some(SourceValue::NonExistence),
-                ordinal: Some(Ordinal::unavailable()),
-                content_version_fp: None,
-            },
-        };
-
-        Ok(data)
+        self.reader.get_value(key, options).await
     }
 
     async fn change_stream(
         &self,
     ) -> Result<Option<BoxStream<'async_trait, Result<SourceChangeMessage>>>> {
+        if let Some(replication_ctx) = &self.replication_ctx {
+            return Ok(Some(self.replication_change_stream(replication_ctx)));
+        }
         let Some(notification_ctx) = &self.notification_ctx else {
             return Ok(None);
         };
@@ -629,7 +532,7 @@ impl SourceExecutor for PostgresSourceExecutor {
     }
 
     fn provides_ordinal(&self) -> bool {
-        self.table_schema.ordinal_field_schema.is_some()
+        self.reader.table_schema.provides_ordinal()
     }
 }
 
@@ -643,8 +546,8 @@ impl PostgresSourceExecutor {
         let trigger_name = &notification_ctx.trigger_name;
 
         let json_object_expr = |var: &str| {
-            let mut fields = (self.table_schema.primary_key_columns.iter())
-                .chain(self.table_schema.ordinal_field_schema.iter())
+            let mut fields = (self.reader.table_schema.primary_key_columns.iter())
+                .chain(self.reader.table_schema.ordinal_field_schema.iter())
                 .map(|col| {
                     let field_name = &col.schema.name;
                     if matches!(
@@ -682,7 +585,7 @@ impl PostgresSourceExecutor {
             format!(
                 "DROP TRIGGER IF EXISTS {trigger_name} ON \"{table_name}\";",
                 trigger_name = trigger_name,
-                table_name = self.table_name,
+                table_name = self.reader.table_name,
             ),
             formatdoc! {r#"
             CREATE TRIGGER {trigger_name}
@@ -690,7 +593,7 @@ impl PostgresSourceExecutor {
                 FOR EACH ROW EXECUTE FUNCTION {function_name}();
             "#,
                 trigger_name = trigger_name,
-                table_name = self.table_name,
+                table_name = self.reader.table_name,
                 function_name = function_name,
             },
         ];
@@ -703,6 +606,214 @@ impl PostgresSourceExecutor {
         Ok(())
     }
 
+    fn replication_change_stream<'a>(
+        &'a self,
+        replication_ctx: &'a ReplicationContext,
+    ) -> BoxStream<'a, Result<SourceChangeMessage>> {
+        let stream = stream! {
+            let mut decoder = PgOutputDecoder::default();
+            let mut last_emitted_lsn = Lsn::default();
+            let mut interval = tokio::time::interval(replication_ctx.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let mut ack_tracker = replication_ctx.ack_tracker.lock().await;
+                let transactions = match self
+                    .peek_replication_transactions(replication_ctx, &mut decoder)
+                    .await
+                {
+                    Ok(transactions) => transactions,
+                    Err(e) => {
+                        yield Err(e);
+                        continue;
+                    }
+                };
+
+                let mut change_messages = Vec::new();
+                let mut confirmable_lsn = None;
+                for (commit_lsn, messages) in transactions {
+                    // The slot is only advanced once acknowledged, so peeks return transactions
+                    // that were already emitted.
+                    if commit_lsn <= last_emitted_lsn {
+                        continue;
+                    }
+                    last_emitted_lsn = commit_lsn;
+                    ack_tracker.emit(commit_lsn);
+
+                    let changes = match self.replication_messages_to_changes(messages, commit_lsn) {
+                        Ok(changes) => changes,
+                        Err(e) => {
+                            yield Err(e);
+                            Vec::new()
+                        }
+                    };
+                    if changes.is_empty() {
+                        // Nothing to wait for, e.g. a transaction touching other tables only.
+                        confirmable_lsn = ack_tracker.ack(commit_lsn).or(confirmable_lsn);
+                        continue;
+                    }
+                    let ack_fn = {
+                        let db_pool = self.db_pool.clone();
+                        let slot_name = replication_ctx.slot_name.clone();
+                        let ack_tracker = replication_ctx.ack_tracker.clone();
+                        move || {
+                            async move {
+                                let confirmable_lsn = ack_tracker.lock().await.ack(commit_lsn);
+                                match confirmable_lsn {
+                                    Some(lsn) => advance_replication_slot(&db_pool, &slot_name, lsn).await,
+                                    None => Ok(()),
+                                }
+                            }
+                            .boxed()
+                        }
+                    };
+                    change_messages.push(SourceChangeMessage {
+                        changes,
+                        ack_fn: Some(Box::new(ack_fn)),
+                    });
+                }
+                if let Some(lsn) = confirmable_lsn
+                    && let Err(e) = advance_replication_slot(&self.db_pool, &replication_ctx.slot_name, lsn).await
+                {
+                    yield Err(e);
+                }
+                std::mem::drop(ack_tracker);
+
+                for change_message in change_messages {
+                    yield Ok(change_message);
+                }
+            }
+        };
+        stream.boxed()
+    }
+
+    /// Reads pending changes from the replication slot without consuming them, grouped by
+    /// transaction and keyed by the LSN of the commit.
+    async fn peek_replication_transactions(
+        &self,
+        replication_ctx: &ReplicationContext,
+        decoder: &mut PgOutputDecoder,
+    ) -> Result<Vec<(Lsn, Vec<DecodedMessage>)>> {
+        // Fetch all rows before decoding: the slot can't be advanced while a peek is in progress.
+        let rows: Vec<(String, Vec<u8>)> = match replication_ctx.plugin {
+            ReplicationPlugin::PgOutput => {
+                sqlx::query_as(
+                    "SELECT lsn::text, data FROM pg_logical_slot_peek_binary_changes($1, NULL, $2, 'proto_version', '1', 'publication_names', $3)",
+                )
+                .bind(&replication_ctx.slot_name)
+                .bind(REPLICATION_POLL_MAX_CHANGES)
+                .bind(&replication_ctx.publication_name)
+                .fetch_all(&self.db_pool)
+                .await?
+            }
+            ReplicationPlugin::Wal2Json => {
+                sqlx::query_as(
+                    "SELECT lsn::text, convert_to(data, 'UTF8') FROM pg_logical_slot_peek_changes($1, NULL, $2, 'format-version', '2', 'add-tables', $3)",
+                )
+                .bind(&replication_ctx.slot_name)
+                .bind(REPLICATION_POLL_MAX_CHANGES)
+                .bind(wal2json_table_pattern(
+                    &replication_ctx.namespace,
+                    &self.reader.table_name,
+                ))
+                .fetch_all(&self.db_pool)
+                .await?
+            }
+        };
+
+        let relation = (
+            replication_ctx.namespace.as_str(),
+            self.reader.table_name.as_str(),
+        );
+        let mut transactions = Vec::new();
+        let mut current = Vec::new();
+        for (lsn, data) in rows {
+            let message = match replication_ctx.plugin {
+                ReplicationPlugin::PgOutput => decoder.decode(&data)?,
+                ReplicationPlugin::Wal2Json => replication::decode_wal2json(
+                    std::str::from_utf8(&data).map_err(Error::internal)?,
+                )?,
+            };
+            match message {
+                DecodedMessage::Begin => current.clear(),
+                DecodedMessage::Commit => {
+                    transactions.push((lsn.parse::<Lsn>()?, std::mem::take(&mut current)));
+                }
+                _ if message.relation() == Some(relation) => current.push(message),
+                _ => {}
+            }
+        }
+        Ok(transactions)
+    }
+
+    fn replication_messages_to_changes(
+        &self,
+        messages: Vec<DecodedMessage>,
+        commit_lsn: Lsn,
+    ) -> Result<Vec<SourceChange>> {
+        let ordinal = Some(Ordinal(Some(commit_lsn.to_ordinal()?)));
+        let deletion = |key| SourceChange {
+            key,
+            key_aux_info: serde_json::Value::Null,
+            data: PartialSourceRowData {
+                value: Some(SourceValue::NonExistence),
+                ordinal,
+                content_version_fp: None,
+            },
+        };
+        let mut changes = Vec::with_capacity(messages.len());
+        for message in messages {
+            match message {
+                DecodedMessage::Upsert { old_key, new, .. } => {
+                    let key = self.decode_replicated_key(new)?;
+                    if let Some(old_key) = old_key {
+                        let old_key = self.decode_replicated_key(old_key)?;
+                        if old_key != key {
+                            changes.push(deletion(old_key));
+                        }
+                    }
+                    // Like notifications, let the engine fetch the latest value.
+                    changes.push(SourceChange {
+                        key,
+                        key_aux_info: serde_json::Value::Null,
+                        data: PartialSourceRowData {
+                            value: None,
+                            ordinal,
+                            content_version_fp: None,
+                        },
+                    });
+                }
+                DecodedMessage::Delete { old_key, .. } => {
+                    changes.push(deletion(self.decode_replicated_key(old_key)?));
+                }
+                _ => {}
+            }
+        }
+        Ok(changes)
+    }
+
+    fn decode_replicated_key(&self, mut values: ColumnValues) -> Result<KeyValue> {
+        let mut key_parts = Vec::with_capacity(self.reader.table_schema.primary_key_columns.len());
+        for pk_col in &self.reader.table_schema.primary_key_columns {
+            let value_type = &pk_col.schema.value_type.typ;
+            let value = match values.remove(&pk_col.schema.name) {
+                Some(ColumnValue::Text(s))
+                | Some(ColumnValue::Json(serde_json::Value::String(s))) => {
+                    Self::decode_key_value_in_text(&s, value_type)?
+                }
+                Some(ColumnValue::Json(json_value)) => {
+                    Self::decode_key_ordinal_value_in_json(json_value, value_type)?
+                }
+                Some(ColumnValue::Null) => Value::Null,
+                Some(ColumnValue::Unchanged) | None => {
+                    client_bail!("Missing primary key field: {}", pk_col.schema.name)
+                }
+            };
+            key_parts.push(value.into_key()?);
+        }
+        Ok(KeyValue(key_parts.into_boxed_slice()))
+    }
+
     fn parse_notification_payload(&self, notification: &PgNotification) -> Result<SourceChange> {
         let mut payload: serde_json::Value = utils::deser::from_json_str(notification.payload())?;
         let payload = payload
@@ -725,8 +836,8 @@ impl PostgresSourceExecutor {
         );
 
         // Extract primary key values to construct the key
-        let mut key_parts = Vec::with_capacity(self.table_schema.primary_key_columns.len());
-        for pk_col in &self.table_schema.primary_key_columns {
+        let mut key_parts = Vec::with_capacity(self.reader.table_schema.primary_key_columns.len());
+        for pk_col in &self.reader.table_schema.primary_key_columns {
             let field_value = fields.get_mut(&pk_col.schema.name).ok_or_else(|| {
                 client_error!("Missing primary key field: {}", pk_col.schema.name)
             })?;
@@ -742,7 +853,7 @@ impl PostgresSourceExecutor {
         let key = KeyValue(key_parts.into_boxed_slice());
 
         // Extract ordinal if available
-        let ordinal = if let Some(ord_schema) = &self.table_schema.ordinal_field_schema {
+        let ordinal = if let Some(ord_schema) = &self.reader.table_schema.ordinal_field_schema {
             if let Some(ord_value) = fields.get_mut(&ord_schema.schema.name) {
                 let value = Self::decode_key_ordinal_value_in_json(
                     std::mem::take(ord_value),
@@ -781,6 +892,43 @@ impl PostgresSourceExecutor {
         })
     }
 
+    /// Decode a value in Postgres' text representation, as emitted by logical replication.
+    fn decode_key_value_in_text(text: &str, value_type: &ValueType) -> Result<Value> {
+        let result = match value_type {
+            ValueType::Basic(BasicValueType::Bool) => match text {
+                "t" | "true" => BasicValue::Bool(true).into(),
+                "f" | "false" => BasicValue::Bool(false).into(),
+                _ => client_bail!("Invalid boolean value: {text}"),
+            },
+            ValueType::Basic(BasicValueType::Bytes) => {
+                let hex = text
+                    .strip_prefix("\\x")
+                    .filter(|hex| hex.len() % 2 == 0)
+                    .ok_or_else(|| client_error!("Invalid bytea value: {text}"))?;
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                    .collect::<std::result::Result<Vec<u8>, _>>()
+                    .map_err(|_| client_error!("Invalid bytea value: {text}"))?;
+                BasicValue::Bytes(bytes::Bytes::from(bytes)).into()
+            }
+            ValueType::Basic(BasicValueType::Str) => BasicValue::Str(text.into()).into(),
+            ValueType::Basic(BasicValueType::Int64) => BasicValue::Int64(text.parse()?).into(),
+            ValueType::Basic(BasicValueType::Uuid) => BasicValue::Uuid(text.parse()?).into(),
+            ValueType::Basic(BasicValueType::Date) => BasicValue::Date(text.parse()?).into(),
+            ValueType::Basic(BasicValueType::LocalDateTime) => BasicValue::LocalDateTime(
+                chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f")?,
+            )
+            .into(),
+            ValueType::Basic(BasicValueType::OffsetDateTime) => BasicValue::OffsetDateTime(
+                chrono::DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f%#z")?,
+            )
+            .into(),
+            _ => client_bail!("Got unsupported text value for type {value_type}: {text}"),
+        };
+        Ok(result)
+    }
+
     fn decode_key_ordinal_value_in_json(
         json_value: serde_json::Value,
         value_type: &ValueType,
@@ -831,6 +979,126 @@ impl PostgresSourceExecutor {
     }
 }
 
+/// Replication slot names may only contain lower case letters, numbers and underscores.
+fn to_replication_slot_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_SLOT_NAME_LEN)
+        .collect()
+}
+
+/// Slot and publication names for a source, in that order.
+fn replication_names(
+    spec: &ReplicationSpec,
+    flow_instance_name: &str,
+    source_name: &str,
+) -> (String, String) {
+    let slot_name = to_replication_slot_name(
+        spec.slot_name
+            .as_deref()
+            .unwrap_or(&format!("{flow_instance_name}__{source_name}__cocoindex")),
+    );
+    let publication_name = spec
+        .publication_name
+        .clone()
+        .unwrap_or_else(|| to_replication_slot_name(&format!("{slot_name}_pub")));
+    (slot_name, publication_name)
+}
+
+/// The `add-tables` option of `wal2json` matching exactly the given table.
+fn wal2json_table_pattern(namespace: &str, table_name: &str) -> String {
+    let escape = |name: &str| {
+        let mut escaped = String::with_capacity(name.len());
+        for c in name.chars() {
+            if matches!(c, ' ' | '\'' | ',' | '.' | '*' | '\\') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    };
+    format!("{}.{}", escape(namespace), escape(table_name))
+}
+
+/// A replication slot or publication created by the source, recorded in the flow's setup state.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+enum ReplicationResource {
+    Slot {
+        database: Option<spec::AuthEntryReference<DatabaseConnectionSpec>>,
+        slot_name: String,
+    },
+    Publication {
+        database: Option<spec::AuthEntryReference<DatabaseConnectionSpec>>,
+        publication_name: String,
+    },
+}
+
+/// Creates the publication and slot if missing.
+/// Returns the position from which a newly created slot captures changes.
+async fn setup_replication(
+    pool: &PgPool,
+    table_name: &str,
+    replication_ctx: &ReplicationContext,
+) -> Result<Option<Lsn>> {
+    // The publication must exist before the slot: changes are decoded with the catalog as of
+    // their position in the WAL.
+    if replication_ctx.plugin == ReplicationPlugin::PgOutput {
+        let exists = sqlx::query("SELECT 1 FROM pg_publication WHERE pubname = $1")
+            .bind(&replication_ctx.publication_name)
+            .fetch_optional(pool)
+            .await?
+            .is_some();
+        if !exists {
+            sqlx::query(&format!(
+                "CREATE PUBLICATION \"{}\" FOR TABLE \"{}\".\"{}\"",
+                replication_ctx.publication_name,
+                replication_ctx.namespace.replace('"', "\"\""),
+                table_name.replace('"', "\"\"")
+            ))
+            .execute(pool)
+            .await?;
+        }
+    }
+    let exists = sqlx::query("SELECT 1 FROM pg_replication_slots WHERE slot_name = $1")
+        .bind(&replication_ctx.slot_name)
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+    if exists {
+        return Ok(None);
+    }
+    let (lsn,): (String,) =
+        sqlx::query_as("SELECT lsn::text FROM pg_create_logical_replication_slot($1, $2)")
+            .bind(&replication_ctx.slot_name)
+            .bind(replication_ctx.plugin.name())
+            .fetch_one(pool)
+            .await
+            .map_err(Error::from)
+            .with_context(|| {
+                format!(
+                    "Failed to create replication slot `{}`. Logical replication requires `wal_level = logical`",
+                    replication_ctx.slot_name
+                )
+            })?;
+    Ok(Some(lsn.parse()?))
+}
+
+async fn advance_replication_slot(pool: &PgPool, slot_name: &str, lsn: Lsn) -> Result<()> {
+    sqlx::query("SELECT pg_replication_slot_advance($1, $2::pg_lsn)")
+        .bind(slot_name)
+        .bind(lsn.to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
 pub struct Factory;
 
 #[async_trait]
@@ -848,31 +1116,17 @@ impl SourceFactoryBase for Factory {
     ) -> Result<EnrichedValueType> {
         // Fetch table schema to build dynamic output schema
         let db_pool = get_db_pool(spec.database.as_ref(), &context.auth_registry).await?;
+        let namespace = resolve_table_namespace(&db_pool, &spec.table_name).await?;
         let table_schema = fetch_table_schema(
             &db_pool,
+            &namespace,
             &spec.table_name,
             &spec.included_columns,
             &spec.ordinal_column,
         )
         .await?;
 
-        Ok(make_output_type(TableSchema::new(
-            TableKind::KTable(KTableInfo {
-                num_key_parts: table_schema.primary_key_columns.len(),
-            }),
-            StructSchema {
-                fields: Arc::new(
-                    (table_schema.primary_key_columns.into_iter().map(|pk_col| {
-                        FieldSchema::new(&pk_col.schema.name, pk_col.schema.value_type)
-                    }))
-                    .chain(table_schema.value_columns.into_iter().map(|value_col| {
-                        FieldSchema::new(&value_col.schema.name, value_col.schema.value_type)
-                    }))
-                    .collect(),
-                ),
-                description: None,
-            },
-        )))
+        Ok(table_schema.output_type())
     }
 
     async fn build_executor(
@@ -881,11 +1135,23 @@ impl SourceFactoryBase for Factory {
         spec: Spec,
         context: Arc<FlowInstanceContext>,
     ) -> Result<Box<dyn SourceExecutor>> {
+        if spec.replication.is_some() {
+            if spec.notification.is_some() {
+                api_bail!("`replication` and `notification` cannot be both specified");
+            }
+            if spec.ordinal_column.is_some() {
+                api_bail!(
+                    "`ordinal_column` cannot be specified with `replication`, which uses the LSN as ordinal"
+                );
+            }
+        }
         let db_pool = get_db_pool(spec.database.as_ref(), &context.auth_registry).await?;
 
         // Fetch table schema for dynamic type handling
+        let namespace = resolve_table_namespace(&db_pool, &spec.table_name).await?;
         let table_schema = fetch_table_schema(
             &db_pool,
+            &namespace,
             &spec.table_name,
             &spec.included_columns,
             &spec.ordinal_column,
@@ -903,14 +1169,277 @@ impl SourceFactoryBase for Factory {
             }
         });
 
-        let executor = PostgresSourceExecutor {
+        let mut replication_ctx = spec.replication.map(|spec| {
+            let (slot_name, publication_name) =
+                replication_names(&spec, &context.flow_instance_name, source_name);
+            ReplicationContext {
+                namespace,
+                publication_name,
+                slot_name,
+                plugin: spec.plugin,
+                poll_interval: spec
+                    .poll_interval
+                    .unwrap_or(DEFAULT_REPLICATION_POLL_INTERVAL),
+                listing_ordinal: Ordinal(Some(0)),
+                ack_tracker: Arc::new(tokio::sync::Mutex::new(LsnAckTracker::default())),
+            }
+        });
+        if let Some(replication_ctx) = &mut replication_ctx {
+            // Set up before the initial listing, so no change after it is missed.
+            // Rows processed before a new slot may be stale, so they're superseded by the listing.
+            if let Some(lsn) =
+                setup_replication(&db_pool, &spec.table_name, replication_ctx).await?
+            {
+                replication_ctx.listing_ordinal = Ordinal(Some(lsn.to_ordinal()?));
+            }
+        }
+
+        let executor = PostgresSourceExecutor::new(
             db_pool,
-            table_name: spec.table_name.clone(),
+            spec.table_name,
             table_schema,
+            spec.filter,
             notification_ctx,
-            filter: spec.filter,
-        };
+            replication_ctx,
+        );
 
         Ok(Box::new(executor))
     }
+
+    fn setup_resources(
+        &self,
+        source_name: &str,
+        spec: &Spec,
+        context: &FlowInstanceContext,
+    ) -> Result<Vec<serde_json::Value>> {
+        let Some(replication) = &spec.replication else {
+            return Ok(vec![]);
+        };
+        let (slot_name, publication_name) =
+            replication_names(replication, &context.flow_instance_name, source_name);
+        let mut resources = vec![ReplicationResource::Slot {
+            database: spec.database.clone(),
+            slot_name,
+        }];
+        if replication.plugin == ReplicationPlugin::PgOutput {
+            resources.push(ReplicationResource::Publication {
+                database: spec.database.clone(),
+                publication_name,
+            });
+        }
+        Ok(resources
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<std::result::Result<_, _>>()?)
+    }
+
+    async fn drop_resource(
+        &self,
+        resource: serde_json::Value,
+        context: Arc<FlowInstanceContext>,
+    ) -> Result<()> {
+        match utils::deser::from_json_value::<ReplicationResource>(resource)? {
+            ReplicationResource::Slot {
+                database,
+                slot_name,
+            } => {
+                let db_pool = get_db_pool(database.as_ref(), &context.auth_registry).await?;
+                sqlx::query(
+                    "SELECT pg_drop_replication_slot(slot_name) FROM pg_replication_slots WHERE slot_name = $1",
+                )
+                .bind(&slot_name)
+                .execute(&db_pool)
+                .await
+                .map_err(Error::from)
+                .with_context(|| format!("Failed to drop replication slot `{slot_name}`"))?;
+            }
+            ReplicationResource::Publication {
+                database,
+                publication_name,
+            } => {
+                let db_pool = get_db_pool(database.as_ref(), &context.auth_registry).await?;
+                sqlx::query(&format!(
+                    "DROP PUBLICATION IF EXISTS \"{}\"",
+                    publication_name.replace('"', "\"\"")
+                ))
+                .execute(&db_pool)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replication_names_and_quoting() {
+        let spec: ReplicationSpec = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(
+            replication_names(&spec, "My.Flow", "docs"),
+            (
+                "my_flow__docs__cocoindex".to_string(),
+                "my_flow__docs__cocoindex_pub".to_string()
+            )
+        );
+        let spec: ReplicationSpec =
+            serde_json::from_value(serde_json::json!({"slot_name": "s", "publication_name": "P"}))
+                .unwrap();
+        assert_eq!(
+            replication_names(&spec, "flow", "docs"),
+            ("s".to_string(), "P".to_string())
+        );
+
+        assert_eq!(wal2json_table_pattern("public", "docs"), "public.docs");
+        assert_eq!(
+            wal2json_table_pattern("my app", "a.b*"),
+            "my\\ app.a\\.b\\*"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "This test requires a local Postgres with `wal_level = logical`, at RECOCO_TEST_POSTGRES_URL."]
+    async fn test_replication_change_stream() -> Result<()> {
+        let url = std::env::var("RECOCO_TEST_POSTGRES_URL")
+            .unwrap_or_else(|_| "postgres://postgres@localhost/postgres".to_string());
+        let db_pool = PgPool::connect(&url).await?;
+        let table_name = "recoco_replication_test";
+        let slot_name = "recoco_replication_test";
+        sqlx::query(&format!("DROP TABLE IF EXISTS {table_name}"))
+            .execute(&db_pool)
+            .await?;
+        sqlx::query(&format!(
+            "CREATE TABLE {table_name} (id INTEGER PRIMARY KEY, body TEXT)"
+        ))
+        .execute(&db_pool)
+        .await?;
+        // A table of the same name in another schema, whose changes must not be captured.
+        for stmt in [
+            "CREATE SCHEMA IF NOT EXISTS recoco_other".to_string(),
+            format!("DROP TABLE IF EXISTS recoco_other.{table_name}"),
+            format!("CREATE TABLE recoco_other.{table_name} (id INTEGER PRIMARY KEY, body TEXT)"),
+        ] {
+            sqlx::query(&stmt).execute(&db_pool).await?;
+        }
+        sqlx::query(
+            "SELECT pg_drop_replication_slot(slot_name) FROM pg_replication_slots WHERE slot_name = $1",
+        )
+        .bind(slot_name)
+        .execute(&db_pool)
+        .await?;
+        sqlx::query(&format!("DROP PUBLICATION IF EXISTS {slot_name}_pub"))
+            .execute(&db_pool)
+            .await?;
+
+        assert!(
+            resolve_table_namespace(&db_pool, &format!("public.{table_name}"))
+                .await
+                .is_err()
+        );
+        let namespace = resolve_table_namespace(&db_pool, table_name).await?;
+        assert_eq!(namespace, "public");
+        let mut replication_ctx = ReplicationContext {
+            namespace: namespace.clone(),
+            slot_name: slot_name.to_string(),
+            plugin: ReplicationPlugin::PgOutput,
+            publication_name: format!("{slot_name}_pub"),
+            poll_interval: std::time::Duration::from_millis(100),
+            listing_ordinal: Ordinal(Some(0)),
+            ack_tracker: Default::default(),
+        };
+        let slot_lsn = setup_replication(&db_pool, table_name, &replication_ctx)
+            .await?
+            .unwrap();
+        replication_ctx.listing_ordinal = Ordinal(Some(slot_lsn.to_ordinal()?));
+        let executor = PostgresSourceExecutor::new(
+            db_pool.clone(),
+            table_name.to_string(),
+            fetch_table_schema(&db_pool, &namespace, table_name, &None, &None).await?,
+            None,
+            None,
+            Some(replication_ctx),
+        );
+
+        for stmt in [
+            format!("INSERT INTO recoco_other.{table_name} VALUES (100, 'x')"),
+            format!("INSERT INTO {table_name} VALUES (1, 'a'), (2, 'b')"),
+            format!("UPDATE {table_name} SET id = 3 WHERE id = 2"),
+            format!("DELETE FROM {table_name} WHERE id = 1"),
+        ] {
+            sqlx::query(&stmt).execute(&db_pool).await?;
+        }
+
+        let mut stream = executor.change_stream().await?.unwrap();
+        let mut changes = Vec::new();
+        let mut ack_fns = Vec::new();
+        while changes.len() < 5 {
+            let message = stream.next().await.unwrap()?;
+            changes.extend(message.changes.into_iter().map(|change| {
+                let exists = !matches!(change.data.value, Some(SourceValue::NonExistence));
+                (change.key, exists, change.data.ordinal.unwrap())
+            }));
+            ack_fns.extend(message.ack_fn);
+        }
+        let keys = changes
+            .iter()
+            .map(|(key, exists, _)| (key.to_string(), *exists))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                ("{1}".to_string(), true),
+                ("{2}".to_string(), true),
+                ("{2}".to_string(), false),
+                ("{3}".to_string(), true),
+                ("{1}".to_string(), false),
+            ]
+        );
+        assert!(changes.windows(2).all(|w| w[0].2.0 <= w[1].2.0));
+
+        for ack_fn in ack_fns {
+            ack_fn().await?;
+        }
+        let (confirmed,): (String,) = sqlx::query_as(
+            "SELECT confirmed_flush_lsn::text FROM pg_replication_slots WHERE slot_name = $1",
+        )
+        .bind(slot_name)
+        .fetch_one(&db_pool)
+        .await?;
+        let last_ordinal = changes.last().unwrap().2.0.unwrap();
+        assert_eq!(confirmed.parse::<Lsn>()?.to_ordinal()?, last_ordinal);
+
+        // Listed rows keep the slot's starting LSN, so they never supersede captured changes,
+        // while fetched values carry the current one.
+        let options = SourceExecutorReadOptions {
+            include_ordinal: true,
+            ..Default::default()
+        };
+        let rows = executor
+            .list(&options)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[0].data.ordinal.unwrap().0.unwrap(),
+            slot_lsn.to_ordinal()?
+        );
+        assert!(slot_lsn.to_ordinal()? < last_ordinal);
+        let data = executor
+            .get_value(&rows[0].key, &serde_json::Value::Null, &options)
+            .await?;
+        assert!(data.ordinal.unwrap().0.unwrap() >= last_ordinal);
+
+        std::mem::drop(stream);
+        sqlx::query("SELECT pg_drop_replication_slot($1)")
+            .bind(slot_name)
+            .execute(&db_pool)
+            .await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,488 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoding of logical replication output (`pgoutput` and `wal2json`) for the Postgres source.
+//!
+//! Changes are read through the SQL-level logical decoding functions, grouped by transaction.
+//! Transactions come out in commit order, so the LSN of the commit record is used as the
+//! ordinal of all its changes, and confirming it acknowledges the whole transaction.
+
+use crate::ops::sdk::*;
+
+use std::collections::BTreeSet;
+
+/// A write-ahead log position.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lsn(pub u64);
+
+impl std::str::FromStr for Lsn {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (hi, lo) = s
+            .split_once('/')
+            .ok_or_else(|| client_error!("Invalid LSN: {s}"))?;
+        let hi = u32::from_str_radix(hi, 16).map_err(|_| client_error!("Invalid LSN: {s}"))?;
+        let lo = u32::from_str_radix(lo, 16).map_err(|_| client_error!("Invalid LSN: {s}"))?;
+        Ok(Lsn(((hi as u64) << 32) | lo as u64))
+    }
+}
+
+impl std::fmt::Display for Lsn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:X}/{:X}", self.0 >> 32, self.0 & 0xFFFF_FFFF)
+    }
+}
+
+impl Lsn {
+    pub fn to_ordinal(self) -> Result<i64> {
+        i64::try_from(self.0).map_err(|_| internal_error!("LSN {self} is out of ordinal range"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValue {
+    Null,
+    /// A TOASTed value not included in the change.
+    Unchanged,
+    /// A value in Postgres' text representation.
+    Text(String),
+    /// A value already converted to JSON by the output plugin.
+    Json(serde_json::Value),
+}
+
+pub type ColumnValues = HashMap<String, ColumnValue>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedMessage {
+    Begin,
+    Commit,
+    Upsert {
+        schema: String,
+        table: String,
+        /// Columns of the old key, if the update may have changed it.
+        old_key: Option<ColumnValues>,
+        new: ColumnValues,
+    },
+    Delete {
+        schema: String,
+        table: String,
+        old_key: ColumnValues,
+    },
+    /// Messages irrelevant to row changes, e.g. relation metadata or truncation.
+    Other,
+}
+
+impl DecodedMessage {
+    /// The schema and name of the table a row change applies to.
+    pub fn relation(&self) -> Option<(&str, &str)> {
+        match self {
+            DecodedMessage::Upsert { schema, table, .. }
+            | DecodedMessage::Delete { schema, table, .. } => Some((schema, table)),
+            _ => None,
+        }
+    }
+}
+
+struct Relation {
+    namespace: String,
+    name: String,
+    columns: Vec<String>,
+}
+
+/// Decodes messages of the `pgoutput` plugin (protocol version 1).
+#[derive(Default)]
+pub struct PgOutputDecoder {
+    relations: HashMap<u32, Relation>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.data.len() < n {
+            client_bail!("Truncated pgoutput message");
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn cstr(&mut self) -> Result<&'a str> {
+        let len = self
+            .data
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| client_error!("Unterminated string in pgoutput message"))?;
+        let s = std::str::from_utf8(self.take(len)?)
+            .map_err(|_| client_error!("Invalid UTF-8 string in pgoutput message"))?;
+        self.take(1)?;
+        Ok(s)
+    }
+}
+
+impl PgOutputDecoder {
+    pub fn decode(&mut self, data: &[u8]) -> Result<DecodedMessage> {
+        let mut reader = Reader { data };
+        let message = match reader.u8()? {
+            b'B' => DecodedMessage::Begin,
+            b'C' => DecodedMessage::Commit,
+            b'R' => {
+                let relation_id = reader.u32()?;
+                let namespace = reader.cstr()?.to_string();
+                let name = reader.cstr()?.to_string();
+                let _replica_identity = reader.u8()?;
+                let num_columns = reader.u16()?;
+                let mut columns = Vec::with_capacity(num_columns as usize);
+                for _ in 0..num_columns {
+                    let _flags = reader.u8()?;
+                    columns.push(reader.cstr()?.to_string());
+                    let _type_id = reader.u32()?;
+                    let _type_modifier = reader.u32()?;
+                }
+                self.relations.insert(
+                    relation_id,
+                    Relation {
+                        namespace,
+                        name,
+                        columns,
+                    },
+                );
+                DecodedMessage::Other
+            }
+            b'I' => {
+                let relation = self.relation(reader.u32()?)?;
+                if reader.u8()? != b'N' {
+                    client_bail!("Expected new tuple in pgoutput insert message");
+                }
+                DecodedMessage::Upsert {
+                    schema: relation.namespace.clone(),
+                    table: relation.name.clone(),
+                    old_key: None,
+                    new: Self::read_tuple(&mut reader, relation)?,
+                }
+            }
+            b'U' => {
+                let relation = self.relation(reader.u32()?)?;
+                let mut old_key = None;
+                let mut tuple_type = reader.u8()?;
+                if tuple_type == b'K' || tuple_type == b'O' {
+                    old_key = Some(Self::read_tuple(&mut reader, relation)?);
+                    tuple_type = reader.u8()?;
+                }
+                if tuple_type != b'N' {
+                    client_bail!("Expected new tuple in pgoutput update message");
+                }
+                DecodedMessage::Upsert {
+                    schema: relation.namespace.clone(),
+                    table: relation.name.clone(),
+                    old_key,
+                    new: Self::read_tuple(&mut reader, relation)?,
+                }
+            }
+            b'D' => {
+                let relation = self.relation(reader.u32()?)?;
+                let tuple_type = reader.u8()?;
+                if tuple_type != b'K' && tuple_type != b'O' {
+                    client_bail!("Expected old tuple in pgoutput delete message");
+                }
+                DecodedMessage::Delete {
+                    schema: relation.namespace.clone(),
+                    table: relation.name.clone(),
+                    old_key: Self::read_tuple(&mut reader, relation)?,
+                }
+            }
+            _ => DecodedMessage::Other,
+        };
+        Ok(message)
+    }
+
+    fn relation(&self, relation_id: u32) -> Result<&Relation> {
+        self.relations
+            .get(&relation_id)
+            .ok_or_else(|| client_error!("Unknown relation {relation_id} in pgoutput message"))
+    }
+
+    fn read_tuple(reader: &mut Reader<'_>, relation: &Relation) -> Result<ColumnValues> {
+        let num_columns = reader.u16()? as usize;
+        if num_columns > relation.columns.len() {
+            client_bail!(
+                "Tuple has {num_columns} columns but relation `{}` has {}",
+                relation.name,
+                relation.columns.len()
+            );
+        }
+        let mut values = ColumnValues::with_capacity(num_columns);
+        for name in relation.columns.iter().take(num_columns) {
+            let value = match reader.u8()? {
+                b'n' => ColumnValue::Null,
+                b'u' => ColumnValue::Unchanged,
+                b't' => {
+                    let len = reader.u32()? as usize;
+                    let text = std::str::from_utf8(reader.take(len)?)
+                        .map_err(|_| client_error!("Invalid UTF-8 value in pgoutput message"))?;
+                    ColumnValue::Text(text.to_string())
+                }
+                kind => client_bail!("Unsupported pgoutput tuple value kind: {}", kind as char),
+            };
+            values.insert(name.clone(), value);
+        }
+        Ok(values)
+    }
+}
+
+/// Decodes a message of the `wal2json` plugin in format version 2.
+pub fn decode_wal2json(data: &str) -> Result<DecodedMessage> {
+    #[derive(Deserialize)]
+    struct Column {
+        name: String,
+        value: serde_json::Value,
+    }
+
+    #[derive(Deserialize)]
+    struct Message {
+        action: String,
+        #[serde(default)]
+        schema: String,
+        #[serde(default)]
+        table: String,
+        #[serde(default)]
+        columns: Vec<Column>,
+        #[serde(default)]
+        identity: Vec<Column>,
+    }
+
+    let to_values = |columns: Vec<Column>| -> ColumnValues {
+        columns
+            .into_iter()
+            .map(|c| (c.name, ColumnValue::Json(c.value)))
+            .collect()
+    };
+    let message: Message = utils::deser::from_json_str(data)?;
+    let decoded = match message.action.as_str() {
+        "B" => DecodedMessage::Begin,
+        "C" => DecodedMessage::Commit,
+        "I" | "U" => DecodedMessage::Upsert {
+            schema: message.schema,
+            table: message.table,
+            old_key: (!message.identity.is_empty()).then(|| to_values(message.identity)),
+            new: to_values(message.columns),
+        },
+        "D" => DecodedMessage::Delete {
+            schema: message.schema,
+            table: message.table,
+            old_key: to_values(message.identity),
+        },
+        _ => DecodedMessage::Other,
+    };
+    Ok(decoded)
+}
+
+/// Tracks emitted transactions until they're acknowledged, so the replication slot is only
+/// confirmed up to a position before which everything has been acknowledged.
+#[derive(Default)]
+pub struct LsnAckTracker {
+    pending: BTreeSet<Lsn>,
+    acked: BTreeSet<Lsn>,
+}
+
+impl LsnAckTracker {
+    pub fn emit(&mut self, lsn: Lsn) {
+        self.pending.insert(lsn);
+    }
+
+    /// Marks the transaction committed at `lsn` as acknowledged.
+    /// Returns the latest LSN that's now safe to confirm, if any.
+    pub fn ack(&mut self, lsn: Lsn) -> Option<Lsn> {
+        self.pending.remove(&lsn);
+        self.acked.insert(lsn);
+        let confirmable = match self.pending.first() {
+            Some(first_pending) => self.acked.range(..first_pending).next_back().copied(),
+            None => self.acked.last().copied(),
+        }?;
+        self.acked = self.acked.split_off(&Lsn(confirmable.0 + 1));
+        Some(confirmable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lsn_round_trip() {
+        let lsn: Lsn = "16/B374D848".parse().unwrap();
+        assert_eq!(lsn, Lsn(0x16_B374_D848));
+        assert_eq!(lsn.to_string(), "16/B374D848");
+        assert!("16B374D848".parse::<Lsn>().is_err());
+    }
+
+    fn tuple(values: &[Option<&str>]) -> Vec<u8> {
+        let mut data = (values.len() as u16).to_be_bytes().to_vec();
+        for value in values {
+            match value {
+                Some(v) => {
+                    data.push(b't');
+                    data.extend((v.len() as u32).to_be_bytes());
+                    data.extend(v.as_bytes());
+                }
+                None => data.push(b'n'),
+            }
+        }
+        data
+    }
+
+    fn relation(relation_id: u32, namespace: &str, name: &str, columns: &[&str]) -> Vec<u8> {
+        let mut data = vec![b'R'];
+        data.extend(relation_id.to_be_bytes());
+        for s in [namespace, name] {
+            data.extend(s.as_bytes());
+            data.push(0);
+        }
+        data.push(b'd');
+        data.extend((columns.len() as u16).to_be_bytes());
+        for column in columns {
+            data.push(1);
+            data.extend(column.as_bytes());
+            data.push(0);
+            data.extend(25u32.to_be_bytes());
+            data.extend(u32::MAX.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_pgoutput_decoder() {
+        let mut decoder = PgOutputDecoder::default();
+
+        let relation = relation(42, "public", "items", &["id", "body"]);
+        assert_eq!(decoder.decode(&relation).unwrap(), DecodedMessage::Other);
+
+        let mut update = vec![b'U'];
+        update.extend(42u32.to_be_bytes());
+        update.push(b'K');
+        update.extend(tuple(&[Some("1"), None]));
+        update.push(b'N');
+        update.extend(tuple(&[Some("2"), Some("hello")]));
+        assert_eq!(
+            decoder.decode(&update).unwrap(),
+            DecodedMessage::Upsert {
+                schema: "public".to_string(),
+                table: "items".to_string(),
+                old_key: Some(HashMap::from([
+                    ("id".to_string(), ColumnValue::Text("1".to_string())),
+                    ("body".to_string(), ColumnValue::Null),
+                ])),
+                new: HashMap::from([
+                    ("id".to_string(), ColumnValue::Text("2".to_string())),
+                    ("body".to_string(), ColumnValue::Text("hello".to_string())),
+                ]),
+            }
+        );
+
+        let mut delete = vec![b'D'];
+        delete.extend(7u32.to_be_bytes());
+        delete.push(b'K');
+        delete.extend(tuple(&[Some("2")]));
+        assert!(decoder.decode(&delete).is_err());
+    }
+
+    #[test]
+    fn test_same_table_name_in_two_schemas() {
+        let mut decoder = PgOutputDecoder::default();
+        for (relation_id, namespace) in [(1u32, "public"), (2, "archive")] {
+            decoder
+                .decode(&relation(relation_id, namespace, "items", &["id"]))
+                .unwrap();
+        }
+        let relations = [1u32, 2]
+            .map(|relation_id| {
+                let mut insert = vec![b'I'];
+                insert.extend(relation_id.to_be_bytes());
+                insert.push(b'N');
+                insert.extend(tuple(&[Some("1")]));
+                decoder.decode(&insert).unwrap()
+            })
+            .map(|message| {
+                message
+                    .relation()
+                    .map(|(s, t)| (s.to_string(), t.to_string()))
+            });
+        assert_eq!(
+            relations,
+            [
+                Some(("public".to_string(), "items".to_string())),
+                Some(("archive".to_string(), "items".to_string())),
+            ]
+        );
+
+        let messages = ["public", "archive"].map(|schema| {
+            decode_wal2json(&format!(
+                r#"{{"action":"I","schema":"{schema}","table":"items","columns":[{{"name":"id","type":"integer","value":1}}]}}"#
+            ))
+            .unwrap()
+        });
+        assert_eq!(messages[0].relation(), Some(("public", "items")));
+        assert_eq!(messages[1].relation(), Some(("archive", "items")));
+    }
+
+    #[test]
+    fn test_wal2json_decoder() {
+        let message = decode_wal2json(
+            r#"{"action":"D","schema":"public","table":"items","identity":[{"name":"id","type":"integer","value":3}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            message,
+            DecodedMessage::Delete {
+                schema: "public".to_string(),
+                table: "items".to_string(),
+                old_key: HashMap::from([(
+                    "id".to_string(),
+                    ColumnValue::Json(serde_json::json!(3))
+                )]),
+            }
+        );
+        assert_eq!(
+            decode_wal2json(r#"{"action":"C"}"#).unwrap(),
+            DecodedMessage::Commit
+        );
+    }
+
+    #[test]
+    fn test_lsn_ack_tracker() {
+        let mut tracker = LsnAckTracker::default();
+        for lsn in [10, 20, 30] {
+            tracker.emit(Lsn(lsn));
+        }
+        // Out-of-order acks can't move past the unacknowledged transaction.
+        assert_eq!(tracker.ack(Lsn(20)), None);
+        assert_eq!(tracker.ack(Lsn(10)), Some(Lsn(20)));
+        assert_eq!(tracker.ack(Lsn(30)), Some(Lsn(30)));
+        tracker.emit(Lsn(40));
+        assert_eq!(tracker.ack(Lsn(40)), Some(Lsn(40)));
+    }
+}
@@ -0,0 +1,423 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+
+use crate::ops::shared::sql_table::{
+    SqlColumnInfo, SqlTableDriver, SqlTableReader, SqlTableSchema, SqlValueDecoder,
+    push_key_conditions,
+};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
+use sqlx::{Row, Sqlite, SqlitePool};
+
+type SqliteValueDecoder = SqlValueDecoder<SqliteRow>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Spec {
+    /// Table name to read from (required)
+    table_name: String,
+    /// Path to the database file (required), opened read-only
+    path: String,
+    /// Optional: columns to include (if None, includes all columns)
+    included_columns: Option<Vec<String>>,
+    /// Optional: ordinal column for tracking changes
+    ordinal_column: Option<String>,
+    /// Optional: WHERE clause filter for rows (arbitrary SQL boolean expression)
+    filter: Option<String>,
+}
+
+struct SqliteTableDriver {
+    db_pool: SqlitePool,
+}
+
+fn bind_key_field<'arg>(
+    builder: &mut sqlx::QueryBuilder<'arg, Sqlite>,
+    key_value: &'arg KeyPart,
+) -> Result<()> {
+    match key_value {
+        KeyPart::Bytes(v) => {
+            builder.push_bind(&**v);
+        }
+        KeyPart::Str(v) => {
+            builder.push_bind(&**v);
+        }
+        KeyPart::Bool(v) => {
+            builder.push_bind(v);
+        }
+        KeyPart::Int64(v) => {
+            builder.push_bind(v);
+        }
+        KeyPart::Date(v) => {
+            builder.push_bind(v);
+        }
+        KeyPart::Uuid(_) | KeyPart::Range(_) | KeyPart::Struct(_) => {
+            internal_bail!("Unsupported key type for SQLite: {key_value}");
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl SqlTableDriver for SqliteTableDriver {
+    type Row = SqliteRow;
+
+    fn quote_identifier(name: &str) -> String {
+        format!("\"{name}\"")
+    }
+
+    fn fetch_rows(&self, query: String) -> BoxStream<'_, Result<SqliteRow>> {
+        try_stream! {
+            let mut rows = sqlx::query(&query).fetch(&self.db_pool);
+            while let Some(row) = rows.try_next().await? {
+                yield row;
+            }
+        }
+        .boxed()
+    }
+
+    async fn fetch_row_by_key(
+        &self,
+        query: String,
+        key_columns: &[String],
+        key: &KeyValue,
+        filter: Option<&str>,
+    ) -> Result<Option<SqliteRow>> {
+        let mut qb = sqlx::QueryBuilder::new(query);
+        push_key_conditions(&mut qb, key_columns, key, filter, bind_key_field)?;
+        Ok(qb.build().fetch_optional(&self.db_pool).await?)
+    }
+}
+
+struct SqliteSourceExecutor {
+    reader: SqlTableReader<SqliteTableDriver>,
+}
+
+#[async_trait]
+impl SourceExecutor for SqliteSourceExecutor {
+    async fn list(
+        &self,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<BoxStream<'async_trait, Result<Vec<PartialSourceRow>>>> {
+        self.reader.list(options).await
+    }
+
+    async fn get_value(
+        &self,
+        key: &KeyValue,
+        _key_aux_info: &serde_json::Value,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<PartialSourceRowData> {
+        self.reader.get_value(key, options).await
+    }
+
+    fn provides_ordinal(&self) -> bool {
+        self.reader.table_schema.provides_ordinal()
+    }
+}
+
+/// Map declared SQLite column types to CocoIndex BasicValueType and a decoder function.
+/// SQLite columns accept values of any type, so the declared type is interpreted the way
+/// SQLite determines column affinity, with date and time types recognized on top.
+fn map_sqlite_type_to_cocoindex_and_decoder(
+    declared_type: &str,
+) -> Option<(BasicValueType, SqliteValueDecoder)> {
+    let t = declared_type.to_uppercase();
+    let result = match t.as_str() {
+        "BOOLEAN" | "BOOL" => (
+            BasicValueType::Bool,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<bool>, _>(idx)?)))
+                as SqliteValueDecoder,
+        ),
+        "DATE" => (
+            BasicValueType::Date,
+            (|row, idx| {
+                Ok(Value::from(
+                    row.try_get::<Option<chrono::NaiveDate>, _>(idx)?,
+                ))
+            }) as SqliteValueDecoder,
+        ),
+        "TIME" => (
+            BasicValueType::Time,
+            (|row, idx| {
+                Ok(Value::from(
+                    row.try_get::<Option<chrono::NaiveTime>, _>(idx)?,
+                ))
+            }) as SqliteValueDecoder,
+        ),
+        "DATETIME" | "TIMESTAMP" => (
+            BasicValueType::LocalDateTime,
+            (|row, idx| {
+                Ok(Value::from(
+                    row.try_get::<Option<chrono::NaiveDateTime>, _>(idx)?,
+                ))
+            }) as SqliteValueDecoder,
+        ),
+        "JSON" | "JSONB" => (
+            BasicValueType::Json,
+            (|row, idx| {
+                Ok(Value::from(
+                    row.try_get::<Option<serde_json::Value>, _>(idx)?,
+                ))
+            }) as SqliteValueDecoder,
+        ),
+        t if t.contains("INT") => (
+            BasicValueType::Int64,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<i64>, _>(idx)?))) as SqliteValueDecoder,
+        ),
+        t if t.contains("CHAR") || t.contains("CLOB") || t.contains("TEXT") => (
+            BasicValueType::Str,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<String>, _>(idx)?)))
+                as SqliteValueDecoder,
+        ),
+        t if t.contains("BLOB") => (
+            BasicValueType::Bytes,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<Vec<u8>>, _>(idx)?)))
+                as SqliteValueDecoder,
+        ),
+        t if t.contains("REAL") || t.contains("FLOA") || t.contains("DOUB") => (
+            BasicValueType::Float64,
+            (|row, idx| Ok(Value::from(row.try_get::<Option<f64>, _>(idx)?))) as SqliteValueDecoder,
+        ),
+        // Skip others, including columns without a declared type
+        _ => {
+            warn!("Skipping unsupported SQLite type: {declared_type}");
+            return None;
+        }
+    };
+    Some(result)
+}
+
+async fn get_db_pool(path: &str) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .connect_with(options)
+        .await
+        .map_err(Error::from)
+        .with_context(|| format!("Failed to open SQLite database {path}"))?;
+    Ok(pool)
+}
+
+/// Fetch table schema information from SQLite
+async fn fetch_table_schema(
+    pool: &SqlitePool,
+    table_name: &str,
+    included_columns: &Option<Vec<String>>,
+    ordinal_column: &Option<String>,
+) -> Result<SqlTableSchema<SqliteRow>> {
+    let query = r#"SELECT name, type, "notnull", pk FROM pragma_table_info(?) ORDER BY cid"#;
+    let rows = sqlx::query(query).bind(table_name).fetch_all(pool).await?;
+
+    let mut columns = Vec::with_capacity(rows.len());
+    for row in rows {
+        let col_name: String = row.try_get("name")?;
+        let declared_type: String = row.try_get("type")?;
+        let not_null: bool = row.try_get("notnull")?;
+        let pk_position: i64 = row.try_get("pk")?;
+
+        let Some((value_type, decoder)) = map_sqlite_type_to_cocoindex_and_decoder(&declared_type)
+        else {
+            continue;
+        };
+        columns.push(SqlColumnInfo {
+            name: col_name,
+            value_type,
+            decoder,
+            is_nullable: !not_null,
+            is_primary_key: pk_position > 0,
+        });
+    }
+
+    SqlTableSchema::new(table_name, columns, included_columns, ordinal_column)
+}
+
+pub struct Factory;
+
+#[async_trait]
+impl SourceFactoryBase for Factory {
+    type Spec = Spec;
+
+    fn name(&self) -> &str {
+        "Sqlite"
+    }
+
+    async fn get_output_schema(
+        &self,
+        spec: &Spec,
+        _context: &FlowInstanceContext,
+    ) -> Result<EnrichedValueType> {
+        let db_pool = get_db_pool(&spec.path).await?;
+        let table_schema = fetch_table_schema(
+            &db_pool,
+            &spec.table_name,
+            &spec.included_columns,
+            &spec.ordinal_column,
+        )
+        .await?;
+        Ok(table_schema.output_type())
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        _source_name: &str,
+        spec: Spec,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<Box<dyn SourceExecutor>> {
+        let db_pool = get_db_pool(&spec.path).await?;
+        let table_schema = fetch_table_schema(
+            &db_pool,
+            &spec.table_name,
+            &spec.included_columns,
+            &spec.ordinal_column,
+        )
+        .await?;
+
+        Ok(Box::new(SqliteSourceExecutor {
+            reader: SqlTableReader {
+                driver: SqliteTableDriver { db_pool },
+                table_name: spec.table_name,
+                table_schema,
+                filter: spec.filter,
+            },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_executor(
+        ordinal_column: Option<String>,
+        filter: Option<String>,
+    ) -> Result<SqliteSourceExecutor> {
+        // A single connection, so all queries see the same in-memory database.
+        let db_pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await?;
+        for stmt in [
+            "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL, data BLOB, updated_at DATETIME, extra)",
+            "INSERT INTO items VALUES (1, 'a', x'0102', '2025-01-02 03:04:05', 1), (2, 'b', NULL, '2025-01-03 00:00:00', 2)",
+        ] {
+            sqlx::query(stmt).execute(&db_pool).await?;
+        }
+        let table_schema = fetch_table_schema(&db_pool, "items", &None, &ordinal_column).await?;
+        Ok(SqliteSourceExecutor {
+            reader: SqlTableReader {
+                driver: SqliteTableDriver { db_pool },
+                table_name: "items".to_string(),
+                table_schema,
+                filter,
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn test_list_and_get_value() -> Result<()> {
+        let executor = test_executor(Some("updated_at".to_string()), None).await?;
+        let schema = executor.reader.table_schema.output_type();
+        let ValueType::Table(table_schema) = &schema.typ else {
+            panic!("Expected a table schema");
+        };
+        let field_names = table_schema
+            .row
+            .fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>();
+        // The column without declared type is skipped.
+        assert_eq!(field_names, ["id", "name", "data", "updated_at"]);
+        assert!(executor.provides_ordinal());
+
+        let options = SourceExecutorReadOptions {
+            include_ordinal: true,
+            include_value: true,
+            ..Default::default()
+        };
+        let rows = executor
+            .list(&options)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].key, KeyValue::from_single_part(1i64));
+        let Some(SourceValue::Existence(values)) = &rows[0].data.value else {
+            panic!("Expected an existing row");
+        };
+        assert_eq!(
+            values.fields[..2],
+            [
+                Value::from("a".to_string()),
+                Value::Basic(BasicValue::Bytes(bytes::Bytes::from_static(&[1, 2]))),
+            ]
+        );
+        let expected_ordinal = chrono::NaiveDate::from_ymd_opt(2025, 1, 2)
+            .unwrap()
+            .and_hms_opt(3, 4, 5)
+            .unwrap()
+            .and_utc()
+            .timestamp_micros();
+        assert_eq!(rows[0].data.ordinal, Some(Ordinal(Some(expected_ordinal))));
+
+        let data = executor
+            .get_value(
+                &KeyValue::from_single_part(2i64),
+                &serde_json::Value::Null,
+                &options,
+            )
+            .await?;
+        let Some(SourceValue::Existence(values)) = &data.value else {
+            panic!("Expected an existing row");
+        };
+        assert_eq!(values.fields[0], Value::from("b".to_string()));
+        assert_eq!(values.fields[1], Value::Null);
+
+        let data = executor
+            .get_value(
+                &KeyValue::from_single_part(3i64),
+                &serde_json::Value::Null,
+                &options,
+            )
+            .await?;
+        assert!(matches!(data.value, Some(SourceValue::NonExistence)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_filter() -> Result<()> {
+        let executor = test_executor(None, Some("name = 'b'".to_string())).await?;
+        let options = SourceExecutorReadOptions {
+            include_value: true,
+            ..Default::default()
+        };
+        let rows = executor
+            .list(&options)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0].key, KeyValue::from_single_part(2i64));
+
+        let data = executor
+            .get_value(
+                &KeyValue::from_single_part(1i64),
+                &serde_json::Value::Null,
+                &options,
+            )
+            .await?;
+        assert!(matches!(data.value, Some(SourceValue::NonExistence)));
+        Ok(())
+    }
+}
@@ -0,0 +1,980 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use async_stream::try_stream;
+use quick_xml::events::Event;
+use reqwest::header;
+use scraper::{Html, Selector};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::base::field_attrs;
+use crate::{fields_value, ops::sdk::*};
+use recoco_splitters::pattern_matcher::PatternMatcher;
+use recoco_utils::fingerprint::Fingerprinter;
+
+const DEFAULT_USER_AGENT: &str = "recoco";
+const DEFAULT_REQUEST_DELAY: Duration = Duration::from_secs(1);
+/// Maximum nesting of sitemap indexes.
+const MAX_SITEMAP_DEPTH: usize = 3;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Spec {
+    /// Pages to start crawling from.
+    seed_urls: Option<Vec<String>>,
+    /// Sitemaps or sitemap indexes listing pages to crawl.
+    sitemap_urls: Option<Vec<String>>,
+    /// Glob patterns matched against the `host/path` of URLs, e.g. `example.com/docs/**`.
+    /// Without `included_patterns`, only pages on the hosts of seeds and sitemaps are crawled.
+    included_patterns: Option<Vec<String>>,
+    excluded_patterns: Option<Vec<String>>,
+    /// Maximum number of links to follow from seeds and sitemap entries. Unlimited if not set.
+    max_depth: Option<usize>,
+    /// Maximum number of pages to crawl.
+    max_pages: Option<usize>,
+    /// Minimum delay between two requests to the same host. Defaults to 1 second.
+    /// A longer `Crawl-delay` in robots.txt takes precedence.
+    request_delay: Option<Duration>,
+    /// Whether to honor robots.txt. Defaults to true.
+    respect_robots_txt: Option<bool>,
+    user_agent: Option<String>,
+}
+
+/// Rules from a robots.txt file that apply to our user agent.
+#[derive(Debug, Default)]
+struct RobotsRules {
+    /// Path patterns, with whether they're allowed.
+    rules: Vec<(String, bool)>,
+    crawl_delay: Option<Duration>,
+}
+
+struct RobotsGroup {
+    user_agents: Vec<String>,
+    rules: Vec<(String, bool)>,
+    crawl_delay: Option<Duration>,
+}
+
+impl RobotsRules {
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![("/".to_string(), false)],
+            crawl_delay: None,
+        }
+    }
+
+    /// Parse robots.txt and keep the group matching `user_agent` most specifically, falling back
+    /// to the `*` group.
+    fn parse(content: &str, user_agent: &str) -> Self {
+        let mut groups: Vec<RobotsGroup> = Vec::new();
+        let mut in_rules = false;
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules || groups.is_empty() {
+                        groups.push(RobotsGroup {
+                            user_agents: Vec::new(),
+                            rules: Vec::new(),
+                            crawl_delay: None,
+                        });
+                        in_rules = false;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.user_agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                directive @ ("allow" | "disallow" | "crawl-delay") => {
+                    in_rules = true;
+                    let Some(group) = groups.last_mut() else {
+                        continue;
+                    };
+                    match directive {
+                        "crawl-delay" => {
+                            group.crawl_delay = value
+                                .parse::<f64>()
+                                .ok()
+                                .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                                .map(Duration::from_secs_f64);
+                        }
+                        // An empty `Disallow` allows everything, same as no rule.
+                        _ if value.is_empty() => {}
+                        _ => group.rules.push((value.to_string(), directive == "allow")),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let product = user_agent
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let group = groups
+            .iter()
+            .filter_map(|group| {
+                group
+                    .user_agents
+                    .iter()
+                    .filter(|agent| *agent != "*" && product.contains(agent.as_str()))
+                    .map(|agent| agent.len())
+                    .max()
+                    .map(|len| (len, group))
+            })
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, group)| group)
+            .or_else(|| {
+                groups
+                    .iter()
+                    .find(|group| group.user_agents.iter().any(|agent| agent == "*"))
+            });
+        match group {
+            Some(group) => Self {
+                rules: group.rules.clone(),
+                crawl_delay: group.crawl_delay,
+            },
+            None => Self::default(),
+        }
+    }
+
+    /// The longest matching pattern decides, and `Allow` wins ties.
+    fn is_allowed(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(pattern, _)| robots_pattern_matches(pattern, path))
+            .max_by_key(|(pattern, allow)| (pattern.len(), *allow))
+            .is_none_or(|(_, allow)| *allow)
+    }
+}
+
+/// Match a robots.txt path pattern, supporting `*` wildcards and a trailing `$` anchor.
+fn robots_pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+/// Canonical form of a URL used as row key: only http(s) URLs, without fragment and empty query.
+/// Scheme and host case, default ports and dot segments are normalized when parsing.
+fn canonicalize_url(url: &Url) -> Option<Url> {
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return None;
+    }
+    let mut url = url.clone();
+    url.set_fragment(None);
+    if url.query() == Some("") {
+        url.set_query(None);
+    }
+    Some(url)
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    let parsed = Url::parse(url).map_err(|e| client_error!("Invalid URL `{url}`: {e}"))?;
+    canonicalize_url(&parsed).ok_or_else(|| client_error!("Unsupported URL `{url}`"))
+}
+
+fn host_key(url: &Url) -> String {
+    match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    }
+}
+
+fn path_and_query(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    }
+}
+
+fn is_html(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    mime.eq_ignore_ascii_case("text/html") || mime.eq_ignore_ascii_case("application/xhtml+xml")
+}
+
+static LINK_SELECTOR: LazyLock<Selector> = LazyLock::new(|| Selector::parse("a[href]").unwrap());
+static BASE_SELECTOR: LazyLock<Selector> = LazyLock::new(|| Selector::parse("base[href]").unwrap());
+
+/// Extract links of an HTML page as canonical URLs.
+fn extract_links(html: &str, page_url: &Url) -> Vec<Url> {
+    let document = Html::parse_document(html);
+    let base_url = document
+        .select(&BASE_SELECTOR)
+        .next()
+        .and_then(|base| base.value().attr("href"))
+        .and_then(|href| page_url.join(href).ok())
+        .unwrap_or_else(|| page_url.clone());
+    document
+        .select(&LINK_SELECTOR)
+        .filter(|link| {
+            !link
+                .value()
+                .attr("rel")
+                .is_some_and(|rel| rel.split_whitespace().any(|r| r == "nofollow"))
+        })
+        .filter_map(|link| link.value().attr("href"))
+        .filter_map(|href| base_url.join(href.trim()).ok())
+        .filter_map(|url| canonicalize_url(&url))
+        .collect()
+}
+
+/// Extract `<loc>` entries of a sitemap. Returns whether it's a sitemap index, along with them.
+fn parse_sitemap(content: &str) -> Result<(bool, Vec<String>)> {
+    let mut reader = quick_xml::Reader::from_str(content);
+    reader.trim_text(true);
+    let mut is_index = false;
+    let mut in_loc = false;
+    let mut locs = Vec::new();
+    loop {
+        match reader.read_event().map_err(Error::internal)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"sitemapindex" => is_index = true,
+                b"loc" => in_loc = true,
+                _ => {}
+            },
+            Event::End(e) if e.local_name().as_ref() == b"loc" => in_loc = false,
+            Event::Text(text) if in_loc => {
+                locs.push(text.unescape().map_err(Error::internal)?.trim().to_string());
+            }
+            Event::CData(data) if in_loc => {
+                locs.push(
+                    String::from_utf8_lossy(&data.into_inner())
+                        .trim()
+                        .to_string(),
+                );
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok((is_index, locs))
+}
+
+/// What we know about a page from its last fetch, to send conditional requests.
+#[derive(Debug, Clone)]
+struct CachedPage {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    ordinal: Ordinal,
+    content_version_fp: Vec<u8>,
+    links: Vec<Url>,
+}
+
+struct FetchedPage {
+    url: Url,
+    /// `None` if the page wasn't modified since the cached version.
+    content: Option<(String, String)>,
+    cached: CachedPage,
+}
+
+struct Executor {
+    client: reqwest::Client,
+    user_agent: String,
+    seed_urls: Vec<Url>,
+    sitemap_urls: Vec<Url>,
+    /// Hosts of seeds and sitemaps, to restrict the crawl when no included pattern is set.
+    allowed_hosts: Option<HashSet<String>>,
+    pattern_matcher: PatternMatcher,
+    max_depth: Option<usize>,
+    max_pages: Option<usize>,
+    request_delay: Duration,
+    respect_robots_txt: bool,
+    /// Next time a request can be sent to each host.
+    next_request_times: Mutex<HashMap<String, Instant>>,
+    robots_rules: tokio::sync::Mutex<HashMap<String, Arc<RobotsRules>>>,
+    pages: Mutex<HashMap<String, CachedPage>>,
+}
+
+impl Executor {
+    fn is_in_scope(&self, url: &Url) -> bool {
+        if let Some(allowed_hosts) = &self.allowed_hosts
+            && !url
+                .host_str()
+                .is_some_and(|host| allowed_hosts.contains(host))
+        {
+            return false;
+        }
+        let path = format!("{}{}", url.host_str().unwrap_or_default(), url.path());
+        self.pattern_matcher.is_file_included(&path)
+    }
+
+    /// Wait for the politeness delay of the URL's host.
+    async fn throttle(&self, url: &Url, robots_rules: Option<&RobotsRules>) {
+        let delay = robots_rules
+            .and_then(|rules| rules.crawl_delay)
+            .map_or(self.request_delay, |delay| delay.max(self.request_delay));
+        let request_time = {
+            let mut next_request_times = self.next_request_times.lock().unwrap();
+            let now = Instant::now();
+            let next_time = next_request_times.entry(host_key(url)).or_insert(now);
+            let request_time = (*next_time).max(now);
+            *next_time = request_time + delay;
+            request_time
+        };
+        tokio::time::sleep_until(request_time).await;
+    }
+
+    async fn get_robots_rules(&self, url: &Url) -> Result<Option<Arc<RobotsRules>>> {
+        if !self.respect_robots_txt {
+            return Ok(None);
+        }
+        let origin = url.origin().ascii_serialization();
+        let mut robots_rules = self.robots_rules.lock().await;
+        if let Some(rules) = robots_rules.get(&origin) {
+            return Ok(Some(rules.clone()));
+        }
+        let robots_url = url.join("/robots.txt").map_err(Error::internal)?;
+        self.throttle(&robots_url, None).await;
+        let resp = http::request_optional(|| {
+            self.client
+                .get(robots_url.clone())
+                .header(header::USER_AGENT, &self.user_agent)
+        })
+        .await;
+        let rules = match resp {
+            Ok(Some(resp)) => RobotsRules::parse(&resp.text().await?, &self.user_agent),
+            Ok(None) => RobotsRules::default(),
+            Err(e) => {
+                warn!("Failed to fetch {robots_url}, not crawling {origin}: {e:?}");
+                RobotsRules::disallow_all()
+            }
+        };
+        let rules = Arc::new(rules);
+        robots_rules.insert(origin, rules.clone());
+        Ok(Some(rules))
+    }
+
+    /// Fetch a page, conditionally on its cached version if `conditional` is set.
+    /// Returns `None` if the page doesn't exist or isn't allowed by robots.txt.
+    async fn fetch_page(&self, url: &Url, conditional: bool) -> Result<Option<FetchedPage>> {
+        let robots_rules = self.get_robots_rules(url).await?;
+        if let Some(rules) = &robots_rules
+            && !rules.is_allowed(&path_and_query(url))
+        {
+            return Ok(None);
+        }
+        let cached = self.pages.lock().unwrap().get(url.as_str()).cloned();
+        self.throttle(url, robots_rules.as_deref()).await;
+        let resp = http::request_optional(|| {
+            let mut req = self
+                .client
+                .get(url.clone())
+                .header(header::USER_AGENT, &self.user_agent);
+            if conditional && let Some(cached) = &cached {
+                if let Some(etag) = &cached.etag {
+                    req = req.header(header::IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &cached.last_modified {
+                    req = req.header(header::IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+            req
+        })
+        .await?;
+        let Some(resp) = resp else {
+            self.pages.lock().unwrap().remove(url.as_str());
+            return Ok(None);
+        };
+
+        if resp.status() == reqwest::StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            return Ok(Some(FetchedPage {
+                url: url.clone(),
+                content: None,
+                cached,
+            }));
+        }
+
+        // Redirects are followed, so the page is keyed by the final URL.
+        let final_url = canonicalize_url(resp.url()).unwrap_or_else(|| url.clone());
+        let header_str = |name: header::HeaderName| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+        };
+        let etag = header_str(header::ETAG);
+        let last_modified = header_str(header::LAST_MODIFIED);
+        let content_type = header_str(header::CONTENT_TYPE).unwrap_or_default();
+        let bytes = resp.bytes().await?;
+        let (content, _) = utils::bytes_decode::bytes_to_string(&bytes);
+        let content = content.into_owned();
+
+        let content_version_fp = match (&etag, &last_modified) {
+            (Some(etag), _) => etag.as_bytes().to_vec(),
+            (None, Some(last_modified)) => last_modified.as_bytes().to_vec(),
+            (None, None) => {
+                let mut fp = Fingerprinter::default();
+                fp.write_raw_bytes(&bytes);
+                fp.into_fingerprint().as_slice().to_vec()
+            }
+        };
+        // Without `Last-Modified`, the ordinal is the time this version was first seen.
+        let prev = self.pages.lock().unwrap().get(final_url.as_str()).cloned();
+        let ordinal = last_modified
+            .as_deref()
+            .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())
+            .map(|time| Ordinal(Some(time.timestamp_micros())))
+            .or_else(|| {
+                prev.as_ref()
+                    .filter(|prev| prev.content_version_fp == content_version_fp)
+                    .map(|prev| prev.ordinal)
+            })
+            .unwrap_or_else(|| Ordinal(Some(chrono::Utc::now().timestamp_micros())));
+        let links = if is_html(&content_type) {
+            extract_links(&content, &final_url)
+        } else {
+            Vec::new()
+        };
+
+        let cached = CachedPage {
+            etag,
+            last_modified,
+            ordinal,
+            content_version_fp,
+            links,
+        };
+        self.pages
+            .lock()
+            .unwrap()
+            .insert(final_url.to_string(), cached.clone());
+        Ok(Some(FetchedPage {
+            url: final_url,
+            content: Some((content_type, content)),
+            cached,
+        }))
+    }
+
+    /// Collect page URLs from the sitemaps, following sitemap indexes.
+    async fn collect_sitemap_urls(&self) -> Vec<Url> {
+        let mut urls = Vec::new();
+        let mut sitemaps = self
+            .sitemap_urls
+            .iter()
+            .map(|url| (url.clone(), 0))
+            .collect::<VecDeque<_>>();
+        while let Some((sitemap_url, depth)) = sitemaps.pop_front() {
+            let result: Result<_> = async {
+                self.throttle(&sitemap_url, None).await;
+                let resp = http::request(|| {
+                    self.client
+                        .get(sitemap_url.clone())
+                        .header(header::USER_AGENT, &self.user_agent)
+                })
+                .await?;
+                parse_sitemap(&resp.text().await?)
+            }
+            .await;
+            let (is_index, locs) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("Failed to read sitemap {sitemap_url}: {e:?}");
+                    continue;
+                }
+            };
+            for loc in locs {
+                let Some(url) = Url::parse(&loc).ok().and_then(|url| canonicalize_url(&url)) else {
+                    warn!("Skipped invalid URL `{loc}` in sitemap {sitemap_url}");
+                    continue;
+                };
+                if !is_index {
+                    urls.push(url);
+                } else if depth < MAX_SITEMAP_DEPTH {
+                    sitemaps.push_back((url, depth + 1));
+                }
+            }
+        }
+        urls
+    }
+
+    fn make_row_data(
+        page: FetchedPage,
+        options: &SourceExecutorReadOptions,
+    ) -> (Url, PartialSourceRowData) {
+        let value = if options.include_value {
+            page.content.map(|(content_type, content)| {
+                SourceValue::Existence(fields_value!(content_type, content))
+            })
+        } else {
+            None
+        };
+        let data = PartialSourceRowData {
+            value,
+            ordinal: options.include_ordinal.then_some(page.cached.ordinal),
+            content_version_fp: options
+                .include_content_version_fp
+                .then_some(page.cached.content_version_fp),
+        };
+        (page.url, data)
+    }
+}
+
+#[async_trait]
+impl SourceExecutor for Executor {
+    async fn list(
+        &self,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<BoxStream<'async_trait, Result<Vec<PartialSourceRow>>>> {
+        // Pick up robots.txt changes on each crawl.
+        self.robots_rules.lock().await.clear();
+        let stream = try_stream! {
+            let mut queue = self
+                .seed_urls
+                .iter()
+                .cloned()
+                .chain(self.collect_sitemap_urls().await)
+                .map(|url| (url, 0))
+                .collect::<VecDeque<_>>();
+            let mut visited: HashSet<String> =
+                queue.iter().map(|(url, _)| url.to_string()).collect();
+            let mut num_pages = 0;
+            while let Some((url, depth)) = queue.pop_front() {
+                if self.max_pages.is_some_and(|max_pages| num_pages >= max_pages) {
+                    break;
+                }
+                let page = match self.fetch_page(&url, true).await {
+                    Ok(Some(page)) => page,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        warn!("Failed to crawl {url}: {e:?}");
+                        continue;
+                    }
+                };
+                if page.url != url && !visited.insert(page.url.to_string()) {
+                    continue;
+                }
+                if self.max_depth.is_none_or(|max_depth| depth < max_depth) {
+                    for link in page.cached.links.iter() {
+                        if self.is_in_scope(link) && visited.insert(link.to_string()) {
+                            queue.push_back((link.clone(), depth + 1));
+                        }
+                    }
+                }
+                num_pages += 1;
+                let (url, data) = Self::make_row_data(page, options);
+                yield vec![PartialSourceRow {
+                    key: KeyValue::from_single_part(url.to_string()),
+                    key_aux_info: serde_json::Value::Null,
+                    data,
+                }];
+            }
+        };
+        Ok(stream.boxed())
+    }
+
+    async fn get_value(
+        &self,
+        key: &KeyValue,
+        _key_aux_info: &serde_json::Value,
+        options: &SourceExecutorReadOptions,
+    ) -> Result<PartialSourceRowData> {
+        let url = parse_url(key.single_part()?.str_value()?)?;
+        let page = if self.is_in_scope(&url) {
+            self.fetch_page(&url, false).await?
+        } else {
+            None
+        };
+        // A redirect to another URL means the page doesn't exist under this key anymore.
+        let Some(page) = page.filter(|page| page.url == url) else {
+            return Ok(PartialSourceRowData {
+                value: Some(SourceValue::NonExistence),
+                ordinal: Some(Ordinal::unavailable()),
+                content_version_fp: None,
+            });
+        };
+        Ok(Self::make_row_data(page, options).1)
+    }
+
+    fn provides_ordinal(&self) -> bool {
+        true
+    }
+}
+
+pub struct Factory;
+
+#[async_trait]
+impl SourceFactoryBase for Factory {
+    type Spec = Spec;
+
+    fn name(&self) -> &str {
+        "WebCrawl"
+    }
+
+    async fn get_output_schema(
+        &self,
+        _spec: &Spec,
+        _context: &FlowInstanceContext,
+    ) -> Result<EnrichedValueType> {
+        let mut struct_schema = StructSchema::default();
+        let mut schema_builder = StructSchemaBuilder::new(&mut struct_schema);
+        schema_builder.add_field(FieldSchema::new(
+            "url",
+            make_output_type(BasicValueType::Str),
+        ));
+        let content_type_field = schema_builder.add_field(FieldSchema::new(
+            "content_type",
+            make_output_type(BasicValueType::Str),
+        ));
+        schema_builder.add_field(FieldSchema::new(
+            "content",
+            make_output_type(BasicValueType::Str).with_attr(
+                field_attrs::CONTENT_MIME_TYPE,
+                serde_json::to_value(content_type_field.to_field_ref())?,
+            ),
+        ));
+        Ok(make_output_type(TableSchema::new(
+            TableKind::KTable(KTableInfo { num_key_parts: 1 }),
+            struct_schema,
+        )))
+    }
+
+    async fn build_executor(
+        self: Arc<Self>,
+        _source_name: &str,
+        spec: Spec,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<Box<dyn SourceExecutor>> {
+        Ok(Box::new(Executor::new(spec)?))
+    }
+}
+
+impl Executor {
+    fn new(spec: Spec) -> Result<Self> {
+        let seed_urls = spec
+            .seed_urls
+            .unwrap_or_default()
+            .iter()
+            .map(|url| parse_url(url))
+            .collect::<Result<Vec<_>>>()?;
+        let sitemap_urls = spec
+            .sitemap_urls
+            .unwrap_or_default()
+            .iter()
+            .map(|url| parse_url(url))
+            .collect::<Result<Vec<_>>>()?;
+        if seed_urls.is_empty() && sitemap_urls.is_empty() {
+            client_bail!("At least one of `seed_urls` and `sitemap_urls` must be provided");
+        }
+        let allowed_hosts = spec.included_patterns.is_none().then(|| {
+            seed_urls
+                .iter()
+                .chain(sitemap_urls.iter())
+                .filter_map(|url| url.host_str().map(|host| host.to_string()))
+                .collect()
+        });
+        Ok(Self {
+            client: reqwest::Client::new(),
+            user_agent: spec
+                .user_agent
+                .unwrap_or_else(|| DEFAULT_USER_AGENT.to_string()),
+            seed_urls,
+            sitemap_urls,
+            allowed_hosts,
+            pattern_matcher: PatternMatcher::new(spec.included_patterns, spec.excluded_patterns)?,
+            max_depth: spec.max_depth,
+            max_pages: spec.max_pages,
+            request_delay: spec.request_delay.unwrap_or(DEFAULT_REQUEST_DELAY),
+            respect_robots_txt: spec.respect_robots_txt.unwrap_or(true),
+            next_request_times: Mutex::new(HashMap::new()),
+            robots_rules: tokio::sync::Mutex::new(HashMap::new()),
+            pages: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::IntoResponse;
+    use axum::{Router, routing::get};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_robots_rules() {
+        let content = "\
+# Comment
+User-agent: *
+Disallow: /private
+Allow: /private/public
+Disallow: /*.pdf$
+
+User-agent: recoco
+User-agent: other
+Disallow: /no-recoco
+Crawl-delay: 2
+";
+        let rules = RobotsRules::parse(content, "recoco/0.2");
+        assert!(!rules.is_allowed("/no-recoco/page"));
+        assert!(rules.is_allowed("/private"));
+        assert_eq!(rules.crawl_delay, Some(Duration::from_secs(2)));
+
+        let rules = RobotsRules::parse(content, "SomeBot");
+        assert!(rules.is_allowed("/"));
+        assert!(rules.is_allowed("/no-recoco"));
+        assert!(!rules.is_allowed("/private/page"));
+        assert!(rules.is_allowed("/private/public/page"));
+        assert!(!rules.is_allowed("/docs/file.pdf"));
+        assert!(rules.is_allowed("/docs/file.pdf?download=1"));
+        assert_eq!(rules.crawl_delay, None);
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let page_url = Url::parse("https://Example.com:443/docs/index.html").unwrap();
+        let html = r#"<html><body>
+            <a href="intro.html#section">Intro</a>
+            <a href="../about?">About</a>
+            <a href="https://other.org/x">Other</a>
+            <a href="mailto:a@example.com">Mail</a>
+            <a href="/login" rel="nofollow">Login</a>
+        </body></html>"#;
+        let links = extract_links(html, &page_url)
+            .into_iter()
+            .map(|url| url.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            [
+                "https://example.com/docs/intro.html",
+                "https://example.com/about",
+                "https://other.org/x",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_sitemap() -> Result<()> {
+        let sitemap = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc>https://example.com/a?x=1&amp;y=2</loc><lastmod>2025-01-01</lastmod></url>
+              <url><loc><![CDATA[https://example.com/b]]></loc></url>
+            </urlset>"#;
+        assert_eq!(
+            parse_sitemap(sitemap)?,
+            (
+                false,
+                vec![
+                    "https://example.com/a?x=1&y=2".to_string(),
+                    "https://example.com/b".to_string()
+                ]
+            )
+        );
+        let index = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <sitemap><loc>https://example.com/sitemap-1.xml</loc></sitemap>
+            </sitemapindex>"#;
+        assert_eq!(
+            parse_sitemap(index)?,
+            (true, vec!["https://example.com/sitemap-1.xml".to_string()])
+        );
+        Ok(())
+    }
+
+    const HTML: &str = "text/html; charset=utf-8";
+
+    async fn serve_test_site(etag_hits: Arc<AtomicUsize>) -> Result<Url> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let base_url = Url::parse(&format!("http://{}/", listener.local_addr()?)).unwrap();
+        let sitemap = format!("<urlset><url><loc>{base_url}c</loc></url></urlset>");
+        let app = Router::new()
+            .route(
+                "/robots.txt",
+                get(|| async { "User-agent: *\nDisallow: /private\n" }),
+            )
+            .route(
+                "/",
+                get(|| async {
+                    (
+                        [("content-type", HTML)],
+                        r#"<a href="/a">A</a> <a href="/b#top">B</a>
+                        <a href="/private/x">X</a> <a href="/missing">M</a>
+                        <a href="http://other.invalid/">Other</a>"#,
+                    )
+                }),
+            )
+            .route(
+                "/a",
+                get(move |headers: HeaderMap| async move {
+                    if headers
+                        .get("if-none-match")
+                        .is_some_and(|v| v.as_bytes() == b"\"v1\"")
+                    {
+                        etag_hits.fetch_add(1, Ordering::SeqCst);
+                        return StatusCode::NOT_MODIFIED.into_response();
+                    }
+                    (
+                        [("content-type", HTML), ("etag", "\"v1\"")],
+                        r#"<a href="/deep">Deep</a>"#,
+                    )
+                        .into_response()
+                }),
+            )
+            .route(
+                "/b",
+                get(|| async {
+                    (
+                        [
+                            ("content-type", "text/plain"),
+                            ("last-modified", "Wed, 01 Jan 2025 00:00:00 GMT"),
+                        ],
+                        "plain text",
+                    )
+                }),
+            )
+            .route("/c", get(|| async { ([("content-type", HTML)], "c") }))
+            .route(
+                "/deep",
+                get(|| async { ([("content-type", HTML)], "deep") }),
+            )
+            .route("/private/x", get(|| async { "private" }))
+            .route("/sitemap.xml", get(move || async move { sitemap }));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok(base_url)
+    }
+
+    fn test_executor(base_url: &Url, max_depth: Option<usize>) -> Result<Executor> {
+        Executor::new(Spec {
+            seed_urls: Some(vec![base_url.to_string()]),
+            sitemap_urls: Some(vec![base_url.join("sitemap.xml").unwrap().to_string()]),
+            included_patterns: None,
+            excluded_patterns: None,
+            max_depth,
+            max_pages: None,
+            request_delay: Some(Duration::ZERO),
+            respect_robots_txt: None,
+            user_agent: None,
+        })
+    }
+
+    async fn crawl(executor: &Executor) -> Result<Vec<(String, PartialSourceRowData)>> {
+        let options = SourceExecutorReadOptions {
+            include_ordinal: true,
+            include_content_version_fp: true,
+            include_value: true,
+        };
+        let rows = executor
+            .list(&options)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .flatten()
+            .map(|row| {
+                let url = row
+                    .key
+                    .single_part()
+                    .unwrap()
+                    .str_value()
+                    .unwrap()
+                    .to_string();
+                (url, row.data)
+            })
+            .collect();
+        Ok(rows)
+    }
+
+    #[tokio::test]
+    async fn test_crawl_with_conditional_requests() -> Result<()> {
+        let etag_hits = Arc::new(AtomicUsize::new(0));
+        let base_url = serve_test_site(etag_hits.clone()).await?;
+        let url = |path: &str| base_url.join(path).unwrap().to_string();
+
+        let executor = test_executor(&base_url, Some(1))?;
+        let rows = crawl(&executor).await?;
+        assert_eq!(
+            rows.iter().map(|(url, _)| url.clone()).collect::<Vec<_>>(),
+            [url("/"), url("/c"), url("/a"), url("/b")]
+        );
+        let (_, b_data) = &rows[3];
+        assert_eq!(
+            b_data.ordinal,
+            Some(Ordinal(Some(
+                chrono::DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+                    .unwrap()
+                    .timestamp_micros()
+            )))
+        );
+        let Some(SourceValue::Existence(values)) = &b_data.value else {
+            panic!("Expected a value");
+        };
+        assert_eq!(
+            values.fields,
+            [
+                Value::from("text/plain".to_string()),
+                Value::from("plain text".to_string())
+            ]
+        );
+        assert_eq!(etag_hits.load(Ordering::SeqCst), 0);
+
+        // The second crawl revalidates `/a` with its ETag and still follows its cached links.
+        let executor = Executor {
+            max_depth: Some(2),
+            ..executor
+        };
+        let rows = crawl(&executor).await?;
+        assert_eq!(etag_hits.load(Ordering::SeqCst), 1);
+        let (_, a_data) = rows.iter().find(|(u, _)| *u == url("/a")).unwrap();
+        assert!(a_data.value.is_none());
+        assert_eq!(a_data.content_version_fp.as_deref(), Some(&b"\"v1\""[..]));
+        assert!(rows.iter().any(|(u, _)| *u == url("/deep")));
+
+        let options = SourceExecutorReadOptions {
+            include_value: true,
+            ..Default::default()
+        };
+        for (path, exists) in [("/a", true), ("/missing", false), ("/private/x", false)] {
+            let data = executor
+                .get_value(
+                    &KeyValue::from_single_part(url(path)),
+                    &serde_json::Value::Null,
+                    &options,
+                )
+                .await?;
+            assert_eq!(
+                matches!(data.value, Some(SourceValue::Existence(_))),
+                exists,
+                "{path}"
+            );
+        }
+        Ok(())
+    }
+}
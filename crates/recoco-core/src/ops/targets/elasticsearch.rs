@@ -0,0 +1,1029 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+use crate::prelude::*;
+
+use crate::ops::registry::ExecutorFactoryRegistry;
+use crate::setup;
+use reqwest::Method;
+use serde_json::json;
+use std::fmt::Write;
+
+const DEFAULT_URL: &str = "http://localhost:9200";
+const DEFAULT_VECTOR_SIMILARITY_METRIC: spec::VectorSimilarityMetric =
+    spec::VectorSimilarityMetric::CosineSimilarity;
+/// Maximum number of actions sent in a single bulk request.
+const MAX_BULK_ACTIONS: usize = 1000;
+/// Strings not indexed for full-text search are indexed as keywords up to this length.
+const KEYWORD_IGNORE_ABOVE: usize = 1024;
+
+////////////////////////////////////////////////////////////
+// Public Types
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum SearchEngine {
+    #[default]
+    Elasticsearch,
+    OpenSearch,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectionSpec {
+    /// The URL of the cluster, e.g. `http://localhost:9200`.
+    url: String,
+    /// Which engine runs at the URL. Vector fields are mapped differently by each.
+    #[serde(default)]
+    engine: SearchEngine,
+    api_key: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Spec {
+    connection: Option<spec::AuthEntryReference<ConnectionSpec>>,
+    index_name: String,
+}
+
+////////////////////////////////////////////////////////////
+// Common
+////////////////////////////////////////////////////////////
+
+struct SearchClient {
+    reqwest_client: reqwest::Client,
+    conn_spec: ConnectionSpec,
+}
+
+impl SearchClient {
+    fn request(&self, method: Method, path: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}/{path}", self.conn_spec.url.trim_end_matches('/'));
+        let req = self.reqwest_client.request(method, url);
+        if let Some(api_key) = &self.conn_spec.api_key {
+            req.header(reqwest::header::AUTHORIZATION, format!("ApiKey {api_key}"))
+        } else if let Some(username) = &self.conn_spec.username {
+            req.basic_auth(username, self.conn_spec.password.as_ref())
+        } else {
+            req
+        }
+    }
+
+    async fn bulk(&self, bulk: BulkBody) -> Result<()> {
+        if bulk.num_actions == 0 {
+            return Ok(());
+        }
+        let resp = http::request(|| {
+            self.request(Method::POST, "_bulk")
+                .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+                .body(bulk.body.clone())
+        })
+        .await
+        .with_context(|| "Search engine bulk API error")?;
+        let resp: BulkResponse = resp.json().await?;
+        if resp.errors {
+            let failures = resp
+                .items
+                .iter()
+                .flat_map(|item| item.iter())
+                .filter_map(|(action, result)| result.error.as_ref().map(|error| (action, error)))
+                .collect::<Vec<_>>();
+            if let Some((action, error)) = failures.first() {
+                client_bail!(
+                    "{} of {} bulk actions failed, e.g. `{action}`: {error}",
+                    failures.len(),
+                    bulk.num_actions
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Body of a bulk request, in NDJSON.
+#[derive(Default)]
+struct BulkBody {
+    body: String,
+    num_actions: usize,
+}
+
+impl BulkBody {
+    fn push(
+        &mut self,
+        action: &serde_json::Value,
+        source: Option<&serde_json::Value>,
+    ) -> Result<()> {
+        writeln!(&mut self.body, "{action}")?;
+        if let Some(source) = source {
+            writeln!(&mut self.body, "{source}")?;
+        }
+        self.num_actions += 1;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct BulkResponse {
+    errors: bool,
+    #[serde(default)]
+    items: Vec<HashMap<String, BulkItemResult>>,
+}
+
+#[derive(Deserialize)]
+struct BulkItemResult {
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+fn disabled_object_mapping() -> serde_json::Value {
+    // Kept in `_source` without being indexed.
+    json!({ "type": "object", "enabled": false })
+}
+
+fn basic_type_mapping(typ: &BasicValueType) -> serde_json::Value {
+    match typ {
+        BasicValueType::Bytes => json!({ "type": "binary" }),
+        BasicValueType::Str => json!({ "type": "keyword", "ignore_above": KEYWORD_IGNORE_ABOVE }),
+        BasicValueType::Bool => json!({ "type": "boolean" }),
+        BasicValueType::Int64 => json!({ "type": "long" }),
+        BasicValueType::Float32 => json!({ "type": "float" }),
+        BasicValueType::Float64 => json!({ "type": "double" }),
+        BasicValueType::Uuid | BasicValueType::Time | BasicValueType::TimeDelta => {
+            json!({ "type": "keyword" })
+        }
+        BasicValueType::Date | BasicValueType::LocalDateTime | BasicValueType::OffsetDateTime => {
+            json!({ "type": "date" })
+        }
+        // Arrays of numbers need no special mapping.
+        BasicValueType::Vector(vector_schema)
+            if matches!(
+                *vector_schema.element_type,
+                BasicValueType::Int64 | BasicValueType::Float32 | BasicValueType::Float64
+            ) =>
+        {
+            basic_type_mapping(&vector_schema.element_type)
+        }
+        BasicValueType::Range
+        | BasicValueType::Json
+        | BasicValueType::Vector(_)
+        | BasicValueType::SparseVector
+        | BasicValueType::Union(_) => disabled_object_mapping(),
+    }
+}
+
+fn value_type_mapping(typ: &ValueType) -> serde_json::Value {
+    match typ {
+        ValueType::Basic(basic_type) => basic_type_mapping(basic_type),
+        ValueType::Struct(struct_schema) => json!({
+            "type": "object",
+            "properties": struct_schema
+                .fields
+                .iter()
+                .map(|field| (field.name.clone(), value_type_mapping(&field.value_type.typ)))
+                .collect::<serde_json::Map<_, _>>(),
+        }),
+        ValueType::Table(_) => disabled_object_mapping(),
+    }
+}
+
+/// Dimension of a field that can be indexed as a dense vector.
+fn dense_vector_dimension(typ: &ValueType) -> Option<usize> {
+    match typ {
+        ValueType::Basic(BasicValueType::Vector(vector_schema)) => {
+            match *vector_schema.element_type {
+                BasicValueType::Float32 | BasicValueType::Float64 | BasicValueType::Int64 => {
+                    vector_schema.dimension
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+struct DenseVectorDef<'a> {
+    dimension: usize,
+    metric: spec::VectorSimilarityMetric,
+    method: Option<&'a spec::VectorIndexMethod>,
+}
+
+impl DenseVectorDef<'_> {
+    fn mapping(&self, engine: SearchEngine, field_name: &str) -> Result<serde_json::Value> {
+        let (m, ef_construction) = match self.method {
+            None => (None, None),
+            Some(spec::VectorIndexMethod::Hnsw { m, ef_construction }) => (*m, *ef_construction),
+            Some(spec::VectorIndexMethod::IvfFlat { .. }) => {
+                api_bail!(
+                    "IVFFlat vector index method is not supported for field `{field_name}`. Only HNSW is supported."
+                );
+            }
+        };
+        let mut hnsw_parameters = serde_json::Map::new();
+        if let Some(m) = m {
+            hnsw_parameters.insert("m".to_string(), json!(m));
+        }
+        if let Some(ef_construction) = ef_construction {
+            hnsw_parameters.insert("ef_construction".to_string(), json!(ef_construction));
+        }
+        Ok(match engine {
+            SearchEngine::Elasticsearch => {
+                let mut mapping = json!({
+                    "type": "dense_vector",
+                    "dims": self.dimension,
+                    "index": true,
+                    "similarity": match self.metric {
+                        spec::VectorSimilarityMetric::CosineSimilarity => "cosine",
+                        spec::VectorSimilarityMetric::L2Distance => "l2_norm",
+                        spec::VectorSimilarityMetric::InnerProduct => "max_inner_product",
+                    },
+                });
+                if !hnsw_parameters.is_empty() {
+                    hnsw_parameters.insert("type".to_string(), json!("hnsw"));
+                    mapping["index_options"] = serde_json::Value::Object(hnsw_parameters);
+                }
+                mapping
+            }
+            SearchEngine::OpenSearch => {
+                let mut method = json!({
+                    "name": "hnsw",
+                    "engine": "lucene",
+                    "space_type": match self.metric {
+                        spec::VectorSimilarityMetric::CosineSimilarity => "cosinesimil",
+                        spec::VectorSimilarityMetric::L2Distance => "l2",
+                        spec::VectorSimilarityMetric::InnerProduct => "innerproduct",
+                    },
+                });
+                if !hnsw_parameters.is_empty() {
+                    method["parameters"] = serde_json::Value::Object(hnsw_parameters);
+                }
+                json!({
+                    "type": "knn_vector",
+                    "dimension": self.dimension,
+                    "method": method,
+                })
+            }
+        })
+    }
+}
+
+fn key_to_doc_id(key: &KeyValue) -> Result<String> {
+    Ok(match serde_json::to_value(key)? {
+        serde_json::Value::String(s) => s,
+        v => v.to_string(),
+    })
+}
+
+////////////////////////////////////////////////////////////
+// Setup
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct IndexKey {
+    connection: Option<spec::AuthEntryReference<ConnectionSpec>>,
+    index_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct SetupState {
+    /// Settings of the index, e.g. `index.knn` for OpenSearch.
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    settings: serde_json::Map<String, serde_json::Value>,
+    /// Mapping of each field, i.e. `properties` of the index mapping.
+    properties: BTreeMap<String, serde_json::Value>,
+}
+
+impl SetupState {
+    fn new(
+        engine: SearchEngine,
+        key_fields_schema: &[FieldSchema],
+        value_fields_schema: &[FieldSchema],
+        index_options: &IndexOptions,
+    ) -> Result<Self> {
+        let mut settings = serde_json::Map::new();
+        let mut properties = BTreeMap::new();
+        for field in key_fields_schema.iter() {
+            let mapping = match &field.value_type.typ {
+                ValueType::Basic(BasicValueType::Str) => json!({ "type": "keyword" }),
+                typ => value_type_mapping(typ),
+            };
+            properties.insert(field.name.clone(), mapping);
+        }
+
+        let mut dense_vectors = BTreeMap::new();
+        for field in value_fields_schema.iter() {
+            if let Some(dimension) = dense_vector_dimension(&field.value_type.typ) {
+                dense_vectors.insert(
+                    field.name.as_str(),
+                    DenseVectorDef {
+                        dimension,
+                        metric: DEFAULT_VECTOR_SIMILARITY_METRIC,
+                        method: None,
+                    },
+                );
+            } else {
+                properties.insert(
+                    field.name.clone(),
+                    value_type_mapping(&field.value_type.typ),
+                );
+            }
+        }
+
+        let mut specified_vector_fields = HashSet::new();
+        for vector_index in index_options.vector_indexes.iter() {
+            let Some(dense_vector) = dense_vectors.get_mut(vector_index.field_name.as_str()) else {
+                match value_fields_schema
+                    .iter()
+                    .find(|f| f.name == vector_index.field_name)
+                {
+                    Some(field) => api_bail!(
+                        "Field `{}` specified in vector index is expected to be a number vector with fixed size, actual type: {}",
+                        vector_index.field_name,
+                        field.value_type.typ
+                    ),
+                    None => api_bail!(
+                        "Field `{}` specified in vector index is not found",
+                        vector_index.field_name
+                    ),
+                }
+            };
+            if !specified_vector_fields.insert(vector_index.field_name.as_str()) {
+                api_bail!(
+                    "Field `{}` specified more than once in vector index definition",
+                    vector_index.field_name
+                );
+            }
+            dense_vector.metric = vector_index.metric;
+            dense_vector.method = vector_index.method.as_ref();
+        }
+        for (field_name, dense_vector) in dense_vectors.iter() {
+            properties.insert(
+                field_name.to_string(),
+                dense_vector.mapping(engine, field_name)?,
+            );
+        }
+        if engine == SearchEngine::OpenSearch && !dense_vectors.is_empty() {
+            settings.insert("index.knn".to_string(), json!(true));
+        }
+
+        let mut specified_fts_fields = HashSet::new();
+        for fts_index in index_options.fts_indexes.iter() {
+            match value_fields_schema
+                .iter()
+                .find(|f| f.name == fts_index.field_name)
+            {
+                Some(field) => {
+                    if !matches!(field.value_type.typ, ValueType::Basic(BasicValueType::Str)) {
+                        api_bail!(
+                            "Field `{}` specified in FTS index is expected to be a string, actual type: {}",
+                            fts_index.field_name,
+                            field.value_type.typ
+                        );
+                    }
+                }
+                None => api_bail!(
+                    "Field `{}` specified in FTS index is not found",
+                    fts_index.field_name
+                ),
+            }
+            if !specified_fts_fields.insert(fts_index.field_name.as_str()) {
+                api_bail!(
+                    "Field `{}` specified more than once in FTS index definition",
+                    fts_index.field_name
+                );
+            }
+            // Parameters, e.g. `analyzer` and `search_analyzer`, go to the field mapping.
+            let mut mapping = serde_json::Map::new();
+            mapping.insert("type".to_string(), json!("text"));
+            if let Some(parameters) = &fts_index.parameters {
+                mapping.extend(parameters.clone());
+            }
+            properties.insert(
+                fts_index.field_name.clone(),
+                serde_json::Value::Object(mapping),
+            );
+        }
+
+        Ok(Self {
+            settings,
+            properties,
+        })
+    }
+}
+
+fn describe_properties<'a>(
+    properties: impl Iterator<Item = (&'a String, &'a serde_json::Value)>,
+) -> String {
+    properties
+        .map(
+            |(name, mapping)| match mapping.get("type").and_then(|t| t.as_str()) {
+                Some(typ) => format!("{name} ({typ})"),
+                None => name.clone(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Debug)]
+struct SetupChange {
+    delete_index: bool,
+    create_index: Option<SetupState>,
+    /// Fields added to the mapping of the existing index.
+    add_properties: BTreeMap<String, serde_json::Value>,
+}
+
+impl SetupChange {
+    fn from_states(
+        desired: Option<SetupState>,
+        existing: &setup::CombinedState<SetupState>,
+    ) -> Self {
+        // New fields can be added to the mapping of an existing index in place.
+        // Any other change needs the index to be recreated.
+        let extendable = desired.as_ref().is_some_and(|desired| {
+            existing.always_exists_and(|v| {
+                v.settings == desired.settings
+                    && v.properties
+                        .iter()
+                        .all(|(name, mapping)| desired.properties.get(name) == Some(mapping))
+            })
+        });
+        if extendable {
+            let add_properties = desired
+                .into_iter()
+                .flat_map(|desired| desired.properties.into_iter())
+                .filter(|(name, _)| {
+                    existing
+                        .possible_versions()
+                        .any(|v| !v.properties.contains_key(name))
+                })
+                .collect();
+            return Self {
+                delete_index: false,
+                create_index: None,
+                add_properties,
+            };
+        }
+        let delete_index = existing.possible_versions().next().is_some();
+        Self {
+            delete_index,
+            create_index: desired,
+            add_properties: BTreeMap::new(),
+        }
+    }
+
+    async fn apply(&self, index_name: &str, client: &SearchClient) -> Result<()> {
+        if self.delete_index {
+            http::request_optional(|| client.request(Method::DELETE, index_name))
+                .await
+                .with_context(|| format!("Deleting index `{index_name}`"))?;
+        }
+        if let Some(create_index) = &self.create_index {
+            let body = json!({
+                "settings": create_index.settings,
+                "mappings": { "properties": create_index.properties },
+            });
+            http::request(|| client.request(Method::PUT, index_name).json(&body))
+                .await
+                .with_context(|| format!("Creating index `{index_name}`"))?;
+        }
+        if !self.add_properties.is_empty() {
+            let body = json!({ "properties": self.add_properties });
+            http::request(|| {
+                client
+                    .request(Method::PUT, &format!("{index_name}/_mapping"))
+                    .json(&body)
+            })
+            .await
+            .with_context(|| format!("Updating mapping of index `{index_name}`"))?;
+        }
+        Ok(())
+    }
+}
+
+impl setup::ResourceSetupChange for SetupChange {
+    fn describe_changes(&self) -> Vec<setup::ChangeDescription> {
+        let mut result = vec![];
+        if self.delete_index {
+            result.push(setup::ChangeDescription::Action("Delete index".to_string()));
+        }
+        if let Some(create_index) = &self.create_index {
+            result.push(setup::ChangeDescription::Action(format!(
+                "Create index with fields: {}",
+                describe_properties(create_index.properties.iter())
+            )));
+        }
+        if !self.add_properties.is_empty() {
+            result.push(setup::ChangeDescription::Action(format!(
+                "Add fields to index mapping: {}",
+                describe_properties(self.add_properties.iter())
+            )));
+        }
+        result
+    }
+
+    fn change_type(&self) -> setup::SetupChangeType {
+        match (
+            self.delete_index,
+            self.create_index.is_some(),
+            !self.add_properties.is_empty(),
+        ) {
+            (false, false, false) => setup::SetupChangeType::NoChange,
+            (false, true, _) => setup::SetupChangeType::Create,
+            (true, false, _) => setup::SetupChangeType::Delete,
+            (true, true, _) | (false, false, true) => setup::SetupChangeType::Update,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Deal with mutations
+////////////////////////////////////////////////////////////
+
+struct ExportContext {
+    client: SearchClient,
+    index_name: String,
+    key_fields_schema: Box<[FieldSchema]>,
+    value_fields_schema: Vec<FieldSchema>,
+}
+
+impl ExportContext {
+    fn document(&self, key: &KeyValue, value: &FieldValues) -> Result<serde_json::Value> {
+        let mut doc = serde_json::Map::new();
+        for (key_part, field) in std::iter::zip(key.iter(), self.key_fields_schema.iter()) {
+            let v = Value::from(key_part.clone());
+            doc.insert(
+                field.name.clone(),
+                serde_json::to_value(TypedValue {
+                    t: &field.value_type.typ,
+                    v: &v,
+                })?,
+            );
+        }
+        for (v, field) in std::iter::zip(value.fields.iter(), self.value_fields_schema.iter()) {
+            doc.insert(
+                field.name.clone(),
+                serde_json::to_value(TypedValue {
+                    t: &field.value_type.typ,
+                    v,
+                })?,
+            );
+        }
+        Ok(serde_json::Value::Object(doc))
+    }
+
+    async fn apply_mutation(&self, mutation: ExportTargetMutation) -> Result<()> {
+        let mut bulk = BulkBody::default();
+        for upsert in mutation.upserts.iter() {
+            let action = json!({
+                "index": { "_index": self.index_name, "_id": key_to_doc_id(&upsert.key)? }
+            });
+            bulk.push(&action, Some(&self.document(&upsert.key, &upsert.value)?))?;
+            if bulk.num_actions >= MAX_BULK_ACTIONS {
+                self.client.bulk(std::mem::take(&mut bulk)).await?;
+            }
+        }
+        for delete in mutation.deletes.iter() {
+            let action = json!({
+                "delete": { "_index": self.index_name, "_id": key_to_doc_id(&delete.key)? }
+            });
+            bulk.push(&action, None)?;
+            if bulk.num_actions >= MAX_BULK_ACTIONS {
+                self.client.bulk(std::mem::take(&mut bulk)).await?;
+            }
+        }
+        self.client.bulk(bulk).await
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Factory implementation
+////////////////////////////////////////////////////////////
+
+struct Factory {
+    reqwest_client: reqwest::Client,
+}
+
+impl Factory {
+    fn get_client(
+        &self,
+        auth_entry: &Option<spec::AuthEntryReference<ConnectionSpec>>,
+        auth_registry: &AuthRegistry,
+    ) -> Result<SearchClient> {
+        let conn_spec = auth_entry.as_ref().map_or_else(
+            || {
+                Ok(ConnectionSpec {
+                    url: DEFAULT_URL.to_string(),
+                    engine: SearchEngine::default(),
+                    api_key: None,
+                    username: None,
+                    password: None,
+                })
+            },
+            |auth_entry| auth_registry.get(auth_entry),
+        )?;
+        Ok(SearchClient {
+            reqwest_client: self.reqwest_client.clone(),
+            conn_spec,
+        })
+    }
+}
+
+#[async_trait]
+impl TargetFactoryBase for Factory {
+    type Spec = Spec;
+    type DeclarationSpec = ();
+    type SetupState = SetupState;
+    type SetupChange = SetupChange;
+    type SetupKey = IndexKey;
+    type ExportContext = ExportContext;
+
+    fn name(&self) -> &str {
+        "Elasticsearch"
+    }
+
+    async fn build(
+        self: Arc<Self>,
+        data_collections: Vec<TypedExportDataCollectionSpec<Self>>,
+        _declarations: Vec<()>,
+        context: Arc<FlowInstanceContext>,
+    ) -> Result<(
+        Vec<TypedExportDataCollectionBuildOutput<Self>>,
+        Vec<(IndexKey, SetupState)>,
+    )> {
+        let data_coll_output = data_collections
+            .into_iter()
+            .map(|d| {
+                let client = self.get_client(&d.spec.connection, &context.auth_registry)?;
+                let desired_setup_state = SetupState::new(
+                    client.conn_spec.engine,
+                    &d.key_fields_schema,
+                    &d.value_fields_schema,
+                    &d.index_options,
+                )?;
+                let export_context = Arc::new(ExportContext {
+                    client,
+                    index_name: d.spec.index_name.clone(),
+                    key_fields_schema: d.key_fields_schema,
+                    value_fields_schema: d.value_fields_schema,
+                });
+                Ok(TypedExportDataCollectionBuildOutput {
+                    export_context: Box::pin(async move { Ok(export_context) }),
+                    setup_key: IndexKey {
+                        connection: d.spec.connection,
+                        index_name: d.spec.index_name,
+                    },
+                    desired_setup_state,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((data_coll_output, vec![]))
+    }
+
+    async fn diff_setup_states(
+        &self,
+        _key: IndexKey,
+        desired: Option<SetupState>,
+        existing: setup::CombinedState<SetupState>,
+        _flow_instance_ctx: Arc<FlowInstanceContext>,
+    ) -> Result<Self::SetupChange> {
+        Ok(SetupChange::from_states(desired, &existing))
+    }
+
+    fn check_state_compatibility(
+        &self,
+        desired: &SetupState,
+        existing: &SetupState,
+    ) -> Result<SetupStateCompatibility> {
+        Ok(
+            if desired.settings == existing.settings
+                && existing
+                    .properties
+                    .iter()
+                    .all(|(name, mapping)| desired.properties.get(name) == Some(mapping))
+            {
+                SetupStateCompatibility::Compatible
+            } else {
+                SetupStateCompatibility::NotCompatible
+            },
+        )
+    }
+
+    fn describe_resource(&self, key: &IndexKey) -> Result<String> {
+        Ok(format!(
+            "Search index {}{}",
+            key.index_name,
+            key.connection
+                .as_ref()
+                .map_or_else(|| "".to_string(), |auth_entry| format!(" @ {auth_entry}"))
+        ))
+    }
+
+    async fn apply_mutation(
+        &self,
+        mutations: Vec<ExportTargetMutationWithContext<'async_trait, ExportContext>>,
+    ) -> Result<()> {
+        for mutation_w_ctx in mutations.into_iter() {
+            mutation_w_ctx
+                .export_context
+                .apply_mutation(mutation_w_ctx.mutation)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn apply_setup_changes(
+        &self,
+        setup_change: Vec<TypedResourceSetupChangeItem<'async_trait, Self>>,
+        context: Arc<FlowInstanceContext>,
+    ) -> Result<()> {
+        for change in setup_change.iter() {
+            let client = self.get_client(&change.key.connection, &context.auth_registry)?;
+            change
+                .setup_change
+                .apply(&change.key.index_name, &client)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+pub fn register(
+    registry: &mut ExecutorFactoryRegistry,
+    reqwest_client: reqwest::Client,
+) -> Result<()> {
+    Factory { reqwest_client }.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector_type(dimension: usize) -> EnrichedValueType {
+        make_output_type(BasicValueType::Vector(VectorTypeSchema {
+            element_type: Box::new(BasicValueType::Float32),
+            dimension: Some(dimension),
+        }))
+    }
+
+    fn test_setup_state(
+        engine: SearchEngine,
+        value_fields_schema: &[FieldSchema],
+    ) -> Result<SetupState> {
+        SetupState::new(
+            engine,
+            &[FieldSchema::new(
+                "id",
+                make_output_type(BasicValueType::Str),
+            )],
+            value_fields_schema,
+            &IndexOptions {
+                primary_key_fields: Some(vec!["id".to_string()]),
+                vector_indexes: vec![spec::VectorIndexDef {
+                    field_name: "embedding".to_string(),
+                    metric: spec::VectorSimilarityMetric::L2Distance,
+                    method: Some(spec::VectorIndexMethod::Hnsw {
+                        m: Some(16),
+                        ef_construction: None,
+                    }),
+                }],
+                fts_indexes: vec![spec::FtsIndexDef {
+                    field_name: "text".to_string(),
+                    parameters: Some(serde_json::Map::from_iter([(
+                        "analyzer".to_string(),
+                        json!("english"),
+                    )])),
+                }],
+            },
+        )
+    }
+
+    #[test]
+    fn test_setup_state_mapping() -> Result<()> {
+        let value_fields_schema = [
+            FieldSchema::new("text", make_output_type(BasicValueType::Str)),
+            FieldSchema::new("filename", make_output_type(BasicValueType::Str)),
+            FieldSchema::new("embedding", vector_type(3)),
+        ];
+        let state = test_setup_state(SearchEngine::Elasticsearch, &value_fields_schema)?;
+        assert!(state.settings.is_empty());
+        assert_eq!(
+            serde_json::to_value(&state.properties)?,
+            json!({
+                "id": { "type": "keyword" },
+                "text": { "type": "text", "analyzer": "english" },
+                "filename": { "type": "keyword", "ignore_above": KEYWORD_IGNORE_ABOVE },
+                "embedding": {
+                    "type": "dense_vector",
+                    "dims": 3,
+                    "index": true,
+                    "similarity": "l2_norm",
+                    "index_options": { "type": "hnsw", "m": 16 },
+                },
+            })
+        );
+
+        let state = test_setup_state(SearchEngine::OpenSearch, &value_fields_schema)?;
+        assert_eq!(state.settings.get("index.knn"), Some(&json!(true)));
+        assert_eq!(
+            state.properties["embedding"],
+            json!({
+                "type": "knn_vector",
+                "dimension": 3,
+                "method": {
+                    "name": "hnsw",
+                    "engine": "lucene",
+                    "space_type": "l2",
+                    "parameters": { "m": 16 },
+                },
+            })
+        );
+
+        // FTS indexes only apply to strings.
+        let value_fields_schema = [
+            FieldSchema::new("text", make_output_type(BasicValueType::Int64)),
+            FieldSchema::new("embedding", vector_type(3)),
+        ];
+        assert!(test_setup_state(SearchEngine::Elasticsearch, &value_fields_schema).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_setup_change() -> Result<()> {
+        let value_fields_schema = vec![
+            FieldSchema::new("text", make_output_type(BasicValueType::Str)),
+            FieldSchema::new("embedding", vector_type(3)),
+        ];
+        let existing = test_setup_state(SearchEngine::Elasticsearch, &value_fields_schema)?;
+
+        // Added fields are added to the mapping in place.
+        let mut extended_fields_schema = value_fields_schema.clone();
+        extended_fields_schema.push(FieldSchema::new(
+            "page",
+            make_output_type(BasicValueType::Int64),
+        ));
+        let desired = test_setup_state(SearchEngine::Elasticsearch, &extended_fields_schema)?;
+        let change = SetupChange::from_states(
+            Some(desired),
+            &setup::CombinedState::current(existing.clone()),
+        );
+        assert!(!change.delete_index && change.create_index.is_none());
+        assert_eq!(
+            serde_json::to_value(&change.add_properties)?,
+            json!({ "page": { "type": "long" } })
+        );
+
+        // Changed fields need the index to be recreated.
+        let changed_fields_schema = vec![
+            FieldSchema::new("text", make_output_type(BasicValueType::Str)),
+            FieldSchema::new("embedding", vector_type(4)),
+        ];
+        let desired = test_setup_state(SearchEngine::Elasticsearch, &changed_fields_schema)?;
+        let change = SetupChange::from_states(
+            Some(desired),
+            &setup::CombinedState::current(existing.clone()),
+        );
+        assert!(change.delete_index && change.create_index.is_some());
+        assert!(change.add_properties.is_empty());
+
+        let change = SetupChange::from_states(
+            Some(existing.clone()),
+            &setup::CombinedState::current(existing),
+        );
+        assert_eq!(
+            setup::ResourceSetupChange::change_type(&change),
+            setup::SetupChangeType::NoChange
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "This test requires a local single-node Elasticsearch at RECOCO_TEST_ELASTICSEARCH_URL."]
+    async fn test_index_and_search() -> Result<()> {
+        let client = SearchClient {
+            reqwest_client: reqwest::Client::new(),
+            conn_spec: ConnectionSpec {
+                url: std::env::var("RECOCO_TEST_ELASTICSEARCH_URL")
+                    .unwrap_or_else(|_| DEFAULT_URL.to_string()),
+                engine: SearchEngine::Elasticsearch,
+                api_key: None,
+                username: None,
+                password: None,
+            },
+        };
+        let index_name = "recoco_elasticsearch_test";
+        let key_fields_schema = [FieldSchema::new(
+            "id",
+            make_output_type(BasicValueType::Str),
+        )];
+        let value_fields_schema = vec![
+            FieldSchema::new("text", make_output_type(BasicValueType::Str)),
+            FieldSchema::new("embedding", vector_type(3)),
+        ];
+        let desired = test_setup_state(SearchEngine::Elasticsearch, &value_fields_schema)?;
+        http::request_optional(|| client.request(Method::DELETE, index_name)).await?;
+        SetupChange::from_states(
+            Some(desired),
+            &setup::CombinedState {
+                current: None,
+                staging: vec![],
+                legacy_state_key: None,
+            },
+        )
+        .apply(index_name, &client)
+        .await?;
+
+        let export_context = ExportContext {
+            client,
+            index_name: index_name.to_string(),
+            key_fields_schema: key_fields_schema.into(),
+            value_fields_schema,
+        };
+        let upsert = |id: &str, text: &str, embedding: [f32; 3]| ExportTargetUpsertEntry {
+            key: KeyValue::from_single_part(id.to_string()),
+            additional_key: serde_json::Value::Null,
+            value: FieldValues {
+                fields: vec![
+                    Value::Basic(BasicValue::Str(text.into())),
+                    Value::Basic(BasicValue::Vector(
+                        embedding
+                            .into_iter()
+                            .map(BasicValue::Float32)
+                            .collect::<Vec<_>>()
+                            .into(),
+                    )),
+                ],
+            },
+        };
+        export_context
+            .apply_mutation(ExportTargetMutation {
+                upserts: vec![
+                    upsert("a", "The quick brown foxes", [1.0, 0.0, 0.0]),
+                    upsert("b", "A lazy dog sleeps", [0.0, 1.0, 0.0]),
+                    upsert("c", "Nothing to see", [0.0, 0.0, 1.0]),
+                ],
+                deletes: vec![],
+            })
+            .await?;
+        export_context
+            .apply_mutation(ExportTargetMutation {
+                upserts: vec![],
+                deletes: vec![ExportTargetDeleteEntry {
+                    key: KeyValue::from_single_part("c".to_string()),
+                    additional_key: serde_json::Value::Null,
+                }],
+            })
+            .await?;
+        let client = &export_context.client;
+        http::request(|| client.request(Method::POST, &format!("{index_name}/_refresh"))).await?;
+
+        let search = async |query: serde_json::Value| -> Result<Vec<String>> {
+            let resp: serde_json::Value = http::request(|| {
+                client
+                    .request(Method::POST, &format!("{index_name}/_search"))
+                    .json(&query)
+            })
+            .await?
+            .json()
+            .await?;
+            Ok(resp["hits"]["hits"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|hit| hit["_id"].as_str().map(str::to_string))
+                .collect())
+        };
+        // The English analyzer stems "fox" to match "foxes".
+        assert_eq!(
+            search(json!({ "query": { "match": { "text": "fox" } } })).await?,
+            vec!["a"]
+        );
+        assert_eq!(
+            search(json!({
+                "knn": {
+                    "field": "embedding",
+                    "query_vector": [0.1, 0.9, 0.0],
+                    "k": 1,
+                    "num_candidates": 10,
+                },
+            }))
+            .await?,
+            vec!["b"]
+        );
+        assert_eq!(
+            search(json!({ "query": { "match_all": {} } })).await?.len(),
+            2
+        );
+
+        http::request_optional(|| client.request(Method::DELETE, index_name)).await?;
+        Ok(())
+    }
+}
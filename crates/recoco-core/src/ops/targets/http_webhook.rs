@@ -0,0 +1,631 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+use crate::prelude::*;
+
+use super::shared::table_columns::{TableColumnsSchema, check_table_compatibility};
+use crate::ops::registry::ExecutorFactoryRegistry;
+use crate::setup;
+use hmac::{Hmac, Mac};
+use recoco_utils::fingerprint::Fingerprinter;
+use serde_json::json;
+use sha2::Sha256;
+use std::time::Duration;
+
+const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+const TIMESTAMP_HEADER: &str = "X-Recoco-Timestamp";
+const SIGNATURE_HEADER: &str = "X-Recoco-Signature";
+
+////////////////////////////////////////////////////////////
+// Public Types
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PayloadFormat {
+    /// A JSON object with all entries of the batch in `entries`.
+    #[default]
+    Json,
+    /// One entry per line.
+    Ndjson,
+}
+
+/// What to do with a batch when delivery still fails after retries.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DeliveryFailurePolicy {
+    /// Fail the export, so the rows are exported again on the next update.
+    #[default]
+    Fail,
+    /// Log the failure and move on. The receiver misses the batch.
+    Skip,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Spec {
+    url: String,
+    #[serde(default)]
+    format: PayloadFormat,
+    /// Extra headers sent with each request, e.g. `Authorization`.
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// Secret to sign requests with. The signature is an HMAC-SHA256 of
+    /// `{timestamp}.{body}`, sent as `sha256=<hex>` in `X-Recoco-Signature`, with the
+    /// timestamp in `X-Recoco-Timestamp`.
+    signing_secret: Option<spec::AuthEntryReference<String>>,
+    /// Maximum number of entries in each request.
+    max_batch_size: Option<usize>,
+    /// Timeout of each request.
+    timeout: Option<Duration>,
+    /// How long to keep retrying a batch before applying `on_failure`.
+    retry_timeout: Option<Duration>,
+    #[serde(default)]
+    on_failure: DeliveryFailurePolicy,
+}
+
+////////////////////////////////////////////////////////////
+// Payloads
+////////////////////////////////////////////////////////////
+
+fn fields_to_json<'a>(
+    fields_schema: &[FieldSchema],
+    values: impl Iterator<Item = Cow<'a, Value>>,
+) -> Result<serde_json::Value> {
+    let mut object = serde_json::Map::new();
+    for (v, field) in values.zip(fields_schema.iter()) {
+        object.insert(
+            field.name.clone(),
+            serde_json::to_value(TypedValue {
+                t: &field.value_type.typ,
+                v: &v,
+            })?,
+        );
+    }
+    Ok(serde_json::Value::Object(object))
+}
+
+/// Adds an idempotency key to the entry, from a fingerprint of its content. Sending the same
+/// change again gives the same key, so receivers can drop duplicates from retries.
+fn with_idempotency_key(mut entry: serde_json::Value) -> Result<serde_json::Value> {
+    let mut fp = Fingerprinter::default();
+    fp.write_raw_bytes(&serde_json::to_vec(&entry)?);
+    entry["idempotency_key"] = json!(fp.into_fingerprint().to_base64());
+    Ok(entry)
+}
+
+/// A batch of entries ready to send.
+struct Batch {
+    body: Vec<u8>,
+    idempotency_key: String,
+    num_entries: usize,
+}
+
+impl Batch {
+    fn new(entries: &[serde_json::Value], format: PayloadFormat) -> Result<Self> {
+        let body = match format {
+            PayloadFormat::Json => serde_json::to_vec(&json!({ "entries": entries }))?,
+            PayloadFormat::Ndjson => {
+                let mut body = Vec::new();
+                for entry in entries {
+                    serde_json::to_writer(&mut body, entry)?;
+                    body.push(b'\n');
+                }
+                body
+            }
+        };
+        let mut fp = Fingerprinter::default();
+        for entry in entries {
+            if let Some(key) = entry["idempotency_key"].as_str() {
+                fp.write_raw_bytes(key.as_bytes());
+            }
+        }
+        Ok(Self {
+            body,
+            idempotency_key: fp.into_fingerprint().to_base64(),
+            num_entries: entries.len(),
+        })
+    }
+}
+
+fn sign(secret: &str, timestamp: i64, body: &[u8]) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(Error::internal)?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    Ok(format!(
+        "sha256={}",
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+////////////////////////////////////////////////////////////
+// Setup
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct WebhookKey {
+    url: String,
+    /// Name of the export, to tell apart exports to the same URL.
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SetupState {
+    #[serde(flatten)]
+    columns: TableColumnsSchema<ValueType>,
+}
+
+/// Nothing to set up on the receiver's side.
+#[derive(Debug)]
+struct SetupChange;
+
+impl setup::ResourceSetupChange for SetupChange {
+    fn describe_changes(&self) -> Vec<setup::ChangeDescription> {
+        vec![]
+    }
+
+    fn change_type(&self) -> setup::SetupChangeType {
+        setup::SetupChangeType::NoChange
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Deal with mutations
+////////////////////////////////////////////////////////////
+
+struct ExportContext {
+    reqwest_client: reqwest::Client,
+    url: String,
+    format: PayloadFormat,
+    headers: reqwest::header::HeaderMap,
+    signing_secret: Option<String>,
+    max_batch_size: usize,
+    timeout: Duration,
+    retry_options: retryable::RetryOptions,
+    on_failure: DeliveryFailurePolicy,
+    key_fields_schema: Box<[FieldSchema]>,
+    value_fields_schema: Vec<FieldSchema>,
+}
+
+impl ExportContext {
+    fn entries(&self, mutation: &ExportTargetMutation) -> Result<Vec<serde_json::Value>> {
+        let key_json = |key: &KeyValue| {
+            fields_to_json(
+                &self.key_fields_schema,
+                key.iter().map(|part| Cow::Owned(Value::from(part.clone()))),
+            )
+        };
+        let mut entries = Vec::with_capacity(mutation.upserts.len() + mutation.deletes.len());
+        for upsert in mutation.upserts.iter() {
+            entries.push(with_idempotency_key(json!({
+                "op": "upsert",
+                "key": key_json(&upsert.key)?,
+                "value": fields_to_json(
+                    &self.value_fields_schema,
+                    upsert.value.fields.iter().map(Cow::Borrowed),
+                )?,
+            }))?);
+        }
+        for delete in mutation.deletes.iter() {
+            entries.push(with_idempotency_key(json!({
+                "op": "delete",
+                "key": key_json(&delete.key)?,
+            }))?);
+        }
+        Ok(entries)
+    }
+
+    fn request(&self, batch: &Batch, timestamp: i64) -> Result<reqwest::RequestBuilder> {
+        let mut req = self
+            .reqwest_client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .headers(self.headers.clone())
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                match self.format {
+                    PayloadFormat::Json => "application/json",
+                    PayloadFormat::Ndjson => "application/x-ndjson",
+                },
+            )
+            .header(IDEMPOTENCY_KEY_HEADER, &batch.idempotency_key)
+            .body(batch.body.clone());
+        if let Some(secret) = &self.signing_secret {
+            req = req
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(SIGNATURE_HEADER, sign(secret, timestamp, &batch.body)?);
+        }
+        Ok(req)
+    }
+
+    async fn send(&self, batch: &Batch) -> Result<()> {
+        // Retries keep the timestamp, so they're signed the same.
+        let req = self.request(batch, chrono::Utc::now().timestamp())?;
+        retryable::run(
+            || async {
+                let req = req.try_clone().ok_or_else(|| {
+                    retryable::Error::not_retryable(internal_error!("Request is not clonable"))
+                })?;
+                let resp = req.send().await.map_err(|e| {
+                    let is_retryable = e.is_timeout() || e.is_connect() || e.is_request();
+                    retryable::Error {
+                        error: Error::from(e),
+                        is_retryable,
+                    }
+                })?;
+                let status = resp.status();
+                if status.is_success() {
+                    return retryable::Ok(());
+                }
+                let body = resp.text().await.unwrap_or_default();
+                Err(retryable::Error {
+                    error: client_error!("Webhook responded with {status}: {body}"),
+                    is_retryable: is_retryable_status(status),
+                })
+            },
+            &self.retry_options,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn apply_mutation(&self, mutation: &ExportTargetMutation) -> Result<()> {
+        let entries = self.entries(mutation)?;
+        for chunk in entries.chunks(self.max_batch_size) {
+            let batch = Batch::new(chunk, self.format)?;
+            if let Err(err) = self.send(&batch).await {
+                match self.on_failure {
+                    DeliveryFailurePolicy::Fail => {
+                        return Err(err).with_context(|| {
+                            format!(
+                                "Delivering {} entries to webhook {}",
+                                batch.num_entries, self.url
+                            )
+                        });
+                    }
+                    DeliveryFailurePolicy::Skip => {
+                        warn!(
+                            "Skipping {} entries after failing to deliver them to webhook {}: {err:?}",
+                            batch.num_entries, self.url
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Factory implementation
+////////////////////////////////////////////////////////////
+
+struct Factory {
+    reqwest_client: reqwest::Client,
+}
+
+#[async_trait]
+impl TargetFactoryBase for Factory {
+    type Spec = Spec;
+    type DeclarationSpec = ();
+    type SetupState = SetupState;
+    type SetupChange = SetupChange;
+    type SetupKey = WebhookKey;
+    type ExportContext = ExportContext;
+
+    fn name(&self) -> &str {
+        "HttpWebhook"
+    }
+
+    async fn build(
+        self: Arc<Self>,
+        data_collections: Vec<TypedExportDataCollectionSpec<Self>>,
+        _declarations: Vec<()>,
+        context: Arc<FlowInstanceContext>,
+    ) -> Result<(
+        Vec<TypedExportDataCollectionBuildOutput<Self>>,
+        Vec<(WebhookKey, SetupState)>,
+    )> {
+        let data_coll_output = data_collections
+            .into_iter()
+            .map(|d| {
+                if !d.index_options.vector_indexes.is_empty()
+                    || !d.index_options.fts_indexes.is_empty()
+                {
+                    api_bail!("Indexes are not supported for HttpWebhook target");
+                }
+                if d.spec.max_batch_size == Some(0) {
+                    api_bail!("`max_batch_size` of HttpWebhook target must be positive");
+                }
+                let mut headers = reqwest::header::HeaderMap::new();
+                for (name, value) in d.spec.headers.iter() {
+                    headers.insert(
+                        reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                            .map_err(|e| api_error!("Invalid header name `{name}`: {e}"))?,
+                        reqwest::header::HeaderValue::from_str(value)
+                            .map_err(|e| api_error!("Invalid value of header `{name}`: {e}"))?,
+                    );
+                }
+                let signing_secret = d
+                    .spec
+                    .signing_secret
+                    .as_ref()
+                    .map(|secret| context.auth_registry.get(secret))
+                    .transpose()?;
+                let desired_setup_state = SetupState {
+                    columns: TableColumnsSchema {
+                        key_columns: d
+                            .key_fields_schema
+                            .iter()
+                            .map(|f| (f.name.clone(), f.value_type.typ.without_attrs()))
+                            .collect(),
+                        value_columns: d
+                            .value_fields_schema
+                            .iter()
+                            .map(|f| (f.name.clone(), f.value_type.typ.without_attrs()))
+                            .collect(),
+                    },
+                };
+                let export_context = Arc::new(ExportContext {
+                    reqwest_client: self.reqwest_client.clone(),
+                    url: d.spec.url.clone(),
+                    format: d.spec.format,
+                    headers,
+                    signing_secret,
+                    max_batch_size: d.spec.max_batch_size.unwrap_or(DEFAULT_MAX_BATCH_SIZE),
+                    timeout: d.spec.timeout.unwrap_or(DEFAULT_TIMEOUT),
+                    retry_options: retryable::RetryOptions {
+                        retry_timeout: Some(
+                            d.spec
+                                .retry_timeout
+                                .unwrap_or(retryable::DEFAULT_RETRY_TIMEOUT),
+                        ),
+                        ..Default::default()
+                    },
+                    on_failure: d.spec.on_failure,
+                    key_fields_schema: d.key_fields_schema,
+                    value_fields_schema: d.value_fields_schema,
+                });
+                Ok(TypedExportDataCollectionBuildOutput {
+                    export_context: Box::pin(async move { Ok(export_context) }),
+                    setup_key: WebhookKey {
+                        url: d.spec.url,
+                        name: d.name,
+                    },
+                    desired_setup_state,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((data_coll_output, vec![]))
+    }
+
+    async fn diff_setup_states(
+        &self,
+        _key: WebhookKey,
+        _desired: Option<SetupState>,
+        _existing: setup::CombinedState<SetupState>,
+        _flow_instance_ctx: Arc<FlowInstanceContext>,
+    ) -> Result<Self::SetupChange> {
+        Ok(SetupChange)
+    }
+
+    fn check_state_compatibility(
+        &self,
+        desired: &SetupState,
+        existing: &SetupState,
+    ) -> Result<SetupStateCompatibility> {
+        Ok(check_table_compatibility(
+            &desired.columns,
+            &existing.columns,
+        ))
+    }
+
+    fn describe_resource(&self, key: &WebhookKey) -> Result<String> {
+        Ok(format!("Webhook {} for {}", key.url, key.name))
+    }
+
+    async fn apply_mutation(
+        &self,
+        mutations: Vec<ExportTargetMutationWithContext<'async_trait, ExportContext>>,
+    ) -> Result<()> {
+        for mutation_w_ctx in mutations.into_iter() {
+            mutation_w_ctx
+                .export_context
+                .apply_mutation(&mutation_w_ctx.mutation)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn apply_setup_changes(
+        &self,
+        _setup_change: Vec<TypedResourceSetupChangeItem<'async_trait, Self>>,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub fn register(
+    registry: &mut ExecutorFactoryRegistry,
+    reqwest_client: reqwest::Client,
+) -> Result<()> {
+    Factory { reqwest_client }.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::{Router, routing::post};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Requests received by the test server, with the signature and idempotency headers.
+    type Received = Arc<Mutex<Vec<(String, Option<String>, Option<String>, Option<String>)>>>;
+
+    fn test_export_context(url: String, format: PayloadFormat) -> ExportContext {
+        ExportContext {
+            reqwest_client: reqwest::Client::new(),
+            url,
+            format,
+            headers: reqwest::header::HeaderMap::new(),
+            signing_secret: Some("secret".to_string()),
+            max_batch_size: 2,
+            timeout: DEFAULT_TIMEOUT,
+            retry_options: retryable::RetryOptions {
+                retry_timeout: Some(Duration::from_secs(5)),
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(10),
+            },
+            on_failure: DeliveryFailurePolicy::Fail,
+            key_fields_schema: vec![FieldSchema::new(
+                "id",
+                make_output_type(BasicValueType::Int64),
+            )]
+            .into(),
+            value_fields_schema: vec![FieldSchema::new(
+                "text",
+                make_output_type(BasicValueType::Str),
+            )],
+        }
+    }
+
+    fn test_mutation() -> ExportTargetMutation {
+        let upsert = |id: i64, text: &str| ExportTargetUpsertEntry {
+            key: KeyValue::from_single_part(id),
+            additional_key: serde_json::Value::Null,
+            value: FieldValues {
+                fields: vec![Value::Basic(BasicValue::Str(text.into()))],
+            },
+        };
+        ExportTargetMutation {
+            upserts: vec![upsert(1, "one"), upsert(2, "two")],
+            deletes: vec![ExportTargetDeleteEntry {
+                key: KeyValue::from_single_part(3i64),
+                additional_key: serde_json::Value::Null,
+            }],
+        }
+    }
+
+    /// Serves a webhook responding with the statuses in turn, then with 200.
+    async fn serve_webhook(statuses: Vec<StatusCode>, received: Received) -> Result<String> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/hook", listener.local_addr()?);
+        let num_requests = Arc::new(AtomicUsize::new(0));
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: String| async move {
+                let header = |name: &str| {
+                    headers
+                        .get(name)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string)
+                };
+                received.lock().unwrap().push((
+                    body,
+                    header(TIMESTAMP_HEADER),
+                    header(SIGNATURE_HEADER),
+                    header(IDEMPOTENCY_KEY_HEADER),
+                ));
+                let i = num_requests.fetch_add(1, Ordering::SeqCst);
+                statuses.get(i).copied().unwrap_or(StatusCode::OK)
+            }),
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok(url)
+    }
+
+    #[test]
+    fn test_entries_and_signature() -> Result<()> {
+        let export_context = test_export_context(String::new(), PayloadFormat::Ndjson);
+        let entries = export_context.entries(&test_mutation())?;
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["op"], json!("upsert"));
+        assert_eq!(entries[0]["key"], json!({ "id": 1 }));
+        assert_eq!(entries[0]["value"], json!({ "text": "one" }));
+        assert_eq!(entries[2]["op"], json!("delete"));
+        assert_eq!(entries[2]["key"], json!({ "id": 3 }));
+        assert!(entries[2].get("value").is_none());
+
+        // Idempotency keys only change with the content.
+        let again = export_context.entries(&test_mutation())?;
+        assert_eq!(entries[0]["idempotency_key"], again[0]["idempotency_key"]);
+        assert_ne!(entries[0]["idempotency_key"], entries[1]["idempotency_key"]);
+
+        let batch = Batch::new(&entries[..2], PayloadFormat::Ndjson)?;
+        assert_eq!(String::from_utf8_lossy(&batch.body).lines().count(), 2);
+        assert_eq!(
+            batch.idempotency_key,
+            Batch::new(&again[..2], PayloadFormat::Json)?.idempotency_key
+        );
+
+        assert_eq!(
+            sign("secret", 1700000000, br#"{"entries":[]}"#)?,
+            "sha256=968d381ada0dff1fb26e0268510204aebd0f79631a1fb5538644f0e7eb4d9e07"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delivery() -> Result<()> {
+        // The first batch succeeds after a retry.
+        let received = Received::default();
+        let url = serve_webhook(vec![StatusCode::SERVICE_UNAVAILABLE], received.clone()).await?;
+        let export_context = test_export_context(url, PayloadFormat::Json);
+        export_context.apply_mutation(&test_mutation()).await?;
+        {
+            let received = received.lock().unwrap();
+            assert_eq!(received.len(), 3);
+            // Retried as is.
+            assert_eq!(received[0], received[1]);
+            let (body, timestamp, signature, _) = &received[1];
+            let body: serde_json::Value = serde_json::from_str(body)?;
+            assert_eq!(body["entries"].as_array().map(|e| e.len()), Some(2));
+            let timestamp: i64 = timestamp.as_deref().unwrap_or_default().parse()?;
+            assert_eq!(
+                signature.as_deref(),
+                Some(sign("secret", timestamp, received[1].0.as_bytes())?.as_str())
+            );
+            let (body, _, _, idempotency_key) = &received[2];
+            let body: serde_json::Value = serde_json::from_str(body)?;
+            assert_eq!(body["entries"][0]["op"], json!("delete"));
+            assert!(idempotency_key.is_some());
+        }
+
+        // Client errors aren't retried, and fail the export unless skipped.
+        let received = Received::default();
+        let url = serve_webhook(
+            vec![StatusCode::BAD_REQUEST, StatusCode::BAD_REQUEST],
+            received.clone(),
+        )
+        .await?;
+        let mut export_context = test_export_context(url, PayloadFormat::Json);
+        assert!(
+            export_context
+                .apply_mutation(&test_mutation())
+                .await
+                .is_err()
+        );
+        assert_eq!(received.lock().unwrap().len(), 1);
+        export_context.on_failure = DeliveryFailurePolicy::Skip;
+        export_context.apply_mutation(&test_mutation()).await?;
+        assert_eq!(received.lock().unwrap().len(), 3);
+        Ok(())
+    }
+}
@@ -134,7 +134,10 @@ fn basic_type_to_ladybug(basic_type: &BasicValueType) -> Result<String> {
             t.dimension
                 .map_or_else(|| "".to_string(), |d| d.to_string())
         ),
-        t @ (BasicValueType::Union(_) | BasicValueType::Time | BasicValueType::Json) => {
+        t @ (BasicValueType::Union(_)
+        | BasicValueType::Time
+        | BasicValueType::Json
+        | BasicValueType::SparseVector) => {
             api_bail!("{t} is not supported in Ladybug")
         }
     })
@@ -385,7 +388,10 @@ fn append_basic_value(cypher: &mut CypherBuilder, basic_value: &BasicValue) -> R
             }
             write!(cypher.query_mut(), "]")?;
         }
-        v @ (BasicValue::UnionVariant { .. } | BasicValue::Time(_) | BasicValue::Json(_)) => {
+        v @ (BasicValue::UnionVariant { .. }
+        | BasicValue::Time(_)
+        | BasicValue::Json(_)
+        | BasicValue::SparseVector(_)) => {
             client_bail!("value types are not supported in Ladybug: {}", v.kind());
         }
     }
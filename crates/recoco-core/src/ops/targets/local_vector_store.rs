@@ -0,0 +1,1017 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+use crate::prelude::*;
+
+use super::shared::table_columns::{
+    TableColumnsSchema, TableMainSetupAction, TableUpsertionAction, check_table_compatibility,
+};
+use crate::ops::registry::ExecutorFactoryRegistry;
+use crate::ops::shared::vector_index::{self, VectorIndex};
+use crate::setup;
+use futures::TryStreamExt;
+use sqlx::Row;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use std::path::{Path, PathBuf};
+
+const DEFAULT_VECTOR_SIMILARITY_METRIC: spec::VectorSimilarityMetric =
+    spec::VectorSimilarityMetric::CosineSimilarity;
+const SETUP_STATE_META_KEY: &str = "setup_state";
+
+////////////////////////////////////////////////////////////
+// Public Types
+////////////////////////////////////////////////////////////
+
+/// Rows and vectors are stored in SQLite. Vector indexes are kept in memory only: each process
+/// builds them from the stored vectors on its first query, which takes time proportional to the
+/// number of vectors, and exports keep them up to date afterwards.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Spec {
+    /// Directory holding the stores. Each table is a single file in it.
+    path: String,
+    table_name: String,
+}
+
+////////////////////////////////////////////////////////////
+// Common
+////////////////////////////////////////////////////////////
+
+fn store_file_path(dir: impl AsRef<Path>, table_name: &str) -> PathBuf {
+    dir.as_ref().join(format!("{table_name}.sqlite"))
+}
+
+/// Dimension of a field that can be indexed as a vector.
+fn vector_dimension(typ: &ValueType) -> Option<usize> {
+    match typ {
+        ValueType::Basic(BasicValueType::Vector(vector_schema)) => {
+            match *vector_schema.element_type {
+                BasicValueType::Float32 | BasicValueType::Float64 | BasicValueType::Int64 => {
+                    vector_schema.dimension
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn encode_vector(v: &BasicValue, dimension: usize) -> Result<Vec<f32>> {
+    let vector = match v {
+        BasicValue::Vector(v) => v
+            .iter()
+            .map(|elem| {
+                Ok(match elem {
+                    BasicValue::Float32(f) => *f,
+                    BasicValue::Float64(f) => *f as f32,
+                    BasicValue::Int64(i) => *i as f32,
+                    _ => client_bail!("Unsupported vector type: {:?}", elem.kind()),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?,
+        _ => client_bail!("Expected a vector field, got {:?}", v),
+    };
+    if vector.len() != dimension {
+        client_bail!(
+            "Expected a vector of dimension {dimension}, got {}",
+            vector.len()
+        );
+    }
+    Ok(vector)
+}
+
+fn vector_to_bytes(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn vector_from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+fn key_to_row_id(key: &KeyValue) -> Result<String> {
+    Ok(match serde_json::to_value(key)? {
+        serde_json::Value::String(s) => s,
+        v => v.to_string(),
+    })
+}
+
+/// JSON path of a top-level field of a document, for SQLite's JSON functions.
+fn doc_field_path(field_name: &str) -> String {
+    format!("$.\"{field_name}\"")
+}
+
+async fn connect(file_path: &Path, create_if_missing: bool) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::new()
+        .filename(file_path)
+        .create_if_missing(create_if_missing);
+    let pool = SqlitePoolOptions::new()
+        .connect_with(options)
+        .await
+        .map_err(Error::from)
+        .with_context(|| format!("Failed to open vector store {}", file_path.display()))?;
+    Ok(pool)
+}
+
+type VectorIndexes = HashMap<String, VectorIndex>;
+
+/// An opened store: rows and vectors in SQLite, with an in-memory index for each vector
+/// field, built from the stored vectors on the first query.
+struct Store {
+    pool: SqlitePool,
+    state: SetupState,
+    /// Index of each vector field, by field name. `None` until the first query, so opening
+    /// a store only to export to it doesn't read all vectors.
+    indexes: tokio::sync::RwLock<Option<VectorIndexes>>,
+}
+
+/// Stores opened in this process, by file path. Shared by exports and query handlers.
+static OPEN_STORES: LazyLock<tokio::sync::Mutex<HashMap<PathBuf, Arc<Store>>>> =
+    LazyLock::new(|| tokio::sync::Mutex::new(HashMap::new()));
+
+impl Store {
+    async fn open(file_path: &Path) -> Result<Arc<Self>> {
+        let mut open_stores = OPEN_STORES.lock().await;
+        if let Some(store) = open_stores.get(file_path) {
+            return Ok(store.clone());
+        }
+        if !tokio::fs::try_exists(file_path).await? {
+            client_bail!(
+                "Vector store {} doesn't exist. Please run setup for the flow first.",
+                file_path.display()
+            );
+        }
+        let pool = connect(file_path, false).await?;
+        let state_json: String = sqlx::query_scalar("SELECT value FROM meta WHERE key = ?")
+            .bind(SETUP_STATE_META_KEY)
+            .fetch_one(&pool)
+            .await?;
+        let state: SetupState = utils::deser::from_json_str(&state_json)?;
+        let store = Arc::new(Self {
+            pool,
+            state,
+            indexes: tokio::sync::RwLock::new(None),
+        });
+        open_stores.insert(file_path.to_path_buf(), store.clone());
+        Ok(store)
+    }
+
+    async fn build_indexes(&self) -> Result<VectorIndexes> {
+        let mut indexes = self
+            .state
+            .vector_indexes
+            .iter()
+            .map(|(field_name, index_def)| {
+                (
+                    field_name.clone(),
+                    VectorIndex::new(index_def.metric, index_def.method.as_ref()),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let mut rows = sqlx::query("SELECT id, field, vector FROM vectors").fetch(&self.pool);
+        while let Some(row) = rows.try_next().await? {
+            let field: &str = row.try_get(1)?;
+            if let Some(index) = indexes.get_mut(field) {
+                let id: &str = row.try_get(0)?;
+                index.upsert(id.into(), vector_from_bytes(row.try_get(2)?));
+            }
+        }
+        Ok(indexes)
+    }
+
+    /// The indexes, built first if they aren't yet.
+    async fn read_indexes(&self) -> Result<tokio::sync::RwLockReadGuard<'_, VectorIndexes>> {
+        let indexes = self.indexes.read().await;
+        if indexes.is_some() {
+            return Ok(tokio::sync::RwLockReadGuard::map(indexes, |indexes| {
+                indexes.as_ref().expect("indexes are built")
+            }));
+        }
+        drop(indexes);
+        let mut indexes = self.indexes.write().await;
+        if indexes.is_none() {
+            *indexes = Some(self.build_indexes().await?);
+        }
+        Ok(tokio::sync::RwLockReadGuard::map(
+            indexes.downgrade(),
+            |indexes| indexes.as_ref().expect("indexes are built"),
+        ))
+    }
+
+    /// Close the store if it's open, e.g. before its setup changes.
+    async fn close(file_path: &Path) {
+        let store = OPEN_STORES.lock().await.remove(file_path);
+        if let Some(store) = store {
+            store.pool.close().await;
+        }
+    }
+
+    /// Fields of the row with the ID, in schema order.
+    async fn get_row(&self, id: &str) -> Result<Option<Vec<(FieldName, serde_json::Value)>>> {
+        let doc: Option<String> = sqlx::query_scalar("SELECT doc FROM rows WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(doc) = doc else {
+            return Ok(None);
+        };
+        let mut doc: serde_json::Map<String, serde_json::Value> =
+            utils::deser::from_json_str(&doc)?;
+        let columns = &self.state.columns;
+        Ok(Some(
+            columns
+                .key_columns
+                .keys()
+                .chain(columns.value_columns.keys())
+                .filter_map(|name| doc.remove(name).map(|v| (name.clone(), v)))
+                .collect(),
+        ))
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Setup
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct TableId {
+    path: String,
+    table_name: String,
+}
+
+impl TableId {
+    fn file_path(&self) -> PathBuf {
+        store_file_path(&self.path, &self.table_name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SetupState {
+    #[serde(flatten)]
+    columns: TableColumnsSchema<ValueType>,
+
+    /// Index of each vector field, by field name.
+    vector_indexes: BTreeMap<String, spec::VectorIndexDef>,
+}
+
+impl SetupState {
+    fn new(
+        key_fields_schema: &[FieldSchema],
+        value_fields_schema: &[FieldSchema],
+        index_options: &IndexOptions,
+    ) -> Result<Self> {
+        if !index_options.fts_indexes.is_empty() {
+            api_bail!("FTS indexes are not supported for LocalVectorStore target");
+        }
+        let mut vector_indexes = value_fields_schema
+            .iter()
+            .filter(|field| vector_dimension(&field.value_type.typ).is_some())
+            .map(|field| {
+                (
+                    field.name.clone(),
+                    spec::VectorIndexDef {
+                        field_name: field.name.clone(),
+                        metric: DEFAULT_VECTOR_SIMILARITY_METRIC,
+                        method: None,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut specified_vector_fields = HashSet::new();
+        for vector_index in index_options.vector_indexes.iter() {
+            let Some(index_def) = vector_indexes.get_mut(&vector_index.field_name) else {
+                match value_fields_schema
+                    .iter()
+                    .find(|f| f.name == vector_index.field_name)
+                {
+                    Some(field) => api_bail!(
+                        "Field `{}` specified in vector index is expected to be a number vector with fixed size, actual type: {}",
+                        vector_index.field_name,
+                        field.value_type.typ
+                    ),
+                    None => api_bail!(
+                        "Field `{}` specified in vector index is not found",
+                        vector_index.field_name
+                    ),
+                }
+            };
+            if !specified_vector_fields.insert(vector_index.field_name.as_str()) {
+                api_bail!(
+                    "Field `{}` specified more than once in vector index definition",
+                    vector_index.field_name
+                );
+            }
+            *index_def = vector_index.clone();
+        }
+        Ok(Self {
+            columns: TableColumnsSchema {
+                key_columns: key_fields_schema
+                    .iter()
+                    .map(|f| (f.name.clone(), f.value_type.typ.without_attrs()))
+                    .collect(),
+                value_columns: value_fields_schema
+                    .iter()
+                    .map(|f| (f.name.clone(), f.value_type.typ.without_attrs()))
+                    .collect(),
+            },
+            vector_indexes,
+        })
+    }
+}
+
+impl<'a> From<&'a SetupState> for Cow<'a, TableColumnsSchema<ValueType>> {
+    fn from(val: &'a SetupState) -> Self {
+        Cow::Borrowed(&val.columns)
+    }
+}
+
+#[derive(Debug)]
+struct SetupChange {
+    actions: TableMainSetupAction<ValueType>,
+    /// Recorded in the store, when different from the existing one.
+    desired_state: Option<SetupState>,
+}
+
+impl SetupChange {
+    fn from_states(
+        desired: Option<SetupState>,
+        existing: &setup::CombinedState<SetupState>,
+    ) -> Self {
+        let actions = TableMainSetupAction::from_states(desired.as_ref(), existing, false);
+        let desired_state = desired.filter(|desired| {
+            actions.table_upsertion.is_some()
+                || existing
+                    .possible_versions()
+                    .any(|v| v.vector_indexes != desired.vector_indexes)
+        });
+        Self {
+            actions,
+            desired_state,
+        }
+    }
+
+    async fn apply(&self, file_path: &Path) -> Result<()> {
+        if !self.actions.drop_existing && self.desired_state.is_none() {
+            return Ok(());
+        }
+        Store::close(file_path).await;
+        if self.actions.drop_existing {
+            match tokio::fs::remove_file(file_path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let Some(desired_state) = &self.desired_state else {
+            return Ok(());
+        };
+        if let Some(dir) = file_path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let pool = connect(file_path, true).await?;
+        let mut txn = pool.begin().await?;
+        for stmt in [
+            "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+            "CREATE TABLE IF NOT EXISTS rows (id TEXT PRIMARY KEY, doc TEXT NOT NULL)",
+            "CREATE TABLE IF NOT EXISTS vectors (id TEXT NOT NULL, field TEXT NOT NULL, vector BLOB NOT NULL, PRIMARY KEY (id, field))",
+        ] {
+            sqlx::query(stmt).execute(&mut *txn).await?;
+        }
+        // Values of removed or changed fields are stale, and are filled again on reindexing.
+        if let Some(TableUpsertionAction::Update {
+            columns_to_delete,
+            columns_to_upsert,
+        }) = &self.actions.table_upsertion
+        {
+            for field_name in columns_to_delete.iter().chain(columns_to_upsert.keys()) {
+                sqlx::query("UPDATE rows SET doc = json_remove(doc, ?)")
+                    .bind(doc_field_path(field_name))
+                    .execute(&mut *txn)
+                    .await?;
+                sqlx::query("DELETE FROM vectors WHERE field = ?")
+                    .bind(field_name)
+                    .execute(&mut *txn)
+                    .await?;
+            }
+        }
+        sqlx::query(
+            "INSERT INTO meta (key, value) VALUES (?, ?) ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        )
+        .bind(SETUP_STATE_META_KEY)
+        .bind(serde_json::to_string(desired_state)?)
+        .execute(&mut *txn)
+        .await?;
+        txn.commit().await?;
+        pool.close().await;
+        Ok(())
+    }
+}
+
+impl setup::ResourceSetupChange for SetupChange {
+    fn describe_changes(&self) -> Vec<setup::ChangeDescription> {
+        let mut result = self.actions.describe_changes();
+        if let Some(desired_state) = &self.desired_state
+            && !desired_state.vector_indexes.is_empty()
+        {
+            result.push(setup::ChangeDescription::Action(format!(
+                "Index vector fields: {}",
+                desired_state.vector_indexes.values().join(", ")
+            )));
+        }
+        result
+    }
+
+    fn change_type(&self) -> setup::SetupChangeType {
+        self.actions.change_type(self.desired_state.is_some())
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Deal with mutations
+////////////////////////////////////////////////////////////
+
+type IndexUpdate<'a> = (&'a str, Arc<str>, Option<Vec<f32>>);
+
+struct ExportContext {
+    file_path: PathBuf,
+    key_fields_schema: Box<[FieldSchema]>,
+    value_fields_schema: Vec<FieldSchema>,
+    /// Position in the value fields and dimension of each vector field.
+    vector_fields: Vec<(usize, usize)>,
+}
+
+impl ExportContext {
+    fn new(
+        file_path: PathBuf,
+        key_fields_schema: Box<[FieldSchema]>,
+        value_fields_schema: Vec<FieldSchema>,
+    ) -> Self {
+        let vector_fields = value_fields_schema
+            .iter()
+            .enumerate()
+            .filter_map(|(i, field)| vector_dimension(&field.value_type.typ).map(|dim| (i, dim)))
+            .collect();
+        Self {
+            file_path,
+            key_fields_schema,
+            value_fields_schema,
+            vector_fields,
+        }
+    }
+
+    fn document(&self, key: &KeyValue, value: &FieldValues) -> Result<serde_json::Value> {
+        let mut doc = serde_json::Map::new();
+        for (key_part, field) in std::iter::zip(key.iter(), self.key_fields_schema.iter()) {
+            let v = Value::from(key_part.clone());
+            doc.insert(
+                field.name.clone(),
+                serde_json::to_value(TypedValue {
+                    t: &field.value_type.typ,
+                    v: &v,
+                })?,
+            );
+        }
+        for (v, field) in std::iter::zip(value.fields.iter(), self.value_fields_schema.iter()) {
+            doc.insert(
+                field.name.clone(),
+                serde_json::to_value(TypedValue {
+                    t: &field.value_type.typ,
+                    v,
+                })?,
+            );
+        }
+        Ok(serde_json::Value::Object(doc))
+    }
+
+    async fn apply_mutation(&self, mutation: ExportTargetMutation) -> Result<()> {
+        let store = Store::open(&self.file_path).await?;
+        // Held until the indexes are updated, so they're consistent with the committed rows.
+        // Indexes not built yet pick up the committed rows when they are.
+        let mut indexes = store.indexes.write().await;
+        // Field name, row ID and the new vector, if any.
+        let mut index_updates: Vec<IndexUpdate<'_>> = vec![];
+        let mut txn = store.pool.begin().await?;
+        for upsert in mutation.upserts.iter() {
+            let id: Arc<str> = key_to_row_id(&upsert.key)?.into();
+            sqlx::query(
+                "INSERT INTO rows (id, doc) VALUES (?, ?) ON CONFLICT (id) DO UPDATE SET doc = excluded.doc",
+            )
+            .bind(id.as_ref())
+            .bind(self.document(&upsert.key, &upsert.value)?.to_string())
+            .execute(&mut *txn)
+            .await?;
+            sqlx::query("DELETE FROM vectors WHERE id = ?")
+                .bind(id.as_ref())
+                .execute(&mut *txn)
+                .await?;
+            for &(i, dimension) in self.vector_fields.iter() {
+                let field_name = self.value_fields_schema[i].name.as_str();
+                let vector = match &upsert.value.fields[i] {
+                    Value::Null => None,
+                    Value::Basic(v) => Some(
+                        encode_vector(v, dimension)
+                            .with_context(|| format!("Field `{field_name}`"))?,
+                    ),
+                    v => client_bail!("Expected a vector for field `{field_name}`, got {v:?}"),
+                };
+                if let Some(vector) = &vector {
+                    sqlx::query("INSERT INTO vectors (id, field, vector) VALUES (?, ?, ?)")
+                        .bind(id.as_ref())
+                        .bind(field_name)
+                        .bind(vector_to_bytes(vector))
+                        .execute(&mut *txn)
+                        .await?;
+                }
+                index_updates.push((field_name, id.clone(), vector));
+            }
+        }
+        for delete in mutation.deletes.iter() {
+            let id: Arc<str> = key_to_row_id(&delete.key)?.into();
+            for stmt in [
+                "DELETE FROM rows WHERE id = ?",
+                "DELETE FROM vectors WHERE id = ?",
+            ] {
+                sqlx::query(stmt)
+                    .bind(id.as_ref())
+                    .execute(&mut *txn)
+                    .await?;
+            }
+            for &(i, _) in self.vector_fields.iter() {
+                index_updates.push((&self.value_fields_schema[i].name, id.clone(), None));
+            }
+        }
+        txn.commit().await?;
+
+        let Some(indexes) = indexes.as_mut() else {
+            return Ok(());
+        };
+        for (field_name, id, vector) in index_updates {
+            let Some(index) = indexes.get_mut(field_name) else {
+                continue;
+            };
+            match vector {
+                Some(vector) => index.upsert(id, vector),
+                None => index.remove(&id),
+            }
+        }
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Factory implementation
+////////////////////////////////////////////////////////////
+
+struct Factory;
+
+#[async_trait]
+impl TargetFactoryBase for Factory {
+    type Spec = Spec;
+    type DeclarationSpec = ();
+    type SetupState = SetupState;
+    type SetupChange = SetupChange;
+    type SetupKey = TableId;
+    type ExportContext = ExportContext;
+
+    fn name(&self) -> &str {
+        "LocalVectorStore"
+    }
+
+    async fn build(
+        self: Arc<Self>,
+        data_collections: Vec<TypedExportDataCollectionSpec<Self>>,
+        _declarations: Vec<()>,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<(
+        Vec<TypedExportDataCollectionBuildOutput<Self>>,
+        Vec<(TableId, SetupState)>,
+    )> {
+        let data_coll_output = data_collections
+            .into_iter()
+            .map(|d| {
+                let desired_setup_state = SetupState::new(
+                    &d.key_fields_schema,
+                    &d.value_fields_schema,
+                    &d.index_options,
+                )?;
+                let table_id = TableId {
+                    path: d.spec.path,
+                    table_name: d.spec.table_name,
+                };
+                let export_context = Arc::new(ExportContext::new(
+                    table_id.file_path(),
+                    d.key_fields_schema,
+                    d.value_fields_schema,
+                ));
+                Ok(TypedExportDataCollectionBuildOutput {
+                    export_context: Box::pin(async move { Ok(export_context) }),
+                    setup_key: table_id,
+                    desired_setup_state,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((data_coll_output, vec![]))
+    }
+
+    async fn diff_setup_states(
+        &self,
+        _key: TableId,
+        desired: Option<SetupState>,
+        existing: setup::CombinedState<SetupState>,
+        _flow_instance_ctx: Arc<FlowInstanceContext>,
+    ) -> Result<Self::SetupChange> {
+        Ok(SetupChange::from_states(desired, &existing))
+    }
+
+    fn check_state_compatibility(
+        &self,
+        desired: &SetupState,
+        existing: &SetupState,
+    ) -> Result<SetupStateCompatibility> {
+        Ok(check_table_compatibility(
+            &desired.columns,
+            &existing.columns,
+        ))
+    }
+
+    fn describe_resource(&self, key: &TableId) -> Result<String> {
+        Ok(format!("Local vector store {}", key.file_path().display()))
+    }
+
+    async fn apply_mutation(
+        &self,
+        mutations: Vec<ExportTargetMutationWithContext<'async_trait, ExportContext>>,
+    ) -> Result<()> {
+        for mutation_w_ctx in mutations.into_iter() {
+            mutation_w_ctx
+                .export_context
+                .apply_mutation(mutation_w_ctx.mutation)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn apply_setup_changes(
+        &self,
+        setup_change: Vec<TypedResourceSetupChangeItem<'async_trait, Self>>,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<()> {
+        for change in setup_change.iter() {
+            change.setup_change.apply(&change.key.file_path()).await?;
+        }
+        Ok(())
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+////////////////////////////////////////////////////////////
+// Query handler
+////////////////////////////////////////////////////////////
+
+/// Turns query text into a vector, e.g. by calling the embedding model used for indexing.
+#[cfg(feature = "persistence")]
+pub type QueryEmbedder = Arc<dyn Fn(String) -> BoxFuture<'static, Result<Vec<f32>>> + Send + Sync>;
+
+/// Searches a vector field of a `LocalVectorStore` table, with the index configured for it.
+///
+/// Without an embedder, queries are vectors as JSON arrays, e.g. `[0.1, 0.2, 0.3]`.
+#[cfg(feature = "persistence")]
+pub struct LocalVectorStoreQueryHandler {
+    file_path: PathBuf,
+    vector_field: String,
+    score_field: String,
+    top_k: usize,
+    embedder: Option<QueryEmbedder>,
+}
+
+#[cfg(feature = "persistence")]
+impl LocalVectorStoreQueryHandler {
+    const DEFAULT_TOP_K: usize = 10;
+
+    /// `path` and `table_name` are the same as in the target spec.
+    pub fn new(path: impl AsRef<Path>, table_name: &str, vector_field: impl ToString) -> Self {
+        Self {
+            file_path: store_file_path(path, table_name),
+            vector_field: vector_field.to_string(),
+            score_field: "score".to_string(),
+            top_k: Self::DEFAULT_TOP_K,
+            embedder: None,
+        }
+    }
+
+    pub fn top_k(mut self, top_k: usize) -> Self {
+        self.top_k = top_k;
+        self
+    }
+
+    /// Name of the field carrying the score in results. Defaults to `score`.
+    pub fn score_field(mut self, score_field: impl ToString) -> Self {
+        self.score_field = score_field.to_string();
+        self
+    }
+
+    pub fn embed_with(mut self, embedder: QueryEmbedder) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// Spec to register the handler with, e.g. by [`FlowContext::add_query_handler`].
+    pub fn spec(&self) -> crate::service::query_handler::QueryHandlerSpec {
+        crate::service::query_handler::QueryHandlerSpec {
+            result_fields: crate::service::query_handler::QueryHandlerResultFields {
+                embedding: vec![self.vector_field.clone()],
+                score: Some(self.score_field.clone()),
+            },
+        }
+    }
+
+    async fn search(&self, query: String) -> Result<crate::service::query_handler::QueryOutput> {
+        use crate::service::query_handler::{QueryInfo, QueryOutput};
+
+        let store = Store::open(&self.file_path).await?;
+        let Some(dimension) = store
+            .state
+            .columns
+            .value_columns
+            .get(&self.vector_field)
+            .and_then(vector_dimension)
+        else {
+            client_bail!(
+                "Field `{}` is not a vector field of {}",
+                self.vector_field,
+                self.file_path.display()
+            );
+        };
+        let query_vector = match &self.embedder {
+            Some(embedder) => embedder(query).await?,
+            None => utils::deser::from_json_str::<Vec<f32>>(&query).map_err(|e| {
+                client_error!("Query is expected to be a vector as a JSON array of numbers: {e}")
+            })?,
+        };
+        if query_vector.len() != dimension {
+            client_bail!(
+                "Query vector is expected to be of dimension {dimension}, got {}",
+                query_vector.len()
+            );
+        }
+
+        let (metric, hits) = {
+            let indexes = store.read_indexes().await?;
+            let Some(index) = indexes.get(&self.vector_field) else {
+                client_bail!("Field `{}` is not indexed", self.vector_field);
+            };
+            (index.metric(), index.search(&query_vector, self.top_k))
+        };
+        let mut results = Vec::with_capacity(hits.len());
+        for (id, distance) in hits {
+            // Rows deleted since the search are skipped.
+            if let Some(mut row) = store.get_row(&id).await? {
+                row.push((
+                    self.score_field.clone(),
+                    serde_json::json!(vector_index::score(metric, distance)),
+                ));
+                results.push(row);
+            }
+        }
+        Ok(QueryOutput {
+            results,
+            query_info: QueryInfo {
+                embedding: Some(serde_json::to_value(&query_vector)?),
+                similarity_metric: Some(metric),
+            },
+        })
+    }
+}
+
+#[cfg(feature = "persistence")]
+#[async_trait]
+impl crate::service::query_handler::QueryHandler for LocalVectorStoreQueryHandler {
+    async fn query(
+        &self,
+        input: crate::service::query_handler::QueryInput,
+        _flow_ctx: &FlowInstanceContext,
+    ) -> Result<crate::service::query_handler::QueryOutput> {
+        self.search(input.query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector_type(dimension: usize) -> EnrichedValueType {
+        make_output_type(BasicValueType::Vector(VectorTypeSchema {
+            element_type: Box::new(BasicValueType::Float32),
+            dimension: Some(dimension),
+        }))
+    }
+
+    fn test_setup_state(value_fields_schema: &[FieldSchema]) -> Result<SetupState> {
+        SetupState::new(
+            &[FieldSchema::new(
+                "id",
+                make_output_type(BasicValueType::Str),
+            )],
+            value_fields_schema,
+            &IndexOptions {
+                primary_key_fields: Some(vec!["id".to_string()]),
+                vector_indexes: vec![spec::VectorIndexDef {
+                    field_name: "embedding".to_string(),
+                    metric: spec::VectorSimilarityMetric::L2Distance,
+                    method: Some(spec::VectorIndexMethod::Hnsw {
+                        m: None,
+                        ef_construction: None,
+                    }),
+                }],
+                fts_indexes: vec![],
+            },
+        )
+    }
+
+    fn upsert(id: &str, text: &str, embedding: [f32; 2]) -> ExportTargetUpsertEntry {
+        ExportTargetUpsertEntry {
+            key: KeyValue::from_single_part(id.to_string()),
+            additional_key: serde_json::Value::Null,
+            value: FieldValues {
+                fields: vec![
+                    Value::Basic(BasicValue::Str(text.into())),
+                    Value::Basic(BasicValue::Vector(
+                        embedding
+                            .into_iter()
+                            .map(BasicValue::Float32)
+                            .collect::<Vec<_>>()
+                            .into(),
+                    )),
+                ],
+            },
+        }
+    }
+
+    fn result_ids(output: &crate::service::query_handler::QueryOutput) -> Vec<&str> {
+        output
+            .results
+            .iter()
+            .filter_map(|row| row.iter().find(|(name, _)| name == "id"))
+            .filter_map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_upsert_delete_and_query() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!(
+            "recoco-local-vector-store-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let table_id = TableId {
+            path: dir.to_string_lossy().to_string(),
+            table_name: "docs".to_string(),
+        };
+        let file_path = table_id.file_path();
+        let key_fields_schema = [FieldSchema::new(
+            "id",
+            make_output_type(BasicValueType::Str),
+        )];
+        let value_fields_schema = vec![
+            FieldSchema::new("text", make_output_type(BasicValueType::Str)),
+            FieldSchema::new("embedding", vector_type(2)),
+        ];
+        let state = test_setup_state(&value_fields_schema)?;
+        let change = SetupChange::from_states(
+            Some(state.clone()),
+            &setup::CombinedState {
+                current: None,
+                staging: vec![],
+                legacy_state_key: None,
+            },
+        );
+        assert_eq!(
+            setup::ResourceSetupChange::change_type(&change),
+            setup::SetupChangeType::Create
+        );
+        change.apply(&file_path).await?;
+
+        let export_context = ExportContext::new(
+            file_path.clone(),
+            key_fields_schema.into(),
+            value_fields_schema,
+        );
+        export_context
+            .apply_mutation(ExportTargetMutation {
+                upserts: vec![
+                    upsert("a", "apple", [1.0, 0.0]),
+                    upsert("b", "banana", [0.0, 1.0]),
+                    upsert("c", "cherry", [5.0, 5.0]),
+                ],
+                deletes: vec![],
+            })
+            .await?;
+        export_context
+            .apply_mutation(ExportTargetMutation {
+                upserts: vec![upsert("b", "blueberry", [0.9, 0.1])],
+                deletes: vec![ExportTargetDeleteEntry {
+                    key: KeyValue::from_single_part("a".to_string()),
+                    additional_key: serde_json::Value::Null,
+                }],
+            })
+            .await?;
+
+        // Indexes are only built on the first query.
+        let store = Store::open(&file_path).await?;
+        assert!(store.indexes.read().await.is_none());
+        let handler = LocalVectorStoreQueryHandler::new(&dir, "docs", "embedding").top_k(2);
+        let output = handler.search("[1.0, 0.0]".to_string()).await?;
+        assert!(store.indexes.read().await.is_some());
+        drop(store);
+        assert_eq!(result_ids(&output), vec!["b", "c"]);
+        assert_eq!(
+            output.results[0],
+            vec![
+                ("id".to_string(), serde_json::json!("b")),
+                ("text".to_string(), serde_json::json!("blueberry")),
+                ("embedding".to_string(), serde_json::json!([0.9f32, 0.1f32])),
+                (
+                    "score".to_string(),
+                    serde_json::json!(vector_index::distance(
+                        spec::VectorSimilarityMetric::L2Distance,
+                        &[1.0, 0.0],
+                        &[0.9, 0.1]
+                    ))
+                ),
+            ]
+        );
+        assert_eq!(
+            output.query_info.similarity_metric,
+            Some(spec::VectorSimilarityMetric::L2Distance)
+        );
+        assert!(handler.search("[1.0]".to_string()).await.is_err());
+        assert!(handler.search("apple".to_string()).await.is_err());
+
+        // Indexes are rebuilt from the stored vectors, including ones exported in between.
+        Store::close(&file_path).await;
+        export_context
+            .apply_mutation(ExportTargetMutation {
+                upserts: vec![upsert("d", "date", [5.0, 2.5])],
+                deletes: vec![],
+            })
+            .await?;
+        let output = handler.search("[5.0, 4.0]".to_string()).await?;
+        assert_eq!(result_ids(&output), vec!["c", "d"]);
+
+        // Embedders turn query text into vectors.
+        let handler = LocalVectorStoreQueryHandler::new(&dir, "docs", "embedding")
+            .top_k(1)
+            .embed_with(Arc::new(|query: String| {
+                async move { Ok(vec![query.len() as f32, query.len() as f32]) }.boxed()
+            }));
+        let output = handler.search("12345".to_string()).await?;
+        assert_eq!(result_ids(&output), vec!["c"]);
+
+        // Changed fields are cleared in place; rows stay.
+        let changed_fields_schema = vec![
+            FieldSchema::new("text", make_output_type(BasicValueType::Str)),
+            FieldSchema::new("embedding", vector_type(3)),
+        ];
+        let desired = test_setup_state(&changed_fields_schema)?;
+        assert_eq!(
+            check_table_compatibility(&desired.columns, &state.columns),
+            SetupStateCompatibility::PartialCompatible
+        );
+        let change = SetupChange::from_states(Some(desired), &setup::CombinedState::current(state));
+        assert!(!change.actions.drop_existing);
+        change.apply(&file_path).await?;
+        let store = Store::open(&file_path).await?;
+        assert_eq!(
+            store.get_row("b").await?,
+            Some(vec![
+                ("id".to_string(), serde_json::json!("b")),
+                ("text".to_string(), serde_json::json!("blueberry")),
+            ])
+        );
+        assert!(
+            LocalVectorStoreQueryHandler::new(&dir, "docs", "embedding")
+                .search("[0.0, 0.0, 0.0]".to_string())
+                .await?
+                .results
+                .is_empty()
+        );
+
+        Store::close(&file_path).await;
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
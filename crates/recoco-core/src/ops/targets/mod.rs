@@ -11,21 +11,34 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #[cfg(any(
+    feature = "target-http-webhook",
     feature = "target-kuzu",
     feature = "target-ladybug",
+    feature = "target-local-vector-store",
     feature = "target-neo4j",
     feature = "target-postgres",
-    feature = "target-qdrant"
+    feature = "target-qdrant",
+    feature = "target-redis"
 ))]
 pub mod shared;
 
+#[cfg(feature = "target-elasticsearch")]
+pub mod elasticsearch;
+#[cfg(feature = "target-http-webhook")]
+pub mod http_webhook;
 #[cfg(feature = "target-kuzu")]
 pub mod kuzu;
 #[cfg(feature = "target-ladybug")]
 pub mod ladybug;
+#[cfg(feature = "target-local-vector-store")]
+pub mod local_vector_store;
+#[cfg(feature = "target-near-duplicate-index")]
+pub mod near_duplicate_index;
 #[cfg(feature = "target-neo4j")]
 pub mod neo4j;
 #[cfg(feature = "target-postgres")]
 pub mod postgres;
 #[cfg(feature = "target-qdrant")]
 pub mod qdrant;
+#[cfg(feature = "target-redis")]
+pub mod redis;
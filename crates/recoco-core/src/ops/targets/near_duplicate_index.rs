@@ -0,0 +1,681 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+use crate::prelude::*;
+
+use crate::ops::registry::ExecutorFactoryRegistry;
+use crate::setup;
+use sqlx::Row;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use std::path::{Path, PathBuf};
+use utils::similarity::{DEFAULT_NUM_PERMUTATIONS, LshBanding, MinHashSignature};
+
+pub(crate) const DEFAULT_THRESHOLD: f64 = 0.8;
+const SETUP_STATE_META_KEY: &str = "setup_state";
+
+////////////////////////////////////////////////////////////
+// Public Types
+////////////////////////////////////////////////////////////
+
+/// Keeps the MinHash signature (from `ComputeMinHash`) of each exported row, bucketed by LSH
+/// bands, for `DetectNearDuplicate` to look up near-duplicates.
+///
+/// Collected rows have a single key field and a single bytes value field for the signature.
+/// Rows with a null signature aren't indexed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Spec {
+    /// Directory holding the indexes. Each index is a single file in it.
+    path: String,
+    index_name: String,
+    /// Minimum Jaccard similarity the LSH bands are tuned for. Defaults to 0.8.
+    threshold: Option<f64>,
+    /// Signature length given to `ComputeMinHash`. Defaults to 128.
+    num_permutations: Option<usize>,
+}
+
+////////////////////////////////////////////////////////////
+// Common
+////////////////////////////////////////////////////////////
+
+pub(crate) fn index_file_path(dir: impl AsRef<Path>, index_name: &str) -> PathBuf {
+    dir.as_ref().join(format!("{index_name}.sqlite"))
+}
+
+pub(crate) fn key_to_row_id(key: &KeyValue) -> Result<String> {
+    Ok(match serde_json::to_value(key)? {
+        serde_json::Value::String(s) => s,
+        v => v.to_string(),
+    })
+}
+
+async fn connect(file_path: &Path, create_if_missing: bool) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::new()
+        .filename(file_path)
+        .create_if_missing(create_if_missing);
+    let pool = SqlitePoolOptions::new()
+        .connect_with(options)
+        .await
+        .map_err(Error::from)
+        .with_context(|| {
+            format!(
+                "Failed to open near-duplicate index {}",
+                file_path.display()
+            )
+        })?;
+    Ok(pool)
+}
+
+/// A row of the index whose signature is similar to the one queried.
+#[cfg_attr(not(feature = "function-near-dedup"), allow(dead_code))]
+pub(crate) struct IndexMatch {
+    pub id: String,
+    /// Key of the row, as JSON.
+    pub key: serde_json::Value,
+    pub similarity: f64,
+}
+
+/// An opened index. Signatures and band buckets live in SQLite only, so nothing is rebuilt on
+/// open.
+pub(crate) struct IndexStore {
+    pool: SqlitePool,
+    state: SetupState,
+}
+
+/// Indexes opened in this process, by file path. Shared by exports and `DetectNearDuplicate`.
+static OPEN_INDEXES: LazyLock<tokio::sync::Mutex<HashMap<PathBuf, Arc<IndexStore>>>> =
+    LazyLock::new(|| tokio::sync::Mutex::new(HashMap::new()));
+
+impl IndexStore {
+    pub(crate) async fn open(file_path: &Path) -> Result<Arc<Self>> {
+        let mut open_indexes = OPEN_INDEXES.lock().await;
+        if let Some(store) = open_indexes.get(file_path) {
+            return Ok(store.clone());
+        }
+        if !tokio::fs::try_exists(file_path).await? {
+            client_bail!(
+                "Near-duplicate index {} doesn't exist. Please run setup for the flow exporting \
+                 to it first.",
+                file_path.display()
+            );
+        }
+        let pool = connect(file_path, false).await?;
+        let state_json: String = sqlx::query_scalar("SELECT value FROM meta WHERE key = ?")
+            .bind(SETUP_STATE_META_KEY)
+            .fetch_one(&pool)
+            .await?;
+        let state: SetupState = utils::deser::from_json_str(&state_json)?;
+        let store = Arc::new(Self { pool, state });
+        open_indexes.insert(file_path.to_path_buf(), store.clone());
+        Ok(store)
+    }
+
+    /// Close the index if it's open, e.g. before its setup changes.
+    async fn close(file_path: &Path) {
+        let store = OPEN_INDEXES.lock().await.remove(file_path);
+        if let Some(store) = store {
+            store.pool.close().await;
+        }
+    }
+
+    #[cfg_attr(not(feature = "function-near-dedup"), allow(dead_code))]
+    pub(crate) fn threshold(&self) -> f64 {
+        self.state.threshold
+    }
+
+    /// Rows sharing a band with `signature` whose estimated Jaccard similarity is at least
+    /// `threshold`, most similar first.
+    #[cfg_attr(not(feature = "function-near-dedup"), allow(dead_code))]
+    pub(crate) async fn query(
+        &self,
+        signature: &MinHashSignature,
+        threshold: f64,
+    ) -> Result<Vec<IndexMatch>> {
+        let band_keys = self.state.banding.band_keys(signature)?;
+        let sql = format!(
+            "SELECT id, key, signature FROM signatures WHERE id IN \
+             (SELECT id FROM bands WHERE bucket IN ({}))",
+            vec!["?"; band_keys.len()].join(", ")
+        );
+        let mut query = sqlx::query(&sql);
+        for band_key in band_keys.iter() {
+            query = query.bind(band_key.0.as_slice());
+        }
+        let mut matches = Vec::new();
+        for row in query.fetch_all(&self.pool).await? {
+            let similarity = MinHashSignature::from_bytes(row.try_get(2)?)?.jaccard(signature);
+            if similarity >= threshold {
+                let key: &str = row.try_get(1)?;
+                matches.push(IndexMatch {
+                    id: row.try_get(0)?,
+                    key: utils::deser::from_json_str(key)?,
+                    similarity,
+                });
+            }
+        }
+        matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        Ok(matches)
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Setup
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct IndexId {
+    path: String,
+    index_name: String,
+}
+
+impl IndexId {
+    fn file_path(&self) -> PathBuf {
+        index_file_path(&self.path, &self.index_name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct SetupState {
+    key_type: ValueType,
+    banding: LshBanding,
+    threshold: f64,
+}
+
+impl SetupState {
+    fn new(
+        spec: &Spec,
+        key_fields_schema: &[FieldSchema],
+        value_fields_schema: &[FieldSchema],
+    ) -> Result<Self> {
+        let [key_field] = key_fields_schema else {
+            api_bail!(
+                "NearDuplicateIndex expects a single key field, got {}",
+                key_fields_schema.len()
+            );
+        };
+        match value_fields_schema {
+            [field]
+                if matches!(
+                    field.value_type.typ,
+                    ValueType::Basic(BasicValueType::Bytes)
+                ) => {}
+            _ => api_bail!(
+                "NearDuplicateIndex expects a single bytes value field for the MinHash signature"
+            ),
+        }
+        let threshold = spec.threshold.unwrap_or(DEFAULT_THRESHOLD);
+        if !(threshold > 0.0 && threshold <= 1.0) {
+            api_bail!("`threshold` must be in (0, 1]");
+        }
+        let num_permutations = spec.num_permutations.unwrap_or(DEFAULT_NUM_PERMUTATIONS);
+        if num_permutations == 0 {
+            api_bail!("`num_permutations` must be positive");
+        }
+        Ok(Self {
+            key_type: key_field.value_type.typ.without_attrs(),
+            banding: LshBanding::for_threshold(num_permutations, threshold),
+            threshold,
+        })
+    }
+
+    /// Whether rows indexed for `other` can be kept, i.e. keys and buckets are the same.
+    fn is_compatible(&self, other: &Self) -> bool {
+        self.key_type == other.key_type && self.banding == other.banding
+    }
+}
+
+#[derive(Debug)]
+struct SetupChange {
+    drop_existing: bool,
+    /// Whether the index may exist before the change.
+    existing: bool,
+    /// Recorded in the index, when different from the existing one.
+    desired_state: Option<SetupState>,
+}
+
+impl SetupChange {
+    fn from_states(
+        desired: Option<SetupState>,
+        existing: &setup::CombinedState<SetupState>,
+    ) -> Self {
+        let drop_existing = existing.possible_versions().any(|v| {
+            desired
+                .as_ref()
+                .is_none_or(|desired| !desired.is_compatible(v))
+        });
+        let desired_state = desired.filter(|desired| {
+            drop_existing
+                || !existing.always_exists()
+                || existing.possible_versions().any(|v| v != desired)
+        });
+        Self {
+            drop_existing,
+            existing: existing.possible_versions().next().is_some(),
+            desired_state,
+        }
+    }
+
+    async fn apply(&self, file_path: &Path) -> Result<()> {
+        if !self.drop_existing && self.desired_state.is_none() {
+            return Ok(());
+        }
+        IndexStore::close(file_path).await;
+        if self.drop_existing {
+            match tokio::fs::remove_file(file_path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let Some(desired_state) = &self.desired_state else {
+            return Ok(());
+        };
+        if let Some(dir) = file_path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let pool = connect(file_path, true).await?;
+        let mut txn = pool.begin().await?;
+        for stmt in [
+            "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+            "CREATE TABLE IF NOT EXISTS signatures (id TEXT PRIMARY KEY, key TEXT NOT NULL, signature BLOB NOT NULL)",
+            "CREATE TABLE IF NOT EXISTS bands (bucket BLOB NOT NULL, id TEXT NOT NULL, PRIMARY KEY (bucket, id))",
+            "CREATE INDEX IF NOT EXISTS bands_id ON bands (id)",
+        ] {
+            sqlx::query(stmt).execute(&mut *txn).await?;
+        }
+        sqlx::query(
+            "INSERT INTO meta (key, value) VALUES (?, ?) ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        )
+        .bind(SETUP_STATE_META_KEY)
+        .bind(serde_json::to_string(desired_state)?)
+        .execute(&mut *txn)
+        .await?;
+        txn.commit().await?;
+        pool.close().await;
+        Ok(())
+    }
+}
+
+impl setup::ResourceSetupChange for SetupChange {
+    fn describe_changes(&self) -> Vec<setup::ChangeDescription> {
+        let mut result = vec![];
+        if self.drop_existing {
+            result.push(setup::ChangeDescription::Action(
+                "Drop near-duplicate index".to_string(),
+            ));
+        }
+        if let Some(desired_state) = &self.desired_state {
+            result.push(setup::ChangeDescription::Action(format!(
+                "Index MinHash signatures in {} bands of {} rows, for similarity threshold {}",
+                desired_state.banding.num_bands(),
+                desired_state.banding.rows_per_band(),
+                desired_state.threshold
+            )));
+        }
+        result
+    }
+
+    fn change_type(&self) -> setup::SetupChangeType {
+        match (self.drop_existing, &self.desired_state) {
+            (false, None) => setup::SetupChangeType::NoChange,
+            (true, None) => setup::SetupChangeType::Delete,
+            (_, Some(_)) if !self.existing => setup::SetupChangeType::Create,
+            (_, Some(_)) => setup::SetupChangeType::Update,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Deal with mutations
+////////////////////////////////////////////////////////////
+
+struct ExportContext {
+    file_path: PathBuf,
+}
+
+async fn delete_row(conn: &mut sqlx::SqliteConnection, id: &str) -> Result<()> {
+    for stmt in [
+        "DELETE FROM signatures WHERE id = ?",
+        "DELETE FROM bands WHERE id = ?",
+    ] {
+        sqlx::query(stmt).bind(id).execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+impl ExportContext {
+    async fn apply_mutation(&self, mutation: ExportTargetMutation) -> Result<()> {
+        let store = IndexStore::open(&self.file_path).await?;
+        let mut txn = store.pool.begin().await?;
+        for upsert in mutation.upserts.iter() {
+            let id = key_to_row_id(&upsert.key)?;
+            delete_row(&mut txn, &id).await?;
+            let signature = match &upsert.value.fields[0] {
+                Value::Null => continue,
+                Value::Basic(BasicValue::Bytes(bytes)) => bytes,
+                v => client_bail!("Expected a MinHash signature as bytes, got {v:?}"),
+            };
+            let band_keys = store
+                .state
+                .banding
+                .band_keys(&MinHashSignature::from_bytes(signature)?)?;
+            sqlx::query("INSERT INTO signatures (id, key, signature) VALUES (?, ?, ?)")
+                .bind(&id)
+                .bind(serde_json::to_string(&upsert.key)?)
+                .bind(signature.as_ref())
+                .execute(&mut *txn)
+                .await?;
+            for band_key in band_keys {
+                sqlx::query("INSERT OR IGNORE INTO bands (bucket, id) VALUES (?, ?)")
+                    .bind(band_key.0.as_slice())
+                    .bind(&id)
+                    .execute(&mut *txn)
+                    .await?;
+            }
+        }
+        for delete in mutation.deletes.iter() {
+            delete_row(&mut txn, &key_to_row_id(&delete.key)?).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Factory implementation
+////////////////////////////////////////////////////////////
+
+struct Factory;
+
+#[async_trait]
+impl TargetFactoryBase for Factory {
+    type Spec = Spec;
+    type DeclarationSpec = ();
+    type SetupState = SetupState;
+    type SetupChange = SetupChange;
+    type SetupKey = IndexId;
+    type ExportContext = ExportContext;
+
+    fn name(&self) -> &str {
+        "NearDuplicateIndex"
+    }
+
+    async fn build(
+        self: Arc<Self>,
+        data_collections: Vec<TypedExportDataCollectionSpec<Self>>,
+        _declarations: Vec<()>,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<(
+        Vec<TypedExportDataCollectionBuildOutput<Self>>,
+        Vec<(IndexId, SetupState)>,
+    )> {
+        let data_coll_output = data_collections
+            .into_iter()
+            .map(|d| {
+                if d.spec.index_name.is_empty() {
+                    api_bail!("`index_name` must not be empty");
+                }
+                let desired_setup_state =
+                    SetupState::new(&d.spec, &d.key_fields_schema, &d.value_fields_schema)?;
+                let index_id = IndexId {
+                    path: d.spec.path,
+                    index_name: d.spec.index_name,
+                };
+                let export_context = Arc::new(ExportContext {
+                    file_path: index_id.file_path(),
+                });
+                Ok(TypedExportDataCollectionBuildOutput {
+                    export_context: Box::pin(async move { Ok(export_context) }),
+                    setup_key: index_id,
+                    desired_setup_state,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((data_coll_output, vec![]))
+    }
+
+    async fn diff_setup_states(
+        &self,
+        _key: IndexId,
+        desired: Option<SetupState>,
+        existing: setup::CombinedState<SetupState>,
+        _flow_instance_ctx: Arc<FlowInstanceContext>,
+    ) -> Result<Self::SetupChange> {
+        Ok(SetupChange::from_states(desired, &existing))
+    }
+
+    fn check_state_compatibility(
+        &self,
+        desired: &SetupState,
+        existing: &SetupState,
+    ) -> Result<SetupStateCompatibility> {
+        Ok(if desired.is_compatible(existing) {
+            SetupStateCompatibility::Compatible
+        } else {
+            SetupStateCompatibility::NotCompatible
+        })
+    }
+
+    fn describe_resource(&self, key: &IndexId) -> Result<String> {
+        Ok(format!(
+            "Near-duplicate index {}",
+            key.file_path().display()
+        ))
+    }
+
+    async fn apply_mutation(
+        &self,
+        mutations: Vec<ExportTargetMutationWithContext<'async_trait, ExportContext>>,
+    ) -> Result<()> {
+        for mutation_w_ctx in mutations.into_iter() {
+            mutation_w_ctx
+                .export_context
+                .apply_mutation(mutation_w_ctx.mutation)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn apply_setup_changes(
+        &self,
+        setup_change: Vec<TypedResourceSetupChangeItem<'async_trait, Self>>,
+        _context: Arc<FlowInstanceContext>,
+    ) -> Result<()> {
+        for change in setup_change.iter() {
+            change.setup_change.apply(&change.key.file_path()).await?;
+        }
+        Ok(())
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+
+    /// Sets up an index in `dir` and exports the rows with string keys into it.
+    pub(crate) async fn build_index(
+        dir: &Path,
+        index_name: &str,
+        threshold: f64,
+        rows: Vec<(&str, Vec<u8>)>,
+    ) -> Result<PathBuf> {
+        let spec = Spec {
+            path: dir.to_string_lossy().to_string(),
+            index_name: index_name.to_string(),
+            threshold: Some(threshold),
+            num_permutations: None,
+        };
+        let state = SetupState::new(
+            &spec,
+            &[FieldSchema::new(
+                "id",
+                make_output_type(BasicValueType::Str),
+            )],
+            &[FieldSchema::new(
+                "signature",
+                make_output_type(BasicValueType::Bytes),
+            )],
+        )?;
+        let file_path = index_file_path(dir, index_name);
+        SetupChange::from_states(
+            Some(state),
+            &setup::CombinedState {
+                current: None,
+                staging: vec![],
+                legacy_state_key: None,
+            },
+        )
+        .apply(&file_path)
+        .await?;
+        ExportContext {
+            file_path: file_path.clone(),
+        }
+        .apply_mutation(ExportTargetMutation {
+            upserts: rows
+                .into_iter()
+                .map(|(id, signature)| ExportTargetUpsertEntry {
+                    key: KeyValue::from_single_part(id.to_string()),
+                    additional_key: serde_json::Value::Null,
+                    value: FieldValues {
+                        fields: vec![BasicValue::from(signature).into()],
+                    },
+                })
+                .collect(),
+            deletes: vec![],
+        })
+        .await?;
+        Ok(file_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::similarity::MinHasher;
+
+    const TEXT: &str = "Recoco keeps derived data in sync with its sources, reprocessing only \
+                        the rows whose inputs changed since the previous run of the flow.";
+
+    fn signature(text: &str) -> MinHashSignature {
+        MinHasher::new(DEFAULT_NUM_PERMUTATIONS, 0).text_signature(text, 3)
+    }
+
+    fn matched_ids(matches: &[IndexMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_upsert_delete_and_query() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!(
+            "recoco-near-duplicate-index-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let file_path = test_utils::build_index(
+            &dir,
+            "docs",
+            0.7,
+            vec![
+                ("a", signature(TEXT).to_bytes()),
+                ("b", signature(&format!("{TEXT} Thanks!")).to_bytes()),
+                (
+                    "c",
+                    signature("A completely different document.").to_bytes(),
+                ),
+            ],
+        )
+        .await?;
+        let export_context = ExportContext {
+            file_path: file_path.clone(),
+        };
+
+        let store = IndexStore::open(&file_path).await?;
+        assert_eq!(store.threshold(), 0.7);
+        let matches = store.query(&signature(TEXT), 0.7).await?;
+        assert_eq!(matched_ids(&matches), vec!["a", "b"]);
+        assert_eq!(matches[0].key, serde_json::json!("a"));
+        assert_eq!(matches[0].similarity, 1.0);
+
+        // Changed and deleted rows leave their buckets.
+        export_context
+            .apply_mutation(ExportTargetMutation {
+                upserts: vec![ExportTargetUpsertEntry {
+                    key: KeyValue::from_single_part("b".to_string()),
+                    additional_key: serde_json::Value::Null,
+                    value: FieldValues {
+                        fields: vec![Value::Null],
+                    },
+                }],
+                deletes: vec![ExportTargetDeleteEntry {
+                    key: KeyValue::from_single_part("c".to_string()),
+                    additional_key: serde_json::Value::Null,
+                }],
+            })
+            .await?;
+        assert_eq!(
+            matched_ids(&store.query(&signature(TEXT), 0.7).await?),
+            vec!["a"]
+        );
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM bands")
+            .fetch_one(&store.pool)
+            .await?;
+        assert_eq!(count as usize, store.state.banding.num_bands());
+
+        // Rows are kept across reopening.
+        IndexStore::close(&file_path).await;
+        let store = IndexStore::open(&file_path).await?;
+        assert_eq!(
+            matched_ids(&store.query(&signature(TEXT), 0.7).await?),
+            vec!["a"]
+        );
+
+        // Other bands can't reuse the buckets, so the index is rebuilt.
+        let spec = Spec {
+            path: dir.to_string_lossy().to_string(),
+            index_name: "docs".to_string(),
+            threshold: Some(0.3),
+            num_permutations: None,
+        };
+        let key_fields_schema = [FieldSchema::new(
+            "id",
+            make_output_type(BasicValueType::Str),
+        )];
+        let value_fields_schema = [FieldSchema::new(
+            "signature",
+            make_output_type(BasicValueType::Bytes),
+        )];
+        let desired = SetupState::new(&spec, &key_fields_schema, &value_fields_schema)?;
+        assert!(!desired.is_compatible(&store.state));
+        let change = SetupChange::from_states(
+            Some(desired),
+            &setup::CombinedState::current(store.state.clone()),
+        );
+        assert!(change.drop_existing);
+        assert_eq!(
+            setup::ResourceSetupChange::change_type(&change),
+            setup::SetupChangeType::Update
+        );
+        change.apply(&file_path).await?;
+        let store = IndexStore::open(&file_path).await?;
+        assert!(store.query(&signature(TEXT), 0.3).await?.is_empty());
+
+        assert!(SetupState::new(&spec, &key_fields_schema, &key_fields_schema).is_err());
+
+        IndexStore::close(&file_path).await;
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
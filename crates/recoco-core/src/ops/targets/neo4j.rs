@@ -228,6 +228,7 @@ fn basic_value_to_bolt(value: &BasicValue, schema: &BasicValueType) -> Result<Bo
             _ => internal_bail!("Non-vector type got vector value: {}", schema),
         },
         BasicValue::Json(v) => json_value_to_bolt_value(v)?,
+        BasicValue::SparseVector(v) => json_value_to_bolt_value(&serde_json::to_value(v)?)?,
         BasicValue::UnionVariant { tag_id, value } => match schema {
             BasicValueType::Union(s) => {
                 let typ = s
@@ -153,6 +153,9 @@ fn bind_value_field<'arg>(
                     builder.push_bind(sqlx::types::Json(v));
                 }
             },
+            BasicValue::SparseVector(v) => {
+                builder.push_bind(sqlx::types::Json(v));
+            }
             BasicValue::UnionVariant { .. } => {
                 builder.push_bind(sqlx::types::Json(
                     utils::str_sanitize::ZeroCodeStrippedSerialize(TypedValue {
@@ -543,7 +546,7 @@ fn to_column_type_sql(column_type: &ValueType) -> String {
                     "jsonb".into()
                 }
             }
-            BasicValueType::Union(_) => "jsonb".into(),
+            BasicValueType::SparseVector | BasicValueType::Union(_) => "jsonb".into(),
         },
         _ => "jsonb".into(),
     }
@@ -18,9 +18,10 @@ use crate::setup;
 use qdrant_client::Qdrant;
 use qdrant_client::qdrant::{
     CreateCollectionBuilder, DeletePointsBuilder, DenseVector, Distance, HnswConfigDiffBuilder,
-    MultiDenseVector, MultiVectorComparator, MultiVectorConfigBuilder, NamedVectors, PointId,
-    PointStruct, PointsIdsList, UpsertPointsBuilder, Value as QdrantValue, Vector as QdrantVector,
-    VectorParamsBuilder, VectorsConfigBuilder,
+    Modifier, MultiDenseVector, MultiVectorComparator, MultiVectorConfigBuilder, NamedVectors,
+    PointId, PointStruct, PointsIdsList, SparseVectorParamsBuilder, SparseVectorsConfigBuilder,
+    UpsertPointsBuilder, Value as QdrantValue, Vector as QdrantVector, VectorParamsBuilder,
+    VectorsConfigBuilder,
 };
 
 const DEFAULT_VECTOR_SIMILARITY_METRIC: spec::VectorSimilarityMetric =
@@ -44,6 +45,9 @@ pub struct ConnectionSpec {
 pub struct Spec {
     connection: Option<spec::AuthEntryReference<ConnectionSpec>>,
     collection_name: String,
+    /// Sparse vector fields for which Qdrant applies IDF at query time, e.g. BM25 term weights.
+    #[serde(default)]
+    idf_sparse_vector_fields: Vec<String>,
 }
 
 ////////////////////////////////////////////////////////////
@@ -58,13 +62,15 @@ struct FieldInfo {
 enum VectorShape {
     Vector(usize),
     MultiVector(usize),
+    SparseVector,
 }
 
 impl VectorShape {
-    fn vector_size(&self) -> usize {
+    fn dense_vector_size(&self) -> Option<usize> {
         match self {
-            VectorShape::Vector(size) => *size,
-            VectorShape::MultiVector(size) => *size,
+            VectorShape::Vector(size) => Some(*size),
+            VectorShape::MultiVector(size) => Some(*size),
+            VectorShape::SparseVector => None,
         }
     }
 
@@ -97,6 +103,9 @@ fn parse_vector_shape(typ: &schema::ValueType) -> Option<VectorShape> {
         schema::ValueType::Basic(schema::BasicValueType::Vector(vector_schema)) => {
             parse_vector_schema_shape(vector_schema)
         }
+        schema::ValueType::Basic(schema::BasicValueType::SparseVector) => {
+            Some(VectorShape::SparseVector)
+        }
         _ => None,
     }
 }
@@ -130,6 +139,16 @@ fn encode_multi_dense_vector(v: &BasicValue) -> Result<MultiDenseVector> {
     Ok(vecs.into())
 }
 
+fn encode_sparse_vector(v: &BasicValue) -> Result<QdrantVector> {
+    match v {
+        BasicValue::SparseVector(v) => Ok(QdrantVector::new_sparse(
+            v.indices.clone(),
+            v.values.clone(),
+        )),
+        _ => client_bail!("Expected a sparse vector field, got {:?}", v),
+    }
+}
+
 fn embedding_metric_to_qdrant(metric: spec::VectorSimilarityMetric) -> Result<Distance> {
     Ok(match metric {
         spec::VectorSimilarityMetric::CosineSimilarity => Distance::Cosine,
@@ -159,11 +178,21 @@ struct VectorDef {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     hnsw_ef_construction: Option<u32>,
 }
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+struct SparseVectorDef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modifier: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct SetupState {
     #[serde(default)]
     vectors: BTreeMap<String, VectorDef>,
 
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    sparse_vectors: BTreeMap<String, SparseVectorDef>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     unsupported_vector_fields: Vec<(String, ValueType)>,
 }
@@ -192,6 +221,21 @@ impl setup::ResourceSetupChange for SetupChange {
                         name, vector_def.vector_size, vector_def.metric
                     )
                 })
+                .chain(
+                    add_collection
+                        .sparse_vectors
+                        .iter()
+                        .map(|(name, sparse_vector_def)| {
+                            format!(
+                                "{}[sparse]{}",
+                                name,
+                                sparse_vector_def
+                                    .modifier
+                                    .as_ref()
+                                    .map_or_else(String::new, |m| format!(", {m}"))
+                            )
+                        }),
+                )
                 .collect::<Vec<_>>()
                 .join("; ");
             result.push(setup::ChangeDescription::Action(format!(
@@ -266,6 +310,19 @@ impl SetupChange {
                 }
                 builder = builder.vectors_config(vectors_config);
             }
+            if !add_collection.sparse_vectors.is_empty() {
+                let mut sparse_vectors_config = SparseVectorsConfigBuilder::default();
+                for (name, sparse_vector_def) in add_collection.sparse_vectors.iter() {
+                    let mut params = SparseVectorParamsBuilder::default();
+                    if let Some(modifier) = &sparse_vector_def.modifier {
+                        params = params.modifier(Modifier::from_str_name(modifier).ok_or_else(
+                            || client_error!("unrecognized sparse vector modifier: {}", modifier),
+                        )?);
+                    }
+                    sparse_vectors_config.add_named_vector_params(name, params);
+                }
+                builder = builder.sparse_vectors_config(sparse_vectors_config);
+            }
             qdrant_client.create_collection(builder).await?;
         }
         Ok(())
@@ -350,6 +407,7 @@ fn values_to_payload(
                         VectorShape::MultiVector(_) => {
                             encode_multi_dense_vector(basic_value)?.into()
                         }
+                        VectorShape::SparseVector => encode_sparse_vector(basic_value)?,
                     },
                     _ => {
                         client_bail!("Expected a vector field, got {:?}", value);
@@ -413,15 +471,23 @@ impl TargetFactoryBase for Factory {
 
                 let mut fields_info = Vec::<FieldInfo>::new();
                 let mut vector_def = BTreeMap::<String, VectorDef>::new();
+                let mut sparse_vector_def = BTreeMap::<String, SparseVectorDef>::new();
                 let mut unsupported_vector_fields = Vec::<(String, ValueType)>::new();
 
                 for field in d.value_fields_schema.iter() {
                     let vector_shape = parse_vector_shape(&field.value_type.typ);
-                    if let Some(vector_shape) = &vector_shape {
+                    if let Some(VectorShape::SparseVector) = &vector_shape {
+                        sparse_vector_def.insert(
+                            field.name.clone(),
+                            SparseVectorDef { modifier: None },
+                        );
+                    } else if let Some(vector_shape) = &vector_shape
+                        && let Some(vector_size) = vector_shape.dense_vector_size()
+                    {
                         vector_def.insert(
                             field.name.clone(),
                             VectorDef {
-                                vector_size: vector_shape.vector_size(),
+                                vector_size,
                                 metric: DEFAULT_VECTOR_SIMILARITY_METRIC,
                                 multi_vector_comparator: vector_shape.multi_vector_comparator().map(|s| s.as_str_name().to_string()),
                                 hnsw_m: None,
@@ -441,6 +507,18 @@ impl TargetFactoryBase for Factory {
                     });
                 }
 
+                for field_name in d.spec.idf_sparse_vector_fields.iter() {
+                    match sparse_vector_def.get_mut(field_name) {
+                        Some(sparse_vector_def) => {
+                            sparse_vector_def.modifier = Some(Modifier::Idf.as_str_name().to_string());
+                        }
+                        None => api_bail!(
+                            "Field `{}` in `idf_sparse_vector_fields` is expected to be a sparse vector field",
+                            field_name
+                        ),
+                    }
+                }
+
                 if !d.index_options.fts_indexes.is_empty() {
                     api_bail!("FTS indexes are not supported for Qdrant target");
                 }
@@ -497,6 +575,7 @@ impl TargetFactoryBase for Factory {
                     },
                     desired_setup_state: SetupState {
                         vectors: vector_def,
+                        sparse_vectors: sparse_vector_def,
                         unsupported_vector_fields,
                     },
                 })
@@ -530,7 +609,7 @@ impl TargetFactoryBase for Factory {
             !existing.always_exists()
                 || existing
                     .possible_versions()
-                    .any(|v| v.vectors != state.vectors)
+                    .any(|v| v.vectors != state.vectors || v.sparse_vectors != state.sparse_vectors)
         });
         let delete_collection = existing.possible_versions().next().is_some()
             && (!desired_exists || add_collection.is_some());
@@ -545,11 +624,15 @@ impl TargetFactoryBase for Factory {
         desired: &SetupState,
         existing: &SetupState,
     ) -> Result<SetupStateCompatibility> {
-        Ok(if desired.vectors == existing.vectors {
-            SetupStateCompatibility::Compatible
-        } else {
-            SetupStateCompatibility::NotCompatible
-        })
+        Ok(
+            if desired.vectors == existing.vectors
+                && desired.sparse_vectors == existing.sparse_vectors
+            {
+                SetupStateCompatibility::Compatible
+            } else {
+                SetupStateCompatibility::NotCompatible
+            },
+        )
     }
 
     fn describe_resource(&self, key: &CollectionKey) -> Result<String> {
@@ -0,0 +1,1252 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ops::sdk::*;
+use crate::prelude::*;
+
+use super::shared::table_columns::{TableColumnsSchema, check_table_compatibility};
+use crate::ops::registry::ExecutorFactoryRegistry;
+use crate::setup;
+use redis::aio::ConnectionManager;
+use std::fmt::Write;
+use std::time::Duration;
+
+const DEFAULT_URL: &str = "redis://localhost:6379";
+/// Separator between parts of the primary key in keys, when there's no key template.
+const KEY_PART_SEPARATOR: &str = ":";
+/// Escape character of characters in key parts that would make keys ambiguous.
+const KEY_ESCAPE_CHAR: char = '%';
+/// Number of keys asked for in each `SCAN` when removing keys of a target.
+const SCAN_BATCH_SIZE: usize = 1000;
+/// Set of key prefixes of all targets on the server, so no target's prefix extends another's,
+/// which would make dropping a target remove keys of the other.
+const KEY_PREFIX_REGISTRY_KEY: &str = "recoco:redis_target_key_prefixes";
+
+////////////////////////////////////////////////////////////
+// Public Types
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectionSpec {
+    /// The URL of the server, e.g. `redis://localhost:6379`.
+    url: String,
+}
+
+/// How values are stored under each key.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ValueEncoding {
+    /// A hash with one field per field of the row. Strings and bytes are stored as is,
+    /// vectors in vector indexes as little-endian `f32`s, others as JSON.
+    #[default]
+    Hash,
+    /// A RedisJSON document. Requires the RedisJSON module.
+    Json,
+    /// A string holding the row serialized as JSON.
+    Blob,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Spec {
+    connection: Option<spec::AuthEntryReference<ConnectionSpec>>,
+    /// Prefix of all keys written by the target, e.g. `summary:`. It can't extend or be extended
+    /// by the prefix of another target on the same server.
+    key_prefix: String,
+    /// Template of keys after the prefix, with primary key fields in braces, e.g.
+    /// `{repo}/{path}`. Defaults to primary key fields joined by `:`. In field values, `%`, `:`
+    /// and characters of the template are percent-encoded, so distinct keys never collide.
+    key_template: Option<String>,
+    #[serde(default)]
+    encoding: ValueEncoding,
+    /// Time to live of each key, refreshed whenever the row is updated.
+    ttl: Option<Duration>,
+}
+
+////////////////////////////////////////////////////////////
+// Common
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum KeySegment {
+    Literal(String),
+    /// Position of a primary key field.
+    Field(usize),
+}
+
+/// Keys of rows: the prefix followed by a rendered template.
+#[derive(Debug, Clone)]
+struct KeyTemplate {
+    prefix: String,
+    segments: Vec<KeySegment>,
+    /// Characters percent-encoded in field values.
+    escaped_chars: Vec<char>,
+}
+
+impl KeyTemplate {
+    fn new(
+        prefix: &str,
+        template: Option<&str>,
+        key_fields_schema: &[FieldSchema],
+    ) -> Result<Self> {
+        let Some(template) = template else {
+            let segments = (0..key_fields_schema.len())
+                .flat_map(|i| {
+                    (i > 0)
+                        .then(|| KeySegment::Literal(KEY_PART_SEPARATOR.to_string()))
+                        .into_iter()
+                        .chain(std::iter::once(KeySegment::Field(i)))
+                })
+                .collect();
+            return Ok(Self::from_segments(prefix, segments));
+        };
+        let mut segments = vec![];
+        let mut used_fields = HashSet::new();
+        let mut rest = template;
+        while !rest.is_empty() {
+            let Some(start) = rest.find(['{', '}']) else {
+                segments.push(KeySegment::Literal(rest.to_string()));
+                break;
+            };
+            if rest[start..].starts_with('}') {
+                api_bail!("Unmatched `}}` in key template `{template}`");
+            }
+            if start > 0 {
+                segments.push(KeySegment::Literal(rest[..start].to_string()));
+            }
+            let Some(len) = rest[start + 1..].find('}') else {
+                api_bail!("Unmatched `{{` in key template `{template}`");
+            };
+            let field_name = &rest[start + 1..start + 1 + len];
+            let Some(field_idx) = key_fields_schema.iter().position(|f| f.name == field_name)
+            else {
+                api_bail!(
+                    "Field `{field_name}` in key template `{template}` is not a primary key field"
+                );
+            };
+            used_fields.insert(field_idx);
+            segments.push(KeySegment::Field(field_idx));
+            rest = &rest[start + len + 2..];
+        }
+        if let Some(field) = key_fields_schema
+            .iter()
+            .enumerate()
+            .find(|(i, _)| !used_fields.contains(i))
+            .map(|(_, f)| f)
+        {
+            api_bail!(
+                "Primary key field `{}` is missing in key template `{template}`, so keys may collide",
+                field.name
+            );
+        }
+        Ok(Self::from_segments(prefix, segments))
+    }
+
+    fn from_segments(prefix: &str, segments: Vec<KeySegment>) -> Self {
+        let mut escaped_chars = vec![KEY_ESCAPE_CHAR];
+        escaped_chars.extend(KEY_PART_SEPARATOR.chars());
+        for segment in segments.iter() {
+            if let KeySegment::Literal(s) = segment {
+                escaped_chars.extend(s.chars());
+            }
+        }
+        escaped_chars.sort_unstable();
+        escaped_chars.dedup();
+        Self {
+            prefix: prefix.to_string(),
+            segments,
+            escaped_chars,
+        }
+    }
+
+    fn push_escaped(&self, result: &mut String, s: &str) {
+        for c in s.chars() {
+            if self.escaped_chars.binary_search(&c).is_ok() {
+                let mut buf = [0; 4];
+                for b in c.encode_utf8(&mut buf).bytes() {
+                    write!(result, "%{b:02X}").expect("writing to a string never fails");
+                }
+            } else {
+                result.push(c);
+            }
+        }
+    }
+
+    fn render(&self, key: &KeyValue) -> String {
+        let mut result = self.prefix.clone();
+        for segment in self.segments.iter() {
+            match segment {
+                KeySegment::Literal(s) => result.push_str(s),
+                KeySegment::Field(i) => {
+                    if let Some(part) = key.iter().nth(*i) {
+                        for (j, s) in part.to_strs().iter().enumerate() {
+                            if j > 0 {
+                                result.push_str(KEY_PART_SEPARATOR);
+                            }
+                            self.push_escaped(&mut result, s);
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Pattern for `SCAN MATCH` matching all keys with the prefix.
+fn prefix_match_pattern(prefix: &str) -> String {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('*');
+    pattern
+}
+
+/// A prefix of another target that extends or is extended by the prefix.
+fn overlapping_key_prefix<'a>(key_prefix: &str, registered: &'a [String]) -> Option<&'a str> {
+    registered
+        .iter()
+        .map(|p| p.as_str())
+        .find(|p| *p != key_prefix && (p.starts_with(key_prefix) || key_prefix.starts_with(p)))
+}
+
+fn get_client(
+    auth_entry: &Option<spec::AuthEntryReference<ConnectionSpec>>,
+    auth_registry: &AuthRegistry,
+) -> Result<redis::Client> {
+    let url = match auth_entry {
+        Some(auth_entry) => auth_registry.get(auth_entry)?.url,
+        None => DEFAULT_URL.to_string(),
+    };
+    Ok(redis::Client::open(url)?)
+}
+
+fn vector_dimension(typ: &ValueType) -> Option<usize> {
+    match typ {
+        ValueType::Basic(BasicValueType::Vector(vector_schema)) => {
+            match *vector_schema.element_type {
+                BasicValueType::Float32 | BasicValueType::Float64 | BasicValueType::Int64 => {
+                    vector_schema.dimension
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn encode_vector_bytes(v: &BasicValue) -> Result<Vec<u8>> {
+    match v {
+        BasicValue::Vector(v) => v
+            .iter()
+            .map(|elem| {
+                Ok(match elem {
+                    BasicValue::Float32(f) => *f,
+                    BasicValue::Float64(f) => *f as f32,
+                    BasicValue::Int64(i) => *i as f32,
+                    _ => client_bail!("Unsupported vector type: {:?}", elem.kind()),
+                }
+                .to_le_bytes())
+            })
+            .flatten_ok()
+            .collect(),
+        _ => client_bail!("Expected a vector field, got {:?}", v),
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Setup
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct TargetKey {
+    connection: Option<spec::AuthEntryReference<ConnectionSpec>>,
+    key_prefix: String,
+}
+
+impl TargetKey {
+    /// Name of the search index over keys of the target.
+    fn index_name(&self) -> String {
+        format!("{}idx", self.key_prefix)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct VectorFieldIndex {
+    dimension: usize,
+    #[serde(flatten)]
+    index_def: spec::VectorIndexDef,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SetupState {
+    #[serde(flatten)]
+    columns: TableColumnsSchema<ValueType>,
+    encoding: ValueEncoding,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_template: Option<String>,
+    /// Vector fields in the search index over keys of the target, by field name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    vector_indexes: BTreeMap<String, VectorFieldIndex>,
+}
+
+impl SetupState {
+    fn new(
+        spec: &Spec,
+        key_fields_schema: &[FieldSchema],
+        value_fields_schema: &[FieldSchema],
+        index_options: &IndexOptions,
+    ) -> Result<Self> {
+        if !index_options.fts_indexes.is_empty() {
+            api_bail!("FTS indexes are not supported for Redis target");
+        }
+        if !index_options.vector_indexes.is_empty() && spec.encoding == ValueEncoding::Blob {
+            api_bail!("Vector indexes are not supported for Redis target with `Blob` encoding");
+        }
+        let mut vector_indexes = BTreeMap::new();
+        for vector_index in index_options.vector_indexes.iter() {
+            let dimension = match value_fields_schema
+                .iter()
+                .find(|f| f.name == vector_index.field_name)
+            {
+                Some(field) => match vector_dimension(&field.value_type.typ) {
+                    Some(dimension) => dimension,
+                    None => api_bail!(
+                        "Field `{}` specified in vector index is expected to be a number vector with fixed size, actual type: {}",
+                        vector_index.field_name,
+                        field.value_type.typ
+                    ),
+                },
+                None => api_bail!(
+                    "Field `{}` specified in vector index is not found",
+                    vector_index.field_name
+                ),
+            };
+            if let Some(spec::VectorIndexMethod::IvfFlat { .. }) = &vector_index.method {
+                api_bail!(
+                    "IVFFlat vector index method is not supported for field `{}`. Only HNSW is supported.",
+                    vector_index.field_name
+                );
+            }
+            let field_index = VectorFieldIndex {
+                dimension,
+                index_def: vector_index.clone(),
+            };
+            if vector_indexes
+                .insert(vector_index.field_name.clone(), field_index)
+                .is_some()
+            {
+                api_bail!(
+                    "Field `{}` specified more than once in vector index definition",
+                    vector_index.field_name
+                );
+            }
+        }
+        Ok(Self {
+            columns: TableColumnsSchema {
+                key_columns: key_fields_schema
+                    .iter()
+                    .map(|f| (f.name.clone(), f.value_type.typ.without_attrs()))
+                    .collect(),
+                value_columns: value_fields_schema
+                    .iter()
+                    .map(|f| (f.name.clone(), f.value_type.typ.without_attrs()))
+                    .collect(),
+            },
+            encoding: spec.encoding,
+            key_template: spec.key_template.clone(),
+            vector_indexes,
+        })
+    }
+
+    /// Whether keys written with the other state are laid out and encoded the same way.
+    fn is_key_layout_compatible(&self, other: &SetupState) -> bool {
+        self.encoding == other.encoding
+            && self.key_template == other.key_template
+            && self.columns.key_columns == other.columns.key_columns
+    }
+
+    /// `FT.CREATE` arguments after the index name, or `None` without vector indexes.
+    fn create_index_args(&self, key_prefix: &str) -> Option<Vec<String>> {
+        if self.vector_indexes.is_empty() {
+            return None;
+        }
+        let mut args = vec![
+            "ON".to_string(),
+            match self.encoding {
+                ValueEncoding::Json => "JSON",
+                ValueEncoding::Hash | ValueEncoding::Blob => "HASH",
+            }
+            .to_string(),
+            "PREFIX".to_string(),
+            "1".to_string(),
+            key_prefix.to_string(),
+            "SCHEMA".to_string(),
+        ];
+        for (field_name, field_index) in self.vector_indexes.iter() {
+            if self.encoding == ValueEncoding::Json {
+                args.extend([format!("$.{field_name}"), "AS".to_string()]);
+            }
+            let mut attributes = vec![
+                "TYPE".to_string(),
+                "FLOAT32".to_string(),
+                "DIM".to_string(),
+                field_index.dimension.to_string(),
+                "DISTANCE_METRIC".to_string(),
+                match field_index.index_def.metric {
+                    spec::VectorSimilarityMetric::CosineSimilarity => "COSINE",
+                    spec::VectorSimilarityMetric::L2Distance => "L2",
+                    spec::VectorSimilarityMetric::InnerProduct => "IP",
+                }
+                .to_string(),
+            ];
+            let algorithm = match &field_index.index_def.method {
+                Some(spec::VectorIndexMethod::Hnsw { m, ef_construction }) => {
+                    if let Some(m) = m {
+                        attributes.extend(["M".to_string(), m.to_string()]);
+                    }
+                    if let Some(ef_construction) = ef_construction {
+                        attributes
+                            .extend(["EF_CONSTRUCTION".to_string(), ef_construction.to_string()]);
+                    }
+                    "HNSW"
+                }
+                // Rejected when building the state.
+                Some(spec::VectorIndexMethod::IvfFlat { .. }) | None => "FLAT",
+            };
+            args.extend([
+                field_name.clone(),
+                "VECTOR".to_string(),
+                algorithm.to_string(),
+                attributes.len().to_string(),
+            ]);
+            args.extend(attributes);
+        }
+        Some(args)
+    }
+}
+
+#[derive(Debug)]
+struct SetupChange {
+    /// Add the prefix to the registry, rejecting it if it overlaps with another target's.
+    register_key_prefix: bool,
+    /// Remove the prefix from the registry, when the target is dropped.
+    unregister_key_prefix: bool,
+    /// Remove all keys with the prefix, e.g. when the target is dropped.
+    delete_keys: bool,
+    drop_index: bool,
+    /// `FT.CREATE` arguments after the index name.
+    create_index: Option<Vec<String>>,
+}
+
+impl SetupChange {
+    fn from_states(
+        key: &TargetKey,
+        desired: Option<&SetupState>,
+        existing: &setup::CombinedState<SetupState>,
+    ) -> Self {
+        let delete_keys = existing
+            .possible_versions()
+            .any(|v| desired.is_none_or(|desired| !desired.is_key_layout_compatible(v)));
+        let desired_index_args = desired.and_then(|d| d.create_index_args(&key.key_prefix));
+        let index_changed = !existing.always_exists()
+            || existing
+                .possible_versions()
+                .any(|v| v.create_index_args(&key.key_prefix) != desired_index_args);
+        let (drop_index, create_index) = if index_changed {
+            (
+                existing
+                    .possible_versions()
+                    .any(|v| !v.vector_indexes.is_empty()),
+                desired_index_args,
+            )
+        } else {
+            (false, None)
+        };
+        Self {
+            register_key_prefix: desired.is_some() && !existing.always_exists(),
+            unregister_key_prefix: desired.is_none()
+                && existing.possible_versions().next().is_some(),
+            delete_keys,
+            drop_index,
+            create_index,
+        }
+    }
+
+    async fn apply(&self, key: &TargetKey, conn: &mut ConnectionManager) -> Result<()> {
+        if self.register_key_prefix {
+            let registered: Vec<String> = redis::cmd("SMEMBERS")
+                .arg(KEY_PREFIX_REGISTRY_KEY)
+                .query_async(conn)
+                .await?;
+            if let Some(other) = overlapping_key_prefix(&key.key_prefix, &registered) {
+                client_bail!(
+                    "Key prefix `{}` overlaps with `{other}` of another Redis target, so dropping either would remove keys of the other",
+                    key.key_prefix
+                );
+            }
+            redis::cmd("SADD")
+                .arg(KEY_PREFIX_REGISTRY_KEY)
+                .arg(&key.key_prefix)
+                .query_async::<()>(conn)
+                .await?;
+        }
+        let index_name = key.index_name();
+        if self.drop_index {
+            let result: redis::RedisResult<()> = redis::cmd("FT.DROPINDEX")
+                .arg(&index_name)
+                .query_async(conn)
+                .await;
+            if let Err(e) = result {
+                // The index may be gone already.
+                if !e.to_string().to_lowercase().contains("unknown index") {
+                    return Err(e.into());
+                }
+            }
+        }
+        if self.delete_keys {
+            let pattern = prefix_match_pattern(&key.key_prefix);
+            let mut cursor = 0u64;
+            loop {
+                let (next_cursor, mut keys): (u64, Vec<String>) = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(SCAN_BATCH_SIZE)
+                    .query_async(conn)
+                    .await?;
+                keys.retain(|k| k != KEY_PREFIX_REGISTRY_KEY);
+                if !keys.is_empty() {
+                    redis::cmd("UNLINK")
+                        .arg(&keys)
+                        .query_async::<()>(conn)
+                        .await?;
+                }
+                if next_cursor == 0 {
+                    break;
+                }
+                cursor = next_cursor;
+            }
+        }
+        if let Some(create_index) = &self.create_index {
+            redis::cmd("FT.CREATE")
+                .arg(&index_name)
+                .arg(create_index)
+                .query_async::<()>(conn)
+                .await
+                .map_err(Error::from)
+                .with_context(|| format!("Creating search index `{index_name}`"))?;
+        }
+        if self.unregister_key_prefix {
+            redis::cmd("SREM")
+                .arg(KEY_PREFIX_REGISTRY_KEY)
+                .arg(&key.key_prefix)
+                .query_async::<()>(conn)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+impl setup::ResourceSetupChange for SetupChange {
+    fn describe_changes(&self) -> Vec<setup::ChangeDescription> {
+        let mut result = vec![];
+        if self.register_key_prefix {
+            result.push(setup::ChangeDescription::Action(
+                "Register key prefix".to_string(),
+            ));
+        }
+        if self.drop_index {
+            result.push(setup::ChangeDescription::Action(
+                "Drop search index".to_string(),
+            ));
+        }
+        if self.delete_keys {
+            result.push(setup::ChangeDescription::Action(
+                "Delete keys with the prefix".to_string(),
+            ));
+        }
+        if let Some(create_index) = &self.create_index {
+            result.push(setup::ChangeDescription::Action(format!(
+                "Create search index: {}",
+                create_index.join(" ")
+            )));
+        }
+        if self.unregister_key_prefix {
+            result.push(setup::ChangeDescription::Action(
+                "Unregister key prefix".to_string(),
+            ));
+        }
+        result
+    }
+
+    fn change_type(&self) -> setup::SetupChangeType {
+        match (
+            self.unregister_key_prefix || self.delete_keys || self.drop_index,
+            self.register_key_prefix || self.create_index.is_some(),
+        ) {
+            (false, false) => setup::SetupChangeType::NoChange,
+            (false, true) => setup::SetupChangeType::Create,
+            (true, false) => setup::SetupChangeType::Delete,
+            (true, true) => setup::SetupChangeType::Update,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Deal with mutations
+////////////////////////////////////////////////////////////
+
+struct ExportContext {
+    conn: ConnectionManager,
+    key_template: KeyTemplate,
+    encoding: ValueEncoding,
+    ttl: Option<Duration>,
+    key_fields_schema: Box<[FieldSchema]>,
+    value_fields_schema: Vec<FieldSchema>,
+    /// Whether each value field is stored as a vector in a search index.
+    indexed_vector_fields: Vec<bool>,
+}
+
+impl ExportContext {
+    fn document(&self, key: &KeyValue, value: &FieldValues) -> Result<serde_json::Value> {
+        let mut doc = serde_json::Map::new();
+        for (key_part, field) in std::iter::zip(key.iter(), self.key_fields_schema.iter()) {
+            let v = Value::from(key_part.clone());
+            doc.insert(
+                field.name.clone(),
+                serde_json::to_value(TypedValue {
+                    t: &field.value_type.typ,
+                    v: &v,
+                })?,
+            );
+        }
+        for (v, field) in std::iter::zip(value.fields.iter(), self.value_fields_schema.iter()) {
+            doc.insert(
+                field.name.clone(),
+                serde_json::to_value(TypedValue {
+                    t: &field.value_type.typ,
+                    v,
+                })?,
+            );
+        }
+        Ok(serde_json::Value::Object(doc))
+    }
+
+    fn hash_field_value(typ: &ValueType, v: &Value, as_vector: bool) -> Result<Option<Vec<u8>>> {
+        Ok(Some(match v {
+            Value::Null => return Ok(None),
+            Value::Basic(BasicValue::Str(s)) => s.as_bytes().to_vec(),
+            Value::Basic(BasicValue::Bytes(b)) => b.to_vec(),
+            Value::Basic(v) if as_vector => encode_vector_bytes(v)?,
+            v => serde_json::to_vec(&TypedValue { t: typ, v })?,
+        }))
+    }
+
+    fn hash_fields(&self, key: &KeyValue, value: &FieldValues) -> Result<Vec<(String, Vec<u8>)>> {
+        let mut fields = vec![];
+        for (key_part, field) in std::iter::zip(key.iter(), self.key_fields_schema.iter()) {
+            let v = Value::from(key_part.clone());
+            if let Some(bytes) = Self::hash_field_value(&field.value_type.typ, &v, false)? {
+                fields.push((field.name.clone(), bytes));
+            }
+        }
+        for ((v, field), &as_vector) in value
+            .fields
+            .iter()
+            .zip(self.value_fields_schema.iter())
+            .zip(self.indexed_vector_fields.iter())
+        {
+            if let Some(bytes) = Self::hash_field_value(&field.value_type.typ, v, as_vector)? {
+                fields.push((field.name.clone(), bytes));
+            }
+        }
+        Ok(fields)
+    }
+
+    fn build_pipeline(&self, mutation: &ExportTargetMutation) -> Result<redis::Pipeline> {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        let ttl_millis = self.ttl.map(|ttl| ttl.as_millis() as u64);
+        for upsert in mutation.upserts.iter() {
+            let key = self.key_template.render(&upsert.key);
+            match self.encoding {
+                ValueEncoding::Hash => {
+                    // Replaces the whole hash, so fields that became null don't linger.
+                    pipe.cmd("DEL").arg(&key).ignore();
+                    let fields = self.hash_fields(&upsert.key, &upsert.value)?;
+                    if !fields.is_empty() {
+                        pipe.cmd("HSET").arg(&key).arg(fields).ignore();
+                    }
+                }
+                ValueEncoding::Json => {
+                    pipe.cmd("JSON.SET")
+                        .arg(&key)
+                        .arg("$")
+                        .arg(self.document(&upsert.key, &upsert.value)?.to_string())
+                        .ignore();
+                }
+                ValueEncoding::Blob => {
+                    pipe.cmd("SET")
+                        .arg(&key)
+                        .arg(serde_json::to_vec(
+                            &self.document(&upsert.key, &upsert.value)?,
+                        )?)
+                        .ignore();
+                }
+            }
+            if let Some(ttl_millis) = ttl_millis {
+                pipe.cmd("PEXPIRE").arg(&key).arg(ttl_millis).ignore();
+            }
+        }
+        for delete in mutation.deletes.iter() {
+            pipe.cmd("DEL")
+                .arg(self.key_template.render(&delete.key))
+                .ignore();
+        }
+        Ok(pipe)
+    }
+
+    async fn apply_mutation(&self, mutation: &ExportTargetMutation) -> Result<()> {
+        let pipe = self.build_pipeline(mutation)?;
+        if pipe.is_empty() {
+            return Ok(());
+        }
+        pipe.query_async::<()>(&mut self.conn.clone()).await?;
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Factory implementation
+////////////////////////////////////////////////////////////
+
+struct Factory;
+
+#[async_trait]
+impl TargetFactoryBase for Factory {
+    type Spec = Spec;
+    type DeclarationSpec = ();
+    type SetupState = SetupState;
+    type SetupChange = SetupChange;
+    type SetupKey = TargetKey;
+    type ExportContext = ExportContext;
+
+    fn name(&self) -> &str {
+        "Redis"
+    }
+
+    async fn build(
+        self: Arc<Self>,
+        data_collections: Vec<TypedExportDataCollectionSpec<Self>>,
+        _declarations: Vec<()>,
+        context: Arc<FlowInstanceContext>,
+    ) -> Result<(
+        Vec<TypedExportDataCollectionBuildOutput<Self>>,
+        Vec<(TargetKey, SetupState)>,
+    )> {
+        let data_coll_output = data_collections
+            .into_iter()
+            .map(|d| {
+                if d.spec.key_prefix.is_empty() {
+                    api_bail!("`key_prefix` of Redis target must not be empty");
+                }
+                let desired_setup_state = SetupState::new(
+                    &d.spec,
+                    &d.key_fields_schema,
+                    &d.value_fields_schema,
+                    &d.index_options,
+                )?;
+                let key_template = KeyTemplate::new(
+                    &d.spec.key_prefix,
+                    d.spec.key_template.as_deref(),
+                    &d.key_fields_schema,
+                )?;
+                let client = get_client(&d.spec.connection, &context.auth_registry)?;
+                let indexed_vector_fields = d
+                    .value_fields_schema
+                    .iter()
+                    .map(|f| desired_setup_state.vector_indexes.contains_key(&f.name))
+                    .collect();
+                let encoding = d.spec.encoding;
+                let ttl = d.spec.ttl;
+                let key_fields_schema = d.key_fields_schema;
+                let value_fields_schema = d.value_fields_schema;
+                let export_context = async move {
+                    Ok(Arc::new(ExportContext {
+                        conn: client.get_connection_manager().await?,
+                        key_template,
+                        encoding,
+                        ttl,
+                        key_fields_schema,
+                        value_fields_schema,
+                        indexed_vector_fields,
+                    }))
+                };
+                Ok(TypedExportDataCollectionBuildOutput {
+                    export_context: Box::pin(export_context),
+                    setup_key: TargetKey {
+                        connection: d.spec.connection,
+                        key_prefix: d.spec.key_prefix,
+                    },
+                    desired_setup_state,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((data_coll_output, vec![]))
+    }
+
+    async fn diff_setup_states(
+        &self,
+        key: TargetKey,
+        desired: Option<SetupState>,
+        existing: setup::CombinedState<SetupState>,
+        _flow_instance_ctx: Arc<FlowInstanceContext>,
+    ) -> Result<Self::SetupChange> {
+        Ok(SetupChange::from_states(&key, desired.as_ref(), &existing))
+    }
+
+    fn check_state_compatibility(
+        &self,
+        desired: &SetupState,
+        existing: &SetupState,
+    ) -> Result<SetupStateCompatibility> {
+        Ok(if desired.is_key_layout_compatible(existing) {
+            check_table_compatibility(&desired.columns, &existing.columns)
+        } else {
+            SetupStateCompatibility::NotCompatible
+        })
+    }
+
+    fn describe_resource(&self, key: &TargetKey) -> Result<String> {
+        Ok(format!(
+            "Redis keys {}*{}",
+            key.key_prefix,
+            key.connection
+                .as_ref()
+                .map_or_else(|| "".to_string(), |auth_entry| format!(" @ {auth_entry}"))
+        ))
+    }
+
+    async fn apply_mutation(
+        &self,
+        mutations: Vec<ExportTargetMutationWithContext<'async_trait, ExportContext>>,
+    ) -> Result<()> {
+        for mutation_w_ctx in mutations.into_iter() {
+            mutation_w_ctx
+                .export_context
+                .apply_mutation(&mutation_w_ctx.mutation)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn apply_setup_changes(
+        &self,
+        setup_change: Vec<TypedResourceSetupChangeItem<'async_trait, Self>>,
+        context: Arc<FlowInstanceContext>,
+    ) -> Result<()> {
+        for change in setup_change.iter() {
+            let client = get_client(&change.key.connection, &context.auth_registry)?;
+            let mut conn = client.get_connection_manager().await?;
+            change.setup_change.apply(&change.key, &mut conn).await?;
+        }
+        Ok(())
+    }
+}
+
+pub fn register(registry: &mut ExecutorFactoryRegistry) -> Result<()> {
+    Factory.register(registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_fields_schema() -> Vec<FieldSchema> {
+        vec![
+            FieldSchema::new("repo", make_output_type(BasicValueType::Str)),
+            FieldSchema::new("page", make_output_type(BasicValueType::Int64)),
+        ]
+    }
+
+    fn value_fields_schema() -> Vec<FieldSchema> {
+        vec![
+            FieldSchema::new("summary", make_output_type(BasicValueType::Str)),
+            FieldSchema::new("tokens", make_output_type(BasicValueType::Int64)),
+            FieldSchema::new(
+                "embedding",
+                make_output_type(BasicValueType::Vector(VectorTypeSchema {
+                    element_type: Box::new(BasicValueType::Float32),
+                    dimension: Some(2),
+                })),
+            ),
+        ]
+    }
+
+    fn test_spec(encoding: ValueEncoding) -> Spec {
+        Spec {
+            connection: None,
+            key_prefix: "summary:".to_string(),
+            key_template: Some("{repo}/{page}".to_string()),
+            encoding,
+            ttl: Some(Duration::from_secs(60)),
+        }
+    }
+
+    fn test_index_options() -> IndexOptions {
+        IndexOptions {
+            primary_key_fields: Some(vec!["repo".to_string(), "page".to_string()]),
+            vector_indexes: vec![spec::VectorIndexDef {
+                field_name: "embedding".to_string(),
+                metric: spec::VectorSimilarityMetric::CosineSimilarity,
+                method: Some(spec::VectorIndexMethod::Hnsw {
+                    m: Some(16),
+                    ef_construction: None,
+                }),
+            }],
+            fts_indexes: vec![],
+        }
+    }
+
+    fn test_key(repo: &str, page: i64) -> KeyValue {
+        KeyValue(Box::new([KeyPart::Str(repo.into()), KeyPart::Int64(page)]))
+    }
+
+    fn command_args(pipe: &redis::Pipeline) -> Vec<Vec<String>> {
+        pipe.cmd_iter()
+            .map(|cmd| {
+                cmd.args_iter()
+                    .filter_map(|arg| match arg {
+                        redis::Arg::Simple(bytes) => {
+                            Some(String::from_utf8_lossy(bytes).to_string())
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_key_template() -> Result<()> {
+        let key_fields_schema = key_fields_schema();
+        let template = KeyTemplate::new("summary:", Some("{repo}/{page}"), &key_fields_schema)?;
+        assert_eq!(template.render(&test_key("recoco", 3)), "summary:recoco/3");
+        let template = KeyTemplate::new("summary:", None, &key_fields_schema)?;
+        assert_eq!(template.render(&test_key("recoco", 3)), "summary:recoco:3");
+
+        // Separators and the escape character in field values are percent-encoded.
+        assert_eq!(
+            template.render(&test_key("a:b%c/d", 3)),
+            "summary:a%3Ab%25c/d:3"
+        );
+        let template = KeyTemplate::new("summary:", Some("{repo}/{page}"), &key_fields_schema)?;
+        assert_eq!(
+            template.render(&test_key("a/b:c", 3)),
+            "summary:a%2Fb%3Ac/3"
+        );
+
+        assert!(KeyTemplate::new("s:", Some("{repo}"), &key_fields_schema).is_err());
+        assert!(KeyTemplate::new("s:", Some("{repo}/{other}"), &key_fields_schema).is_err());
+        assert!(KeyTemplate::new("s:", Some("{repo}/{page"), &key_fields_schema).is_err());
+        assert!(KeyTemplate::new("s:", Some("{repo}}{page}"), &key_fields_schema).is_err());
+
+        assert_eq!(prefix_match_pattern("a*b:"), "a\\*b:*");
+
+        let registered = ["summary:".to_string(), "doc:".to_string()];
+        assert_eq!(overlapping_key_prefix("summary:", &registered), None);
+        assert_eq!(overlapping_key_prefix("docs:", &registered), None);
+        assert_eq!(
+            overlapping_key_prefix("summary:long:", &registered),
+            Some("summary:")
+        );
+        assert_eq!(overlapping_key_prefix("do", &registered), Some("doc:"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_setup_change() -> Result<()> {
+        let key = TargetKey {
+            connection: None,
+            key_prefix: "summary:".to_string(),
+        };
+        let state = SetupState::new(
+            &test_spec(ValueEncoding::Hash),
+            &key_fields_schema(),
+            &value_fields_schema(),
+            &test_index_options(),
+        )?;
+        assert_eq!(
+            state.create_index_args(&key.key_prefix).unwrap().join(" "),
+            "ON HASH PREFIX 1 summary: SCHEMA embedding VECTOR HNSW 8 TYPE FLOAT32 DIM 2 DISTANCE_METRIC COSINE M 16"
+        );
+
+        let no_existing = setup::CombinedState {
+            current: None,
+            staging: vec![],
+            legacy_state_key: None,
+        };
+        let change = SetupChange::from_states(&key, Some(&state), &no_existing);
+        assert!(!change.delete_keys && !change.drop_index && change.create_index.is_some());
+        assert!(change.register_key_prefix && !change.unregister_key_prefix);
+
+        let existing = setup::CombinedState::current(state.clone());
+        let change = SetupChange::from_states(&key, Some(&state), &existing);
+        assert_eq!(
+            setup::ResourceSetupChange::change_type(&change),
+            setup::SetupChangeType::NoChange
+        );
+
+        // Dropping the target removes its keys and index.
+        let change = SetupChange::from_states(&key, None, &existing);
+        assert!(change.delete_keys && change.drop_index && change.create_index.is_none());
+        assert!(!change.register_key_prefix && change.unregister_key_prefix);
+
+        // Without a search index, the prefix is still registered when the target is created.
+        let state_without_index = SetupState::new(
+            &test_spec(ValueEncoding::Hash),
+            &key_fields_schema(),
+            &value_fields_schema(),
+            &IndexOptions {
+                vector_indexes: vec![],
+                ..test_index_options()
+            },
+        )?;
+        let change = SetupChange::from_states(&key, Some(&state_without_index), &no_existing);
+        assert_eq!(
+            setup::ResourceSetupChange::change_type(&change),
+            setup::SetupChangeType::Create
+        );
+
+        // Keys laid out differently are removed, and the index is recreated.
+        let json_state = SetupState::new(
+            &test_spec(ValueEncoding::Json),
+            &key_fields_schema(),
+            &value_fields_schema(),
+            &test_index_options(),
+        )?;
+        assert!(
+            json_state
+                .create_index_args(&key.key_prefix)
+                .unwrap()
+                .join(" ")
+                .contains("SCHEMA $.embedding AS embedding VECTOR")
+        );
+        let change = SetupChange::from_states(&key, Some(&json_state), &existing);
+        assert!(change.delete_keys && change.drop_index && change.create_index.is_some());
+        assert_eq!(
+            TargetFactoryBase::check_state_compatibility(&Factory, &json_state, &state)?,
+            SetupStateCompatibility::NotCompatible
+        );
+
+        assert!(
+            SetupState::new(
+                &test_spec(ValueEncoding::Blob),
+                &key_fields_schema(),
+                &value_fields_schema(),
+                &test_index_options(),
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_build_pipeline() -> Result<()> {
+        let key_fields_schema = key_fields_schema();
+        let export_context = |encoding: ValueEncoding| -> Result<ExportContext> {
+            Ok(ExportContext {
+                // Connects lazily, so no server is needed to build pipelines.
+                conn: ConnectionManager::new_lazy_with_config(
+                    redis::Client::open(DEFAULT_URL)?,
+                    Default::default(),
+                )?,
+                key_template: KeyTemplate::new(
+                    "summary:",
+                    Some("{repo}/{page}"),
+                    &key_fields_schema,
+                )?,
+                encoding,
+                ttl: Some(Duration::from_secs(60)),
+                key_fields_schema: key_fields_schema.clone().into(),
+                value_fields_schema: value_fields_schema(),
+                indexed_vector_fields: vec![false, false, true],
+            })
+        };
+        let mutation = ExportTargetMutation {
+            upserts: vec![ExportTargetUpsertEntry {
+                key: test_key("recoco", 3),
+                additional_key: serde_json::Value::Null,
+                value: FieldValues {
+                    fields: vec![
+                        Value::Basic(BasicValue::Str("A summary".into())),
+                        Value::Null,
+                        Value::Basic(BasicValue::Vector(
+                            vec![BasicValue::Float32(1.0), BasicValue::Float32(0.5)].into(),
+                        )),
+                    ],
+                },
+            }],
+            deletes: vec![ExportTargetDeleteEntry {
+                key: test_key("recoco", 4),
+                additional_key: serde_json::Value::Null,
+            }],
+        };
+
+        let pipe = export_context(ValueEncoding::Hash)?.build_pipeline(&mutation)?;
+        let embedding_bytes = [1.0f32.to_le_bytes(), 0.5f32.to_le_bytes()].concat();
+        assert_eq!(
+            command_args(&pipe),
+            vec![
+                vec!["DEL", "summary:recoco/3"],
+                [
+                    "HSET".to_string(),
+                    "summary:recoco/3".to_string(),
+                    "repo".to_string(),
+                    "recoco".to_string(),
+                    "page".to_string(),
+                    "3".to_string(),
+                    "summary".to_string(),
+                    "A summary".to_string(),
+                    "embedding".to_string(),
+                    String::from_utf8_lossy(&embedding_bytes).to_string(),
+                ]
+                .iter()
+                .map(|s| s.as_str())
+                .collect(),
+                vec!["PEXPIRE", "summary:recoco/3", "60000"],
+                vec!["DEL", "summary:recoco/4"],
+            ]
+        );
+
+        let pipe = export_context(ValueEncoding::Json)?.build_pipeline(&mutation)?;
+        let commands = command_args(&pipe);
+        assert_eq!(commands[0][..3], ["JSON.SET", "summary:recoco/3", "$"]);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&commands[0][3])?,
+            serde_json::json!({
+                "repo": "recoco",
+                "page": 3,
+                "summary": "A summary",
+                "tokens": null,
+                "embedding": [1.0, 0.5],
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "This test requires a Redis server at RECOCO_TEST_REDIS_URL."]
+    async fn test_export_and_drop() -> Result<()> {
+        let url =
+            std::env::var("RECOCO_TEST_REDIS_URL").unwrap_or_else(|_| DEFAULT_URL.to_string());
+        let client = redis::Client::open(url)?;
+        let mut conn = client.get_connection_manager().await?;
+        let key = TargetKey {
+            connection: None,
+            key_prefix: "recoco_redis_test:".to_string(),
+        };
+        let key_fields_schema = key_fields_schema();
+        let spec = Spec {
+            key_prefix: key.key_prefix.clone(),
+            ..test_spec(ValueEncoding::Blob)
+        };
+        let state = SetupState::new(
+            &spec,
+            &key_fields_schema,
+            &value_fields_schema(),
+            &IndexOptions {
+                primary_key_fields: Some(vec!["repo".to_string(), "page".to_string()]),
+                vector_indexes: vec![],
+                fts_indexes: vec![],
+            },
+        )?;
+        let no_existing = setup::CombinedState {
+            current: None,
+            staging: vec![],
+            legacy_state_key: None,
+        };
+        SetupChange::from_states(&key, Some(&state), &no_existing)
+            .apply(&key, &mut conn)
+            .await?;
+        let overlapping_key = TargetKey {
+            key_prefix: "recoco_redis_test:nested:".to_string(),
+            ..key.clone()
+        };
+        assert!(
+            SetupChange::from_states(&overlapping_key, Some(&state), &no_existing)
+                .apply(&overlapping_key, &mut conn)
+                .await
+                .is_err()
+        );
+
+        let export_context = ExportContext {
+            conn: conn.clone(),
+            key_template: KeyTemplate::new(&key.key_prefix, None, &key_fields_schema)?,
+            encoding: ValueEncoding::Blob,
+            ttl: spec.ttl,
+            key_fields_schema: key_fields_schema.into(),
+            value_fields_schema: value_fields_schema(),
+            indexed_vector_fields: vec![false, false, false],
+        };
+        let upsert = |page: i64| ExportTargetUpsertEntry {
+            key: test_key("recoco", page),
+            additional_key: serde_json::Value::Null,
+            value: FieldValues {
+                fields: vec![Value::Null, Value::Null, Value::Null],
+            },
+        };
+        export_context
+            .apply_mutation(&ExportTargetMutation {
+                upserts: (0..10).map(upsert).collect(),
+                deletes: vec![ExportTargetDeleteEntry {
+                    key: test_key("recoco", 0),
+                    additional_key: serde_json::Value::Null,
+                }],
+            })
+            .await?;
+        let ttl: i64 = redis::cmd("TTL")
+            .arg("recoco_redis_test:recoco:1")
+            .query_async(&mut conn)
+            .await?;
+        assert!(ttl > 0 && ttl <= 60);
+        let exists: usize = redis::cmd("EXISTS")
+            .arg("recoco_redis_test:recoco:0")
+            .query_async(&mut conn)
+            .await?;
+        assert_eq!(exists, 0);
+
+        SetupChange::from_states(&key, None, &setup::CombinedState::current(state))
+            .apply(&key, &mut conn)
+            .await?;
+        let (_, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(0)
+            .arg("MATCH")
+            .arg(prefix_match_pattern(&key.key_prefix))
+            .arg("COUNT")
+            .arg(SCAN_BATCH_SIZE)
+            .query_async(&mut conn)
+            .await?;
+        assert!(keys.is_empty());
+        let registered: bool = redis::cmd("SISMEMBER")
+            .arg(KEY_PREFIX_REGISTRY_KEY)
+            .arg(&key.key_prefix)
+            .query_async(&mut conn)
+            .await?;
+        assert!(!registered);
+        Ok(())
+    }
+}
@@ -48,11 +48,6 @@ pub use crate::setup::AuthRegistry;
     feature = "source-s3"
 ))]
 pub use async_stream::{stream, try_stream};
-#[cfg(any(
-    feature = "source-azure",
-    feature = "source-local-file",
-    feature = "source-s3"
-))]
 pub use recoco_utils as utils;
 #[cfg(any(
     feature = "function-embed",
@@ -65,6 +60,8 @@ pub use recoco_utils as utils;
 pub use recoco_utils::batching;
 pub use recoco_utils::concur_control;
 #[cfg(any(
+    feature = "target-elasticsearch",
+    feature = "target-http-webhook",
     feature = "target-kuzu",
     feature = "target-ladybug",
     feature = "function-embed",
@@ -78,6 +75,7 @@ pub use recoco_utils::http;
     feature = "provider-gemini",
     feature = "persistence",
     feature = "function-split",
+    feature = "target-http-webhook",
     feature = "target-neo4j"
 ))]
 pub use recoco_utils::retryable;
@@ -11,4 +11,4 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub(crate) mod flows;
-pub(crate) mod query_handler;
+pub mod query_handler;
@@ -17,14 +17,16 @@ use crate::{
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct QueryHandlerResultFields {
-    embedding: Vec<String>,
-    score: Option<String>,
+    /// Fields of results carrying embeddings, e.g. for visualizing results against the query.
+    pub embedding: Vec<String>,
+    /// Field of results carrying the score.
+    pub score: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct QueryHandlerSpec {
     #[serde(default)]
-    result_fields: QueryHandlerResultFields,
+    pub result_fields: QueryHandlerResultFields,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -13,7 +13,7 @@
 use crate::{
     lib_context::{FlowContext, FlowExecutionContext, LibSetupContext},
     ops::{
-        get_attachment_factory, get_optional_target_factory,
+        get_attachment_factory, get_optional_target_factory, get_source_factory,
         interface::{AttachmentSetupKey, FlowInstanceContext, TargetFactory},
     },
     prelude::*,
@@ -30,7 +30,7 @@ use super::{AllSetupStates, GlobalSetupChange};
 use super::{
     CombinedState, DesiredMode, ExistingMode, FlowSetupChange, FlowSetupState, ObjectSetupChange,
     ObjectStatus, ResourceIdentifier, ResourceSetupChange, ResourceSetupInfo, SetupChangeType,
-    StateChange, TargetSetupState, db_metadata,
+    SourceResourceDrop, StateChange, TargetSetupState, db_metadata,
 };
 use crate::execution::db_tracking_setup;
 use std::fmt::Write;
@@ -416,6 +416,36 @@ pub async fn diff_flow_setup_states(
             BTreeMap::new()
         };
 
+    // Resources of sources are dropped once no source of the flow lists them.
+    let desired_source_resources = desired_state
+        .iter()
+        .flat_map(|d| d.metadata.sources.values())
+        .flat_map(|s| s.resources.iter().map(move |r| (&s.source_kind, r)))
+        .collect::<Vec<_>>();
+    let mut source_resources_to_drop = Vec::<SourceResourceDrop>::new();
+    for source in existing_state
+        .iter()
+        .flat_map(|e| e.metadata.possible_versions())
+        .flat_map(|v| v.sources.values())
+    {
+        for resource in source.resources.iter() {
+            let is_listed =
+                |kind: &String, r: &serde_json::Value| kind == &source.source_kind && r == resource;
+            if !desired_source_resources
+                .iter()
+                .any(|(kind, r)| is_listed(kind, r))
+                && !source_resources_to_drop
+                    .iter()
+                    .any(|d| is_listed(&d.source_kind, &d.resource))
+            {
+                source_resources_to_drop.push(SourceResourceDrop {
+                    source_kind: source.source_kind.clone(),
+                    resource: resource.clone(),
+                });
+            }
+        }
+    }
+
     let tracking_table_change = db_tracking_setup::TrackingTableSetupChange::new(
         desired_state.map(|d| &d.tracking_table),
         &existing_state
@@ -520,6 +550,7 @@ pub async fn diff_flow_setup_states(
         metadata_change,
         tracking_table: tracking_table_change.map(|c| c.into_setup_info()),
         target_resources,
+        source_resources_to_drop,
         unknown_resources,
     })
 }
@@ -710,6 +741,34 @@ async fn apply_changes_for_flow(
         )
         .await?;
     }
+    for drop in &flow_setup_change.source_resources_to_drop {
+        write!(
+            write,
+            "Dropping {} source resource {}...",
+            drop.source_kind, drop.resource
+        )?;
+        let result = async {
+            get_source_factory(&drop.source_kind)?
+                .drop_resource(
+                    drop.resource.clone(),
+                    flow_ctx.flow.flow_instance_ctx.clone(),
+                )
+                .await
+        }
+        .await;
+        match result {
+            Ok(()) => writeln!(write, "DONE")?,
+            Err(e) if is_deletion && ignore_target_drop_failures => {
+                writeln!(write, "FAILED")?;
+                tracing::error!(
+                    "Ignoring source resource drop failure in flow '{}': {:#}",
+                    flow_ctx.flow_name(),
+                    e
+                );
+            }
+            Err(e) => return Err(e),
+        }
+    }
     // Apply tracking table change after target changes, since it may need to clean up tracking states based on the target info.
     if let Some(tracking_table) = &flow_setup_change.tracking_table {
         maybe_update_resource_setup(
@@ -194,6 +194,17 @@ pub struct SourceSetupState {
     // Allow empty string during deserialization for backward compatibility.
     #[serde(default)]
     pub source_kind: String,
+
+    /// Resources created by the source outside the flow's own tables, e.g. a replication slot.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<serde_json::Value>,
+}
+
+/// A resource of a source that no source of the flow lists any more, to be dropped.
+#[derive(Debug, Clone)]
+pub struct SourceResourceDrop {
+    pub source_kind: String,
+    pub resource: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -489,6 +500,7 @@ pub struct FlowSetupChange {
         Option<ResourceSetupInfo<(), TrackingTableSetupState, TrackingTableSetupChange>>,
     pub target_resources:
         Vec<ResourceSetupInfo<ResourceIdentifier, TargetSetupState, TargetSetupChange>>,
+    pub source_resources_to_drop: Vec<SourceResourceDrop>,
 
     pub unknown_resources: Vec<ResourceIdentifier>,
 }
@@ -532,6 +544,7 @@ impl ObjectSetupChange for FlowSetupChange {
                 .target_resources
                 .iter()
                 .any(|target| !target.is_up_to_date())
+            || !self.source_resources_to_drop.is_empty()
     }
 }
 
@@ -625,6 +638,13 @@ impl std::fmt::Display for FormattedFlowSetupChange<'_> {
         for target_resource in &flow_setup_change.target_resources {
             write!(f, "{target_resource}")?;
         }
+        for drop in &flow_setup_change.source_resources_to_drop {
+            writeln!(
+                f,
+                "[ TO DELETE ] {} source resource {}",
+                drop.source_kind, drop.resource
+            )?;
+        }
         for resource in &flow_setup_change.unknown_resources {
             writeln!(f, "[  UNKNOWN  ] {resource}")?;
         }
@@ -12,13 +12,15 @@
 //! Run with:
 //!   cargo bench -p recoco-splitters --features rust,python,markdown
 //!
+//! Add the `parallel` feature to compare parallel chunking of large inputs.
+//!
 //! Or for all languages:
 //!   cargo bench -p recoco-splitters --features all
 
 use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
 use recoco_splitters::split::{
-    RecursiveChunkConfig, RecursiveChunker, RecursiveSplitConfig, SeparatorSplitConfig,
-    SeparatorSplitter,
+    DEFAULT_SEGMENT_SIZE, RecursiveChunkConfig, RecursiveChunker, RecursiveSplitConfig,
+    SeparatorSplitConfig, SeparatorSplitter,
 };
 use std::fs;
 use std::path::PathBuf;
@@ -177,6 +179,7 @@ fn bench_recursive_chunk(c: &mut Criterion) {
                             min_chunk_size: None,
                             chunk_overlap: Some(chunk_size / 10),
                             language: None,
+                            include_context: false,
                         },
                     )
                 });
@@ -200,6 +203,7 @@ fn bench_recursive_chunk(c: &mut Criterion) {
                             min_chunk_size: None,
                             chunk_overlap: Some(chunk_size / 10),
                             language: Some("rust".to_string()),
+                            include_context: false,
                         },
                     )
                 });
@@ -226,6 +230,7 @@ fn bench_recursive_chunk(c: &mut Criterion) {
                                 min_chunk_size: None,
                                 chunk_overlap: Some(chunk_size / 10),
                                 language: Some("python".to_string()),
+                                include_context: false,
                             },
                         )
                     });
@@ -253,6 +258,7 @@ fn bench_recursive_chunk(c: &mut Criterion) {
                                 min_chunk_size: None,
                                 chunk_overlap: Some(chunk_size / 10),
                                 language: Some("markdown".to_string()),
+                                include_context: false,
                             },
                         )
                     });
@@ -263,6 +269,51 @@ fn bench_recursive_chunk(c: &mut Criterion) {
     group.finish();
 }
 
+// ---------------------------------------------------------------------------
+// Benchmark: Segmented chunking of large inputs
+// ---------------------------------------------------------------------------
+
+fn bench_segmented_chunk(c: &mut Criterion) {
+    let fixtures = load_all_fixtures();
+    let chunker = RecursiveChunker::new(RecursiveSplitConfig::default()).unwrap();
+    let chunk_config = |language: Option<&str>| RecursiveChunkConfig {
+        chunk_size: 1024,
+        min_chunk_size: None,
+        chunk_overlap: Some(102),
+        language: language.map(str::to_string),
+        include_context: false,
+    };
+
+    let mut group = c.benchmark_group("segmented_chunk");
+    group.sample_size(10);
+    let large = [
+        ("prose", None, &fixtures.prose),
+        ("rust", Some("rust"), &fixtures.rust),
+    ];
+    for (name, language, tiers) in large {
+        let Some((_, content)) = tiers.iter().find(|(tier, _)| tier == "large") else {
+            continue;
+        };
+        group.throughput(Throughput::Bytes(content.len() as u64));
+        group.bench_with_input(BenchmarkId::new("stream", name), content, |b, text| {
+            b.iter(|| {
+                chunker
+                    .split_stream(
+                        text.as_bytes(),
+                        chunk_config(language),
+                        DEFAULT_SEGMENT_SIZE,
+                    )
+                    .count()
+            });
+        });
+        #[cfg(feature = "parallel")]
+        group.bench_with_input(BenchmarkId::new("parallel", name), content, |b, text| {
+            b.iter(|| chunker.split_parallel(text, chunk_config(language), DEFAULT_SEGMENT_SIZE));
+        });
+    }
+    group.finish();
+}
+
 // ---------------------------------------------------------------------------
 // Benchmark: Splitter construction
 // ---------------------------------------------------------------------------
@@ -351,6 +402,7 @@ criterion_group!(
     benches,
     bench_separator_split,
     bench_recursive_chunk,
+    bench_segmented_chunk,
     bench_construction,
     bench_language_detection,
 );
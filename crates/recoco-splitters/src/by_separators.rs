@@ -143,6 +143,7 @@ impl SeparatorSplitter {
                     range: TextRange::new(raw.start, raw.end),
                     start: start_pos,
                     end: end_pos,
+                    context: Vec::new(),
                 }
             })
             .collect()
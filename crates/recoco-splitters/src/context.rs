@@ -0,0 +1,153 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Enclosing-scope breadcrumbs for chunks.
+
+use std::collections::HashMap;
+
+use crate::prog_langs::TreeSitterLanguageInfo;
+use crate::split::TextRange;
+use crate::symbols;
+
+pub(crate) struct Heading {
+    offset: usize,
+    level: usize,
+    text: String,
+}
+
+/// Resolves the chain of scopes enclosing a chunk.
+pub(crate) enum ContextResolver<'t> {
+    /// Markdown heading hierarchy; headings are in document order.
+    Markdown(Vec<Heading>),
+    /// Enclosing named syntax nodes.
+    Syntax {
+        root: tree_sitter::Node<'t>,
+        source: &'t [u8],
+        /// Names from the language's symbol query. Without one, any node with a `name`
+        /// field counts as a scope.
+        scope_names: Option<HashMap<usize, String>>,
+    },
+}
+
+impl<'t> ContextResolver<'t> {
+    pub fn markdown(text: &str) -> Self {
+        let mut headings = Vec::new();
+        let mut fence: Option<&str> = None;
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            let line_offset = offset;
+            offset += line.len();
+
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            if indent > 3 {
+                continue;
+            }
+            let content = line.trim();
+            if let Some(marker) = fence {
+                if content.starts_with(marker) {
+                    fence = None;
+                }
+                continue;
+            }
+            if let Some(marker) = ["```", "~~~"].into_iter().find(|m| content.starts_with(m)) {
+                fence = Some(marker);
+                continue;
+            }
+
+            let level = content.len() - content.trim_start_matches('#').len();
+            if level == 0 || level > 6 {
+                continue;
+            }
+            let rest = &content[level..];
+            if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+                continue;
+            }
+            // Strip an optional closing sequence, e.g. `## Title ##`.
+            let mut heading = rest.trim();
+            let without_closing = heading.trim_end_matches('#');
+            if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+                heading = without_closing.trim_end();
+            }
+            headings.push(Heading {
+                offset: line_offset,
+                level,
+                text: heading.to_string(),
+            });
+        }
+        Self::Markdown(headings)
+    }
+
+    pub fn syntax(
+        tree_sitter_info: &TreeSitterLanguageInfo,
+        tree: &'t tree_sitter::Tree,
+        text: &'t str,
+    ) -> Self {
+        let root = tree.root_node();
+        let source = text.as_bytes();
+        let scope_names = tree_sitter_info
+            .symbol_query
+            .as_ref()
+            .map(|query| symbols::scope_names(query, root, source));
+        Self::Syntax {
+            root,
+            source,
+            scope_names,
+        }
+    }
+
+    /// Names of the scopes enclosing `range`, outermost first.
+    pub fn context(&self, range: TextRange) -> Vec<String> {
+        match self {
+            Self::Markdown(headings) => {
+                let mut stack: Vec<&Heading> = Vec::new();
+                for heading in headings.iter().take_while(|h| h.offset <= range.start) {
+                    while stack.last().is_some_and(|h| h.level >= heading.level) {
+                        stack.pop();
+                    }
+                    stack.push(heading);
+                }
+                stack.into_iter().map(|h| h.text.clone()).collect()
+            }
+            Self::Syntax {
+                root,
+                source,
+                scope_names,
+            } => {
+                // Surrounding whitespace shouldn't widen the range out of a scope.
+                let chunk = &source[range.start..range.end];
+                let Some(first) = chunk.iter().position(|b| !b.is_ascii_whitespace()) else {
+                    return Vec::new();
+                };
+                let last = chunk
+                    .iter()
+                    .rposition(|b| !b.is_ascii_whitespace())
+                    .unwrap();
+                let mut names = Vec::new();
+                let mut node =
+                    root.descendant_for_byte_range(range.start + first, range.start + last);
+                while let Some(n) = node {
+                    let name = match scope_names {
+                        Some(scope_names) => scope_names.get(&n.id()).cloned(),
+                        None => n
+                            .child_by_field_name("name")
+                            .and_then(|name| name.utf8_text(source).ok())
+                            .map(str::to_string),
+                    };
+                    names.extend(name);
+                    node = n.parent();
+                }
+                names.reverse();
+                names
+            }
+        }
+    }
+}
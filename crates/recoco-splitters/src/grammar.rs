@@ -0,0 +1,209 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tree-sitter grammars loaded at runtime for custom languages.
+//!
+//! Grammars are either native shared libraries built by `tree-sitter build`
+//! (requires the `runtime-grammars` feature) or WebAssembly modules built by
+//! `tree-sitter build --wasm` (requires the `wasm-grammars` feature).
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::prog_langs::TreeSitterLanguageInfo;
+
+/// A tree-sitter grammar to load at runtime for a custom language.
+#[derive(Debug, Clone)]
+pub struct CustomGrammarConfig {
+    /// Path to the compiled grammar: a shared library (`.so`, `.dylib`, `.dll`),
+    /// or a WebAssembly module if the extension is `.wasm`.
+    pub path: PathBuf,
+    /// Name of the grammar, as passed to `tree-sitter generate`. The shared library must
+    /// export `tree_sitter_<grammar_name>`. Defaults to the language name.
+    pub grammar_name: Option<String>,
+    /// Node kinds that are never split further, e.g. string literals or comments.
+    pub terminal_node_kinds: Vec<String>,
+}
+
+/// A grammar loaded from a [`CustomGrammarConfig`].
+///
+/// Fields drop in declaration order, so the language is released before the
+/// library backing it is unloaded.
+pub(crate) struct LoadedGrammar {
+    pub tree_sitter_info: TreeSitterLanguageInfo,
+    #[cfg(feature = "wasm-grammars")]
+    wasm_engine: Option<tree_sitter::wasmtime::Engine>,
+    #[cfg(feature = "runtime-grammars")]
+    _library: Option<libloading::Library>,
+}
+
+impl LoadedGrammar {
+    /// Load the grammar for `language_name` and resolve its terminal node kinds.
+    pub fn load(language_name: &str, config: &CustomGrammarConfig) -> Result<Self, String> {
+        let grammar_name = config
+            .grammar_name
+            .clone()
+            .unwrap_or_else(|| language_name.to_lowercase().replace('-', "_"));
+        let is_wasm = config
+            .path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wasm"));
+        let mut grammar = if is_wasm {
+            Self::load_wasm(&config.path, &grammar_name)?
+        } else {
+            Self::load_shared_library(&config.path, &grammar_name)?
+        };
+        let tree_sitter_lang = &grammar.tree_sitter_info.tree_sitter_lang;
+        let abi_version = tree_sitter_lang.abi_version();
+        if !(tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+            .contains(&abi_version)
+        {
+            return Err(format!(
+                "grammar `{}` for language `{}` has ABI version {}, expected {} to {}",
+                config.path.display(),
+                language_name,
+                abi_version,
+                tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION,
+                tree_sitter::LANGUAGE_VERSION
+            ));
+        }
+        grammar.tree_sitter_info.terminal_node_kind_ids =
+            resolve_node_kinds(tree_sitter_lang, &config.terminal_node_kinds).map_err(|kind| {
+                format!("unknown terminal node kind `{kind}` for language `{language_name}`")
+            })?;
+        Ok(grammar)
+    }
+
+    #[cfg(all(test, feature = "rust"))]
+    pub fn from_language(
+        tree_sitter_lang: tree_sitter::Language,
+        terminal_node_kinds: &[String],
+    ) -> Result<Self, String> {
+        let terminal_node_kind_ids = resolve_node_kinds(&tree_sitter_lang, terminal_node_kinds)
+            .map_err(|kind| format!("unknown terminal node kind `{kind}`"))?;
+        Ok(Self::new(TreeSitterLanguageInfo {
+            tree_sitter_lang,
+            terminal_node_kind_ids,
+            symbol_query: None,
+        }))
+    }
+
+    #[cfg(any(
+        all(test, feature = "rust"),
+        feature = "runtime-grammars",
+        feature = "wasm-grammars"
+    ))]
+    fn new(tree_sitter_info: TreeSitterLanguageInfo) -> Self {
+        Self {
+            tree_sitter_info,
+            #[cfg(feature = "wasm-grammars")]
+            wasm_engine: None,
+            #[cfg(feature = "runtime-grammars")]
+            _library: None,
+        }
+    }
+
+    /// Create a parser for this grammar.
+    pub fn parser(&self) -> Option<tree_sitter::Parser> {
+        let mut parser = tree_sitter::Parser::new();
+        #[cfg(feature = "wasm-grammars")]
+        if let Some(engine) = &self.wasm_engine {
+            let store = tree_sitter::WasmStore::new(engine).ok()?;
+            parser.set_wasm_store(store).ok()?;
+        }
+        parser
+            .set_language(&self.tree_sitter_info.tree_sitter_lang)
+            .ok()?;
+        Some(parser)
+    }
+
+    #[cfg(feature = "runtime-grammars")]
+    fn load_shared_library(path: &Path, grammar_name: &str) -> Result<Self, String> {
+        type LanguageFnPtr = unsafe extern "C" fn() -> *const ();
+
+        let symbol = format!("tree_sitter_{grammar_name}");
+        // SAFETY: loading a grammar runs no initialization code besides the platform
+        // loader's; the caller vouches for the library the same way as for any plugin.
+        let library = unsafe { libloading::Library::new(path) }
+            .map_err(|e| format!("failed to load grammar `{}`: {}", path.display(), e))?;
+        // SAFETY: tree-sitter grammars export `const TSLanguage *tree_sitter_<name>(void)`.
+        let language_fn = unsafe {
+            let symbol_fn = library
+                .get::<LanguageFnPtr>(symbol.as_bytes())
+                .map_err(|e| {
+                    format!(
+                        "grammar `{}` does not export `{}`: {}",
+                        path.display(),
+                        symbol,
+                        e
+                    )
+                })?;
+            tree_sitter_language::LanguageFn::from_raw(*symbol_fn)
+        };
+        let mut grammar = Self::new(TreeSitterLanguageInfo {
+            tree_sitter_lang: language_fn.into(),
+            terminal_node_kind_ids: HashSet::new(),
+            symbol_query: None,
+        });
+        grammar._library = Some(library);
+        Ok(grammar)
+    }
+
+    #[cfg(not(feature = "runtime-grammars"))]
+    fn load_shared_library(path: &Path, _grammar_name: &str) -> Result<Self, String> {
+        Err(format!(
+            "cannot load grammar `{}`: shared-library grammars require the `runtime-grammars` feature",
+            path.display()
+        ))
+    }
+
+    #[cfg(feature = "wasm-grammars")]
+    fn load_wasm(path: &Path, grammar_name: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("failed to read grammar `{}`: {}", path.display(), e))?;
+        let engine = tree_sitter::wasmtime::Engine::default();
+        let mut store = tree_sitter::WasmStore::new(&engine)
+            .map_err(|e| format!("failed to create WebAssembly store: {e}"))?;
+        let tree_sitter_lang = store
+            .load_language(grammar_name, &bytes)
+            .map_err(|e| format!("failed to load grammar `{}`: {}", path.display(), e))?;
+        let mut grammar = Self::new(TreeSitterLanguageInfo {
+            tree_sitter_lang,
+            terminal_node_kind_ids: HashSet::new(),
+            symbol_query: None,
+        });
+        grammar.wasm_engine = Some(engine);
+        Ok(grammar)
+    }
+
+    #[cfg(not(feature = "wasm-grammars"))]
+    fn load_wasm(path: &Path, _grammar_name: &str) -> Result<Self, String> {
+        Err(format!(
+            "cannot load grammar `{}`: WebAssembly grammars require the `wasm-grammars` feature",
+            path.display()
+        ))
+    }
+}
+
+/// Resolve node kind names to ids, returning the first unknown name on failure.
+fn resolve_node_kinds<'a>(
+    tree_sitter_lang: &tree_sitter::Language,
+    kinds: &'a [String],
+) -> Result<HashSet<u16>, &'a str> {
+    kinds
+        .iter()
+        .map(|kind| match tree_sitter_lang.id_for_node_kind(kind, true) {
+            0 => Err(kind.as_str()),
+            id => Ok(id),
+        })
+        .collect()
+}
@@ -0,0 +1,450 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ignore files in gitignore format (`.gitignore`, `.ignore` and git's exclude files), honored
+//! hierarchically the way git does when walking a directory tree.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::HashMap;
+use std::path::{MAIN_SEPARATOR, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Names of ignore files read in each directory. Rules in later ones take precedence.
+pub const IGNORE_FILE_NAMES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Directory where git keeps its data. It's never listed.
+const GIT_DIR_NAME: &str = ".git";
+
+#[derive(Debug)]
+struct IgnoreRule {
+    matcher: GlobMatcher,
+    /// `!pattern`: re-includes paths ignored by earlier rules.
+    negated: bool,
+    /// `pattern/`: only matches directories.
+    dir_only: bool,
+}
+
+/// Parse a line of an ignore file. Returns `None` for blank lines, comments and invalid
+/// patterns, which git skips as well.
+fn parse_rule(line: &str) -> Option<IgnoreRule> {
+    let mut line = line.strip_suffix('\r').unwrap_or(line);
+    if line.starts_with('#') {
+        return None;
+    }
+    // Trailing spaces are ignored unless escaped with a backslash.
+    while line.ends_with(' ') && !line.ends_with("\\ ") {
+        line = &line[..line.len() - 1];
+    }
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(pattern) => (true, pattern),
+        None => (false, line),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(pattern) => (true, pattern),
+        None => (false, pattern),
+    };
+    // A slash at the beginning or in the middle anchors the pattern to the directory of the
+    // ignore file. Otherwise it matches at any depth.
+    let glob = match pattern.strip_prefix('/') {
+        Some(pattern) => pattern.to_string(),
+        None if pattern.contains('/') => pattern.to_string(),
+        None => format!("**/{pattern}"),
+    };
+    if glob.is_empty() || glob == "**/" {
+        return None;
+    }
+    let matcher = GlobBuilder::new(&glob)
+        .literal_separator(true)
+        .backslash_escape(true)
+        .build()
+        .ok()?
+        .compile_matcher();
+    Some(IgnoreRule {
+        matcher,
+        negated,
+        dir_only,
+    })
+}
+
+/// Rules of ignore files in gitignore format, matched against paths relative to the directory
+/// the ignore files are in.
+#[derive(Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    pub fn parse(content: &str) -> Self {
+        let mut rules = Self::default();
+        rules.add(content);
+        rules
+    }
+
+    /// Add rules from the content of another ignore file, taking precedence over existing ones.
+    pub fn add(&mut self, content: &str) {
+        self.rules.extend(content.lines().filter_map(parse_rule));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether the path is ignored by the last rule matching it: `Some(false)` if it's
+    /// re-included by a negated rule, `None` if no rule matches.
+    pub fn matched(&self, path: &str, is_dir: bool) -> Option<bool> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.matcher.is_match(path))
+            .map(|rule| !rule.negated)
+    }
+}
+
+/// Read rules of ignore files with the given names in a directory.
+/// Returns `None` if there's no rule.
+fn read_rules(dir: &Path, file_names: &[&str]) -> Option<Arc<IgnoreRules>> {
+    let mut rules = IgnoreRules::default();
+    for file_name in file_names {
+        if let Ok(content) = std::fs::read_to_string(dir.join(file_name)) {
+            rules.add(&content);
+        }
+    }
+    (!rules.is_empty()).then(|| Arc::new(rules))
+}
+
+/// Value of `core.excludesFile` in a git config file.
+fn core_excludes_file(config: &str) -> Option<String> {
+    let mut in_core = false;
+    let mut excludes_file = None;
+    for line in config.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[') {
+            in_core = section
+                .trim_end_matches(']')
+                .trim()
+                .eq_ignore_ascii_case("core");
+        } else if in_core
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim().eq_ignore_ascii_case("excludesfile")
+        {
+            excludes_file = Some(value.trim().trim_matches('"').to_string());
+        }
+    }
+    excludes_file
+}
+
+/// Git directory of a repository: `.git`, or the one it points to for worktrees and submodules.
+fn git_dir(repo_root: &Path) -> PathBuf {
+    let dot_git = repo_root.join(GIT_DIR_NAME);
+    if dot_git.is_file()
+        && let Ok(content) = std::fs::read_to_string(&dot_git)
+        && let Some(git_dir) = content.trim().strip_prefix("gitdir:")
+    {
+        return repo_root.join(git_dir.trim());
+    }
+    dot_git
+}
+
+/// Path of git's global excludes file: `core.excludesFile` if set, otherwise
+/// `$XDG_CONFIG_HOME/git/ignore`.
+fn global_excludes_file(git_dir: Option<&Path>) -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".config")));
+    // Later config files override earlier ones.
+    let config_files = [
+        config_home
+            .as_ref()
+            .map(|dir| dir.join("git").join("config")),
+        home.as_ref().map(|home| home.join(".gitconfig")),
+        git_dir.map(|git_dir| git_dir.join("config")),
+    ];
+    let excludes_file = config_files
+        .into_iter()
+        .rev()
+        .flatten()
+        .find_map(|config_file| {
+            std::fs::read_to_string(config_file)
+                .ok()
+                .and_then(|config| core_excludes_file(&config))
+        });
+    match excludes_file {
+        Some(path) => match (path.strip_prefix("~/"), &home) {
+            (Some(path), Some(home)) => Some(home.join(path)),
+            _ => Some(PathBuf::from(path)),
+        },
+        None => config_home.map(|dir| dir.join("git").join("ignore")),
+    }
+}
+
+/// Ignore files applying to a directory tree, honored the way git does:
+///
+/// - Ignore files in a directory apply to paths under it, and rules of closer ones take
+///   precedence. Within a directory, `.ignore` takes precedence over `.gitignore`.
+/// - Within the git repository containing the tree, ignore files in directories above the tree
+///   apply as well, followed by `.git/info/exclude` and git's global excludes file.
+/// - Paths within an ignored directory are ignored, even if negated rules match them.
+/// - `.git` directories are always ignored.
+///
+/// Ignore files within the tree are read lazily and cached. Call [`IgnoreFiles::reload`] or
+/// [`IgnoreFiles::invalidate`] to pick up changes to them.
+#[derive(Debug)]
+pub struct IgnoreFiles {
+    root: PathBuf,
+    /// Rules applying from outside the tree, by precedence, with the path of the root relative
+    /// to the directory of the rules.
+    outer_rules: RwLock<Vec<(String, Arc<IgnoreRules>)>>,
+    /// Rules of ignore files within the tree, by directory relative to the root.
+    dir_rules: RwLock<HashMap<String, Option<Arc<IgnoreRules>>>>,
+}
+
+impl IgnoreFiles {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let ignore_files = Self {
+            root: root.into(),
+            outer_rules: RwLock::new(Vec::new()),
+            dir_rules: RwLock::new(HashMap::new()),
+        };
+        ignore_files.reload();
+        ignore_files
+    }
+
+    /// Whether the file name of the path is one of [`IGNORE_FILE_NAMES`].
+    pub fn is_ignore_file(path: &str) -> bool {
+        let file_name = path.rsplit(MAIN_SEPARATOR).next().unwrap_or(path);
+        IGNORE_FILE_NAMES.contains(&file_name)
+    }
+
+    /// Discard all cached rules, and re-read rules applying from outside the tree.
+    pub fn reload(&self) {
+        *self.outer_rules.write().unwrap() = self.load_outer_rules();
+        self.dir_rules.write().unwrap().clear();
+    }
+
+    /// Discard cached rules of ignore files in a directory (relative to the root).
+    pub fn invalidate(&self, dir: &str) {
+        self.dir_rules.write().unwrap().remove(dir);
+    }
+
+    fn load_outer_rules(&self) -> Vec<(String, Arc<IgnoreRules>)> {
+        let root = self
+            .root
+            .canonicalize()
+            .unwrap_or_else(|_| self.root.clone());
+        let mut outer_rules = Vec::new();
+        let mut repo_root = None;
+        let mut prefix = String::new();
+        let mut child = root.as_path();
+        if root.join(GIT_DIR_NAME).exists() {
+            repo_root = Some(root.as_path());
+        } else {
+            for dir in root.ancestors().skip(1) {
+                let Some(name) = child.file_name().and_then(|name| name.to_str()) else {
+                    break;
+                };
+                prefix = format!("{name}/{prefix}");
+                if let Some(rules) = read_rules(dir, &IGNORE_FILE_NAMES) {
+                    outer_rules.push((prefix.clone(), rules));
+                }
+                if dir.join(GIT_DIR_NAME).exists() {
+                    repo_root = Some(dir);
+                    break;
+                }
+                child = dir;
+            }
+            if repo_root.is_none() {
+                // Ignore files above the tree only apply within a repository.
+                outer_rules.clear();
+                prefix.clear();
+            }
+        }
+        let git_dir = repo_root.map(git_dir);
+        if let Some(git_dir) = &git_dir
+            && let Some(rules) = read_rules(&git_dir.join("info"), &["exclude"])
+        {
+            outer_rules.push((prefix.clone(), rules));
+        }
+        if let Some(excludes_file) = global_excludes_file(git_dir.as_deref())
+            && let Ok(content) = std::fs::read_to_string(excludes_file)
+        {
+            let rules = IgnoreRules::parse(&content);
+            if !rules.is_empty() {
+                outer_rules.push((prefix, Arc::new(rules)));
+            }
+        }
+        outer_rules
+    }
+
+    /// Rules of ignore files in a directory (relative to the root).
+    fn rules_in(&self, dir: &str) -> Option<Arc<IgnoreRules>> {
+        if let Some(rules) = self.dir_rules.read().unwrap().get(dir) {
+            return rules.clone();
+        }
+        let rules = read_rules(&self.root.join(dir), &IGNORE_FILE_NAMES);
+        self.dir_rules
+            .write()
+            .unwrap()
+            .insert(dir.to_string(), rules.clone());
+        rules
+    }
+
+    /// Whether the path (relative to the root) is ignored by the rules applying to it,
+    /// regardless of its parent directories.
+    fn matched(&self, path: &str, is_dir: bool) -> bool {
+        let mut dir_end = path.rfind(MAIN_SEPARATOR);
+        loop {
+            let (dir, sub_path) = match dir_end {
+                Some(end) => (&path[..end], &path[end + 1..]),
+                None => ("", path),
+            };
+            if let Some(ignored) = self
+                .rules_in(dir)
+                .and_then(|rules| rules.matched(sub_path, is_dir))
+            {
+                return ignored;
+            }
+            match dir_end {
+                Some(end) => dir_end = path[..end].rfind(MAIN_SEPARATOR),
+                None => break,
+            }
+        }
+        self.outer_rules
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|(prefix, rules)| rules.matched(&format!("{prefix}{path}"), is_dir))
+            .unwrap_or(false)
+    }
+
+    /// Whether a file or directory at the path (relative to the root) is ignored, either by
+    /// itself or by one of its parent directories.
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        let mut start = 0;
+        loop {
+            let end = path[start..]
+                .find(MAIN_SEPARATOR)
+                .map(|offset| start + offset);
+            let sub_path_is_dir = end.is_some() || is_dir;
+            let sub_path = &path[..end.unwrap_or(path.len())];
+            let name = &sub_path[start..];
+            if (sub_path_is_dir && name == GIT_DIR_NAME) || self.matched(sub_path, sub_path_is_dir)
+            {
+                return true;
+            }
+            match end {
+                Some(end) => start = end + 1,
+                None => return false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(content: &str) -> IgnoreRules {
+        IgnoreRules::parse(content)
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let rules = rules(
+            "# comment\n\n*.log\n!keep.log\nbuild/\n/target\ndocs/*.tmp\n\\#hash\ntrailing \n",
+        );
+        assert_eq!(rules.matched("debug.log", false), Some(true));
+        assert_eq!(rules.matched("a/b/debug.log", false), Some(true));
+        assert_eq!(rules.matched("a/keep.log", false), Some(false));
+        assert_eq!(rules.matched("main.rs", false), None);
+
+        // Directory-only patterns.
+        assert_eq!(rules.matched("a/build", true), Some(true));
+        assert_eq!(rules.matched("a/build", false), None);
+
+        // Anchored patterns only match relative to the ignore file.
+        assert_eq!(rules.matched("target", true), Some(true));
+        assert_eq!(rules.matched("a/target", true), None);
+        assert_eq!(rules.matched("docs/x.tmp", false), Some(true));
+        assert_eq!(rules.matched("docs/sub/x.tmp", false), None);
+        assert_eq!(rules.matched("a/docs/x.tmp", false), None);
+
+        assert_eq!(rules.matched("#hash", false), Some(true));
+        assert_eq!(rules.matched("trailing", false), Some(true));
+    }
+
+    fn test_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("recoco-ignore-files-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_ignore_files_hierarchy() {
+        let repo = test_root("hierarchy");
+        std::fs::create_dir_all(repo.join(".git/info")).unwrap();
+        write(&repo, ".git/info/exclude", "*.secret\n");
+        write(&repo, ".gitignore", "*.log\nvendor/\n");
+        write(&repo, "src/.gitignore", "!important.log\ngenerated/\n");
+        write(&repo, "src/.ignore", "*.bak\n");
+        write(&repo, "src/nested/.gitignore", "*.bak\n!old.bak\n");
+
+        // The tree is a subdirectory of the repository.
+        let ignore_files = IgnoreFiles::new(repo.join("src"));
+        let is_ignored = |path: &str, is_dir| {
+            ignore_files.is_ignored(&path.replace('/', std::path::MAIN_SEPARATOR_STR), is_dir)
+        };
+        assert!(is_ignored("debug.log", false));
+        assert!(!is_ignored("important.log", false));
+        assert!(!is_ignored("a/important.log", false));
+        assert!(is_ignored("key.secret", false));
+        assert!(is_ignored("a.bak", false));
+        assert!(!is_ignored("nested/old.bak", false));
+        assert!(is_ignored("nested/new.bak", false));
+        assert!(!is_ignored("main.rs", false));
+
+        // Paths within ignored directories stay ignored.
+        assert!(is_ignored("generated", true));
+        assert!(is_ignored("generated/important.log", false));
+        assert!(is_ignored("lib/vendor/x.rs", false));
+        assert!(is_ignored(".git/config", false));
+
+        // Changes are picked up once reloaded.
+        write(&repo, "src/.ignore", "");
+        assert!(is_ignored("a.bak", false));
+        ignore_files.invalidate("");
+        assert!(!is_ignored("a.bak", false));
+        write(&repo, ".git/info/exclude", "");
+        ignore_files.reload();
+        assert!(!is_ignored("key.secret", false));
+
+        std::fs::remove_dir_all(&repo).unwrap();
+    }
+
+    #[test]
+    fn test_core_excludes_file() {
+        let config = "[user]\n\tname = x\n[core]\n\texcludesFile = \"~/.gitignore_global\"\n";
+        assert_eq!(
+            core_excludes_file(config).as_deref(),
+            Some("~/.gitignore_global")
+        );
+        assert_eq!(core_excludes_file("[user]\n\texcludesfile = x\n"), None);
+    }
+}
@@ -0,0 +1,688 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Content-aware programming language detection.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use regex::Regex;
+
+use crate::prog_langs::get_language_info;
+
+/// Template suffixes stripped to find the language of the rendered file
+/// (e.g. `config.yaml.j2`).
+const TEMPLATE_SUFFIXES: &[&str] = &[
+    ".dist",
+    ".erb",
+    ".example",
+    ".in",
+    ".j2",
+    ".jinja",
+    ".jinja2",
+    ".liquid",
+    ".sample",
+    ".template",
+    ".tmpl",
+    ".tpl",
+];
+
+/// Files recognized by their exact name, beyond the aliases in the language table.
+const WELL_KNOWN_FILENAMES: &[(&str, &str)] = &[
+    (".bash_aliases", "bash"),
+    (".bash_logout", "bash"),
+    (".bash_profile", "bash"),
+    (".bashrc", "bash"),
+    (".dockerignore", "gitignore"),
+    (".editorconfig", "ini"),
+    (".emacs", "elisp"),
+    (".gitconfig", "ini"),
+    (".gitmodules", "ini"),
+    (".gvimrc", "vim"),
+    (".irbrc", "ruby"),
+    (".npmrc", "ini"),
+    (".profile", "bash"),
+    (".pryrc", "ruby"),
+    (".vimrc", "vim"),
+    (".zprofile", "bash"),
+    (".zshenv", "bash"),
+    (".zshrc", "bash"),
+    ("APKBUILD", "bash"),
+    ("BUILD", "starlark"),
+    ("BUILD.bazel", "starlark"),
+    ("Brewfile", "ruby"),
+    ("CMakeLists.txt", "cmake"),
+    ("Capfile", "ruby"),
+    ("Cargo.lock", "toml"),
+    ("Containerfile", "dockerfile"),
+    ("Dockerfile", "dockerfile"),
+    ("Fastfile", "ruby"),
+    ("GNUmakefile", "make"),
+    ("Gemfile", "ruby"),
+    ("Guardfile", "ruby"),
+    ("Jenkinsfile", "groovy"),
+    ("MODULE.bazel", "starlark"),
+    ("Makefile", "make"),
+    ("PKGBUILD", "bash"),
+    ("Pipfile", "toml"),
+    ("Podfile", "ruby"),
+    ("Rakefile", "ruby"),
+    ("SConscript", "python"),
+    ("SConstruct", "python"),
+    ("Snakefile", "python"),
+    ("Tiltfile", "starlark"),
+    ("Vagrantfile", "ruby"),
+    ("WORKSPACE", "starlark"),
+    ("WORKSPACE.bazel", "starlark"),
+    ("_emacs", "elisp"),
+    ("_vimrc", "vim"),
+    ("makefile", "make"),
+    ("meson_options.txt", "meson"),
+    ("poetry.lock", "toml"),
+    ("wscript", "python"),
+];
+
+/// Filename prefixes for variants such as `Dockerfile.dev` or `Makefile.linux`.
+const WELL_KNOWN_PREFIXES: &[(&str, &str)] = &[
+    ("Containerfile.", "dockerfile"),
+    ("Dockerfile.", "dockerfile"),
+    ("Makefile.", "make"),
+    ("requirements-", "requirements"),
+    ("requirements_", "requirements"),
+];
+
+/// Interpreter names (from shebangs) and editor mode names that aren't language names or
+/// aliases themselves.
+const INTERPRETER_ALIASES: &[(&str, &str)] = &[
+    ("ash", "bash"),
+    ("bun", "javascript"),
+    ("clisp", "commonlisp"),
+    ("dash", "bash"),
+    ("deno", "typescript"),
+    ("emacs-lisp", "elisp"),
+    ("guile", "scheme"),
+    ("jruby", "ruby"),
+    ("ksh", "bash"),
+    ("luajit", "lua"),
+    ("makefile", "make"),
+    ("node", "javascript"),
+    ("nodejs", "javascript"),
+    ("pwsh", "powershell"),
+    ("pypy", "python"),
+    ("rscript", "r"),
+    ("runghc", "haskell"),
+    ("runhaskell", "haskell"),
+    ("sbcl", "commonlisp"),
+    ("sh", "bash"),
+    ("shell-script", "bash"),
+    ("tclsh", "tcl"),
+    ("ts-node", "typescript"),
+    ("wish", "tcl"),
+    ("zsh", "bash"),
+];
+
+/// Weighted markers for the token-frequency classifier. Markers made of word characters
+/// match whole tokens; others match as substrings.
+const CLASSIFIER_MARKERS: &[(&str, &[(&str, f64)])] = &[
+    (
+        "bash",
+        &[
+            ("fi", 3.0),
+            ("esac", 3.0),
+            ("done", 1.5),
+            ("then", 1.5),
+            ("echo", 1.5),
+            ("local", 1.0),
+            ("export", 1.0),
+            ("$(", 1.5),
+            ("${", 1.0),
+            ("[[ ", 2.0),
+        ],
+    ),
+    (
+        "c",
+        &[
+            ("#include", 2.0),
+            ("printf", 1.5),
+            ("malloc", 2.0),
+            ("sizeof", 1.5),
+            ("typedef", 1.5),
+            ("NULL", 1.5),
+            ("int", 0.5),
+            ("char", 1.0),
+            ("void", 0.5),
+            ("struct", 0.5),
+        ],
+    ),
+    (
+        "cpp",
+        &[
+            ("#include", 1.5),
+            ("std::", 3.0),
+            ("namespace", 1.0),
+            ("template", 2.0),
+            ("nullptr", 2.0),
+            ("cout", 2.0),
+            ("public:", 2.0),
+            ("private:", 2.0),
+            ("auto", 0.5),
+        ],
+    ),
+    (
+        "csharp",
+        &[
+            ("using", 1.5),
+            ("namespace", 1.0),
+            ("Console", 2.0),
+            ("string", 0.5),
+            ("var", 0.5),
+            ("async", 0.5),
+            ("{ get;", 3.0),
+            ("public", 0.5),
+            ("override", 1.0),
+        ],
+    ),
+    (
+        "css",
+        &[
+            ("px;", 2.0),
+            ("@media", 3.0),
+            ("color:", 1.5),
+            ("margin:", 2.0),
+            ("padding:", 2.0),
+            ("display:", 2.0),
+            ("font-", 1.0),
+        ],
+    ),
+    (
+        "go",
+        &[
+            ("func", 2.0),
+            ("package", 1.5),
+            (":=", 2.0),
+            ("chan", 2.0),
+            ("defer", 2.5),
+            ("fmt", 2.0),
+            ("nil", 1.0),
+            ("err != nil", 3.0),
+        ],
+    ),
+    (
+        "html",
+        &[
+            ("<!DOCTYPE", 3.0),
+            ("<html", 3.0),
+            ("<div", 2.0),
+            ("<head", 2.0),
+            ("<body", 2.0),
+            ("</", 0.5),
+            ("class=\"", 1.0),
+        ],
+    ),
+    (
+        "java",
+        &[
+            ("public", 1.0),
+            ("class", 0.5),
+            ("static", 0.5),
+            ("void", 0.5),
+            ("System.out", 3.0),
+            ("extends", 1.0),
+            ("implements", 1.0),
+            ("@Override", 3.0),
+            ("final", 1.0),
+            ("import java", 3.0),
+        ],
+    ),
+    (
+        "javascript",
+        &[
+            ("function", 1.5),
+            ("const", 1.0),
+            ("let", 0.5),
+            ("var", 0.5),
+            ("=>", 1.0),
+            ("===", 2.0),
+            ("console", 2.0),
+            ("require", 1.5),
+            ("module.exports", 3.0),
+            ("undefined", 2.0),
+            ("document", 1.5),
+        ],
+    ),
+    (
+        "markdown",
+        &[
+            ("# ", 1.0),
+            ("## ", 2.0),
+            ("```", 2.0),
+            ("](", 2.0),
+            ("- [", 1.0),
+        ],
+    ),
+    (
+        "php",
+        &[
+            ("<?php", 5.0),
+            ("$this", 3.0),
+            ("echo", 1.0),
+            ("function", 1.0),
+            ("array", 1.0),
+            ("->", 0.5),
+        ],
+    ),
+    (
+        "python",
+        &[
+            ("def", 2.0),
+            ("elif", 3.0),
+            ("self", 2.0),
+            ("None", 2.0),
+            ("True", 1.0),
+            ("False", 1.0),
+            ("import", 1.0),
+            ("lambda", 1.5),
+            ("__init__", 3.0),
+            ("__name__", 3.0),
+        ],
+    ),
+    (
+        "ruby",
+        &[
+            ("def", 1.5),
+            ("end", 1.5),
+            ("elsif", 3.0),
+            ("puts", 2.0),
+            ("require", 1.0),
+            ("attr_accessor", 3.0),
+            ("nil", 1.0),
+            ("do |", 3.0),
+            ("module", 1.0),
+        ],
+    ),
+    (
+        "rust",
+        &[
+            ("fn", 2.0),
+            ("let", 1.0),
+            ("mut", 2.0),
+            ("impl", 2.0),
+            ("pub", 1.0),
+            ("::", 1.0),
+            ("->", 0.5),
+            ("match", 1.0),
+            ("&self", 3.0),
+            ("println!", 3.0),
+            ("Some", 1.0),
+            ("Ok", 1.0),
+        ],
+    ),
+    (
+        "sql",
+        &[
+            ("SELECT", 2.0),
+            ("select", 1.0),
+            ("FROM", 1.5),
+            ("WHERE", 1.5),
+            ("INSERT", 2.0),
+            ("CREATE", 1.5),
+            ("TABLE", 1.5),
+            ("JOIN", 2.0),
+            ("GROUP BY", 2.0),
+        ],
+    ),
+    (
+        "typescript",
+        &[
+            ("interface", 1.5),
+            ("type", 0.5),
+            (": string", 3.0),
+            (": number", 3.0),
+            (": boolean", 3.0),
+            ("export", 0.5),
+            ("readonly", 2.0),
+            ("const", 0.5),
+            ("=>", 0.5),
+        ],
+    ),
+];
+
+/// Maximum number of bytes inspected by the content classifier.
+const CLASSIFIER_MAX_BYTES: usize = 16 * 1024;
+/// Minimum score for the classifier to report a language.
+const CLASSIFIER_MIN_SCORE: f64 = 6.0;
+/// Upper bound of the classifier's confidence, below every explicit signal.
+const CLASSIFIER_MAX_CONFIDENCE: f64 = 0.6;
+
+static EMACS_MODELINE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"-\*-(.*?)-\*-").unwrap());
+static VIM_MODELINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|\s)(?:vi|vim|ex)(?:[<=>]?\d+)?:.*?\b(?:ft|filetype|syntax)=([\w+#.-]+)")
+        .unwrap()
+});
+
+/// How a language was detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionSource {
+    /// An Emacs or Vim modeline in the content.
+    Modeline,
+    /// A well-known filename, e.g. `Dockerfile` or `Makefile`.
+    Filename,
+    /// The file extension.
+    Extension,
+    /// The extension under a template suffix, e.g. `.yaml` in `config.yaml.j2`.
+    TemplateExtension,
+    /// The interpreter named by a `#!` line.
+    Shebang,
+    /// Token frequencies in the content.
+    Classifier,
+}
+
+/// A detected programming language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LanguageDetection {
+    /// The main name of the language.
+    pub language: Arc<str>,
+    /// Confidence in `[0, 1]`.
+    pub confidence: f64,
+    /// The signal the language was detected from.
+    pub source: DetectionSource,
+}
+
+impl DetectionSource {
+    fn confidence(&self) -> f64 {
+        match self {
+            Self::Modeline => 1.0,
+            Self::Filename => 0.95,
+            Self::Extension => 0.9,
+            Self::Shebang => 0.9,
+            Self::TemplateExtension => 0.8,
+            Self::Classifier => CLASSIFIER_MAX_CONFIDENCE,
+        }
+    }
+
+    fn detection(self, language: &str) -> Option<LanguageDetection> {
+        Some(LanguageDetection {
+            language: get_language_info(language)?.name.clone(),
+            confidence: self.confidence(),
+            source: self,
+        })
+    }
+}
+
+/// Detect the language of a file from its name and, if available, its content.
+///
+/// Signals are tried from the most to the least explicit: modelines, well-known filenames,
+/// the extension (also under template suffixes like `.j2`), the shebang, and finally a
+/// token-frequency classifier over the content.
+pub fn detect_language_with_content(
+    filename: &str,
+    content: Option<&str>,
+) -> Option<LanguageDetection> {
+    if let Some(language) = content.and_then(language_from_modeline) {
+        return DetectionSource::Modeline.detection(language);
+    }
+    if let Some((language, source)) = detect_from_filename(filename) {
+        return source.detection(language);
+    }
+    let content = content?;
+    if let Some(language) = language_from_shebang(content) {
+        return DetectionSource::Shebang.detection(language);
+    }
+    classify_content(content)
+}
+
+/// Detection based on the file name only.
+pub(crate) fn detect_from_filename(filename: &str) -> Option<(&str, DetectionSource)> {
+    let basename = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    if let Some(language) = language_from_basename(basename) {
+        return Some((language, DetectionSource::Filename));
+    }
+    if let Some(language) = language_from_extension(basename) {
+        return Some((language, DetectionSource::Extension));
+    }
+    let lowercase = basename.to_ascii_lowercase();
+    let stripped = TEMPLATE_SUFFIXES
+        .iter()
+        .find_map(|suffix| lowercase.strip_suffix(suffix))?;
+    let stripped = &basename[..stripped.len()];
+    language_from_basename(stripped)
+        .or_else(|| language_from_extension(stripped))
+        .map(|language| (language, DetectionSource::TemplateExtension))
+}
+
+fn canonical_name(name: &str) -> Option<&str> {
+    let name = INTERPRETER_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map_or(name, |(_, language)| language);
+    get_language_info(name).map(|info| info.name.as_ref())
+}
+
+fn language_from_basename(basename: &str) -> Option<&str> {
+    if let Some((_, language)) = WELL_KNOWN_FILENAMES.iter().find(|(f, _)| *f == basename) {
+        return canonical_name(language);
+    }
+    if let Some((_, language)) = WELL_KNOWN_PREFIXES
+        .iter()
+        .find(|(prefix, _)| basename.starts_with(prefix))
+    {
+        return canonical_name(language);
+    }
+    // Full filenames listed as language aliases, e.g. `go.mod`.
+    if basename.contains('.') && !basename.starts_with('.') {
+        return get_language_info(basename).map(|info| info.name.as_ref());
+    }
+    None
+}
+
+fn language_from_extension(basename: &str) -> Option<&str> {
+    let last_dot = basename.rfind('.')?;
+    get_language_info(&basename[last_dot..]).map(|info| info.name.as_ref())
+}
+
+fn language_from_modeline(content: &str) -> Option<&str> {
+    let lines: Vec<&str> = content.lines().collect();
+    // Emacs reads the first line, or the second one after a shebang.
+    for line in lines.iter().take(2) {
+        let Some(captures) = EMACS_MODELINE.captures(line) else {
+            continue;
+        };
+        let Some(vars) = captures.get(1).map(|m| m.as_str().trim()) else {
+            continue;
+        };
+        let mode = if vars.contains(':') {
+            vars.split(';').find_map(|var| {
+                let (key, value) = var.split_once(':')?;
+                key.trim()
+                    .eq_ignore_ascii_case("mode")
+                    .then(|| value.trim())
+            })
+        } else {
+            Some(vars)
+        };
+        if let Some(language) = mode.and_then(canonical_name) {
+            return Some(language);
+        }
+    }
+    // Vim reads the first and last five lines.
+    let tail_start = lines.len().saturating_sub(5).max(5.min(lines.len()));
+    lines
+        .iter()
+        .take(5)
+        .chain(lines[tail_start..].iter())
+        .find_map(|line| {
+            let captures = VIM_MODELINE.captures(line)?;
+            canonical_name(captures.get(1)?.as_str())
+        })
+}
+
+fn language_from_shebang(content: &str) -> Option<&str> {
+    let line = content.lines().next()?.strip_prefix("#!")?;
+    let mut parts = line.split_whitespace();
+    let program = parts.next()?;
+    let mut interpreter = program.rsplit('/').next()?;
+    if interpreter == "env" {
+        // Skip `env` options (e.g. `-S`) and variable assignments.
+        interpreter = parts.find(|arg| !arg.starts_with('-') && !arg.contains('='))?;
+    }
+    // `python3.11` -> `python`
+    let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    canonical_name(interpreter)
+}
+
+fn classify_content(content: &str) -> Option<LanguageDetection> {
+    let mut end = content.len().min(CLASSIFIER_MAX_BYTES);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    let sample = &content[..end];
+
+    let mut word_counts: HashMap<&str, usize> = HashMap::new();
+    for word in sample
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+    {
+        *word_counts.entry(word).or_default() += 1;
+    }
+
+    let scores: Vec<(&str, f64)> = CLASSIFIER_MARKERS
+        .iter()
+        .map(|(language, markers)| {
+            let score = markers
+                .iter()
+                .map(|(marker, weight)| {
+                    let count = if marker.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                        word_counts.get(marker).copied().unwrap_or(0)
+                    } else {
+                        sample.matches(marker).count()
+                    };
+                    // Diminishing returns, so one frequent marker doesn't dominate.
+                    weight * (count as f64).ln_1p()
+                })
+                .sum::<f64>();
+            (*language, score)
+        })
+        .collect();
+
+    let total: f64 = scores.iter().map(|(_, score)| score).sum();
+    let (language, best) = scores.into_iter().max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    if best < CLASSIFIER_MIN_SCORE {
+        return None;
+    }
+    Some(LanguageDetection {
+        confidence: CLASSIFIER_MAX_CONFIDENCE * best / total,
+        ..DetectionSource::Classifier.detection(language)?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(filename: &str, content: &str) -> Option<(String, DetectionSource)> {
+        detect_language_with_content(filename, Some(content))
+            .map(|d| (d.language.to_string(), d.source))
+    }
+
+    #[test]
+    fn test_tables_use_canonical_names() {
+        let languages = WELL_KNOWN_FILENAMES
+            .iter()
+            .chain(WELL_KNOWN_PREFIXES)
+            .chain(INTERPRETER_ALIASES)
+            .map(|(_, language)| *language)
+            .chain(CLASSIFIER_MARKERS.iter().map(|(language, _)| *language));
+        for language in languages {
+            let info = get_language_info(language)
+                .unwrap_or_else(|| panic!("unknown language `{language}`"));
+            assert_eq!(info.name.as_ref(), language);
+        }
+    }
+
+    #[test]
+    fn test_detect_from_filename() {
+        assert_eq!(
+            detect("build/Dockerfile", ""),
+            Some(("dockerfile".to_string(), DetectionSource::Filename))
+        );
+        assert_eq!(
+            detect("Makefile.linux", ""),
+            Some(("make".to_string(), DetectionSource::Filename))
+        );
+        assert_eq!(
+            detect("src/main.rs", ""),
+            Some(("rust".to_string(), DetectionSource::Extension))
+        );
+        assert_eq!(
+            detect("templates/values.yaml.tmpl", ""),
+            Some(("yaml".to_string(), DetectionSource::TemplateExtension))
+        );
+        assert_eq!(detect("notes", ""), None);
+    }
+
+    #[test]
+    fn test_detect_from_shebang() {
+        for (shebang, language) in [
+            ("#!/usr/bin/env python3.11", "python"),
+            ("#!/bin/sh -e", "bash"),
+            ("#!/usr/bin/env -S node --no-warnings", "javascript"),
+            ("#!/usr/bin/env DEBUG=1 ruby", "ruby"),
+            ("#! /usr/bin/perl -w", "perl"),
+        ] {
+            assert_eq!(
+                detect("script", &format!("{shebang}\nbody\n")),
+                Some((language.to_string(), DetectionSource::Shebang)),
+                "{shebang}"
+            );
+        }
+        // The extension wins over the shebang.
+        assert_eq!(
+            detect("run.py", "#!/bin/bash\n").map(|(l, _)| l),
+            Some("python".to_string())
+        );
+    }
+
+    #[test]
+    fn test_detect_from_modeline() {
+        for (content, language) in [
+            ("# -*- mode: ruby; coding: utf-8 -*-\nputs 1\n", "ruby"),
+            ("#!/bin/sh\n# -*- python -*-\n", "python"),
+            ("x = 1\n\n# vim: set ft=python ts=4 :\n", "python"),
+            ("// vim: filetype=javascript\n", "javascript"),
+            ("; -*- emacs-lisp -*-\n", "elisp"),
+        ] {
+            assert_eq!(
+                detect("file.txt", content),
+                Some((language.to_string(), DetectionSource::Modeline)),
+                "{content}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_classify_content() {
+        let python = "import os\n\nclass Foo:\n    def __init__(self):\n        self.x = None\n\n    def run(self):\n        if self.x is None:\n            return False\n        elif self.x:\n            return True\n";
+        let detection = detect_language_with_content("snippet", Some(python)).unwrap();
+        assert_eq!(detection.language.as_ref(), "python");
+        assert_eq!(detection.source, DetectionSource::Classifier);
+        assert!(detection.confidence > 0.0 && detection.confidence <= CLASSIFIER_MAX_CONFIDENCE);
+
+        let rust = "use std::fmt;\n\npub struct Foo {\n    x: i32,\n}\n\nimpl Foo {\n    pub fn new() -> Self {\n        let mut x = 0;\n        x += 1;\n        println!(\"{}\", x);\n        Self { x }\n    }\n\n    fn get(&self) -> Option<i32> {\n        Some(self.x)\n    }\n}\n";
+        assert_eq!(
+            detect("snippet", rust).map(|(l, _)| l),
+            Some("rust".to_string())
+        );
+
+        // Too little evidence.
+        assert_eq!(detect("snippet", "hello world"), None);
+    }
+}
@@ -15,17 +15,40 @@
 //! This crate provides text processing functionality including:
 //! - Programming language detection and tree-sitter support
 //! - Text splitting by separators
-//! - Recursive text chunking with syntax awareness
-//! - Pattern matching for file filtering
+//! - Recursive text chunking with syntax awareness, streamed or in parallel for large texts
+//! - Semantic chunking at topic shifts
+//! - Code symbol extraction
+//! - PII and secret detection
+//! - Pattern matching for file filtering, optionally honoring `.gitignore` and `.ignore` files
 
 #[cfg(feature = "splitter-separator")]
 mod by_separators;
-#[cfg(any(feature = "splitter-separator", feature = "splitter-recursive"))]
+#[cfg(feature = "splitter-recursive")]
+mod context;
+#[cfg(feature = "splitter-recursive")]
+mod grammar;
+#[cfg(feature = "pattern-matching")]
+pub mod ignore_files;
+#[cfg(feature = "splitter-recursive")]
+mod lang_detect;
+#[cfg(any(
+    feature = "pii",
+    feature = "splitter-separator",
+    feature = "splitter-recursive"
+))]
 mod output_positions;
 #[cfg(feature = "pattern-matching")]
 pub mod pattern_matcher;
+#[cfg(feature = "pii")]
+pub mod pii;
 #[cfg(feature = "splitter-recursive")]
 pub mod prog_langs;
 #[cfg(feature = "splitter-recursive")]
 mod recursive;
+#[cfg(feature = "splitter-recursive")]
+mod segmented;
+#[cfg(feature = "splitter-semantic")]
+mod semantic;
 pub mod split;
+#[cfg(feature = "splitter-recursive")]
+pub mod symbols;
@@ -12,6 +12,10 @@
 
 use anyhow::Result;
 use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::ignore_files::IgnoreFiles;
 
 /// Builds a GlobSet from a vector of pattern strings
 fn build_glob_set(patterns: Vec<String>) -> Result<GlobSet> {
@@ -30,6 +34,8 @@ pub struct PatternMatcher {
     /// Patterns matching full path of files and directories to be excluded.
     /// If a directory is excluded, all files and subdirectories within it are also excluded.
     excluded_glob_set: Option<GlobSet>,
+    /// Ignore files honored under the directory matched paths are relative to, if enabled.
+    ignore_files: Option<Arc<IgnoreFiles>>,
 }
 
 impl PatternMatcher {
@@ -44,24 +50,50 @@ impl PatternMatcher {
         Ok(Self {
             included_glob_set,
             excluded_glob_set,
+            ignore_files: None,
         })
     }
 
-    /// Check if a file or directory is excluded by the exclude patterns
-    /// Can be called on directories to prune traversal on excluded directories.
-    pub fn is_excluded(&self, path: &str) -> bool {
+    /// Also exclude paths ignored by ignore files (`.gitignore`, `.ignore` and git's exclude
+    /// files) under `root`, which matched paths are relative to.
+    pub fn with_ignore_files(mut self, root: impl Into<PathBuf>) -> Self {
+        self.ignore_files = Some(Arc::new(IgnoreFiles::new(root)));
+        self
+    }
+
+    /// Ignore files honored by the matcher, shared by its clones.
+    pub fn ignore_files(&self) -> Option<&IgnoreFiles> {
+        self.ignore_files.as_deref()
+    }
+
+    fn is_excluded_by_patterns(&self, path: &str) -> bool {
         self.excluded_glob_set
             .as_ref()
             .is_some_and(|glob_set| glob_set.is_match(path))
     }
 
+    /// Check if a file or directory is excluded by the exclude patterns
+    /// Can be called on directories to prune traversal on excluded directories.
+    /// Ignore files are applied treating the path as a directory.
+    pub fn is_excluded(&self, path: &str) -> bool {
+        self.is_excluded_by_patterns(path)
+            || self
+                .ignore_files
+                .as_ref()
+                .is_some_and(|ignore_files| ignore_files.is_ignored(path, true))
+    }
+
     /// Check if a file should be included based on both include and exclude patterns
     /// Should be called for each file.
     pub fn is_file_included(&self, path: &str) -> bool {
         self.included_glob_set
             .as_ref()
             .is_none_or(|glob_set| glob_set.is_match(path))
-            && !self.is_excluded(path)
+            && !self.is_excluded_by_patterns(path)
+            && !self
+                .ignore_files
+                .as_ref()
+                .is_some_and(|ignore_files| ignore_files.is_ignored(path, false))
     }
 }
 
@@ -16,10 +16,20 @@ use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, LazyLock};
 use unicase::UniCase;
 
+use crate::lang_detect;
+pub use crate::lang_detect::{DetectionSource, LanguageDetection, detect_language_with_content};
+
 /// Tree-sitter language information for syntax-aware parsing.
 pub struct TreeSitterLanguageInfo {
     pub tree_sitter_lang: tree_sitter::Language,
     pub terminal_node_kind_ids: HashSet<u16>,
+    /// Query locating symbol definitions, used by [`crate::symbols::extract_symbols`].
+    ///
+    /// Captures follow the `tags.scm` convention: `@definition.<kind>` marks a definition
+    /// node and `@name` its name. `@scope.type` and `@scope.module` mark containers that
+    /// only contribute to the parent path (e.g. Rust `impl` blocks, namespaces), and an
+    /// optional `@parent` names an out-of-line owner (e.g. a Go method receiver).
+    pub symbol_query: Option<tree_sitter::Query>,
 }
 
 impl TreeSitterLanguageInfo {
@@ -44,10 +54,146 @@ impl TreeSitterLanguageInfo {
         Self {
             tree_sitter_lang,
             terminal_node_kind_ids,
+            symbol_query: None,
         }
     }
+
+    #[allow(dead_code)]
+    fn with_symbol_query(mut self, source: &str) -> Self {
+        let query = tree_sitter::Query::new(&self.tree_sitter_lang, source)
+            .unwrap_or_else(|e| panic!("Invalid symbol query: {e}"));
+        self.symbol_query = Some(query);
+        self
+    }
 }
 
+// Symbol queries, see `TreeSitterLanguageInfo::symbol_query` for the capture conventions.
+
+#[cfg(feature = "c")]
+const C_SYMBOL_QUERY: &str = r#"
+(function_definition
+  declarator: [
+    (function_declarator declarator: (identifier) @name)
+    (pointer_declarator declarator: (function_declarator declarator: (identifier) @name))
+  ]) @definition.function
+(struct_specifier name: (type_identifier) @name body: (_)) @definition.struct
+(union_specifier name: (type_identifier) @name body: (_)) @definition.struct
+(enum_specifier name: (type_identifier) @name body: (_)) @definition.enum
+"#;
+
+#[cfg(feature = "c-sharp")]
+const CSHARP_SYMBOL_QUERY: &str = r#"
+(class_declaration name: (identifier) @name) @definition.class
+(record_declaration name: (identifier) @name) @definition.class
+(struct_declaration name: (identifier) @name) @definition.struct
+(interface_declaration name: (identifier) @name) @definition.interface
+(enum_declaration name: (identifier) @name) @definition.enum
+(method_declaration name: (identifier) @name) @definition.method
+(constructor_declaration name: (identifier) @name) @definition.method
+(namespace_declaration name: (_) @name) @scope.module
+"#;
+
+#[cfg(feature = "cpp")]
+const CPP_SYMBOL_QUERY: &str = r#"
+(function_definition
+  declarator: [
+    (function_declarator declarator: [(identifier) (field_identifier)] @name)
+    (pointer_declarator declarator: (function_declarator declarator: [(identifier) (field_identifier)] @name))
+  ]) @definition.function
+(function_definition
+  declarator: (function_declarator
+    declarator: (qualified_identifier scope: (_) @parent name: (identifier) @name))) @definition.method
+(class_specifier name: (type_identifier) @name body: (_)) @definition.class
+(struct_specifier name: (type_identifier) @name body: (_)) @definition.struct
+(union_specifier name: (type_identifier) @name body: (_)) @definition.struct
+(enum_specifier name: (type_identifier) @name body: (_)) @definition.enum
+(namespace_definition name: (namespace_identifier) @name) @scope.module
+"#;
+
+#[cfg(feature = "go")]
+const GO_SYMBOL_QUERY: &str = r#"
+(function_declaration name: (identifier) @name) @definition.function
+(method_declaration
+  receiver: (parameter_list
+    (parameter_declaration
+      type: [(type_identifier) @parent (pointer_type (type_identifier) @parent)]))
+  name: (field_identifier) @name) @definition.method
+(type_declaration (type_spec name: (type_identifier) @name type: (struct_type))) @definition.struct
+(type_declaration (type_spec name: (type_identifier) @name type: (interface_type))) @definition.interface
+"#;
+
+#[cfg(feature = "java")]
+const JAVA_SYMBOL_QUERY: &str = r#"
+(class_declaration name: (identifier) @name) @definition.class
+(record_declaration name: (identifier) @name) @definition.class
+(interface_declaration name: (identifier) @name) @definition.interface
+(enum_declaration name: (identifier) @name) @definition.enum
+(method_declaration name: (identifier) @name) @definition.method
+(constructor_declaration name: (identifier) @name) @definition.method
+"#;
+
+#[cfg(feature = "javascript")]
+const JAVASCRIPT_SYMBOL_QUERY: &str = r#"
+(function_declaration name: (identifier) @name) @definition.function
+(generator_function_declaration name: (identifier) @name) @definition.function
+(lexical_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: [(arrow_function) (function_expression)])) @definition.function
+(class_declaration name: (_) @name) @definition.class
+(method_definition name: (_) @name) @definition.method
+"#;
+
+#[cfg(feature = "python")]
+const PYTHON_SYMBOL_QUERY: &str = r#"
+(function_definition name: (identifier) @name) @definition.function
+(class_definition name: (identifier) @name) @definition.class
+"#;
+
+#[cfg(feature = "ruby")]
+const RUBY_SYMBOL_QUERY: &str = r#"
+(method name: (_) @name) @definition.function
+(singleton_method name: (_) @name) @definition.method
+(class name: [(constant) @name (scope_resolution name: (_) @name)]) @definition.class
+(module name: [(constant) @name (scope_resolution name: (_) @name)]) @scope.module
+"#;
+
+#[cfg(feature = "rust")]
+const RUST_SYMBOL_QUERY: &str = r#"
+(function_item name: (identifier) @name) @definition.function
+(function_signature_item name: (identifier) @name) @definition.function
+(struct_item name: (type_identifier) @name) @definition.struct
+(union_item name: (type_identifier) @name) @definition.struct
+(enum_item name: (type_identifier) @name) @definition.enum
+(trait_item name: (type_identifier) @name) @definition.trait
+(impl_item
+  type: [
+    (type_identifier) @name
+    (generic_type type: (type_identifier) @name)
+    (scoped_type_identifier name: (type_identifier) @name)
+  ]) @scope.type
+(mod_item name: (identifier) @name) @scope.module
+"#;
+
+#[cfg(feature = "typescript")]
+const TYPESCRIPT_SYMBOL_QUERY: &str = r#"
+(function_declaration name: (identifier) @name) @definition.function
+(generator_function_declaration name: (identifier) @name) @definition.function
+(function_signature name: (identifier) @name) @definition.function
+(lexical_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: [(arrow_function) (function_expression)])) @definition.function
+(class_declaration name: (_) @name) @definition.class
+(abstract_class_declaration name: (_) @name) @definition.class
+(interface_declaration name: (_) @name) @definition.interface
+(enum_declaration name: (_) @name) @definition.enum
+(method_definition name: (_) @name) @definition.method
+(method_signature name: (_) @name) @definition.method
+(abstract_method_signature name: (_) @name) @definition.method
+(internal_module name: (_) @name) @scope.module
+"#;
+
 /// Information about a programming language.
 pub struct ProgrammingLanguageInfo {
     /// The main name of the language.
@@ -99,7 +245,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             add(
                 "c",
                 &[".c", ".cats", ".h.in", ".idc"],
-                Some(TreeSitterLanguageInfo::new(tree_sitter_c::LANGUAGE, [])),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_c::LANGUAGE, [])
+                        .with_symbol_query(C_SYMBOL_QUERY),
+                ),
             );
         } else {
             add("c", &[".c", ".cats", ".h.in", ".idc"], None);
@@ -132,7 +281,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
                     ".cpp", ".h", ".c++", ".cc", ".cp", ".cppm", ".cxx", ".h++", ".hh", ".hpp", ".hxx",
                     ".inl", ".ipp", ".ixx", ".tcc", ".tpp", ".txx", "c++",
                 ],
-                Some(TreeSitterLanguageInfo::new(tree_sitter_cpp::LANGUAGE, [])),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_cpp::LANGUAGE, [])
+                        .with_symbol_query(CPP_SYMBOL_QUERY),
+                ),
             );
         } else {
             add(
@@ -151,10 +303,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             add(
                 "csharp",
                 &[".cs", ".cake", ".cs.pp", ".csx", ".linq", "cs", "c#"],
-                Some(TreeSitterLanguageInfo::new(
-                    tree_sitter_c_sharp::LANGUAGE,
-                    [],
-                )),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_c_sharp::LANGUAGE, [])
+                        .with_symbol_query(CSHARP_SYMBOL_QUERY),
+                ),
             );
         } else {
             add(
@@ -239,7 +391,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             add(
                 "go",
                 &[".go", "golang"],
-                Some(TreeSitterLanguageInfo::new(tree_sitter_go::LANGUAGE, [])),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_go::LANGUAGE, [])
+                        .with_symbol_query(GO_SYMBOL_QUERY),
+                ),
             );
         } else {
             add("go", &[".go", "golang"], None);
@@ -280,7 +435,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             add(
                 "java",
                 &[".java", ".jav", ".jsh"],
-                Some(TreeSitterLanguageInfo::new(tree_sitter_java::LANGUAGE, [])),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_java::LANGUAGE, [])
+                        .with_symbol_query(JAVA_SYMBOL_QUERY),
+                ),
             );
         } else {
             add("java", &[".java", ".jav", ".jsh"], None);
@@ -317,10 +475,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             ".xsjslib",
             "js",
         ],
-                Some(TreeSitterLanguageInfo::new(
-                    tree_sitter_javascript::LANGUAGE,
-                    [],
-                )),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_javascript::LANGUAGE, [])
+                        .with_symbol_query(JAVASCRIPT_SYMBOL_QUERY),
+                ),
             );
         } else {
             add("javascript", &[
@@ -539,10 +697,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             add(
                 "python",
                 &[".py", ".pyw", ".pyi", ".pyx", ".pxd", ".pxi"],
-                Some(TreeSitterLanguageInfo::new(
-                    tree_sitter_python::LANGUAGE,
-                    [],
-                )),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_python::LANGUAGE, [])
+                        .with_symbol_query(PYTHON_SYMBOL_QUERY),
+                ),
             );
         } else {
             add("python", &[".py", ".pyw", ".pyi", ".pyx", ".pxd", ".pxi"], None);
@@ -572,7 +730,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             add(
                 "ruby",
                 &[".rb"],
-                Some(TreeSitterLanguageInfo::new(tree_sitter_ruby::LANGUAGE, [])),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_ruby::LANGUAGE, [])
+                        .with_symbol_query(RUBY_SYMBOL_QUERY),
+                ),
             );
         } else {
             add("ruby", &[".rb"], None);
@@ -583,7 +744,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             add(
                 "rust",
                 &[".rs", "rs"],
-                Some(TreeSitterLanguageInfo::new(tree_sitter_rust::LANGUAGE, [])),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_rust::LANGUAGE, [])
+                        .with_symbol_query(RUST_SYMBOL_QUERY),
+                ),
             );
         } else {
             add("rust", &[".rs", "rs"], None);
@@ -670,10 +834,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             add(
                 "tsx",
                 &[".tsx"],
-                Some(TreeSitterLanguageInfo::new(
-                    tree_sitter_typescript::LANGUAGE_TSX,
-                    [],
-                )),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_typescript::LANGUAGE_TSX, [])
+                        .with_symbol_query(TYPESCRIPT_SYMBOL_QUERY),
+                ),
             );
         } else {
             add("tsx", &[".tsx"], None);
@@ -685,10 +849,10 @@ static LANGUAGE_INFO_BY_NAME: LazyLock<
             add(
                 "typescript",
                 &[".ts", "ts"],
-                Some(TreeSitterLanguageInfo::new(
-                    tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
-                    [],
-                )),
+                Some(
+                    TreeSitterLanguageInfo::new(tree_sitter_typescript::LANGUAGE_TYPESCRIPT, [])
+                        .with_symbol_query(TYPESCRIPT_SYMBOL_QUERY),
+                ),
             );
         } else {
             add("typescript", &[".ts", "ts"], None);
@@ -749,11 +913,10 @@ pub fn get_language_info(name: &str) -> Option<&ProgrammingLanguageInfo> {
 
 /// Detect programming language from a filename.
 ///
-/// Returns the language name if the file extension is recognized.
+/// Returns the language name if the filename (e.g. `Dockerfile`) or the file extension is
+/// recognized. See [`detect_language_with_content`] to also use the file content.
 pub fn detect_language(filename: &str) -> Option<&str> {
-    let last_dot = filename.rfind('.')?;
-    let extension = &filename[last_dot..];
-    get_language_info(extension).map(|info| info.name.as_ref())
+    lang_detect::detect_from_filename(filename).map(|(language, _)| language)
 }
 
 #[cfg(test)]
@@ -813,5 +976,9 @@ mod tests {
         assert_eq!(detect_language("app.js"), Some("javascript"));
         assert_eq!(detect_language("noextension"), None);
         assert_eq!(detect_language("unknown.xyz"), None);
+        assert_eq!(detect_language("docker/Dockerfile.dev"), Some("dockerfile"));
+        assert_eq!(detect_language("Makefile"), Some("make"));
+        assert_eq!(detect_language("go.mod"), Some("gomod"));
+        assert_eq!(detect_language("config.yaml.j2"), Some("yaml"));
     }
 }
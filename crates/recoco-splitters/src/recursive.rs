@@ -17,6 +17,8 @@ use std::collections::HashMap;
 use std::sync::{Arc, LazyLock};
 use unicase::UniCase;
 
+use crate::context::ContextResolver;
+use crate::grammar::{CustomGrammarConfig, LoadedGrammar};
 use crate::output_positions::{Position, set_output_positions};
 use crate::prog_langs::{self, TreeSitterLanguageInfo};
 use crate::split::{Chunk, TextRange};
@@ -26,7 +28,8 @@ const MISSING_OVERLAP_COST: usize = 512;
 const PER_LINE_BREAK_LEVEL_GAP_COST: usize = 64;
 const TOO_SMALL_CHUNK_COST: usize = 1048576;
 
-/// Configuration for a custom language with regex-based separators.
+/// Configuration for a custom language with regex-based separators and, optionally,
+/// a tree-sitter grammar loaded at runtime.
 #[derive(Debug, Clone)]
 pub struct CustomLanguageConfig {
     /// The name of the language.
@@ -34,7 +37,11 @@ pub struct CustomLanguageConfig {
     /// Aliases for the language name.
     pub aliases: Vec<String>,
     /// Regex patterns for separators, in order of priority.
+    ///
+    /// With a grammar, these are only used if the text fails to parse.
     pub separators_regex: Vec<String>,
+    /// A tree-sitter grammar enabling syntax-aware splitting for the language.
+    pub grammar: Option<CustomGrammarConfig>,
 }
 
 /// Configuration for recursive text splitting.
@@ -55,12 +62,15 @@ pub struct RecursiveChunkConfig {
     pub chunk_overlap: Option<usize>,
     /// Language name or file extension for syntax-aware splitting.
     pub language: Option<String>,
+    /// Whether to fill [`Chunk::context`] with the scopes enclosing each chunk.
+    pub include_context: bool,
 }
 
 struct SimpleLanguageConfig {
     name: String,
     aliases: Vec<String>,
     separator_regex: Vec<Regex>,
+    grammar: Option<LoadedGrammar>,
 }
 
 static DEFAULT_LANGUAGE_CONFIG: LazyLock<SimpleLanguageConfig> =
@@ -78,6 +88,7 @@ static DEFAULT_LANGUAGE_CONFIG: LazyLock<SimpleLanguageConfig> =
         .into_iter()
         .map(|s| Regex::new(s).unwrap())
         .collect(),
+        grammar: None,
     });
 
 enum ChunkKind<'t> {
@@ -599,7 +610,8 @@ pub struct RecursiveChunker {
 impl RecursiveChunker {
     /// Create a new recursive chunker with the given configuration.
     ///
-    /// Returns an error if any regex pattern is invalid or if there are duplicate language names.
+    /// Returns an error if any regex pattern is invalid, if a grammar fails to load, or if
+    /// there are duplicate language names.
     pub fn new(config: RecursiveSplitConfig) -> Result<Self, String> {
         let mut custom_languages = HashMap::new();
         for lang in config.custom_languages {
@@ -614,10 +626,16 @@ impl RecursiveChunker {
                         lang.language_name, e
                     )
                 })?;
+            let grammar = lang
+                .grammar
+                .as_ref()
+                .map(|grammar| LoadedGrammar::load(&lang.language_name, grammar))
+                .transpose()?;
             let language_config = Arc::new(SimpleLanguageConfig {
                 name: lang.language_name,
                 aliases: lang.aliases,
                 separator_regex,
+                grammar,
             });
             if custom_languages
                 .insert(
@@ -643,6 +661,25 @@ impl RecursiveChunker {
         Ok(Self { custom_languages })
     }
 
+    /// Parse the text with the tree-sitter grammar of the language, if it has one.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn parse(&self, text: &str, language: &str) -> Option<tree_sitter::Tree> {
+        let language = UniCase::new(language.to_string());
+        let mut parser = if let Some(lang_config) = self.custom_languages.get(&language) {
+            lang_config.grammar.as_ref()?.parser()?
+        } else {
+            let tree_sitter_info = prog_langs::get_language_info(&language)?
+                .treesitter_info
+                .as_ref()?;
+            let mut parser = tree_sitter::Parser::new();
+            parser
+                .set_language(&tree_sitter_info.tree_sitter_lang)
+                .ok()?;
+            parser
+        };
+        parser.parse(text, None)
+    }
+
     /// Split the text into chunks according to the configuration.
     pub fn split(&self, text: &str, config: RecursiveChunkConfig) -> Vec<Chunk> {
         let min_chunk_size = config.min_chunk_size.unwrap_or(config.chunk_size / 2);
@@ -661,11 +698,25 @@ impl RecursiveChunker {
         };
 
         let language = UniCase::new(config.language.unwrap_or_default());
+        let mut syntax_tree = None;
         let mut output = if let Some(lang_config) = self.custom_languages.get(&language) {
-            internal_chunker.split_root_chunk(ChunkKind::RegexpSepChunk {
-                lang_config,
-                next_regexp_sep_id: 0,
-            })
+            let parsed = lang_config.grammar.as_ref().and_then(|grammar| {
+                let tree = grammar.parser()?.parse(text, None)?;
+                Some((&grammar.tree_sitter_info, tree))
+            });
+            if let Some((tree_sitter_info, tree)) = parsed {
+                let output = internal_chunker.split_root_chunk(ChunkKind::TreeSitterNode {
+                    tree_sitter_info,
+                    node: tree.root_node(),
+                });
+                syntax_tree = Some((tree_sitter_info, tree));
+                output
+            } else {
+                internal_chunker.split_root_chunk(ChunkKind::RegexpSepChunk {
+                    lang_config,
+                    next_regexp_sep_id: 0,
+                })
+            }
         } else if let Some(lang_info) = prog_langs::get_language_info(&language)
             && let Some(tree_sitter_info) = lang_info.treesitter_info.as_ref()
         {
@@ -680,10 +731,12 @@ impl RecursiveChunker {
                     next_regexp_sep_id: 0,
                 })
             } else if let Some(tree) = parser.parse(text, None) {
-                internal_chunker.split_root_chunk(ChunkKind::TreeSitterNode {
+                let output = internal_chunker.split_root_chunk(ChunkKind::TreeSitterNode {
                     tree_sitter_info,
                     node: tree.root_node(),
-                })
+                });
+                syntax_tree = Some((tree_sitter_info, tree));
+                output
             } else {
                 // Fall back to default if parsing fails
                 internal_chunker.split_root_chunk(ChunkKind::RegexpSepChunk {
@@ -707,19 +760,36 @@ impl RecursiveChunker {
             }),
         );
 
+        let context_resolver = if !config.include_context {
+            None
+        } else if prog_langs::get_language_info(&language)
+            .is_some_and(|info| info.name.as_ref() == "markdown")
+        {
+            Some(ContextResolver::markdown(text))
+        } else {
+            syntax_tree.as_ref().map(|(tree_sitter_info, tree)| {
+                ContextResolver::syntax(tree_sitter_info, tree, text)
+            })
+        };
+
         // Convert to final output
         output
             .into_iter()
             .map(|chunk_output| {
                 let start = chunk_output.start_pos.output.unwrap();
                 let end = chunk_output.end_pos.output.unwrap();
+                let range = TextRange::new(
+                    chunk_output.start_pos.byte_offset,
+                    chunk_output.end_pos.byte_offset,
+                );
                 Chunk {
-                    range: TextRange::new(
-                        chunk_output.start_pos.byte_offset,
-                        chunk_output.end_pos.byte_offset,
-                    ),
+                    range,
                     start,
                     end,
+                    context: context_resolver
+                        .as_ref()
+                        .map(|resolver| resolver.context(range))
+                        .unwrap_or_default(),
                 }
             })
             .collect()
@@ -739,6 +809,7 @@ mod tests {
             min_chunk_size: Some(5),
             chunk_overlap: Some(0),
             language: None,
+            include_context: false,
         };
         let chunks = chunker.split(text, config);
 
@@ -766,6 +837,7 @@ mod tests {
             min_chunk_size: Some(12),
             chunk_overlap: Some(0),
             language: None,
+            include_context: false,
         };
         let chunks = chunker.split(text, config);
 
@@ -785,6 +857,7 @@ mod tests {
             min_chunk_size: Some(10),
             chunk_overlap: Some(5),
             language: None,
+            include_context: false,
         };
         let chunks = chunker.split(text, config);
 
@@ -808,6 +881,7 @@ mod tests {
             min_chunk_size: Some(10),
             chunk_overlap: Some(0),
             language: None,
+            include_context: false,
         };
         let chunks = chunker.split(text, config);
 
@@ -834,6 +908,7 @@ fn other() {
             min_chunk_size: Some(20),
             chunk_overlap: Some(0),
             language: Some("rust".to_string()),
+            include_context: false,
         };
         let chunks = chunker.split(text, config);
 
@@ -849,6 +924,7 @@ fn other() {
             min_chunk_size: Some(5),
             chunk_overlap: Some(0),
             language: None,
+            include_context: false,
         };
         let chunks = chunker.split(text, config);
 
@@ -866,6 +942,7 @@ fn other() {
                 language_name: "myformat".to_string(),
                 aliases: vec!["mf".to_string()],
                 separators_regex: vec![r"---".to_string()],
+                grammar: None,
             }],
         };
         let chunker = RecursiveChunker::new(config).unwrap();
@@ -875,6 +952,7 @@ fn other() {
             min_chunk_size: Some(4),
             chunk_overlap: Some(0),
             language: Some("myformat".to_string()),
+            include_context: false,
         };
         let chunks = chunker.split(text, chunk_config);
 
@@ -883,4 +961,157 @@ fn other() {
         assert_eq!(&text[chunks[1].range.start..chunks[1].range.end], "Part2");
         assert_eq!(&text[chunks[2].range.start..chunks[2].range.end], "Part3");
     }
+
+    #[test]
+    fn test_split_markdown_context() {
+        let chunker = RecursiveChunker::new(RecursiveSplitConfig::default()).unwrap();
+        let text = "# Guide\n\nIntro text here.\n\n## Install\n\nRun the installer.\n\n```sh\n# not a heading\n```\n\n### Linux\n\nUse the package.\n\n## Usage ##\n\nCall the tool.\n";
+        let config = RecursiveChunkConfig {
+            chunk_size: 30,
+            min_chunk_size: Some(5),
+            chunk_overlap: Some(0),
+            language: Some("markdown".to_string()),
+            include_context: true,
+        };
+        let chunks = chunker.split(text, config);
+
+        let context_of = |needle: &str| {
+            chunks
+                .iter()
+                .find(|c| text[c.range.start..c.range.end].contains(needle))
+                .map(|c| c.context.clone())
+                .unwrap()
+        };
+        assert_eq!(context_of("Intro"), vec!["Guide"]);
+        assert_eq!(context_of("installer"), vec!["Guide", "Install"]);
+        assert_eq!(context_of("not a heading"), vec!["Guide", "Install"]);
+        assert_eq!(context_of("package"), vec!["Guide", "Install", "Linux"]);
+        assert_eq!(context_of("Call"), vec!["Guide", "Usage"]);
+    }
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_split_rust_context() {
+        let chunker = RecursiveChunker::new(RecursiveSplitConfig::default()).unwrap();
+        let text = r#"mod shapes {
+    impl Circle {
+        fn area(&self) -> f64 {
+            let r = self.radius;
+            3.14 * r * r
+        }
+
+        fn perimeter(&self) -> f64 {
+            2.0 * 3.14 * self.radius
+        }
+    }
+}
+"#;
+        let config = RecursiveChunkConfig {
+            chunk_size: 60,
+            min_chunk_size: Some(20),
+            chunk_overlap: Some(0),
+            language: Some("rust".to_string()),
+            include_context: true,
+        };
+        let chunks = chunker.split(text, config.clone());
+        let area = chunks
+            .iter()
+            .find(|c| text[c.range.start..c.range.end].contains("let r"))
+            .unwrap();
+        assert_eq!(area.context, vec!["shapes", "Circle", "area"]);
+        let perimeter = chunks
+            .iter()
+            .find(|c| text[c.range.start..c.range.end].contains("fn perimeter"))
+            .unwrap();
+        assert_eq!(perimeter.context, vec!["shapes", "Circle", "perimeter"]);
+
+        let chunks = chunker.split(
+            text,
+            RecursiveChunkConfig {
+                include_context: false,
+                ..config
+            },
+        );
+        assert!(chunks.iter().all(|c| c.context.is_empty()));
+    }
+
+    #[test]
+    fn test_custom_language_grammar_load_error() {
+        for path in [
+            "/nonexistent/libtree-sitter-foo.so",
+            "/nonexistent/foo.wasm",
+        ] {
+            let config = RecursiveSplitConfig {
+                custom_languages: vec![CustomLanguageConfig {
+                    language_name: "foo".to_string(),
+                    aliases: vec![],
+                    separators_regex: vec![],
+                    grammar: Some(CustomGrammarConfig {
+                        path: path.into(),
+                        grammar_name: None,
+                        terminal_node_kinds: vec![],
+                    }),
+                }],
+            };
+            let err = RecursiveChunker::new(config).err().unwrap();
+            assert!(err.contains(path), "unexpected error: {err}");
+        }
+    }
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_custom_language_with_grammar() {
+        let tree_sitter_lang: tree_sitter::Language = tree_sitter_rust::LANGUAGE.into();
+        assert!(
+            LoadedGrammar::from_language(tree_sitter_lang.clone(), &["no_such_kind".into()])
+                .is_err()
+        );
+        let grammar =
+            LoadedGrammar::from_language(tree_sitter_lang, &["string_literal".into()]).unwrap();
+        let chunker = RecursiveChunker {
+            custom_languages: HashMap::from([(
+                UniCase::new("myrust".to_string()),
+                Arc::new(SimpleLanguageConfig {
+                    name: "myrust".to_string(),
+                    aliases: vec![],
+                    separator_regex: vec![],
+                    grammar: Some(grammar),
+                }),
+            )]),
+        };
+        let text = r#"fn alpha() {
+    let message = "a string literal that should never be split apart";
+}
+
+fn beta() {
+    println!("short");
+}
+"#;
+        let chunks = chunker.split(
+            text,
+            RecursiveChunkConfig {
+                chunk_size: 60,
+                min_chunk_size: Some(10),
+                chunk_overlap: Some(0),
+                language: Some("MyRust".to_string()),
+                include_context: true,
+            },
+        );
+        let chunk_texts: Vec<&str> = chunks
+            .iter()
+            .map(|c| &text[c.range.start..c.range.end])
+            .collect();
+        assert!(
+            chunk_texts
+                .iter()
+                .any(|t| t.contains("\"a string literal that should never be split apart\"")),
+            "terminal node was split: {chunk_texts:?}"
+        );
+        let beta = chunks
+            .iter()
+            .find(|c| text[c.range.start..c.range.end].contains("fn beta"))
+            .unwrap();
+        assert!(text[beta.range.start..beta.range.end].starts_with("fn beta"));
+        assert_eq!(beta.context, vec!["beta"]);
+    }
 }
@@ -0,0 +1,416 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Chunking very large texts in segments, streamed from a reader or split in parallel.
+//!
+//! Segments end at top-level boundaries (the start of an unindented paragraph, or of a
+//! top-level syntax node in parallel mode) and are chunked independently, so no chunk spans
+//! two segments. When segment boundaries fall on chunk boundaries of [`RecursiveChunker::split`],
+//! which is typical when segments are much larger than chunks, the output is identical to it.
+
+use regex::Regex;
+use std::io::Read;
+use std::sync::LazyLock;
+
+use crate::recursive::{RecursiveChunkConfig, RecursiveChunker};
+use crate::split::{Chunk, OutputPosition, TextRange};
+
+/// Default target size of segments in bytes.
+pub const DEFAULT_SEGMENT_SIZE: usize = 1 << 20;
+
+/// Size of reads from the underlying reader of a [`ChunkStream`].
+const READ_SIZE: usize = 64 * 1024;
+
+/// One or more blank lines followed by an unindented line.
+static PARAGRAPH_START: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\n(?:[ \t]*\r?\n)+[^\s]").unwrap());
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// End of the first segment of `text`. It's the first paragraph start within
+/// `[segment_size, 2 * segment_size)`, or failing that the first line start, or the end of
+/// that window. Only the first `2 * segment_size` bytes of `text` are looked at.
+fn segment_end(text: &str, segment_size: usize) -> usize {
+    let segment_size = segment_size.max(1);
+    if text.len() <= segment_size {
+        return text.len();
+    }
+    let window_start = floor_char_boundary(text, segment_size);
+    let window_end = floor_char_boundary(text, 2 * segment_size);
+    let window = &text[window_start..window_end];
+    if let Some(m) = PARAGRAPH_START.find(window) {
+        // Right after the last line break of the match.
+        return window_start + m.start() + m.as_str().rfind('\n').unwrap() + 1;
+    }
+    match window.find('\n') {
+        Some(pos) => window_start + pos + 1,
+        // Make progress even within a line of multi-byte characters.
+        None => window_end.max(
+            window_start
+                + text[window_start..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8),
+        ),
+    }
+}
+
+/// Advance the position past the text.
+fn advance_position(position: &mut OutputPosition, text: &str) {
+    for ch in text.chars() {
+        position.char_offset += 1;
+        if ch == '\n' {
+            position.line += 1;
+            position.column = 1;
+        } else {
+            position.column += 1;
+        }
+    }
+}
+
+/// Turn a position within a segment into one within the whole text.
+fn offset_position(position: &mut OutputPosition, segment_start: &OutputPosition) {
+    if position.line == 1 {
+        position.column += segment_start.column - 1;
+    }
+    position.line += segment_start.line - 1;
+    position.char_offset += segment_start.char_offset;
+}
+
+fn text_start() -> OutputPosition {
+    OutputPosition {
+        char_offset: 0,
+        line: 1,
+        column: 1,
+    }
+}
+
+impl RecursiveChunker {
+    /// Chunk a segment starting at `start_byte` and `start` within the whole text.
+    fn split_segment(
+        &self,
+        segment: &str,
+        config: &RecursiveChunkConfig,
+        start_byte: usize,
+        start: &OutputPosition,
+    ) -> Vec<Chunk> {
+        let mut chunks = self.split(segment, config.clone());
+        for chunk in chunks.iter_mut() {
+            chunk.range =
+                TextRange::new(chunk.range.start + start_byte, chunk.range.end + start_byte);
+            offset_position(&mut chunk.start, start);
+            offset_position(&mut chunk.end, start);
+        }
+        chunks
+    }
+
+    /// Split text read incrementally from `reader`, emitting chunks as segments of about
+    /// `segment_size` bytes (see [`DEFAULT_SEGMENT_SIZE`]) are read, with ranges and positions
+    /// within the whole text.
+    ///
+    /// Context of chunks ([`RecursiveChunkConfig::include_context`]) only covers scopes within
+    /// their segment.
+    pub fn split_stream<R: Read>(
+        &self,
+        reader: R,
+        config: RecursiveChunkConfig,
+        segment_size: usize,
+    ) -> ChunkStream<'_, R> {
+        ChunkStream {
+            chunker: self,
+            reader,
+            config,
+            segment_size: segment_size.max(1),
+            buffer: String::new(),
+            undecoded: Vec::new(),
+            eof: false,
+            start_byte: 0,
+            start: text_start(),
+            chunks: Vec::new().into_iter(),
+        }
+    }
+
+    /// Split the text into segments of about `segment_size` bytes (see
+    /// [`DEFAULT_SEGMENT_SIZE`]) at top-level syntax nodes, or at paragraphs for languages
+    /// without a grammar, and chunk them in parallel on the rayon thread pool.
+    ///
+    /// Context of chunks ([`RecursiveChunkConfig::include_context`]) only covers scopes within
+    /// their segment.
+    #[cfg(feature = "parallel")]
+    pub fn split_parallel(
+        &self,
+        text: &str,
+        config: RecursiveChunkConfig,
+        segment_size: usize,
+    ) -> Vec<Chunk> {
+        use rayon::prelude::*;
+
+        let mut segments = Vec::new();
+        let mut position = text_start();
+        let mut prev_end = 0;
+        for end in self.segment_ends(text, config.language.as_deref(), segment_size) {
+            segments.push((prev_end..end, position));
+            advance_position(&mut position, &text[prev_end..end]);
+            prev_end = end;
+        }
+        segments
+            .into_par_iter()
+            .map(|(range, start)| {
+                self.split_segment(&text[range.clone()], &config, range.start, &start)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Ends of segments of the text, the last being the end of the text.
+    #[cfg(feature = "parallel")]
+    fn segment_ends(&self, text: &str, language: Option<&str>, segment_size: usize) -> Vec<usize> {
+        let mut ends = Vec::new();
+        let tree = language.and_then(|language| self.parse(text, language));
+        if let Some(tree) = &tree {
+            let mut segment_start = 0;
+            let mut cursor = tree.root_node().walk();
+            for node in tree.root_node().children(&mut cursor) {
+                let mut start = node.start_byte();
+                // Keep the indentation of the node with it.
+                let line_start = text[..start].rfind('\n').map_or(0, |pos| pos + 1);
+                if text[line_start..start].trim().is_empty() {
+                    start = line_start;
+                }
+                if start > segment_start && start - segment_start >= segment_size {
+                    ends.push(start);
+                    segment_start = start;
+                }
+            }
+        } else {
+            let mut segment_start = 0;
+            while text.len() - segment_start > segment_size {
+                segment_start += segment_end(&text[segment_start..], segment_size);
+                ends.push(segment_start);
+            }
+        }
+        if ends.last() != Some(&text.len()) {
+            ends.push(text.len());
+        }
+        ends
+    }
+}
+
+/// Chunks of text read incrementally, returned by [`RecursiveChunker::split_stream`].
+///
+/// The text must be valid UTF-8. Otherwise an error of kind
+/// [`std::io::ErrorKind::InvalidData`] is returned, ending the stream.
+pub struct ChunkStream<'c, R> {
+    chunker: &'c RecursiveChunker,
+    reader: R,
+    config: RecursiveChunkConfig,
+    segment_size: usize,
+    /// Text read but not chunked yet.
+    buffer: String,
+    /// Bytes read after the buffer, ending with an incomplete UTF-8 sequence.
+    undecoded: Vec<u8>,
+    eof: bool,
+    /// Byte offset and position of the buffer within the whole text.
+    start_byte: usize,
+    start: OutputPosition,
+    /// Chunks of the last segment not emitted yet.
+    chunks: std::vec::IntoIter<Chunk>,
+}
+
+impl<R: Read> ChunkStream<'_, R> {
+    /// Read more text into the buffer.
+    fn fill(&mut self) -> std::io::Result<()> {
+        let len = self.undecoded.len();
+        self.undecoded.resize(len + READ_SIZE, 0);
+        let read = match self.reader.read(&mut self.undecoded[len..]) {
+            Ok(read) => read,
+            Err(e) => {
+                self.undecoded.truncate(len);
+                return Err(e);
+            }
+        };
+        self.undecoded.truncate(len + read);
+        if read == 0 {
+            self.eof = true;
+            if !self.undecoded.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "stream did not contain valid UTF-8",
+                ));
+            }
+            return Ok(());
+        }
+        let valid_len = match std::str::from_utf8(&self.undecoded) {
+            Ok(text) => text.len(),
+            // An incomplete sequence at the end is completed by the next read.
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+            }
+        };
+        // Valid as checked above.
+        self.buffer
+            .push_str(std::str::from_utf8(&self.undecoded[..valid_len]).unwrap());
+        self.undecoded.drain(..valid_len);
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for ChunkStream<'_, R> {
+    type Item = std::io::Result<Chunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(chunk) = self.chunks.next() {
+                return Some(Ok(chunk));
+            }
+            // Segments are cut once `2 * segment_size` bytes are buffered, the most
+            // `segment_end` looks at, so they don't depend on how the text is read.
+            while !self.eof && self.buffer.len() < 2 * self.segment_size {
+                match self.fill() {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(e) => {
+                        self.eof = true;
+                        self.buffer.clear();
+                        return Some(Err(e));
+                    }
+                }
+            }
+            if self.buffer.is_empty() {
+                return None;
+            }
+            let end = segment_end(&self.buffer, self.segment_size);
+            let rest = self.buffer.split_off(end);
+            let segment = std::mem::replace(&mut self.buffer, rest);
+            self.chunks = self
+                .chunker
+                .split_segment(&segment, &self.config, self.start_byte, &self.start)
+                .into_iter();
+            self.start_byte += segment.len();
+            advance_position(&mut self.start, &segment);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recursive::RecursiveSplitConfig;
+
+    fn config(language: Option<&str>) -> RecursiveChunkConfig {
+        RecursiveChunkConfig {
+            chunk_size: 100,
+            min_chunk_size: None,
+            chunk_overlap: None,
+            language: language.map(str::to_string),
+            include_context: false,
+        }
+    }
+
+    /// Paragraphs too long to be merged with each other, so each is a chunk.
+    fn paragraphs() -> String {
+        (0..40)
+            .map(|i| format!("Paragraph {i} — ça {}", "word ".repeat(12 + i % 4)))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Reads at most `size` bytes at a time, splitting multi-byte characters.
+    struct SmallReads<'a>(&'a [u8], usize);
+
+    impl Read for SmallReads<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let size = self.0.len().min(self.1).min(buf.len());
+            buf[..size].copy_from_slice(&self.0[..size]);
+            self.0 = &self.0[size..];
+            Ok(size)
+        }
+    }
+
+    #[test]
+    fn test_segment_end() {
+        let text = "aaaa\n\n  bbb\ncc\n\ndd\n";
+        // The first unindented paragraph within the window.
+        assert_eq!(segment_end(text, 10), 16);
+        // A line start, without paragraphs in the window.
+        assert_eq!(segment_end(text, 6), 12);
+        assert_eq!(segment_end(text, 4), 5);
+        assert_eq!(segment_end(text, 100), text.len());
+        // No line break in the window.
+        assert_eq!(segment_end("ééééé", 3), 6);
+    }
+
+    #[test]
+    fn test_split_stream_matches_split() {
+        let chunker = RecursiveChunker::new(RecursiveSplitConfig::default()).unwrap();
+        let text = paragraphs();
+        let expected = chunker.split(&text, config(None));
+        for segment_size in [150, 500, DEFAULT_SEGMENT_SIZE] {
+            let chunks = chunker
+                .split_stream(SmallReads(text.as_bytes(), 7), config(None), segment_size)
+                .collect::<std::io::Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(chunks, expected, "segment size {segment_size}");
+        }
+
+        let invalid = chunker
+            .split_stream(&b"abc\xff"[..], config(None), 100)
+            .collect::<std::io::Result<Vec<_>>>();
+        assert_eq!(invalid.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_split_parallel_matches_split() {
+        let chunker = RecursiveChunker::new(RecursiveSplitConfig::default()).unwrap();
+        let text = paragraphs();
+        let expected = chunker.split(&text, config(None));
+        assert_eq!(chunker.split_parallel(&text, config(None), 300), expected);
+    }
+
+    #[cfg(all(feature = "parallel", feature = "rust"))]
+    #[test]
+    fn test_split_parallel_at_syntax_nodes() {
+        let chunker = RecursiveChunker::new(RecursiveSplitConfig::default()).unwrap();
+        let text = (0..30)
+            .map(|i| format!("fn f{i}() {{\n    let value = {i} * 2 + {i};\n    println!(\"{{value}}\");\n}}\n"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = config(Some("rust"));
+        let ends = chunker.segment_ends(&text, Some("rust"), 500);
+        assert!(ends.len() > 1);
+        assert!(
+            ends[..ends.len() - 1]
+                .iter()
+                .all(|end| text[*end..].starts_with("fn "))
+        );
+
+        let chunks = chunker.split_parallel(&text, config.clone(), 500);
+        let expected = chunker.split(&text, config);
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.range).collect::<Vec<_>>(),
+            expected.iter().map(|chunk| chunk.range).collect::<Vec<_>>()
+        );
+        assert_eq!(chunks, expected);
+    }
+}
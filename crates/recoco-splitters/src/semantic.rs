@@ -0,0 +1,434 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Semantic chunking: sentences are grouped into chunks, with breakpoints placed where
+//! the embeddings of adjacent sentence windows are least similar.
+//!
+//! Embedding is left to the caller, so splitting happens in two steps:
+//! [`SemanticSplitter::prepare`] splits the text into sentences and returns the windows
+//! to embed, then [`SemanticSplit::into_chunks`] places breakpoints from the embeddings.
+
+use crate::by_separators::{KeepSeparator, SeparatorSplitConfig, SeparatorSplitter};
+use crate::output_positions::{Position, set_output_positions};
+use crate::split::{Chunk, TextRange};
+
+/// Default sentence boundaries: sentence-ending punctuation followed by whitespace,
+/// CJK full stops, and blank lines.
+const DEFAULT_SENTENCE_SEPARATORS: [&str; 2] = [r"[.!?]\s+|。|？|！", r"\n\s*\n"];
+
+/// Configuration for a [`SemanticSplitter`].
+#[derive(Debug, Clone)]
+pub struct SemanticSplitConfig {
+    /// Regex patterns for sentence boundaries. Empty means the default boundaries.
+    pub sentence_separators_regex: Vec<String>,
+    /// Number of neighboring sentences on each side embedded together with a sentence,
+    /// which smooths out noise from very short sentences.
+    pub buffer_size: usize,
+    /// Breakpoints go where the similarity of adjacent windows is below this percentile
+    /// (0-100) of all adjacent similarities in the text.
+    pub breakpoint_percentile: f64,
+}
+
+impl Default for SemanticSplitConfig {
+    fn default() -> Self {
+        Self {
+            sentence_separators_regex: vec![],
+            buffer_size: 1,
+            breakpoint_percentile: 5.0,
+        }
+    }
+}
+
+/// Size limits for a single semantic chunking operation.
+#[derive(Debug, Clone, Copy)]
+pub struct SemanticChunkConfig {
+    /// Chunks aren't broken at a breakpoint until they reach this size in bytes.
+    pub min_chunk_size: usize,
+    /// Maximum chunk size in bytes. Sentences longer than this are split at whitespace.
+    pub max_chunk_size: usize,
+}
+
+/// A splitter placing chunk boundaries at topic shifts.
+pub struct SemanticSplitter {
+    sentence_splitter: SeparatorSplitter,
+    buffer_size: usize,
+    breakpoint_percentile: f64,
+}
+
+impl SemanticSplitter {
+    /// Create a new semantic splitter with the given configuration.
+    ///
+    /// Returns an error if a regex pattern is invalid or the percentile is out of range.
+    pub fn new(config: SemanticSplitConfig) -> Result<Self, String> {
+        if !(0.0..=100.0).contains(&config.breakpoint_percentile) {
+            return Err(format!(
+                "breakpoint_percentile must be between 0 and 100, got {}",
+                config.breakpoint_percentile
+            ));
+        }
+        let separators_regex = if config.sentence_separators_regex.is_empty() {
+            DEFAULT_SENTENCE_SEPARATORS
+                .iter()
+                .map(|s| s.to_string())
+                .collect()
+        } else {
+            config.sentence_separators_regex
+        };
+        let sentence_splitter = SeparatorSplitter::new(SeparatorSplitConfig {
+            separators_regex,
+            keep_separator: Some(KeepSeparator::Left),
+            include_empty: false,
+            trim: true,
+        })
+        .map_err(|e| format!("failed in parsing sentence separators regexp: {e}"))?;
+        Ok(Self {
+            sentence_splitter,
+            buffer_size: config.buffer_size,
+            breakpoint_percentile: config.breakpoint_percentile,
+        })
+    }
+
+    /// Split the text into sentences, ready to be embedded.
+    pub fn prepare<'s, 't>(
+        &'s self,
+        text: &'t str,
+        config: SemanticChunkConfig,
+    ) -> SemanticSplit<'s, 't> {
+        let max_chunk_size = config.max_chunk_size.max(1);
+        let sentences = self
+            .sentence_splitter
+            .split(text)
+            .into_iter()
+            .flat_map(|chunk| split_long_range(text, chunk.range, max_chunk_size))
+            .collect();
+        SemanticSplit {
+            splitter: self,
+            text,
+            sentences,
+            min_chunk_size: config.min_chunk_size.min(max_chunk_size),
+            max_chunk_size,
+        }
+    }
+}
+
+/// A text split into sentences, waiting for the embeddings of its windows.
+pub struct SemanticSplit<'s, 't> {
+    splitter: &'s SemanticSplitter,
+    text: &'t str,
+    sentences: Vec<TextRange>,
+    min_chunk_size: usize,
+    max_chunk_size: usize,
+}
+
+impl<'t> SemanticSplit<'_, 't> {
+    /// The text to embed for each sentence: the sentence with `buffer_size` neighbors
+    /// on each side.
+    ///
+    /// Empty if there's at most one sentence, as there's nothing to compare.
+    pub fn windows(&self) -> Vec<&'t str> {
+        if self.sentences.len() <= 1 {
+            return vec![];
+        }
+        let last = self.sentences.len() - 1;
+        let buffer_size = self.splitter.buffer_size;
+        (0..self.sentences.len())
+            .map(|i| {
+                let start = self.sentences[i.saturating_sub(buffer_size)].start;
+                let end = self.sentences[(i + buffer_size).min(last)].end;
+                &self.text[start..end]
+            })
+            .collect()
+    }
+
+    /// Group the sentences into chunks, given one embedding per window from
+    /// [`Self::windows`].
+    ///
+    /// Returns an error if the number of embeddings doesn't match.
+    pub fn into_chunks(self, embeddings: &[impl AsRef<[f32]>]) -> Result<Vec<Chunk>, String> {
+        let expected = if self.sentences.len() <= 1 {
+            0
+        } else {
+            self.sentences.len()
+        };
+        if embeddings.len() != expected {
+            return Err(format!(
+                "expected {expected} embeddings, got {}",
+                embeddings.len()
+            ));
+        }
+
+        let similarities: Vec<f64> = embeddings
+            .windows(2)
+            .map(|pair| cosine_similarity(pair[0].as_ref(), pair[1].as_ref()))
+            .collect();
+        let threshold = percentile(&similarities, self.splitter.breakpoint_percentile);
+
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for (i, similarity) in similarities.iter().enumerate() {
+            let chunk_start = self.sentences[start].start;
+            let len = self.sentences[i].end - chunk_start;
+            let next_len = self.sentences[i + 1].end - chunk_start;
+            if next_len > self.max_chunk_size
+                || (*similarity < threshold && len >= self.min_chunk_size)
+            {
+                ranges.push(TextRange::new(chunk_start, self.sentences[i].end));
+                start = i + 1;
+            }
+        }
+        if let Some(last) = self.sentences.last() {
+            let tail = TextRange::new(self.sentences[start].start, last.end);
+            // Fold a too-small tail into the previous chunk if it fits.
+            match ranges.last_mut() {
+                Some(prev)
+                    if tail.len() < self.min_chunk_size
+                        && tail.end - prev.start <= self.max_chunk_size =>
+                {
+                    prev.end = tail.end;
+                }
+                _ => ranges.push(tail),
+            }
+        }
+
+        let mut positions: Vec<Position> = ranges
+            .iter()
+            .flat_map(|range| [Position::new(range.start), Position::new(range.end)])
+            .collect();
+        set_output_positions(self.text, positions.iter_mut());
+        Ok(ranges
+            .into_iter()
+            .zip(positions.chunks_exact(2))
+            .map(|(range, positions)| Chunk {
+                range,
+                start: positions[0].output.unwrap(),
+                end: positions[1].output.unwrap(),
+                context: Vec::new(),
+            })
+            .collect())
+    }
+}
+
+/// Split a range longer than `max_size` bytes into pieces, preferably at whitespace.
+fn split_long_range(text: &str, range: TextRange, max_size: usize) -> Vec<TextRange> {
+    let mut pieces = Vec::new();
+    let mut start = range.start;
+    while range.end - start > max_size {
+        let mut end = start + max_size;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        if let Some(ws) = text[start..end].rfind(char::is_whitespace)
+            && ws > 0
+        {
+            end = start + ws;
+        }
+        if end == start {
+            // A single character wider than `max_size`.
+            end = start + text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        pieces.push(TextRange::new(start, end));
+        start = end;
+        while start < range.end && text.as_bytes()[start].is_ascii_whitespace() {
+            start += 1;
+        }
+    }
+    if start < range.end {
+        pieces.push(TextRange::new(start, range.end));
+    }
+    pieces
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (&x, &y) in a.iter().zip(b) {
+        let (x, y) = (x as f64, y as f64);
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a.sqrt() * norm_b.sqrt())
+    }
+}
+
+/// The `p`-th percentile of `values`, interpolating linearly between closest ranks.
+fn percentile(values: &[f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return f64::NEG_INFINITY;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk_texts<'t>(text: &'t str, chunks: &[Chunk]) -> Vec<&'t str> {
+        chunks
+            .iter()
+            .map(|c| &text[c.range.start..c.range.end])
+            .collect()
+    }
+
+    /// Embeds a window by which topics it mentions.
+    fn topic_embedding(window: &str) -> Vec<f32> {
+        ["cat", "rocket"]
+            .iter()
+            .map(|topic| window.matches(topic).count() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values = [4.0, 1.0, 3.0, 2.0, 5.0];
+        assert_eq!(percentile(&values, 0.0), 1.0);
+        assert_eq!(percentile(&values, 50.0), 3.0);
+        assert_eq!(percentile(&values, 100.0), 5.0);
+        assert_eq!(percentile(&values, 10.0), 1.4);
+    }
+
+    #[test]
+    fn test_split_long_range() {
+        let text = "alpha beta gamma delta";
+        let pieces = split_long_range(text, TextRange::new(0, text.len()), 11);
+        let texts: Vec<_> = pieces.iter().map(|r| &text[r.start..r.end]).collect();
+        assert_eq!(texts, vec!["alpha beta", "gamma delta"]);
+    }
+
+    #[test]
+    fn test_semantic_split_at_topic_shift() {
+        let splitter = SemanticSplitter::new(SemanticSplitConfig {
+            buffer_size: 0,
+            breakpoint_percentile: 50.0,
+            ..Default::default()
+        })
+        .unwrap();
+        let text = "The cat sleeps. A cat purrs. My cat eats. The rocket launches. \
+                    A rocket lands. Our rocket flies.";
+        let split = splitter.prepare(
+            text,
+            SemanticChunkConfig {
+                min_chunk_size: 0,
+                max_chunk_size: 1000,
+            },
+        );
+        let windows = split.windows();
+        assert_eq!(windows.len(), 6);
+        assert_eq!(windows[0], "The cat sleeps.");
+        let embeddings: Vec<_> = windows.iter().map(|w| topic_embedding(w)).collect();
+        let chunks = split.into_chunks(&embeddings).unwrap();
+        assert_eq!(
+            chunk_texts(text, &chunks),
+            vec![
+                "The cat sleeps. A cat purrs. My cat eats.",
+                "The rocket launches. A rocket lands. Our rocket flies."
+            ]
+        );
+        assert_eq!(chunks[1].start.char_offset, 42);
+        assert_eq!(chunks[1].start.line, 1);
+    }
+
+    #[test]
+    fn test_semantic_split_enforces_sizes() {
+        let splitter = SemanticSplitter::new(SemanticSplitConfig {
+            breakpoint_percentile: 100.0,
+            ..Default::default()
+        })
+        .unwrap();
+        let text = "One cat. Two cat. Three cat. Four cat.";
+
+        // Every gap is a breakpoint candidate, but chunks must reach the minimum size.
+        let split = splitter.prepare(
+            text,
+            SemanticChunkConfig {
+                min_chunk_size: 15,
+                max_chunk_size: 1000,
+            },
+        );
+        let embeddings: Vec<_> = split
+            .windows()
+            .iter()
+            .enumerate()
+            .map(|(i, _)| vec![1.0, i as f32])
+            .collect();
+        let chunks = split.into_chunks(&embeddings).unwrap();
+        assert_eq!(
+            chunk_texts(text, &chunks),
+            vec!["One cat. Two cat.", "Three cat. Four cat."]
+        );
+
+        // No breakpoints at all, so only the maximum size splits.
+        let splitter = SemanticSplitter::new(SemanticSplitConfig {
+            breakpoint_percentile: 0.0,
+            ..Default::default()
+        })
+        .unwrap();
+        let split = splitter.prepare(
+            text,
+            SemanticChunkConfig {
+                min_chunk_size: 0,
+                max_chunk_size: 19,
+            },
+        );
+        let embeddings = vec![[1.0f32]; split.windows().len()];
+        let chunks = split.into_chunks(&embeddings).unwrap();
+        assert_eq!(
+            chunk_texts(text, &chunks),
+            vec!["One cat. Two cat.", "Three cat.", "Four cat."]
+        );
+        assert!(chunks.iter().all(|c| c.range.len() <= 19));
+    }
+
+    #[test]
+    fn test_semantic_split_single_sentence() {
+        let splitter = SemanticSplitter::new(SemanticSplitConfig::default()).unwrap();
+        let text = "  Just one sentence  ";
+        let split = splitter.prepare(
+            text,
+            SemanticChunkConfig {
+                min_chunk_size: 0,
+                max_chunk_size: 100,
+            },
+        );
+        assert!(split.windows().is_empty());
+        let chunks = split.into_chunks(&Vec::<Vec<f32>>::new()).unwrap();
+        assert_eq!(chunk_texts(text, &chunks), vec!["Just one sentence"]);
+
+        let split = splitter.prepare(
+            "",
+            SemanticChunkConfig {
+                min_chunk_size: 0,
+                max_chunk_size: 100,
+            },
+        );
+        assert!(
+            split
+                .into_chunks(&Vec::<Vec<f32>>::new())
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            SemanticSplitter::new(SemanticSplitConfig {
+                breakpoint_percentile: 150.0,
+                ..Default::default()
+            })
+            .is_err()
+        );
+    }
+}
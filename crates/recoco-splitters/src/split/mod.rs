@@ -15,13 +15,22 @@
 //! This module provides text splitting functionality including:
 //! - Splitting by regex separators
 //! - Recursive syntax-aware chunking
+//! - Semantic chunking at topic shifts
 
 #[cfg(feature = "splitter-separator")]
 pub use crate::by_separators::{KeepSeparator, SeparatorSplitConfig, SeparatorSplitter};
 #[cfg(feature = "splitter-recursive")]
+pub use crate::grammar::CustomGrammarConfig;
+#[cfg(feature = "splitter-recursive")]
 pub use crate::recursive::{
     CustomLanguageConfig, RecursiveChunkConfig, RecursiveChunker, RecursiveSplitConfig,
 };
+#[cfg(feature = "splitter-recursive")]
+pub use crate::segmented::{ChunkStream, DEFAULT_SEGMENT_SIZE};
+#[cfg(feature = "splitter-semantic")]
+pub use crate::semantic::{
+    SemanticChunkConfig, SemanticSplit, SemanticSplitConfig, SemanticSplitter,
+};
 
 /// A text range specified by byte offsets.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -61,7 +70,7 @@ pub struct OutputPosition {
 }
 
 /// A chunk of text with its range and position information.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Chunk {
     /// Byte range in the original text. Use this to slice the original string.
     pub range: TextRange,
@@ -69,6 +78,11 @@ pub struct Chunk {
     pub start: OutputPosition,
     /// End position (character offset, line, column).
     pub end: OutputPosition,
+    /// Names of the scopes enclosing the chunk, outermost first: the enclosing named
+    /// syntax nodes for tree-sitter languages, or the heading hierarchy for Markdown.
+    ///
+    /// Only filled when requested by [`RecursiveChunkConfig::include_context`].
+    pub context: Vec<String>,
 }
 
 #[cfg(test)]
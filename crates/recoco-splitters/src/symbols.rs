@@ -0,0 +1,611 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Code symbol extraction based on tree-sitter.
+//!
+//! Definitions are located with the per-language symbol queries registered alongside
+//! [`TreeSitterLanguageInfo`](crate::prog_langs::TreeSitterLanguageInfo).
+
+use std::collections::HashMap;
+
+use crate::output_positions::{Position, set_output_positions};
+use crate::prog_langs;
+use crate::split::{OutputPosition, TextRange};
+use tree_sitter::StreamingIterator;
+
+/// Node kinds attaching metadata to the definition that follows them.
+const ATTRIBUTE_NODE_KINDS: &[&str] = &[
+    "annotation",
+    "attribute_item",
+    "decorator",
+    "marker_annotation",
+];
+
+/// Kind of a code symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Class,
+    Struct,
+    Enum,
+    Trait,
+    Interface,
+}
+
+impl SymbolKind {
+    fn from_capture(name: &str) -> Option<Self> {
+        let kind = match name {
+            "function" => Self::Function,
+            "method" => Self::Method,
+            "class" => Self::Class,
+            "struct" => Self::Struct,
+            "enum" => Self::Enum,
+            "trait" => Self::Trait,
+            "interface" => Self::Interface,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Lowercase name of the kind, e.g. `"function"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Function => "function",
+            Self::Method => "method",
+            Self::Class => "class",
+            Self::Struct => "struct",
+            Self::Enum => "enum",
+            Self::Trait => "trait",
+            Self::Interface => "interface",
+        }
+    }
+}
+
+impl std::fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A symbol definition found in source code.
+#[derive(Debug, Clone)]
+pub struct CodeSymbol {
+    /// Name of the symbol.
+    pub name: String,
+    /// Kind of the symbol.
+    pub kind: SymbolKind,
+    /// Names of the enclosing symbols, outermost first (e.g. `["outer", "Foo"]` for a
+    /// method of `impl Foo` within `mod outer`).
+    pub parent_path: Vec<String>,
+    /// Declaration text up to the body, with whitespace collapsed.
+    pub signature: String,
+    /// Doc comment (or docstring) attached to the definition, with comment markers stripped.
+    pub doc: Option<String>,
+    /// Byte range of the whole definition in the original text.
+    pub range: TextRange,
+    /// Start position (character offset, line, column).
+    pub start: OutputPosition,
+    /// End position (character offset, line, column).
+    pub end: OutputPosition,
+}
+
+enum EntryRole {
+    Definition(SymbolKind),
+    TypeScope,
+    ModuleScope,
+}
+
+struct Entry<'tree> {
+    node: tree_sitter::Node<'tree>,
+    role: EntryRole,
+    name: String,
+    parent: Option<String>,
+}
+
+struct SymbolOutput {
+    name: String,
+    kind: SymbolKind,
+    parent_path: Vec<String>,
+    signature: String,
+    doc: Option<String>,
+    start_pos: Position,
+    end_pos: Position,
+}
+
+/// Extract symbol definitions from source code.
+///
+/// `language` is a language name or file extension, as accepted by
+/// [`prog_langs::get_language_info`]. Returns `None` if the language has no tree-sitter
+/// grammar or symbol query available; symbols are ordered by their start offset.
+pub fn extract_symbols(text: &str, language: &str) -> Option<Vec<CodeSymbol>> {
+    let ts_info = prog_langs::get_language_info(language)?
+        .treesitter_info
+        .as_ref()?;
+    let query = ts_info.symbol_query.as_ref()?;
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&ts_info.tree_sitter_lang).ok()?;
+    let tree = parser.parse(text, None)?;
+    let source = text.as_bytes();
+
+    let (entries, entry_by_node_id) = collect_entries(query, tree.root_node(), source);
+
+    let mut symbols = Vec::new();
+    for entry in entries.iter() {
+        let EntryRole::Definition(mut kind) = entry.role else {
+            continue;
+        };
+
+        let mut enclosing = Vec::new();
+        let mut ancestor = entry.node.parent();
+        while let Some(node) = ancestor {
+            if let Some(&idx) = entry_by_node_id.get(&node.id()) {
+                enclosing.push(&entries[idx]);
+            }
+            ancestor = node.parent();
+        }
+        let owned_by_type = entry.parent.is_some()
+            || enclosing.first().is_some_and(|e| match e.role {
+                EntryRole::Definition(kind) => {
+                    !matches!(kind, SymbolKind::Function | SymbolKind::Method)
+                }
+                EntryRole::TypeScope => true,
+                EntryRole::ModuleScope => false,
+            });
+        if kind == SymbolKind::Function && owned_by_type {
+            kind = SymbolKind::Method;
+        }
+        let mut parent_path: Vec<String> = enclosing.iter().rev().map(|e| e.name.clone()).collect();
+        parent_path.extend(entry.parent.clone());
+
+        symbols.push(SymbolOutput {
+            name: entry.name.clone(),
+            kind,
+            parent_path,
+            signature: signature(entry.node, source),
+            doc: doc_comment(entry.node, source),
+            start_pos: Position::new(entry.node.start_byte()),
+            end_pos: Position::new(entry.node.end_byte()),
+        });
+    }
+
+    set_output_positions(
+        text,
+        symbols.iter_mut().flat_map(|symbol| {
+            std::iter::once(&mut symbol.start_pos).chain(std::iter::once(&mut symbol.end_pos))
+        }),
+    );
+
+    let mut symbols: Vec<CodeSymbol> = symbols
+        .into_iter()
+        .map(|symbol| CodeSymbol {
+            name: symbol.name,
+            kind: symbol.kind,
+            parent_path: symbol.parent_path,
+            signature: symbol.signature,
+            doc: symbol.doc,
+            range: TextRange::new(symbol.start_pos.byte_offset, symbol.end_pos.byte_offset),
+            start: symbol.start_pos.output.unwrap(),
+            end: symbol.end_pos.output.unwrap(),
+        })
+        .collect();
+    symbols.sort_by_key(|s| (s.range.start, std::cmp::Reverse(s.range.end)));
+    Some(symbols)
+}
+
+/// Runs the symbol query, keeping the first match for each node.
+fn collect_entries<'tree>(
+    query: &tree_sitter::Query,
+    root: tree_sitter::Node<'tree>,
+    source: &[u8],
+) -> (Vec<Entry<'tree>>, HashMap<usize, usize>) {
+    let capture_names = query.capture_names();
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut entry_by_node_id: HashMap<usize, usize> = HashMap::new();
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let mut matches = cursor.matches(query, root, source);
+    while let Some(m) = matches.next() {
+        let mut target = None;
+        let mut name = None;
+        let mut parent = None;
+        for capture in m.captures {
+            let capture_name = capture_names[capture.index as usize];
+            let role = match capture_name {
+                "name" => {
+                    name = Some(capture.node);
+                    continue;
+                }
+                "parent" => {
+                    parent = Some(capture.node);
+                    continue;
+                }
+                "scope.type" => EntryRole::TypeScope,
+                "scope.module" => EntryRole::ModuleScope,
+                _ => match capture_name
+                    .strip_prefix("definition.")
+                    .and_then(SymbolKind::from_capture)
+                {
+                    Some(kind) => EntryRole::Definition(kind),
+                    None => continue,
+                },
+            };
+            target = Some((capture.node, role));
+        }
+        let (Some((node, role)), Some(name)) = (target, name) else {
+            continue;
+        };
+        if entry_by_node_id.contains_key(&node.id()) {
+            continue;
+        }
+        entry_by_node_id.insert(node.id(), entries.len());
+        entries.push(Entry {
+            node,
+            role,
+            name: node_text(name, source).to_string(),
+            parent: parent.map(|p| node_text(p, source).to_string()),
+        });
+    }
+    (entries, entry_by_node_id)
+}
+
+/// Names of the definitions and scopes matched by `query`, keyed by node id.
+pub(crate) fn scope_names(
+    query: &tree_sitter::Query,
+    root: tree_sitter::Node,
+    source: &[u8],
+) -> HashMap<usize, String> {
+    let (entries, _) = collect_entries(query, root, source);
+    entries
+        .into_iter()
+        .map(|entry| (entry.node.id(), entry.name))
+        .collect()
+}
+
+fn node_text<'a>(node: tree_sitter::Node, source: &'a [u8]) -> &'a str {
+    node.utf8_text(source).unwrap_or_default()
+}
+
+/// Declaration text before the body, or the first line when there's no body field.
+fn signature(node: tree_sitter::Node, source: &[u8]) -> String {
+    let text = node_text(node, source);
+    let head = match node.child_by_field_name("body") {
+        Some(body) => &text[..body.start_byte() - node.start_byte()],
+        None => text.lines().next().unwrap_or_default(),
+    };
+    let head = head.trim_end().trim_end_matches('{');
+    head.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_attribute(node: &tree_sitter::Node) -> bool {
+    ATTRIBUTE_NODE_KINDS.contains(&node.kind())
+}
+
+fn is_comment(node: &tree_sitter::Node) -> bool {
+    node.kind().contains("comment")
+}
+
+/// Leading docstring of the body if any, otherwise comments immediately preceding the definition.
+fn doc_comment(node: tree_sitter::Node, source: &[u8]) -> Option<String> {
+    if let Some(docstring) = docstring(node, source) {
+        return Some(docstring);
+    }
+
+    // Climb out of wrappers that only hold this definition (e.g. `export`, decorators,
+    // C declarations), since comments are siblings of the outermost wrapper.
+    let mut anchor = node;
+    loop {
+        let mut prev = anchor.prev_named_sibling();
+        while prev.as_ref().is_some_and(is_attribute) {
+            prev = prev.and_then(|p| p.prev_named_sibling());
+        }
+        if prev.is_some() {
+            break;
+        }
+        let Some(parent) = anchor.parent() else {
+            break;
+        };
+        let mut walker = parent.walk();
+        let siblings = parent
+            .named_children(&mut walker)
+            .filter(|n| !is_attribute(n) && !is_comment(n))
+            .count();
+        if siblings != 1 {
+            break;
+        }
+        anchor = parent;
+    }
+
+    let mut comments = Vec::new();
+    let mut row = anchor.start_position().row;
+    let mut prev = anchor.prev_named_sibling();
+    while let Some(node) = prev {
+        if is_attribute(&node) {
+            row = node.start_position().row;
+        } else if is_comment(&node) && node.end_position().row + 1 >= row {
+            comments.push(node);
+            row = node.start_position().row;
+        } else {
+            break;
+        }
+        prev = node.prev_named_sibling();
+    }
+    if comments.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = comments
+        .iter()
+        .rev()
+        .flat_map(|c| strip_comment_markers(node_text(*c, source)))
+        .collect();
+    non_empty(lines.join("\n"))
+}
+
+fn docstring(node: tree_sitter::Node, source: &[u8]) -> Option<String> {
+    let body = node.child_by_field_name("body")?;
+    let first = body.named_child(0)?;
+    if first.kind() != "expression_statement" {
+        return None;
+    }
+    let string = first.named_child(0).filter(|n| n.kind() == "string")?;
+    non_empty(strip_docstring_quotes(node_text(string, source)))
+}
+
+fn non_empty(doc: String) -> Option<String> {
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+fn strip_comment_markers(comment: &str) -> Vec<String> {
+    if let Some(inner) = comment.strip_prefix("/*") {
+        let inner = inner.strip_suffix("*/").unwrap_or(inner);
+        let inner = inner
+            .strip_prefix('*')
+            .or_else(|| inner.strip_prefix('!'))
+            .unwrap_or(inner);
+        return inner
+            .lines()
+            .map(|line| {
+                let line = line.trim_start();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                line.strip_prefix(' ')
+                    .unwrap_or(line)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect();
+    }
+    comment
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = ["///", "//!", "//", "#"]
+                .iter()
+                .find_map(|marker| line.strip_prefix(marker))
+                .unwrap_or(line);
+            line.strip_prefix(' ')
+                .unwrap_or(line)
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+fn strip_docstring_quotes(literal: &str) -> String {
+    let literal = literal.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    let inner = ["\"\"\"", "'''", "\"", "'"]
+        .iter()
+        .find_map(|quote| {
+            literal
+                .strip_prefix(quote)
+                .and_then(|s| s.strip_suffix(quote))
+        })
+        .unwrap_or(literal);
+
+    // Remove the common indentation of continuation lines.
+    let mut lines = inner.lines();
+    let first = lines.next().unwrap_or_default().trim();
+    let rest: Vec<&str> = lines.collect();
+    let indent = rest
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    std::iter::once(first)
+        .chain(
+            rest.iter()
+                .map(|l| l.get(indent..).unwrap_or_default().trim_end()),
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(
+        feature = "rust",
+        feature = "python",
+        feature = "go",
+        feature = "typescript"
+    ))]
+    fn find<'a>(symbols: &'a [CodeSymbol], name: &str) -> &'a CodeSymbol {
+        symbols
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("symbol `{name}` not found in {symbols:?}"))
+    }
+
+    #[test]
+    fn test_strip_comment_markers() {
+        assert_eq!(strip_comment_markers("/// Adds.\n"), vec!["Adds."]);
+        assert_eq!(
+            strip_comment_markers("/**\n * First.\n * Second.\n */"),
+            vec!["", "First.", "Second.", ""]
+        );
+        assert_eq!(strip_comment_markers("# note"), vec!["note"]);
+        assert_eq!(
+            strip_docstring_quotes("\"\"\"Summary.\n\n    Details.\n    \"\"\""),
+            "Summary.\n\nDetails.\n"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert!(extract_symbols("some text", "unknown-language").is_none());
+    }
+
+    #[cfg(feature = "rust")]
+    #[test]
+    fn test_extract_rust_symbols() {
+        let text = r#"/// A point.
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+}
+
+mod geo {
+    impl<T> Shape<T> {
+        /// Computes the area.
+        pub fn area(&self) -> f64 {
+            0.0
+        }
+    }
+
+    pub trait Draw {
+        fn draw(&self);
+    }
+}
+
+fn main() {}
+"#;
+        let symbols = extract_symbols(text, ".rs").unwrap();
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Point", "area", "Draw", "draw", "main"]);
+
+        let point = find(&symbols, "Point");
+        assert_eq!(point.kind, SymbolKind::Struct);
+        assert!(point.parent_path.is_empty());
+        assert_eq!(point.signature, "pub struct Point");
+        assert_eq!(point.doc.as_deref(), Some("A point."));
+        assert_eq!(point.start.line, 3);
+        assert_eq!(point.end.line, 5);
+        assert!(text[point.range.start..point.range.end].starts_with("pub struct Point {"));
+
+        let area = find(&symbols, "area");
+        assert_eq!(area.kind, SymbolKind::Method);
+        assert_eq!(area.parent_path, vec!["geo", "Shape"]);
+        assert_eq!(area.signature, "pub fn area(&self) -> f64");
+        assert_eq!(area.doc.as_deref(), Some("Computes the area."));
+
+        let draw = find(&symbols, "draw");
+        assert_eq!(draw.kind, SymbolKind::Method);
+        assert_eq!(draw.parent_path, vec!["geo", "Draw"]);
+        assert_eq!(find(&symbols, "Draw").kind, SymbolKind::Trait);
+        assert_eq!(find(&symbols, "main").kind, SymbolKind::Function);
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn test_extract_python_symbols() {
+        let text = r#"# Not the doc.
+def add(a, b):
+    """Add two numbers.
+
+    Returns the sum.
+    """
+    return a + b
+
+class Foo(Base):
+    # Does bar.
+    @staticmethod
+    def bar():
+        def helper():
+            pass
+"#;
+        let symbols = extract_symbols(text, "python").unwrap();
+
+        let add = find(&symbols, "add");
+        assert_eq!(add.kind, SymbolKind::Function);
+        assert_eq!(add.signature, "def add(a, b):");
+        assert_eq!(
+            add.doc.as_deref(),
+            Some("Add two numbers.\n\nReturns the sum.")
+        );
+
+        let foo = find(&symbols, "Foo");
+        assert_eq!(foo.kind, SymbolKind::Class);
+        assert_eq!(foo.doc, None);
+
+        let bar = find(&symbols, "bar");
+        assert_eq!(bar.kind, SymbolKind::Method);
+        assert_eq!(bar.parent_path, vec!["Foo"]);
+        assert_eq!(bar.doc.as_deref(), Some("Does bar."));
+
+        let helper = find(&symbols, "helper");
+        assert_eq!(helper.kind, SymbolKind::Function);
+        assert_eq!(helper.parent_path, vec!["Foo", "bar"]);
+    }
+
+    #[cfg(feature = "go")]
+    #[test]
+    fn test_extract_go_symbols() {
+        let text = "package geo\n\n// Point is a point.\ntype Point struct {\n\tX int\n}\n\ntype Shape interface {\n\tArea() float64\n}\n\n// Area computes the area.\nfunc (p *Point) Area() float64 { return 0 }\n";
+        let symbols = extract_symbols(text, "go").unwrap();
+
+        let point = find(&symbols, "Point");
+        assert_eq!(point.kind, SymbolKind::Struct);
+        assert_eq!(point.signature, "type Point struct");
+        assert_eq!(point.doc.as_deref(), Some("Point is a point."));
+        assert_eq!(find(&symbols, "Shape").kind, SymbolKind::Interface);
+
+        let area = find(&symbols, "Area");
+        assert_eq!(area.kind, SymbolKind::Method);
+        assert_eq!(area.parent_path, vec!["Point"]);
+        assert_eq!(area.signature, "func (p *Point) Area() float64");
+        assert_eq!(area.doc.as_deref(), Some("Area computes the area."));
+    }
+
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn test_extract_typescript_symbols() {
+        let text = r#"/** Greets someone. */
+export function greet(name: string): string {
+  return name;
+}
+
+namespace NS {
+  export class Foo extends Bar {
+    run(): void {}
+  }
+}
+
+interface Shape {
+  area(): number;
+}
+"#;
+        let symbols = extract_symbols(text, ".ts").unwrap();
+
+        let greet = find(&symbols, "greet");
+        assert_eq!(greet.kind, SymbolKind::Function);
+        assert_eq!(greet.signature, "function greet(name: string): string");
+        assert_eq!(greet.doc.as_deref(), Some("Greets someone."));
+
+        let run = find(&symbols, "run");
+        assert_eq!(run.kind, SymbolKind::Method);
+        assert_eq!(run.parent_path, vec!["NS", "Foo"]);
+        assert_eq!(find(&symbols, "Shape").kind, SymbolKind::Interface);
+        assert_eq!(find(&symbols, "area").parent_path, vec!["Shape"]);
+    }
+}
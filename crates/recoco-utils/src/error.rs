@@ -357,6 +357,13 @@ impl From<sqlx::Error> for Error {
     }
 }
 
+#[cfg(feature = "git2")]
+impl From<git2::Error> for Error {
+    fn from(e: git2::Error) -> Self {
+        Error::Internal(e.into())
+    }
+}
+
 #[cfg(feature = "neo4rs")]
 impl From<neo4rs::Error> for Error {
     fn from(e: neo4rs::Error) -> Self {
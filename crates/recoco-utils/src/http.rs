@@ -10,18 +10,42 @@
 // Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, invariance_violation};
 use crate::retryable;
 
 pub async fn request(
     req_builder: impl Fn() -> reqwest::RequestBuilder,
 ) -> Result<reqwest::Response> {
+    let resp = send(req_builder, false).await?;
+    resp.ok_or_else(|| invariance_violation().into())
+}
+
+/// Like [`request`], but returns `None` instead of an error if the resource doesn't exist
+/// (404 or 410).
+pub async fn request_optional(
+    req_builder: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<Option<reqwest::Response>> {
+    send(req_builder, true).await
+}
+
+async fn send(
+    req_builder: impl Fn() -> reqwest::RequestBuilder,
+    allow_not_found: bool,
+) -> Result<Option<reqwest::Response>> {
     let resp = retryable::run(
         || async {
             let req = req_builder();
             let resp = req.send().await?;
+            if allow_not_found
+                && matches!(
+                    resp.status(),
+                    reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE
+                )
+            {
+                return Ok(None);
+            }
             let Err(err) = resp.error_for_status_ref() else {
-                return Ok(resp);
+                return Ok(Some(resp));
             };
 
             let is_retryable = retryable::IsRetryable::is_retryable(&err);
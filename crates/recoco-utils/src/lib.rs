@@ -25,6 +25,8 @@ pub mod fingerprint;
 pub mod immutable;
 #[cfg(feature = "retryable")]
 pub mod retryable;
+#[cfg(feature = "similarity")]
+pub mod similarity;
 
 pub mod prelude;
 
@@ -0,0 +1,445 @@
+// Recoco is a Rust-only fork of CocoIndex, by [CocoIndex](https://CocoIndex)
+// Original code from CocoIndex is copyrighted by CocoIndex
+// SPDX-FileCopyrightText: 2025-2026 CocoIndex (upstream)
+// SPDX-FileContributor: CocoIndex Contributors
+//
+// All modifications from the upstream for Recoco are copyrighted by Knitli Inc.
+// SPDX-FileCopyrightText: 2026 Knitli Inc. (Recoco)
+// SPDX-FileContributor: Adam Poulemanos <adam@knit.li>
+//
+// Both the upstream CocoIndex code and the Recoco modifications are licensed under the Apache-2.0 License.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Near-duplicate detection: MinHash and SimHash signatures over text shingles, and an
+//! LSH banding index for finding MinHash signatures above a Jaccard similarity threshold.
+
+use crate::{
+    client_bail,
+    error::Result,
+    fingerprint::{Fingerprint, Fingerprinter},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Lowercased alphanumeric words of `text`.
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+}
+
+/// Hashes of the overlapping word n-grams of `text`. Text with fewer than `size` words
+/// yields a single shingle of all its words.
+pub fn shingle_hashes(text: &str, size: usize) -> Vec<u64> {
+    let words: Vec<String> = words(text).collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+    words
+        .windows(size.clamp(1, words.len()))
+        .map(|shingle| feature_hash(&shingle.join(" ")))
+        .collect()
+}
+
+/// A 64-bit hash of a feature, taken from its [`Fingerprint`].
+pub fn feature_hash(feature: &str) -> u64 {
+    let mut fingerprinter = Fingerprinter::default();
+    fingerprinter.write_raw_bytes(feature.as_bytes());
+    let fingerprint = fingerprinter.into_fingerprint();
+    u64::from_le_bytes(fingerprint.0[..8].try_into().unwrap())
+}
+
+/// The SplitMix64 finalizer, used to derive independent hash functions from a single hash.
+fn mix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+////////////////////////////////////////////////////////////
+// MinHash
+////////////////////////////////////////////////////////////
+
+/// Number of permutations of MinHash signatures when not specified.
+pub const DEFAULT_NUM_PERMUTATIONS: usize = 128;
+
+/// Computes MinHash signatures, whose agreement rate estimates Jaccard similarity.
+#[derive(Debug, Clone)]
+pub struct MinHasher {
+    seeds: Vec<u64>,
+}
+
+impl MinHasher {
+    /// Signatures from hashers with the same `num_permutations` and `seed` are comparable.
+    pub fn new(num_permutations: usize, seed: u64) -> Self {
+        let seeds = (0..num_permutations as u64)
+            .map(|i| mix64(seed ^ mix64(i)))
+            .collect();
+        Self { seeds }
+    }
+
+    pub fn num_permutations(&self) -> usize {
+        self.seeds.len()
+    }
+
+    pub fn signature(&self, feature_hashes: impl IntoIterator<Item = u64>) -> MinHashSignature {
+        let mut mins = vec![u32::MAX; self.seeds.len()];
+        for hash in feature_hashes {
+            for (min, seed) in mins.iter_mut().zip(self.seeds.iter()) {
+                *min = (*min).min((mix64(hash ^ seed) >> 32) as u32);
+            }
+        }
+        MinHashSignature(mins)
+    }
+
+    pub fn text_signature(&self, text: &str, shingle_size: usize) -> MinHashSignature {
+        self.signature(shingle_hashes(text, shingle_size))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinHashSignature(pub Vec<u32>);
+
+impl MinHashSignature {
+    /// Estimated Jaccard similarity. Signatures of different lengths are compared on their
+    /// common prefix.
+    pub fn jaccard(&self, other: &MinHashSignature) -> f64 {
+        let len = self.0.len().min(other.0.len());
+        if len == 0 {
+            return 0.0;
+        }
+        let matches = (self.0.iter().zip(other.0.iter()))
+            .filter(|(a, b)| a == b)
+            .count();
+        matches as f64 / len as f64
+    }
+
+    /// Little-endian encoding, 4 bytes per permutation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if !bytes.len().is_multiple_of(4) {
+            client_bail!(
+                "MinHash signature length must be a multiple of 4 bytes, got {}",
+                bytes.len()
+            );
+        }
+        Ok(MinHashSignature(
+            bytes
+                .chunks_exact(4)
+                .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+                .collect(),
+        ))
+    }
+}
+
+////////////////////////////////////////////////////////////
+// SimHash
+////////////////////////////////////////////////////////////
+
+/// A 64-bit SimHash, whose Hamming distance to another approximates their cosine distance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SimHash(pub u64);
+
+impl SimHash {
+    /// Computes the SimHash of weighted feature hashes.
+    pub fn from_weighted(features: impl IntoIterator<Item = (u64, f64)>) -> Self {
+        let mut counts = [0.0f64; 64];
+        for (hash, weight) in features {
+            for (bit, count) in counts.iter_mut().enumerate() {
+                if hash >> bit & 1 == 1 {
+                    *count += weight;
+                } else {
+                    *count -= weight;
+                }
+            }
+        }
+        let bits = (counts.iter().enumerate())
+            .filter(|(_, count)| **count > 0.0)
+            .fold(0u64, |bits, (bit, _)| bits | 1 << bit);
+        SimHash(bits)
+    }
+
+    /// SimHash of the text's shingles, each weighted by its number of occurrences.
+    pub fn from_text(text: &str, shingle_size: usize) -> Self {
+        let mut counts = HashMap::<u64, f64>::new();
+        for hash in shingle_hashes(text, shingle_size) {
+            *counts.entry(hash).or_default() += 1.0;
+        }
+        Self::from_weighted(counts)
+    }
+
+    pub fn hamming_distance(&self, other: &SimHash) -> u32 {
+        (self.0 ^ other.0).count_ones()
+    }
+
+    /// Fraction of bits in common.
+    pub fn similarity(&self, other: &SimHash) -> f64 {
+        1.0 - self.hamming_distance(other) as f64 / 64.0
+    }
+}
+
+////////////////////////////////////////////////////////////
+// LSH index
+////////////////////////////////////////////////////////////
+
+/// Splits MinHash signatures into bands of rows. Two signatures with Jaccard similarity `s`
+/// share a band with probability `1 - (1 - s^rows_per_band)^num_bands`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LshBanding {
+    num_bands: usize,
+    rows_per_band: usize,
+}
+
+impl LshBanding {
+    pub fn new(num_bands: usize, rows_per_band: usize) -> Self {
+        Self {
+            num_bands: num_bands.max(1),
+            rows_per_band: rows_per_band.max(1),
+        }
+    }
+
+    /// Picks the banding of `num_permutations` rows whose similarity threshold, where
+    /// candidates become more likely than not, is closest to `threshold`.
+    pub fn for_threshold(num_permutations: usize, threshold: f64) -> Self {
+        let num_permutations = num_permutations.max(1);
+        let (num_bands, rows_per_band) = (1..=num_permutations)
+            .map(|rows| (num_permutations / rows, rows))
+            .min_by(|(b1, r1), (b2, r2)| {
+                let error =
+                    |b: usize, r: usize| ((1.0 / b as f64).powf(1.0 / r as f64) - threshold).abs();
+                error(*b1, *r1).total_cmp(&error(*b2, *r2))
+            })
+            .unwrap();
+        Self::new(num_bands, rows_per_band)
+    }
+
+    pub fn num_bands(&self) -> usize {
+        self.num_bands
+    }
+
+    pub fn rows_per_band(&self) -> usize {
+        self.rows_per_band
+    }
+
+    /// Bucket of each band of `signature`. Buckets of different bands never collide.
+    pub fn band_keys(&self, signature: &MinHashSignature) -> Result<Vec<Fingerprint>> {
+        let required = self.num_bands * self.rows_per_band;
+        if signature.0.len() < required {
+            client_bail!(
+                "MinHash signature has {} permutations, but the LSH banding needs {required}",
+                signature.0.len()
+            );
+        }
+        Ok((signature.0[..required].chunks_exact(self.rows_per_band))
+            .enumerate()
+            .map(|(band, rows)| {
+                let mut fingerprinter = Fingerprinter::default();
+                fingerprinter.write_raw_bytes(&(band as u32).to_le_bytes());
+                for row in rows {
+                    fingerprinter.write_raw_bytes(&row.to_le_bytes());
+                }
+                fingerprinter.into_fingerprint()
+            })
+            .collect())
+    }
+}
+
+/// Indexes MinHash signatures in memory by their LSH bands, so that signatures sharing any band
+/// become candidates.
+#[derive(Debug, Clone)]
+pub struct LshIndex<K> {
+    banding: LshBanding,
+    buckets: HashMap<Fingerprint, HashSet<K>>,
+    signatures: HashMap<K, MinHashSignature>,
+}
+
+impl<K: Clone + Eq + Hash> LshIndex<K> {
+    pub fn new(num_bands: usize, rows_per_band: usize) -> Self {
+        Self::with_banding(LshBanding::new(num_bands, rows_per_band))
+    }
+
+    /// See [`LshBanding::for_threshold`].
+    pub fn for_threshold(num_permutations: usize, threshold: f64) -> Self {
+        Self::with_banding(LshBanding::for_threshold(num_permutations, threshold))
+    }
+
+    pub fn with_banding(banding: LshBanding) -> Self {
+        Self {
+            banding,
+            buckets: HashMap::new(),
+            signatures: HashMap::new(),
+        }
+    }
+
+    pub fn banding(&self) -> LshBanding {
+        self.banding
+    }
+
+    pub fn num_bands(&self) -> usize {
+        self.banding.num_bands
+    }
+
+    pub fn rows_per_band(&self) -> usize {
+        self.banding.rows_per_band
+    }
+
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    fn band_keys(&self, signature: &MinHashSignature) -> Result<Vec<Fingerprint>> {
+        self.banding.band_keys(signature)
+    }
+
+    /// Adds or replaces the signature of `key`.
+    pub fn insert(&mut self, key: K, signature: MinHashSignature) -> Result<()> {
+        let band_keys = self.band_keys(&signature)?;
+        self.remove(&key);
+        for band_key in band_keys {
+            self.buckets
+                .entry(band_key)
+                .or_default()
+                .insert(key.clone());
+        }
+        self.signatures.insert(key, signature);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<MinHashSignature> {
+        let signature = self.signatures.remove(key)?;
+        for band_key in self.band_keys(&signature).unwrap_or_default() {
+            if let Some(bucket) = self.buckets.get_mut(&band_key) {
+                bucket.remove(key);
+                if bucket.is_empty() {
+                    self.buckets.remove(&band_key);
+                }
+            }
+        }
+        Some(signature)
+    }
+
+    /// Keys sharing at least one band with `signature`.
+    pub fn candidates(&self, signature: &MinHashSignature) -> Result<HashSet<&K>> {
+        Ok(self
+            .band_keys(signature)?
+            .iter()
+            .filter_map(|band_key| self.buckets.get(band_key))
+            .flatten()
+            .collect())
+    }
+
+    /// Candidates whose estimated Jaccard similarity to `signature` is at least `threshold`,
+    /// most similar first.
+    pub fn query(&self, signature: &MinHashSignature, threshold: f64) -> Result<Vec<(&K, f64)>> {
+        let mut matches: Vec<(&K, f64)> = self
+            .candidates(signature)?
+            .into_iter()
+            .map(|key| (key, self.signatures[key].jaccard(signature)))
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .collect();
+        matches.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "The quick brown fox jumps over the lazy dog while the farmer \
+                        watches from the porch and the sun sets behind the old red barn";
+
+    #[test]
+    fn test_shingle_hashes() {
+        assert!(shingle_hashes("  ...  ", 3).is_empty());
+        assert_eq!(shingle_hashes("one two", 3).len(), 1);
+        assert_eq!(shingle_hashes("one two three four", 3).len(), 2);
+        assert_eq!(
+            shingle_hashes("One, TWO three", 3),
+            shingle_hashes("one two three", 3)
+        );
+    }
+
+    #[test]
+    fn test_minhash_estimates_jaccard() {
+        let hasher = MinHasher::new(256, 42);
+        let a = hasher.text_signature(BASE, 1);
+        assert_eq!(a.0.len(), 256);
+        assert_eq!(a.jaccard(&hasher.text_signature(BASE, 1)), 1.0);
+
+        // 19 of the 21 distinct words are shared.
+        let b = hasher.text_signature(&BASE.replace("barn", "house"), 1);
+        let similarity = a.jaccard(&b);
+        assert!((similarity - 19.0 / 21.0).abs() < 0.1, "{similarity}");
+
+        let unrelated = hasher.text_signature("completely different words about rust code", 1);
+        assert!(a.jaccard(&unrelated) < 0.1);
+
+        assert_eq!(MinHashSignature::from_bytes(&a.to_bytes()).unwrap(), a);
+        assert!(MinHashSignature::from_bytes(&[0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_simhash() {
+        let a = SimHash::from_text(BASE, 2);
+        let b = SimHash::from_text(&BASE.replace("barn", "house"), 2);
+        let unrelated = SimHash::from_text("completely different words about rust code", 2);
+        assert_eq!(a, SimHash::from_text(BASE, 2));
+        assert!(a.hamming_distance(&b) < a.hamming_distance(&unrelated));
+        assert!(a.similarity(&b) > 0.75);
+    }
+
+    #[test]
+    fn test_lsh_index() {
+        let hasher = MinHasher::new(128, 0);
+        let mut index = LshIndex::for_threshold(128, 0.8);
+        assert!(index.num_bands() * index.rows_per_band() <= 128);
+
+        index
+            .insert("base", hasher.text_signature(BASE, 3))
+            .unwrap();
+        index
+            .insert(
+                "other",
+                hasher.text_signature("an entirely unrelated document about databases", 3),
+            )
+            .unwrap();
+        assert_eq!(index.len(), 2);
+
+        let near = hasher.text_signature(&format!("{BASE} today"), 3);
+        let matches = index.query(&near, 0.8).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(*matches[0].0, "base");
+
+        let far = hasher.text_signature("yet another text that shares nothing", 3);
+        assert!(index.query(&far, 0.8).unwrap().is_empty());
+
+        // Re-inserting a key replaces its signature.
+        index.insert("base", far.clone()).unwrap();
+        assert!(index.query(&near, 0.8).unwrap().is_empty());
+        assert_eq!(*index.query(&far, 0.8).unwrap()[0].0, "base");
+        assert!(index.remove(&"base").is_some());
+        assert!(index.query(&far, 0.8).unwrap().is_empty());
+
+        assert!(index.insert("short", MinHashSignature(vec![1, 2])).is_err());
+    }
+
+    #[test]
+    fn test_lsh_banding() {
+        let banding = LshBanding::new(4, 2);
+        // Equal rows in different bands fall into different buckets.
+        let keys = banding.band_keys(&MinHashSignature(vec![7; 9])).unwrap();
+        assert_eq!(keys.len(), 4);
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 4);
+        assert!(banding.band_keys(&MinHashSignature(vec![7; 7])).is_err());
+    }
+}